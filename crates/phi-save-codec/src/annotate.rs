@@ -0,0 +1,455 @@
+//! 存档 entry 字段标注（诊断用）
+//!
+//! 按与各 `parse_*` 相同的布局逐字段读取，记录每段字节区间对应的字段名与解码值，
+//! 用于在存档版本变更时定位“从哪个字节开始解析偏离了预期”。
+//! 标注过程不会因格式错误中断：出错时记录错误与偏移，并把剩余字节标为未解析。
+
+use alloc::format;
+use alloc::string::{String, ToString as _};
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::error::{CodecError, Result};
+use crate::game_key::{Key, parse_single_key};
+use crate::game_record::SONG_ID_SUFFIX;
+use crate::reader::{Reader, get_bit, strict_bool};
+
+/// 单个 span 的 hex 预览最多输出的字节数（完整长度见 `len`）
+const HEX_PREVIEW_BYTES: usize = 32;
+
+const DIFFICULTY_NAMES: [&str; 4] = ["EZ", "HD", "IN", "AT"];
+
+/// 一段字节区间及其解码结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSpan {
+    /// 在 entry 内的起始偏移（含第 1 字节 prefix/version）
+    pub offset: usize,
+    pub len: usize,
+    /// 字段路径，例如 `money[2]`、`keys[Glaciaxion].payload`
+    pub field: String,
+    /// 解码后的值（人类可读）
+    pub value: String,
+    /// 原始字节 hex（超过 32 字节时截断）
+    pub hex: String,
+}
+
+/// 单个 entry 的字段标注结果
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryAnnotation {
+    pub entry: String,
+    pub total_len: usize,
+    /// 按偏移递增排列，首尾相接覆盖整个 entry
+    pub spans: Vec<FieldSpan>,
+    /// 解析中断原因（含偏移）；完整解析时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl EntryAnnotation {
+    /// 渲染为逐行的带标注 hex 视图：`偏移  +长度  hex  字段 = 值`
+    #[must_use]
+    pub fn render_hex(&self) -> String {
+        let width = self
+            .spans
+            .iter()
+            .map(|s| s.hex.len())
+            .max()
+            .unwrap_or(0)
+            .min(HEX_PREVIEW_BYTES * 3);
+        let mut out = String::new();
+        for s in &self.spans {
+            let _ = writeln!(
+                out,
+                "{:06x}  +{:<4} {:<width$}  {} = {}",
+                s.offset,
+                s.len,
+                s.hex,
+                s.field,
+                s.value,
+                width = width
+            );
+        }
+        if let Some(e) = self.error.as_deref() {
+            let _ = writeln!(out, "!! {e}");
+        }
+        out
+    }
+}
+
+/// 按 entry 名称选择布局并标注（`entry` 为解密后的完整明文，含第 1 字节）
+///
+/// 支持 `gameRecord`、`gameKey`、`gameProgress`、`user`、`settings`；
+/// 其他名称整体标为 `unknown`。
+#[must_use]
+pub fn annotate_entry(name: &str, entry: &[u8]) -> EntryAnnotation {
+    let mut a = Annotator::new(entry);
+    let walked = match name {
+        "gameRecord" => walk_game_record(&mut a),
+        "gameKey" => walk_game_key(&mut a),
+        "gameProgress" => walk_game_progress(&mut a),
+        "user" => walk_user(&mut a),
+        "settings" => walk_settings(&mut a),
+        _ => Ok(()),
+    };
+    // 读取失败时 reader 可能已越过部分字节，以最后一个成功 span 的末尾为中断点
+    let stop = a.spans.last().map_or(0, |s| s.offset + s.len);
+    let error = walked.err().map(|e| format!("{e} at offset {stop:#x}"));
+    let rest_label = match (name, error.is_some()) {
+        (_, true) => "unparsed (parse stopped here)",
+        ("gameKey" | "gameProgress" | "user" | "settings", false) => "overflow",
+        ("gameRecord", false) => "trailing (ignored by parser)",
+        _ => "unknown",
+    };
+    a.rest_from(stop, rest_label);
+    EntryAnnotation {
+        entry: name.to_string(),
+        total_len: entry.len(),
+        spans: a.spans,
+        error,
+    }
+}
+
+struct Annotator<'a> {
+    r: Reader<'a>,
+    spans: Vec<FieldSpan>,
+}
+
+impl<'a> Annotator<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            r: Reader::new(data),
+            spans: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, start: usize, field: String, value: String) {
+        let end = self.r.offset();
+        self.spans.push(FieldSpan {
+            offset: start,
+            len: end - start,
+            field,
+            value,
+            hex: hex_preview(&self.r.data[start..end]),
+        });
+    }
+
+    fn u8(&mut self, field: impl Into<String>) -> Result<u8> {
+        let start = self.r.offset();
+        let v = self.r.read_u8()?;
+        self.push(start, field.into(), format!("{v} ({v:#04x})"));
+        Ok(v)
+    }
+
+    /// 位标志字节：列出置位的字段名，未建模的置位高位单独提示
+    fn flags(&mut self, field: impl Into<String>, names: &[&str]) -> Result<u8> {
+        let start = self.r.offset();
+        let v = self.r.read_u8()?;
+        let set = names
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| get_bit(v, *i))
+            .map(|(_, n)| *n)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut value = format!("{v:#010b} [{set}]");
+        let unmodeled = v & !(u8::MAX >> (8 - names.len().min(8)));
+        if names.len() < 8 && unmodeled != 0 {
+            let _ = write!(value, " unmodeled_bits={unmodeled:#010b}");
+        }
+        self.push(start, field.into(), value);
+        Ok(v)
+    }
+
+    fn u16(&mut self, field: impl Into<String>) -> Result<u16> {
+        let start = self.r.offset();
+        let v = self.r.read_u16_le()?;
+        self.push(start, field.into(), v.to_string());
+        Ok(v)
+    }
+
+    fn i32(&mut self, field: impl Into<String>) -> Result<i32> {
+        let start = self.r.offset();
+        let v = self.r.read_i32_le()?;
+        self.push(start, field.into(), v.to_string());
+        Ok(v)
+    }
+
+    fn f32(&mut self, field: impl Into<String>) -> Result<f32> {
+        let start = self.r.offset();
+        let v = self.r.read_f32_le()?;
+        self.push(start, field.into(), v.to_string());
+        Ok(v)
+    }
+
+    fn varshort(&mut self, field: impl Into<String>) -> Result<usize> {
+        let start = self.r.offset();
+        let v = self.r.read_varshort()?;
+        self.push(start, field.into(), format!("{v} (varshort)"));
+        Ok(v)
+    }
+
+    fn string(&mut self, field: impl Into<String>) -> Result<&'a str> {
+        let start = self.r.offset();
+        let s = self.r.read_string(0)?;
+        self.push(start, field.into(), format!("{s:?}"));
+        Ok(s)
+    }
+
+    /// 按已知长度标注一段字节（不做进一步解码）
+    fn raw(&mut self, field: impl Into<String>, len: usize, value: String) -> Result<()> {
+        let start = self.r.offset();
+        if self.r.remain() < len {
+            return Err(CodecError::NotEnoughData);
+        }
+        self.r.skip(len);
+        self.push(start, field.into(), value);
+        Ok(())
+    }
+
+    fn rest_from(&mut self, start: usize, field: &str) {
+        let n = self.r.data.len() - start;
+        if n > 0 {
+            self.r.skip(self.r.remain());
+            self.push(start, field.to_string(), format!("{n} bytes"));
+        }
+    }
+}
+
+fn hex_preview(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().min(HEX_PREVIEW_BYTES) * 3);
+    for (i, b) in bytes.iter().take(HEX_PREVIEW_BYTES).enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        let _ = write!(out, "{b:02x}");
+    }
+    if bytes.len() > HEX_PREVIEW_BYTES {
+        out.push_str(" ..");
+    }
+    out
+}
+
+fn walk_game_progress(a: &mut Annotator) -> Result<()> {
+    let version = a.u8("version")?;
+    if version >= 1 {
+        a.flags(
+            "flags",
+            &[
+                "is_first_run",
+                "legacy_chapter_finished",
+                "already_show_collection_tip",
+                "already_show_auto_unlock_in_tip",
+            ],
+        )?;
+        a.string("completed")?;
+        a.u8("song_update_info")?;
+        a.u16("challenge_mode_rank")?;
+        for i in 0..5 {
+            a.varshort(format!("money[{i}]"))?;
+        }
+        a.u8("unlock_flag_of_spasmodic")?;
+        a.u8("unlock_flag_of_igallta")?;
+        a.u8("unlock_flag_of_rrharil")?;
+        a.u8("flag_of_song_record_key")?;
+    }
+    if version >= 2 {
+        a.u8("random_version_unlocked")?;
+    }
+    if version >= 3 {
+        a.flags(
+            "chapter8_flags",
+            &[
+                "chapter8_unlock_begin",
+                "chapter8_unlock_second_phase",
+                "chapter8_passed",
+            ],
+        )?;
+        a.u8("chapter8_song_unlocked")?;
+    }
+    if version >= 4 {
+        a.flags(
+            "flag_of_song_record_key_takumi",
+            &["takumi[0]", "takumi[1]", "takumi[2]"],
+        )?;
+    }
+    Ok(())
+}
+
+fn walk_game_key(a: &mut Annotator) -> Result<()> {
+    let version = a.u8("version")?;
+    let count = a.varshort("keys.count")?;
+    for i in 0..count {
+        let name = a.string(format!("keys[{i}].name"))?;
+        let len = usize::from(a.u8(format!("keys[{name}].len"))?);
+        let start = a.r.offset();
+        if a.r.remain() < len {
+            return Err(CodecError::NotEnoughData);
+        }
+        let value = match parse_single_key(&a.r.data[start..start + len]) {
+            Key::Normal(nk) => format!("Normal {nk:?}"),
+            Key::Raw(_) => "Raw fallback (non-canonical payload)".to_string(),
+        };
+        a.raw(format!("keys[{name}].payload"), len, value)?;
+    }
+    if version >= 1 {
+        a.u8("lanota_read_keys")?;
+    }
+    // 与解析器一致：标志字节不是 0/1 时停止，其余字节归入 overflow
+    let flag_names: &[&str] = match version {
+        0 | 1 => &[],
+        2 => &["camellia_read_key"],
+        _ => &[
+            "camellia_read_key",
+            "side_story4_begin_read_key",
+            "old_score_cleared_v390",
+        ],
+    };
+    for &field in flag_names {
+        let next = a.r.data.get(a.r.offset()).copied();
+        if next.and_then(strict_bool).is_none() {
+            break;
+        }
+        a.flags(field, &[field])?;
+    }
+    Ok(())
+}
+
+fn walk_game_record(a: &mut Annotator) -> Result<()> {
+    a.u8("prefix (skipped by parser)")?;
+    let count = a.varshort("songs.count")?;
+    for i in 0..count {
+        let full_id = a.string(format!("songs[{i}].id"))?;
+        let id = full_id.strip_suffix(SONG_ID_SUFFIX).unwrap_or(full_id);
+        let first_len = usize::from(a.u8(format!("{id}.len"))?);
+        let payload_start = a.r.offset();
+        let mask = a.flags(format!("{id}.mask"), &DIFFICULTY_NAMES)?;
+        a.flags(format!("{id}.fc_mask"), &DIFFICULTY_NAMES)?;
+        for (idx, diff) in DIFFICULTY_NAMES.iter().enumerate() {
+            if get_bit(mask, idx) {
+                a.i32(format!("{id}.{diff}.score"))?;
+                a.f32(format!("{id}.{diff}.acc"))?;
+            }
+        }
+        let consumed = a.r.offset() - payload_start;
+        if consumed > first_len {
+            return Err(CodecError::from(format!(
+                "song `{id}` payload overran declared len {first_len} (consumed {consumed})"
+            )));
+        }
+        if consumed < first_len {
+            let extra = first_len - consumed;
+            a.raw(
+                format!("{id}.unparsed"),
+                extra,
+                format!("{extra} bytes skipped by parser"),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn walk_user(a: &mut Annotator) -> Result<()> {
+    a.u8("prefix (skipped by parser)")?;
+    a.flags("flags", &["show_player_id"])?;
+    a.string("self_intro")?;
+    a.string("avatar")?;
+    a.string("background")?;
+    Ok(())
+}
+
+fn walk_settings(a: &mut Annotator) -> Result<()> {
+    a.u8("prefix (skipped by parser)")?;
+    a.flags(
+        "flags",
+        &[
+            "chord_support",
+            "fc_ap_indicator",
+            "enable_hit_sound",
+            "low_resolution_mode",
+        ],
+    )?;
+    a.string("device_name")?;
+    for field in [
+        "bright",
+        "music_volume",
+        "effect_volume",
+        "hit_sound_volume",
+        "sound_offset",
+        "note_scale",
+    ] {
+        a.f32(field)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn assert_contiguous(ann: &EntryAnnotation) {
+        let mut off = 0;
+        for s in &ann.spans {
+            assert_eq!(s.offset, off, "gap before `{}`", s.field);
+            off += s.len;
+        }
+        assert_eq!(off, ann.total_len);
+    }
+
+    #[test]
+    fn game_progress_v4_labels_takumi_bits_and_overflow() {
+        let mut e = vec![4u8, 0b0000_0001, 0];
+        e.push(0);
+        e.extend_from_slice(&0u16.to_le_bytes());
+        e.extend_from_slice(&[0, 0, 0, 0, 0]);
+        e.extend_from_slice(&[0, 0, 0, 0]);
+        e.push(0);
+        e.extend_from_slice(&[0, 0]);
+        e.push(0b0000_0101); // takumi[0], takumi[2]
+        e.push(0xEE); // overflow
+
+        let ann = annotate_entry("gameProgress", &e);
+        assert!(ann.error.is_none());
+        assert_contiguous(&ann);
+        let takumi = ann
+            .spans
+            .iter()
+            .find(|s| s.field == "flag_of_song_record_key_takumi")
+            .expect("takumi span");
+        assert_eq!(takumi.offset, e.len() - 2);
+        assert!(takumi.value.contains("takumi[0], takumi[2]"));
+        let last = ann.spans.last().expect("overflow span");
+        assert_eq!(last.field, "overflow");
+        assert_eq!(last.hex, "ee");
+    }
+
+    #[test]
+    fn game_key_marks_raw_fallback_payload() {
+        // version=0, 1 个 key "a"，payload 的 type_byte 高位被占用 -> Raw
+        let e = vec![0u8, 1, 1, b'a', 2, 0x02, 0b0010_0000];
+        let ann = annotate_entry("gameKey", &e);
+        assert!(ann.error.is_none());
+        assert_contiguous(&ann);
+        let payload = ann
+            .spans
+            .iter()
+            .find(|s| s.field == "keys[a].payload")
+            .expect("payload span");
+        assert!(payload.value.starts_with("Raw fallback"));
+    }
+
+    #[test]
+    fn truncated_entry_reports_offset_and_unparsed_tail() {
+        // user: prefix + flags + self_intro 声明 5 字节但只剩 2 字节
+        let e = vec![0u8, 1, 5, b'h', b'i'];
+        let ann = annotate_entry("user", &e);
+        assert_contiguous(&ann);
+        assert!(ann.error.as_deref().is_some_and(|m| m.contains("0x2")));
+        let last = ann.spans.last().expect("tail span");
+        assert_eq!(last.field, "unparsed (parse stopped here)");
+        assert_eq!(last.offset, 2);
+        assert!(ann.render_hex().contains("!! not enough data"));
+    }
+}
@@ -2,11 +2,11 @@ use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-use base64::Engine as _;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{CodecError, Result};
-use crate::reader::{Reader, get_bit};
+use crate::reader::{Reader, get_bit, set_bit, strict_bool};
+use crate::writer::Writer;
 
 /// 单首歌的密钥：解析成功时为结构化 NormalKey，格式未知时保留原始字节
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,9 +51,10 @@ pub struct GameKeyParsed {
 
 /// 解析单首歌的密钥原始字节为 Key enum
 ///
-/// 健壮性设计：如果解析出的格式不符合预期（`type_byte` 高位不为 0、长度不匹配等），
-/// 不报错，直接返回 `Key::Raw` 保留原始字节。
-fn parse_single_key(data: &[u8]) -> Key {
+/// 健壮性设计：如果解析出的格式不符合预期（`type_byte` 高位不为 0、长度不匹配、
+/// 末尾有多余字节、bool 字段不是 0/1 等），不报错，直接返回 `Key::Raw` 保留原始字节，
+/// 保证 `Normal` 一定能被 `encode_single_key` 逐字节还原。
+pub(crate) fn parse_single_key(data: &[u8]) -> Key {
     // 至少需要 2 字节：length + type_byte
     if data.len() < 2 {
         return Key::Raw(data.to_vec());
@@ -80,41 +81,75 @@ fn parse_single_key(data: &[u8]) -> Key {
         + usize::from(exist_illust)
         + usize::from(exist_avatar);
 
-    // payload 长度 = length 自身(1) + 字段数据
-    let field_data_len = payload_len.saturating_sub(1);
+    // payload 长度 = length 自身(1) + 字段数据，且其后不能再有多余字节
     let data_start = 2; // 跳过 length + type_byte
-    if data.len() < data_start + field_data_len || expected_fields != field_data_len {
+    if payload_len != expected_fields + 1 || data.len() != data_start + expected_fields {
         return Key::Raw(data.to_vec());
     }
 
-    let field_data = &data[data_start..data_start + field_data_len];
-    let mut idx = 0;
-
+    let mut fields = data[data_start..].iter().copied();
     let mut key = NormalKey::default();
 
     if exist_read {
-        key.read_collection_piece_num = Some(field_data[idx]);
-        idx += 1;
+        key.read_collection_piece_num = fields.next();
     }
     if exist_single {
-        key.unlock_single = Some(field_data[idx] == 1);
-        idx += 1;
+        let Some(v) = fields.next().and_then(strict_bool) else {
+            return Key::Raw(data.to_vec());
+        };
+        key.unlock_single = Some(v);
     }
     if exist_collection {
-        key.unlock_collection_piece_num = Some(field_data[idx]);
-        idx += 1;
+        key.unlock_collection_piece_num = fields.next();
     }
     if exist_illust {
-        key.unlock_illustration = Some(field_data[idx] == 1);
-        idx += 1;
+        let Some(v) = fields.next().and_then(strict_bool) else {
+            return Key::Raw(data.to_vec());
+        };
+        key.unlock_illustration = Some(v);
     }
     if exist_avatar {
-        key.unlock_avatar = Some(field_data[idx] == 1);
+        let Some(v) = fields.next().and_then(strict_bool) else {
+            return Key::Raw(data.to_vec());
+        };
+        key.unlock_avatar = Some(v);
     }
 
     Key::Normal(key)
 }
 
+/// 将 Key 编码回单首歌的密钥字节（`parse_single_key` 的逆操作）
+///
+/// `Raw` 原样返回；`Normal` 按 bit 顺序写出存在的字段，bool 写为 0/1。
+fn encode_single_key(key: &Key) -> Vec<u8> {
+    match key {
+        Key::Raw(bytes) => bytes.clone(),
+        Key::Normal(nk) => {
+            let fields = [
+                nk.read_collection_piece_num,
+                nk.unlock_single.map(u8::from),
+                nk.unlock_collection_piece_num,
+                nk.unlock_illustration.map(u8::from),
+                nk.unlock_avatar.map(u8::from),
+            ];
+            let mut type_byte = 0u8;
+            let mut field_data = Vec::with_capacity(fields.len());
+            for (bit, field) in fields.iter().enumerate() {
+                if let Some(v) = field {
+                    set_bit(&mut type_byte, bit, true);
+                    field_data.push(*v);
+                }
+            }
+            let mut out = Vec::with_capacity(2 + field_data.len());
+            // payload 长度 = type_byte(1) + 字段数据（最多 5 字节，不会溢出 u8）
+            out.push(u8::try_from(field_data.len() + 1).unwrap_or(u8::MAX));
+            out.push(type_byte);
+            out.extend_from_slice(&field_data);
+            out
+        }
+    }
+}
+
 fn parse_game_key_map(reader: &mut Reader) -> Result<BTreeMap<String, Key>> {
     let length = reader.read_varshort()?;
     let mut map = BTreeMap::new();
@@ -152,33 +187,28 @@ pub fn parse_game_key_entry(entry: &[u8]) -> Result<GameKeyParsed> {
         None
     };
 
-    let camellia_read_key = if version >= 2 {
-        if r.remain() > 0 {
-            Some((r.read_u8()? & 1) != 0)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-
-    let side_story4_begin_read_key = if version >= 3 && r.offset() < entry.len() {
-        Some((r.read_u8()? & 1) != 0)
-    } else {
-        None
-    };
-
-    let old_score_cleared_v390 = if version >= 3 && r.offset() < entry.len() {
-        Some((r.read_u8()? & 1) != 0)
-    } else {
-        None
+    // v2 起有 camellia，v3 起另有 side_story4 / old_score 两个标志字节。
+    // 遇到不是 0/1 的字节即停止解析，该字节及之后的内容整体留在 overflow 中原样写回。
+    let flag_count = match version {
+        0 | 1 => 0,
+        2 => 1,
+        _ => 3,
     };
+    let mut flags = [None; 3];
+    for slot in flags.iter_mut().take(flag_count) {
+        let Some(v) = entry.get(r.offset()).copied().and_then(strict_bool) else {
+            break;
+        };
+        *slot = Some(v);
+        r.skip(1);
+    }
+    let [
+        camellia_read_key,
+        side_story4_begin_read_key,
+        old_score_cleared_v390,
+    ] = flags;
 
-    let overflow = if r.offset() < entry.len() {
-        Some(base64::engine::general_purpose::STANDARD.encode(&entry[r.offset()..]))
-    } else {
-        None
-    };
+    let overflow = r.rest_base64();
 
     Ok(GameKeyParsed {
         version,
@@ -191,6 +221,41 @@ pub fn parse_game_key_entry(entry: &[u8]) -> Result<GameKeyParsed> {
     })
 }
 
+/// 将 `GameKeyParsed` 编码回 gameKey entry（`parse_game_key_entry` 的逆操作）
+///
+/// 键按 `BTreeMap` 顺序写出；`Option` 字段仅在为 `Some` 时写出，`overflow` 原样追加在末尾。
+///
+/// # Errors
+///
+/// 键名过长、单个密钥超过 255 字节或 `overflow` 不是合法 base64 时返回错误。
+pub fn encode_game_key_entry(game_key: &GameKeyParsed) -> Result<Vec<u8>> {
+    let mut w = Writer::with_capacity(16 + game_key.keys.len() * 24);
+    w.write_u8(game_key.version);
+    w.write_varshort(game_key.keys.len())?;
+    for (name, key) in &game_key.keys {
+        w.write_string(name, "")?;
+        let data = encode_single_key(key);
+        let len = u8::try_from(data.len()).map_err(|_| CodecError::InvalidData)?;
+        w.write_u8(len);
+        w.write_raw(&data);
+    }
+    if let Some(v) = game_key.lanota_read_keys {
+        w.write_u8(v);
+    }
+    for flag in [
+        game_key.camellia_read_key,
+        game_key.side_story4_begin_read_key,
+        game_key.old_score_cleared_v390,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        w.write_u8(u8::from(flag));
+    }
+    w.write_overflow(game_key.overflow.as_deref())?;
+    Ok(w.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,4 +371,64 @@ mod tests {
         assert_eq!(parsed.lanota_read_keys, Some(0b0011_1111));
         assert!(parsed.overflow.is_none());
     }
+
+    #[test]
+    fn encode_single_key_round_trip() {
+        for data in [
+            vec![0x02, 0b0000_0001, 42],
+            vec![0x02, 0b0000_0010, 0],
+            vec![0x06, 0b0001_1111, 10, 1, 20, 0, 1],
+            vec![0x02, 0b0010_0000],
+            vec![0x01],
+            // bool 字段为 2、末尾多余字节、payload_len 为 0：均回退为 Raw
+            vec![0x02, 0b0000_0010, 2],
+            vec![0x02, 0b0000_0001, 42, 0xFF],
+            vec![0x00, 0b0000_0000],
+        ] {
+            assert_eq!(encode_single_key(&parse_single_key(&data)), data);
+        }
+    }
+
+    #[test]
+    fn parse_single_key_non_canonical_returns_raw() {
+        for data in [
+            vec![0x02, 0b0001_0000, 2],
+            vec![0x02, 0b0000_0001, 42, 0xFF],
+            vec![0x00, 0b0000_0000],
+        ] {
+            assert!(matches!(parse_single_key(&data), Key::Raw(_)), "{data:?}");
+        }
+    }
+
+    #[test]
+    fn game_key_flag_byte_outside_bool_is_kept_in_overflow() {
+        // camellia=1, side_story4=2（非 0/1）→ side_story4 及之后进入 overflow
+        let entry = vec![0x03, 0x00, 0x00, 1, 2, 1];
+        let parsed = parse_game_key_entry(&entry).expect("should parse");
+        assert_eq!(parsed.camellia_read_key, Some(true));
+        assert!(parsed.side_story4_begin_read_key.is_none());
+        assert!(parsed.old_score_cleared_v390.is_none());
+        assert!(parsed.overflow.is_some());
+        assert_eq!(encode_game_key_entry(&parsed).expect("encode"), entry);
+    }
+
+    #[test]
+    fn game_key_entry_round_trip_is_byte_exact() {
+        let mut entry = vec![0x03, 0x02];
+        // "a": Normal(read=5, avatar=true)
+        entry.extend_from_slice(&[1, b'a', 4, 0x03, 0b0001_0001, 5, 1]);
+        // "b": Raw（type_byte 高位被占用）
+        entry.extend_from_slice(&[1, b'b', 2, 0x02, 0b1000_0000]);
+        // lanota, camellia, side_story4, old_score_cleared_v390, overflow
+        entry.extend_from_slice(&[0b0011_1111, 1, 0, 1, 0xAB]);
+
+        let parsed = parse_game_key_entry(&entry).expect("should parse");
+        assert!(matches!(parsed.keys.get("a"), Some(Key::Normal(_))));
+        assert!(matches!(parsed.keys.get("b"), Some(Key::Raw(_))));
+        assert_eq!(parsed.old_score_cleared_v390, Some(true));
+        assert!(parsed.overflow.is_some());
+
+        let encoded = encode_game_key_entry(&parsed).expect("should encode");
+        assert_eq!(encoded, entry);
+    }
 }
@@ -1,10 +1,11 @@
 use alloc::string::String;
+use alloc::vec::Vec;
 
-use base64::Engine as _;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{CodecError, Result};
-use crate::reader::Reader;
+use crate::reader::{Reader, set_bit};
+use crate::writer::{Writer, required};
 
 /// 解析后的游戏进度信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +43,14 @@ fn read_bool_array<const N: usize>(reader: &mut Reader) -> Result<[bool; N]> {
     Ok(arr)
 }
 
+fn pack_bools(bits: &[bool]) -> u8 {
+    let mut b = 0u8;
+    for (i, &v) in bits.iter().enumerate() {
+        set_bit(&mut b, i, v);
+    }
+    b
+}
+
 /// 解析 gameProgress entry
 ///
 /// # Errors
@@ -110,8 +119,152 @@ pub fn parse_game_progress_entry(entry: &[u8]) -> Result<GameProgressParsed> {
         // v4: flag_of_song_record_key_takumi (3 bits)
         out.flag_of_song_record_key_takumi = Some(read_bool_array::<3>(&mut r)?);
     }
-    if r.offset() < entry.len() {
-        out.overflow = Some(base64::engine::general_purpose::STANDARD.encode(&entry[r.offset()..]));
-    }
+    out.overflow = r.rest_base64();
     Ok(out)
 }
+
+/// 将 `GameProgressParsed` 编码回 gameProgress entry（`parse_game_progress_entry` 的逆操作）
+///
+/// 按 `version` 写出对应版本的字段，`overflow` 原样追加在末尾。
+/// 位标志字节中未建模的高位写为 0。
+///
+/// # Errors
+///
+/// 当前版本要求的字段缺失、`money` 超出 varshort 范围或 `overflow` 不是合法 base64 时返回错误。
+pub fn encode_game_progress_entry(progress: &GameProgressParsed) -> Result<Vec<u8>> {
+    let mut w = Writer::with_capacity(64);
+    let version = progress.version;
+    w.write_u8(version);
+
+    if version >= 1 {
+        w.write_u8(pack_bools(&[
+            required(progress.is_first_run, "is_first_run")?,
+            required(progress.legacy_chapter_finished, "legacy_chapter_finished")?,
+            required(
+                progress.already_show_collection_tip,
+                "already_show_collection_tip",
+            )?,
+            required(
+                progress.already_show_auto_unlock_in_tip,
+                "already_show_auto_unlock_in_tip",
+            )?,
+        ]));
+        let completed = progress
+            .completed
+            .as_deref()
+            .ok_or_else(|| CodecError::from("missing field `completed`"))?;
+        w.write_string(completed, "")?;
+        w.write_u8(required(progress.song_update_info, "song_update_info")?);
+        w.write_u16_le(required(
+            progress.challenge_mode_rank,
+            "challenge_mode_rank",
+        )?);
+        for slot in required(progress.money, "money")? {
+            let v = usize::try_from(slot).map_err(|_| CodecError::InvalidData)?;
+            w.write_varshort(v)?;
+        }
+        w.write_u8(required(
+            progress.unlock_flag_of_spasmodic,
+            "unlock_flag_of_spasmodic",
+        )?);
+        w.write_u8(required(
+            progress.unlock_flag_of_igallta,
+            "unlock_flag_of_igallta",
+        )?);
+        w.write_u8(required(
+            progress.unlock_flag_of_rrharil,
+            "unlock_flag_of_rrharil",
+        )?);
+        w.write_u8(required(
+            progress.flag_of_song_record_key,
+            "flag_of_song_record_key",
+        )?);
+    }
+    if version >= 2 {
+        w.write_u8(required(
+            progress.random_version_unlocked,
+            "random_version_unlocked",
+        )?);
+    }
+    if version >= 3 {
+        w.write_u8(pack_bools(&[
+            required(progress.chapter8_unlock_begin, "chapter8_unlock_begin")?,
+            required(
+                progress.chapter8_unlock_second_phase,
+                "chapter8_unlock_second_phase",
+            )?,
+            required(progress.chapter8_passed, "chapter8_passed")?,
+        ]));
+        w.write_u8(required(
+            progress.chapter8_song_unlocked,
+            "chapter8_song_unlocked",
+        )?);
+    }
+    if version >= 4 {
+        w.write_u8(pack_bools(&required(
+            progress.flag_of_song_record_key_takumi,
+            "flag_of_song_record_key_takumi",
+        )?));
+    }
+    w.write_overflow(progress.overflow.as_deref())?;
+    Ok(w.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// v4 gameProgress：flags + completed + money(含两字节 varshort) + 章节 8 + Takumi + overflow
+    fn sample_v4_entry() -> Vec<u8> {
+        let mut e = vec![4u8, 0b0000_1010];
+        e.push(3);
+        e.extend_from_slice(b"abc");
+        e.push(7); // song_update_info
+        e.extend_from_slice(&321u16.to_le_bytes());
+        // money: 1, 0x80(两字节), 0x1234(两字节), 0, 127
+        e.extend_from_slice(&[0x01, 0x80, 0x01, 0xB4, 0x24, 0x00, 0x7F]);
+        e.extend_from_slice(&[1, 2, 3, 4]); // 四个 unlock/flag 字节
+        e.push(9); // random_version_unlocked
+        e.push(0b0000_0101); // chapter8 flags
+        e.push(6); // chapter8_song_unlocked
+        e.push(0b0000_0110); // takumi
+        e.extend_from_slice(&[0xDE, 0xAD]); // overflow
+        e
+    }
+
+    #[test]
+    fn v4_round_trip_is_byte_exact() {
+        let entry = sample_v4_entry();
+        let parsed = parse_game_progress_entry(&entry).expect("should parse");
+        assert_eq!(parsed.money, Some([1, 0x80, 0x1234, 0, 127]));
+        assert_eq!(
+            parsed.flag_of_song_record_key_takumi,
+            Some([false, true, true])
+        );
+        assert!(parsed.overflow.is_some());
+        let encoded = encode_game_progress_entry(&parsed).expect("should encode");
+        assert_eq!(encoded, entry);
+    }
+
+    #[test]
+    fn v0_round_trip_is_byte_exact() {
+        let entry = vec![0u8];
+        let parsed = parse_game_progress_entry(&entry).expect("should parse");
+        assert_eq!(encode_game_progress_entry(&parsed).expect("encode"), entry);
+    }
+
+    #[test]
+    fn missing_versioned_field_is_error() {
+        let mut parsed = parse_game_progress_entry(&sample_v4_entry()).expect("should parse");
+        parsed.chapter8_passed = None;
+        assert!(encode_game_progress_entry(&parsed).is_err());
+    }
+
+    #[test]
+    fn negative_money_is_error() {
+        let mut parsed = parse_game_progress_entry(&sample_v4_entry()).expect("should parse");
+        parsed.money = Some([-1, 0, 0, 0, 0]);
+        assert!(encode_game_progress_entry(&parsed).is_err());
+    }
+}
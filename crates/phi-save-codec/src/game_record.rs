@@ -6,8 +6,12 @@ use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{CodecError, Result};
-use crate::reader::Reader;
+use crate::reader::{Reader, get_bit, set_bit};
 use crate::types::{Difficulty, DifficultyRecord};
+use crate::writer::Writer;
+
+/// gameRecord 中歌曲 ID 的固定后缀（解析时裁掉，编码时补回）
+pub const SONG_ID_SUFFIX: &str = ".0";
 
 /// 单首歌曲各难度的成绩记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,7 +23,51 @@ pub struct SongLevelRecord {
     pub at: Option<LevelRecord>,
 }
 
+impl SongLevelRecord {
+    fn slots(&self) -> [Option<&LevelRecord>; 4] {
+        [
+            self.ez.as_ref(),
+            self.hd.as_ref(),
+            self.r#in.as_ref(),
+            self.at.as_ref(),
+        ]
+    }
+
+    fn slot_mut(&mut self, idx: usize) -> Option<&mut Option<LevelRecord>> {
+        match idx {
+            0 => Some(&mut self.ez),
+            1 => Some(&mut self.hd),
+            2 => Some(&mut self.r#in),
+            3 => Some(&mut self.at),
+            _ => None,
+        }
+    }
+
+    /// 从 `parse_game_record_bytes` 的结果构造（不存在的难度为 `None`）
+    #[must_use]
+    pub fn from_records(records: &[DifficultyRecord]) -> Self {
+        let mut song = SongLevelRecord {
+            ez: None,
+            hd: None,
+            r#in: None,
+            at: None,
+        };
+        for r in records {
+            if let Some(slot) = song.slot_mut(r.difficulty as usize) {
+                *slot = Some(LevelRecord {
+                    score: r.score,
+                    acc: r.accuracy,
+                    fc: r.is_full_combo,
+                });
+            }
+        }
+        song
+    }
+}
+
 /// 单一难度的成绩数据
+///
+/// `score` 为 0 表示该难度在存档中占位但无成绩（`parse_game_record_levels` 会保留这类槽位）。
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct LevelRecord {
     pub score: u32,
@@ -136,6 +184,128 @@ pub fn parse_game_record_bytes(
     Ok(result)
 }
 
+/// 无损解析 gameRecord 二进制 entry 为 `SongLevelRecord` map
+///
+/// 与 `parse_game_record_bytes` 的区别：
+/// - 保留 mask 中存在但 score 为 0 的槽位，acc 原样保留（含非有限值）
+/// - 任何格式异常（payload 长度不符、歌曲 ID 后缀不是 `.0`）直接返回错误，而非静默跳过
+///
+/// 结果可由 `encode_game_record_levels` 编码回原始字节。
+///
+/// # Errors
+///
+/// 数据不足时返回 `CodecError::NotEnoughData`，格式异常时返回 `CodecError::InvalidData`。
+pub fn parse_game_record_levels(
+    game_record_entry: &[u8],
+) -> Result<BTreeMap<String, SongLevelRecord>> {
+    if game_record_entry.is_empty() {
+        return Err(CodecError::NotEnoughData);
+    }
+    let mut reader = Reader::new(&game_record_entry[1..]);
+    let length = reader.read_varshort()?;
+    let mut result = BTreeMap::new();
+
+    for _ in 0..length {
+        let full_id = reader.read_string(0)?;
+        let song_id = full_id
+            .strip_suffix(SONG_ID_SUFFIX)
+            .ok_or(CodecError::InvalidData)?;
+        let first_len = usize::from(reader.read_u8()?);
+        let payload_start = reader.offset();
+
+        let mask = reader.read_u8()?;
+        let fc_mask = reader.read_u8()?;
+        let mut song = SongLevelRecord {
+            ez: None,
+            hd: None,
+            r#in: None,
+            at: None,
+        };
+        for idx in 0..4usize {
+            if !get_bit(mask, idx) {
+                continue;
+            }
+            let score = reader.read_i32_le()?;
+            let acc = reader.read_f32_le()?;
+            let record = LevelRecord {
+                score: u32::try_from(score).map_err(|_| CodecError::InvalidData)?,
+                acc,
+                fc: get_bit(fc_mask, idx),
+            };
+            if let Some(slot) = song.slot_mut(idx) {
+                *slot = Some(record);
+            }
+        }
+
+        // 未建模的高位 / 无对应成绩的 fc 位都会在编码时丢失，视为格式异常
+        if reader.offset() - payload_start != first_len || mask & 0xF0 != 0 || fc_mask & !mask != 0
+        {
+            return Err(CodecError::InvalidData);
+        }
+        result.insert(song_id.to_string(), song);
+    }
+
+    if reader.remain() != 0 {
+        return Err(CodecError::InvalidData);
+    }
+    Ok(result)
+}
+
+/// 将 `SongLevelRecord` map 编码回 gameRecord entry（`parse_game_record_levels` 的逆操作）
+///
+/// `prefix` 为 entry 第 1 字节；歌曲按 `BTreeMap` 顺序写出，ID 末尾补回 `.0`。
+///
+/// # Errors
+///
+/// 歌曲数量或 ID 长度超出 varshort 范围、score 超出 `i32` 时返回 `CodecError::InvalidData`。
+pub fn encode_game_record_levels(
+    prefix: u8,
+    records: &BTreeMap<String, SongLevelRecord>,
+) -> Result<Vec<u8>> {
+    let mut w = Writer::with_capacity(4 + records.len() * 64);
+    w.write_u8(prefix);
+    w.write_varshort(records.len())?;
+    for (song_id, song) in records {
+        w.write_string(song_id, SONG_ID_SUFFIX)?;
+        let mut mask = 0u8;
+        let mut fc_mask = 0u8;
+        let mut payload = Writer::with_capacity(2 + 4 * 8);
+        for (idx, level) in song.slots().into_iter().enumerate() {
+            let Some(level) = level else { continue };
+            set_bit(&mut mask, idx, true);
+            set_bit(&mut fc_mask, idx, level.fc);
+            payload.write_i32_le(i32::try_from(level.score).map_err(|_| CodecError::InvalidData)?);
+            payload.write_f32_le(level.acc);
+        }
+        let payload = payload.into_inner();
+        // payload 最多 2 + 4*8 = 34 字节，不会溢出 u8
+        w.write_u8(u8::try_from(payload.len() + 2).map_err(|_| CodecError::InvalidData)?);
+        w.write_u8(mask);
+        w.write_u8(fc_mask);
+        w.write_raw(&payload);
+    }
+    Ok(w.into_inner())
+}
+
+/// 将 `parse_game_record_bytes` 的结果编码回 gameRecord entry
+///
+/// 注意：`parse_game_record_bytes` 会丢弃 score 为 0 的槽位，因此这条路径只对"全部槽位都有成绩"
+/// 的存档字节精确还原；需要严格无损时请使用 `parse_game_record_levels`/`encode_game_record_levels`。
+///
+/// # Errors
+///
+/// 同 `encode_game_record_levels`。
+pub fn encode_game_record_bytes(
+    prefix: u8,
+    records: &BTreeMap<String, Vec<DifficultyRecord>>,
+) -> Result<Vec<u8>> {
+    let levels: BTreeMap<String, SongLevelRecord> = records
+        .iter()
+        .map(|(id, recs)| (id.clone(), SongLevelRecord::from_records(recs)))
+        .collect();
+    encode_game_record_levels(prefix, &levels)
+}
+
 /// 从 `serde_json` Value 解析 gameRecord JSON 格式
 ///
 /// # Errors
@@ -210,14 +380,23 @@ pub fn parse_game_record_json(
 mod tests {
     use super::*;
     use alloc::format;
-    use alloc::string::ToString;
     use alloc::vec;
 
     /// 辅助函数：构造一条歌曲在 gameRecord 中的完整二进制数据块
     fn build_song_chunk(song_id: &str, mask: u8, fc_mask: u8, scores: &[(u32, f32)]) -> Vec<u8> {
+        build_song_chunk_with_suffix(song_id, "__", mask, fc_mask, scores)
+    }
+
+    fn build_song_chunk_with_suffix(
+        song_id: &str,
+        suffix: &str,
+        mask: u8,
+        fc_mask: u8,
+        scores: &[(u32, f32)],
+    ) -> Vec<u8> {
         let mut chunk = Vec::new();
         // song_id: varshort(len) + id + 2 trim bytes
-        let key_full = format!("{}__", song_id);
+        let key_full = format!("{}{}", song_id, suffix);
         push_varshort(&mut chunk, key_full.len());
         chunk.extend_from_slice(key_full.as_bytes());
 
@@ -332,4 +511,63 @@ mod tests {
         assert_eq!(recs[0].difficulty, Difficulty::HD);
         assert_eq!(recs[0].score, 950_000);
     }
+
+    #[test]
+    fn levels_round_trip_is_byte_exact() {
+        let entry = build_entry(vec![
+            build_song_chunk_with_suffix(
+                "a",
+                ".0",
+                0b1011,
+                0b1001,
+                &[(1_000_000, 100.0), (0, 0.0), (987_654, 99.12)],
+            ),
+            build_song_chunk_with_suffix("b", ".0", 0b0100, 0b0000, &[(123_456, f32::NAN)]),
+        ]);
+        let levels = parse_game_record_levels(&entry).expect("should parse");
+        let a = levels.get("a").expect("a exists");
+        assert_eq!(a.hd.map(|l| l.score), Some(0), "zero-score slot is kept");
+        assert!(a.at.is_some_and(|l| l.fc));
+        assert!(
+            levels
+                .get("b")
+                .and_then(|b| b.r#in)
+                .is_some_and(|l| l.acc.is_nan())
+        );
+
+        let encoded = encode_game_record_levels(entry[0], &levels).expect("should encode");
+        assert_eq!(encoded, entry);
+    }
+
+    #[test]
+    fn levels_reject_bad_suffix_and_payload_len() {
+        let bad_suffix = build_entry(vec![build_song_chunk("a", 0b0001, 0, &[(1, 1.0)])]);
+        assert!(parse_game_record_levels(&bad_suffix).is_err());
+
+        let mut chunk = Vec::new();
+        push_varshort(&mut chunk, 3);
+        chunk.extend_from_slice(b"a.0");
+        chunk.extend_from_slice(&[12, 0b0001, 0]); // 声明 12 字节，实际只有 10
+        chunk.extend_from_slice(&1i32.to_le_bytes());
+        chunk.extend_from_slice(&1f32.to_le_bytes());
+        assert!(parse_game_record_levels(&build_entry(vec![chunk])).is_err());
+    }
+
+    #[test]
+    fn encode_from_difficulty_records_round_trips_through_parser() {
+        let entry = build_entry(vec![build_song_chunk(
+            "s",
+            0b0011,
+            0b0010,
+            &[(900_000, 90.0), (950_000, 95.0)],
+        )]);
+        let parsed = parse_game_record_bytes(&entry, noop_chart_lookup).expect("should parse");
+        let encoded = encode_game_record_bytes(entry[0], &parsed).expect("should encode");
+        let reparsed = parse_game_record_bytes(&encoded, noop_chart_lookup).expect("reparse");
+        let recs = reparsed.get("s").expect("s exists");
+        assert_eq!(recs.len(), 2);
+        assert_eq!(recs[1].score, 950_000);
+        assert!(recs[1].is_full_combo);
+        assert!(!recs[0].is_full_combo);
+    }
 }
@@ -1,14 +1,18 @@
 //! phi-save-codec: Phigros Cloud Save Binary Format Codec
 //!
-//! 提供 Phigros 存档二进制格式的解析能力，不依赖异步运行时。
-//! 使用方式：解析后的 struct 可通过 serde 序列化为 JSON 等格式。
+//! 提供 Phigros 存档二进制格式的解析与编码能力，不依赖异步运行时。
+//! 使用方式：解析后的 struct 可通过 serde 序列化为 JSON 等格式；
+//! 各 `encode_*` 函数是对应 `parse_*` 的逆操作，可将结构化数据写回原始字节布局；
+//! `annotate_entry` 按同一布局给每段字节标注字段名，供诊断工具使用。
 
 #![no_std]
 
 extern crate alloc;
 
 mod reader;
+mod writer;
 
+pub mod annotate;
 pub mod error;
 pub mod game_key;
 pub mod game_progress;
@@ -19,19 +23,21 @@ pub mod types;
 pub mod user;
 
 // Re-exports
+pub use annotate::{EntryAnnotation, FieldSpan, annotate_entry};
 pub use error::CodecError;
 pub use types::*;
 
 pub use game_key::GameKeyParsed;
-pub use game_key::parse_game_key_entry;
+pub use game_key::{encode_game_key_entry, parse_game_key_entry};
 pub use game_progress::GameProgressParsed;
-pub use game_progress::parse_game_progress_entry;
+pub use game_progress::{encode_game_progress_entry, parse_game_progress_entry};
 pub use game_record::{
-    LevelRecord, SongLevelRecord, parse_game_record_bytes, parse_game_record_json,
+    LevelRecord, SongLevelRecord, encode_game_record_bytes, encode_game_record_levels,
+    parse_game_record_bytes, parse_game_record_json, parse_game_record_levels,
 };
 pub use settings::SettingsParsed;
-pub use settings::parse_settings_entry;
+pub use settings::{encode_settings_entry, parse_settings_entry};
 pub use summary::SummaryParsed;
-pub use summary::parse_summary_base64;
+pub use summary::{encode_summary_base64, parse_summary_base64};
 pub use user::UserParsed;
-pub use user::parse_user_entry;
+pub use user::{encode_user_entry, parse_user_entry};
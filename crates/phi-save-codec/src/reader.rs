@@ -1,5 +1,6 @@
 use crate::error::{CodecError, Result};
 use alloc::borrow::ToOwned as _;
+use base64::Engine as _;
 
 /// 二进制读取器（从原始字节切片中按顺序读取字段）
 pub struct Reader<'a> {
@@ -96,19 +97,34 @@ impl<'a> Reader<'a> {
         self.read_string(trim_end).map(str::to_owned)
     }
 
+    /// 剩余未读字节的 base64（用作 `overflow`，编码时原样写回）；已读完时返回 `None`
+    pub fn rest_base64(&self) -> Option<alloc::string::String> {
+        (self.remain() > 0)
+            .then(|| base64::engine::general_purpose::STANDARD.encode(&self.data[self.off..]))
+    }
+
     /// 跳过指定字节数
     pub fn skip(&mut self, n: usize) {
         self.off = self.off.saturating_add(n).min(self.data.len());
     }
 }
 
+/// 严格的 bool 字节：仅 0/1 合法，其余值返回 `None`（调用方据此保留原始字节）
+#[inline]
+pub fn strict_bool(byte: u8) -> Option<bool> {
+    match byte {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
 /// 位操作工具
 #[inline]
 pub fn get_bit(byte: u8, index: usize) -> bool {
     ((byte >> index) & 1) != 0
 }
 
-#[allow(dead_code)]
 #[inline]
 pub fn set_bit(byte: &mut u8, index: usize, value: bool) {
     if value {
@@ -1,9 +1,11 @@
 use alloc::string::String;
+use alloc::vec::Vec;
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::{CodecError, Result};
-use crate::reader::Reader;
+use crate::reader::{Reader, set_bit};
+use crate::writer::Writer;
 
 /// 解析后的客户端设置
 #[allow(clippy::struct_excessive_bools)]
@@ -20,8 +22,17 @@ pub struct SettingsParsed {
     pub hit_sound_volume: f64,
     pub sound_offset: f64,
     pub note_scale: f64,
+    /// flags 字节中未建模的位（bit 4-7），非零时保留以便原样写回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_flags: Option<u8>,
+    /// 已知字段之后的剩余字节（base64）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overflow: Option<String>,
 }
 
+/// flags 字节中已建模的位（bit 0-3）
+const KNOWN_FLAGS: u8 = 0b1111;
+
 /// 解析 settings entry
 ///
 /// # Errors
@@ -45,5 +56,82 @@ pub fn parse_settings_entry(entry: &[u8]) -> Result<SettingsParsed> {
         hit_sound_volume: f64::from(r.read_f32_le()?),
         sound_offset: f64::from(r.read_f32_le()?),
         note_scale: f64::from(r.read_f32_le()?),
+        unknown_flags: Some(flags & !KNOWN_FLAGS).filter(|&b| b != 0),
+        overflow: r.rest_base64(),
     })
 }
+
+/// 将 `SettingsParsed` 编码回 settings entry（`parse_settings_entry` 的逆操作）
+///
+/// `prefix` 为 entry 第 1 字节（解析时被跳过）；浮点字段在存档中为 f32，按 f32 写回；
+/// `unknown_flags` 并入 flags 字节，`overflow` 原样追加在末尾。
+///
+/// # Errors
+///
+/// `device_name` 超出 varshort 长度范围或 `overflow` 不是合法 base64 时返回错误。
+#[allow(clippy::cast_possible_truncation)]
+pub fn encode_settings_entry(prefix: u8, settings: &SettingsParsed) -> Result<Vec<u8>> {
+    let mut w = Writer::with_capacity(32 + settings.device_name.len());
+    w.write_u8(prefix);
+    let mut flags = settings.unknown_flags.unwrap_or(0) & !KNOWN_FLAGS;
+    set_bit(&mut flags, 0, settings.chord_support);
+    set_bit(&mut flags, 1, settings.fc_ap_indicator);
+    set_bit(&mut flags, 2, settings.enable_hit_sound);
+    set_bit(&mut flags, 3, settings.low_resolution_mode);
+    w.write_u8(flags);
+    w.write_string(&settings.device_name, "")?;
+    for v in [
+        settings.bright,
+        settings.music_volume,
+        settings.effect_volume,
+        settings.hit_sound_volume,
+        settings.sound_offset,
+        settings.note_scale,
+    ] {
+        w.write_f32_le(v as f32);
+    }
+    w.write_overflow(settings.overflow.as_deref())?;
+    Ok(w.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn sample_entry() -> Vec<u8> {
+        let mut e = vec![1u8, 0b0000_0101];
+        e.push(9);
+        e.extend_from_slice(b"Pixel 7a\0");
+        for v in [0.75_f32, 1.0, 0.9, 0.6, -0.012, 1.15] {
+            e.extend_from_slice(&v.to_le_bytes());
+        }
+        e
+    }
+
+    #[test]
+    fn round_trip_is_byte_exact() {
+        let entry = sample_entry();
+        let parsed = parse_settings_entry(&entry).expect("should parse");
+        assert!(parsed.chord_support && parsed.enable_hit_sound);
+        assert!(parsed.unknown_flags.is_none() && parsed.overflow.is_none());
+        assert_eq!(
+            encode_settings_entry(entry[0], &parsed).expect("encode"),
+            entry
+        );
+    }
+
+    #[test]
+    fn unknown_flags_and_trailing_bytes_round_trip() {
+        let mut entry = sample_entry();
+        entry[1] |= 0b1010_0000;
+        entry.extend_from_slice(&[0xAB, 0xCD]);
+        let parsed = parse_settings_entry(&entry).expect("should parse");
+        assert_eq!(parsed.unknown_flags, Some(0b1010_0000));
+        assert!(parsed.overflow.is_some());
+        assert_eq!(
+            encode_settings_entry(entry[0], &parsed).expect("encode"),
+            entry
+        );
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{CodecError, Result};
 use crate::reader::Reader;
+use crate::writer::Writer;
 
 /// 解析后的存档摘要
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,3 +50,47 @@ pub fn parse_summary_base64(b64: &str) -> Result<SummaryParsed> {
         progress,
     })
 }
+
+/// 将 `SummaryParsed` 编码为 base64 summary（`parse_summary_base64` 的逆操作）
+///
+/// # Errors
+///
+/// `avatar` 超出 varshort 长度范围时返回 `CodecError::InvalidData`。
+pub fn encode_summary_base64(summary: &SummaryParsed) -> Result<String> {
+    let mut w = Writer::with_capacity(40 + summary.avatar.len());
+    w.write_u8(summary.save_version);
+    w.write_u16_le(summary.challenge_mode_rank);
+    w.write_f32_le(summary.ranking_score);
+    w.write_u8(summary.game_version);
+    w.write_string(&summary.avatar, "")?;
+    for slot in summary.progress {
+        w.write_u16_le(slot);
+    }
+    Ok(base64::engine::general_purpose::STANDARD.encode(w.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_round_trip_is_byte_exact() {
+        let summary = SummaryParsed {
+            save_version: 6,
+            challenge_mode_rank: 548,
+            ranking_score: 15.234_567,
+            game_version: 112,
+            avatar: "Introduction".into(),
+            progress: [12, 34, 5, 0, 99, 1, 2, 3, 4, 5, 6, 7],
+        };
+        let b64 = encode_summary_base64(&summary).expect("should encode");
+        let parsed = parse_summary_base64(&b64).expect("should parse");
+        assert_eq!(parsed.avatar, summary.avatar);
+        assert_eq!(parsed.progress, summary.progress);
+        assert_eq!(
+            parsed.ranking_score.to_bits(),
+            summary.ranking_score.to_bits()
+        );
+        assert_eq!(encode_summary_base64(&parsed).expect("re-encode"), b64);
+    }
+}
@@ -1,9 +1,11 @@
 use alloc::string::String;
+use alloc::vec::Vec;
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::{CodecError, Result};
-use crate::reader::Reader;
+use crate::reader::{Reader, set_bit};
+use crate::writer::Writer;
 
 /// 解析后的用户信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,8 +14,17 @@ pub struct UserParsed {
     pub self_intro: String,
     pub avatar: String,
     pub background: String,
+    /// flags 字节中未建模的位（bit 1-7），非零时保留以便原样写回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_flags: Option<u8>,
+    /// 已知字段之后的剩余字节（base64）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overflow: Option<String>,
 }
 
+/// flags 字节中已建模的位（bit 0）
+const KNOWN_FLAGS: u8 = 0b0001;
+
 /// 解析 user entry
 ///
 /// # Errors
@@ -30,5 +41,65 @@ pub fn parse_user_entry(entry: &[u8]) -> Result<UserParsed> {
         self_intro: r.read_owned_string(0)?,
         avatar: r.read_owned_string(0)?,
         background: r.read_owned_string(0)?,
+        unknown_flags: Some(flags & !KNOWN_FLAGS).filter(|&b| b != 0),
+        overflow: r.rest_base64(),
     })
 }
+
+/// 将 `UserParsed` 编码回 user entry（`parse_user_entry` 的逆操作）
+///
+/// `prefix` 为 entry 第 1 字节（解析时被跳过）；`unknown_flags` 并入 flags 字节，
+/// `overflow` 原样追加在末尾。
+///
+/// # Errors
+///
+/// 任一字符串超出 varshort 长度范围或 `overflow` 不是合法 base64 时返回错误。
+pub fn encode_user_entry(prefix: u8, user: &UserParsed) -> Result<Vec<u8>> {
+    let mut w = Writer::with_capacity(
+        8 + user.self_intro.len() + user.avatar.len() + user.background.len(),
+    );
+    w.write_u8(prefix);
+    let mut flags = user.unknown_flags.unwrap_or(0) & !KNOWN_FLAGS;
+    set_bit(&mut flags, 0, user.show_player_id);
+    w.write_u8(flags);
+    w.write_string(&user.self_intro, "")?;
+    w.write_string(&user.avatar, "")?;
+    w.write_string(&user.background, "")?;
+    w.write_overflow(user.overflow.as_deref())?;
+    Ok(w.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn sample_entry() -> Vec<u8> {
+        let mut e = vec![1u8, 0b0000_0001];
+        for s in ["hello", "Introduction", "Glaciaxion.SunsetRay"] {
+            e.push(u8::try_from(s.len()).expect("short string"));
+            e.extend_from_slice(s.as_bytes());
+        }
+        e
+    }
+
+    #[test]
+    fn round_trip_is_byte_exact() {
+        let entry = sample_entry();
+        let parsed = parse_user_entry(&entry).expect("should parse");
+        assert!(parsed.show_player_id);
+        assert_eq!(parsed.avatar, "Introduction");
+        assert_eq!(encode_user_entry(entry[0], &parsed).expect("encode"), entry);
+    }
+
+    #[test]
+    fn unknown_flags_and_trailing_bytes_round_trip() {
+        let mut entry = sample_entry();
+        entry[1] |= 0b0100_0000;
+        entry.push(0x00);
+        let parsed = parse_user_entry(&entry).expect("should parse");
+        assert_eq!(parsed.unknown_flags, Some(0b0100_0000));
+        assert!(parsed.overflow.is_some());
+        assert_eq!(encode_user_entry(entry[0], &parsed).expect("encode"), entry);
+    }
+}
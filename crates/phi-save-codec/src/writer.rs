@@ -0,0 +1,87 @@
+use crate::error::{CodecError, Result};
+use alloc::vec::Vec;
+use base64::Engine as _;
+
+/// varshort 能表示的最大值（与 `Reader::read_varshort` 的两字节形式对应）
+pub const VARSHORT_MAX: usize = 0x7FFF;
+
+/// 二进制写入器（按顺序追加字段，与 `Reader` 的布局一一对应）
+pub struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(cap),
+        }
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn write_u16_le(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_i32_le(&mut self, v: i32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_f32_le(&mut self, v: f32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// 可变长度整数（`read_varshort` 的逆操作）
+    ///
+    /// 小于 0x80 时写 1 字节，否则写 2 字节：`b0 = (v & 0x7F) | 0x80`，`b1 = v >> 7`。
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn write_varshort(&mut self, v: usize) -> Result<()> {
+        if v < 0x80 {
+            self.buf.push(v as u8);
+            Ok(())
+        } else if v <= VARSHORT_MAX {
+            self.buf.push(((v & 0x7F) | 0x80) as u8);
+            self.buf.push((v >> 7) as u8);
+            Ok(())
+        } else {
+            Err(CodecError::InvalidData)
+        }
+    }
+
+    /// 写入 varshort 长度前缀的字符串，可追加末尾后缀（对应 `read_string` 的 `trim_end`）
+    pub fn write_string(&mut self, s: &str, suffix: &str) -> Result<()> {
+        self.write_varshort(s.len() + suffix.len())?;
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.extend_from_slice(suffix.as_bytes());
+        Ok(())
+    }
+
+    /// 直接追加原始字节（不写长度）
+    pub fn write_raw(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// 追加 base64 形式的 `overflow` 原始字节（`Reader::rest_base64` 的逆操作）
+    pub fn write_overflow(&mut self, overflow: Option<&str>) -> Result<()> {
+        if let Some(overflow) = overflow {
+            let tail = base64::engine::general_purpose::STANDARD
+                .decode(overflow)
+                .map_err(|e| {
+                    CodecError::from(alloc::format!("overflow base64 decode failed: {e}"))
+                })?;
+            self.buf.extend_from_slice(&tail);
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// 读取 Option 字段；缺失时返回带字段名的错误（用于版本门控字段的编码）
+pub fn required<T: Copy>(value: Option<T>, field: &'static str) -> Result<T> {
+    value.ok_or_else(|| CodecError::from(alloc::format!("missing field `{field}`")))
+}
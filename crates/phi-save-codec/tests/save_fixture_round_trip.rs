@@ -0,0 +1,78 @@
+//! 存档样例的逐字节往返测试
+//!
+//! `tests/fixtures/save/` 下是一份完整存档解密后的各 entry（hex / base64），
+//! 按客户端存档布局整理；每个 `parse_*` 的结果经对应 `encode_*` 后必须与原始字节完全一致。
+
+use phi_save_codec::{
+    encode_game_key_entry, encode_game_progress_entry, encode_game_record_levels,
+    encode_settings_entry, encode_summary_base64, encode_user_entry, parse_game_key_entry,
+    parse_game_progress_entry, parse_game_record_levels, parse_settings_entry,
+    parse_summary_base64, parse_user_entry,
+};
+
+fn fixture(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex.bytes().filter(u8::is_ascii_hexdigit).collect();
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let s = std::str::from_utf8(pair).expect("ascii hex");
+            u8::from_str_radix(s, 16).expect("hex digit pair")
+        })
+        .collect()
+}
+
+#[test]
+fn game_record_fixture_round_trip() {
+    let entry = fixture(include_str!("fixtures/save/gameRecord.hex"));
+    let parsed = parse_game_record_levels(&entry).expect("parse gameRecord");
+    assert_eq!(parsed.len(), 4);
+    assert_eq!(
+        encode_game_record_levels(entry[0], &parsed).expect("encode"),
+        entry
+    );
+}
+
+#[test]
+fn game_key_fixture_round_trip() {
+    let entry = fixture(include_str!("fixtures/save/gameKey.hex"));
+    let parsed = parse_game_key_entry(&entry).expect("parse gameKey");
+    assert_eq!(parsed.keys.len(), 4);
+    assert_eq!(parsed.old_score_cleared_v390, Some(false));
+    assert!(parsed.overflow.is_none());
+    assert_eq!(encode_game_key_entry(&parsed).expect("encode"), entry);
+}
+
+#[test]
+fn game_progress_fixture_round_trip() {
+    let entry = fixture(include_str!("fixtures/save/gameProgress.hex"));
+    let parsed = parse_game_progress_entry(&entry).expect("parse gameProgress");
+    assert_eq!(parsed.money, Some([12, 512, 3, 0, 0]));
+    assert_eq!(encode_game_progress_entry(&parsed).expect("encode"), entry);
+}
+
+#[test]
+fn settings_fixture_round_trip() {
+    let entry = fixture(include_str!("fixtures/save/settings.hex"));
+    let parsed = parse_settings_entry(&entry).expect("parse settings");
+    assert_eq!(parsed.device_name, "Xiaomi 2211133C");
+    assert_eq!(
+        encode_settings_entry(entry[0], &parsed).expect("encode"),
+        entry
+    );
+}
+
+#[test]
+fn user_fixture_round_trip() {
+    let entry = fixture(include_str!("fixtures/save/user.hex"));
+    let parsed = parse_user_entry(&entry).expect("parse user");
+    assert_eq!(parsed.background, "Glaciaxion.SunsetRay");
+    assert_eq!(encode_user_entry(entry[0], &parsed).expect("encode"), entry);
+}
+
+#[test]
+fn summary_fixture_round_trip() {
+    let b64 = include_str!("fixtures/save/summary.b64").trim();
+    let parsed = parse_summary_base64(b64).expect("parse summary");
+    assert_eq!(parsed.avatar, "Introduction");
+    assert_eq!(encode_summary_base64(&parsed).expect("encode"), b64);
+}
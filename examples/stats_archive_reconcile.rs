@@ -256,7 +256,7 @@ async fn connect_readonly_storage(db_path: &Path) -> Result<StatsStorage, AppErr
         .await
         .map_err(|e| AppError::Internal(format!("sqlite connect readonly: {e}")))?;
 
-    Ok(StatsStorage { pool })
+    Ok(StatsStorage::from_pool(pool))
 }
 
 #[tokio::main]
@@ -1,2 +1,5 @@
-pub use crate::features::image::handler::{ImageQueryOpts, render_bn, render_song};
+pub use crate::features::image::handler::leaderboard::LeaderboardImageQuery;
+pub use crate::features::image::handler::{
+    ImageQueryOpts, render_bn, render_leaderboard, render_song,
+};
 pub use crate::features::image::{RenderBnRequest, RenderSongRequest};
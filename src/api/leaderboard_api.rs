@@ -1,2 +1,9 @@
-pub use crate::features::leaderboard::handler::{RankQuery, TopQuery, get_by_rank, get_top};
-pub use crate::features::leaderboard::models::LeaderboardTopResponse;
+pub use crate::features::leaderboard::handler::{
+    ChartTopQuery, DistributionQuery, GainersQuery, RankHistoryQuery, RankQuery, TopQuery,
+    get_by_rank, get_chart_top, get_public_rank_history, get_rks_distribution, get_rks_gainers,
+    get_top,
+};
+pub use crate::features::leaderboard::models::{
+    ChartLeaderboardResponse, LeaderboardTopResponse, RankHistoryResponse, RksDistributionResponse,
+    RksGainersResponse,
+};
@@ -1 +1,3 @@
-pub use crate::features::save::handler::get_save_data;
+pub use crate::features::save::handler::{
+    get_save_data, post_save_diff, post_save_diff_upload, post_save_upload,
+};
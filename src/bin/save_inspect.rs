@@ -13,15 +13,18 @@
 //!
 //! 安全原则：
 //! - 不建议在命令行参数里传 stoken（容易被 shell history 记录），默认从环境变量读取；
-//! - 默认输出为“脱敏模式”，需要显式 flag 才会输出完整 URL / 原始 summary / 明文预览。
+//! - 默认输出为“脱敏模式”，需要显式 flag 才会输出完整 URL / 原始 summary / 明文预览 / 字段标注。
+//!
+//! 离线模式（`--zip` / `--entry-file`）直接读取本地文件，不需要 stoken 与配置文件。
 
 use std::fmt::Write as _;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use phi_backend::AppConfig;
 use phi_backend::features::save::inspector::{
-    DEFAULT_STOKEN_ENV, InspectOptions, OutputFormat, inspect_official_save,
+    DEFAULT_STOKEN_ENV, InspectOptions, InspectReport, LocalSaveInput, OutputFormat,
+    inspect_local_save, inspect_official_save,
 };
 
 #[tokio::main]
@@ -37,27 +40,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    AppConfig::init_global()?;
-
-    let stoken_env = args.stoken_env.as_deref().unwrap_or(DEFAULT_STOKEN_ENV);
-    let stoken = std::env::var(stoken_env).map_err(|_| {
-        format!("未找到环境变量 `{stoken_env}`（建议 PowerShell: `$env:{stoken_env}='...'`）")
-    })?;
-
     let opts = InspectOptions {
         show_full_url: args.show_url,
         show_summary_raw: args.show_summary_raw,
         preview_plain_bytes: args.preview_bytes,
         max_download_bytes: args.max_download_bytes,
+        annotate: args.annotate,
     };
 
-    let report = inspect_official_save(
-        &stoken,
-        &phi_backend::AppConfig::global().taptap,
-        args.taptap_version.as_deref(),
-        opts,
-    )
-    .await?;
+    let report = if let Some(input) = args.local_input()? {
+        inspect_local_save(input.0, &input.1, &opts)
+    } else {
+        AppConfig::init_global()?;
+
+        let stoken_env = args.stoken_env.as_deref().unwrap_or(DEFAULT_STOKEN_ENV);
+        let stoken = std::env::var(stoken_env).map_err(|_| {
+            format!("未找到环境变量 `{stoken_env}`（建议 PowerShell: `$env:{stoken_env}='...'`）")
+        })?;
+
+        inspect_official_save(
+            &stoken,
+            &phi_backend::AppConfig::global().taptap,
+            args.taptap_version.as_deref(),
+            opts,
+        )
+        .await?
+    };
 
     let output = match args.format {
         OutputFormat::Json => serde_json::to_string_pretty(&report)?,
@@ -74,7 +82,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn render_text(report: &phi_backend::features::save::inspector::InspectReport) -> String {
+fn render_text(report: &InspectReport) -> String {
     let mut out = String::new();
 
     writeln!(out, "generated_at: {}", report.generated_at).expect("write inspect text");
@@ -200,6 +208,12 @@ fn render_text(report: &phi_backend::features::save::inspector::InspectReport) -
                 writeln!(out, "  plain_preview_hex={p}").expect("write inspect text");
             }
         }
+        if let Some(ann) = ent.annotation.as_ref() {
+            writeln!(out, "  annotated ({} bytes):", ann.total_len).expect("write inspect text");
+            for line in ann.render_hex().lines() {
+                writeln!(out, "    {line}").expect("write inspect text");
+            }
+        }
     }
 
     if !report.notes.is_empty() {
@@ -223,6 +237,11 @@ struct Args {
     preview_bytes: usize,
     max_download_bytes: usize,
     out_path: Option<PathBuf>,
+    annotate: bool,
+    zip_path: Option<PathBuf>,
+    entry_path: Option<PathBuf>,
+    entry_name: Option<String>,
+    summary_b64: Option<String>,
 }
 
 impl Args {
@@ -237,6 +256,11 @@ impl Args {
             preview_bytes: 0,
             max_download_bytes: 64 * 1024 * 1024,
             out_path: None,
+            annotate: false,
+            zip_path: None,
+            entry_path: None,
+            entry_name: None,
+            summary_b64: None,
         };
 
         let mut it = argv.into_iter();
@@ -273,11 +297,50 @@ impl Args {
                         args.out_path = Some(PathBuf::from(v));
                     }
                 }
+                "--annotate" => args.annotate = true,
+                "--zip" => args.zip_path = it.next().map(PathBuf::from),
+                "--entry-file" => args.entry_path = it.next().map(PathBuf::from),
+                "--entry-name" => args.entry_name = it.next(),
+                "--summary" => args.summary_b64 = it.next(),
                 _ => {}
             }
         }
         args
     }
+
+    /// 离线输入：`--zip` 优先；`--entry-file` 未指定 `--entry-name` 时取文件名（去扩展名）。
+    fn local_input(&self) -> Result<Option<(LocalSaveInput, String)>, Box<dyn std::error::Error>> {
+        if let Some(path) = self.zip_path.as_deref() {
+            let bytes = read_limited(path, self.max_download_bytes)?;
+            let input = LocalSaveInput::Zip {
+                bytes,
+                summary_b64: self.summary_b64.clone(),
+            };
+            return Ok(Some((input, path.display().to_string())));
+        }
+        if let Some(path) = self.entry_path.as_deref() {
+            let name = match self.entry_name.clone() {
+                Some(n) => n,
+                None => path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(str::to_string)
+                    .ok_or("无法从文件名推断 entry 名称，请指定 --entry-name")?,
+            };
+            let plain = read_limited(path, self.max_download_bytes)?;
+            let input = LocalSaveInput::DecryptedEntry { name, plain };
+            return Ok(Some((input, path.display().to_string())));
+        }
+        Ok(None)
+    }
+}
+
+fn read_limited(path: &Path, max_bytes: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let len = fs::metadata(path)?.len();
+    if usize::try_from(len).map_or(true, |size| size > max_bytes) {
+        return Err(format!("文件过大: {len} bytes 超过上限 {max_bytes}").into());
+    }
+    Ok(fs::read(path)?)
 }
 
 fn print_help() {
@@ -287,6 +350,10 @@ fn print_help() {
 用法（推荐：通过环境变量提供 stoken）：
   $env:PHI_STOKEN='...'; cargo run --bin save_inspect -- --taptap-version cn
 
+离线用法（无需 stoken）：
+  cargo run --bin save_inspect -- --zip save.zip --annotate
+  cargo run --bin save_inspect -- --entry-file gameProgress.bin --annotate
+
 常用参数：
   --format text|json            输出格式（默认 text）
   --taptap-version cn|global    选择 TapTap 版本（默认 cn）
@@ -294,8 +361,15 @@ fn print_help() {
   --show-url                    输出完整 download_url（可能包含敏感 query）
   --show-summary-raw            输出完整 summary_b64（可能包含个人信息）
   --preview-bytes N             输出解密后明文前 N 字节（hex，默认 0）
-  --max-download-bytes N        限制下载最大字节数（默认 67108864）
+  --max-download-bytes N        限制下载/本地文件最大字节数（默认 67108864）
   --out PATH                    写入到文件（否则 stdout）
+  --annotate                    输出逐字段标注的 hex 视图（包含明文字段值）
+
+离线输入：
+  --zip PATH                    本地存档 zip（按默认密钥解密）
+  --summary B64                 配合 --zip 诊断 summary（base64）
+  --entry-file PATH             已解密的单个 entry（含第 1 字节 prefix/version）
+  --entry-name NAME             entry 名称（gameRecord/gameKey/gameProgress/user/settings，默认取文件名）
 "
     );
 }
@@ -105,9 +105,27 @@ pub struct ResourcesConfig {
     /// info 文件远程基地址（如 `https://example.com/info`），留空则仅使用本地文件
     #[serde(default)]
     pub info_base_url: Option<String>,
+    /// 远端 info ETag 轮询间隔（秒），0 表示关闭；仅在配置了 `info_base_url` 时生效
+    #[serde(default)]
+    pub info_poll_interval_secs: u64,
     /// 曲绘签名URL配置（CDN防盗链），None 表示不启用
     #[serde(default)]
     pub illustration_signing: Option<IllustrationSigningConfig>,
+    /// 按游戏版本归档的历史定数表（当前定数表始终为 info_path 下的 difficulty.csv）
+    #[serde(default)]
+    pub constant_tables: Vec<ConstantTableConfig>,
+}
+
+/// 归档定数表配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstantTableConfig {
+    /// 版本标识（如 `3.9.0`），即请求中的 `constantsVersion`
+    pub version: String,
+    /// difficulty.csv 格式的文件路径；相对路径基于 `info_path`
+    pub path: String,
+    /// 对应的存档 summary `game_version` 取值，用于 `constantsVersion=auto` 自动匹配
+    #[serde(default)]
+    pub game_versions: Vec<u8>,
 }
 
 impl ResourcesConfig {
@@ -157,6 +175,9 @@ pub struct SessionConfig {
     pub revoke_ttl_secs: u64,
     #[serde(default = "SessionConfig::default_exchange_shared_secret")]
     pub exchange_shared_secret: String,
+    /// 刷新令牌有效期（秒）；每次轮换后从轮换时刻重新计算
+    #[serde(default = "SessionConfig::default_refresh_ttl_secs")]
+    pub refresh_ttl_secs: u64,
 }
 impl SessionConfig {
     fn default_enabled() -> bool {
@@ -180,6 +201,9 @@ impl SessionConfig {
     fn default_revoke_ttl_secs() -> u64 {
         864_000
     }
+    fn default_refresh_ttl_secs() -> u64 {
+        2_592_000
+    }
     fn default_exchange_shared_secret() -> String {
         std::env::var("APP_SESSION_EXCHANGE_SHARED_SECRET").unwrap_or_default()
     }
@@ -195,6 +219,7 @@ impl Default for SessionConfig {
             revoke_all_grace_secs: Self::default_revoke_all_grace_secs(),
             revoke_ttl_secs: Self::default_revoke_ttl_secs(),
             exchange_shared_secret: Self::default_exchange_shared_secret(),
+            refresh_ttl_secs: Self::default_refresh_ttl_secs(),
         }
     }
 }
@@ -552,20 +577,25 @@ impl TapTapMultiConfig {
     /// 根据请求参数解析对应的 TapTap 配置，未指定时回落到默认版本。
     #[must_use]
     pub fn resolve(&self, version: Option<&str>) -> &TapTapConfig {
+        match self.resolve_version(version) {
+            TapTapVersion::CN => &self.cn,
+            TapTapVersion::Global => &self.global,
+        }
+    }
+
+    /// 解析请求实际使用的 TapTap 版本（与 [`Self::resolve`] 口径一致）。
+    #[must_use]
+    pub fn resolve_version(&self, version: Option<&str>) -> TapTapVersion {
         let version = version.map(str::trim);
         if let Some(version) = version {
             if version.eq_ignore_ascii_case("global") {
-                return &self.global;
+                return TapTapVersion::Global;
             }
             if version.eq_ignore_ascii_case("cn") {
-                return &self.cn;
+                return TapTapVersion::CN;
             }
         }
-
-        match self.default_version {
-            TapTapVersion::CN => &self.cn,
-            TapTapVersion::Global => &self.global,
-        }
+        self.default_version.clone()
     }
 }
 
@@ -617,6 +647,15 @@ pub struct AppConfig {
     /// 排行榜配置（纯文字）
     #[serde(default)]
     pub leaderboard: LeaderboardConfig,
+    /// 实时推送（SSE）配置
+    #[serde(default)]
+    pub realtime: RealtimeConfig,
+    /// 二维码登录状态存储配置
+    #[serde(default)]
+    pub qrcode_login: QrCodeLoginConfig,
+    /// TapTap 玩家资料（昵称/头像）缓存配置
+    #[serde(default)]
+    pub player_profile: PlayerProfileConfig,
 }
 
 impl AppConfig {
@@ -756,7 +795,9 @@ impl Default for AppConfig {
                 illustration_repo_auto_sync: ResourcesConfig::default_illustration_repo_auto_sync(),
                 info_path: "./info".to_string(),
                 info_base_url: None,
+                info_poll_interval_secs: 0,
                 illustration_signing: None,
+                constant_tables: Vec::new(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -776,6 +817,9 @@ impl Default for AppConfig {
             save: SaveLimitsConfig::default(),
             shutdown: ShutdownConfig::default(),
             leaderboard: LeaderboardConfig::default(),
+            realtime: RealtimeConfig::default(),
+            qrcode_login: QrCodeLoginConfig::default(),
+            player_profile: PlayerProfileConfig::default(),
         }
     }
 }
@@ -924,6 +968,9 @@ pub struct ImageRenderConfig {
     /// 图片内容签名配置
     #[serde(default)]
     pub signing: ImageSigningConfig,
+    /// 登录二维码图片中心 logo（png/jpeg/webp/svg 文件路径；留空则不支持 logo=true）
+    #[serde(default)]
+    pub qrcode_logo_path: Option<String>,
 }
 
 impl ImageRenderConfig {
@@ -955,6 +1002,7 @@ impl Default for ImageRenderConfig {
             max_parallel: 0,
             max_user_scores: Self::default_max_user_scores(),
             signing: ImageSigningConfig::default(),
+            qrcode_logo_path: None,
         }
     }
 }
@@ -1125,7 +1173,7 @@ pub struct StatsConfig {
     /// 展示统计的时区（IANA 名称，如 Asia/Shanghai）
     #[serde(default = "StatsConfig::default_timezone")]
     pub timezone: String,
-    /// 每日聚合与归档时间（本地时区，如 "03:00"）
+    /// 每日聚合、归档与排名快照时间（本地时区，如 "03:00"）
     #[serde(default = "StatsConfig::default_daily_time")]
     pub daily_aggregate_time: String,
 }
@@ -1336,6 +1384,111 @@ impl Default for ShutdownConfig {
     }
 }
 
+/// 实时推送（SSE）配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeConfig {
+    /// 是否启用 SSE 推送端点（关闭后相关端点返回 403）
+    #[serde(default = "RealtimeConfig::default_enabled")]
+    pub enabled: bool,
+    /// 全部 SSE 端点共享的最大并发连接数
+    #[serde(default = "RealtimeConfig::default_max_connections")]
+    pub max_connections: usize,
+    /// 心跳（注释帧）间隔（秒），用于穿透代理的空闲超时
+    #[serde(default = "RealtimeConfig::default_heartbeat_secs")]
+    pub heartbeat_secs: u64,
+    /// 每条连接的事件缓冲上限；消费过慢的连接会收到 lagged 事件并需自行重新拉取
+    #[serde(default = "RealtimeConfig::default_channel_capacity")]
+    pub channel_capacity: usize,
+}
+
+impl RealtimeConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+    fn default_max_connections() -> usize {
+        1000
+    }
+    fn default_heartbeat_secs() -> u64 {
+        15
+    }
+    fn default_channel_capacity() -> usize {
+        256
+    }
+}
+
+impl Default for RealtimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            max_connections: Self::default_max_connections(),
+            heartbeat_secs: Self::default_heartbeat_secs(),
+            channel_capacity: Self::default_channel_capacity(),
+        }
+    }
+}
+
+/// 二维码登录状态存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QrCodeLoginConfig {
+    /// 存储后端：memory（进程内，默认）/ sqlite（复用统计库，多实例与重启后共享扫码进度）
+    ///
+    /// sqlite 需启用统计（stats.enabled）；统计库不可用时回退到 memory。
+    #[serde(default = "QrCodeLoginConfig::default_store")]
+    pub store: String,
+}
+
+impl QrCodeLoginConfig {
+    fn default_store() -> String {
+        "memory".to_string()
+    }
+}
+
+impl Default for QrCodeLoginConfig {
+    fn default() -> Self {
+        Self {
+            store: Self::default_store(),
+        }
+    }
+}
+
+/// TapTap 玩家资料缓存配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerProfileConfig {
+    /// 是否缓存资料（扫码登录时记录、按间隔刷新）并在 BN/单曲图片中显示头像
+    ///
+    /// 关闭后 /auth/me 返回 404，图片昵称仍实时经 users/me 获取。
+    #[serde(default = "PlayerProfileConfig::default_enabled")]
+    pub enabled: bool,
+    /// 资料刷新间隔（秒）：超过后在下一次携带 sessionToken 的请求中经 users/me 刷新
+    #[serde(default = "PlayerProfileConfig::default_refresh_secs")]
+    pub refresh_secs: u64,
+    /// 渲染时下载头像的最大字节数，超出则不显示头像
+    #[serde(default = "PlayerProfileConfig::default_avatar_max_bytes")]
+    pub avatar_max_bytes: usize,
+}
+
+impl PlayerProfileConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+    fn default_refresh_secs() -> u64 {
+        24 * 3600
+    }
+    fn default_avatar_max_bytes() -> usize {
+        512 * 1024
+    }
+}
+
+impl Default for PlayerProfileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            refresh_secs: Self::default_refresh_secs(),
+            avatar_max_bytes: Self::default_avatar_max_bytes(),
+        }
+    }
+}
+
 /// systemd 看门狗配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WatchdogConfig {
@@ -1415,6 +1568,15 @@ pub struct LeaderboardConfig {
         alias = "adminTokens"
     )]
     pub admin_tokens: Vec<String>,
+    /// 提交反作弊规则
+    #[serde(default)]
+    pub anti_cheat: AntiCheatConfig,
+    /// 好友群组（私有排行榜）
+    #[serde(default)]
+    pub groups: LeaderboardGroupConfig,
+    /// RKS 分布与百分位
+    #[serde(default)]
+    pub distribution: RksDistributionConfig,
 }
 
 impl LeaderboardConfig {
@@ -1454,6 +1616,204 @@ impl Default for LeaderboardConfig {
             default_show_best_top3: Self::default_show_b3(),
             default_show_ap_top3: Self::default_show_ap3(),
             admin_tokens: Self::default_admin_tokens(),
+            anti_cheat: AntiCheatConfig::default(),
+            groups: LeaderboardGroupConfig::default(),
+            distribution: RksDistributionConfig::default(),
+        }
+    }
+}
+
+/// RKS 分布与百分位配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RksDistributionConfig {
+    /// 未指定 bucket_width 时的默认桶宽
+    #[serde(default = "RksDistributionConfig::default_bucket_width")]
+    pub default_bucket_width: f64,
+    /// 分数快照的重算间隔（秒）
+    #[serde(default = "RksDistributionConfig::default_refresh_secs")]
+    pub refresh_secs: u64,
+}
+
+impl RksDistributionConfig {
+    fn default_bucket_width() -> f64 {
+        0.5
+    }
+    fn default_refresh_secs() -> u64 {
+        300
+    }
+}
+
+impl Default for RksDistributionConfig {
+    fn default() -> Self {
+        Self {
+            default_bucket_width: Self::default_bucket_width(),
+            refresh_secs: Self::default_refresh_secs(),
+        }
+    }
+}
+
+/// 好友群组（私有排行榜）配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardGroupConfig {
+    /// 单个群组的成员上限（含创建者）
+    #[serde(default = "LeaderboardGroupConfig::default_max_members")]
+    pub max_members: i64,
+    /// 每个用户最多创建的群组数
+    #[serde(default = "LeaderboardGroupConfig::default_max_owned")]
+    pub max_owned_per_user: i64,
+    /// 每个用户最多加入的群组数（含自己创建的）
+    #[serde(default = "LeaderboardGroupConfig::default_max_joined")]
+    pub max_joined_per_user: i64,
+}
+
+impl LeaderboardGroupConfig {
+    fn default_max_members() -> i64 {
+        100
+    }
+    fn default_max_owned() -> i64 {
+        5
+    }
+    fn default_max_joined() -> i64 {
+        20
+    }
+}
+
+impl Default for LeaderboardGroupConfig {
+    fn default() -> Self {
+        Self {
+            max_members: Self::default_max_members(),
+            max_owned_per_user: Self::default_max_owned(),
+            max_joined_per_user: Self::default_max_joined(),
+        }
+    }
+}
+
+/// 提交反作弊规则配置
+///
+/// 每条检测规则命中时累加其权重作为可疑度；权重为 0 表示关闭该规则。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AntiCheatConfig {
+    /// 可疑度达到该值时自动隐藏用户
+    #[serde(default = "AntiCheatConfig::default_hide_threshold")]
+    pub hide_threshold: f64,
+    /// 使用官方 sessionToken 提交时扣减的可疑度
+    #[serde(default = "AntiCheatConfig::default_session_token_discount")]
+    pub session_token_discount: f64,
+    /// 总 RKS 超过该值视为异常
+    #[serde(default = "AntiCheatConfig::default_high_rks_threshold")]
+    pub high_rks_threshold: f64,
+    #[serde(default = "AntiCheatConfig::default_high_rks_weight")]
+    pub high_rks_weight: f64,
+    /// 单次提交 RKS 涨幅超过该值记为大幅跳变
+    #[serde(default = "AntiCheatConfig::default_rks_jump_major")]
+    pub rks_jump_major: f64,
+    #[serde(default = "AntiCheatConfig::default_rks_jump_major_weight")]
+    pub rks_jump_major_weight: f64,
+    /// 单次提交 RKS 涨幅超过该值记为小幅跳变
+    #[serde(default = "AntiCheatConfig::default_rks_jump_minor")]
+    pub rks_jump_minor: f64,
+    #[serde(default = "AntiCheatConfig::default_rks_jump_minor_weight")]
+    pub rks_jump_minor_weight: f64,
+    /// 谱面分数与 ACC 组合不可能成立；默认低于 `hide_threshold`，单独命中不会隐藏用户
+    #[serde(default = "AntiCheatConfig::default_impossible_score_weight")]
+    pub impossible_score_weight: f64,
+    /// 校验分数与 ACC 组合时允许的分数误差
+    #[serde(default = "AntiCheatConfig::default_impossible_score_tolerance")]
+    pub impossible_score_tolerance: f64,
+    /// 存档 summary 中的 RKS 与服务端计算值的差超过该值视为不一致
+    #[serde(default = "AntiCheatConfig::default_ranking_score_tolerance")]
+    pub ranking_score_tolerance: f64,
+    #[serde(default = "AntiCheatConfig::default_ranking_score_weight")]
+    pub ranking_score_weight: f64,
+    /// 两次提交之间新增 AP 谱面数超过该值视为异常
+    #[serde(default = "AntiCheatConfig::default_ap_burst_max")]
+    pub ap_burst_max: usize,
+    #[serde(default = "AntiCheatConfig::default_ap_burst_weight")]
+    pub ap_burst_weight: f64,
+    /// 窗口内同一 IP 提交的账号数（含本账号）超过该值视为异常
+    #[serde(default = "AntiCheatConfig::default_shared_ip_max_accounts")]
+    pub shared_ip_max_accounts: i64,
+    /// 同 IP 多账号统计窗口（小时）
+    #[serde(default = "AntiCheatConfig::default_shared_ip_window_hours")]
+    pub shared_ip_window_hours: i64,
+    #[serde(default = "AntiCheatConfig::default_shared_ip_weight")]
+    pub shared_ip_weight: f64,
+}
+
+impl AntiCheatConfig {
+    fn default_hide_threshold() -> f64 {
+        1.0
+    }
+    fn default_session_token_discount() -> f64 {
+        0.2
+    }
+    fn default_high_rks_threshold() -> f64 {
+        20.0
+    }
+    fn default_high_rks_weight() -> f64 {
+        0.5
+    }
+    fn default_rks_jump_major() -> f64 {
+        1.0
+    }
+    fn default_rks_jump_major_weight() -> f64 {
+        0.8
+    }
+    fn default_rks_jump_minor() -> f64 {
+        0.5
+    }
+    fn default_rks_jump_minor_weight() -> f64 {
+        0.3
+    }
+    fn default_impossible_score_weight() -> f64 {
+        0.8
+    }
+    fn default_impossible_score_tolerance() -> f64 {
+        10.0
+    }
+    fn default_ranking_score_tolerance() -> f64 {
+        0.05
+    }
+    fn default_ranking_score_weight() -> f64 {
+        0.5
+    }
+    fn default_ap_burst_max() -> usize {
+        15
+    }
+    fn default_ap_burst_weight() -> f64 {
+        0.5
+    }
+    fn default_shared_ip_max_accounts() -> i64 {
+        3
+    }
+    fn default_shared_ip_window_hours() -> i64 {
+        24
+    }
+    fn default_shared_ip_weight() -> f64 {
+        0.3
+    }
+}
+
+impl Default for AntiCheatConfig {
+    fn default() -> Self {
+        Self {
+            hide_threshold: Self::default_hide_threshold(),
+            session_token_discount: Self::default_session_token_discount(),
+            high_rks_threshold: Self::default_high_rks_threshold(),
+            high_rks_weight: Self::default_high_rks_weight(),
+            rks_jump_major: Self::default_rks_jump_major(),
+            rks_jump_major_weight: Self::default_rks_jump_major_weight(),
+            rks_jump_minor: Self::default_rks_jump_minor(),
+            rks_jump_minor_weight: Self::default_rks_jump_minor_weight(),
+            impossible_score_weight: Self::default_impossible_score_weight(),
+            impossible_score_tolerance: Self::default_impossible_score_tolerance(),
+            ranking_score_tolerance: Self::default_ranking_score_tolerance(),
+            ranking_score_weight: Self::default_ranking_score_weight(),
+            ap_burst_max: Self::default_ap_burst_max(),
+            ap_burst_weight: Self::default_ap_burst_weight(),
+            shared_ip_max_accounts: Self::default_shared_ip_max_accounts(),
+            shared_ip_window_hours: Self::default_shared_ip_window_hours(),
+            shared_ip_weight: Self::default_shared_ip_weight(),
         }
     }
 }
@@ -0,0 +1,341 @@
+//! 按游戏版本归档的定数表
+//!
+//! 线上定数表（[`crate::game_data::GameData::chart_constants`]）之外，可通过
+//! `resources.constant_tables` 额外加载若干历史版本的 difficulty.csv。RKS 相关接口可按
+//! `constantsVersion` 选择其中一张计算；`auto` 时按存档 summary 的 `game_version` 匹配。
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::config::ConstantTableConfig;
+use crate::error::AppError;
+use crate::game_data::GameData;
+use crate::startup::chart_loader::{ChartConstants, ChartConstantsMap, load_chart_constants};
+
+/// 选择线上定数表的版本标识
+pub const CURRENT_VERSION: &str = "current";
+/// 按存档 summary 的 `game_version` 自动选择
+pub const AUTO_VERSION: &str = "auto";
+
+/// 一张归档定数表
+pub struct ConstantTable {
+    pub version: String,
+    pub game_versions: Vec<u8>,
+    pub constants: Arc<ChartConstantsMap>,
+}
+
+/// 归档定数表集合（按版本标识排序）
+#[derive(Default)]
+pub struct ConstantTableSet {
+    tables: BTreeMap<String, Arc<ConstantTable>>,
+}
+
+/// 选定的定数表
+#[derive(Clone)]
+pub struct SelectedConstants {
+    /// 版本标识；线上定数表为 `current`
+    pub version: String,
+    pub constants: Arc<ChartConstantsMap>,
+}
+
+/// 定数表概要（列表接口用）
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstantTableInfo {
+    /// 版本标识
+    pub version: String,
+    /// 对应的存档 summary `game_version` 取值
+    pub game_versions: Vec<u8>,
+    /// 含定数的曲目数
+    pub songs: usize,
+}
+
+impl ConstantTableSet {
+    pub fn from_tables(tables: impl IntoIterator<Item = ConstantTable>) -> Self {
+        Self {
+            tables: tables
+                .into_iter()
+                .map(|t| (t.version.clone(), Arc::new(t)))
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, version: &str) -> Option<&Arc<ConstantTable>> {
+        self.tables.get(version)
+    }
+
+    /// 按存档 summary 的 `game_version` 查找；多张表声明同一取值时取版本号最大者
+    /// （按数值逐段比较，`3.10` 大于 `3.9`）。
+    #[must_use]
+    pub fn for_game_version(&self, game_version: u8) -> Option<&Arc<ConstantTable>> {
+        self.tables
+            .values()
+            .filter(|t| t.game_versions.contains(&game_version))
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+
+    #[must_use]
+    pub fn infos(&self) -> Vec<ConstantTableInfo> {
+        self.tables
+            .values()
+            .map(|t| ConstantTableInfo {
+                version: t.version.clone(),
+                game_versions: t.game_versions.clone(),
+                songs: t.constants.len(),
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// 解析版本选择：`None`/`current` 为线上表；`auto` 按 `game_version` 匹配，未匹配时回落线上表。
+    pub fn select(
+        &self,
+        game: &GameData,
+        version: Option<&str>,
+        game_version: Option<u8>,
+    ) -> Result<SelectedConstants, AppError> {
+        let current = || SelectedConstants {
+            version: CURRENT_VERSION.to_string(),
+            constants: game.chart_constants.clone(),
+        };
+        let from_table = |t: &Arc<ConstantTable>| SelectedConstants {
+            version: t.version.clone(),
+            constants: t.constants.clone(),
+        };
+        match version.map(str::trim).filter(|v| !v.is_empty()) {
+            None | Some(CURRENT_VERSION) => Ok(current()),
+            Some(AUTO_VERSION) => Ok(game_version
+                .and_then(|gv| self.for_game_version(gv))
+                .map_or_else(current, from_table)),
+            Some(v) => self.get(v).map(from_table).ok_or_else(|| {
+                let known: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+                AppError::Validation(format!(
+                    "constantsVersion 无效：{v}（可选 current/auto/{}）",
+                    known.join("/")
+                ))
+            }),
+        }
+    }
+}
+
+/// 比较两个版本标识：按 `.` 分段，两段均为数字时按数值比较，否则按字符串比较；
+/// 前缀相同时段数多者更大（`3.10.1` > `3.10`）。
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    _ => x.cmp(y),
+                };
+                if ord.is_ne() {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+/// 按配置加载归档定数表；单张表加载失败时记录警告并跳过。
+pub fn load_constant_tables(info_dir: &Path, configs: &[ConstantTableConfig]) -> ConstantTableSet {
+    let mut tables = Vec::with_capacity(configs.len());
+    for cfg in configs {
+        let version = cfg.version.trim();
+        if version.is_empty() || version == CURRENT_VERSION || version == AUTO_VERSION {
+            tracing::warn!("归档定数表版本标识无效，已跳过: {:?}", cfg.version);
+            continue;
+        }
+        let path = Path::new(&cfg.path);
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            info_dir.join(path)
+        };
+        match load_chart_constants(&path) {
+            Ok(constants) if !constants.is_empty() => tables.push(ConstantTable {
+                version: version.to_string(),
+                game_versions: cfg.game_versions.clone(),
+                constants: Arc::new(constants),
+            }),
+            Ok(_) => tracing::warn!("归档定数表 {version} 为空，已跳过"),
+            Err(e) => tracing::warn!("归档定数表 {version} 加载失败，已跳过: {e}"),
+        }
+    }
+    let set = ConstantTableSet::from_tables(tables);
+    if !set.is_empty() {
+        tracing::info!("已加载 {} 张归档定数表", set.len());
+    }
+    set
+}
+
+/// 单个谱面的定数变化；`from`/`to` 为 None 表示该版本中不存在该谱面
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantChange {
+    pub song_id: String,
+    /// 难度（EZ/HD/IN/AT）
+    pub difficulty: String,
+    pub from: Option<f32>,
+    pub to: Option<f32>,
+}
+
+fn levels(c: Option<&ChartConstants>) -> [Option<f32>; 4] {
+    c.map_or([None; 4], |c| [c.ez, c.hd, c.in_level, c.at])
+}
+
+/// 比较两张定数表，返回所有定数不同（含新增/移除）的谱面，按曲目 ID 与难度排序。
+#[must_use]
+pub fn diff_constants(from: &ChartConstantsMap, to: &ChartConstantsMap) -> Vec<ConstantChange> {
+    const DIFFS: [&str; 4] = ["EZ", "HD", "IN", "AT"];
+    let mut song_ids: Vec<&String> = from.keys().chain(to.keys()).collect();
+    song_ids.sort();
+    song_ids.dedup();
+
+    let mut out = Vec::new();
+    for song_id in song_ids {
+        let before = levels(from.get(song_id));
+        let after = levels(to.get(song_id));
+        for (i, diff) in DIFFS.iter().enumerate() {
+            if before[i] != after[i] {
+                out.push(ConstantChange {
+                    song_id: song_id.clone(),
+                    difficulty: (*diff).to_string(),
+                    from: before[i],
+                    to: after[i],
+                });
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::song::models::SongCatalog;
+
+    fn consts(entries: &[(&str, Option<f32>, Option<f32>)]) -> ChartConstantsMap {
+        entries
+            .iter()
+            .map(|(id, hd, at)| {
+                (
+                    (*id).to_string(),
+                    ChartConstants {
+                        ez: Some(1.0),
+                        hd: *hd,
+                        in_level: Some(10.0),
+                        at: *at,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn table(version: &str, game_versions: &[u8], c: ChartConstantsMap) -> ConstantTable {
+        ConstantTable {
+            version: version.to_string(),
+            game_versions: game_versions.to_vec(),
+            constants: Arc::new(c),
+        }
+    }
+
+    #[test]
+    fn diff_reports_changed_added_and_removed_charts() {
+        let from = consts(&[("a", Some(5.0), None), ("b", Some(6.0), Some(15.0))]);
+        let to = consts(&[("a", Some(5.5), Some(14.0)), ("c", Some(3.0), None)]);
+        let diff = diff_constants(&from, &to);
+        let keys: Vec<(&str, &str)> = diff
+            .iter()
+            .map(|c| (c.song_id.as_str(), c.difficulty.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("a", "HD"),
+                ("a", "AT"),
+                ("b", "EZ"),
+                ("b", "HD"),
+                ("b", "IN"),
+                ("b", "AT"),
+                ("c", "EZ"),
+                ("c", "HD"),
+                ("c", "IN"),
+            ]
+        );
+        assert_eq!(diff[0].from, Some(5.0));
+        assert_eq!(diff[0].to, Some(5.5));
+        assert!(diff_constants(&from, &from).is_empty());
+    }
+
+    #[test]
+    fn select_resolves_current_named_and_auto() {
+        let game = GameData {
+            chart_constants: Arc::new(consts(&[("a", Some(5.0), None)])),
+            song_catalog: Arc::new(SongCatalog::default()),
+            version: 1,
+            source: crate::game_data::GameDataSource::Local,
+            loaded_at: String::new(),
+        };
+        let set = ConstantTableSet::from_tables([
+            table("3.9.0", &[108], consts(&[("a", Some(4.0), None)])),
+            table("3.10.0", &[110], consts(&[("a", Some(4.5), None)])),
+        ]);
+
+        assert_eq!(
+            set.select(&game, None, Some(108)).unwrap().version,
+            "current"
+        );
+        assert_eq!(
+            set.select(&game, Some("3.9.0"), None).unwrap().version,
+            "3.9.0"
+        );
+        assert_eq!(
+            set.select(&game, Some("auto"), Some(110)).unwrap().version,
+            "3.10.0"
+        );
+        assert_eq!(
+            set.select(&game, Some("auto"), Some(1)).unwrap().version,
+            "current"
+        );
+        assert_eq!(
+            set.select(&game, Some("auto"), None).unwrap().version,
+            "current"
+        );
+        assert!(set.select(&game, Some("9.9.9"), None).is_err());
+    }
+
+    #[test]
+    fn auto_prefers_numerically_newest_version_for_shared_game_version() {
+        let set = ConstantTableSet::from_tables([
+            table("3.9", &[110], consts(&[("a", Some(4.0), None)])),
+            table("3.10", &[110], consts(&[("a", Some(4.5), None)])),
+            table("3.2", &[110], consts(&[("a", Some(3.5), None)])),
+        ]);
+        assert_eq!(set.for_game_version(110).unwrap().version, "3.10");
+    }
+
+    #[test]
+    fn compare_versions_orders_segments_numerically() {
+        assert_eq!(compare_versions("3.10", "3.9"), Ordering::Greater);
+        assert_eq!(compare_versions("3.10.1", "3.10"), Ordering::Greater);
+        assert_eq!(compare_versions("3.9.0", "3.9.0"), Ordering::Equal);
+    }
+}
@@ -1,2 +1,4 @@
+pub(crate) use crate::features::auth::handler::qrcode::{QrCodeLoginTicket, issue_qrcode_login};
+pub(crate) use crate::features::auth::profile::resolve_player_profile;
 pub use crate::features::save::client::ExternalApiCredentials;
 pub use crate::features::save::models::UnifiedSaveRequest;
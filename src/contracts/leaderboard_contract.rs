@@ -1 +1,8 @@
-pub use crate::features::leaderboard::models::{ChartTextItem, RksCompositionText};
+pub(crate) use crate::features::leaderboard::anti_cheat::{
+    ANTI_CHEAT_ACTOR, SubmissionSignals, evaluate_submission, rks_jump,
+};
+pub(crate) use crate::features::leaderboard::handler::admin::require_admin;
+pub(crate) use crate::features::leaderboard::handler::load_leaderboard_window;
+pub use crate::features::leaderboard::models::{
+    ChartTextItem, LeaderboardTopItem, RksCompositionText,
+};
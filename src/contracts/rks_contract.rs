@@ -1 +1,3 @@
+pub use crate::features::rks::chart_snapshot;
 pub use crate::features::rks::engine;
+pub use crate::features::rks::rules;
@@ -1,4 +1,10 @@
-pub use crate::features::save::models::{Difficulty, DifficultyRecord};
+pub(crate) use crate::features::save::handler::{
+    build_textual_details_from_rks, invalidate_save_cache, validate_and_create_source,
+};
+pub use crate::features::save::models::{
+    BinarySaveBlob, Difficulty, DifficultyRecord, SaveUploadForm,
+};
 pub use crate::features::save::provider::{
     ParsedSave, SaveMeta, SaveSource, fetch_save_meta, get_decrypted_save_from_meta,
+    get_decrypted_save_from_upload,
 };
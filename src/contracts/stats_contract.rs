@@ -1,3 +1,10 @@
 pub use crate::features::stats::StatsHandle;
+pub use crate::features::stats::middleware::client_ip_hash;
 pub use crate::features::stats::models::EventInsert;
-pub use crate::features::stats::storage::{StatsStorage, SubmissionRecord, UserAliasDefaults};
+pub use crate::features::stats::storage::{
+    ChartLeaderboardRow, ChartScoreHistoryEntry, ChartScoreHistoryPage, ChartScoreSnapshot,
+    LeaderboardChartDetails, LeaderboardGroup, LeaderboardRksChange, ModerationFlag,
+    NewModerationFlag, NewSessionFamily, QrCodeLoginRow, RecomputedLeaderboardRow, RefreshRotation,
+    RefreshTokenLookup, RksHistoryCursor, RksHistoryEntry, ServerRegion, SessionFamilyRow,
+    StatsStorage, StoredSaveSnapshot, SubmissionRecord, TapTapProfileRow, UserAliasDefaults,
+};
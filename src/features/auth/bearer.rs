@@ -23,6 +23,9 @@ pub struct SessionClaims {
     pub aud: String,
     pub iat: i64,
     pub exp: i64,
+    /// 刷新会话族 ID；仅经刷新令牌流程签发的令牌携带
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -187,7 +190,12 @@ pub(crate) async fn validate_bearer_not_revoked(
     };
     let now_rfc3339 = chrono::Utc::now().to_rfc3339();
     let (blacklisted, logout_before) = storage
-        .get_session_revoke_state(&claims.jti, &claims.sub, &now_rfc3339)
+        .get_session_revoke_state(
+            &claims.jti,
+            claims.sid.as_deref(),
+            &claims.sub,
+            &now_rfc3339,
+        )
         .await?;
     if blacklisted {
         return Err(AppError::Auth("会话令牌已失效".into()));
@@ -411,6 +419,44 @@ pub fn build_embedded_auth_claim(
     Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(env_json))
 }
 
+/// 加密保存到刷新会话族的登录凭证；AAD 绑定族 ID 与用户，避免跨族挪用
+pub(crate) fn seal_session_family_auth(
+    auth: &UnifiedSaveRequest,
+    family_id: &str,
+    sub: &str,
+) -> Result<String, AppError> {
+    let auth_json = serde_json::to_string(auth)
+        .map_err(|e| AppError::Internal(format!("序列化登录凭证失败: {e}")))?;
+    seal_auth_payload_json(&auth_json, &format!("family:{family_id}"), sub)
+}
+
+pub(crate) fn open_session_family_auth(
+    sealed: &str,
+    family_id: &str,
+    sub: &str,
+) -> Result<UnifiedSaveRequest, AppError> {
+    let plain = open_auth_payload_json(sealed, &format!("family:{family_id}"), sub)?;
+    serde_json::from_str(&plain).map_err(|_| AppError::Auth("会话凭证内容无效".into()))
+}
+
+/// 加密保存到二维码登录记录的会话令牌；AAD 绑定二维码 ID，避免记录间挪用
+pub(crate) fn seal_qrcode_session_token(
+    session_token: &str,
+    qr_id: &str,
+) -> Result<String, AppError> {
+    seal_auth_payload_json(session_token, &format!("qrcode:{qr_id}"), "qrcode")
+}
+
+pub(crate) fn open_qrcode_session_token(sealed: &str, qr_id: &str) -> Result<String, AppError> {
+    open_auth_payload_json(sealed, &format!("qrcode:{qr_id}"), "qrcode")
+}
+
+/// 是否配置了会话凭证加密密钥（落库前需要加密的功能据此决定能否启用）
+#[must_use]
+pub fn session_auth_sealing_configured() -> bool {
+    session_auth_crypto_secret().is_ok()
+}
+
 pub fn decode_embedded_auth(token: &str) -> Result<UnifiedSaveRequest, AppError> {
     let cfg = ensure_session_config()?;
     let claims = decode_access_token(token, cfg, true)?;
@@ -541,6 +587,7 @@ mod tests {
                 aud: "phi-clients".into(),
                 iat: 1,
                 exp: i64::MAX,
+                sid: None,
             },
         });
 
@@ -566,6 +613,7 @@ mod tests {
                 aud: "phi-clients".into(),
                 iat: 1,
                 exp: i64::MAX,
+                sid: None,
             },
         });
 
@@ -591,6 +639,7 @@ mod tests {
                 aud: "phi-clients".into(),
                 iat: 1,
                 exp: i64::MAX,
+                sid: None,
             },
         });
 
@@ -8,10 +8,20 @@ use serde_json::Value;
 
 use crate::error::AppError;
 
-use super::models::{Account, DeviceCodeResponse, SessionData, Token, Wrap};
+use super::models::{
+    Account, DeviceCodeResponse, SessionData, TapTapLogin, TapTapProfile, Token, Wrap,
+};
 
 use crate::config::TapTapMultiConfig;
 
+/// 单个上游请求的超时
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// `poll_for_token` 依次发起的上游请求数（token、账号信息、LeanCloud /users）
+const POLL_FOR_TOKEN_REQUESTS: u64 = 3;
+/// 一次 `poll_for_token` 的最长耗时（各请求超时之和）
+pub const POLL_FOR_TOKEN_BUDGET: Duration =
+    Duration::from_secs(REQUEST_TIMEOUT_SECS * POLL_FOR_TOKEN_REQUESTS);
+
 #[derive(Clone)]
 pub struct TapTapClient {
     pub client: reqwest::Client,
@@ -87,7 +97,7 @@ impl TapTapClient {
     pub fn new(config: &TapTapMultiConfig) -> Result<Self, AppError> {
         let client = reqwest::Client::builder()
             .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(30))
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
             .http1_title_case_headers()
             .user_agent("TapTapUnitySDK/1.0 UnityPlayer/2021.3.40f1c1")
             .build()
@@ -206,7 +216,7 @@ impl TapTapClient {
         device_code: &str,
         device_id: &str,
         version: Option<&str>,
-    ) -> Result<SessionData, AppError> {
+    ) -> Result<TapTapLogin, AppError> {
         // 交换 token
         let info = serde_json::json!({ "device_id": device_id }).to_string();
         let config = self.config.resolve(version);
@@ -292,6 +302,7 @@ impl TapTapClient {
         let account = account_wrap.data;
 
         // 通过 LeanCloud 创建/登录用户，返回 SessionToken
+        // 与官方 SDK 一致附带 name/avatar，之后可经 users/me 刷新资料
         let auth_data = serde_json::json!({
             "authData": {
                 "taptap": {
@@ -302,6 +313,8 @@ impl TapTapClient {
                     "mac_algorithm": "hmac-sha-1",
                     "openid": account.openid,
                     "unionid": account.unionid,
+                    "name": account.name,
+                    "avatar": account.avatar,
                 }
             }
         });
@@ -345,11 +358,46 @@ impl TapTapClient {
             .await
             .map_err(|e| map_reqwest_error("解析 LeanCloud 响应失败", &e))?;
 
-        Ok(SessionData {
-            session_token: user.session_token,
+        Ok(TapTapLogin {
+            session: SessionData {
+                session_token: user.session_token,
+            },
+            profile: TapTapProfile::new(account.name, account.avatar),
         })
     }
 
+    /// 通过 LeanCloud users/me 读取玩家资料：昵称优先取游戏内昵称，头像取 TapTap 绑定信息
+    pub async fn fetch_profile(
+        &self,
+        session_token: &str,
+        version: Option<&str>,
+    ) -> Result<TapTapProfile, AppError> {
+        let config = self.config.resolve(version);
+        let resp = self
+            .client
+            .get(format!("{}/users/me", config.leancloud_base_url))
+            .headers(self.phi_headers.clone())
+            .header("X-LC-Id", &config.leancloud_app_id)
+            .header("X-LC-Key", &config.leancloud_app_key)
+            .header("X-LC-Session", session_token)
+            .send()
+            .await
+            .map_err(|e| map_reqwest_error("获取玩家资料失败", &e))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            tracing::warn!("LeanCloud users/me 请求失败：HTTP {status}");
+            return Err(AppError::Network(format!(
+                "LeanCloud users/me 请求失败: HTTP {status}"
+            )));
+        }
+        let body: Value = resp
+            .json()
+            .await
+            .map_err(|e| map_reqwest_error("解析 LeanCloud users/me 响应失败", &e))?;
+        Ok(parse_users_me_profile(&body))
+    }
+
     fn build_mac_authorization(
         token: &Token,
         request_url: &reqwest::Url,
@@ -384,6 +432,16 @@ impl TapTapClient {
     }
 }
 
+fn parse_users_me_profile(body: &Value) -> TapTapProfile {
+    let taptap = body.get("authData").and_then(|v| v.get("taptap"));
+    let field = |v: Option<&Value>| v.and_then(Value::as_str).map(str::to_string);
+    let game_nickname = field(body.get("nickname")).filter(|s| !s.trim().is_empty());
+    TapTapProfile::new(
+        game_nickname.or_else(|| field(taptap.and_then(|t| t.get("name")))),
+        field(taptap.and_then(|t| t.get("avatar"))),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::TapTapClient;
@@ -547,4 +605,26 @@ mod tests {
             "expected AppError::Auth, got: {err:?}"
         );
     }
+
+    #[test]
+    fn users_me_profile_prefers_game_nickname_and_reads_taptap_avatar() {
+        let body = serde_json::json!({
+            "nickname": "Alice",
+            "authData": { "taptap": { "name": "tap-alice", "avatar": "https://img.example/a.png" } }
+        });
+        let profile = super::parse_users_me_profile(&body);
+        assert_eq!(profile.nickname.as_deref(), Some("Alice"));
+        assert_eq!(
+            profile.avatar_url.as_deref(),
+            Some("https://img.example/a.png")
+        );
+
+        let body = serde_json::json!({
+            "nickname": "  ",
+            "authData": { "taptap": { "name": "tap-alice" } }
+        });
+        let profile = super::parse_users_me_profile(&body);
+        assert_eq!(profile.nickname.as_deref(), Some("tap-alice"));
+        assert!(profile.avatar_url.is_none());
+    }
 }
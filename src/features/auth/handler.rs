@@ -5,17 +5,26 @@ use axum::{
 
 use crate::state::AppState;
 
+pub(crate) mod me;
 pub(crate) mod qrcode;
+pub(crate) mod qrcode_stream;
 pub(crate) mod session;
+pub(crate) mod sessions;
 pub(crate) mod user_id;
 
+pub use self::me::{MeResponse, get_auth_me};
 pub use self::qrcode::{
     QrCodeCreateResponse, QrCodeStatusResponse, QrCodeStatusValue, get_qrcode_status,
 };
 pub(crate) use self::qrcode::{QrCodeQuery, post_qrcode};
+pub use self::qrcode_stream::get_qrcode_events;
 pub use self::session::{
     SessionExchangeRequest, SessionExchangeResponse, SessionLogoutRequest, SessionLogoutResponse,
-    SessionLogoutScope, post_session_exchange, post_session_logout, post_session_refresh,
+    SessionLogoutScope, SessionRefreshRequest, post_session_exchange, post_session_logout,
+    post_session_refresh,
+};
+pub use self::sessions::{
+    SessionInfo, SessionListResponse, SessionRevokeResponse, get_sessions, post_revoke_session,
 };
 pub use self::user_id::{UserIdResponse, post_user_id};
 
@@ -23,8 +32,12 @@ pub fn create_auth_router() -> Router<AppState> {
     Router::<AppState>::new()
         .route("/qrcode", post(post_qrcode))
         .route("/qrcode/:qr_id/status", get(get_qrcode_status))
+        .route("/qrcode/:qr_id/events", get(get_qrcode_events))
         .route("/user-id", post(post_user_id))
+        .route("/me", get(get_auth_me))
         .route("/session/exchange", post(post_session_exchange))
         .route("/session/refresh", post(post_session_refresh))
         .route("/session/logout", post(post_session_logout))
+        .route("/sessions", get(get_sessions))
+        .route("/sessions/:session_id/revoke", post(post_revoke_session))
 }
@@ -0,0 +1,94 @@
+use axum::{Extension, Json, extract::State};
+use serde::Serialize;
+
+use crate::auth_contract::UnifiedSaveRequest;
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::state::AppState;
+
+use crate::features::auth::bearer::{BearerAuthState, merge_auth_from_bearer_if_missing};
+use crate::features::auth::profile::{is_profile_fresh, require_player_profile};
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MeResponse {
+    /// 去敏用户 ID（与 /auth/user-id、排行榜使用的 user_hash 一致）
+    #[schema(example = "ab12cd34ef56ab12cd34ef56ab12cd34")]
+    pub user_id: String,
+    /// TapTap/游戏内昵称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    /// TapTap 头像 URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// 资料最近一次从上游获取的时间（RFC3339）
+    pub fetched_at: String,
+    /// 资料已超过刷新间隔且本次未能刷新（返回的是旧值）
+    pub stale: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    summary = "获取当前玩家资料",
+    description = "返回 Bearer 会话用户的 TapTap 基础资料（昵称/头像）。资料在扫码登录时记录，超过 player_profile.refresh_secs 后本次请求会经 LeanCloud users/me 刷新；刷新失败时返回旧值并标记 stale。",
+    params(("Authorization" = String, Header, description = "Bearer access token")),
+    responses(
+        (status = 200, description = "玩家资料", body = MeResponse),
+        (
+            status = 401,
+            description = "缺少或无效的 Bearer 令牌",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "用户已被封禁",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "暂无该用户的资料（非扫码登录且无法经 users/me 获取，或功能未启用）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Auth"
+)]
+pub async fn get_auth_me(
+    State(state): State<AppState>,
+    bearer: Option<Extension<BearerAuthState>>,
+) -> Result<Json<MeResponse>, AppError> {
+    let bearer = bearer.map(|Extension(b)| b).unwrap_or_default();
+    let user_hash = match &bearer {
+        BearerAuthState::Valid(ctx) => ctx.claims.sub.clone(),
+        BearerAuthState::Invalid(msg) => return Err(AppError::Auth(msg.clone())),
+        BearerAuthState::Absent => return Err(AppError::Auth("缺少 Bearer 会话令牌".into())),
+    };
+    if let Some(storage) = state.stats_storage.as_ref() {
+        storage.ensure_user_not_banned(&user_hash).await?;
+    }
+
+    let mut auth = UnifiedSaveRequest::default();
+    merge_auth_from_bearer_if_missing(state.stats_storage.as_ref(), &bearer, &mut auth).await?;
+    let row = require_player_profile(
+        &state,
+        &user_hash,
+        auth.session_token.as_deref(),
+        auth.taptap_version.as_deref(),
+    )
+    .await?;
+
+    let refresh_secs = AppConfig::global().player_profile.refresh_secs;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    Ok(Json(MeResponse {
+        user_id: user_hash,
+        stale: !is_profile_fresh(&row, now_ms, refresh_secs),
+        fetched_at: chrono::DateTime::<chrono::Utc>::from_timestamp_millis(row.fetched_at_ms)
+            .unwrap_or_default()
+            .to_rfc3339(),
+        nickname: row.nickname,
+        avatar_url: row.avatar_url,
+    }))
+}
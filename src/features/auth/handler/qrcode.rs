@@ -13,6 +13,7 @@ use uuid::Uuid;
 use crate::error::AppError;
 use crate::state::AppState;
 
+use crate::features::auth::profile::record_login_profile;
 use crate::features::auth::qrcode_service::QrCodeStatus;
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -27,7 +28,7 @@ pub struct QrCodeCreateResponse {
     pub qrcode_base64: String,
 }
 
-#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct QrCodeStatusResponse {
     /// 褰撳墠鐘舵€侊細Pending/Scanned/Confirmed/Error/Expired
@@ -133,11 +134,78 @@ pub(crate) async fn post_qrcode(
 ) -> Result<Response, AppError> {
     let t_total = Instant::now();
 
+    let QrCodeLoginTicket {
+        qr_id,
+        verification_url: verification_url_for_scan,
+    } = issue_qrcode_login(&state, params.taptap_version.as_deref()).await?;
+
+    let t_qrcode = Instant::now();
+    let code = match QrCode::new(&verification_url_for_scan) {
+        Ok(code) => code,
+        Err(e) => {
+            tracing::info!(
+                target: "phi_backend::auth::performance",
+                route = "/auth/qrcode",
+                phase = "generate_qrcode_svg",
+                status = "failed",
+                dur_ms = t_qrcode.elapsed().as_millis(),
+                "auth performance"
+            );
+            return Err(AppError::Internal(format!("生成二维码失败: {e}")));
+        }
+    };
+    let image = code
+        .render()
+        .min_dimensions(256, 256)
+        .dark_color(svg::Color("#000"))
+        .light_color(svg::Color("#fff"))
+        .build();
+    tracing::info!(
+        target: "phi_backend::auth::performance",
+        route = "/auth/qrcode",
+        phase = "generate_qrcode_svg",
+        status = "ok",
+        dur_ms = t_qrcode.elapsed().as_millis(),
+        "auth performance"
+    );
+    let qrcode_base64 = format!(
+        "data:image/svg+xml;base64,{}",
+        base64::prelude::BASE64_STANDARD.encode(image)
+    );
+
+    let resp = QrCodeCreateResponse {
+        qr_id,
+        verification_url: verification_url_for_scan,
+        qrcode_base64,
+    };
+    tracing::info!(
+        target: "phi_backend::auth::performance",
+        route = "/auth/qrcode",
+        phase = "total",
+        status = "ok",
+        dur_ms = t_total.elapsed().as_millis(),
+        "auth performance"
+    );
+    Ok(json_no_store(StatusCode::OK, resp))
+}
+
+/// 已登记到二维码状态存储的一次扫码登录
+pub(crate) struct QrCodeLoginTicket {
+    pub(crate) qr_id: String,
+    /// 扫码后跳转的 TapTap 授权地址（二维码内容）
+    pub(crate) verification_url: String,
+}
+
+/// 向 TapTap 申请设备码并登记为 Pending；`/auth/qrcode` 与二维码图片端点共用。
+pub(crate) async fn issue_qrcode_login(
+    state: &AppState,
+    taptap_version: Option<&str>,
+) -> Result<QrCodeLoginTicket, AppError> {
     let device_id = Uuid::new_v4().to_string();
     let qr_id = Uuid::new_v4().to_string();
 
     let t_version = Instant::now();
-    let version = match normalize_taptap_version(params.taptap_version.as_deref()) {
+    let version = match normalize_taptap_version(taptap_version) {
         Ok(v) => {
             tracing::info!(
                 target: "phi_backend::auth::performance",
@@ -212,40 +280,6 @@ pub(crate) async fn post_qrcode(
         verification_url.clone()
     };
 
-    let t_qrcode = Instant::now();
-    let code = match QrCode::new(&verification_url_for_scan) {
-        Ok(code) => code,
-        Err(e) => {
-            tracing::info!(
-                target: "phi_backend::auth::performance",
-                route = "/auth/qrcode",
-                phase = "generate_qrcode_svg",
-                status = "failed",
-                dur_ms = t_qrcode.elapsed().as_millis(),
-                "auth performance"
-            );
-            return Err(AppError::Internal(format!("生成二维码失败: {e}")));
-        }
-    };
-    let image = code
-        .render()
-        .min_dimensions(256, 256)
-        .dark_color(svg::Color("#000"))
-        .light_color(svg::Color("#fff"))
-        .build();
-    tracing::info!(
-        target: "phi_backend::auth::performance",
-        route = "/auth/qrcode",
-        phase = "generate_qrcode_svg",
-        status = "ok",
-        dur_ms = t_qrcode.elapsed().as_millis(),
-        "auth performance"
-    );
-    let qrcode_base64 = format!(
-        "data:image/svg+xml;base64,{}",
-        base64::prelude::BASE64_STANDARD.encode(image)
-    );
-
     let interval_secs = device.interval.unwrap_or(5);
     let t_cache_set = Instant::now();
     state
@@ -258,7 +292,7 @@ pub(crate) async fn post_qrcode(
             device.expires_in,
             version.map(std::string::ToString::to_string),
         )
-        .await;
+        .await?;
     tracing::info!(
         target: "phi_backend::auth::performance",
         route = "/auth/qrcode",
@@ -268,20 +302,10 @@ pub(crate) async fn post_qrcode(
         "auth performance"
     );
 
-    let resp = QrCodeCreateResponse {
+    Ok(QrCodeLoginTicket {
         qr_id,
         verification_url: verification_url_for_scan,
-        qrcode_base64,
-    };
-    tracing::info!(
-        target: "phi_backend::auth::performance",
-        route = "/auth/qrcode",
-        phase = "total",
-        status = "ok",
-        dur_ms = t_total.elapsed().as_millis(),
-        "auth performance"
-    );
-    Ok(json_no_store(StatusCode::OK, resp))
+    })
 }
 
 #[utoipa::path(
@@ -299,6 +323,79 @@ pub async fn get_qrcode_status(
     State(state): State<AppState>,
     Path(qr_id): Path<String>,
 ) -> Result<Response, AppError> {
+    let status = resolve_qrcode_status(&state, &qr_id).await;
+    Ok(json_no_store(StatusCode::OK, status))
+}
+
+fn store_error_response() -> QrCodeStatusResponse {
+    QrCodeStatusResponse {
+        status: QrCodeStatusValue::Error,
+        session_token: None,
+        error_code: Some("INTERNAL_ERROR".to_string()),
+        message: Some("服务器内部错误".to_string()),
+        retry_after: None,
+    }
+}
+
+/// 条件写入落空（其他调用方已确认或记录已删除）时，按存储中的当前状态作答
+async fn stored_status_response(state: &AppState, qr_id: &str) -> QrCodeStatusResponse {
+    match state.qrcode_service.get(qr_id).await {
+        Ok(Some(QrCodeStatus::Confirmed { session_data })) => QrCodeStatusResponse {
+            status: QrCodeStatusValue::Confirmed,
+            session_token: Some(session_data.session_token),
+            error_code: None,
+            message: None,
+            retry_after: None,
+        },
+        Ok(Some(QrCodeStatus::Pending {
+            interval_secs,
+            next_poll_at,
+            ..
+        })) => QrCodeStatusResponse {
+            status: QrCodeStatusValue::Pending,
+            session_token: None,
+            error_code: None,
+            message: None,
+            retry_after: Some(
+                next_poll_at
+                    .saturating_duration_since(std::time::Instant::now())
+                    .as_secs()
+                    .max(interval_secs.max(1)),
+            ),
+        },
+        Ok(Some(QrCodeStatus::Scanned)) => QrCodeStatusResponse {
+            status: QrCodeStatusValue::Scanned,
+            session_token: None,
+            error_code: None,
+            message: None,
+            retry_after: None,
+        },
+        Ok(None) => QrCodeStatusResponse {
+            status: QrCodeStatusValue::Expired,
+            session_token: None,
+            error_code: None,
+            message: Some("二维码不存在或已过期".to_string()),
+            retry_after: None,
+        },
+        Err(e) => {
+            tracing::warn!(err = %e, "qrcode store read failed");
+            store_error_response()
+        }
+    }
+}
+
+/// 终态后清理登录状态；失败只记录日志，残留记录由过期清理兜底
+async fn remove_quietly(state: &AppState, qr_id: &str) {
+    if let Err(e) = state.qrcode_service.remove(qr_id).await {
+        tracing::warn!(err = %e, "qrcode store remove failed");
+    }
+}
+
+/// 推进一次二维码状态机并返回当前状态（轮询接口与 SSE 推送共用）。
+///
+/// 产生 Confirmed / Expired / Error 转换时会广播给 SSE 订阅者。Confirmed 记录保留到存储 TTL
+/// 到期，其他实例上的推送流与重复轮询都能读到同一个会话令牌，而不会误判为过期。
+pub(crate) async fn resolve_qrcode_status(state: &AppState, qr_id: &str) -> QrCodeStatusResponse {
     let t_total = Instant::now();
     let log_total = |result_status: &'static str| {
         tracing::info!(
@@ -313,61 +410,55 @@ pub async fn get_qrcode_status(
     };
 
     let t_cache_get = Instant::now();
-    let current = if let Some(c) = state.qrcode_service.get(&qr_id).await {
-        tracing::info!(
-            target: "phi_backend::auth::performance",
-            route = "/auth/qrcode/:qr_id/status",
-            phase = "cache_get",
-            status = "hit",
-            dur_ms = t_cache_get.elapsed().as_millis(),
-            "auth performance"
-        );
-        c
-    } else {
-        tracing::info!(
-            target: "phi_backend::auth::performance",
-            route = "/auth/qrcode/:qr_id/status",
-            phase = "cache_get",
-            status = "miss",
-            dur_ms = t_cache_get.elapsed().as_millis(),
-            "auth performance"
-        );
-        log_total("expired_not_found");
-        return Ok(json_no_store(
-            StatusCode::OK,
-            QrCodeStatusResponse {
+    let current = match state.qrcode_service.get(qr_id).await {
+        Ok(Some(c)) => {
+            tracing::info!(
+                target: "phi_backend::auth::performance",
+                route = "/auth/qrcode/:qr_id/status",
+                phase = "cache_get",
+                status = "hit",
+                dur_ms = t_cache_get.elapsed().as_millis(),
+                "auth performance"
+            );
+            c
+        }
+        Ok(None) => {
+            tracing::info!(
+                target: "phi_backend::auth::performance",
+                route = "/auth/qrcode/:qr_id/status",
+                phase = "cache_get",
+                status = "miss",
+                dur_ms = t_cache_get.elapsed().as_millis(),
+                "auth performance"
+            );
+            log_total("expired_not_found");
+            return QrCodeStatusResponse {
                 status: QrCodeStatusValue::Expired,
                 session_token: None,
                 error_code: None,
                 message: Some("二维码不存在或已过期".to_string()),
                 retry_after: None,
-            },
-        ));
+            };
+        }
+        Err(e) => {
+            tracing::warn!(err = %e, "qrcode store read failed");
+            log_total("error");
+            return store_error_response();
+        }
     };
 
     match current {
         QrCodeStatus::Confirmed { session_data } => {
-            let t_cache_remove = Instant::now();
-            state.qrcode_service.remove(&qr_id).await;
-            tracing::info!(
-                target: "phi_backend::auth::performance",
-                route = "/auth/qrcode/:qr_id/status",
-                phase = "cache_remove",
-                status = "ok",
-                dur_ms = t_cache_remove.elapsed().as_millis(),
-                "auth performance"
-            );
+            let resp = QrCodeStatusResponse {
+                status: QrCodeStatusValue::Confirmed,
+                session_token: Some(session_data.session_token),
+                error_code: None,
+                message: None,
+                retry_after: None,
+            };
+            state.qrcode_service.publish(qr_id, &resp);
             log_total("confirmed");
-            Ok(json_no_store(
-                StatusCode::OK,
-                QrCodeStatusResponse {
-                    status: QrCodeStatusValue::Confirmed,
-                    session_token: Some(session_data.session_token),
-                    error_code: None,
-                    message: None,
-                    retry_after: None,
-                },
-            ))
+            resp
         }
         QrCodeStatus::Pending {
             device_code,
@@ -380,8 +471,16 @@ pub async fn get_qrcode_status(
             let now = std::time::Instant::now();
 
             if now >= expires_at {
+                let resp = QrCodeStatusResponse {
+                    status: QrCodeStatusValue::Expired,
+                    session_token: None,
+                    error_code: None,
+                    message: Some("二维码已过期".to_string()),
+                    retry_after: None,
+                };
+                state.qrcode_service.publish(qr_id, &resp);
                 let t_cache_remove = Instant::now();
-                state.qrcode_service.remove(&qr_id).await;
+                remove_quietly(state, qr_id).await;
                 tracing::info!(
                     target: "phi_backend::auth::performance",
                     route = "/auth/qrcode/:qr_id/status",
@@ -391,16 +490,7 @@ pub async fn get_qrcode_status(
                     "auth performance"
                 );
                 log_total("expired");
-                return Ok(json_no_store(
-                    StatusCode::OK,
-                    QrCodeStatusResponse {
-                        status: QrCodeStatusValue::Expired,
-                        session_token: None,
-                        error_code: None,
-                        message: Some("二维码已过期".to_string()),
-                        retry_after: None,
-                    },
-                ));
+                return resp;
             }
 
             if now < next_poll_at {
@@ -415,16 +505,33 @@ pub async fn get_qrcode_status(
                     "auth performance"
                 );
                 log_total("pending_wait");
-                return Ok(json_no_store(
-                    StatusCode::OK,
-                    QrCodeStatusResponse {
+                return QrCodeStatusResponse {
+                    status: QrCodeStatusValue::Pending,
+                    session_token: None,
+                    error_code: None,
+                    message: None,
+                    retry_after: Some(retry_secs),
+                };
+            }
+
+            // 多实例共享存储时，同一轮轮询只允许一个实例向 TapTap 发起，避免重复消费 device code
+            match state.qrcode_service.claim_poll(qr_id, interval_secs).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    log_total("pending_claimed");
+                    return QrCodeStatusResponse {
                         status: QrCodeStatusValue::Pending,
                         session_token: None,
                         error_code: None,
                         message: None,
-                        retry_after: Some(retry_secs),
-                    },
-                ));
+                        retry_after: Some(interval_secs.max(1)),
+                    };
+                }
+                Err(e) => {
+                    tracing::warn!(err = %e, "qrcode poll claim failed");
+                    log_total("error");
+                    return store_error_response();
+                }
             }
 
             let t_poll = Instant::now();
@@ -433,7 +540,7 @@ pub async fn get_qrcode_status(
                 .poll_for_token(&device_code, &device_id, version.as_deref())
                 .await
             {
-                Ok(session) => {
+                Ok(login) => {
                     tracing::info!(
                         target: "phi_backend::auth::performance",
                         route = "/auth/qrcode/:qr_id/status",
@@ -442,12 +549,36 @@ pub async fn get_qrcode_status(
                         dur_ms = t_poll.elapsed().as_millis(),
                         "auth performance"
                     );
+                    let session = login.session;
+                    record_login_profile(
+                        state,
+                        &session.session_token,
+                        login.profile,
+                        version.as_deref(),
+                    )
+                    .await;
                     let t_cache_update = Instant::now();
-                    state
+                    match state
                         .qrcode_service
-                        .set_confirmed(&qr_id, session.clone())
-                        .await;
-                    state.qrcode_service.remove(&qr_id).await;
+                        .set_confirmed(qr_id, session.clone())
+                        .await
+                    {
+                        Ok(true) => {}
+                        // 其他调用方已先确认（或记录已删除）：以存储中的状态为准，不下发新令牌
+                        Ok(false) => {
+                            log_total("confirmed_elsewhere");
+                            return stored_status_response(state, qr_id).await;
+                        }
+                        Err(e) => tracing::warn!(err = %e, "qrcode store confirm failed"),
+                    }
+                    let resp = QrCodeStatusResponse {
+                        status: QrCodeStatusValue::Confirmed,
+                        session_token: Some(session.session_token),
+                        error_code: None,
+                        message: None,
+                        retry_after: None,
+                    };
+                    state.qrcode_service.publish(qr_id, &resp);
                     tracing::info!(
                         target: "phi_backend::auth::performance",
                         route = "/auth/qrcode/:qr_id/status",
@@ -457,16 +588,7 @@ pub async fn get_qrcode_status(
                         "auth performance"
                     );
                     log_total("confirmed");
-                    Ok(json_no_store(
-                        StatusCode::OK,
-                        QrCodeStatusResponse {
-                            status: QrCodeStatusValue::Confirmed,
-                            session_token: Some(session.session_token),
-                            error_code: None,
-                            message: None,
-                            retry_after: None,
-                        },
-                    ))
+                    resp
                 }
                 Err(AppError::AuthPending(_)) => {
                     tracing::info!(
@@ -478,17 +600,19 @@ pub async fn get_qrcode_status(
                         "auth performance"
                     );
                     let t_cache_update = Instant::now();
-                    state
+                    match state
                         .qrcode_service
-                        .set_pending_next_poll(
-                            &qr_id,
-                            device_code,
-                            device_id,
-                            interval_secs,
-                            expires_at,
-                            version,
-                        )
-                        .await;
+                        .set_pending_next_poll(qr_id, interval_secs)
+                        .await
+                    {
+                        Ok(true) => {}
+                        // 轮询期间已被其他调用方确认或删除：不覆盖，直接返回当前状态
+                        Ok(false) => {
+                            log_total("pending_superseded");
+                            return stored_status_response(state, qr_id).await;
+                        }
+                        Err(e) => tracing::warn!(err = %e, "qrcode store update failed"),
+                    }
                     tracing::info!(
                         target: "phi_backend::auth::performance",
                         route = "/auth/qrcode/:qr_id/status",
@@ -498,16 +622,13 @@ pub async fn get_qrcode_status(
                         "auth performance"
                     );
                     log_total("pending");
-                    Ok(json_no_store(
-                        StatusCode::OK,
-                        QrCodeStatusResponse {
-                            status: QrCodeStatusValue::Pending,
-                            session_token: None,
-                            error_code: None,
-                            message: None,
-                            retry_after: Some(interval_secs),
-                        },
-                    ))
+                    QrCodeStatusResponse {
+                        status: QrCodeStatusValue::Pending,
+                        session_token: None,
+                        error_code: None,
+                        message: None,
+                        retry_after: Some(interval_secs),
+                    }
                 }
                 Err(e) => {
                     tracing::warn!(err = %e, "qrcode poll failed");
@@ -535,32 +656,36 @@ pub async fn get_qrcode_status(
                         | AppError::ImageRendererError(_)
                         | AppError::AuthPending(_) => ("INTERNAL_ERROR", "服务器内部错误"),
                     };
+                    // 释放轮询租约，允许客户端按间隔重试
+                    if let Err(e) = state
+                        .qrcode_service
+                        .set_pending_next_poll(qr_id, interval_secs)
+                        .await
+                    {
+                        tracing::warn!(err = %e, "qrcode store update failed");
+                    }
                     log_total("error");
-                    Ok(json_no_store(
-                        StatusCode::OK,
-                        QrCodeStatusResponse {
-                            status: QrCodeStatusValue::Error,
-                            session_token: None,
-                            error_code: Some(error_code.to_string()),
-                            message: Some(message.to_string()),
-                            retry_after: None,
-                        },
-                    ))
+                    let resp = QrCodeStatusResponse {
+                        status: QrCodeStatusValue::Error,
+                        session_token: None,
+                        error_code: Some(error_code.to_string()),
+                        message: Some(message.to_string()),
+                        retry_after: None,
+                    };
+                    state.qrcode_service.publish(qr_id, &resp);
+                    resp
                 }
             }
         }
         QrCodeStatus::Scanned => {
             log_total("scanned");
-            Ok(json_no_store(
-                StatusCode::OK,
-                QrCodeStatusResponse {
-                    status: QrCodeStatusValue::Scanned,
-                    session_token: None,
-                    error_code: None,
-                    message: None,
-                    retry_after: None,
-                },
-            ))
+            QrCodeStatusResponse {
+                status: QrCodeStatusValue::Scanned,
+                session_token: None,
+                error_code: None,
+                message: None,
+                retry_after: None,
+            }
         }
     }
 }
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    response::sse::{Event, Sse},
+};
+use futures_util::Stream;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{Instant, sleep_until};
+
+use crate::error::AppError;
+use crate::features::auth::qrcode_service::QrStatusEvent;
+use crate::realtime::{self, StreamGuard};
+use crate::state::AppState;
+
+use super::qrcode::{QrCodeStatusResponse, QrCodeStatusValue, resolve_qrcode_status};
+
+/// 未给出 `retry_after` 时的兜底轮询间隔
+const FALLBACK_POLL_SECS: u64 = 1;
+
+struct QrStreamState {
+    guard: StreamGuard,
+    state: AppState,
+    qr_id: String,
+    rx: broadcast::Receiver<QrStatusEvent>,
+    next_poll: Instant,
+    last: Option<QrCodeStatusValue>,
+    done: bool,
+}
+
+fn is_terminal(status: QrCodeStatusValue) -> bool {
+    matches!(
+        status,
+        QrCodeStatusValue::Confirmed | QrCodeStatusValue::Expired | QrCodeStatusValue::Error
+    )
+}
+
+fn status_event(status: &QrCodeStatusResponse) -> Result<Event, axum::Error> {
+    Event::default().event("status").json_data(status)
+}
+
+impl QrStreamState {
+    /// 等待下一条需要下发的事件；返回 `None` 表示流结束。
+    async fn next_event(&mut self) -> Option<Result<Event, axum::Error>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            let status = tokio::select! {
+                () = self.guard.closed() => {
+                    self.done = true;
+                    return Some(Ok(realtime::shutdown_event()));
+                }
+                recv = self.rx.recv() => match recv {
+                    Ok(ev) if ev.qr_id == self.qr_id => ev.status,
+                    Ok(_) => continue,
+                    // 丢失了广播则立即自行推进一次状态机补齐
+                    Err(RecvError::Lagged(_)) => {
+                        self.next_poll = Instant::now();
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+                () = sleep_until(self.next_poll) => {
+                    let status = resolve_qrcode_status(&self.state, &self.qr_id).await;
+                    let wait = status.retry_after.unwrap_or(FALLBACK_POLL_SECS).max(1);
+                    self.next_poll = Instant::now() + Duration::from_secs(wait);
+                    status
+                }
+            };
+            if self.last == Some(status.status) {
+                continue;
+            }
+            self.last = Some(status.status);
+            self.done = is_terminal(status.status);
+            return Some(status_event(&status));
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/qrcode/{qr_id}/events",
+    summary = "订阅二维码授权状态（SSE）",
+    description = "以 Server-Sent Events 推送二维码状态变化，替代定时轮询 /auth/qrcode/{qr_id}/status。\
+\n\n- `status`：数据同状态轮询接口的响应体，仅在状态变化时下发；Confirmed/Expired/Error 为终态，下发后服务端关闭连接\
+\n- `shutdown`：服务正在关闭，客户端应稍后重连或回退到轮询\
+\n\n服务端按配置间隔发送心跳注释帧；连接数超过 `realtime.max_connections` 或推送未启用时返回 403。",
+    params(("qr_id" = String, Path, description = "二维码ID")),
+    responses(
+        (status = 200, description = "SSE 事件流", content_type = "text/event-stream", body = String),
+        (
+            status = 403,
+            description = "实时推送未启用或连接数已达上限",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Auth"
+)]
+pub async fn get_qrcode_events(
+    State(state): State<AppState>,
+    Path(qr_id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    let guard = realtime::acquire_stream()?;
+    // 先订阅再做首轮轮询，避免错过两者之间发生的转换
+    let rx = state.qrcode_service.subscribe();
+    let init = QrStreamState {
+        guard,
+        state,
+        qr_id,
+        rx,
+        next_poll: Instant::now(),
+        last: None,
+        done: false,
+    };
+    let stream = futures_util::stream::unfold(init, |mut s| async move {
+        s.next_event().await.map(|ev| (ev, s))
+    });
+    Ok(Sse::new(stream).keep_alive(realtime::keep_alive()))
+}
@@ -1,23 +1,34 @@
-use axum::{Json, extract::State, http::StatusCode};
+use axum::{Json, body::Bytes, extract::State, http::StatusCode};
+use base64::Engine;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::time::Instant;
 use uuid::Uuid;
 
 use crate::error::AppError;
 use crate::state::AppState;
+use crate::stats_contract::{NewSessionFamily, RefreshRotation, StatsStorage};
 
 use crate::features::auth::bearer::{
     SessionClaims, build_embedded_auth_claim, decode_access_token,
     decode_access_token_allow_expired, decode_embedded_auth_with_claims, ensure_session_config,
-    extract_bearer_token, resolve_exchange_secret, resolve_expected_exchange_secret,
-    resolve_jwt_secret, validate_bearer_not_revoked,
+    extract_bearer_token, open_session_family_auth, resolve_exchange_secret,
+    resolve_expected_exchange_secret, resolve_jwt_secret, seal_session_family_auth,
+    validate_bearer_not_revoked,
 };
 
+/// 设备标签最大长度（字符）
+const DEVICE_LABEL_MAX_CHARS: usize = 64;
+
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionExchangeRequest {
     #[serde(flatten)]
     pub auth: crate::auth_contract::UnifiedSaveRequest,
+    /// 设备标签（如 "iPhone 15 / Safari"），用于会话列表展示，最长 64 字符
+    #[serde(default)]
+    pub device_label: Option<String>,
 }
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +36,21 @@ pub struct SessionExchangeResponse {
     pub access_token: String,
     pub expires_in: u64,
     pub token_type: &'static str,
+    /// 不透明刷新令牌（仅统计存储可用时返回）；每次使用后失效并返回新令牌
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// 刷新令牌有效期（秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_expires_in: Option<u64>,
+    /// 刷新会话 ID（/auth/sessions 中的 sessionId）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRefreshRequest {
+    /// exchange 或上一次刷新返回的刷新令牌
+    pub refresh_token: String,
 }
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -58,9 +84,34 @@ struct SessionTokenClaims<'a> {
     aud: &'a str,
     iat: i64,
     exp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sid: Option<&'a str>,
     sae: String,
 }
 
+/// 新签发的刷新令牌：明文只返回给客户端，库中仅保存其 SHA-256
+struct IssuedRefreshToken {
+    token: String,
+    hash: String,
+}
+
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn generate_refresh_token() -> IssuedRefreshToken {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+    let hash = hash_refresh_token(&token);
+    IssuedRefreshToken { token, hash }
+}
+
+fn normalize_device_label(label: Option<&str>) -> Option<String> {
+    let label = label.map(str::trim).filter(|v| !v.is_empty())?;
+    Some(label.chars().take(DEVICE_LABEL_MAX_CHARS).collect())
+}
+
 fn parse_authorization_token(
     headers: &axum::http::HeaderMap,
     cfg: &crate::config::SessionConfig,
@@ -106,10 +157,11 @@ fn saturating_u64_to_i64(value: u64) -> i64 {
 fn issue_session_access_token(
     auth: &crate::auth_contract::UnifiedSaveRequest,
     sub: &str,
+    sid: Option<&str>,
     cfg: &crate::config::SessionConfig,
     jwt_secret: &str,
     now: chrono::DateTime<chrono::Utc>,
-) -> Result<String, AppError> {
+) -> Result<(String, SessionClaims), AppError> {
     let iat = now.timestamp();
     let exp =
         (now + chrono::Duration::seconds(saturating_u64_to_i64(cfg.access_ttl_secs))).timestamp();
@@ -120,6 +172,7 @@ fn issue_session_access_token(
         aud: cfg.jwt_audience.clone(),
         iat,
         exp,
+        sid: sid.map(str::to_string),
     };
     let embedded_auth = build_embedded_auth_claim(auth, &claims.jti, &claims.sub)?;
     let token_claims = SessionTokenClaims {
@@ -129,6 +182,7 @@ fn issue_session_access_token(
         aud: claims.aud.as_str(),
         iat: claims.iat,
         exp: claims.exp,
+        sid: claims.sid.as_deref(),
         sae: embedded_auth,
     };
     let token = jsonwebtoken::encode(
@@ -137,7 +191,132 @@ fn issue_session_access_token(
         &jsonwebtoken::EncodingKey::from_secret(jwt_secret.as_bytes()),
     )
     .map_err(|e| AppError::Internal(format!("绛惧彂浼氳瘽浠ょ墝澶辫触: {e}")))?;
-    Ok(token)
+    Ok((token, claims))
+}
+
+fn timestamp_to_rfc3339(ts: i64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+/// 新建刷新会话族：签发带 `sid` 的 access token 与首个刷新令牌
+async fn issue_session_family(
+    storage: &StatsStorage,
+    auth: &crate::auth_contract::UnifiedSaveRequest,
+    user_hash: &str,
+    device_label: Option<&str>,
+    cfg: &crate::config::SessionConfig,
+    jwt_secret: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<SessionExchangeResponse, AppError> {
+    let family_id = Uuid::new_v4().to_string();
+    let (access_token, claims) =
+        issue_session_access_token(auth, user_hash, Some(&family_id), cfg, jwt_secret, now)?;
+    let refresh = generate_refresh_token();
+    let auth_sealed = seal_session_family_auth(auth, &family_id, user_hash)?;
+    let now_rfc3339 = now.to_rfc3339();
+    let expires_at =
+        (now + chrono::Duration::seconds(saturating_u64_to_i64(cfg.refresh_ttl_secs))).to_rfc3339();
+    storage
+        .insert_session_family(&NewSessionFamily {
+            family_id: &family_id,
+            user_hash,
+            device_label,
+            auth_sealed: &auth_sealed,
+            token_hash: &refresh.hash,
+            access_jti: &claims.jti,
+            access_expires_at: &timestamp_to_rfc3339(claims.exp),
+            expires_at: &expires_at,
+            now: &now_rfc3339,
+        })
+        .await?;
+    Ok(SessionExchangeResponse {
+        access_token,
+        expires_in: cfg.access_ttl_secs,
+        token_type: "Bearer",
+        refresh_token: Some(refresh.token),
+        refresh_expires_in: Some(cfg.refresh_ttl_secs),
+        session_id: Some(family_id),
+    })
+}
+
+/// 使用刷新令牌换取新的 access token，并轮换刷新令牌。
+///
+/// 已轮换过的令牌再次出现视为泄露重放：吊销整个会话族（含当前 access token）。
+async fn refresh_with_token(
+    storage: &StatsStorage,
+    refresh_token: &str,
+    cfg: &crate::config::SessionConfig,
+    jwt_secret: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<SessionExchangeResponse, AppError> {
+    let now_rfc3339 = now.to_rfc3339();
+    let old_hash = hash_refresh_token(refresh_token.trim());
+    let lookup = storage
+        .get_refresh_token(&old_hash)
+        .await?
+        .ok_or_else(|| AppError::Auth("刷新令牌无效".into()))?;
+    let family = lookup.family;
+    if family.revoked_at.is_some() {
+        return Err(AppError::Auth("会话已被撤销".into()));
+    }
+    if lookup.used_at.is_some() {
+        storage
+            .revoke_session_family(&family.family_id, "reuse", &now_rfc3339)
+            .await?;
+        tracing::warn!(
+            target: "phi_backend::auth::session",
+            session_id = %family.family_id,
+            "refresh token reuse detected, session family revoked"
+        );
+        return Err(AppError::Auth("刷新令牌已被使用，会话已撤销".into()));
+    }
+    let token_expires_at = chrono::DateTime::parse_from_rfc3339(&lookup.expires_at)
+        .map_err(|e| AppError::Internal(format!("解析刷新令牌过期时间失败: {e}")))?;
+    if token_expires_at <= now {
+        return Err(AppError::Auth("刷新令牌已过期".into()));
+    }
+    storage.ensure_user_not_banned(&family.user_hash).await?;
+
+    let auth = open_session_family_auth(&family.auth_sealed, &family.family_id, &family.user_hash)?;
+    let (access_token, claims) = issue_session_access_token(
+        &auth,
+        &family.user_hash,
+        Some(&family.family_id),
+        cfg,
+        jwt_secret,
+        now,
+    )?;
+    let refresh = generate_refresh_token();
+    let expires_at =
+        (now + chrono::Duration::seconds(saturating_u64_to_i64(cfg.refresh_ttl_secs))).to_rfc3339();
+    let rotated = storage
+        .rotate_refresh_token(&RefreshRotation {
+            family_id: &family.family_id,
+            old_hash: &old_hash,
+            new_hash: &refresh.hash,
+            access_jti: &claims.jti,
+            access_expires_at: &timestamp_to_rfc3339(claims.exp),
+            expires_at: &expires_at,
+            now: &now_rfc3339,
+        })
+        .await?;
+    if !rotated {
+        // 并发请求抢先使用了同一令牌，同样按重放处理
+        storage
+            .revoke_session_family(&family.family_id, "reuse", &now_rfc3339)
+            .await?;
+        return Err(AppError::Auth("刷新令牌已被使用，会话已撤销".into()));
+    }
+    Ok(SessionExchangeResponse {
+        access_token,
+        expires_in: cfg.access_ttl_secs,
+        token_type: "Bearer",
+        refresh_token: Some(refresh.token),
+        refresh_expires_in: Some(cfg.refresh_ttl_secs),
+        session_id: Some(family.family_id),
+    })
 }
 
 async fn try_cleanup_expired_session_records(state: &AppState) {
@@ -155,7 +334,7 @@ async fn try_cleanup_expired_session_records(state: &AppState) {
     post,
     path = "/auth/session/exchange",
     summary = "签发后端会话令牌",
-    description = "使用登录凭证交换后端短期 access token。",
+    description = "使用登录凭证交换后端短期 access token。统计存储可用时同时返回不透明刷新令牌（refreshToken）与会话 ID，可通过 deviceLabel 标注设备。",
     request_body = SessionExchangeRequest,
     params(("X-Exchange-Secret" = String, Header, description = "Next.js 与后端共享密钥")),
     responses(
@@ -191,6 +370,7 @@ pub async fn post_session_exchange(
     let cfg = ensure_session_config()?;
     ensure_exchange_secret_valid(&headers, cfg)?;
     let jwt_secret = resolve_jwt_secret(cfg)?;
+    let device_label = normalize_device_label(req.device_label.as_deref());
     let auth = req.auth;
     if auth.session_token.is_some() && auth.external_credentials.is_some() {
         return Err(AppError::Validation(
@@ -231,11 +411,31 @@ pub async fn post_session_exchange(
         crate::identity_hash::derive_user_identity_from_auth(Some(salt_value.as_str()), &auth);
     let user_hash = user_hash_opt
         .ok_or_else(|| AppError::Auth("鏃犳硶璇嗗埆鐢ㄦ埛锛堢己灏戝彲鐢ㄥ嚟璇侊級".into()))?;
-    if let Some(storage) = state.stats_storage.as_ref() {
+    let now = chrono::Utc::now();
+    let response = if let Some(storage) = state.stats_storage.as_ref() {
         storage.ensure_user_not_banned(&user_hash).await?;
-    }
-    let token =
-        issue_session_access_token(&auth, &user_hash, cfg, &jwt_secret, chrono::Utc::now())?;
+        issue_session_family(
+            storage,
+            &auth,
+            &user_hash,
+            device_label.as_deref(),
+            cfg,
+            &jwt_secret,
+            now,
+        )
+        .await?
+    } else {
+        let (token, _) =
+            issue_session_access_token(&auth, &user_hash, None, cfg, &jwt_secret, now)?;
+        SessionExchangeResponse {
+            access_token: token,
+            expires_in: cfg.access_ttl_secs,
+            token_type: "Bearer",
+            refresh_token: None,
+            refresh_expires_in: None,
+            session_id: None,
+        }
+    };
     tracing::info!(
         target: "phi_backend::auth::performance",
         route = "/auth/session/exchange",
@@ -244,30 +444,30 @@ pub async fn post_session_exchange(
         dur_ms = t_total.elapsed().as_millis(),
         "auth performance"
     );
-    Ok((
-        StatusCode::OK,
-        Json(SessionExchangeResponse {
-            access_token: token,
-            expires_in: cfg.access_ttl_secs,
-            token_type: "Bearer",
-        }),
-    ))
+    Ok((StatusCode::OK, Json(response)))
 }
 
 #[utoipa::path(
     post,
     path = "/auth/session/refresh",
     summary = "刷新会话令牌",
-    description = "使用旧的 Bearer access token（允许过期）与 X-Exchange-Secret 换取新的短期 access token。",
+    description = "携带 refreshToken 请求体时按刷新令牌轮换：返回新的 access token 与新的 refreshToken，旧令牌立即失效；已使用过的 refreshToken 再次出现时吊销整个会话。未携带请求体时沿用旧流程：使用旧的 Bearer access token（允许过期）换取新的 access token，仅适用于未关联刷新会话（不含 sid）的旧令牌；关联刷新会话的令牌必须携带 refreshToken。两种方式均需 X-Exchange-Secret。",
+    request_body(content = SessionRefreshRequest, description = "刷新令牌；可省略请求体以使用旧的 access token 刷新"),
     params(
-        ("Authorization" = String, Header, description = "Bearer access token（可过期）"),
+        ("Authorization" = Option<String>, Header, description = "Bearer access token（可过期；未提供 refreshToken 时必填）"),
         ("X-Exchange-Secret" = String, Header, description = "Next.js 与后端共享密钥")
     ),
     responses(
         (status = 200, description = "刷新成功", body = SessionExchangeResponse),
         (
             status = 401,
-            description = "共享密钥无效、令牌无效或已撤销",
+            description = "共享密钥无效、令牌无效、已过期、已撤销、刷新令牌被重放，或关联刷新会话的令牌未携带 refreshToken",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "请求体 JSON 无效",
             body = crate::error::ProblemDetails,
             content_type = "application/problem+json"
         ),
@@ -283,13 +483,47 @@ pub async fn post_session_exchange(
 pub async fn post_session_refresh(
     State(state): State<AppState>,
     headers: axum::http::HeaderMap,
+    body: Bytes,
 ) -> Result<(StatusCode, Json<SessionExchangeResponse>), AppError> {
     let t_total = Instant::now();
     try_cleanup_expired_session_records(&state).await;
     let cfg = ensure_session_config()?;
     ensure_exchange_secret_valid(&headers, cfg)?;
     let jwt_secret = resolve_jwt_secret(cfg)?;
+
+    if !body.trim_ascii().is_empty() {
+        let req: SessionRefreshRequest = serde_json::from_slice(&body)
+            .map_err(|e| AppError::Validation(format!("请求体 JSON 无效: {e}")))?;
+        let storage = state
+            .stats_storage
+            .as_ref()
+            .ok_or_else(|| AppError::Internal("统计存储未初始化，无法执行会话刷新".into()))?;
+        let response = refresh_with_token(
+            storage,
+            &req.refresh_token,
+            cfg,
+            &jwt_secret,
+            chrono::Utc::now(),
+        )
+        .await?;
+        tracing::info!(
+            target: "phi_backend::auth::performance",
+            route = "/auth/session/refresh",
+            phase = "total",
+            status = "ok",
+            dur_ms = t_total.elapsed().as_millis(),
+            "auth performance"
+        );
+        return Ok((StatusCode::OK, Json(response)));
+    }
+
     let authz = parse_authorization_token_allow_expired(&headers, cfg)?;
+    // 关联刷新会话的令牌只能经轮换刷新：否则会绕过重放检测，且会话族记录的 current_jti 不再同步
+    if authz.claims.sid.is_some() {
+        return Err(AppError::Auth(
+            "该令牌关联刷新会话，请使用 refreshToken 刷新".into(),
+        ));
+    }
 
     let storage = state
         .stats_storage
@@ -306,7 +540,8 @@ pub async fn post_session_refresh(
     }
 
     let auth = decode_embedded_auth_with_claims(&authz.token, &authz.claims)?;
-    let token = issue_session_access_token(&auth, &authz.claims.sub, cfg, &jwt_secret, now)?;
+    let (token, _) =
+        issue_session_access_token(&auth, &authz.claims.sub, None, cfg, &jwt_secret, now)?;
     tracing::info!(
         target: "phi_backend::auth::performance",
         route = "/auth/session/refresh",
@@ -322,6 +557,9 @@ pub async fn post_session_refresh(
             access_token: token,
             expires_in: cfg.access_ttl_secs,
             token_type: "Bearer",
+            refresh_token: None,
+            refresh_expires_in: None,
+            session_id: None,
         }),
     ))
 }
@@ -329,7 +567,7 @@ pub async fn post_session_refresh(
     post,
     path = "/auth/session/logout",
     summary = "注销会话令牌",
-    description = "scope=current 仅注销当前令牌，scope=all 注销该用户所有历史令牌。",
+    description = "scope=current 仅注销当前令牌（及其所属的刷新会话），scope=all 注销该用户所有历史令牌与全部刷新会话。",
     request_body = SessionLogoutRequest,
     params(("Authorization" = String, Header, description = "Bearer access token")),
     responses(
@@ -377,6 +615,13 @@ pub async fn post_session_logout(
             .upsert_logout_gate(&authz.claims.sub, &gate_rfc3339, &gate_expire, &now_rfc3339)
             .await?;
         logout_before = Some(gate_rfc3339);
+        storage
+            .revoke_user_session_families(&authz.claims.sub, "logout_all", &now_rfc3339)
+            .await?;
+    } else if let Some(sid) = authz.claims.sid.as_deref() {
+        storage
+            .revoke_session_family(sid, "logout", &now_rfc3339)
+            .await?;
     }
     let exp_ts = authz.claims.exp;
     let expires_at = chrono::DateTime::<chrono::Utc>::from_timestamp(exp_ts, 0)
@@ -0,0 +1,163 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::stats_contract::{SessionFamilyRow, StatsStorage};
+
+use crate::features::auth::bearer::{BearerAuthState, SessionClaims};
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    /// 会话 ID（exchange 返回的 sessionId）
+    pub session_id: String,
+    /// exchange 时提交的设备标签
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_label: Option<String>,
+    /// 创建时间（RFC3339）
+    pub created_at: String,
+    /// 最近一次刷新时间（RFC3339）
+    pub last_used_at: String,
+    /// 刷新令牌过期时间（RFC3339）
+    pub expires_at: String,
+    /// 是否为发起本次请求的会话
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionListResponse {
+    pub sessions: Vec<SessionInfo>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRevokeResponse {
+    pub session_id: String,
+    /// 本次是否发生吊销（已吊销的会话重复调用时为 false）
+    pub revoked: bool,
+}
+
+fn require_bearer(bearer: Option<Extension<BearerAuthState>>) -> Result<SessionClaims, AppError> {
+    match bearer.map(|Extension(b)| b).unwrap_or_default() {
+        BearerAuthState::Valid(ctx) => Ok(ctx.claims),
+        BearerAuthState::Invalid(msg) => Err(AppError::Auth(msg)),
+        BearerAuthState::Absent => Err(AppError::Auth("缺少 Bearer 会话令牌".into())),
+    }
+}
+
+fn require_storage(state: &AppState) -> Result<&StatsStorage, AppError> {
+    state
+        .stats_storage
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化，无法管理会话".into()))
+}
+
+fn to_info(row: SessionFamilyRow, current_sid: Option<&str>) -> SessionInfo {
+    SessionInfo {
+        current: current_sid == Some(row.family_id.as_str()),
+        session_id: row.family_id,
+        device_label: row.device_label,
+        created_at: row.created_at,
+        last_used_at: row.last_used_at,
+        expires_at: row.expires_at,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    summary = "列出当前用户的刷新会话",
+    description = "返回 Bearer 会话用户所有未吊销且未过期的刷新会话（每次 exchange 一个，按最近使用排序）。",
+    params(("Authorization" = String, Header, description = "Bearer access token")),
+    responses(
+        (status = 200, description = "会话列表", body = SessionListResponse),
+        (
+            status = 401,
+            description = "缺少或无效的 Bearer 令牌",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "存储不可用",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Auth"
+)]
+pub async fn get_sessions(
+    State(state): State<AppState>,
+    bearer: Option<Extension<BearerAuthState>>,
+) -> Result<Json<SessionListResponse>, AppError> {
+    let claims = require_bearer(bearer)?;
+    let storage = require_storage(&state)?;
+    let now_rfc3339 = chrono::Utc::now().to_rfc3339();
+    let rows = storage
+        .list_active_session_families(&claims.sub, &now_rfc3339)
+        .await?;
+    let sessions = rows
+        .into_iter()
+        .map(|row| to_info(row, claims.sid.as_deref()))
+        .collect();
+    Ok(Json(SessionListResponse { sessions }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/sessions/{session_id}/revoke",
+    summary = "吊销指定刷新会话",
+    description = "吊销当前用户的某个刷新会话：其刷新令牌立即失效，该会话最近签发的 access token 写入黑名单。",
+    params(
+        ("Authorization" = String, Header, description = "Bearer access token"),
+        ("session_id" = String, Path, description = "会话 ID")
+    ),
+    responses(
+        (status = 200, description = "已吊销", body = SessionRevokeResponse),
+        (
+            status = 401,
+            description = "缺少或无效的 Bearer 令牌",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "会话不存在或不属于当前用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "存储不可用",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Auth"
+)]
+pub async fn post_revoke_session(
+    State(state): State<AppState>,
+    bearer: Option<Extension<BearerAuthState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionRevokeResponse>, AppError> {
+    let claims = require_bearer(bearer)?;
+    let storage = require_storage(&state)?;
+    let family = storage
+        .get_session_family(&session_id)
+        .await?
+        .filter(|f| f.user_hash == claims.sub)
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))?;
+    let now_rfc3339 = chrono::Utc::now().to_rfc3339();
+    let revoked = storage
+        .revoke_session_family(&family.family_id, "revoked", &now_rfc3339)
+        .await?;
+    Ok(Json(SessionRevokeResponse {
+        session_id: family.family_id,
+        revoked,
+    }))
+}
@@ -2,7 +2,9 @@ pub mod bearer;
 pub mod client;
 pub mod handler;
 pub mod models;
+pub mod profile;
 pub mod qrcode_service;
+pub mod qrcode_store;
 
 // 对外导出路由构建函数，便于 main.rs 引用
 pub use handler::create_auth_router;
@@ -17,6 +17,43 @@ pub struct Token {
 pub struct Account {
     pub openid: String,
     pub unionid: String,
+    /// TapTap 昵称
+    #[serde(default)]
+    pub name: Option<String>,
+    /// TapTap 头像 URL
+    #[serde(default)]
+    pub avatar: Option<String>,
+}
+
+/// TapTap 基础资料（昵称与头像），字段缺失时为 None
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TapTapProfile {
+    pub nickname: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+impl TapTapProfile {
+    /// 空白字符串视为缺失
+    #[must_use]
+    pub fn new(nickname: Option<String>, avatar_url: Option<String>) -> Self {
+        let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Self {
+            nickname: clean(nickname),
+            avatar_url: clean(avatar_url),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nickname.is_none() && self.avatar_url.is_none()
+    }
+}
+
+/// 扫码登录换取令牌的结果：LeanCloud 会话与同一轮获取到的 TapTap 基础资料
+#[derive(Debug, Clone)]
+pub struct TapTapLogin {
+    pub session: SessionData,
+    pub profile: TapTapProfile,
 }
 
 #[derive(Debug, Deserialize, Clone, ToSchema)]
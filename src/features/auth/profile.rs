@@ -0,0 +1,164 @@
+//! TapTap 玩家资料（昵称/头像）缓存
+//!
+//! 扫码登录成功时写入统计库；之后按 `player_profile.refresh_secs` 判断是否过期，
+//! 过期且请求携带 sessionToken 时经 LeanCloud users/me 刷新，刷新失败则继续使用旧值。
+
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::stats_contract::TapTapProfileRow;
+
+use super::models::TapTapProfile;
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// 资料是否仍在刷新间隔内
+pub(crate) fn is_profile_fresh(row: &TapTapProfileRow, now_ms: i64, refresh_secs: u64) -> bool {
+    let ttl_ms = i64::try_from(refresh_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+    now_ms.saturating_sub(row.fetched_at_ms) < ttl_ms
+}
+
+/// 与 /auth/session/exchange 相同的 salt 来源，保证资料与会话令牌的 user_hash 一致
+fn user_hash_for_session_token(session_token: &str) -> Option<String> {
+    let salt = AppConfig::global()
+        .stats
+        .user_hash_salt
+        .clone()
+        .or_else(|| std::env::var("APP_STATS_USER_HASH_SALT").ok())
+        .filter(|v| !v.trim().is_empty())?;
+    Some(crate::identity_hash::hmac_hex16(&salt, session_token))
+}
+
+fn to_row(
+    profile: TapTapProfile,
+    taptap_version: Option<&str>,
+    fetched_at_ms: i64,
+) -> TapTapProfileRow {
+    TapTapProfileRow {
+        nickname: profile.nickname,
+        avatar_url: profile.avatar_url,
+        taptap_version: taptap_version.map(str::to_string),
+        fetched_at_ms,
+    }
+}
+
+/// 扫码登录成功后记录 TapTap 基础资料（尽力而为，失败只记录日志）
+pub(crate) async fn record_login_profile(
+    state: &AppState,
+    session_token: &str,
+    profile: TapTapProfile,
+    taptap_version: Option<&str>,
+) {
+    if !AppConfig::global().player_profile.enabled || profile.is_empty() {
+        return;
+    }
+    let Some(storage) = state.stats_storage.as_ref() else {
+        return;
+    };
+    let Some(user_hash) = user_hash_for_session_token(session_token) else {
+        return;
+    };
+    let row = to_row(profile, taptap_version, now_ms());
+    if let Err(e) = storage.upsert_taptap_profile(&user_hash, &row).await {
+        tracing::warn!(err = %e, "record taptap profile failed");
+    }
+}
+
+/// 读取玩家资料：缓存未过期直接返回；否则在有 sessionToken 时经 users/me 获取并回写。
+///
+/// 未启用时不读写缓存、每次实时获取；无缓存且无法获取时返回 `None`，获取失败时返回过期的缓存。
+pub(crate) async fn resolve_player_profile(
+    state: &AppState,
+    user_hash: Option<&str>,
+    session_token: Option<&str>,
+    taptap_version: Option<&str>,
+) -> Option<TapTapProfileRow> {
+    let cfg = &AppConfig::global().player_profile;
+    let storage = if cfg.enabled {
+        state.stats_storage.as_ref().zip(user_hash)
+    } else {
+        None
+    };
+    let cached = match storage {
+        Some((s, hash)) => s.get_taptap_profile(hash).await.unwrap_or_else(|e| {
+            tracing::warn!(err = %e, "load taptap profile failed");
+            None
+        }),
+        None => None,
+    };
+    let now = now_ms();
+    if cached
+        .as_ref()
+        .is_some_and(|row| is_profile_fresh(row, now, cfg.refresh_secs))
+    {
+        return cached;
+    }
+    let Some(token) = session_token.filter(|t| !t.is_empty()) else {
+        return cached;
+    };
+    let version =
+        taptap_version.or_else(|| cached.as_ref().and_then(|r| r.taptap_version.as_deref()));
+    match state.taptap_client.fetch_profile(token, version).await {
+        Ok(profile) if !profile.is_empty() => {
+            let row = to_row(profile, version, now);
+            if let Some((s, hash)) = storage
+                && let Err(e) = s.upsert_taptap_profile(hash, &row).await
+            {
+                tracing::warn!(err = %e, "refresh taptap profile failed");
+            }
+            Some(row)
+        }
+        Ok(_) => cached,
+        Err(e) => {
+            tracing::debug!(err = %e, "fetch taptap profile failed, keep cached");
+            cached
+        }
+    }
+}
+
+/// `/auth/me` 使用：未启用或取不到资料时返回 404
+pub(crate) async fn require_player_profile(
+    state: &AppState,
+    user_hash: &str,
+    session_token: Option<&str>,
+    taptap_version: Option<&str>,
+) -> Result<TapTapProfileRow, AppError> {
+    if !AppConfig::global().player_profile.enabled {
+        return Err(AppError::Search(crate::error::SearchError::NotFound));
+    }
+    resolve_player_profile(state, Some(user_hash), session_token, taptap_version)
+        .await
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(fetched_at_ms: i64) -> TapTapProfileRow {
+        TapTapProfileRow {
+            nickname: Some("Alice".into()),
+            avatar_url: None,
+            taptap_version: None,
+            fetched_at_ms,
+        }
+    }
+
+    #[test]
+    fn freshness_follows_refresh_interval() {
+        let fetched = 1_700_000_000_000;
+        assert!(is_profile_fresh(&row(fetched), fetched + 59_999, 60));
+        assert!(!is_profile_fresh(&row(fetched), fetched + 60_000, 60));
+        assert!(is_profile_fresh(&row(fetched), fetched + 1, u64::MAX));
+    }
+
+    #[test]
+    fn blank_profile_fields_are_dropped() {
+        let p = TapTapProfile::new(Some("  ".into()), Some(" https://a/b.png ".into()));
+        assert!(p.nickname.is_none());
+        assert_eq!(p.avatar_url.as_deref(), Some("https://a/b.png"));
+        assert!(TapTapProfile::new(None, Some(String::new())).is_empty());
+    }
+}
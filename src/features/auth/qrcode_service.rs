@@ -1,11 +1,17 @@
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use moka::future::Cache;
+use tokio::sync::broadcast;
 
+use crate::error::AppError;
+
+use super::client::POLL_FOR_TOKEN_BUDGET;
+use super::handler::QrCodeStatusResponse;
 use super::models::SessionData;
+use super::qrcode_store::{MemoryQrCodeStore, QrCodeStore};
 
 const DEFAULT_QRCODE_EXPIRES_SECS: u64 = 5 * 60;
-const DEFAULT_CACHE_TTL_SECS: u64 = 30 * 60;
+const STATUS_EVENT_CAPACITY: usize = 1024;
 
 #[derive(Debug, Clone)]
 pub enum QrCodeStatus {
@@ -23,9 +29,17 @@ pub enum QrCodeStatus {
     },
 }
 
+/// 二维码状态转换通知（Scanned / Confirmed / Expired / Error），供 SSE 推送。
+#[derive(Debug, Clone)]
+pub struct QrStatusEvent {
+    pub qr_id: String,
+    pub status: QrCodeStatusResponse,
+}
+
 #[derive(Clone)]
 pub struct QrCodeService {
-    pub cache: Cache<String, QrCodeStatus>,
+    store: Arc<dyn QrCodeStore>,
+    events: broadcast::Sender<QrStatusEvent>,
 }
 
 impl Default for QrCodeService {
@@ -35,13 +49,33 @@ impl Default for QrCodeService {
 }
 
 impl QrCodeService {
+    /// 使用进程内存储（单实例部署与测试）
     #[must_use]
     pub fn new() -> Self {
-        let cache = Cache::builder()
-            .max_capacity(10_000)
-            .time_to_live(Duration::from_secs(DEFAULT_CACHE_TTL_SECS))
-            .build();
-        Self { cache }
+        Self::with_store(Arc::new(MemoryQrCodeStore::new()))
+    }
+
+    #[must_use]
+    pub fn with_store(store: Arc<dyn QrCodeStore>) -> Self {
+        let (events, _) = broadcast::channel(STATUS_EVENT_CAPACITY);
+        Self { store, events }
+    }
+
+    /// 订阅状态转换通知（不区分 qr_id，由订阅方自行过滤）。
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<QrStatusEvent> {
+        self.events.subscribe()
+    }
+
+    /// 广播一次状态转换；无订阅者时直接丢弃。
+    pub fn publish(&self, qr_id: &str, status: &QrCodeStatusResponse) {
+        if self.events.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.events.send(QrStatusEvent {
+            qr_id: qr_id.to_string(),
+            status: status.clone(),
+        });
     }
 
     pub async fn set_pending(
@@ -52,14 +86,14 @@ impl QrCodeService {
         interval_secs: u64,
         expires_in_secs: Option<u64>,
         version: Option<String>,
-    ) {
+    ) -> Result<(), AppError> {
         let now = Instant::now();
         let next_poll_at = now;
         let expires_secs = expires_in_secs.unwrap_or(DEFAULT_QRCODE_EXPIRES_SECS);
         let expires_at = now + Duration::from_secs(expires_secs);
-        self.cache
-            .insert(
-                qr_id,
+        self.store
+            .put(
+                &qr_id,
                 QrCodeStatus::Pending {
                     device_code,
                     device_id,
@@ -69,45 +103,75 @@ impl QrCodeService {
                     version,
                 },
             )
-            .await;
+            .await
     }
 
-    pub async fn set_confirmed(&self, qr_id: &str, session_data: SessionData) {
-        self.cache
-            .insert(qr_id.to_string(), QrCodeStatus::Confirmed { session_data })
-            .await;
+    /// Pending → Confirmed；返回 false 表示已被其他调用方确认或记录已不存在。
+    pub async fn set_confirmed(
+        &self,
+        qr_id: &str,
+        session_data: SessionData,
+    ) -> Result<bool, AppError> {
+        self.store.confirm(qr_id, session_data).await
     }
 
-    pub async fn get(&self, qr_id: &str) -> Option<QrCodeStatus> {
-        self.cache.get(qr_id).await
+    pub async fn get(&self, qr_id: &str) -> Result<Option<QrCodeStatus>, AppError> {
+        self.store.get(qr_id).await
     }
 
-    pub async fn remove(&self, qr_id: &str) {
-        self.cache.invalidate(qr_id).await;
+    pub async fn remove(&self, qr_id: &str) -> Result<(), AppError> {
+        self.store.remove(qr_id).await
     }
 
+    /// 抢占本轮 TapTap 轮询。
+    ///
+    /// 租约覆盖轮询间隔加一次 `poll_for_token` 的全部上游超时，保证请求尚未返回时
+    /// 其他实例不会用同一 device code 再次轮询；本轮结束后由 [`Self::set_pending_next_poll`] 释放。
+    pub async fn claim_poll(&self, qr_id: &str, interval_secs: u64) -> Result<bool, AppError> {
+        let lease = Duration::from_secs(interval_secs) + POLL_FOR_TOKEN_BUDGET;
+        self.store.claim_poll(qr_id, lease).await
+    }
+
+    /// 本轮轮询仍未授权：把下次轮询设为 `interval_secs` 之后（同时释放租约）。
+    ///
+    /// 仅在记录仍为 Pending 时生效；已被其他调用方确认或已删除时返回 false，不覆盖现有状态。
     pub async fn set_pending_next_poll(
         &self,
         qr_id: &str,
-        device_code: String,
-        device_id: String,
         interval_secs: u64,
-        expires_at: Instant,
-        version: Option<String>,
-    ) {
-        let next_poll_at = Instant::now() + Duration::from_secs(interval_secs);
-        self.cache
-            .insert(
-                qr_id.to_string(),
-                QrCodeStatus::Pending {
-                    device_code,
-                    device_id,
-                    interval_secs,
-                    next_poll_at,
-                    expires_at,
-                    version,
-                },
-            )
-            .await;
+    ) -> Result<bool, AppError> {
+        self.store
+            .reschedule_poll(qr_id, Duration::from_secs(interval_secs))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::auth::handler::QrCodeStatusValue;
+
+    fn expired() -> QrCodeStatusResponse {
+        QrCodeStatusResponse {
+            status: QrCodeStatusValue::Expired,
+            session_token: None,
+            error_code: None,
+            message: None,
+            retry_after: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn publish_reaches_subscribers_only_after_subscribe() {
+        let svc = QrCodeService::new();
+        // 无订阅者时直接丢弃，不应积压到之后的订阅者
+        svc.publish("early", &expired());
+
+        let mut rx = svc.subscribe();
+        svc.publish("qr-1", &expired());
+        let ev = rx.recv().await.expect("event");
+        assert_eq!(ev.qr_id, "qr-1");
+        assert_eq!(ev.status.status, QrCodeStatusValue::Expired);
+        assert!(rx.try_recv().is_err());
     }
 }
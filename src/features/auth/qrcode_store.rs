@@ -0,0 +1,553 @@
+//! 二维码登录状态存储
+//!
+//! 默认的内存实现只在单实例内有效；多实例部署（或需要跨重启保留扫码进度）时
+//! 使用 SQLite 实现，与统计库共用连接池。两种实现都保证：
+//! - 同一轮 TapTap 轮询只会被一个调用方抢到（`claim_poll`），device code 不会被重复消费；
+//! - Pending → Confirmed 为原子转换（`confirm`）；
+//! - 推后下次轮询只作用于仍为 Pending 的记录（`reschedule_poll`），不会覆盖已确认或已删除的状态。
+//!
+//! SQLite 实现中会话令牌以 AES-GCM 加密后落库（AAD 绑定二维码 ID），库中不保存明文。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures_util::future::BoxFuture;
+use moka::future::Cache;
+use tokio::sync::Mutex;
+
+use crate::error::AppError;
+use crate::stats_contract::{QrCodeLoginRow, StatsStorage};
+
+use super::bearer::{open_qrcode_session_token, seal_qrcode_session_token};
+use super::models::SessionData;
+use super::qrcode_service::QrCodeStatus;
+
+/// 内存实现的条目存活时间（覆盖二维码有效期与确认后的取回窗口）
+const MEMORY_TTL_SECS: u64 = 30 * 60;
+/// SQLite 实现中过期记录的保留宽限
+const SQLITE_CLEANUP_GRACE_MS: i64 = 30 * 60 * 1000;
+
+pub trait QrCodeStore: Send + Sync {
+    fn put<'a>(
+        &'a self,
+        qr_id: &'a str,
+        status: QrCodeStatus,
+    ) -> BoxFuture<'a, Result<(), AppError>>;
+
+    fn get<'a>(&'a self, qr_id: &'a str) -> BoxFuture<'a, Result<Option<QrCodeStatus>, AppError>>;
+
+    fn remove<'a>(&'a self, qr_id: &'a str) -> BoxFuture<'a, Result<(), AppError>>;
+
+    /// 抢占本轮轮询：仍为 Pending、未过期且已到 `next_poll_at` 时，把下次轮询推后 `lease`
+    /// 并返回 true；否则（其他实例正在轮询、已确认或已过期）返回 false。
+    fn claim_poll<'a>(
+        &'a self,
+        qr_id: &'a str,
+        lease: Duration,
+    ) -> BoxFuture<'a, Result<bool, AppError>>;
+
+    /// Pending → Confirmed 原子转换；记录已不是 Pending 时返回 false。
+    fn confirm<'a>(
+        &'a self,
+        qr_id: &'a str,
+        session_data: SessionData,
+    ) -> BoxFuture<'a, Result<bool, AppError>>;
+
+    /// 仍为 Pending 时把下次轮询设为 `after` 之后并返回 true；否则不做修改并返回 false。
+    fn reschedule_poll<'a>(
+        &'a self,
+        qr_id: &'a str,
+        after: Duration,
+    ) -> BoxFuture<'a, Result<bool, AppError>>;
+}
+
+/// 进程内实现（moka 缓存），用于单实例部署与测试
+pub struct MemoryQrCodeStore {
+    cache: Cache<String, QrCodeStatus>,
+    /// 串行化“读-判断-写”的状态转换
+    transition: Mutex<()>,
+}
+
+impl Default for MemoryQrCodeStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryQrCodeStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(Duration::from_secs(MEMORY_TTL_SECS))
+                .build(),
+            transition: Mutex::new(()),
+        }
+    }
+}
+
+impl QrCodeStore for MemoryQrCodeStore {
+    fn put<'a>(
+        &'a self,
+        qr_id: &'a str,
+        status: QrCodeStatus,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            self.cache.insert(qr_id.to_string(), status).await;
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, qr_id: &'a str) -> BoxFuture<'a, Result<Option<QrCodeStatus>, AppError>> {
+        Box::pin(async move { Ok(self.cache.get(qr_id).await) })
+    }
+
+    fn remove<'a>(&'a self, qr_id: &'a str) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            let _guard = self.transition.lock().await;
+            self.cache.invalidate(qr_id).await;
+            Ok(())
+        })
+    }
+
+    fn claim_poll<'a>(
+        &'a self,
+        qr_id: &'a str,
+        lease: Duration,
+    ) -> BoxFuture<'a, Result<bool, AppError>> {
+        Box::pin(async move {
+            let _guard = self.transition.lock().await;
+            let Some(QrCodeStatus::Pending {
+                device_code,
+                device_id,
+                interval_secs,
+                next_poll_at,
+                expires_at,
+                version,
+            }) = self.cache.get(qr_id).await
+            else {
+                return Ok(false);
+            };
+            let now = Instant::now();
+            if now < next_poll_at || now >= expires_at {
+                return Ok(false);
+            }
+            self.cache
+                .insert(
+                    qr_id.to_string(),
+                    QrCodeStatus::Pending {
+                        device_code,
+                        device_id,
+                        interval_secs,
+                        next_poll_at: now + lease,
+                        expires_at,
+                        version,
+                    },
+                )
+                .await;
+            Ok(true)
+        })
+    }
+
+    fn confirm<'a>(
+        &'a self,
+        qr_id: &'a str,
+        session_data: SessionData,
+    ) -> BoxFuture<'a, Result<bool, AppError>> {
+        Box::pin(async move {
+            let _guard = self.transition.lock().await;
+            if !matches!(
+                self.cache.get(qr_id).await,
+                Some(QrCodeStatus::Pending { .. })
+            ) {
+                return Ok(false);
+            }
+            self.cache
+                .insert(qr_id.to_string(), QrCodeStatus::Confirmed { session_data })
+                .await;
+            Ok(true)
+        })
+    }
+
+    fn reschedule_poll<'a>(
+        &'a self,
+        qr_id: &'a str,
+        after: Duration,
+    ) -> BoxFuture<'a, Result<bool, AppError>> {
+        Box::pin(async move {
+            let _guard = self.transition.lock().await;
+            let Some(QrCodeStatus::Pending {
+                device_code,
+                device_id,
+                interval_secs,
+                expires_at,
+                version,
+                ..
+            }) = self.cache.get(qr_id).await
+            else {
+                return Ok(false);
+            };
+            self.cache
+                .insert(
+                    qr_id.to_string(),
+                    QrCodeStatus::Pending {
+                        device_code,
+                        device_id,
+                        interval_secs,
+                        next_poll_at: Instant::now() + after,
+                        expires_at,
+                        version,
+                    },
+                )
+                .await;
+            Ok(true)
+        })
+    }
+}
+
+/// SQLite 实现：状态落在统计库的 `qrcode_login` 表，多实例共享
+pub struct SqliteQrCodeStore {
+    storage: Arc<StatsStorage>,
+}
+
+impl SqliteQrCodeStore {
+    #[must_use]
+    pub fn new(storage: Arc<StatsStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+}
+
+fn duration_ms(d: Duration) -> i64 {
+    i64::try_from(d.as_millis()).unwrap_or(i64::MAX)
+}
+
+/// 进程内 `Instant` 与 Unix 毫秒互转：以“当前时刻”为锚点平移
+fn instant_to_epoch_ms(t: Instant, now: Instant, now_ms: i64) -> i64 {
+    if t >= now {
+        now_ms.saturating_add(duration_ms(t - now))
+    } else {
+        now_ms.saturating_sub(duration_ms(now - t))
+    }
+}
+
+fn epoch_ms_to_instant(ms: i64, now: Instant, now_ms: i64) -> Instant {
+    let delta = Duration::from_millis(ms.abs_diff(now_ms));
+    if ms >= now_ms {
+        now + delta
+    } else {
+        now.checked_sub(delta).unwrap_or(now)
+    }
+}
+
+fn status_to_row(
+    qr_id: &str,
+    status: QrCodeStatus,
+    now: Instant,
+    now_ms: i64,
+) -> Result<QrCodeLoginRow, AppError> {
+    Ok(match status {
+        QrCodeStatus::Pending {
+            device_code,
+            device_id,
+            interval_secs,
+            next_poll_at,
+            expires_at,
+            version,
+        } => QrCodeLoginRow {
+            status: "pending".into(),
+            device_code,
+            device_id,
+            interval_secs: i64::try_from(interval_secs).unwrap_or(i64::MAX),
+            next_poll_at_ms: instant_to_epoch_ms(next_poll_at, now, now_ms),
+            expires_at_ms: instant_to_epoch_ms(expires_at, now, now_ms),
+            version,
+            session_token: None,
+        },
+        QrCodeStatus::Scanned => QrCodeLoginRow {
+            status: "scanned".into(),
+            device_code: String::new(),
+            device_id: String::new(),
+            interval_secs: 0,
+            next_poll_at_ms: now_ms,
+            expires_at_ms: now_ms.saturating_add(SQLITE_CLEANUP_GRACE_MS),
+            version: None,
+            session_token: None,
+        },
+        QrCodeStatus::Confirmed { session_data } => QrCodeLoginRow {
+            status: "confirmed".into(),
+            device_code: String::new(),
+            device_id: String::new(),
+            interval_secs: 0,
+            next_poll_at_ms: now_ms,
+            expires_at_ms: now_ms.saturating_add(SQLITE_CLEANUP_GRACE_MS),
+            version: None,
+            session_token: Some(seal_qrcode_session_token(
+                &session_data.session_token,
+                qr_id,
+            )?),
+        },
+    })
+}
+
+fn row_to_status(
+    qr_id: &str,
+    row: QrCodeLoginRow,
+    now: Instant,
+    now_ms: i64,
+) -> Option<QrCodeStatus> {
+    match row.status.as_str() {
+        "pending" => Some(QrCodeStatus::Pending {
+            device_code: row.device_code,
+            device_id: row.device_id,
+            interval_secs: u64::try_from(row.interval_secs).unwrap_or(0),
+            next_poll_at: epoch_ms_to_instant(row.next_poll_at_ms, now, now_ms),
+            expires_at: epoch_ms_to_instant(row.expires_at_ms, now, now_ms),
+            version: row.version,
+        }),
+        "scanned" => Some(QrCodeStatus::Scanned),
+        // 解密失败（旧版明文记录或密钥已轮换）时按记录不存在处理，用户重新扫码即可
+        "confirmed" => match open_qrcode_session_token(row.session_token.as_deref()?, qr_id) {
+            Ok(session_token) => Some(QrCodeStatus::Confirmed {
+                session_data: SessionData { session_token },
+            }),
+            Err(e) => {
+                tracing::warn!(err = %e, "二维码登录记录中的会话令牌无法解密，已忽略");
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+impl QrCodeStore for SqliteQrCodeStore {
+    fn put<'a>(
+        &'a self,
+        qr_id: &'a str,
+        status: QrCodeStatus,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            let now_ms = now_epoch_ms();
+            let row = status_to_row(qr_id, status, Instant::now(), now_ms)?;
+            self.storage
+                .upsert_qrcode_login(qr_id, &row, now_ms)
+                .await?;
+            if let Err(e) = self
+                .storage
+                .maybe_cleanup_expired_qrcode_logins(now_ms, SQLITE_CLEANUP_GRACE_MS)
+                .await
+            {
+                tracing::warn!(err = %e, "清理过期二维码登录记录失败");
+            }
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, qr_id: &'a str) -> BoxFuture<'a, Result<Option<QrCodeStatus>, AppError>> {
+        Box::pin(async move {
+            let row = self.storage.get_qrcode_login(qr_id).await?;
+            Ok(row.and_then(|r| row_to_status(qr_id, r, Instant::now(), now_epoch_ms())))
+        })
+    }
+
+    fn remove<'a>(&'a self, qr_id: &'a str) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move { self.storage.delete_qrcode_login(qr_id).await })
+    }
+
+    fn claim_poll<'a>(
+        &'a self,
+        qr_id: &'a str,
+        lease: Duration,
+    ) -> BoxFuture<'a, Result<bool, AppError>> {
+        Box::pin(async move {
+            let now_ms = now_epoch_ms();
+            self.storage
+                .claim_qrcode_poll(qr_id, now_ms, now_ms.saturating_add(duration_ms(lease)))
+                .await
+        })
+    }
+
+    fn confirm<'a>(
+        &'a self,
+        qr_id: &'a str,
+        session_data: SessionData,
+    ) -> BoxFuture<'a, Result<bool, AppError>> {
+        Box::pin(async move {
+            let sealed = seal_qrcode_session_token(&session_data.session_token, qr_id)?;
+            self.storage
+                .confirm_qrcode_login(qr_id, &sealed, now_epoch_ms())
+                .await
+        })
+    }
+
+    fn reschedule_poll<'a>(
+        &'a self,
+        qr_id: &'a str,
+        after: Duration,
+    ) -> BoxFuture<'a, Result<bool, AppError>> {
+        Box::pin(async move {
+            let now_ms = now_epoch_ms();
+            self.storage
+                .reschedule_qrcode_poll(qr_id, now_ms.saturating_add(duration_ms(after)), now_ms)
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+
+    fn pending(next_poll_in: Duration, expires_in: Duration) -> QrCodeStatus {
+        let now = Instant::now();
+        QrCodeStatus::Pending {
+            device_code: "dc".into(),
+            device_id: "di".into(),
+            interval_secs: 5,
+            next_poll_at: now + next_poll_in,
+            expires_at: now + expires_in,
+            version: Some("cn".into()),
+        }
+    }
+
+    fn session() -> SessionData {
+        SessionData {
+            session_token: "r:token".into(),
+        }
+    }
+
+    async fn assert_claim_and_confirm_are_exclusive(a: &dyn QrCodeStore, b: &dyn QrCodeStore) {
+        a.put("qr", pending(Duration::ZERO, Duration::from_mins(1)))
+            .await
+            .unwrap();
+
+        // 同一轮轮询只有一方能抢到
+        assert!(a.claim_poll("qr", Duration::from_secs(5)).await.unwrap());
+        assert!(!b.claim_poll("qr", Duration::from_secs(5)).await.unwrap());
+        assert!(matches!(
+            b.get("qr").await.unwrap(),
+            Some(QrCodeStatus::Pending { next_poll_at, .. }) if next_poll_at > Instant::now()
+        ));
+
+        // Pending → Confirmed 只发生一次，确认后推后轮询不会改回 Pending
+        assert!(b.reschedule_poll("qr", Duration::ZERO).await.unwrap());
+        assert!(a.confirm("qr", session()).await.unwrap());
+        assert!(!b.confirm("qr", session()).await.unwrap());
+        assert!(!b.claim_poll("qr", Duration::from_secs(5)).await.unwrap());
+        assert!(!b.reschedule_poll("qr", Duration::ZERO).await.unwrap());
+        match b.get("qr").await.unwrap() {
+            Some(QrCodeStatus::Confirmed { session_data }) => {
+                assert_eq!(session_data.session_token, "r:token");
+            }
+            other => panic!("unexpected status: {other:?}"),
+        }
+
+        b.remove("qr").await.unwrap();
+        assert!(a.get("qr").await.unwrap().is_none());
+        // 已删除的记录不会被推后轮询重新创建
+        assert!(!a.reschedule_poll("qr", Duration::ZERO).await.unwrap());
+        assert!(b.get("qr").await.unwrap().is_none());
+
+        // 已过期的二维码不可再抢占轮询
+        a.put("old", pending(Duration::ZERO, Duration::ZERO))
+            .await
+            .unwrap();
+        assert!(!b.claim_poll("old", Duration::from_secs(5)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn memory_store_transitions_are_exclusive() {
+        let store = MemoryQrCodeStore::new();
+        assert_claim_and_confirm_are_exclusive(&store, &store).await;
+    }
+
+    /// SQLite 实现需要会话凭证加密密钥
+    fn init_sealing_secret() {
+        static INIT: OnceLock<()> = OnceLock::new();
+        INIT.get_or_init(|| {
+            // SAFETY: 仅在测试初始化阶段设置一次
+            unsafe { std::env::set_var("APP_SESSION_AUTH_EMBED_SECRET", "qrcode-store-test") };
+        });
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_is_shared_between_instances() {
+        init_sealing_secret();
+        let path = format!("./resources/test_qrcode_store_{}.db", uuid::Uuid::new_v4());
+        let first = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+        first.init_schema().await.unwrap();
+        // 模拟两个实例各自持有连接池
+        let second = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+        let a = SqliteQrCodeStore::new(Arc::new(first));
+        let b = SqliteQrCodeStore::new(Arc::new(second));
+
+        assert_claim_and_confirm_are_exclusive(&a, &b).await;
+
+        drop((a, b));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_keeps_session_token_sealed() {
+        init_sealing_secret();
+        let path = format!("./resources/test_qrcode_store_{}.db", uuid::Uuid::new_v4());
+        let storage = Arc::new(StatsStorage::connect_sqlite(&path, false).await.unwrap());
+        storage.init_schema().await.unwrap();
+        let store = SqliteQrCodeStore::new(storage.clone());
+
+        store
+            .put("qr", pending(Duration::ZERO, Duration::from_mins(1)))
+            .await
+            .unwrap();
+        assert!(store.confirm("qr", session()).await.unwrap());
+        store
+            .put(
+                "other",
+                QrCodeStatus::Confirmed {
+                    session_data: session(),
+                },
+            )
+            .await
+            .unwrap();
+
+        for qr_id in ["qr", "other"] {
+            let raw = storage.get_qrcode_login(qr_id).await.unwrap().unwrap();
+            let sealed = raw.session_token.unwrap();
+            assert!(!sealed.contains("r:token"));
+            assert!(matches!(
+                store.get(qr_id).await.unwrap(),
+                Some(QrCodeStatus::Confirmed { session_data }) if session_data.session_token == "r:token"
+            ));
+        }
+
+        // 密文绑定二维码 ID，挪到其他记录上无法解密
+        let moved = storage.get_qrcode_login("qr").await.unwrap().unwrap();
+        storage
+            .upsert_qrcode_login("moved", &moved, now_epoch_ms())
+            .await
+            .unwrap();
+        assert!(store.get("moved").await.unwrap().is_none());
+
+        drop((store, storage));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn instant_epoch_round_trip_keeps_offsets() {
+        let now = Instant::now();
+        let now_ms = 1_700_000_000_000;
+        let later = now + Duration::from_secs(90);
+        let ms = instant_to_epoch_ms(later, now, now_ms);
+        assert_eq!(ms, now_ms + 90_000);
+        assert_eq!(epoch_ms_to_instant(ms, now, now_ms), later);
+        assert_eq!(instant_to_epoch_ms(now, now, now_ms), now_ms);
+    }
+}
@@ -11,12 +11,16 @@ use crate::state::AppState;
 #[cfg(test)]
 use crate::save_contract::{Difficulty, DifficultyRecord};
 
+mod avatar;
 pub(crate) mod bn;
 mod bn_compute;
 mod context;
 mod display;
+pub(crate) mod leaderboard;
+pub(crate) mod login_qrcode;
 mod nickname;
 mod output;
+pub(crate) mod rks_history;
 mod runtime;
 mod save_flow;
 mod score;
@@ -26,7 +30,10 @@ pub(crate) mod user_bn;
 mod user_bn_compute;
 
 pub use bn::render_bn;
+pub use leaderboard::render_leaderboard;
+pub use login_qrcode::render_login_qrcode;
 pub use output::ImageQueryOpts;
+pub use rks_history::render_rks_history;
 pub use song::render_song;
 pub use user_bn::render_bn_user;
 
@@ -38,6 +45,8 @@ use context::{
 #[cfg(test)]
 use display::{format_data_string, parse_challenge_rank, parse_update_time_or_now};
 #[cfg(test)]
+use leaderboard::{LeaderboardImageQuery, leaderboard_entry_from_item};
+#[cfg(test)]
 use output::{ImageOutputCacheSpec, content_type_from_fmt_code, format_code};
 #[cfg(test)]
 use save_flow::save_updated_cache_version;
@@ -62,7 +71,10 @@ pub fn create_image_router() -> Router<AppState> {
     let mut router = Router::new()
         .route("/image/bn", post(render_bn))
         .route("/image/song", post(render_song))
-        .route("/image/bn/user", post(render_bn_user));
+        .route("/image/bn/user", post(render_bn_user))
+        .route("/image/leaderboard", get(render_leaderboard))
+        .route("/image/qrcode", post(render_login_qrcode))
+        .route("/image/rks/history", post(render_rks_history));
 
     // 签名验证端点（仅在配置 public_verify=true 时可用；
     // 也可以在路由层始终注册，handler 内部根据配置决定是否响应）
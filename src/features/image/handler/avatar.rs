@@ -0,0 +1,152 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use base64::Engine;
+use moka::future::Cache;
+
+use crate::config::AppConfig;
+
+/// 头像下载超时：渲染链路上的尽力而为请求，宁可不显示也不拖慢出图
+const AVATAR_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+const AVATAR_CACHE_TTL: Duration = Duration::from_hours(1);
+const AVATAR_CACHE_CAPACITY: u64 = 1024;
+
+/// 按 URL 缓存头像 data URI；失败结果同样缓存（None），避免反复请求坏链接
+fn avatar_cache() -> &'static Cache<String, Option<Arc<str>>> {
+    static CACHE: OnceLock<Cache<String, Option<Arc<str>>>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Cache::builder()
+            .max_capacity(AVATAR_CACHE_CAPACITY)
+            .time_to_live(AVATAR_CACHE_TTL)
+            .build()
+    })
+}
+
+/// 按文件头识别栅格格式（不信任上游 Content-Type，也拒绝 SVG 等可执行内容）
+fn sniff_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+async fn download_avatar(url: &str, max_bytes: usize) -> Option<Arc<str>> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return None;
+    }
+    let client = crate::http::client_default().ok()?;
+    let mut resp = client
+        .get(parsed)
+        .timeout(AVATAR_FETCH_TIMEOUT)
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success()
+        || resp
+            .content_length()
+            .is_some_and(|len| len > max_bytes as u64)
+    {
+        return None;
+    }
+    // 未声明长度（chunked）时边读边计数，超限立即放弃，不把整个响应读进内存
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await.ok()? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let mime = sniff_image_mime(&bytes)?;
+    Some(Arc::from(format!(
+        "data:{mime};base64,{}",
+        base64::prelude::BASE64_STANDARD.encode(&bytes)
+    )))
+}
+
+/// 把头像 URL 转为内嵌 data URI（栅格化时无法访问外链）；下载失败/超限/格式不支持时返回 None
+pub(super) async fn avatar_data_uri(url: &str) -> Option<Arc<str>> {
+    let max_bytes = AppConfig::global().player_profile.avatar_max_bytes;
+    avatar_cache()
+        .get_with(url.to_string(), download_avatar(url, max_bytes))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_accepts_raster_formats_only() {
+        assert_eq!(
+            sniff_image_mime(b"\x89PNG\r\n\x1a\n\0\0"),
+            Some("image/png")
+        );
+        assert_eq!(
+            sniff_image_mime(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            sniff_image_mime(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(sniff_image_mime(b"<svg xmlns="), None);
+        assert_eq!(sniff_image_mime(b""), None);
+    }
+
+    /// 不带 Content-Length 的响应：先发 PNG 头，再按块持续发送 `body_len` 字节
+    async fn start_streaming_png_server(body_len: usize) -> std::net::SocketAddr {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind tcp listener");
+        let addr = listener.local_addr().expect("local addr");
+
+        tokio::spawn(async move {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nConnection: close\r\n\r\n";
+            if socket.write_all(head.as_bytes()).await.is_err() {
+                return;
+            }
+            let mut body = b"\x89PNG\r\n\x1a\n".to_vec();
+            body.resize(body_len, 0);
+            for chunk in body.chunks(256) {
+                // 客户端超限断开后写入失败即停止
+                if socket.write_all(chunk).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn download_stops_once_stream_exceeds_limit() {
+        let addr = start_streaming_png_server(64 * 1024).await;
+        assert!(
+            download_avatar(&format!("http://{addr}/a.png"), 4096)
+                .await
+                .is_none()
+        );
+
+        let addr = start_streaming_png_server(1024).await;
+        let uri = download_avatar(&format!("http://{addr}/a.png"), 4096)
+            .await
+            .expect("within limit");
+        assert!(uri.starts_with("data:image/png;base64,"));
+    }
+}
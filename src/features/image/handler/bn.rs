@@ -24,7 +24,7 @@ use super::{
         derive_image_user_identity, ensure_image_user_not_banned, image_cache_enabled,
         image_footer_text,
     },
-    nickname::resolve_display_name,
+    nickname::resolve_player_display,
     output::{
         ImageOutputCacheSpec, ImageQueryOpts, SvgRenderOptions, image_content_headers,
         render_svg_output_bytes, validate_image_query_opts,
@@ -97,6 +97,8 @@ pub async fn render_bn(
 
     // 全流程计时：从请求进入到返回响应
     let t_total = Instant::now();
+    // 本次请求固定使用同一份定数/目录快照（热更新不影响在途请求）
+    let game = state.game_data();
     // 存档获取耗时（含认证源构造 + 解密）
     let t_save = Instant::now();
     let t_auth_start = Instant::now();
@@ -122,9 +124,10 @@ pub async fn render_bn(
         derive_image_user_identity(&req.auth, &bearer_state)?;
     ensure_image_user_not_banned(&state, user_hash_for_cache.as_deref()).await?;
     let cache_key = if cache_enabled {
-        user_hash_for_cache
-            .as_ref()
-            .map(|user_hash| output.bn_cache_key(user_hash, req.n, &updated_for_cache, req.theme))
+        user_hash_for_cache.as_ref().map(|user_hash| {
+            let key = output.bn_cache_key(user_hash, req.n, &updated_for_cache, req.theme);
+            format!("d{}:{key}", game.version)
+        })
     } else {
         None
     };
@@ -183,7 +186,7 @@ pub async fn render_bn(
     }
 
     // cache miss：下载/解密/解析存档本体
-    let parsed = decrypt_image_save_from_meta(meta, state.chart_constants.clone()).await?;
+    let parsed = decrypt_image_save_from_meta(meta, game.chart_constants.clone()).await?;
     let save_ms = duration_ms_i64(t_save.elapsed());
 
     // 扁平化为渲染记录 + 排序与推分预计算耗时
@@ -201,8 +204,8 @@ pub async fn render_bn(
         flatten_ms,
     } = {
         // 逻辑阶段（扁平化/排序/推分/统计）属于 CPU 密集 + 大量分配，避免阻塞 Tokio worker。
-        let chart_constants = state.chart_constants.clone();
-        let song_catalog = state.song_catalog.clone();
+        let chart_constants = game.chart_constants.clone();
+        let song_catalog = game.song_catalog.clone();
         let join = tokio::task::spawn_blocking(move || {
             bn_compute::build_bn_compute_output(BnComputeInput {
                 parsed,
@@ -216,21 +219,24 @@ pub async fn render_bn(
     };
 
     let t_nickname_start = Instant::now();
-    // 优先级：请求体昵称 > users/me 昵称 > 默认
-    let (display_name, nickname_ms) = resolve_display_name(
+    // 优先级：请求体昵称 > TapTap 资料昵称 > 默认；头像来自 TapTap 资料
+    let (player, nickname_ms) = resolve_player_display(
+        &state,
         req.nickname.clone(),
-        req.auth.session_token.clone(),
-        req.auth.taptap_version.as_deref(),
+        user_hash_for_cache.as_deref(),
+        &req.auth,
+        true,
     )
     .await;
     let nickname_duration = t_nickname_start.elapsed();
-    tracing::info!(target: "bestn_performance", "昵称获取完成: {}, 耗时: {:?}ms", display_name, nickname_duration.as_millis());
+    tracing::info!(target: "bestn_performance", "昵称获取完成: {}, 耗时: {:?}ms", player.name, nickname_duration.as_millis());
 
     let stats = PlayerStats {
         ap_top_3_avg,
         best_27_avg,
         real_rks: Some(exact_rks),
-        player_name: Some(display_name),
+        player_name: Some(player.name),
+        player_avatar: player.avatar,
         update_time,
         n,
         ap_top_3_scores,
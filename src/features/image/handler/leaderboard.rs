@@ -0,0 +1,229 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::AppConfig,
+    error::AppError,
+    features::image::{
+        renderer::{self, LeaderboardEntry, LeaderboardRenderData},
+        signing,
+    },
+    leaderboard_contract::{ChartTextItem, LeaderboardTopItem},
+    state::AppState,
+};
+
+use super::{
+    context::image_cache_enabled,
+    output::{
+        ImageOutputCacheSpec, ImageQueryOpts, SvgRenderOptions, image_content_headers,
+        render_svg_output_bytes, validate_image_query_opts,
+    },
+    runtime::{
+        acquire_render_permit, duration_ms_i64, spawn_blocking_svg_generation, track_image_event,
+    },
+};
+
+const DEFAULT_LEADERBOARD_IMAGE_LIMIT: i64 = 20;
+const MAX_LEADERBOARD_IMAGE_LIMIT: i64 = 50;
+
+/// 排行榜图片参数（与 [`ImageQueryOpts`] 分开解析）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LeaderboardImageQuery {
+    /// 展示条数：默认 20，最大 50
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// 名次窗口中心（1-based）；不传则渲染 TOP N
+    #[serde(default)]
+    pub rank: Option<i64>,
+}
+
+impl LeaderboardImageQuery {
+    /// 归一化为 `(起始名次, 条数)`；窗口尽量让 `rank` 居中。
+    pub(super) fn window(&self) -> (i64, i64) {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_LEADERBOARD_IMAGE_LIMIT)
+            .clamp(1, MAX_LEADERBOARD_IMAGE_LIMIT);
+        let start = self.rank.map_or(1, |r| (r.max(1) - limit / 2).max(1));
+        (start, limit)
+    }
+}
+
+fn chart_summary(items: Option<&[ChartTextItem]>) -> Option<String> {
+    let top = items?.first()?;
+    Some(format!("{} [{}] {:.2}", top.song, top.difficulty, top.rks))
+}
+
+pub(super) fn leaderboard_entry_from_item(item: &LeaderboardTopItem) -> LeaderboardEntry {
+    LeaderboardEntry {
+        rank: item.rank,
+        player_name: item
+            .alias
+            .clone()
+            .filter(|a| !a.trim().is_empty())
+            .unwrap_or_else(|| item.user.clone()),
+        rks: item.score,
+        best_chart: chart_summary(item.best_top3.as_deref()),
+        ap_chart: chart_summary(item.ap_top3.as_deref()),
+    }
+}
+
+/// 以渲染内容本身作为缓存维度：榜单任一行变化（分数/别名/公开设置）都会换 key。
+fn leaderboard_content_digest(entries: &[LeaderboardEntry]) -> String {
+    let mut hasher = Sha256::new();
+    for e in entries {
+        hasher.update(e.rank.to_le_bytes());
+        hasher.update(e.player_name.as_bytes());
+        hasher.update(e.rks.to_le_bytes());
+        hasher.update(e.best_chart.as_deref().unwrap_or_default().as_bytes());
+        hasher.update([0]);
+        hasher.update(e.ap_chart.as_deref().unwrap_or_default().as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(&hasher.finalize()[..16])
+}
+
+#[utoipa::path(
+    get,
+    path = "/image/leaderboard",
+    summary = "生成 RKS 排行榜图片",
+    description = "渲染公开玩家的 RKS 排行榜（TOP N 或以指定名次为中心的窗口）。仅包含公开资料的玩家；BestTop3/APTop3 摘要仅在玩家开启对应展示时出现。",
+    params(
+        ("limit" = Option<i64>, Query, description = "展示条数：默认 20，最大 50"),
+        ("rank" = Option<i64>, Query, description = "名次窗口中心（1-based）；不传则渲染 TOP N"),
+        ("format" = Option<String>, Query, description = "输出格式：png|jpeg|webp|svg，默认 png"),
+        ("template" = Option<String>, Query, description = "SVG 模板 ID：对应 resources/templates/image/leaderboard/{id}.svg.jinja（不传则使用内置手写 SVG）"),
+        ("width" = Option<u32>, Query, description = "目标宽度像素：按宽度同比例缩放"),
+        ("webp_quality" = Option<u8>, Query, description = "WebP 质量：1-100（仅在 format=webp 时有效，默认 80）"),
+        ("webp_lossless" = Option<bool>, Query, description = "WebP 无损模式（仅在 format=webp 时有效，默认 false）")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "图片（由 query format 决定）",
+            content(
+                (crate::features::image::types::BinaryImage = "image/png"),
+                (crate::features::image::types::BinaryImage = "image/jpeg"),
+                (crate::features::image::types::BinaryImage = "image/webp"),
+                (String = "image/svg+xml")
+            )
+        ),
+        (
+            status = 422,
+            description = "参数校验失败/渲染错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/服务器内部错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Image"
+)]
+pub async fn render_leaderboard(
+    State(state): State<AppState>,
+    Query(q): Query<ImageQueryOpts>,
+    Query(lq): Query<LeaderboardImageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let t_total = Instant::now();
+    validate_image_query_opts(&q)?;
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+
+    let (start_rank, limit) = lq.window();
+    let items =
+        crate::leaderboard_contract::load_leaderboard_window(storage, start_rank, limit).await?;
+    let entries: Vec<LeaderboardEntry> = items.iter().map(leaderboard_entry_from_item).collect();
+
+    let output = ImageOutputCacheSpec::from_query(&q, false);
+    let fmt_code = output.fmt_code;
+    let cache_key = image_cache_enabled()
+        .then(|| output.leaderboard_cache_key(start_rank, &leaderboard_content_digest(&entries)));
+    if let Some(key) = cache_key.as_ref()
+        && let Some(bytes) = state.bn_image_cache.get(key).await
+    {
+        if let Some(h) = state.stats.as_ref() {
+            track_image_event(
+                h,
+                "/image/leaderboard",
+                "image_cache",
+                "leaderboard_hit",
+                None,
+                None,
+                serde_json::json!({"cached": true, "tpl": output.tpl_code.as_str()}),
+            );
+        }
+        return Ok((
+            StatusCode::OK,
+            image_content_headers(output.content_type),
+            bytes,
+        ));
+    }
+
+    let title = if start_rank == 1 {
+        format!("RKS 排行榜 TOP {limit}")
+    } else {
+        format!("RKS 排行榜 #{start_rank} 起")
+    };
+    let render_data = LeaderboardRenderData {
+        title,
+        update_time: chrono::Utc::now(),
+        display_count: entries.len(),
+        entries,
+    };
+
+    let render_permit = acquire_render_permit(&state).await?;
+    let t_render = Instant::now();
+    let svg_options = SvgRenderOptions::from_query(None, &q);
+    let svg = spawn_blocking_svg_generation(move || {
+        renderer::generate_leaderboard_svg_string(&render_data, svg_options.template_id())
+    })
+    .await?;
+
+    let signed_svg = {
+        let signing_cfg = &AppConfig::global().image.signing;
+        if signing_cfg.is_usable() {
+            if let Some(sig) = signing::sign_svg(&svg, signing_cfg, None) {
+                signing::inject_sig_footer(&svg, &sig)
+            } else {
+                svg
+            }
+        } else {
+            svg
+        }
+    };
+
+    let (bytes, content_type) = render_svg_output_bytes(signed_svg, fmt_code, false, &q).await?;
+    let render_ms = duration_ms_i64(t_render.elapsed());
+    drop(render_permit);
+
+    if let Some(key) = cache_key {
+        state.bn_image_cache.insert(key, bytes.clone()).await;
+    }
+
+    if let Some(h) = state.stats.as_ref() {
+        let total_ms = duration_ms_i64(t_total.elapsed());
+        track_image_event(
+            h,
+            "/image/leaderboard",
+            "image_render",
+            "leaderboard",
+            Some(total_ms),
+            None,
+            serde_json::json!({"render_ms": render_ms, "bytes": bytes.len(), "fmt": fmt_code, "width": q.width, "start_rank": start_rank, "limit": limit}),
+        );
+    }
+    Ok((StatusCode::OK, image_content_headers(content_type), bytes))
+}
@@ -0,0 +1,332 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
+use base64::Engine;
+use qrcode::EcLevel;
+use serde::Deserialize;
+
+use crate::{
+    auth_contract::{QrCodeLoginTicket, issue_qrcode_login},
+    config::AppConfig,
+    error::AppError,
+    features::image::{
+        Theme,
+        renderer::{self, QrCodeRenderData},
+    },
+    state::AppState,
+};
+
+use super::{
+    output::{
+        ImageQueryOpts, format_code, image_content_headers, render_svg_output_bytes,
+        validate_image_query_opts,
+    },
+    runtime::{
+        acquire_render_permit, duration_ms_i64, spawn_blocking_svg_generation, track_image_event,
+    },
+};
+
+const DEFAULT_QRCODE_SIZE: u32 = 512;
+const MIN_QRCODE_SIZE: u32 = 128;
+const MAX_QRCODE_SIZE: u32 = 2048;
+const DEFAULT_QRCODE_MARGIN: u32 = 4;
+const MAX_QRCODE_MARGIN: u32 = 16;
+
+/// 登录二维码图片参数（与 [`ImageQueryOpts`] 分开解析）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QrCodeImageQuery {
+    /// TapTap 版本：cn 或 global
+    #[serde(default, rename = "taptapVersion")]
+    pub taptap_version: Option<String>,
+    /// 边长像素：128-2048，默认 512
+    #[serde(default)]
+    pub size: Option<u32>,
+    /// 静区宽度（模块数）：0-16，默认 4
+    #[serde(default)]
+    pub margin: Option<u32>,
+    /// 纠错等级：L/M/Q/H，默认 M（带 logo 时至少 Q）
+    #[serde(default)]
+    pub ec: Option<String>,
+    /// 配色主题：white（白底黑码，默认）/ black（深色底浅色码）
+    #[serde(default)]
+    pub theme: Option<Theme>,
+    /// 自定义暗模块颜色（#RGB / #RRGGBB），覆盖主题
+    #[serde(default)]
+    pub dark: Option<String>,
+    /// 自定义背景颜色（#RGB / #RRGGBB），覆盖主题
+    #[serde(default)]
+    pub light: Option<String>,
+    /// 是否在中心放置 logo（需配置 image.qrcode_logo_path）
+    #[serde(default)]
+    pub logo: Option<bool>,
+}
+
+fn parse_ec_level(raw: Option<&str>) -> Result<EcLevel, AppError> {
+    match raw.map(str::trim).filter(|s| !s.is_empty()) {
+        None => Ok(EcLevel::M),
+        Some(s) if s.eq_ignore_ascii_case("l") => Ok(EcLevel::L),
+        Some(s) if s.eq_ignore_ascii_case("m") => Ok(EcLevel::M),
+        Some(s) if s.eq_ignore_ascii_case("q") => Ok(EcLevel::Q),
+        Some(s) if s.eq_ignore_ascii_case("h") => Ok(EcLevel::H),
+        Some(s) => Err(AppError::Validation(format!(
+            "ec 无效：{s}（可选 L / M / Q / H）"
+        ))),
+    }
+}
+
+/// 规范化为小写 `#rrggbb`；只接受十六进制颜色，避免注入 SVG 属性
+fn parse_hex_color(field: &str, raw: &str) -> Result<String, AppError> {
+    let hex = raw.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::Validation(format!(
+            "{field} 必须为 #RGB 或 #RRGGBB 颜色"
+        )));
+    }
+    match hex.len() {
+        6 => Ok(format!("#{}", hex.to_ascii_lowercase())),
+        3 => Ok(hex
+            .chars()
+            .fold(String::from("#"), |mut out, c| {
+                out.push(c);
+                out.push(c);
+                out
+            })
+            .to_ascii_lowercase()),
+        _ => Err(AppError::Validation(format!(
+            "{field} 必须为 #RGB 或 #RRGGBB 颜色"
+        ))),
+    }
+}
+
+fn theme_colors(theme: Theme) -> (&'static str, &'static str) {
+    match theme {
+        Theme::White => ("#000000", "#ffffff"),
+        Theme::Black => ("#f5f5f5", "#14141f"),
+    }
+}
+
+fn logo_mime(path: &str) -> Option<&'static str> {
+    let ext = std::path::Path::new(path)
+        .extension()?
+        .to_str()?
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        _ => None,
+    }
+}
+
+/// 读取并缓存 logo 的 data URI；未配置或读取失败返回 None（失败只记录一次日志）
+fn logo_data_uri() -> Option<&'static str> {
+    static LOGO: OnceLock<Option<String>> = OnceLock::new();
+    LOGO.get_or_init(|| {
+        let path = AppConfig::global().image.qrcode_logo_path.as_deref()?;
+        let Some(mime) = logo_mime(path) else {
+            tracing::warn!(path, "二维码 logo 格式不受支持（仅 png/jpeg/webp/svg）");
+            return None;
+        };
+        match std::fs::read(path) {
+            Ok(bytes) => Some(format!(
+                "data:{mime};base64,{}",
+                base64::prelude::BASE64_STANDARD.encode(bytes)
+            )),
+            Err(e) => {
+                tracing::warn!(path, err = %e, "读取二维码 logo 失败");
+                None
+            }
+        }
+    })
+    .as_deref()
+}
+
+impl QrCodeImageQuery {
+    fn render_data(&self, content: String) -> Result<QrCodeRenderData, AppError> {
+        let size = self.size.unwrap_or(DEFAULT_QRCODE_SIZE);
+        if !(MIN_QRCODE_SIZE..=MAX_QRCODE_SIZE).contains(&size) {
+            return Err(AppError::Validation(format!(
+                "size 必须在 {MIN_QRCODE_SIZE}-{MAX_QRCODE_SIZE} 范围内"
+            )));
+        }
+        let margin = self.margin.unwrap_or(DEFAULT_QRCODE_MARGIN);
+        if margin > MAX_QRCODE_MARGIN {
+            return Err(AppError::Validation(format!(
+                "margin 不能超过 {MAX_QRCODE_MARGIN}"
+            )));
+        }
+        let (theme_dark, theme_light) = theme_colors(self.theme.unwrap_or(Theme::White));
+        let dark_color = match self.dark.as_deref() {
+            Some(c) => parse_hex_color("dark", c)?,
+            None => theme_dark.to_string(),
+        };
+        let light_color = match self.light.as_deref() {
+            Some(c) => parse_hex_color("light", c)?,
+            None => theme_light.to_string(),
+        };
+        let logo_data_uri = if self.logo.unwrap_or(false) {
+            Some(
+                logo_data_uri()
+                    .ok_or_else(|| AppError::Validation("服务端未配置二维码 logo".into()))?
+                    .to_string(),
+            )
+        } else {
+            None
+        };
+        Ok(QrCodeRenderData {
+            content,
+            size,
+            margin,
+            ec_level: parse_ec_level(self.ec.as_deref())?,
+            dark_color,
+            light_color,
+            logo_data_uri,
+        })
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/image/qrcode",
+    summary = "生成登录二维码图片",
+    description = "与 POST /auth/qrcode 相同地申请 TapTap 设备码并登记扫码登录，但直接返回二维码图片，便于只能发送图片的机器人使用。qr_id 通过响应头 X-Qr-Id 返回，之后按 /auth/qrcode/{qr_id}/status 或 /auth/qrcode/{qr_id}/events 跟进授权状态。",
+    params(
+        ("taptapVersion" = Option<String>, Query, description = "TapTap 版本：cn 或 global"),
+        ("size" = Option<u32>, Query, description = "边长像素：128-2048，默认 512"),
+        ("margin" = Option<u32>, Query, description = "静区宽度（模块数）：0-16，默认 4"),
+        ("ec" = Option<String>, Query, description = "纠错等级：L/M/Q/H，默认 M；带 logo 时低于 Q 自动提升为 H"),
+        ("theme" = Option<String>, Query, description = "配色主题：white（默认，白底黑码）/ black（深色底浅色码）"),
+        ("dark" = Option<String>, Query, description = "暗模块颜色（#RGB/#RRGGBB），覆盖主题"),
+        ("light" = Option<String>, Query, description = "背景颜色（#RGB/#RRGGBB），覆盖主题"),
+        ("logo" = Option<bool>, Query, description = "是否在中心放置 logo（需服务端配置 image.qrcode_logo_path）"),
+        ("format" = Option<String>, Query, description = "输出格式：png|jpeg|webp|svg，默认 png"),
+        ("width" = Option<u32>, Query, description = "目标宽度像素：按宽度同比例缩放（覆盖 size 的栅格输出）"),
+        ("webp_quality" = Option<u8>, Query, description = "WebP 质量：1-100（仅在 format=webp 时有效，默认 80）"),
+        ("webp_lossless" = Option<bool>, Query, description = "WebP 无损模式（仅在 format=webp 时有效，默认 false）")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "二维码图片（由 query format 决定）；响应头 X-Qr-Id 为二维码标识",
+            headers(("X-Qr-Id" = String, description = "二维码标识，用于查询授权状态")),
+            content(
+                (crate::features::image::types::BinaryImage = "image/png"),
+                (crate::features::image::types::BinaryImage = "image/jpeg"),
+                (crate::features::image::types::BinaryImage = "image/webp"),
+                (String = "image/svg+xml")
+            )
+        ),
+        (
+            status = 422,
+            description = "参数校验失败（taptapVersion/size/margin/ec/颜色无效，或未配置 logo）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 502,
+            description = "上游网络错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "服务器内部错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Image"
+)]
+pub async fn render_login_qrcode(
+    State(state): State<AppState>,
+    Query(q): Query<ImageQueryOpts>,
+    Query(qq): Query<QrCodeImageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let t_total = Instant::now();
+    validate_image_query_opts(&q)?;
+    // 先校验全部参数，避免参数错误时白白申请设备码
+    qq.render_data(String::new())?;
+
+    let QrCodeLoginTicket {
+        qr_id,
+        verification_url,
+    } = issue_qrcode_login(&state, qq.taptap_version.as_deref()).await?;
+    let render_data = qq.render_data(verification_url)?;
+
+    let fmt_code = format_code(&q);
+    let render_permit = acquire_render_permit(&state).await?;
+    let t_render = Instant::now();
+    let svg =
+        spawn_blocking_svg_generation(move || renderer::generate_qrcode_svg_string(&render_data))
+            .await?;
+    let (bytes, content_type) = render_svg_output_bytes(svg, fmt_code, false, &q).await?;
+    let render_ms = duration_ms_i64(t_render.elapsed());
+    drop(render_permit);
+
+    if let Some(h) = state.stats.as_ref() {
+        track_image_event(
+            h,
+            "/image/qrcode",
+            "image_render",
+            "login_qrcode",
+            Some(duration_ms_i64(t_total.elapsed())),
+            None,
+            serde_json::json!({"render_ms": render_ms, "bytes": bytes.len(), "fmt": fmt_code, "width": q.width, "logo": qq.logo.unwrap_or(false)}),
+        );
+    }
+
+    let mut headers = image_content_headers(content_type);
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if let Ok(v) = HeaderValue::from_str(&qr_id) {
+        headers.insert("x-qr-id", v);
+    }
+    Ok((StatusCode::OK, headers, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_normalized_and_validated() {
+        assert_eq!(parse_hex_color("dark", "#ABC").unwrap(), "#aabbcc");
+        assert_eq!(parse_hex_color("dark", "1a2B3c").unwrap(), "#1a2b3c");
+        assert!(parse_hex_color("dark", "#12345").is_err());
+        assert!(parse_hex_color("dark", "red\" onload=\"x").is_err());
+    }
+
+    #[test]
+    fn render_data_applies_theme_and_bounds() {
+        let q = QrCodeImageQuery {
+            theme: Some(Theme::Black),
+            light: Some("#000".into()),
+            ec: Some("q".into()),
+            ..Default::default()
+        };
+        let data = q.render_data("x".into()).unwrap();
+        assert_eq!(data.dark_color, "#f5f5f5");
+        assert_eq!(data.light_color, "#000000");
+        assert_eq!(data.ec_level, EcLevel::Q);
+        assert_eq!(
+            (data.size, data.margin),
+            (DEFAULT_QRCODE_SIZE, DEFAULT_QRCODE_MARGIN)
+        );
+
+        let too_big = QrCodeImageQuery {
+            size: Some(MAX_QRCODE_SIZE + 1),
+            ..Default::default()
+        };
+        assert!(too_big.render_data("x".into()).is_err());
+        let bad_ec = QrCodeImageQuery {
+            ec: Some("X".into()),
+            ..Default::default()
+        };
+        assert!(bad_ec.render_data("x".into()).is_err());
+    }
+}
@@ -1,50 +1,57 @@
 use std::time::Instant;
 
+use crate::auth_contract::UnifiedSaveRequest;
 use crate::config::AppConfig;
+use crate::state::AppState;
 
+use super::avatar::avatar_data_uri;
 use super::runtime::duration_ms_i64;
 
-pub(super) async fn resolve_display_name(
-    nickname: Option<String>,
-    session_token: Option<String>,
-    taptap_version: Option<&str>,
-) -> (String, i64) {
-    if let Some(name) = nickname {
-        return (name, 0);
-    }
-
-    if let Some(token) = session_token {
-        let started_at = Instant::now();
-        let name = fetch_nickname(&token, taptap_version)
-            .await
-            .unwrap_or_else(|| "Phigros Player".into());
-        return (name, duration_ms_i64(started_at.elapsed()));
-    }
+const DEFAULT_DISPLAY_NAME: &str = "Phigros Player";
 
-    ("Phigros Player".into(), 0)
+pub(super) struct PlayerDisplay {
+    pub(super) name: String,
+    /// 头像 data URI；未启用资料缓存、无头像或下载失败时为 None
+    pub(super) avatar: Option<String>,
 }
 
-/// 从 LeanCloud users/me 获取昵称（复用 phigros.cxx 的请求头部）
-async fn fetch_nickname(session_token: &str, taptap_version: Option<&str>) -> Option<String> {
-    #[derive(serde::Deserialize)]
-    struct UserMe {
-        nickname: Option<String>,
-    }
-    let tap_config = AppConfig::global().taptap.resolve(taptap_version);
-    let url = format!("{}/users/me", tap_config.leancloud_base_url);
-    // 复用全局连接池，避免每次请求创建 Client。
-    let client = crate::http::client_default().ok()?;
-    let resp = client
-        .get(url)
-        .header("X-LC-Id", &tap_config.leancloud_app_id)
-        .header("X-LC-Key", &tap_config.leancloud_app_key)
-        .header("X-LC-Session", session_token)
-        .send()
-        .await
-        .ok()?;
-    if !resp.status().is_success() {
-        return None;
+/// 解析图片中显示的玩家昵称与头像，返回值附带资料获取耗时（毫秒）。
+///
+/// 昵称优先级：请求体昵称 > TapTap 资料（缓存或 users/me） > 默认占位；头像只来自 TapTap 资料。
+pub(super) async fn resolve_player_display(
+    state: &AppState,
+    nickname: Option<String>,
+    user_hash: Option<&str>,
+    auth: &UnifiedSaveRequest,
+    with_avatar: bool,
+) -> (PlayerDisplay, i64) {
+    let with_avatar = with_avatar && AppConfig::global().player_profile.enabled;
+    if nickname.is_some() && !with_avatar {
+        let name = nickname.unwrap_or_default();
+        return (PlayerDisplay { name, avatar: None }, 0);
     }
-    let me: UserMe = resp.json().await.ok()?;
-    me.nickname
+
+    let started_at = Instant::now();
+    let profile = crate::auth_contract::resolve_player_profile(
+        state,
+        user_hash,
+        auth.session_token.as_deref(),
+        auth.taptap_version.as_deref(),
+    )
+    .await;
+    let avatar_url = profile
+        .as_ref()
+        .and_then(|p| p.avatar_url.as_deref())
+        .filter(|_| with_avatar);
+    let avatar = match avatar_url {
+        Some(url) => avatar_data_uri(url).await.map(|uri| uri.to_string()),
+        None => None,
+    };
+    let name = nickname
+        .or_else(|| profile.and_then(|p| p.nickname))
+        .unwrap_or_else(|| DEFAULT_DISPLAY_NAME.into());
+    (
+        PlayerDisplay { name, avatar },
+        duration_ms_i64(started_at.elapsed()),
+    )
 }
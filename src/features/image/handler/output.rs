@@ -203,6 +203,46 @@ impl ImageOutputCacheSpec {
             i32::from(self.signing_enabled),
         )
     }
+
+    /// 排行榜图片不区分用户；`digest` 为榜单内容摘要。
+    pub(super) fn leaderboard_cache_key(&self, start_rank: i64, digest: &str) -> String {
+        format!(
+            "lb:{}:{}:{}:{}:{}:{}:{}:{}",
+            start_rank,
+            digest,
+            self.tpl_code,
+            self.fmt_code,
+            self.width_code,
+            self.webp_quality_code,
+            self.webp_lossless_code,
+            i32::from(self.signing_enabled),
+        )
+    }
+
+    /// `version` 由调用方根据最新提交与日期拼出，历史有新增即换 key。
+    pub(super) fn rks_history_cache_key(
+        &self,
+        user_hash: &str,
+        range: &str,
+        version: &str,
+        theme: Theme,
+        jump_threshold: f64,
+    ) -> String {
+        format!(
+            "{}:rksh:{}:{}:{}:{:.3}:{}:{}:{}:{}:{}:{}",
+            user_hash,
+            range,
+            version,
+            theme_cache_code(theme),
+            jump_threshold,
+            self.tpl_code,
+            self.fmt_code,
+            self.width_code,
+            self.webp_quality_code,
+            self.webp_lossless_code,
+            i32::from(self.signing_enabled),
+        )
+    }
 }
 
 fn theme_cache_code(theme: Theme) -> &'static str {
@@ -0,0 +1,280 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    config::AppConfig,
+    error::AppError,
+    features::image::{
+        renderer::{self, RksHistoryPoint, RksHistoryRenderData},
+        signing,
+        types::RenderRksHistoryRequest,
+    },
+    state::AppState,
+    stats_contract::{RksHistoryCursor, RksHistoryEntry, StatsStorage},
+};
+
+use super::{
+    context::{derive_image_user_identity, image_cache_enabled, image_footer_text},
+    nickname::resolve_player_display,
+    output::{
+        ImageOutputCacheSpec, ImageQueryOpts, SvgRenderOptions, image_content_headers,
+        render_svg_output_bytes, validate_image_query_opts,
+    },
+    runtime::{
+        acquire_render_permit, duration_ms_i64, spawn_blocking_svg_generation, track_image_event,
+    },
+};
+
+/// 单页拉取条数（与 `query_rks_history_page` 的上限一致）
+const HISTORY_PAGE_SIZE: i64 = 500;
+/// 单张图最多使用的数据点（更早的记录被截断）
+const MAX_HISTORY_POINTS: usize = 2000;
+
+/// 按 `(created_at, id)` 倒序分页读取，直到越过 `since` 或达到点数上限；返回时间升序的点。
+async fn load_rks_history_points(
+    storage: &StatsStorage,
+    user_hash: &str,
+    since: Option<DateTime<Utc>>,
+) -> Result<(Vec<RksHistoryPoint>, Option<i64>), AppError> {
+    let mut points = Vec::new();
+    let mut latest_id = None;
+    let mut cursor: Option<RksHistoryCursor> = None;
+    'pages: loop {
+        let page = storage
+            .query_rks_history_page(user_hash, HISTORY_PAGE_SIZE, 0, cursor.as_ref())
+            .await?;
+        if latest_id.is_none() {
+            latest_id = page.entries.first().map(|e| e.id);
+        }
+        for entry in &page.entries {
+            let Some(at) = parse_created_at(entry) else {
+                continue;
+            };
+            if since.is_some_and(|s| at < s) || points.len() >= MAX_HISTORY_POINTS {
+                break 'pages;
+            }
+            points.push(RksHistoryPoint {
+                at,
+                rks: entry.rks,
+                rks_jump: entry.rks_jump,
+            });
+        }
+        if !page.has_more {
+            break;
+        }
+        cursor = page.entries.last().map(|e| RksHistoryCursor {
+            created_at: e.created_at.clone(),
+            id: e.id,
+        });
+    }
+    points.reverse();
+    Ok((points, latest_id))
+}
+
+fn parse_created_at(entry: &RksHistoryEntry) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&entry.created_at)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[utoipa::path(
+    post,
+    path = "/image/rks/history",
+    summary = "生成 RKS 历史折线图",
+    description = "基于服务端记录的存档提交历史（与 /rks/history 同源）绘制 RKS 随时间变化的折线图，标出区间峰值/刷新最高点，并标注超过阈值的 RKS 跳变。",
+    request_body = RenderRksHistoryRequest,
+    params(
+        ("format" = Option<String>, Query, description = "输出格式：png|jpeg|webp|svg，默认 png"),
+        ("template" = Option<String>, Query, description = "SVG 模板 ID：对应 resources/templates/image/rks_history/{id}.svg.jinja（不传则使用内置手写 SVG）"),
+        ("width" = Option<u32>, Query, description = "目标宽度像素：按宽度同比例缩放"),
+        ("webp_quality" = Option<u8>, Query, description = "WebP 质量：1-100（仅在 format=webp 时有效，默认 80）"),
+        ("webp_lossless" = Option<bool>, Query, description = "WebP 无损模式（仅在 format=webp 时有效，默认 false）")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "图片（由 query format 决定）",
+            content(
+                (crate::features::image::types::BinaryImage = "image/png"),
+                (crate::features::image::types::BinaryImage = "image/jpeg"),
+                (crate::features::image::types::BinaryImage = "image/webp"),
+                (String = "image/svg+xml")
+            )
+        ),
+        (
+            status = 401,
+            description = "认证失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "参数校验失败/渲染错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/服务器内部错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Image"
+)]
+pub async fn render_rks_history(
+    State(state): State<AppState>,
+    Query(q): Query<ImageQueryOpts>,
+    request: axum::extract::Request,
+) -> Result<impl IntoResponse, AppError> {
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<RenderRksHistoryRequest>(request)
+            .await?;
+    crate::session_auth::merge_auth_from_bearer_if_missing(
+        state.stats_storage.as_ref(),
+        &bearer_state,
+        &mut req.auth,
+    )
+    .await?;
+    let t_total = Instant::now();
+    validate_image_query_opts(&q)?;
+    if !req.jump_threshold.is_finite() || req.jump_threshold < 0.0 {
+        return Err(AppError::Validation("jumpThreshold 必须为非负数".into()));
+    }
+
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let (user_hash, user_kind) = derive_image_user_identity(&req.auth, &bearer_state)?;
+    let user_hash =
+        user_hash.ok_or_else(|| AppError::Auth("无法识别用户（缺少可用凭证）".into()))?;
+    storage.ensure_user_not_banned(&user_hash).await?;
+
+    let now = Utc::now();
+    let since = req
+        .range
+        .days()
+        .map(|days| now - chrono::Duration::days(days));
+    let (points, latest_id) = load_rks_history_points(storage, &user_hash, since).await?;
+
+    // 相对时间范围的左边界每天滚动一次：按日期分桶即可，同一天内由最新提交 id 决定是否失效
+    let output = ImageOutputCacheSpec::from_query(&q, false);
+    let fmt_code = output.fmt_code;
+    let cache_key = image_cache_enabled().then(|| {
+        let version = format!(
+            "{}-{}-{}",
+            latest_id.unwrap_or(0),
+            points.len(),
+            if req.range.days().is_some() {
+                now.format("%Y%m%d").to_string()
+            } else {
+                String::new()
+            }
+        );
+        output.rks_history_cache_key(
+            &user_hash,
+            req.range.code(),
+            &version,
+            req.theme,
+            req.jump_threshold,
+        )
+    });
+    if let Some(key) = cache_key.as_ref()
+        && let Some(bytes) = state.bn_image_cache.get(key).await
+    {
+        if let Some(h) = state.stats.as_ref() {
+            track_image_event(
+                h,
+                "/image/rks/history",
+                "image_cache",
+                "rks_history_hit",
+                None,
+                Some(user_hash.clone()),
+                serde_json::json!({"cached": true, "user_kind": user_kind.as_deref(), "tpl": output.tpl_code.as_str()}),
+            );
+        }
+        return Ok((
+            StatusCode::OK,
+            image_content_headers(output.content_type),
+            bytes,
+        ));
+    }
+
+    let (player, _) = resolve_player_display(
+        &state,
+        req.nickname.clone(),
+        Some(&user_hash),
+        &req.auth,
+        false,
+    )
+    .await;
+    let point_count = points.len();
+    let render_data = RksHistoryRenderData {
+        player_name: Some(player.name),
+        range_label: req.range.label().to_string(),
+        points,
+        jump_threshold: req.jump_threshold,
+        theme: req.theme,
+        custom_footer_text: image_footer_text(),
+    };
+
+    let render_permit = acquire_render_permit(&state).await?;
+    let t_render = Instant::now();
+    let svg_options = SvgRenderOptions::from_query(None, &q);
+    let svg = spawn_blocking_svg_generation(move || {
+        renderer::generate_rks_history_svg_string(&render_data, svg_options.template_id())
+    })
+    .await?;
+
+    let signed_svg = {
+        let signing_cfg = &AppConfig::global().image.signing;
+        if signing_cfg.is_usable() {
+            if let Some(sig) = signing::sign_svg(&svg, signing_cfg, Some(&user_hash)) {
+                signing::inject_sig_footer(&svg, &sig)
+            } else {
+                svg
+            }
+        } else {
+            svg
+        }
+    };
+
+    let (bytes, content_type) = render_svg_output_bytes(signed_svg, fmt_code, false, &q).await?;
+    let render_ms = duration_ms_i64(t_render.elapsed());
+    drop(render_permit);
+
+    if let Some(stats) = state.stats.as_ref() {
+        let extra = serde_json::json!({ "range": req.range.code(), "points": point_count, "user_kind": user_kind.as_deref() });
+        stats.track_feature(
+            "rks_history",
+            "generate_image",
+            Some(user_hash.clone()),
+            Some(extra),
+        );
+    }
+
+    if let Some(key) = cache_key {
+        state.bn_image_cache.insert(key, bytes.clone()).await;
+    }
+
+    if let Some(h) = state.stats.as_ref() {
+        let total_ms = duration_ms_i64(t_total.elapsed());
+        track_image_event(
+            h,
+            "/image/rks/history",
+            "image_render",
+            "rks_history",
+            Some(total_ms),
+            None,
+            serde_json::json!({"render_ms": render_ms, "bytes": bytes.len(), "fmt": fmt_code, "width": q.width, "points": point_count}),
+        );
+    }
+    Ok((StatusCode::OK, image_content_headers(content_type), bytes))
+}
@@ -22,7 +22,7 @@ use super::{
         derive_image_user_identity, ensure_image_user_not_banned, image_cache_enabled,
         image_footer_text,
     },
-    nickname::resolve_display_name,
+    nickname::resolve_player_display,
     output::{
         ImageOutputCacheSpec, ImageQueryOpts, SvgRenderOptions, image_content_headers,
         render_svg_output_bytes, validate_image_query_opts,
@@ -111,7 +111,9 @@ pub async fn render_song(
     // 缓存前移：先拿 updatedAt（作为版本号）再决定是否需要下载/解密/解析存档本体。
     let (meta, updated_for_cache) = fetch_image_save_meta(source, taptap_version).await?;
 
-    let song = state
+    // 本次请求固定使用同一份定数/目录快照（热更新不影响在途请求）
+    let game = state.game_data();
+    let song = game
         .song_catalog
         .search_unique(&req.song)
         .map_err(AppError::Search)?;
@@ -130,9 +132,10 @@ pub async fn render_song(
         derive_image_user_identity(&req.auth, &bearer_state)?;
     ensure_image_user_not_banned(&state, user_hash_for_cache.as_deref()).await?;
     let cache_key = if cache_enabled {
-        user_hash_for_cache
-            .as_ref()
-            .map(|user_hash| output.song_cache_key(user_hash, &song.id, &updated_for_cache))
+        user_hash_for_cache.as_ref().map(|user_hash| {
+            let key = output.song_cache_key(user_hash, &song.id, &updated_for_cache);
+            format!("d{}:{key}", game.version)
+        })
     } else {
         None
     };
@@ -165,14 +168,14 @@ pub async fn render_song(
     }
 
     // cache miss：下载/解密/解析存档本体
-    let parsed = decrypt_image_save_from_meta(meta, state.chart_constants.clone()).await?;
+    let parsed = decrypt_image_save_from_meta(meta, game.chart_constants.clone()).await?;
     // 单曲成绩聚合、排序、推分求解与文件存在性检查均为同步 CPU/FS 工作，移出 Tokio worker。
     let SongComputeOutput {
         difficulty_scores,
         illustration_path,
         update_time,
     } = {
-        let chart_constants = state.chart_constants.clone();
+        let chart_constants = game.chart_constants.clone();
         let song_id = song.id.clone();
         let song_chart_constants = song.chart_constants.clone();
         let join = tokio::task::spawn_blocking(move || {
@@ -187,18 +190,21 @@ pub async fn render_song(
         join.map_err(blocking_join_error)??
     };
 
-    // 优先级：请求体昵称 > users/me 昵称 > 默认
-    let (display_name, _) = resolve_display_name(
+    // 优先级：请求体昵称 > TapTap 资料昵称 > 默认；头像来自 TapTap 资料
+    let (player, _) = resolve_player_display(
+        &state,
         req.nickname.clone(),
-        req.auth.session_token.clone(),
-        req.auth.taptap_version.as_deref(),
+        user_hash_for_cache.as_deref(),
+        &req.auth,
+        true,
     )
     .await;
 
     let render_data = SongRenderData {
         song_name: song.name.clone(),
         song_id: song.id.clone(),
-        player_name: Some(display_name),
+        player_name: Some(player.name),
+        player_avatar: player.avatar,
         update_time,
         difficulty_scores,
         illustration_path,
@@ -1,6 +1,6 @@
 use super::{
-    ImageOutputCacheSpec, ImageQueryOpts, content_type_from_fmt_code, format_code,
-    parse_user_score_difficulty,
+    ImageOutputCacheSpec, ImageQueryOpts, LeaderboardImageQuery, content_type_from_fmt_code,
+    format_code, leaderboard_entry_from_item, parse_user_score_difficulty,
 };
 use axum::Json;
 use axum::extract::{Query, State};
@@ -563,8 +563,7 @@ fn dummy_state() -> crate::state::AppState {
     let qrcode_service = Arc::new(crate::auth_services::QrCodeService::new());
 
     crate::state::AppState {
-        chart_constants,
-        song_catalog,
+        game_data: crate::game_data::GameDataStore::new(chart_constants, song_catalog),
         taptap_client,
         qrcode_service,
         stats: None,
@@ -625,3 +624,40 @@ async fn user_bn_rejects_scores_over_limit() {
         Ok(_) => panic!("expected Validation error, got Ok"),
     }
 }
+
+#[test]
+fn leaderboard_window_clamps_limit_and_centers_rank() {
+    assert_eq!(LeaderboardImageQuery::default().window(), (1, 20));
+    let q = LeaderboardImageQuery {
+        limit: Some(500),
+        rank: Some(100),
+    };
+    assert_eq!(q.window(), (75, 50));
+    let q = LeaderboardImageQuery {
+        limit: Some(10),
+        rank: Some(3),
+    };
+    assert_eq!(q.window(), (1, 10));
+}
+
+#[test]
+fn leaderboard_entry_falls_back_to_masked_user_and_summarizes_top_chart() {
+    let item = crate::leaderboard_contract::LeaderboardTopItem {
+        rank: 3,
+        alias: None,
+        user: "abcd****".into(),
+        score: 15.5,
+        updated_at: String::new(),
+        best_top3: Some(vec![crate::leaderboard_contract::ChartTextItem {
+            song: "Igallta".into(),
+            difficulty: "AT".into(),
+            acc: 99.5,
+            rks: 16.2,
+        }]),
+        ap_top3: None,
+    };
+    let entry = leaderboard_entry_from_item(&item);
+    assert_eq!(entry.player_name, "abcd****");
+    assert_eq!(entry.best_chart.as_deref(), Some("Igallta [AT] 16.20"));
+    assert!(entry.ap_chart.is_none());
+}
@@ -123,7 +123,7 @@ pub async fn render_bn_user(
         best_27_avg,
         ap_top_3_scores,
     } = {
-        let song_catalog = state.song_catalog.clone();
+        let song_catalog = state.game_data().song_catalog.clone();
         let join = tokio::task::spawn_blocking(move || {
             user_bn_compute::build_user_bn_compute_output(scores, song_catalog)
         })
@@ -152,6 +152,7 @@ pub async fn render_bn_user(
         best_27_avg,
         real_rks: Some(exact_rks),
         player_name: Some(display_name),
+        player_avatar: None,
         update_time: Utc::now(),
         n: u32_from_usize(n),
         ap_top_3_scores,
@@ -16,9 +16,12 @@ pub(crate) fn prewarm_illustration_assets() {
 
 pub use handler::create_image_router;
 pub use renderer::{
-    LeaderboardRenderData, PlayerStats, RenderRecord, SongDifficultyScore, SongRenderData,
-    generate_leaderboard_svg_string, generate_song_svg_string, generate_svg_string,
+    LeaderboardEntry, LeaderboardRenderData, PlayerStats, RenderRecord, RksHistoryPoint,
+    RksHistoryRenderData, SongDifficultyScore, SongRenderData, generate_leaderboard_svg_string,
+    generate_rks_history_svg_string, generate_song_svg_string, generate_svg_string,
     render_svg_to_png,
 };
 pub use service::ImageService;
-pub use types::{RenderBnRequest, RenderSongRequest, Theme};
+pub use types::{
+    RenderBnRequest, RenderRksHistoryRequest, RenderSongRequest, RksHistoryRange, Theme,
+};
@@ -8,6 +8,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+mod avatar;
 mod background_layer;
 mod bn;
 mod bn_background;
@@ -24,6 +25,7 @@ mod bn_layout;
 mod bn_sections;
 mod bn_theme;
 mod leaderboard;
+mod login_qrcode;
 mod math;
 mod raster_jpeg;
 mod raster_options;
@@ -37,6 +39,7 @@ mod resource_fonts;
 mod resource_image;
 mod resource_scaled;
 mod resources;
+mod rks_history;
 mod score;
 mod song;
 mod song_background;
@@ -50,6 +53,8 @@ mod song_sections;
 mod svg_error;
 mod template_bn;
 mod template_bn_card;
+mod template_leaderboard;
+mod template_rks_history;
 mod template_shared;
 mod template_song;
 mod template_song_card;
@@ -63,6 +68,8 @@ pub struct PlayerStats {
     pub best_27_avg: Option<f64>,
     pub real_rks: Option<f64>,
     pub player_name: Option<String>,
+    /// 玩家头像（data URI 或 URL），None 时不绘制
+    pub player_avatar: Option<String>,
     pub update_time: DateTime<Utc>,
     pub n: u32,                                   // 请求的 Best N 数量
     pub ap_top_3_scores: Vec<RenderRecord>,       // AP Top 3 的具体成绩
@@ -101,6 +108,8 @@ pub struct SongRenderData {
     pub song_name: String,
     pub song_id: String, // 用于加载封面
     pub player_name: Option<String>,
+    /// 玩家头像（data URI 或 URL），None 时不绘制
+    pub player_avatar: Option<String>,
     pub update_time: DateTime<Utc>,
     // 使用 HashMap 存储不同难度的成绩，Key 为 "EZ", "HD", "IN", "AT"
     pub difficulty_scores: HashMap<String, Option<SongDifficultyScore>>,
@@ -110,14 +119,43 @@ pub struct SongRenderData {
     pub custom_footer_text: Option<String>,
 }
 
+/// 登录二维码渲染数据
+#[derive(Debug, Clone)]
+pub struct QrCodeRenderData {
+    /// 编码内容（扫码跳转 URL）
+    pub content: String,
+    /// 输出边长（像素）
+    pub size: u32,
+    /// 静区宽度（模块数）
+    pub margin: u32,
+    pub ec_level: qrcode::EcLevel,
+    /// 暗模块颜色（已校验的 `#RRGGBB`）
+    pub dark_color: String,
+    /// 背景颜色（已校验的 `#RRGGBB`）
+    pub light_color: String,
+    /// 中心 logo（data URI）；存在时纠错等级至少为 Q
+    pub logo_data_uri: Option<String>,
+}
+
 /// 排行榜渲染数据
 #[derive(Debug, Clone)]
 pub struct LeaderboardEntry {
+    /// 实际名次（1-based；名次窗口时不从 1 开始）
+    pub rank: i64,
     pub player_name: String,
     pub rks: f64,
+    /// 最佳谱面摘要（玩家公开 BestTop3 时提供）
+    pub best_chart: Option<String>,
+    /// AP 最佳谱面摘要（玩家公开 APTop3 时提供）
+    pub ap_chart: Option<String>,
+}
+
+impl LeaderboardEntry {
+    pub(crate) fn has_detail(&self) -> bool {
+        self.best_chart.is_some() || self.ap_chart.is_some()
+    }
 }
 
-#[allow(dead_code)]
 pub struct LeaderboardRenderData {
     pub title: String,
     pub update_time: DateTime<Utc>,
@@ -125,6 +163,30 @@ pub struct LeaderboardRenderData {
     pub display_count: usize,
 }
 
+/// RKS 历史折线图的单个数据点
+#[derive(Debug, Clone)]
+pub struct RksHistoryPoint {
+    pub at: DateTime<Utc>,
+    pub rks: f64,
+    /// 相比上一次提交的变化量
+    pub rks_jump: f64,
+}
+
+/// RKS 历史折线图渲染数据
+#[derive(Debug, Clone)]
+pub struct RksHistoryRenderData {
+    pub player_name: Option<String>,
+    /// 时间范围说明（如“近 30 天”）
+    pub range_label: String,
+    /// 按时间升序排列的数据点
+    pub points: Vec<RksHistoryPoint>,
+    /// |rks_jump| 不小于该值的点会被标注
+    pub jump_threshold: f64,
+    pub theme: Theme,
+    /// 可选：右下角自定义文字
+    pub custom_footer_text: Option<String>,
+}
+
 // 常量定义
 const MAIN_FONT_NAME: &str = "思源黑体 CN";
 const DEFAULT_PLAYER_NAME: &str = "Phigros Player";
@@ -287,8 +349,26 @@ pub fn generate_song_svg_string(
 }
 
 /// 生成排行榜SVG字符串
-pub fn generate_leaderboard_svg_string(data: &LeaderboardRenderData) -> Result<String, AppError> {
-    leaderboard::generate_leaderboard_svg_string(data)
+pub fn generate_leaderboard_svg_string(
+    data: &LeaderboardRenderData,
+    // 外部模板 ID：对应 `resources/templates/image/leaderboard/{id}.svg.jinja`（为空则使用内置手写 SVG 实现）。
+    template_id: Option<&str>,
+) -> Result<String, AppError> {
+    leaderboard::generate_leaderboard_svg_string(data, template_id)
+}
+
+/// 生成登录二维码 SVG 字符串
+pub fn generate_qrcode_svg_string(data: &QrCodeRenderData) -> Result<String, AppError> {
+    login_qrcode::generate_qrcode_svg_string(data)
+}
+
+/// 生成 RKS 历史折线图 SVG 字符串
+pub fn generate_rks_history_svg_string(
+    data: &RksHistoryRenderData,
+    // 外部模板 ID：对应 `resources/templates/image/rks_history/{id}.svg.jinja`（为空则使用内置手写 SVG 实现）。
+    template_id: Option<&str>,
+) -> Result<String, AppError> {
+    rks_history::generate_rks_history_svg_string(data, template_id)
 }
 
 #[cfg(test)]
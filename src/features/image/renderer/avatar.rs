@@ -0,0 +1,63 @@
+use std::fmt::Write;
+
+use crate::error::AppError;
+
+use super::svg_error::svg_fmt_error;
+use super::text::escape_xml;
+
+/// 同一张图只有一个玩家头像，clipPath id 固定即可
+const AVATAR_CLIP_ID: &str = "player-avatar-clip";
+
+/// 圆形玩家头像（按圆裁剪 + 描边）；`href` 为原始 URL/data URI，内部负责转义
+pub(super) fn write_circle_avatar(
+    svg: &mut String,
+    href: &str,
+    cx: f64,
+    cy: f64,
+    r: f64,
+    stroke: &str,
+) -> Result<(), AppError> {
+    writeln!(
+        svg,
+        r#"<defs><clipPath id="{AVATAR_CLIP_ID}"><circle cx="{cx}" cy="{cy}" r="{r}"/></clipPath></defs>"#
+    )
+    .map_err(svg_fmt_error)?;
+    writeln!(
+        svg,
+        r#"<image href="{}" x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="xMidYMid slice" clip-path="url(#{AVATAR_CLIP_ID})"/>"#,
+        escape_xml(href),
+        cx - r,
+        cy - r,
+        r * 2.0,
+        r * 2.0
+    )
+    .map_err(svg_fmt_error)?;
+    writeln!(
+        svg,
+        r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="none" stroke="{stroke}" stroke-width="2" stroke-opacity="0.8"/>"#
+    )
+    .map_err(svg_fmt_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avatar_is_clipped_and_escaped() {
+        let mut svg = String::new();
+        write_circle_avatar(
+            &mut svg,
+            "https://a/b.png?x=1&y=2",
+            80.0,
+            65.0,
+            40.0,
+            "#fff",
+        )
+        .unwrap();
+        assert!(svg.contains(r#"href="https://a/b.png?x=1&amp;y=2""#));
+        assert!(svg.contains(r#"x="40" y="25" width="80" height="80""#));
+        assert!(svg.contains("clip-path=\"url(#player-avatar-clip)\""));
+    }
+}
@@ -5,6 +5,7 @@ use std::fmt::Write;
 use crate::error::AppError;
 
 use super::PlayerStats;
+use super::avatar::write_circle_avatar;
 use super::bn_header_text::{build_bn_header_text, build_challenge_rank_inner_xml};
 use super::svg_error::svg_fmt_error;
 use super::text::escape_xml;
@@ -40,21 +41,28 @@ pub(super) fn write_header(ctx: BnHeaderRenderContext<'_>) -> Result<(), AppErro
     } = ctx;
 
     let header_text = build_bn_header_text(stats);
+    // 有头像时绘制在标题左侧，文字整体右移
+    let text_x = if let Some(avatar) = stats.player_avatar.as_deref() {
+        write_circle_avatar(svg, avatar, 80.0, 65.0, 40.0, card_stroke_color)?;
+        136
+    } else {
+        40
+    };
     writeln!(
         svg,
-        r#"<text x="40" y="55" class="text-title">{}</text>"#,
+        r#"<text x="{text_x}" y="55" class="text-title">{}</text>"#,
         escape_xml(&header_text.player_title),
     )
     .map_err(svg_fmt_error)?;
     writeln!(
         svg,
-        r#"<text x="40" y="85" class="text-stat">{}</text>"#,
+        r#"<text x="{text_x}" y="85" class="text-stat">{}</text>"#,
         header_text.ap_text
     )
     .map_err(svg_fmt_error)?;
     writeln!(
         svg,
-        r#"<text x="40" y="110" class="text-stat">{}</text>"#,
+        r#"<text x="{text_x}" y="110" class="text-stat">{}</text>"#,
         header_text.bn_text
     )
     .map_err(svg_fmt_error)?;
@@ -123,6 +131,7 @@ mod tests {
             best_27_avg: None,
             real_rks: Some(15.123_456),
             player_name: Some("Tester".to_string()),
+            player_avatar: None,
             update_time: Utc::now(),
             n: 1,
             ap_top_3_scores: vec![],
@@ -148,6 +157,38 @@ mod tests {
         assert!(!svg.contains("Green<&>\""));
         assert!(!svg.contains("Lv<1&\""));
     }
+
+    #[test]
+    fn write_header_shifts_title_when_avatar_present() {
+        let stats = PlayerStats {
+            ap_top_3_avg: None,
+            best_27_avg: None,
+            real_rks: Some(15.0),
+            player_name: Some("Tester".to_string()),
+            player_avatar: Some("data:image/png;base64,AAAA".to_string()),
+            update_time: Utc::now(),
+            n: 1,
+            ap_top_3_scores: vec![],
+            challenge_rank: None,
+            data_string: None,
+            custom_footer_text: None,
+            is_user_generated: false,
+        };
+        let mut svg = String::new();
+
+        write_header(BnHeaderRenderContext {
+            svg: &mut svg,
+            stats: &stats,
+            width: 1200,
+            header_height: 130,
+            card_stroke_color: "#333333",
+            text_secondary_color: "#666666",
+        })
+        .expect("write bn header");
+
+        assert!(svg.contains(r#"href="data:image/png;base64,AAAA""#));
+        assert!(svg.contains(r#"<text x="136" y="55" class="text-title">"#));
+    }
 }
 
 pub(super) fn write_footer(ctx: BnFooterRenderContext<'_>) -> Result<(), AppError> {
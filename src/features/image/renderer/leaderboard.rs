@@ -4,14 +4,24 @@ use crate::error::AppError;
 
 use super::math::i32_from_usize;
 use super::svg_error::svg_fmt_error;
+use super::template_leaderboard::generate_leaderboard_svg_with_template;
 use super::text::{escape_xml, truncate_chars_with_ellipsis};
-use super::{LeaderboardEntry, LeaderboardRenderData};
+use super::{LeaderboardEntry, LeaderboardRenderData, MAIN_FONT_NAME};
 
 pub(super) fn generate_leaderboard_svg_string(
     data: &LeaderboardRenderData,
+    template_id: Option<&str>,
 ) -> Result<String, AppError> {
+    if template_id.is_some() {
+        return generate_leaderboard_svg_with_template(data, template_id);
+    }
+
     let width = 1200;
-    let row_height = 60;
+    let row_height = if data.entries.iter().any(LeaderboardEntry::has_detail) {
+        84
+    } else {
+        60
+    };
     let header_height = 120;
     let footer_height = 40;
     let total_height =
@@ -23,53 +33,61 @@ pub(super) fn generate_leaderboard_svg_string(
 
     // 添加渐变背景和样式
     // 使用 r##"..."## 来避免 # 颜色值与原始字符串分隔符冲突
-    svg.push_str(r##"
+    svg.push_str(
+        r##"
     <defs>
         <linearGradient id="bg-gradient" x1="0%" y1="0%" x2="100%" y2="100%">
             <stop offset="0%" stop-color="#1a1a2e" />
             <stop offset="100%" stop-color="#16213e" />
         </linearGradient>
-        <style>
-            @font-face {
-                font-family: 'NotoSansSC';
-                src: url('https://fonts.gstatic.com/s/notosanssc/v36/k3kXo84MPvpLmixcA63oeALhLIiP-Q-87KaAavc.woff2') format('woff2');
-            }
+        <style>"##,
+    );
+    // 字体使用 resources/fonts 中随项目分发的字体（由全局 fontdb 加载），不依赖外网字体
+    write!(
+        svg,
+        r#"
+            text {{ font-family: "{MAIN_FONT_NAME}", "Microsoft YaHei", "SimHei", "DengXian", Arial, sans-serif; }}"#
+    )
+    .map_err(svg_fmt_error)?;
+    svg.push_str(
+        r"
             .header-text {
-                font-family: 'NotoSansSC', sans-serif;
                 font-size: 48px;
                 fill: white;
                 text-anchor: middle;
                 font-weight: bold; /* 加粗标题 */
             }
             .rank-text {
-                font-family: 'NotoSansSC', sans-serif;
                 font-size: 32px;
                 fill: white;
                 text-anchor: middle;
                 font-weight: bold;
             }
             .name-text {
-                font-family: 'NotoSansSC', sans-serif;
                 font-size: 32px;
                 fill: white;
                 text-anchor: start;
             }
+            .detail-text {
+                font-size: 20px;
+                fill: #a0aec0;
+                text-anchor: start;
+            }
             .rks-text {
-                font-family: 'NotoSansSC', sans-serif;
                 font-size: 32px;
                 fill: white;
                 text-anchor: end;
                 font-weight: bold;
             }
             .footer-text {
-                font-family: 'NotoSansSC', sans-serif;
                 font-size: 20px;
                 fill: #aaaaaa;
                 text-anchor: end;
             }
         </style>
     </defs>
-"##);
+",
+    );
 
     // 绘制背景
     write!(
@@ -106,7 +124,6 @@ pub(super) fn generate_leaderboard_svg_string(
             &mut svg,
             entry,
             LeaderboardEntryRenderLayout {
-                rank: i + 1,
                 y_pos,
                 row_height,
                 width,
@@ -132,7 +149,6 @@ pub(super) fn generate_leaderboard_svg_string(
 
 #[derive(Debug, Clone, Copy)]
 struct LeaderboardEntryRenderLayout {
-    rank: usize,
     y_pos: i32,
     row_height: i32,
     width: i32,
@@ -144,12 +160,17 @@ fn write_leaderboard_entry(
     entry: &LeaderboardEntry,
     layout: LeaderboardEntryRenderLayout,
 ) -> Result<(), AppError> {
-    let text_y = layout.y_pos + (layout.row_height / 2) + 10;
+    let detail = leaderboard_detail_text(entry);
+    let text_y = if detail.is_some() {
+        layout.y_pos + 40
+    } else {
+        layout.y_pos + (layout.row_height / 2) + 10
+    };
 
     write!(
         svg,
         r#"<text x="60" y="{text_y}" class="rank-text">#{}</text>"#,
-        layout.rank
+        entry.rank
     )
     .map_err(svg_fmt_error)?;
 
@@ -161,6 +182,16 @@ fn write_leaderboard_entry(
     )
     .map_err(svg_fmt_error)?;
 
+    if let Some(detail) = detail {
+        write!(
+            svg,
+            r#"<text x="120" y="{}" class="detail-text">{}</text>"#,
+            text_y + 30,
+            escape_xml(&detail)
+        )
+        .map_err(svg_fmt_error)?;
+    }
+
     write!(
         svg,
         r#"<text x="{}" y="{text_y}" class="rks-text">{:.2}</text>"#,
@@ -182,27 +213,46 @@ fn write_leaderboard_entry(
     Ok(())
 }
 
-fn leaderboard_name_display(player_name: &str) -> String {
+pub(super) fn leaderboard_name_display(player_name: &str) -> String {
     truncate_chars_with_ellipsis(player_name, 20, 17)
 }
 
+/// 次要信息行：Best / AP 最高谱面（仅在玩家公开对应信息时存在）
+pub(super) fn leaderboard_detail_text(entry: &LeaderboardEntry) -> Option<String> {
+    let parts: Vec<String> = [("Best", &entry.best_chart), ("AP", &entry.ap_chart)]
+        .into_iter()
+        .filter_map(|(label, text)| text.as_deref().map(|t| format!("{label}: {t}")))
+        .collect();
+    (!parts.is_empty()).then(|| truncate_chars_with_ellipsis(&parts.join("  ·  "), 72, 69))
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
 
     use super::*;
 
+    fn entry(rank: i64, name: &str) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            player_name: name.to_string(),
+            rks: 15.234,
+            best_chart: None,
+            ap_chart: None,
+        }
+    }
+
     #[test]
     fn leaderboard_escapes_title_and_player_name() {
-        let svg = generate_leaderboard_svg_string(&LeaderboardRenderData {
-            title: "A&B <Top> \"Q\"".to_string(),
-            update_time: Utc::now(),
-            entries: vec![LeaderboardEntry {
-                player_name: "玩家<&>\"".to_string(),
-                rks: 15.234,
-            }],
-            display_count: 10,
-        })
+        let svg = generate_leaderboard_svg_string(
+            &LeaderboardRenderData {
+                title: "A&B <Top> \"Q\"".to_string(),
+                update_time: Utc::now(),
+                entries: vec![entry(1, "玩家<&>\"")],
+                display_count: 10,
+            },
+            None,
+        )
         .expect("render leaderboard svg");
 
         assert!(svg.contains("A&amp;B &lt;Top&gt; &quot;Q&quot;"));
@@ -210,4 +260,27 @@ mod tests {
         assert!(!svg.contains("A&B <Top> \"Q\""));
         assert!(!svg.contains("玩家<&>\""));
     }
+
+    #[test]
+    fn leaderboard_uses_entry_rank_bundled_font_and_optional_detail() {
+        let mut second = entry(42, "Bob");
+        second.best_chart = Some("Rrhar'il [AT] 16.40".to_string());
+        let svg = generate_leaderboard_svg_string(
+            &LeaderboardRenderData {
+                title: "RKS".to_string(),
+                update_time: Utc::now(),
+                entries: vec![entry(41, "Alice"), second],
+                display_count: 2,
+            },
+            None,
+        )
+        .expect("render leaderboard svg");
+
+        assert!(svg.contains(">#41<"));
+        assert!(svg.contains(">#42<"));
+        assert!(svg.contains("Best: Rrhar&apos;il [AT] 16.40"));
+        assert!(svg.contains(MAIN_FONT_NAME));
+        assert!(!svg.contains("@font-face"));
+        assert!(!svg.contains("fonts.gstatic.com"));
+    }
 }
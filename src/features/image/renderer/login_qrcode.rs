@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use qrcode::{Color, EcLevel, QrCode};
+
+use crate::error::AppError;
+
+use super::QrCodeRenderData;
+use super::svg_error::svg_fmt_error;
+
+/// 中心 logo 占二维码边长的比例；H 级纠错下遮挡约 5% 面积仍可稳定识别
+const LOGO_SIDE_RATIO: f64 = 0.22;
+
+/// 带 logo 时至少使用 Q 级纠错，低于 Q 的请求自动提升到 H
+fn effective_ec_level(requested: EcLevel, has_logo: bool) -> EcLevel {
+    if has_logo && matches!(requested, EcLevel::L | EcLevel::M) {
+        EcLevel::H
+    } else {
+        requested
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub(super) fn generate_qrcode_svg_string(data: &QrCodeRenderData) -> Result<String, AppError> {
+    let ec = effective_ec_level(data.ec_level, data.logo_data_uri.is_some());
+    let code = QrCode::with_error_correction_level(data.content.as_bytes(), ec)
+        .map_err(|e| AppError::ImageRendererError(format!("生成二维码失败: {e}")))?;
+    let modules = code.width();
+    let margin = data.margin as usize;
+    let total = modules + margin * 2;
+    let size = data.size;
+
+    let mut svg = String::with_capacity(modules * modules * 12 + 512);
+    write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {total} {total}" shape-rendering="crispEdges">"#
+    )
+    .map_err(svg_fmt_error)?;
+    write!(
+        svg,
+        r#"<rect width="{total}" height="{total}" fill="{}"/>"#,
+        data.light_color
+    )
+    .map_err(svg_fmt_error)?;
+
+    // 所有暗模块合并为一条 path，按行把连续模块写成一段横线以减小体积
+    write!(svg, r#"<path fill="{}" d=""#, data.dark_color).map_err(svg_fmt_error)?;
+    let colors = code.to_colors();
+    for (y, row) in colors.chunks(modules).enumerate() {
+        let mut x = 0;
+        while x < modules {
+            if row[x] != Color::Dark {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < modules && row[x] == Color::Dark {
+                x += 1;
+            }
+            write!(
+                svg,
+                "M{} {}h{}v1h-{}z",
+                start + margin,
+                y + margin,
+                x - start,
+                x - start
+            )
+            .map_err(svg_fmt_error)?;
+        }
+    }
+    svg.push_str(r#""/>"#);
+
+    if let Some(uri) = data.logo_data_uri.as_deref() {
+        let side = modules as f64 * LOGO_SIDE_RATIO;
+        let pad = side * 0.12;
+        let origin = (total as f64 - side) / 2.0;
+        write!(
+            svg,
+            r#"<rect x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}" rx="{:.3}" fill="{}"/>"#,
+            origin - pad,
+            origin - pad,
+            side + pad * 2.0,
+            side + pad * 2.0,
+            pad * 1.5,
+            data.light_color
+        )
+        .map_err(svg_fmt_error)?;
+        write!(
+            svg,
+            r#"<image x="{origin:.3}" y="{origin:.3}" width="{side:.3}" height="{side:.3}" preserveAspectRatio="xMidYMid meet" href="{uri}"/>"#
+        )
+        .map_err(svg_fmt_error)?;
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(logo: Option<&str>) -> QrCodeRenderData {
+        QrCodeRenderData {
+            content: "https://www.taptap.com/account/device?code=abcd".into(),
+            size: 300,
+            margin: 2,
+            ec_level: EcLevel::M,
+            dark_color: "#000000".into(),
+            light_color: "#ffffff".into(),
+            logo_data_uri: logo.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn renders_modules_with_quiet_zone() {
+        let svg = generate_qrcode_svg_string(&data(None)).unwrap();
+        let code = QrCode::with_error_correction_level(
+            "https://www.taptap.com/account/device?code=abcd".as_bytes(),
+            EcLevel::M,
+        )
+        .unwrap();
+        let total = code.width() + 4;
+        assert!(svg.contains(&format!(r#"viewBox="0 0 {total} {total}""#)));
+        assert!(svg.contains(r#"width="300""#));
+        // 左上角定位图案从静区之后开始
+        assert!(svg.contains("M2 2h7v1h-7z"));
+        assert!(!svg.contains("<image"));
+    }
+
+    #[test]
+    fn logo_upgrades_error_correction() {
+        assert_eq!(effective_ec_level(EcLevel::L, true), EcLevel::H);
+        assert_eq!(effective_ec_level(EcLevel::Q, true), EcLevel::Q);
+        assert_eq!(effective_ec_level(EcLevel::L, false), EcLevel::L);
+        let svg = generate_qrcode_svg_string(&data(Some("data:image/png;base64,AAAA"))).unwrap();
+        assert!(svg.contains(r#"href="data:image/png;base64,AAAA""#));
+    }
+}
@@ -0,0 +1,507 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::features::image::Theme;
+
+use super::math::f64_from_usize;
+use super::svg_error::svg_fmt_error;
+use super::template_rks_history::generate_rks_history_svg_with_template;
+use super::text::{escape_xml, truncate_chars_with_ellipsis};
+use super::time::{format_utc8_datetime, generated_at_utc8_text};
+use super::{DEFAULT_PLAYER_NAME, MAIN_FONT_NAME, RksHistoryRenderData};
+
+/// 最多标注的 RKS 跳变数量（按幅度取前 N，避免图面拥挤）
+const MAX_JUMP_ANNOTATIONS: usize = 8;
+const Y_TICKS: usize = 5;
+const X_TICKS: usize = 5;
+
+/// 折线图画布参数（内置 SVG 与模板共用；模板可通过同名 json 覆盖）
+#[derive(Debug, Clone, Copy, Serialize, serde::Deserialize)]
+#[serde(default)]
+pub(super) struct RksHistoryChartLayout {
+    pub width: u32,
+    pub height: u32,
+    pub plot_left: f64,
+    pub plot_right: f64,
+    pub plot_top: f64,
+    pub plot_bottom: f64,
+}
+
+impl Default for RksHistoryChartLayout {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 675,
+            plot_left: 100.0,
+            plot_right: 1150.0,
+            plot_top: 150.0,
+            plot_bottom: 590.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct RksHistoryPalette {
+    pub bg: &'static str,
+    pub text: &'static str,
+    pub text_secondary: &'static str,
+    pub grid: &'static str,
+    pub line: &'static str,
+    pub area: &'static str,
+    pub peak: &'static str,
+    pub jump_up: &'static str,
+    pub jump_down: &'static str,
+}
+
+impl RksHistoryPalette {
+    pub(super) fn from_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::White => Self {
+                bg: "#F7FAFF",
+                text: "#000000",
+                text_secondary: "#555555",
+                grid: "#D0D4DD",
+                line: "#4682B4",
+                area: "rgba(70,130,180,0.15)",
+                peak: "#D4A017",
+                jump_up: "#2E8B57",
+                jump_down: "#C0392B",
+            },
+            Theme::Black => Self {
+                bg: "#141826",
+                text: "#FFFFFF",
+                text_secondary: "#BBBBBB",
+                grid: "#333848",
+                line: "#87CEEB",
+                area: "rgba(135,206,235,0.15)",
+                peak: "#FFD700",
+                jump_up: "#7CFC9A",
+                jump_down: "#FF7F7F",
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct AxisTick {
+    pub pos: f64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct ChartMarker {
+    pub x: f64,
+    pub y: f64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct JumpMarker {
+    pub x: f64,
+    pub y: f64,
+    pub label: String,
+    /// 标注文字的 y（上涨在点上方，下跌在点下方）
+    pub label_y: f64,
+    pub up: bool,
+}
+
+/// 由原始数据点计算出的图表几何（坐标已映射到画布）
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct RksHistoryChart {
+    pub empty: bool,
+    /// polyline 的 `points` 属性
+    pub line_points: String,
+    /// 折线下方填充区域的 `points` 属性
+    pub area_points: String,
+    pub y_ticks: Vec<AxisTick>,
+    pub x_ticks: Vec<AxisTick>,
+    /// 区间内每次刷新最高值的点（不含全局峰值本身）
+    pub records: Vec<ChartMarker>,
+    /// 区间内全局峰值
+    pub peak: Option<ChartMarker>,
+    pub jumps: Vec<JumpMarker>,
+    pub current_rks: Option<f64>,
+    pub peak_rks: Option<f64>,
+}
+
+pub(super) fn build_rks_history_chart(
+    data: &RksHistoryRenderData,
+    layout: &RksHistoryChartLayout,
+) -> RksHistoryChart {
+    let points = &data.points;
+    if points.is_empty() {
+        return RksHistoryChart {
+            empty: true,
+            line_points: String::new(),
+            area_points: String::new(),
+            y_ticks: Vec::new(),
+            x_ticks: Vec::new(),
+            records: Vec::new(),
+            peak: None,
+            jumps: Vec::new(),
+            current_rks: None,
+            peak_rks: None,
+        };
+    }
+
+    let (mut y_min, mut y_max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p.rks), hi.max(p.rks))
+        });
+    let peak_rks = y_max;
+    let span = (y_max - y_min).max(0.1);
+    y_min -= span * 0.08;
+    y_max += span * 0.12;
+
+    let t0 = points[0].at.timestamp();
+    let t1 = points[points.len() - 1].at.timestamp();
+    let t_span = t1 - t0;
+    let plot_w = layout.plot_right - layout.plot_left;
+    let plot_h = layout.plot_bottom - layout.plot_top;
+
+    #[allow(clippy::cast_precision_loss)]
+    let x_of = |ts: i64| {
+        if t_span <= 0 {
+            layout.plot_left + plot_w / 2.0
+        } else {
+            layout.plot_left + plot_w * ((ts - t0) as f64 / t_span as f64)
+        }
+    };
+    let y_of = |rks: f64| layout.plot_bottom - plot_h * ((rks - y_min) / (y_max - y_min));
+
+    let coords: Vec<(f64, f64)> = points
+        .iter()
+        .map(|p| (x_of(p.at.timestamp()), y_of(p.rks)))
+        .collect();
+
+    let mut line_points = String::with_capacity(coords.len() * 16);
+    for (x, y) in &coords {
+        let _ = write!(line_points, "{x:.1},{y:.1} ");
+    }
+    let line_points = line_points.trim_end().to_string();
+    let area_points = format!(
+        "{:.1},{:.1} {line_points} {:.1},{:.1}",
+        coords[0].0,
+        layout.plot_bottom,
+        coords[coords.len() - 1].0,
+        layout.plot_bottom
+    );
+
+    let y_ticks = (0..Y_TICKS)
+        .map(|i| {
+            let v = y_min + (y_max - y_min) * f64_from_usize(i) / f64_from_usize(Y_TICKS - 1);
+            AxisTick {
+                pos: y_of(v),
+                label: format!("{v:.2}"),
+            }
+        })
+        .collect();
+
+    let long_span = t_span > 365 * 24 * 3600;
+    let x_ticks = if t_span <= 0 {
+        vec![AxisTick {
+            pos: coords[0].0,
+            label: format_utc8_datetime(&points[0].at, "%Y-%m-%d"),
+        }]
+    } else {
+        (0..X_TICKS)
+            .map(|i| {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+                let ts =
+                    t0 + (t_span as f64 * f64_from_usize(i) / f64_from_usize(X_TICKS - 1)) as i64;
+                let at = chrono::DateTime::from_timestamp(ts, 0).unwrap_or(points[0].at);
+                AxisTick {
+                    pos: x_of(ts),
+                    label: format_utc8_datetime(&at, if long_span { "%Y-%m" } else { "%m-%d" }),
+                }
+            })
+            .collect()
+    };
+
+    // 全局峰值取最早达到的那一次；刷新最高值的点单独标出
+    let peak_idx =
+        points.iter().enumerate().fold(
+            0,
+            |best, (i, p)| if p.rks > points[best].rks { i } else { best },
+        );
+    let mut records = Vec::new();
+    let mut running_max = f64::NEG_INFINITY;
+    for (i, p) in points.iter().enumerate() {
+        if p.rks > running_max {
+            running_max = p.rks;
+            if i != 0 && i != peak_idx {
+                records.push(ChartMarker {
+                    x: coords[i].0,
+                    y: coords[i].1,
+                    label: format!("{:.2}", p.rks),
+                });
+            }
+        }
+    }
+    let peak = Some(ChartMarker {
+        x: coords[peak_idx].0,
+        y: coords[peak_idx].1,
+        label: format!("Peak {peak_rks:.4}"),
+    });
+
+    let mut jump_idx: Vec<usize> = (0..points.len())
+        .filter(|&i| points[i].rks_jump.abs() >= data.jump_threshold)
+        .collect();
+    jump_idx.sort_by(|&a, &b| {
+        points[b]
+            .rks_jump
+            .abs()
+            .total_cmp(&points[a].rks_jump.abs())
+    });
+    jump_idx.truncate(MAX_JUMP_ANNOTATIONS);
+    jump_idx.sort_unstable();
+    let jumps = jump_idx
+        .into_iter()
+        .map(|i| {
+            let up = points[i].rks_jump >= 0.0;
+            let (x, y) = coords[i];
+            JumpMarker {
+                x,
+                y,
+                label: format!("{:+.2}", points[i].rks_jump),
+                label_y: if up { y - 14.0 } else { y + 26.0 },
+                up,
+            }
+        })
+        .collect();
+
+    RksHistoryChart {
+        empty: false,
+        line_points,
+        area_points,
+        y_ticks,
+        x_ticks,
+        records,
+        peak,
+        jumps,
+        current_rks: points.last().map(|p| p.rks),
+        peak_rks: Some(peak_rks),
+    }
+}
+
+pub(super) fn rks_history_title(data: &RksHistoryRenderData) -> String {
+    let name = data
+        .player_name
+        .as_deref()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or(DEFAULT_PLAYER_NAME);
+    format!("{} · RKS 历史", truncate_chars_with_ellipsis(name, 24, 21))
+}
+
+pub(super) fn rks_history_subtitle(data: &RksHistoryRenderData, chart: &RksHistoryChart) -> String {
+    match (chart.current_rks, chart.peak_rks) {
+        (Some(cur), Some(peak)) => format!(
+            "{} · 当前 {cur:.4} · 区间峰值 {peak:.4} · {} 条记录",
+            data.range_label,
+            data.points.len()
+        ),
+        _ => format!("{} · 暂无记录", data.range_label),
+    }
+}
+
+pub(super) fn rks_history_footer(data: &RksHistoryRenderData) -> String {
+    data.custom_footer_text
+        .clone()
+        .unwrap_or_else(generated_at_utc8_text)
+}
+
+pub(super) fn generate_rks_history_svg_string(
+    data: &RksHistoryRenderData,
+    template_id: Option<&str>,
+) -> Result<String, AppError> {
+    if template_id.is_some() {
+        return generate_rks_history_svg_with_template(data, template_id);
+    }
+
+    let layout = RksHistoryChartLayout::default();
+    let chart = build_rks_history_chart(data, &layout);
+    let c = RksHistoryPalette::from_theme(data.theme);
+    let (w, h) = (layout.width, layout.height);
+
+    let mut svg = String::with_capacity(16_000);
+    write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    )
+    .map_err(svg_fmt_error)?;
+    write!(
+        svg,
+        r#"<style>text {{ font-family: "{MAIN_FONT_NAME}", "Microsoft YaHei", Arial, sans-serif; }}</style><rect width="100%" height="100%" fill="{}" />"#,
+        c.bg
+    )
+    .map_err(svg_fmt_error)?;
+
+    write!(
+        svg,
+        r#"<text x="{}" y="64" font-size="40" font-weight="bold" fill="{}">{}</text><text x="{}" y="108" font-size="22" fill="{}">{}</text>"#,
+        layout.plot_left,
+        c.text,
+        escape_xml(&rks_history_title(data)),
+        layout.plot_left,
+        c.text_secondary,
+        escape_xml(&rks_history_subtitle(data, &chart)),
+    )
+    .map_err(svg_fmt_error)?;
+
+    if chart.empty {
+        write!(
+            svg,
+            r#"<text x="{}" y="{}" font-size="28" fill="{}" text-anchor="middle">所选时间范围内没有 RKS 记录</text>"#,
+            f64::from(w) / 2.0,
+            f64::midpoint(layout.plot_top, layout.plot_bottom),
+            c.text_secondary
+        )
+        .map_err(svg_fmt_error)?;
+    }
+
+    for t in &chart.y_ticks {
+        write!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="1" /><text x="{:.1}" y="{:.1}" font-size="18" fill="{}" text-anchor="end">{}</text>"#,
+            layout.plot_left,
+            t.pos,
+            layout.plot_right,
+            t.pos,
+            c.grid,
+            layout.plot_left - 12.0,
+            t.pos + 6.0,
+            c.text_secondary,
+            t.label
+        )
+        .map_err(svg_fmt_error)?;
+    }
+    for t in &chart.x_ticks {
+        write!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-size="18" fill="{}" text-anchor="middle">{}</text>"#,
+            t.pos,
+            layout.plot_bottom + 30.0,
+            c.text_secondary,
+            t.label
+        )
+        .map_err(svg_fmt_error)?;
+    }
+
+    if !chart.empty {
+        write!(
+            svg,
+            r#"<polygon points="{}" fill="{}" stroke="none" /><polyline points="{}" fill="none" stroke="{}" stroke-width="3" stroke-linejoin="round" stroke-linecap="round" />"#,
+            chart.area_points, c.area, chart.line_points, c.line
+        )
+        .map_err(svg_fmt_error)?;
+    }
+
+    for m in &chart.records {
+        write!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="{}" />"#,
+            m.x, m.y, c.peak
+        )
+        .map_err(svg_fmt_error)?;
+    }
+    for j in &chart.jumps {
+        let color = if j.up { c.jump_up } else { c.jump_down };
+        write!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="6" fill="none" stroke="{color}" stroke-width="2" /><text x="{:.1}" y="{:.1}" font-size="18" font-weight="bold" fill="{color}" text-anchor="middle">{}</text>"#,
+            j.x, j.y, j.x, j.label_y, j.label
+        )
+        .map_err(svg_fmt_error)?;
+    }
+    if let Some(p) = &chart.peak {
+        write!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="8" fill="{}" stroke="{}" stroke-width="2" /><text x="{:.1}" y="{:.1}" font-size="20" font-weight="bold" fill="{}" text-anchor="middle">{}</text>"#,
+            p.x,
+            p.y,
+            c.peak,
+            c.bg,
+            p.x.clamp(layout.plot_left + 60.0, layout.plot_right - 60.0),
+            p.y - 18.0,
+            c.peak,
+            escape_xml(&p.label)
+        )
+        .map_err(svg_fmt_error)?;
+    }
+
+    write!(
+        svg,
+        r#"<text x="{}" y="{}" font-size="16" fill="{}" text-anchor="end">{}</text></svg>"#,
+        layout.plot_right,
+        h - 20,
+        c.text_secondary,
+        escape_xml(&rks_history_footer(data))
+    )
+    .map_err(svg_fmt_error)?;
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::super::RksHistoryPoint;
+    use super::*;
+
+    fn data(rks: &[(f64, f64)]) -> RksHistoryRenderData {
+        let t0 = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        RksHistoryRenderData {
+            player_name: Some("Alice & Bob".to_string()),
+            range_label: "近 30 天".to_string(),
+            points: rks
+                .iter()
+                .enumerate()
+                .map(|(i, &(rks, rks_jump))| RksHistoryPoint {
+                    at: t0 + Duration::days(i64::try_from(i).unwrap()),
+                    rks,
+                    rks_jump,
+                })
+                .collect(),
+            jump_threshold: 0.1,
+            theme: Theme::Black,
+            custom_footer_text: None,
+        }
+    }
+
+    #[test]
+    fn chart_marks_running_highs_peak_and_big_jumps() {
+        let d = data(&[
+            (14.0, 0.0),
+            (14.05, 0.05),
+            (14.30, 0.25),
+            (14.20, -0.10),
+            (14.40, 0.20),
+        ]);
+        let chart = build_rks_history_chart(&d, &RksHistoryChartLayout::default());
+        assert!(!chart.empty);
+        assert_eq!(chart.peak_rks, Some(14.40));
+        assert_eq!(chart.current_rks, Some(14.40));
+        // 14.05 与 14.30 为刷新最高点；14.40 是全局峰值，单独标出
+        assert_eq!(chart.records.len(), 2);
+        let labels: Vec<&str> = chart.jumps.iter().map(|j| j.label.as_str()).collect();
+        assert_eq!(labels, ["+0.25", "-0.10", "+0.20"]);
+        assert!(!chart.jumps[1].up);
+        assert_eq!(chart.line_points.split(' ').count(), 5);
+    }
+
+    #[test]
+    fn svg_escapes_name_and_handles_empty_history() {
+        let svg = generate_rks_history_svg_string(&data(&[(14.0, 0.0), (14.5, 0.5)]), None)
+            .expect("render rks history svg");
+        assert!(svg.contains("Alice &amp; Bob"));
+        assert!(svg.contains("Peak 14.5000"));
+        assert!(svg.contains("+0.50"));
+
+        let svg = generate_rks_history_svg_string(&data(&[]), None).expect("render empty");
+        assert!(svg.contains("没有 RKS 记录"));
+        assert!(!svg.contains("<polyline"));
+    }
+}
@@ -5,6 +5,7 @@ use std::fmt::Write;
 use crate::error::AppError;
 
 use super::SongRenderData;
+use super::avatar::write_circle_avatar;
 use super::song_illustration::resolve_song_illustration_href;
 use super::svg_error::svg_fmt_error;
 use super::text::escape_xml;
@@ -58,12 +59,26 @@ pub(super) fn write_player_info(ctx: SongPlayerInfoRenderContext<'_>) -> Result<
 
     writeln!(svg, r#"<rect x="{player_info_x}" y="{player_info_y}" width="{player_info_width}" height="{player_info_height}" rx="8" ry="8" class="player-info-card" filter="url(#card-shadow)" />"#).map_err(svg_fmt_error)?;
 
+    let mut text_x = player_info_x + 20.0;
+    if let Some(avatar) = data.player_avatar.as_deref() {
+        let r = (player_info_height / 2.0 - 10.0).max(12.0);
+        write_circle_avatar(
+            svg,
+            avatar,
+            text_x + r,
+            player_info_y + player_info_height / 2.0,
+            r,
+            "#FFFFFF",
+        )?;
+        text_x += r * 2.0 + 14.0;
+    }
+
     let player_name_display = data.player_name.as_deref().unwrap_or("Player");
     let player_name_display_xml = escape_xml(player_name_display);
     writeln!(
         svg,
         r#"<text x="{}" y="{}" class="text text-player-info">Player: {}</text>"#,
-        player_info_x + 20.0,
+        text_x,
         player_info_y + 49.0,
         player_name_display_xml
     )
@@ -95,6 +110,7 @@ mod tests {
             song_name: "Song".to_string(),
             song_id: "song-id".to_string(),
             player_name: Some("玩家<&>\"".to_string()),
+            player_avatar: None,
             update_time: Utc::now(),
             difficulty_scores: HashMap::new(),
             illustration_path: None,
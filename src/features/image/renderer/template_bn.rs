@@ -64,6 +64,8 @@ struct HeaderRightLineCtx {
 #[derive(Debug, Clone, Serialize)]
 struct HeaderCtx {
     player_title_xml: String,
+    /// 玩家头像（已转义）；None 时模板不绘制头像
+    avatar_href_xml: Option<String>,
     ap_text_xml: String,
     bn_text_xml: String,
     right_lines: Vec<HeaderRightLineCtx>,
@@ -306,6 +308,7 @@ where
         },
         header: HeaderCtx {
             player_title_xml: escape_xml(&header_text.player_title),
+            avatar_href_xml: stats.player_avatar.as_deref().map(escape_xml),
             ap_text_xml: escape_xml(&header_text.ap_text),
             bn_text_xml: escape_xml(&header_text.bn_text),
             right_lines,
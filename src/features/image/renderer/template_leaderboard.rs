@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+use super::leaderboard::{leaderboard_detail_text, leaderboard_name_display};
+use super::template_shared::{
+    FontsCtx, JsonOverrideCacheEntry, PageCtx, clamp_template_id, read_json_override_cached,
+    render_template, template_base_dir,
+};
+use super::text::escape_xml;
+use super::{LeaderboardRenderData, MAIN_FONT_NAME};
+
+// ---------------- 排行榜（Leaderboard）----------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct LeaderboardTemplateLayout {
+    pub width: u32,
+    pub header_height: f64,
+    pub row_height: f64,
+    /// 存在次要信息行（Best/AP）时的行高
+    pub row_height_detail: f64,
+    pub footer_height: f64,
+    pub padding: f64,
+}
+
+impl Default for LeaderboardTemplateLayout {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            header_height: 120.0,
+            row_height: 60.0,
+            row_height_detail: 84.0,
+            footer_height: 40.0,
+            padding: 20.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LeaderboardCtx {
+    page: PageCtx,
+    fonts: FontsCtx,
+    layout: LeaderboardTemplateLayout,
+    title_xml: String,
+    rows: Vec<LeaderboardRowCtx>,
+    footer: LeaderboardFooterCtx,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LeaderboardRowCtx {
+    rank: i64,
+    y: f64,
+    h: f64,
+    text_y: f64,
+    name_xml: String,
+    rks_text: String,
+    detail_xml: Option<String>,
+    is_last: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LeaderboardFooterCtx {
+    y: f64,
+    text_xml: String,
+}
+
+static LEADERBOARD_TEMPLATE_LAYOUT_JSON_CACHE: OnceLock<
+    RwLock<HashMap<PathBuf, JsonOverrideCacheEntry<LeaderboardTemplateLayout>>>,
+> = OnceLock::new();
+
+fn read_leaderboard_template_layout_override(cfg_path: &Path) -> Option<LeaderboardTemplateLayout> {
+    let cache = LEADERBOARD_TEMPLATE_LAYOUT_JSON_CACHE.get_or_init(|| RwLock::new(HashMap::new()));
+    read_json_override_cached(cache, cfg_path)
+}
+
+pub(super) fn generate_leaderboard_svg_with_template(
+    data: &LeaderboardRenderData,
+    template_id: Option<&str>,
+) -> Result<String, AppError> {
+    let template_id = clamp_template_id(template_id);
+    let template_name = format!("leaderboard/{template_id}.svg.jinja");
+
+    let mut layout = LeaderboardTemplateLayout::default();
+    let cfg_path = template_base_dir()
+        .join("leaderboard")
+        .join(format!("{template_id}.json"));
+    if let Some(v) = read_leaderboard_template_layout_override(&cfg_path) {
+        layout = v;
+    }
+
+    let details: Vec<Option<String>> = data.entries.iter().map(leaderboard_detail_text).collect();
+    let row_h = if details.iter().any(Option::is_some) {
+        layout.row_height_detail
+    } else {
+        layout.row_height
+    };
+
+    let last = data.entries.len().saturating_sub(1);
+    let mut rows = Vec::with_capacity(data.entries.len());
+    let mut y = layout.header_height;
+    for (i, (entry, detail)) in data.entries.iter().zip(details).enumerate() {
+        let text_y = if detail.is_some() {
+            y + 40.0
+        } else {
+            y + row_h / 2.0 + 10.0
+        };
+        rows.push(LeaderboardRowCtx {
+            rank: entry.rank,
+            y,
+            h: row_h,
+            text_y,
+            name_xml: escape_xml(&leaderboard_name_display(&entry.player_name)),
+            rks_text: format!("{:.2}", entry.rks),
+            detail_xml: detail.map(|d| escape_xml(&d)),
+            is_last: i == last,
+        });
+        y += row_h;
+    }
+
+    let height_f = y + layout.footer_height;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let height = height_f.ceil().max(1.0) as u32;
+    let updated = data.update_time.format("%Y-%m-%d %H:%M:%S");
+
+    let ctx = LeaderboardCtx {
+        page: PageCtx {
+            width: layout.width.max(1),
+            height,
+        },
+        fonts: FontsCtx {
+            main: MAIN_FONT_NAME,
+        },
+        title_xml: escape_xml(&data.title),
+        rows,
+        footer: LeaderboardFooterCtx {
+            y: height_f - 15.0,
+            text_xml: escape_xml(&format!("更新时间: {updated} UTC")),
+        },
+        layout,
+    };
+
+    render_template(&template_name, &ctx)
+}
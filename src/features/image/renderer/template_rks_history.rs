@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+
+use super::rks_history::{
+    RksHistoryChart, RksHistoryChartLayout, RksHistoryPalette, build_rks_history_chart,
+    rks_history_footer, rks_history_subtitle, rks_history_title,
+};
+use super::template_shared::{
+    FontsCtx, JsonOverrideCacheEntry, PageCtx, clamp_template_id, read_json_override_cached,
+    render_template, template_base_dir,
+};
+use super::text::escape_xml;
+use super::{MAIN_FONT_NAME, RksHistoryRenderData};
+
+// ---------------- RKS 历史折线图（RKS History）----------------
+
+#[derive(Debug, Clone, Serialize)]
+struct RksHistoryCtx {
+    page: PageCtx,
+    fonts: FontsCtx,
+    layout: RksHistoryChartLayout,
+    colors: RksHistoryPalette,
+    title_xml: String,
+    subtitle_xml: String,
+    chart: RksHistoryChart,
+    footer_xml: String,
+}
+
+static RKS_HISTORY_TEMPLATE_LAYOUT_JSON_CACHE: OnceLock<
+    RwLock<HashMap<PathBuf, JsonOverrideCacheEntry<RksHistoryChartLayout>>>,
+> = OnceLock::new();
+
+fn read_rks_history_template_layout_override(cfg_path: &Path) -> Option<RksHistoryChartLayout> {
+    let cache = RKS_HISTORY_TEMPLATE_LAYOUT_JSON_CACHE.get_or_init(|| RwLock::new(HashMap::new()));
+    read_json_override_cached(cache, cfg_path)
+}
+
+pub(super) fn generate_rks_history_svg_with_template(
+    data: &RksHistoryRenderData,
+    template_id: Option<&str>,
+) -> Result<String, AppError> {
+    let template_id = clamp_template_id(template_id);
+    let template_name = format!("rks_history/{template_id}.svg.jinja");
+
+    let cfg_path = template_base_dir()
+        .join("rks_history")
+        .join(format!("{template_id}.json"));
+    let layout = read_rks_history_template_layout_override(&cfg_path).unwrap_or_default();
+
+    let chart = build_rks_history_chart(data, &layout);
+    let ctx = RksHistoryCtx {
+        page: PageCtx {
+            width: layout.width.max(1),
+            height: layout.height.max(1),
+        },
+        fonts: FontsCtx {
+            main: MAIN_FONT_NAME,
+        },
+        colors: RksHistoryPalette::from_theme(data.theme),
+        title_xml: escape_xml(&rks_history_title(data)),
+        subtitle_xml: escape_xml(&rks_history_subtitle(data, &chart)),
+        footer_xml: escape_xml(&rks_history_footer(data)),
+        chart,
+        layout,
+    };
+
+    render_template(&template_name, &ctx)
+}
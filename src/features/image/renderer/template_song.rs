@@ -73,6 +73,8 @@ struct PlayerInfoCtx {
     y_rks: f64,
     name_xml: String,
     rks_xml: String,
+    /// 玩家头像（已转义）；None 时模板不绘制头像
+    avatar_href_xml: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -202,6 +204,7 @@ pub(super) fn generate_song_svg_with_template(
             y_name: padding + 28.0,
             y_rks: padding + 55.0,
             name_xml: escape_xml(player_name),
+            avatar_href_xml: data.player_avatar.as_deref().map(escape_xml),
             rks_xml: escape_xml(&player_rks),
         },
         song: SongTitleCtx {
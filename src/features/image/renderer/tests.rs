@@ -3,8 +3,10 @@ use super::urls::{
     remote_illustration_dir_for_category, to_public_url_for_base, to_somnia_public_url_for_base,
 };
 use super::{
-    PlayerStats, RenderRecord, SongRenderData, Theme, generate_song_svg_string,
-    generate_svg_string, render_svg_unified,
+    LeaderboardEntry, LeaderboardRenderData, PlayerStats, RenderRecord, RksHistoryPoint,
+    RksHistoryRenderData, SongRenderData, Theme, generate_leaderboard_svg_string,
+    generate_rks_history_svg_string, generate_song_svg_string, generate_svg_string,
+    render_svg_unified,
 };
 use chrono::Utc;
 use std::fs;
@@ -125,6 +127,7 @@ fn generate_svg_uses_remote_cover_url_when_base_provided() {
         best_27_avg: None,
         real_rks: None,
         player_name: Some("Tester".to_string()),
+        player_avatar: None,
         update_time: Utc::now(),
         n: 1,
         ap_top_3_scores: vec![],
@@ -156,6 +159,7 @@ fn generate_song_svg_uses_remote_illust_when_missing_path() {
         song_name: "RemoteSong".to_string(),
         song_id: "SONG REMOTE 456".to_string(),
         player_name: Some("Tester".to_string()),
+        player_avatar: None,
         update_time: Utc::now(),
         difficulty_scores: std::collections::HashMap::default(),
         illustration_path: None,
@@ -185,6 +189,7 @@ fn generate_bn_svg_renders_with_external_template() {
         best_27_avg: Some(12.3456),
         real_rks: Some(12.345_678),
         player_name: Some("Tester".to_string()),
+        player_avatar: None,
         update_time: Utc::now(),
         n: 1,
         ap_top_3_scores: vec![],
@@ -203,6 +208,7 @@ fn generate_bn_svg_renders_with_external_template() {
         Some("default"),
     )
     .unwrap();
+    assert!(!svg.contains("player-avatar-clip"));
     assert!(svg.contains("<svg"));
     assert!(svg.contains("id=\"main-cards\""));
 }
@@ -225,6 +231,7 @@ fn generate_bn_svg_renders_with_neo_template() {
         best_27_avg: Some(12.3456),
         real_rks: Some(12.345_678),
         player_name: Some("Tester".to_string()),
+        player_avatar: Some("data:image/png;base64,AAAA".to_string()),
         update_time: Utc::now(),
         n: 1,
         ap_top_3_scores: vec![],
@@ -243,6 +250,8 @@ fn generate_bn_svg_renders_with_neo_template() {
         Some("neo"),
     )
     .unwrap();
+    assert!(svg.contains("player-avatar-clip"));
+    assert!(svg.contains(r#"<text x="84" y="56""#));
     assert!(svg.contains("<svg"));
     assert!(svg.contains("id=\"main-cards\""));
 }
@@ -265,6 +274,7 @@ fn generate_bn_svg_renders_with_firstlook_template() {
         best_27_avg: Some(12.3456),
         real_rks: Some(12.345_678),
         player_name: Some("Tester".to_string()),
+        player_avatar: Some("data:image/png;base64,AAAA".to_string()),
         update_time: Utc::now(),
         n: 1,
         ap_top_3_scores: vec![record.clone()],
@@ -283,6 +293,8 @@ fn generate_bn_svg_renders_with_firstlook_template() {
         Some("firstlook"),
     )
     .unwrap();
+    assert!(svg.contains("player-avatar-clip"));
+    assert!(svg.contains(r#"<text x="136" y="55""#));
     assert!(svg.contains("<svg"));
     assert!(svg.contains("id=\"dashboard-header\""));
     assert!(svg.contains("id=\"champion-wall\""));
@@ -296,16 +308,80 @@ fn generate_song_svg_renders_with_external_template() {
         song_name: "TemplateSong".to_string(),
         song_id: "TEMPLATE_SONG_ID".to_string(),
         player_name: Some("Tester".to_string()),
+        player_avatar: Some("data:image/png;base64,AAAA".to_string()),
         update_time: Utc::now(),
         difficulty_scores: std::collections::HashMap::default(),
         illustration_path: None,
         custom_footer_text: Some("Footer".to_string()),
     };
     let svg = generate_song_svg_string(&data, false, None, Some("default")).unwrap();
+    assert!(svg.contains(r#"href="data:image/png;base64,AAAA""#));
     assert!(svg.contains("<svg"));
     assert!(svg.contains("difficulty-card"));
 }
 
+#[test]
+fn generate_leaderboard_svg_renders_with_external_template() {
+    ensure_config_inited();
+    let data = LeaderboardRenderData {
+        title: "RKS <Top>".to_string(),
+        update_time: Utc::now(),
+        entries: vec![
+            LeaderboardEntry {
+                rank: 7,
+                player_name: "Alice".to_string(),
+                rks: 16.01,
+                best_chart: Some("Igallta [AT] 16.60".to_string()),
+                ap_chart: None,
+            },
+            LeaderboardEntry {
+                rank: 8,
+                player_name: "ab12****".to_string(),
+                rks: 15.5,
+                best_chart: None,
+                ap_chart: None,
+            },
+        ],
+        display_count: 2,
+    };
+    let svg = generate_leaderboard_svg_string(&data, Some("default")).unwrap();
+    assert!(svg.contains("<svg"));
+    assert!(svg.contains("RKS &lt;Top&gt;"));
+    assert!(svg.contains("#7"));
+    assert!(svg.contains("Best: Igallta [AT] 16.60"));
+    assert!(svg.contains("16.01"));
+}
+
+#[test]
+fn generate_rks_history_svg_renders_with_external_template() {
+    ensure_config_inited();
+    let now = Utc::now();
+    let data = RksHistoryRenderData {
+        player_name: Some("Tester".to_string()),
+        range_label: "近 7 天".to_string(),
+        points: vec![
+            RksHistoryPoint {
+                at: now - chrono::Duration::days(2),
+                rks: 15.1,
+                rks_jump: 0.0,
+            },
+            RksHistoryPoint {
+                at: now,
+                rks: 15.4,
+                rks_jump: 0.3,
+            },
+        ],
+        jump_threshold: 0.1,
+        theme: Theme::White,
+        custom_footer_text: Some("footer".to_string()),
+    };
+    let svg = generate_rks_history_svg_string(&data, Some("default")).unwrap();
+    assert!(svg.contains("<polyline"));
+    assert!(svg.contains("Peak 15.4000"));
+    assert!(svg.contains("+0.30"));
+    assert!(svg.contains("#F7FAFF"));
+}
+
 #[test]
 fn webp_encoding_respects_quality_and_lossless() {
     ensure_config_inited();
@@ -31,7 +31,7 @@ pub struct RenderBnRequest {
     /// 是否将封面等资源内嵌到 PNG（默认为 false）
     #[serde(default)]
     pub embed_images: bool,
-    /// 可选：用于显示的玩家昵称（若未提供且无法从服务端获取，将使用默认占位）
+    /// 可选：用于显示的玩家昵称（未提供时使用 TapTap 资料昵称，仍无法获取则使用默认占位）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nickname: Option<String>,
 }
@@ -58,6 +58,84 @@ fn default_n() -> u32 {
     30
 }
 
+/// RKS 历史折线图的时间范围
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, utoipa::ToSchema, PartialEq, Eq)]
+pub enum RksHistoryRange {
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    #[default]
+    Month,
+    #[serde(rename = "90d")]
+    Quarter,
+    #[serde(rename = "1y")]
+    Year,
+    #[serde(rename = "all")]
+    All,
+}
+
+impl RksHistoryRange {
+    /// 回溯天数；`All` 为 None
+    #[must_use]
+    pub fn days(self) -> Option<i64> {
+        match self {
+            Self::Week => Some(7),
+            Self::Month => Some(30),
+            Self::Quarter => Some(90),
+            Self::Year => Some(365),
+            Self::All => None,
+        }
+    }
+
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Week => "7d",
+            Self::Month => "30d",
+            Self::Quarter => "90d",
+            Self::Year => "1y",
+            Self::All => "all",
+        }
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Week => "近 7 天",
+            Self::Month => "近 30 天",
+            Self::Quarter => "近 90 天",
+            Self::Year => "近 1 年",
+            Self::All => "全部记录",
+        }
+    }
+}
+
+/// RKS 历史折线图渲染请求体
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderRksHistoryRequest {
+    /// 认证方式（二选一）：sessionToken 或 externalCredentials
+    #[serde(flatten)]
+    pub auth: UnifiedSaveRequest,
+    /// 时间范围：7d/30d/90d/1y/all（默认 30d）
+    #[serde(default)]
+    pub range: RksHistoryRange,
+    /// 渲染主题：white/black（默认 black）
+    #[serde(default)]
+    pub theme: Theme,
+    /// 标注阈值：单次 RKS 变化绝对值不小于该值时在图上标注（默认 0.1）
+    #[schema(example = 0.1)]
+    #[serde(default = "default_jump_threshold")]
+    pub jump_threshold: f64,
+    /// 可选：用于显示的玩家昵称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+}
+
+fn default_jump_threshold() -> f64 {
+    0.1
+}
+
 /// 用户自定义 BN 渲染请求（未验证成绩）
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -65,7 +143,7 @@ pub struct RenderUserBnRequest {
     /// 主题（默认 black）
     #[serde(default)]
     pub theme: Theme,
-    /// 可选昵称（未提供时可从 users/me 获取）
+    /// 可选昵称（未提供时使用 TapTap 资料昵称）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nickname: Option<String>,
     /// 解除水印的口令（匹配配置或动态口令时，显式/隐式水印均关闭）
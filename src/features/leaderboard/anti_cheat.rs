@@ -0,0 +1,313 @@
+//! 存档提交反作弊规则引擎
+//!
+//! 写排行榜前按 `leaderboard.anti_cheat` 逐条评估检测规则，命中规则的权重累加为可疑度，
+//! 达到 `hide_threshold` 时隐藏用户。命中的规则连同原因写入 `moderation_flags`，
+//! 管理员可通过 `/admin/users/flags` 查看。
+
+use std::collections::HashSet;
+
+use crate::config::AntiCheatConfig;
+use crate::stats_contract::ChartScoreSnapshot;
+
+/// 写入 `moderation_flags.created_by` 的自动审核标识
+pub const ANTI_CHEAT_ACTOR: &str = "anti_cheat";
+
+const MAX_SCORE: f64 = 1_000_000.0;
+/// 分数中由 ACC 决定的部分（其余 100000 分由最大连击决定）
+const ACC_SCORE: f64 = 900_000.0;
+const COMBO_SCORE: f64 = 100_000.0;
+/// 原因中最多列出的谱面数
+const MAX_REASON_CHARTS: usize = 3;
+
+/// 一次提交的检测输入
+pub struct SubmissionSignals<'a> {
+    /// 当前规则下的总 RKS
+    pub total_rks: f64,
+    /// 上一次提交的总 RKS；首次提交为 None
+    pub prev_rks: Option<f64>,
+    pub user_kind: Option<&'a str>,
+    /// 存档 summary 中游戏自身记录的 RKS
+    pub summary_ranking_score: Option<f64>,
+    pub charts: &'a [ChartScoreSnapshot],
+    /// 上一次提交时的逐谱面成绩；首次提交（无基线）为 None
+    pub prev_charts: Option<&'a [ChartScoreSnapshot]>,
+    /// 统计窗口内同一 IP 提交过的其他账号数；无 IP 信息时为 None
+    pub other_accounts_on_ip: Option<i64>,
+}
+
+/// 命中的检测规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiredRule {
+    pub rule: &'static str,
+    pub weight: f64,
+    pub reason: String,
+}
+
+/// 评估结果
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionVerdict {
+    pub suspicion: f64,
+    pub hide: bool,
+    pub fired: Vec<FiredRule>,
+}
+
+type Detector = fn(&AntiCheatConfig, &SubmissionSignals<'_>) -> Option<FiredRule>;
+
+const DETECTORS: [Detector; 6] = [
+    detect_high_rks,
+    detect_rks_jump,
+    detect_impossible_score,
+    detect_ranking_score_mismatch,
+    detect_ap_burst,
+    detect_shared_ip,
+];
+
+/// 评估一次提交：累加命中规则的权重，sessionToken 提交按配置扣减。
+#[must_use]
+pub fn evaluate_submission(
+    cfg: &AntiCheatConfig,
+    signals: &SubmissionSignals<'_>,
+) -> SubmissionVerdict {
+    let fired: Vec<FiredRule> = DETECTORS
+        .iter()
+        .filter_map(|detect| detect(cfg, signals))
+        .filter(|rule| rule.weight > 0.0)
+        .collect();
+    let mut suspicion: f64 = fired.iter().map(|r| r.weight).sum();
+    if signals.user_kind == Some("session_token") {
+        suspicion = (suspicion - cfg.session_token_discount).max(0.0);
+    }
+    SubmissionVerdict {
+        suspicion,
+        hide: suspicion >= cfg.hide_threshold,
+        fired,
+    }
+}
+
+/// 相对上一次提交的 RKS 涨跌；首次提交或变化可忽略时为 0。
+#[must_use]
+pub fn rks_jump(total_rks: f64, prev_rks: Option<f64>) -> f64 {
+    const RKS_JUMP_EPS: f64 = 1e-9;
+    match prev_rks {
+        Some(prev) if prev > 0.0 && (total_rks - prev).abs() >= RKS_JUMP_EPS => total_rks - prev,
+        _ => 0.0,
+    }
+}
+
+fn detect_high_rks(cfg: &AntiCheatConfig, s: &SubmissionSignals<'_>) -> Option<FiredRule> {
+    (s.total_rks > cfg.high_rks_threshold).then(|| FiredRule {
+        rule: "high_rks",
+        weight: cfg.high_rks_weight,
+        reason: format!(
+            "总 RKS {:.4} 超过 {:.2}",
+            s.total_rks, cfg.high_rks_threshold
+        ),
+    })
+}
+
+fn detect_rks_jump(cfg: &AntiCheatConfig, s: &SubmissionSignals<'_>) -> Option<FiredRule> {
+    let jump = rks_jump(s.total_rks, s.prev_rks);
+    let (threshold, weight) = if jump > cfg.rks_jump_major {
+        (cfg.rks_jump_major, cfg.rks_jump_major_weight)
+    } else if jump > cfg.rks_jump_minor {
+        (cfg.rks_jump_minor, cfg.rks_jump_minor_weight)
+    } else {
+        return None;
+    };
+    Some(FiredRule {
+        rule: "rks_jump",
+        weight,
+        reason: format!("单次提交 RKS 上涨 {jump:.4}（阈值 {threshold:.2}）"),
+    })
+}
+
+/// 分数 = 900000 × ACC + 100000 × 最大连击占比，据此校验单个谱面的分数/ACC 组合。
+///
+/// 存档中的最高分、最高 ACC 与 FC 标记各自取历史最大值，可能来自不同的游玩，
+/// 因此只校验对独立最大值仍成立的约束，不用 FC 推断连击部分。
+fn is_impossible_chart(c: &ChartScoreSnapshot, tolerance: f64) -> bool {
+    #[allow(clippy::cast_precision_loss)]
+    let score = c.score as f64;
+    if !(0.0..=MAX_SCORE).contains(&score) || !(0.0..=100.0 + 1e-6).contains(&c.acc) {
+        return true;
+    }
+    let combo_part = score - ACC_SCORE * c.acc / 100.0;
+    if combo_part < -tolerance || combo_part > COMBO_SCORE + tolerance {
+        return true;
+    }
+    // 全 Perfect 的那次游玩必为满分，最高分不会低于它
+    let all_perfect = c.acc >= 100.0 - 1e-6;
+    all_perfect && (combo_part - COMBO_SCORE).abs() > tolerance
+}
+
+fn detect_impossible_score(cfg: &AntiCheatConfig, s: &SubmissionSignals<'_>) -> Option<FiredRule> {
+    let bad: Vec<&ChartScoreSnapshot> = s
+        .charts
+        .iter()
+        .filter(|c| is_impossible_chart(c, cfg.impossible_score_tolerance))
+        .collect();
+    if bad.is_empty() {
+        return None;
+    }
+    let examples: Vec<String> = bad
+        .iter()
+        .take(MAX_REASON_CHARTS)
+        .map(|c| {
+            format!(
+                "{} {} score={} acc={:.4} fc={}",
+                c.song_id, c.difficulty, c.score, c.acc, c.is_fc
+            )
+        })
+        .collect();
+    Some(FiredRule {
+        rule: "impossible_score",
+        weight: cfg.impossible_score_weight,
+        reason: format!(
+            "{} 个谱面的分数/ACC 组合不可能成立：{}",
+            bad.len(),
+            examples.join("; ")
+        ),
+    })
+}
+
+fn detect_ranking_score_mismatch(
+    cfg: &AntiCheatConfig,
+    s: &SubmissionSignals<'_>,
+) -> Option<FiredRule> {
+    let reported = s.summary_ranking_score.filter(|v| *v > 0.0)?;
+    let diff = (reported - s.total_rks).abs();
+    (diff > cfg.ranking_score_tolerance).then(|| FiredRule {
+        rule: "ranking_score_mismatch",
+        weight: cfg.ranking_score_weight,
+        reason: format!(
+            "存档 summary RKS {reported:.4} 与计算值 {:.4} 相差 {diff:.4}",
+            s.total_rks
+        ),
+    })
+}
+
+fn is_ap(c: &ChartScoreSnapshot) -> bool {
+    c.score >= 1_000_000
+}
+
+fn detect_ap_burst(cfg: &AntiCheatConfig, s: &SubmissionSignals<'_>) -> Option<FiredRule> {
+    let prev = s.prev_charts?;
+    let prev_ap: HashSet<(&str, &str)> = prev
+        .iter()
+        .filter(|c| is_ap(c))
+        .map(|c| (c.song_id.as_str(), c.difficulty.as_str()))
+        .collect();
+    let new_ap = s
+        .charts
+        .iter()
+        .filter(|c| is_ap(c) && !prev_ap.contains(&(c.song_id.as_str(), c.difficulty.as_str())))
+        .count();
+    (new_ap > cfg.ap_burst_max).then(|| FiredRule {
+        rule: "ap_burst",
+        weight: cfg.ap_burst_weight,
+        reason: format!(
+            "两次提交之间新增 {new_ap} 个 AP 谱面（上限 {}）",
+            cfg.ap_burst_max
+        ),
+    })
+}
+
+fn detect_shared_ip(cfg: &AntiCheatConfig, s: &SubmissionSignals<'_>) -> Option<FiredRule> {
+    let accounts = s.other_accounts_on_ip? + 1;
+    (accounts > cfg.shared_ip_max_accounts).then(|| FiredRule {
+        rule: "shared_ip",
+        weight: cfg.shared_ip_weight,
+        reason: format!(
+            "{} 小时内同一 IP 提交了 {accounts} 个账号（上限 {}）",
+            cfg.shared_ip_window_hours, cfg.shared_ip_max_accounts
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(song: &str, score: i64, acc: f64, is_fc: bool) -> ChartScoreSnapshot {
+        ChartScoreSnapshot {
+            song_id: song.to_string(),
+            difficulty: "IN".to_string(),
+            score,
+            acc,
+            is_fc,
+        }
+    }
+
+    fn signals(charts: &[ChartScoreSnapshot]) -> SubmissionSignals<'_> {
+        SubmissionSignals {
+            total_rks: 15.0,
+            prev_rks: Some(14.9),
+            user_kind: None,
+            summary_ranking_score: Some(15.0),
+            charts,
+            prev_charts: None,
+            other_accounts_on_ip: Some(0),
+        }
+    }
+
+    #[test]
+    fn default_rules_keep_legacy_scoring() {
+        let cfg = AntiCheatConfig::default();
+        let mut s = signals(&[]);
+        s.total_rks = 20.5;
+        s.prev_rks = Some(19.0);
+        s.summary_ranking_score = None;
+        let v = evaluate_submission(&cfg, &s);
+        assert!((v.suspicion - 1.3).abs() < 1e-9);
+        assert!(v.hide);
+
+        s.user_kind = Some("session_token");
+        s.prev_rks = Some(20.4);
+        let v = evaluate_submission(&cfg, &s);
+        assert!((v.suspicion - 0.3).abs() < 1e-9);
+        assert!(!v.hide);
+    }
+
+    #[test]
+    fn impossible_score_detects_inconsistent_combos() {
+        let tol = AntiCheatConfig::default().impossible_score_tolerance;
+        // 合法：99% ACC + 半数连击；FC 时连击部分满分；AP
+        assert!(!is_impossible_chart(&chart("a", 941_000, 99.0, false), tol));
+        assert!(!is_impossible_chart(&chart("a", 991_000, 99.0, true), tol));
+        // 最高分、最高 ACC 与 FC 来自不同游玩：FC 不要求连击部分满分
+        assert!(!is_impossible_chart(&chart("a", 950_000, 99.0, true), tol));
+        assert!(!is_impossible_chart(
+            &chart("a", 1_000_000, 100.0, true),
+            tol
+        ));
+        // 分数低于 ACC 下限、满 ACC 非满分、超过上限
+        assert!(is_impossible_chart(&chart("a", 800_000, 99.0, false), tol));
+        assert!(is_impossible_chart(&chart("a", 950_000, 100.0, false), tol));
+        assert!(is_impossible_chart(
+            &chart("a", 1_000_001, 100.0, true),
+            tol
+        ));
+    }
+
+    #[test]
+    fn fired_rules_carry_reasons_and_zero_weight_disables() {
+        let mut cfg = AntiCheatConfig::default();
+        let charts: Vec<ChartScoreSnapshot> = (0..20)
+            .map(|i| chart(&format!("s{i}"), 1_000_000, 100.0, true))
+            .collect();
+        let prev = vec![chart("s0", 990_000, 99.0, true)];
+        let mut s = signals(&charts);
+        s.prev_charts = Some(&prev);
+        s.summary_ranking_score = Some(14.0);
+        s.other_accounts_on_ip = Some(5);
+
+        let v = evaluate_submission(&cfg, &s);
+        let rules: Vec<&str> = v.fired.iter().map(|r| r.rule).collect();
+        assert_eq!(rules, ["ranking_score_mismatch", "ap_burst", "shared_ip"]);
+        assert!(v.fired[1].reason.contains("新增 20 个"));
+
+        cfg.ap_burst_weight = 0.0;
+        let v = evaluate_submission(&cfg, &s);
+        assert!(v.fired.iter().all(|r| r.rule != "ap_burst"));
+    }
+}
@@ -0,0 +1,138 @@
+//! RKS 分布快照
+//!
+//! 公开榜全部分数按 `leaderboard.distribution.refresh_secs` 周期缓存为升序数组
+//! （合并榜与各区域榜分别缓存），直方图与百分位都基于该快照计算，
+//! 请求本身不再扫描 `leaderboard_rks`。
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use moka::future::Cache;
+
+use crate::error::AppError;
+use crate::stats_contract::{ServerRegion, StatsStorage};
+
+/// 桶宽允许范围
+pub const MIN_BUCKET_WIDTH: f64 = 0.01;
+pub const MAX_BUCKET_WIDTH: f64 = 5.0;
+/// 浮点边界容差：避免 13.5 / 0.5 之类的整除结果落入前一个桶
+const BUCKET_EPS: f64 = 1e-9;
+
+/// 公开榜分数快照（升序）
+pub struct RksScoreSnapshot {
+    pub scores: Vec<f64>,
+    /// 快照计算时间（UTC RFC3339）
+    pub computed_at: String,
+}
+
+/// 直方图的一个桶：[from, to)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreBucket {
+    pub from: f64,
+    pub to: f64,
+    pub count: i64,
+}
+
+type SnapshotKey = (usize, Option<ServerRegion>);
+
+fn snapshot_cache() -> &'static Cache<SnapshotKey, Arc<RksScoreSnapshot>> {
+    static CACHE: OnceLock<Cache<SnapshotKey, Arc<RksScoreSnapshot>>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        let refresh = crate::config::AppConfig::global()
+            .leaderboard
+            .distribution
+            .refresh_secs
+            .max(1);
+        Cache::builder()
+            .max_capacity(16)
+            .time_to_live(Duration::from_secs(refresh))
+            .build()
+    })
+}
+
+/// 读取分数快照（`region` 为 None 时为合并榜）；过期后由首个请求重算，并发请求共享同一次加载。
+pub async fn load_score_snapshot(
+    storage: &Arc<StatsStorage>,
+    region: Option<ServerRegion>,
+) -> Result<Arc<RksScoreSnapshot>, AppError> {
+    let key = (Arc::as_ptr(storage) as usize, region);
+    let storage = storage.clone();
+    snapshot_cache()
+        .try_get_with(key, async move {
+            let scores = storage
+                .query_public_rks_scores(region.map(ServerRegion::as_str))
+                .await?;
+            Ok::<_, AppError>(Arc::new(RksScoreSnapshot {
+                scores,
+                computed_at: chrono::Utc::now().to_rfc3339(),
+            }))
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("加载 RKS 分布快照失败: {e}")))
+}
+
+fn round4(v: f64) -> f64 {
+    (v * 10_000.0).round() / 10_000.0
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn bucket_index(score: f64, width: f64) -> i64 {
+    (score / width + BUCKET_EPS).floor() as i64
+}
+
+/// 按桶宽统计升序分数的直方图；返回从最低到最高非空桶之间的连续桶（含空桶）。
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn histogram(sorted_scores: &[f64], width: f64) -> Vec<ScoreBucket> {
+    let (Some(first), Some(last)) = (sorted_scores.first(), sorted_scores.last()) else {
+        return Vec::new();
+    };
+    let lo = bucket_index(*first, width);
+    let hi = bucket_index(*last, width);
+    let mut buckets: Vec<ScoreBucket> = (lo..=hi)
+        .map(|i| ScoreBucket {
+            from: round4(i as f64 * width),
+            to: round4((i + 1) as f64 * width),
+            count: 0,
+        })
+        .collect();
+    for s in sorted_scores {
+        let idx = usize::try_from(bucket_index(*s, width) - lo).unwrap_or(0);
+        if let Some(b) = buckets.get_mut(idx) {
+            b.count += 1;
+        }
+    }
+    buckets
+}
+
+/// 分数高于 `score` 的人数（升序快照上二分）
+#[must_use]
+pub fn count_higher(sorted_scores: &[f64], score: f64) -> usize {
+    sorted_scores.len() - sorted_scores.partition_point(|s| *s <= score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_covers_contiguous_buckets() {
+        let scores = [12.1, 12.4, 13.5, 13.99, 14.0];
+        let b = histogram(&scores, 0.5);
+        let counts: Vec<(f64, i64)> = b.iter().map(|b| (b.from, b.count)).collect();
+        assert_eq!(
+            counts,
+            [(12.0, 2), (12.5, 0), (13.0, 0), (13.5, 2), (14.0, 1)]
+        );
+        assert!((b[0].to - 12.5).abs() < 1e-9);
+        assert!(histogram(&[], 0.5).is_empty());
+    }
+
+    #[test]
+    fn count_higher_uses_strict_comparison() {
+        let scores = [10.0, 11.0, 11.0, 12.0];
+        assert_eq!(count_higher(&scores, 11.0), 1);
+        assert_eq!(count_higher(&scores, 9.0), 4);
+        assert_eq!(count_higher(&scores, 12.5), 0);
+    }
+}
@@ -5,9 +5,14 @@ use axum::{
 use serde::Serialize;
 
 pub(crate) mod admin;
+pub(crate) mod chart;
 mod cursor;
+pub(crate) mod distribution;
+pub(crate) mod group;
+pub(crate) mod history;
 pub(crate) mod profile;
 pub(crate) mod ranking;
+pub(crate) mod stream;
 
 use crate::{error::AppError, state::AppState};
 
@@ -15,12 +20,26 @@ use crate::{error::AppError, state::AppState};
 pub(crate) use self::admin::require_admin_with_cfg;
 pub use self::admin::{
     AdminLeaderboardUserItem, AdminLeaderboardUsersResponse, AdminSetUserStatusRequest,
-    AdminUserStatusQuery, AdminUserStatusResponse, AdminUsersQuery, ForceAliasRequest,
-    ResolveRequest, SuspiciousItem, get_admin_leaderboard_users, get_admin_user_status,
-    get_suspicious, post_admin_user_status, post_alias_force, post_resolve,
+    AdminUserFlagItem, AdminUserFlagsQuery, AdminUserFlagsResponse, AdminUserStatusQuery,
+    AdminUserStatusResponse, AdminUsersQuery, ForceAliasRequest, ResolveRequest, SuspiciousItem,
+    get_admin_leaderboard_users, get_admin_user_flags, get_admin_user_status,
+    get_recompute_progress, get_suspicious, post_admin_user_status, post_alias_force,
+    post_recompute_cancel, post_recompute_start, post_resolve,
+};
+pub use self::chart::{ChartTopQuery, get_chart_top};
+pub use self::distribution::{DistributionQuery, get_rks_distribution};
+pub use self::group::{
+    GroupTopQuery, post_create_group, post_disband_group, post_group_by_rank, post_group_members,
+    post_group_top, post_join_group, post_kick_group_member, post_leave_group, post_my_groups,
+    post_reset_group_invite,
+};
+pub use self::history::{
+    GainersQuery, RankHistoryQuery, get_public_rank_history, get_rks_gainers, post_me_rank_history,
 };
 pub use self::profile::{get_public_profile, put_alias, put_profile};
-pub use self::ranking::{RankQuery, TopQuery, get_by_rank, get_top, post_me};
+pub(crate) use self::ranking::load_leaderboard_window;
+pub use self::ranking::{MeQuery, RankQuery, TopQuery, get_by_rank, get_top, post_me};
+pub use self::stream::{RksStreamQuery, get_rks_stream};
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -37,6 +56,23 @@ pub struct OkAliasResponse {
     pub alias: String,
 }
 
+/// 解析排行榜区域过滤参数：缺省/空/all 表示合并榜，其余须为 cn / global / unspecified。
+pub(super) fn parse_region_filter(
+    raw: Option<&str>,
+) -> Result<Option<crate::stats_contract::ServerRegion>, AppError> {
+    match raw.map(str::trim).filter(|r| !r.is_empty()) {
+        None => Ok(None),
+        Some(r) if r.eq_ignore_ascii_case("all") => Ok(None),
+        Some(r) => crate::stats_contract::ServerRegion::from_code(r)
+            .map(Some)
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "region 无效：{r}（可选 all / cn / global / unspecified）"
+                ))
+            }),
+    }
+}
+
 pub(super) fn normalize_moderation_status(raw: &str) -> Result<(&'static str, i64), AppError> {
     let st = raw.trim().to_lowercase();
     let mapped = match st.as_str() {
@@ -120,15 +156,49 @@ pub fn create_leaderboard_router() -> Router<AppState> {
         .route("/leaderboard/rks/top", get(get_top))
         .route("/leaderboard/rks/by-rank", get(get_by_rank))
         .route("/leaderboard/rks/me", post(post_me))
+        .route("/leaderboard/rks/me/history", post(post_me_rank_history))
+        .route("/leaderboard/rks/gainers", get(get_rks_gainers))
+        .route("/leaderboard/rks/distribution", get(get_rks_distribution))
+        .route("/leaderboard/rks/stream", get(get_rks_stream))
+        .route("/leaderboard/chart/top", get(get_chart_top))
+        .route("/leaderboard/groups", post(post_create_group))
+        .route("/leaderboard/groups/mine", post(post_my_groups))
+        .route("/leaderboard/groups/join", post(post_join_group))
+        .route("/leaderboard/groups/:id/members", post(post_group_members))
+        .route("/leaderboard/groups/:id/leave", post(post_leave_group))
+        .route("/leaderboard/groups/:id/kick", post(post_kick_group_member))
+        .route(
+            "/leaderboard/groups/:id/invite/reset",
+            post(post_reset_group_invite),
+        )
+        .route("/leaderboard/groups/:id/disband", post(post_disband_group))
+        .route("/leaderboard/groups/:id/rks/top", post(post_group_top))
+        .route(
+            "/leaderboard/groups/:id/rks/by-rank",
+            post(post_group_by_rank),
+        )
         .route("/leaderboard/alias", put(put_alias))
         .route("/leaderboard/profile", put(put_profile))
         .route("/public/profile/:alias", get(get_public_profile))
+        .route(
+            "/public/profile/:alias/rank-history",
+            get(get_public_rank_history),
+        )
         .route("/admin/leaderboard/suspicious", get(get_suspicious))
         .route("/admin/leaderboard/users", get(get_admin_leaderboard_users))
         .route("/admin/leaderboard/resolve", post(post_resolve))
         .route("/admin/users/status", get(get_admin_user_status))
         .route("/admin/users/status", post(post_admin_user_status))
+        .route("/admin/users/flags", get(get_admin_user_flags))
         .route("/admin/leaderboard/alias/force", post(post_alias_force))
+        .route(
+            "/admin/leaderboard/recompute",
+            get(get_recompute_progress).post(post_recompute_start),
+        )
+        .route(
+            "/admin/leaderboard/recompute/cancel",
+            post(post_recompute_cancel),
+        )
 }
 
 #[cfg(test)]
@@ -137,6 +207,26 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn parse_region_filter_accepts_known_codes() {
+        use crate::stats_contract::ServerRegion;
+
+        assert_eq!(parse_region_filter(None).unwrap(), None);
+        assert_eq!(parse_region_filter(Some(" All ")).unwrap(), None);
+        assert_eq!(
+            parse_region_filter(Some("CN")).unwrap(),
+            Some(ServerRegion::Cn)
+        );
+        assert_eq!(
+            parse_region_filter(Some("unspecified")).unwrap(),
+            Some(ServerRegion::Unspecified)
+        );
+        assert!(matches!(
+            parse_region_filter(Some("jp")),
+            Err(AppError::Validation(_))
+        ));
+    }
+
     #[test]
     fn test_mask_user_prefix() {
         assert_eq!(mask_user_prefix("abcd1234"), "abcd****");
@@ -9,6 +9,8 @@ use std::collections::BTreeMap;
 
 use crate::{error::AppError, state::AppState};
 
+use super::super::recompute::{self, RecomputeProgressResponse, RecomputeStartRequest};
+
 use super::{
     OkAliasResponse, OkResponse, apply_user_status, mask_user_prefix, normalize_moderation_status,
     validate_alias_format,
@@ -395,6 +397,94 @@ pub async fn post_admin_user_status(
     }))
 }
 
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUserFlagsQuery {
+    pub user_hash: String,
+    /// 返回条数（1-200，默认 50）
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUserFlagItem {
+    pub id: i64,
+    /// 人工审核为设置的状态；自动规则为 auto_flagged / auto_hidden
+    pub status: String,
+    /// 原因；自动规则形如 `[规则] 说明`
+    pub reason: Option<String>,
+    /// 严重度（自动规则为权重 ×100）
+    pub severity: i64,
+    /// 操作者：管理员令牌或 anti_cheat
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUserFlagsResponse {
+    pub user_hash: String,
+    pub items: Vec<AdminUserFlagItem>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/users/flags",
+    summary = "查询用户审核记录",
+    description = "需要在 Header 中提供 X-Admin-Token。按时间倒序返回人工状态变更与反作弊规则命中记录（moderation_flags）。",
+    params(
+        ("X-Admin-Token" = String, Header, description = "管理员令牌（config.leaderboard.admin_tokens）"),
+        ("userHash" = String, Query, description = "完整 user_hash"),
+        ("limit" = Option<i64>, Query, description = "返回条数（1-200，默认 50）")
+    ),
+    security(("AdminToken" = [])),
+    responses(
+        (status = 200, description = "查询成功", body = AdminUserFlagsResponse),
+        (
+            status = 401,
+            description = "管理员令牌缺失或无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_admin_user_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<AdminUserFlagsQuery>,
+) -> Result<Json<AdminUserFlagsResponse>, AppError> {
+    require_admin(&headers)?;
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let limit = q.limit.unwrap_or(50).clamp(1, 200);
+    let items = storage
+        .query_moderation_flags(&q.user_hash, limit)
+        .await?
+        .into_iter()
+        .map(|f| AdminUserFlagItem {
+            id: f.id,
+            status: f.status,
+            reason: f.reason,
+            severity: f.severity,
+            created_by: f.created_by,
+            created_at: f.created_at,
+        })
+        .collect();
+    Ok(Json(AdminUserFlagsResponse {
+        user_hash: q.user_hash,
+        items,
+    }))
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 #[schema(example = json!({"userHash":"abcde12345","status":"shadow","reason":"suspicious jump"}))]
 #[serde(rename_all = "camelCase")]
@@ -524,3 +614,115 @@ pub async fn post_alias_force(
         alias: alias.to_string(),
     }))
 }
+
+#[utoipa::path(
+    get,
+    path = "/admin/leaderboard/recompute",
+    summary = "排行榜重算任务进度",
+    description = "查询按当前定数重算排行榜的后台任务进度。需要在 Header 中提供 X-Admin-Token。",
+    params(("X-Admin-Token" = String, Header, description = "管理员令牌（config.leaderboard.admin_tokens）")),
+    security(("AdminToken" = [])),
+    responses(
+        (status = 200, description = "任务进度", body = RecomputeProgressResponse),
+        (
+            status = 401,
+            description = "管理员令牌缺失/无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_recompute_progress(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<RecomputeProgressResponse>, AppError> {
+    require_admin(&headers)?;
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    Ok(Json(recompute::progress(storage).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/leaderboard/recompute",
+    summary = "启动排行榜重算任务",
+    description = "按玩家最近一次提交的谱面快照，以当前定数分批重写 leaderboard_rks / leaderboard_details 及分规则榜。存在未完成（运行中断/已取消/失败）的任务时默认从上次进度续跑，restart=true 时从头开始。没有谱面快照的用户会被跳过，直到其下次提交存档。需要在 Header 中提供 X-Admin-Token。",
+    params(("X-Admin-Token" = String, Header, description = "管理员令牌（config.leaderboard.admin_tokens）")),
+    security(("AdminToken" = [])),
+    request_body(content = RecomputeStartRequest, description = "可省略"),
+    responses(
+        (status = 200, description = "任务已启动", body = RecomputeProgressResponse),
+        (
+            status = 401,
+            description = "管理员令牌缺失/无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 409,
+            description = "任务正在运行",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/写入失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_recompute_start(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    req: Option<Json<RecomputeStartRequest>>,
+) -> Result<Json<RecomputeProgressResponse>, AppError> {
+    let admin = require_admin(&headers)?;
+    let storage = state
+        .stats_storage
+        .clone()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let restart = req.is_some_and(|Json(r)| r.restart);
+    tracing::info!(
+        target: "phi_backend::leaderboard",
+        admin = %mask_user_prefix(&admin),
+        restart,
+        "排行榜重算任务由管理员触发"
+    );
+    Ok(Json(recompute::start(state, storage, restart).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/leaderboard/recompute/cancel",
+    summary = "停止排行榜重算任务",
+    description = "在当前批次结束后停止任务，进度保留，可再次启动续跑。需要在 Header 中提供 X-Admin-Token。",
+    params(("X-Admin-Token" = String, Header, description = "管理员令牌（config.leaderboard.admin_tokens）")),
+    security(("AdminToken" = [])),
+    responses(
+        (status = 200, description = "ok=false 表示当前没有运行中的任务", body = OkResponse),
+        (
+            status = 401,
+            description = "管理员令牌缺失/无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_recompute_cancel(headers: HeaderMap) -> Result<Json<OkResponse>, AppError> {
+    require_admin(&headers)?;
+    Ok(Json(OkResponse {
+        ok: recompute::request_cancel(),
+    }))
+}
@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+use std::time::Instant;
+
+use crate::{error::AppError, state::AppState};
+
+use super::super::models::{ChartLeaderboardItem, ChartLeaderboardResponse};
+use super::cursor::{ChartLeaderboardCursor, parse_chart_cursor, seal_chart_cursor};
+use super::mask_user_prefix;
+
+#[derive(Deserialize)]
+pub struct ChartTopQuery {
+    /// 歌曲 ID/名称/别名（需唯一匹配）
+    pub song: String,
+    /// 难度（EZ/HD/IN/AT，大小写不敏感）
+    pub difficulty: String,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// 加密游标。存在时优先使用 cursor，并忽略 offset。
+    pub cursor: Option<String>,
+}
+
+fn normalize_difficulty(raw: &str) -> Result<&'static str, AppError> {
+    match raw.trim().to_ascii_uppercase().as_str() {
+        "EZ" => Ok("EZ"),
+        "HD" => Ok("HD"),
+        "IN" => Ok("IN"),
+        "AT" => Ok("AT"),
+        _ => Err(AppError::Validation(format!(
+            "difficulty 无效：{raw}（可选 EZ/HD/IN/AT）"
+        ))),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/leaderboard/chart/top",
+    summary = "单谱面排行榜TOP",
+    description = "按 (歌曲, 难度) 返回公开玩家的成绩排行，依次按分数、ACC 降序与更新时间升序排序。公开/别名规则与 RKS 排行榜一致，并排除被隐藏或处于 shadow/banned/rejected 状态的用户。成绩来自玩家上传存档时写入的逐谱面成绩。",
+    params(
+        ("song" = String, Query, description = "歌曲 ID/名称/别名（需唯一匹配）"),
+        ("difficulty" = String, Query, description = "难度：EZ/HD/IN/AT"),
+        ("limit" = Option<i64>, Query, description = "每页数量，默认50，最大200"),
+        ("offset" = Option<i64>, Query, description = "偏移量"),
+        ("cursor" = Option<String>, Query, description = "加密游标；存在时优先使用 cursor 并忽略 offset；只能在同一谱面下续用")
+    ),
+    responses(
+        (status = 200, description = "单谱面排行榜", body = ChartLeaderboardResponse),
+        (
+            status = 404,
+            description = "歌曲未找到",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 409,
+            description = "歌曲匹配到多个结果",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "参数校验失败（difficulty/cursor 无效）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_chart_top(
+    State(state): State<AppState>,
+    Query(q): Query<ChartTopQuery>,
+) -> Result<Json<ChartLeaderboardResponse>, AppError> {
+    let t_total = Instant::now();
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let difficulty = normalize_difficulty(&q.difficulty)?;
+    let song = state
+        .game_data()
+        .song_catalog
+        .search_unique(q.song.trim())
+        .map_err(AppError::Search)?;
+    let limit = q.limit.unwrap_or(50).clamp(1, 200);
+    let offset = q.offset.unwrap_or(0).max(0);
+    let cursor = parse_chart_cursor(q.cursor.as_deref(), &song.id, difficulty)?;
+    let rank_base = cursor.as_ref().map_or(offset + 1, |c| c.rank_base);
+    let fetch_limit = limit.saturating_add(1);
+
+    let total_fut = storage.count_public_chart_leaderboard_total(&song.id, difficulty);
+    let rows_fut = async {
+        match cursor.as_ref() {
+            Some(c) => {
+                storage
+                    .query_chart_leaderboard_seek(
+                        &song.id,
+                        difficulty,
+                        (c.score, c.acc, &c.updated_at, &c.user_hash),
+                        fetch_limit,
+                    )
+                    .await
+            }
+            None => {
+                storage
+                    .query_chart_leaderboard_offset(&song.id, difficulty, fetch_limit, offset)
+                    .await
+            }
+        }
+    };
+    let (total, mut rows) = tokio::try_join!(total_fut, rows_fut)?;
+
+    let limit_len = usize::try_from(limit).unwrap_or(usize::MAX);
+    let has_more = rows.len() > limit_len;
+    rows.truncate(limit_len);
+
+    let next_cursor = if has_more {
+        rows.last().and_then(|last| {
+            seal_chart_cursor(&ChartLeaderboardCursor {
+                song_id: song.id.clone(),
+                difficulty: difficulty.to_string(),
+                score: last.score,
+                acc: last.acc,
+                updated_at: last.updated_at.clone(),
+                user_hash: last.user_hash.clone(),
+                rank_base: rank_base + i64::try_from(rows.len()).unwrap_or(i64::MAX),
+            })
+        })
+    } else {
+        None
+    };
+    let items: Vec<ChartLeaderboardItem> = rows
+        .into_iter()
+        .zip(rank_base..)
+        .map(|(r, rank)| ChartLeaderboardItem {
+            rank,
+            alias: r.alias,
+            user: mask_user_prefix(&r.user_hash),
+            score: r.score,
+            acc: r.acc,
+            fc: r.is_fc,
+            updated_at: r.updated_at,
+        })
+        .collect();
+
+    tracing::info!(
+        target: "phi_backend::leaderboard::performance",
+        route = "/leaderboard/chart/top",
+        phase = "total",
+        status = "ok",
+        items = items.len(),
+        total,
+        total_dur_ms = t_total.elapsed().as_millis(),
+        "leaderboard performance"
+    );
+    Ok(Json(ChartLeaderboardResponse {
+        song_id: song.id.clone(),
+        song_name: song.name.clone(),
+        difficulty: difficulty.to_string(),
+        items,
+        total,
+        next_cursor,
+    }))
+}
@@ -1,8 +1,11 @@
 use base64::Engine;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::error::AppError;
 
+const LEADERBOARD_CURSOR_AAD: &[u8] = b"leaderboard-rks-top";
+const CHART_LEADERBOARD_CURSOR_AAD: &[u8] = b"leaderboard-chart-top";
+
 #[derive(Debug, Clone)]
 pub(super) struct LeaderboardCursor {
     pub(super) score: f64,
@@ -22,6 +25,31 @@ struct LeaderboardCursorEnvelope {
     rank_base: Option<i64>,
 }
 
+/// 单谱面榜游标：排序键为 (score, acc, updated_at, user_hash)，并绑定谱面，不能跨谱面续用。
+#[derive(Debug, Clone)]
+pub(super) struct ChartLeaderboardCursor {
+    pub(super) song_id: String,
+    pub(super) difficulty: String,
+    pub(super) score: i64,
+    pub(super) acc: f64,
+    pub(super) updated_at: String,
+    pub(super) user_hash: String,
+    pub(super) rank_base: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartLeaderboardCursorEnvelope {
+    version: u8,
+    song_id: String,
+    difficulty: String,
+    score: i64,
+    acc: f64,
+    updated_at: String,
+    user_hash: String,
+    rank_base: i64,
+}
+
 fn leaderboard_cursor_secret() -> Option<String> {
     let cfg = crate::config::AppConfig::global();
     let session_secret = cfg.session.jwt_secret.trim();
@@ -61,34 +89,18 @@ fn validate_leaderboard_cursor(cursor: LeaderboardCursor) -> Result<LeaderboardC
     Ok(cursor)
 }
 
-fn seal_leaderboard_cursor_with_secret(
-    cursor: &LeaderboardCursor,
-    secret: &str,
-) -> Result<String, AppError> {
+/// 以 AES-256-GCM 加密游标信封（nonce 前置，URL-safe base64）；`aad` 区分不同榜单的游标。
+fn seal_envelope<T: Serialize>(envelope: &T, secret: &str, aad: &[u8]) -> Result<String, AppError> {
     use aes_gcm::aead::{Aead, KeyInit};
 
-    let cursor = validate_leaderboard_cursor(cursor.clone())?;
-    let envelope = LeaderboardCursorEnvelope {
-        version: 1,
-        score: cursor.score,
-        updated_at: cursor.updated_at,
-        user_hash: cursor.user_hash,
-        rank_base: cursor.rank_base,
-    };
-    let payload = serde_json::to_vec(&envelope)
+    let payload = serde_json::to_vec(envelope)
         .map_err(|e| AppError::Internal(format!("序列化排行榜游标失败: {e}")))?;
     let key = derive_leaderboard_cursor_key(secret);
     let cipher = aes_gcm::Aes256Gcm::new(aes_gcm::Key::<aes_gcm::Aes256Gcm>::from_slice(&key));
     let nonce_bytes = uuid::Uuid::new_v4().as_bytes().to_owned();
     let nonce = aes_gcm::Nonce::from_slice(&nonce_bytes[..12]);
     let encrypted = cipher
-        .encrypt(
-            nonce,
-            aes_gcm::aead::Payload {
-                msg: &payload,
-                aad: b"leaderboard-rks-top",
-            },
-        )
+        .encrypt(nonce, aes_gcm::aead::Payload { msg: &payload, aad })
         .map_err(|e| AppError::Internal(format!("加密排行榜游标失败: {e}")))?;
 
     let mut sealed = Vec::with_capacity(12 + encrypted.len());
@@ -97,10 +109,7 @@ fn seal_leaderboard_cursor_with_secret(
     Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(sealed))
 }
 
-fn open_leaderboard_cursor_with_secret(
-    raw: &str,
-    secret: &str,
-) -> Result<LeaderboardCursor, AppError> {
+fn open_envelope<T: DeserializeOwned>(raw: &str, secret: &str, aad: &[u8]) -> Result<T, AppError> {
     use aes_gcm::aead::{Aead, KeyInit};
 
     let sealed = base64::engine::general_purpose::URL_SAFE_NO_PAD
@@ -117,12 +126,33 @@ fn open_leaderboard_cursor_with_secret(
             nonce,
             aes_gcm::aead::Payload {
                 msg: &sealed[12..],
-                aad: b"leaderboard-rks-top",
+                aad,
             },
         )
         .map_err(|_| AppError::Validation("cursor 无效或不属于当前服务实例".into()))?;
-    let envelope: LeaderboardCursorEnvelope = serde_json::from_slice(&payload)
-        .map_err(|_| AppError::Validation("cursor 内容无效".into()))?;
+    serde_json::from_slice(&payload).map_err(|_| AppError::Validation("cursor 内容无效".into()))
+}
+
+fn seal_leaderboard_cursor_with_secret(
+    cursor: &LeaderboardCursor,
+    secret: &str,
+) -> Result<String, AppError> {
+    let cursor = validate_leaderboard_cursor(cursor.clone())?;
+    let envelope = LeaderboardCursorEnvelope {
+        version: 1,
+        score: cursor.score,
+        updated_at: cursor.updated_at,
+        user_hash: cursor.user_hash,
+        rank_base: cursor.rank_base,
+    };
+    seal_envelope(&envelope, secret, LEADERBOARD_CURSOR_AAD)
+}
+
+fn open_leaderboard_cursor_with_secret(
+    raw: &str,
+    secret: &str,
+) -> Result<LeaderboardCursor, AppError> {
+    let envelope: LeaderboardCursorEnvelope = open_envelope(raw, secret, LEADERBOARD_CURSOR_AAD)?;
     if envelope.version != 1 {
         return Err(AppError::Validation("cursor 版本无效".into()));
     }
@@ -184,6 +214,79 @@ pub(super) fn seal_leaderboard_cursor(cursor: &LeaderboardCursor) -> Option<Stri
     }
 }
 
+fn seal_chart_cursor_with_secret(
+    cursor: &ChartLeaderboardCursor,
+    secret: &str,
+) -> Result<String, AppError> {
+    let envelope = ChartLeaderboardCursorEnvelope {
+        version: 1,
+        song_id: cursor.song_id.clone(),
+        difficulty: cursor.difficulty.clone(),
+        score: cursor.score,
+        acc: cursor.acc,
+        updated_at: cursor.updated_at.clone(),
+        user_hash: cursor.user_hash.clone(),
+        rank_base: cursor.rank_base,
+    };
+    seal_envelope(&envelope, secret, CHART_LEADERBOARD_CURSOR_AAD)
+}
+
+fn open_chart_cursor_with_secret(
+    raw: &str,
+    secret: &str,
+    song_id: &str,
+    difficulty: &str,
+) -> Result<ChartLeaderboardCursor, AppError> {
+    let envelope: ChartLeaderboardCursorEnvelope =
+        open_envelope(raw, secret, CHART_LEADERBOARD_CURSOR_AAD)?;
+    if envelope.version != 1 {
+        return Err(AppError::Validation("cursor 版本无效".into()));
+    }
+    if envelope.song_id != song_id || envelope.difficulty != difficulty {
+        return Err(AppError::Validation("cursor 不属于当前谱面".into()));
+    }
+    if !envelope.acc.is_finite()
+        || envelope.updated_at.trim().is_empty()
+        || envelope.user_hash.trim().is_empty()
+        || envelope.rank_base <= 0
+    {
+        return Err(AppError::Validation("cursor 内容无效".into()));
+    }
+    Ok(ChartLeaderboardCursor {
+        song_id: envelope.song_id,
+        difficulty: envelope.difficulty,
+        score: envelope.score,
+        acc: envelope.acc,
+        updated_at: envelope.updated_at,
+        user_hash: envelope.user_hash,
+        rank_base: envelope.rank_base,
+    })
+}
+
+pub(super) fn parse_chart_cursor(
+    raw: Option<&str>,
+    song_id: &str,
+    difficulty: &str,
+) -> Result<Option<ChartLeaderboardCursor>, AppError> {
+    let Some(raw) = raw.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let secret = leaderboard_cursor_secret()
+        .ok_or_else(|| AppError::Internal("排行榜 cursor 密钥未配置".into()))?;
+    open_chart_cursor_with_secret(raw, &secret, song_id, difficulty).map(Some)
+}
+
+pub(super) fn seal_chart_cursor(cursor: &ChartLeaderboardCursor) -> Option<String> {
+    let secret = leaderboard_cursor_secret()?;
+    match seal_chart_cursor_with_secret(cursor, &secret) {
+        Ok(cursor) => Some(cursor),
+        Err(e) => {
+            tracing::warn!(target: "phi_backend::leaderboard", "seal chart leaderboard cursor failed: {e}");
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +329,28 @@ mod tests {
         };
         assert!(seal_leaderboard_cursor_with_secret(&invalid, "test-secret").is_err());
     }
+
+    #[test]
+    fn chart_cursor_is_bound_to_chart_and_board() {
+        let cursor = ChartLeaderboardCursor {
+            song_id: "Glaciaxion.SunsetRay.0".to_string(),
+            difficulty: "IN".to_string(),
+            score: 995_000,
+            acc: 99.5,
+            updated_at: "2025-09-20T04:10:44Z".to_string(),
+            user_hash: "0123456789abcdef".to_string(),
+            rank_base: 21,
+        };
+        let sealed = seal_chart_cursor_with_secret(&cursor, "test-secret").unwrap();
+        let opened =
+            open_chart_cursor_with_secret(&sealed, "test-secret", &cursor.song_id, "IN").unwrap();
+        assert_eq!(opened.score, cursor.score);
+        assert_eq!(opened.rank_base, 21);
+
+        assert!(
+            open_chart_cursor_with_secret(&sealed, "test-secret", &cursor.song_id, "AT").is_err()
+        );
+        // 总榜游标与谱面榜游标的 AAD 不同，不能混用
+        assert!(open_leaderboard_cursor_with_secret(&sealed, "test-secret").is_err());
+    }
 }
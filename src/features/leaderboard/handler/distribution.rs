@@ -0,0 +1,132 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::{error::AppError, session_auth::BearerAuthState, state::AppState};
+
+use super::super::distribution::{
+    MAX_BUCKET_WIDTH, MIN_BUCKET_WIDTH, count_higher, histogram, load_score_snapshot,
+};
+use super::super::models::{RksBucket, RksDistributionResponse, RksStanding};
+use super::ranking::{i64_to_f64_lossy, usize_to_i64_saturating};
+
+#[derive(Deserialize)]
+pub struct DistributionQuery {
+    /// 桶宽（0.01~5.0），默认取 leaderboard.distribution.default_bucket_width
+    pub bucket_width: Option<f64>,
+    /// 指定分数计算位置；缺省时使用 Bearer 会话用户的排行榜分数
+    pub score: Option<f64>,
+    /// 服务器区域：all（默认，合并榜）/ cn / global / unspecified
+    pub region: Option<String>,
+}
+
+fn standing(sorted_scores: &[f64], score: f64) -> Option<RksStanding> {
+    let total = usize_to_i64_saturating(sorted_scores.len());
+    if total == 0 {
+        return None;
+    }
+    let rank = usize_to_i64_saturating(count_higher(sorted_scores, score)) + 1;
+    Some(RksStanding {
+        score,
+        rank,
+        percentile: 100.0 * (1.0 - i64_to_f64_lossy(rank - 1) / i64_to_f64_lossy(total)),
+        top_percent: 100.0 * i64_to_f64_lossy(rank.min(total)) / i64_to_f64_lossy(total),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/leaderboard/rks/distribution",
+    summary = "RKS 分布与百分位",
+    description = "返回公开玩家 RKS 的直方图，以及调用者所处的名次/百分位；可按服务器区域拆分。分数快照按 leaderboard.distribution.refresh_secs 周期重算并缓存，结果可能滞后于实时排行榜。调用者分数优先取 score 参数，否则取 Bearer 会话用户在排行榜中的分数。",
+    params(
+        ("bucket_width" = Option<f64>, Query, description = "桶宽（0.01~5.0），默认 0.5（可配置）"),
+        ("score" = Option<f64>, Query, description = "指定分数计算位置；缺省时使用 Bearer 会话用户的分数"),
+        ("region" = Option<String>, Query, description = "服务器区域：all（默认，合并榜）/ cn / global / unspecified")
+    ),
+    responses(
+        (status = 200, description = "RKS 分布", body = RksDistributionResponse),
+        (
+            status = 422,
+            description = "参数校验失败（bucket_width/score/region 无效）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_rks_distribution(
+    State(state): State<AppState>,
+    Query(q): Query<DistributionQuery>,
+    bearer: Option<Extension<BearerAuthState>>,
+) -> Result<Json<RksDistributionResponse>, AppError> {
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let width = q.bucket_width.unwrap_or(
+        crate::config::AppConfig::global()
+            .leaderboard
+            .distribution
+            .default_bucket_width,
+    );
+    if !(MIN_BUCKET_WIDTH..=MAX_BUCKET_WIDTH).contains(&width) {
+        return Err(AppError::Validation(format!(
+            "bucket_width 需在 {MIN_BUCKET_WIDTH}~{MAX_BUCKET_WIDTH} 之间"
+        )));
+    }
+    if q.score.is_some_and(|s| !s.is_finite() || s < 0.0) {
+        return Err(AppError::Validation("score 无效".into()));
+    }
+    let region = super::parse_region_filter(q.region.as_deref())?;
+
+    let my_score = match (q.score, bearer) {
+        (Some(score), _) => Some(score),
+        (None, Some(Extension(BearerAuthState::Valid(ctx)))) => storage
+            .get_prev_rks(&ctx.claims.sub)
+            .await?
+            .map(|(score, _)| score),
+        _ => None,
+    };
+
+    let snapshot = load_score_snapshot(storage, region).await?;
+    let buckets = histogram(&snapshot.scores, width)
+        .into_iter()
+        .map(|b| RksBucket {
+            from: b.from,
+            to: b.to,
+            count: b.count,
+        })
+        .collect();
+    Ok(Json(RksDistributionResponse {
+        bucket_width: width,
+        total: usize_to_i64_saturating(snapshot.scores.len()),
+        buckets,
+        me: my_score.and_then(|s| standing(&snapshot.scores, s)),
+        computed_at: snapshot.computed_at.clone(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standing_reports_rank_and_top_percent() {
+        let scores: Vec<f64> = (1..=100).map(f64::from).collect();
+        let s = standing(&scores, 98.0).unwrap();
+        assert_eq!(s.rank, 3);
+        assert!((s.top_percent - 3.0).abs() < 1e-9);
+        assert!((s.percentile - 98.0).abs() < 1e-9);
+        assert!(standing(&[], 10.0).is_none());
+    }
+}
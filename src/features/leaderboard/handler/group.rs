@@ -0,0 +1,709 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    auth_contract::UnifiedSaveRequest, error::AppError, session_auth::BearerAuthState,
+    state::AppState, stats_contract::LeaderboardGroup,
+};
+
+use super::super::models::{
+    GroupCreateRequest, GroupInfo, GroupJoinRequest, GroupKickRequest, GroupListResponse,
+    GroupMemberItem, GroupMembersResponse, LeaderboardTopResponse,
+};
+use super::ranking::{
+    RankQuery, build_leaderboard_items, parse_rank_range, truncate_overfetched_rows,
+};
+use super::{OkResponse, ensure_not_banned, mask_user_prefix};
+
+/// 邀请码字符集：去掉易混淆的 I/O/0/1
+const INVITE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const INVITE_CODE_LEN: usize = 8;
+/// 邀请码冲突时的重试次数
+const INVITE_CODE_ATTEMPTS: usize = 3;
+const MAX_GROUP_NAME_CHARS: usize = 32;
+
+#[derive(Deserialize)]
+pub struct GroupTopQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// 精简模式：不返回 BestTop3/APTop3（默认 false）
+    pub lite: Option<bool>,
+}
+
+/// 每位独立、均匀地从字符集中抽取（`thread_rng` 为 CSPRNG），共 40 bit 熵
+fn generate_invite_code() -> String {
+    use rand::Rng as _;
+
+    let alphabet =
+        rand::distributions::Slice::new(INVITE_ALPHABET).expect("invite alphabet is non-empty");
+    rand::thread_rng()
+        .sample_iter(alphabet)
+        .take(INVITE_CODE_LEN)
+        .map(|b| char::from(*b))
+        .collect()
+}
+
+fn validate_group_name(raw: &str) -> Result<String, AppError> {
+    let name = raw.trim();
+    let chars = name.chars().count();
+    if chars == 0 || chars > MAX_GROUP_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "群组名称长度需在 1~{MAX_GROUP_NAME_CHARS} 字符之间"
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(AppError::Validation("群组名称不能包含控制字符".into()));
+    }
+    Ok(name.to_string())
+}
+
+fn group_info(group: LeaderboardGroup, user_hash: &str) -> GroupInfo {
+    GroupInfo {
+        is_owner: group.owner_hash == user_hash,
+        id: group.id,
+        name: group.name,
+        invite_code: group.invite_code,
+        member_count: group.member_count,
+        created_at: group.created_at,
+    }
+}
+
+/// 与 /leaderboard/rks/me 相同的认证流程：合并 Bearer 凭证后解析用户身份并检查封禁状态。
+async fn resolve_group_user<'a>(
+    state: &'a AppState,
+    auth: &mut UnifiedSaveRequest,
+    bearer_state: &BearerAuthState,
+) -> Result<(&'a crate::stats_contract::StatsStorage, String), AppError> {
+    crate::session_auth::merge_auth_from_bearer_if_missing(
+        state.stats_storage.as_ref(),
+        bearer_state,
+        auth,
+    )
+    .await?;
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let salt = crate::config::AppConfig::global()
+        .stats
+        .user_hash_salt
+        .as_deref();
+    let (user_hash_opt, _kind) =
+        crate::session_auth::derive_user_identity_with_bearer(salt, auth, bearer_state)?;
+    let user_hash =
+        user_hash_opt.ok_or_else(|| AppError::Internal("无法识别用户（缺少可用凭证）".into()))?;
+    ensure_not_banned(storage, &user_hash).await?;
+    Ok((storage, user_hash))
+}
+
+/// 读取群组并校验当前用户是成员
+async fn load_member_group(
+    storage: &crate::stats_contract::StatsStorage,
+    group_id: &str,
+    user_hash: &str,
+) -> Result<LeaderboardGroup, AppError> {
+    let group = storage
+        .get_group(group_id)
+        .await?
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))?;
+    if !storage.is_group_member(group_id, user_hash).await? {
+        return Err(AppError::Forbidden("不是该群组成员".into()));
+    }
+    Ok(group)
+}
+
+fn ensure_owner(group: &LeaderboardGroup, user_hash: &str) -> Result<(), AppError> {
+    if group.owner_hash == user_hash {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("仅群组创建者可执行该操作".into()))
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups",
+    summary = "创建好友群组",
+    description = "创建一个私有排行榜群组，创建者自动成为成员。返回的邀请码可分享给他人加入。",
+    request_body = GroupCreateRequest,
+    responses(
+        (status = 200, description = "创建成功", body = GroupInfo),
+        (
+            status = 409,
+            description = "已达到可创建/可加入的群组上限",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "群组名称非法",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/写入失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_create_group(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<GroupInfo>, AppError> {
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<GroupCreateRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut req.auth, &bearer_state).await?;
+    let name = validate_group_name(&req.name)?;
+
+    let cfg = &crate::config::AppConfig::global().leaderboard.groups;
+    let (owned, joined) = tokio::try_join!(
+        storage.count_owned_groups(&user_hash),
+        storage.count_joined_groups(&user_hash)
+    )?;
+    if owned >= cfg.max_owned_per_user {
+        return Err(AppError::Conflict(format!(
+            "最多只能创建 {} 个群组",
+            cfg.max_owned_per_user
+        )));
+    }
+    if joined >= cfg.max_joined_per_user {
+        return Err(AppError::Conflict(format!(
+            "最多只能加入 {} 个群组",
+            cfg.max_joined_per_user
+        )));
+    }
+
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match storage
+            .create_group(&id, &name, &user_hash, &generate_invite_code(), &now)
+            .await
+        {
+            Ok(()) => break,
+            Err(AppError::Conflict(_)) if attempt < INVITE_CODE_ATTEMPTS => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let group = storage
+        .get_group(&id)
+        .await?
+        .ok_or_else(|| AppError::Internal("群组创建后读取失败".into()))?;
+    Ok(Json(group_info(group, &user_hash)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/mine",
+    summary = "我加入的群组",
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "群组列表（按加入时间升序）", body = GroupListResponse),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_my_groups(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<GroupListResponse>, AppError> {
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    let items = storage
+        .list_user_groups(&user_hash)
+        .await?
+        .into_iter()
+        .map(|g| group_info(g, &user_hash))
+        .collect();
+    Ok(Json(GroupListResponse { items }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/join",
+    summary = "通过邀请码加入群组",
+    description = "已是成员时直接返回群组信息（幂等）。",
+    request_body = GroupJoinRequest,
+    responses(
+        (status = 200, description = "加入成功", body = GroupInfo),
+        (
+            status = 404,
+            description = "邀请码无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 409,
+            description = "群组已满或已达到可加入的群组上限",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/写入失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_join_group(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<GroupInfo>, AppError> {
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<GroupJoinRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut req.auth, &bearer_state).await?;
+    let group = storage
+        .get_group_by_invite_code(req.invite_code.trim())
+        .await?
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))?;
+
+    if !storage.is_group_member(&group.id, &user_hash).await? {
+        let cfg = &crate::config::AppConfig::global().leaderboard.groups;
+        if storage.count_joined_groups(&user_hash).await? >= cfg.max_joined_per_user {
+            return Err(AppError::Conflict(format!(
+                "最多只能加入 {} 个群组",
+                cfg.max_joined_per_user
+            )));
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        if !storage
+            .add_group_member(&group.id, &user_hash, cfg.max_members, &now)
+            .await?
+        {
+            return Err(AppError::Conflict("群组成员已满".into()));
+        }
+    }
+    let group = storage
+        .get_group(&group.id)
+        .await?
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))?;
+    Ok(Json(group_info(group, &user_hash)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/members",
+    summary = "群组成员列表",
+    description = "仅成员可查看。成员别名不要求公开。",
+    params(("id" = String, Path, description = "群组 ID")),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "成员列表（按加入时间升序）", body = GroupMembersResponse),
+        (
+            status = 403,
+            description = "不是该群组成员",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_group_members(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    request: axum::extract::Request,
+) -> Result<Json<GroupMembersResponse>, AppError> {
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    let group = load_member_group(storage, &group_id, &user_hash).await?;
+    let items = storage
+        .query_group_members(&group_id)
+        .await?
+        .into_iter()
+        .map(|m| GroupMemberItem {
+            member_id: m.id,
+            alias: m.alias,
+            user: mask_user_prefix(&m.user_hash),
+            is_owner: m.user_hash == group.owner_hash,
+            is_self: m.user_hash == user_hash,
+            joined_at: m.joined_at,
+        })
+        .collect();
+    Ok(Json(GroupMembersResponse {
+        group: group_info(group, &user_hash),
+        items,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/leave",
+    summary = "退出群组",
+    description = "创建者不能退出，只能解散群组。",
+    params(("id" = String, Path, description = "群组 ID")),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "已退出", body = OkResponse),
+        (
+            status = 403,
+            description = "不是该群组成员",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "创建者不能退出群组",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_leave_group(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    request: axum::extract::Request,
+) -> Result<Json<OkResponse>, AppError> {
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    let group = load_member_group(storage, &group_id, &user_hash).await?;
+    if group.owner_hash == user_hash {
+        return Err(AppError::Validation(
+            "创建者不能退出群组，请改为解散".into(),
+        ));
+    }
+    storage.remove_group_member(&group_id, &user_hash).await?;
+    Ok(Json(OkResponse { ok: true }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/kick",
+    summary = "移除群组成员",
+    description = "仅创建者可操作；memberId 取自成员列表。",
+    params(("id" = String, Path, description = "群组 ID")),
+    request_body = GroupKickRequest,
+    responses(
+        (status = 200, description = "已移除", body = OkResponse),
+        (
+            status = 403,
+            description = "不是群组创建者",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组或成员不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "不能移除创建者",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_kick_group_member(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    request: axum::extract::Request,
+) -> Result<Json<OkResponse>, AppError> {
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<GroupKickRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut req.auth, &bearer_state).await?;
+    let group = load_member_group(storage, &group_id, &user_hash).await?;
+    ensure_owner(&group, &user_hash)?;
+    let member = storage
+        .query_group_members(&group_id)
+        .await?
+        .into_iter()
+        .find(|m| m.id == req.member_id)
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))?;
+    if member.user_hash == group.owner_hash {
+        return Err(AppError::Validation("不能移除群组创建者".into()));
+    }
+    storage
+        .remove_group_member(&group_id, &member.user_hash)
+        .await?;
+    Ok(Json(OkResponse { ok: true }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/invite/reset",
+    summary = "重置群组邀请码",
+    description = "仅创建者可操作；旧邀请码立即失效，已加入的成员不受影响。",
+    params(("id" = String, Path, description = "群组 ID")),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "新的群组信息", body = GroupInfo),
+        (
+            status = 403,
+            description = "不是群组创建者",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_reset_group_invite(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    request: axum::extract::Request,
+) -> Result<Json<GroupInfo>, AppError> {
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    let group = load_member_group(storage, &group_id, &user_hash).await?;
+    ensure_owner(&group, &user_hash)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match storage
+            .set_group_invite_code(&group_id, &generate_invite_code(), &now)
+            .await
+        {
+            Ok(()) => break,
+            Err(AppError::Conflict(_)) if attempt < INVITE_CODE_ATTEMPTS => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let group = storage
+        .get_group(&group_id)
+        .await?
+        .ok_or(AppError::Search(crate::error::SearchError::NotFound))?;
+    Ok(Json(group_info(group, &user_hash)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/disband",
+    summary = "解散群组",
+    description = "仅创建者可操作；删除群组与全部成员关系。",
+    params(("id" = String, Path, description = "群组 ID")),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "已解散", body = OkResponse),
+        (
+            status = 403,
+            description = "不是群组创建者",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_disband_group(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    request: axum::extract::Request,
+) -> Result<Json<OkResponse>, AppError> {
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    let group = load_member_group(storage, &group_id, &user_hash).await?;
+    ensure_owner(&group, &user_hash)?;
+    storage.delete_group(&group_id).await?;
+    Ok(Json(OkResponse { ok: true }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/rks/top",
+    summary = "群组排行榜TOP（按RKS）",
+    description = "仅成员可查看。排序与公开榜一致，但不要求成员公开资料；被隐藏的用户不上榜。BestTop3/APTop3 按每位成员自己的展示开关返回。",
+    params(
+        ("id" = String, Path, description = "群组 ID"),
+        ("limit" = Option<i64>, Query, description = "每页数量，默认50，最大200"),
+        ("offset" = Option<i64>, Query, description = "偏移量"),
+        ("lite" = Option<bool>, Query, description = "精简模式：不返回 bestTop3/apTop3（默认 false）")
+    ),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "群组排行榜", body = LeaderboardTopResponse),
+        (
+            status = 403,
+            description = "不是该群组成员",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_group_top(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    Query(q): Query<GroupTopQuery>,
+    request: axum::extract::Request,
+) -> Result<Json<LeaderboardTopResponse>, AppError> {
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    load_member_group(storage, &group_id, &user_hash).await?;
+
+    let lite = q.lite.unwrap_or(false);
+    let limit = q.limit.unwrap_or(50).clamp(1, 200);
+    let offset = q.offset.unwrap_or(0).max(0);
+    let (total, mut rows) = tokio::try_join!(
+        storage.count_group_leaderboard_total(&group_id),
+        storage.query_group_leaderboard_offset(&group_id, limit.saturating_add(1), offset)
+    )?;
+    truncate_overfetched_rows(&mut rows, limit);
+    let items = build_leaderboard_items(storage, rows, offset + 1, lite).await;
+    Ok(Json(LeaderboardTopResponse {
+        items,
+        total,
+        next_after_score: None,
+        next_after_updated: None,
+        next_after_user: None,
+        next_cursor: None,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/groups/{id}/rks/by-rank",
+    summary = "群组排行榜按排名区间（按RKS）",
+    description = "仅成员可查看。参数与 /leaderboard/rks/by-rank 相同，名次为群组内名次。",
+    params(
+        ("id" = String, Path, description = "群组 ID"),
+        ("rank" = Option<i64>, Query, description = "单个排名（1-based）"),
+        ("start" = Option<i64>, Query, description = "起始排名（1-based）"),
+        ("end" = Option<i64>, Query, description = "结束排名（包含）"),
+        ("count" = Option<i64>, Query, description = "返回数量（与 start 组合使用）"),
+        ("lite" = Option<bool>, Query, description = "精简模式：不返回 bestTop3/apTop3（默认 false）")
+    ),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "区间结果", body = LeaderboardTopResponse),
+        (
+            status = 403,
+            description = "不是该群组成员",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "群组不存在",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "参数校验失败（缺少 rank/start 等）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_group_by_rank(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    Query(q): Query<RankQuery>,
+    request: axum::extract::Request,
+) -> Result<Json<LeaderboardTopResponse>, AppError> {
+    let (start_rank, count) = parse_rank_range(&q)?;
+    let (mut auth, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<UnifiedSaveRequest>(request).await?;
+    let (storage, user_hash) = resolve_group_user(&state, &mut auth, &bearer_state).await?;
+    load_member_group(storage, &group_id, &user_hash).await?;
+
+    let (total, rows) = tokio::try_join!(
+        storage.count_group_leaderboard_total(&group_id),
+        storage.query_group_leaderboard_offset(&group_id, count, start_rank - 1)
+    )?;
+    let items = build_leaderboard_items(storage, rows, start_rank, q.lite.unwrap_or(false)).await;
+    Ok(Json(LeaderboardTopResponse {
+        items,
+        total,
+        next_after_score: None,
+        next_after_updated: None,
+        next_after_user: None,
+        next_cursor: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invite_codes_use_unambiguous_alphabet() {
+        let code = generate_invite_code();
+        assert_eq!(code.len(), INVITE_CODE_LEN);
+        assert!(code.bytes().all(|b| INVITE_ALPHABET.contains(&b)));
+        assert!(validate_group_name("  周末音游部 ").is_ok());
+        assert!(validate_group_name("   ").is_err());
+        assert!(validate_group_name(&"a".repeat(33)).is_err());
+        assert!(validate_group_name("a\nb").is_err());
+    }
+
+    #[test]
+    fn invite_codes_cover_every_position_and_symbol() {
+        // 200 个码共 1600 个字符，任一字符从未出现的概率约为 32·(31/32)^1600 ≈ 1e-20
+        let codes: Vec<String> = (0..200).map(|_| generate_invite_code()).collect();
+        for &symbol in INVITE_ALPHABET {
+            assert!(codes.iter().any(|c| c.as_bytes().contains(&symbol)));
+        }
+        // 与 UUID 不同，各位上不存在固定的版本/变体位
+        for pos in 0..INVITE_CODE_LEN {
+            let first = codes[0].as_bytes()[pos];
+            assert!(codes.iter().any(|c| c.as_bytes()[pos] != first));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::Row;
+
+use crate::{error::AppError, state::AppState};
+
+use super::super::models::{
+    RankHistoryPoint, RankHistoryResponse, RksGainerItem, RksGainersResponse,
+};
+use super::{ensure_not_banned, mask_user_prefix};
+
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+const MAX_HISTORY_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+pub struct GainersQuery {
+    /// 统计窗口：7d（默认）或 30d
+    pub window: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct RankHistoryQuery {
+    /// 回溯天数，默认30，最大366
+    pub days: Option<i64>,
+}
+
+fn parse_window(raw: Option<&str>) -> Result<(&'static str, i64), AppError> {
+    match raw.map(str::trim).filter(|v| !v.is_empty()) {
+        None | Some("7d") => Ok(("7d", 7)),
+        Some("30d") => Ok(("30d", 30)),
+        Some(v) => Err(AppError::Validation(format!(
+            "window 无效：{v}（可选 7d/30d）"
+        ))),
+    }
+}
+
+fn history_days(q: &RankHistoryQuery) -> i64 {
+    q.days
+        .unwrap_or(DEFAULT_HISTORY_DAYS)
+        .clamp(1, MAX_HISTORY_DAYS)
+}
+
+async fn load_rank_history(
+    storage: &crate::stats_contract::StatsStorage,
+    user_hash: &str,
+    alias: Option<String>,
+    days: i64,
+) -> Result<RankHistoryResponse, AppError> {
+    // 含今天在内共 days 天
+    let since = (Utc::now().date_naive() - chrono::Duration::days(days - 1))
+        .format("%Y-%m-%d")
+        .to_string();
+    let points = storage
+        .query_rank_history(user_hash, &since)
+        .await?
+        .into_iter()
+        .map(|p| RankHistoryPoint {
+            date: p.date,
+            rank: p.rank,
+            score: p.total_rks,
+        })
+        .collect();
+    Ok(RankHistoryResponse {
+        alias,
+        days,
+        points,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/leaderboard/rks/gainers",
+    summary = "RKS 涨幅榜",
+    description = "按最近 7/30 天内的 RKS 涨幅降序排列公开玩家。涨幅 = 窗口内提交的最高 RKS − 窗口开始前提交的最高 RKS；窗口开始前没有提交记录的新玩家不参与排名。公开/隐藏规则与 RKS 排行榜一致。",
+    params(
+        ("window" = Option<String>, Query, description = "统计窗口：7d（默认）/30d"),
+        ("limit" = Option<i64>, Query, description = "每页数量，默认50，最大200"),
+        ("offset" = Option<i64>, Query, description = "偏移量")
+    ),
+    responses(
+        (status = 200, description = "RKS 涨幅榜", body = RksGainersResponse),
+        (
+            status = 422,
+            description = "参数校验失败（window 无效）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_rks_gainers(
+    State(state): State<AppState>,
+    Query(q): Query<GainersQuery>,
+) -> Result<Json<RksGainersResponse>, AppError> {
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let (window, days) = parse_window(q.window.as_deref())?;
+    let limit = q.limit.unwrap_or(50).clamp(1, 200);
+    let offset = q.offset.unwrap_or(0).max(0);
+    let since = (Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+
+    let (total, rows) = tokio::try_join!(
+        storage.count_rks_gainers(&since),
+        storage.query_rks_gainers(&since, limit, offset)
+    )?;
+    let items = rows
+        .into_iter()
+        .zip(offset + 1..)
+        .map(|(r, rank)| RksGainerItem {
+            rank,
+            alias: r.alias,
+            user: mask_user_prefix(&r.user_hash),
+            score: r.total_rks,
+            gain: r.to_rks - r.from_rks,
+            from_rks: r.from_rks,
+            to_rks: r.to_rks,
+        })
+        .collect();
+    Ok(Json(RksGainersResponse {
+        window: window.to_string(),
+        since,
+        items,
+        total,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/public/profile/{alias}/rank-history",
+    summary = "公开玩家名次轨迹",
+    description = "返回公开玩家最近 N 天的每日名次快照（每日维护任务按 UTC 日期写入）。当日未公开或被隐藏时不会产生快照点。",
+    params(
+        ("alias" = String, Path, description = "公开别名"),
+        ("days" = Option<i64>, Query, description = "回溯天数，默认30，最大366")
+    ),
+    responses(
+        (status = 200, description = "名次轨迹", body = RankHistoryResponse),
+        (
+            status = 404,
+            description = "未找到（别名不存在或未公开）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_public_rank_history(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+    Query(q): Query<RankHistoryQuery>,
+) -> Result<Json<RankHistoryResponse>, AppError> {
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let Some(r) = storage.query_public_profile_by_alias(&alias).await? else {
+        return Err(AppError::Search(crate::error::SearchError::NotFound));
+    };
+    if r.try_get::<i64, _>("is_public").unwrap_or(0) == 0 {
+        return Err(AppError::Search(crate::error::SearchError::NotFound));
+    }
+    let user_hash: String = r.try_get("user_hash").unwrap_or_default();
+    let resp = load_rank_history(storage, &user_hash, Some(alias), history_days(&q)).await?;
+    Ok(Json(resp))
+}
+
+#[utoipa::path(
+    post,
+    path = "/leaderboard/rks/me/history",
+    summary = "我的名次轨迹",
+    description = "通过认证信息推导用户身份，返回最近 N 天的每日名次快照。",
+    params(("days" = Option<i64>, Query, description = "回溯天数，默认30，最大366")),
+    request_body = crate::auth_contract::UnifiedSaveRequest,
+    responses(
+        (status = 200, description = "名次轨迹", body = RankHistoryResponse),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn post_me_rank_history(
+    State(state): State<AppState>,
+    Query(q): Query<RankHistoryQuery>,
+    request: axum::extract::Request,
+) -> Result<Json<RankHistoryResponse>, AppError> {
+    let (mut auth, bearer_state) = crate::session_auth::parse_json_with_bearer_state::<
+        crate::auth_contract::UnifiedSaveRequest,
+    >(request)
+    .await?;
+    crate::session_auth::merge_auth_from_bearer_if_missing(
+        state.stats_storage.as_ref(),
+        &bearer_state,
+        &mut auth,
+    )
+    .await?;
+
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let salt = crate::config::AppConfig::global()
+        .stats
+        .user_hash_salt
+        .as_deref();
+    let (user_hash_opt, _kind) =
+        crate::session_auth::derive_user_identity_with_bearer(salt, &auth, &bearer_state)?;
+    let user_hash =
+        user_hash_opt.ok_or_else(|| AppError::Internal("无法识别用户（缺少可用凭证）".into()))?;
+    ensure_not_banned(storage, &user_hash).await?;
+
+    let alias = storage.get_user_alias(&user_hash).await?;
+    let resp = load_rank_history(storage, &user_hash, alias, history_days(&q)).await?;
+    Ok(Json(resp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_window_accepts_known_windows_only() {
+        assert_eq!(parse_window(None).unwrap(), ("7d", 7));
+        assert_eq!(parse_window(Some(" 30d ")).unwrap(), ("30d", 30));
+        assert!(parse_window(Some("90d")).is_err());
+        assert_eq!(history_days(&RankHistoryQuery { days: Some(0) }), 1);
+        assert_eq!(history_days(&RankHistoryQuery { days: Some(9999) }), 366);
+    }
+}
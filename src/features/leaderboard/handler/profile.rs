@@ -4,7 +4,7 @@ use axum::{
 };
 use sqlx::Row;
 
-use crate::{error::AppError, state::AppState};
+use crate::{error::AppError, state::AppState, stats_contract::ServerRegion};
 
 use super::super::models::{
     AliasRequest, ChartTextItem, ProfileUpdateRequest, PublicProfileResponse, RksCompositionText,
@@ -221,6 +221,11 @@ pub async fn get_public_profile(
     let user_hash: String = r.try_get("user_hash").unwrap_or_default();
     let score: f64 = r.try_get("total_rks").unwrap_or(0.0);
     let updated_at: String = r.try_get("updated_at").unwrap_or_default();
+    let region: String = r
+        .try_get::<Option<String>, _>("region")
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| ServerRegion::Unspecified.as_str().to_string());
     let show_rc: i64 = r.try_get("show_rks_composition").unwrap_or(0);
     let show_b3: i64 = r.try_get("show_best_top3").unwrap_or(0);
     let show_ap3: i64 = r.try_get("show_ap_top3").unwrap_or(0);
@@ -229,6 +234,7 @@ pub async fn get_public_profile(
         alias: alias.clone(),
         score,
         updated_at,
+        region,
         rks_composition: None,
         best_top3: None,
         ap_top3: None,
@@ -7,7 +7,10 @@ use sqlx::{Row, sqlite::SqliteRow};
 use std::collections::HashMap;
 use std::time::Instant;
 
-use crate::{error::AppError, state::AppState};
+use crate::{
+    error::AppError, rks_contract::rules::RksRuleVersion, state::AppState,
+    stats_contract::ServerRegion,
+};
 
 use super::super::models::{ChartTextItem, LeaderboardTopItem, LeaderboardTopResponse, MeResponse};
 use super::cursor::{
@@ -27,6 +30,10 @@ pub struct TopQuery {
     pub cursor: Option<String>,
     /// 精简模式：不返回 BestTop3/APTop3（默认 false）
     pub lite: Option<bool>,
+    /// RKS 规则版本（默认当前规则 b27-ap3）
+    pub rule: Option<String>,
+    /// 服务器区域：all（默认，合并榜）/ cn / global / unspecified
+    pub region: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -41,9 +48,17 @@ pub struct RankQuery {
     pub end: Option<i64>,
     /// 返回数量（与 start 组合使用）
     pub count: Option<i64>,
+    /// 服务器区域：all（默认，合并榜）/ cn / global / unspecified
+    pub region: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MeQuery {
+    /// 服务器区域：all（默认，合并榜）/ cn / global / unspecified
+    pub region: Option<String>,
 }
 
-fn usize_to_i64_saturating(value: usize) -> i64 {
+pub(super) fn usize_to_i64_saturating(value: usize) -> i64 {
     i64::try_from(value).unwrap_or(i64::MAX)
 }
 
@@ -51,7 +66,7 @@ fn i64_to_usize_saturating(value: i64) -> usize {
     usize::try_from(value).unwrap_or(usize::MAX)
 }
 
-fn i64_to_f64_lossy(value: i64) -> f64 {
+pub(super) fn i64_to_f64_lossy(value: i64) -> f64 {
     value.to_string().parse::<f64>().unwrap_or_else(|_| {
         if value.is_negative() {
             f64::MIN
@@ -61,7 +76,7 @@ fn i64_to_f64_lossy(value: i64) -> f64 {
     })
 }
 
-fn truncate_overfetched_rows<T>(rows: &mut Vec<T>, limit: i64) -> bool {
+pub(super) fn truncate_overfetched_rows<T>(rows: &mut Vec<T>, limit: i64) -> bool {
     let limit = i64_to_usize_saturating(limit.max(0));
     let has_more = rows.len() > limit;
     if has_more {
@@ -70,6 +85,23 @@ fn truncate_overfetched_rows<T>(rows: &mut Vec<T>, limit: i64) -> bool {
     has_more
 }
 
+/// 解析 by-rank 区间参数，返回 (起始名次, 数量)；数量上限 200。
+pub(super) fn parse_rank_range(q: &RankQuery) -> Result<(i64, i64), AppError> {
+    if let Some(r) = q.rank {
+        Ok((r.max(1), 1_i64))
+    } else if let (Some(s), Some(e)) = (q.start, q.end) {
+        let s = s.max(1);
+        let e = e.max(s);
+        Ok((s, (e - s + 1).min(200)))
+    } else if let (Some(s), Some(c)) = (q.start, q.count) {
+        Ok((s.max(1), c.clamp(1, 200)))
+    } else {
+        Err(AppError::Validation(
+            "必须提供 rank 或 (start,end)/(start,count)".into(),
+        ))
+    }
+}
+
 /// 批量查询 BestTop3/APTop3 文本详情，避免 N+1 往返。
 ///
 /// 行为保持：详情查询失败时，仍然返回排行榜主数据（详情字段为 None）。
@@ -86,7 +118,7 @@ async fn fetch_top3_details_map(
     }
 }
 
-async fn build_leaderboard_items(
+pub(super) async fn build_leaderboard_items(
     storage: &crate::stats_contract::StatsStorage,
     rows: Vec<SqliteRow>,
     rank_base: i64,
@@ -148,6 +180,27 @@ async fn build_leaderboard_items(
     items
 }
 
+/// 读取公开排行榜的一段窗口（名次从 `start_rank` 开始，1-based），供图片渲染等内部调用。
+///
+/// 从榜首开始时走 seek 查询；BestTop3/APTop3 仅在玩家开启对应展示时填充。
+pub(crate) async fn load_leaderboard_window(
+    storage: &crate::stats_contract::StatsStorage,
+    start_rank: i64,
+    limit: i64,
+) -> Result<Vec<LeaderboardTopItem>, AppError> {
+    let start_rank = start_rank.max(1);
+    let rows = if start_rank == 1 {
+        storage
+            .query_leaderboard_top_seek(None, f64::MAX, "", "", limit)
+            .await?
+    } else {
+        storage
+            .query_leaderboard_top_offset(None, limit, start_rank - 1)
+            .await?
+    };
+    Ok(build_leaderboard_items(storage, rows, start_rank, false).await)
+}
+
 #[utoipa::path(
     get,
     path = "/leaderboard/rks/top",
@@ -157,10 +210,18 @@ async fn build_leaderboard_items(
         ("limit" = Option<i64>, Query, description = "每页数量，默认50；普通模式最大200，lite=true时最大1000"),
         ("offset" = Option<i64>, Query, description = "偏移量"),
         ("cursor" = Option<String>, Query, description = "加密游标；存在时优先使用 cursor，并忽略 offset 与 after_*"),
-        ("lite" = Option<bool>, Query, description = "精简模式：不返回 bestTop3/apTop3（默认 false）")
+        ("lite" = Option<bool>, Query, description = "精简模式：不返回 bestTop3/apTop3（默认 false）"),
+        ("rule" = Option<String>, Query, description = "RKS 规则版本：b27-ap3（默认）/ b27-ap3-exclusive / b19-phi1 / b19-phi1-exclusive。非当前规则的分数来自玩家提交存档时的重算；游标只能在同一 rule 下续用"),
+        ("region" = Option<String>, Query, description = "服务器区域：all（默认，合并榜）/ cn / global / unspecified（区域记录上线前的历史数据）。名次在所选区域内计算；游标只能在同一 region 下续用")
     ),
     responses(
         (status = 200, description = "排行榜 TOP", body = LeaderboardTopResponse),
+        (
+            status = 422,
+            description = "参数校验失败（rule/region/cursor 无效）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
         (
             status = 500,
             description = "统计存储未初始化/查询失败",
@@ -180,6 +241,16 @@ pub async fn get_top(
         .as_ref()
         .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
     let lite = q.lite.unwrap_or(false);
+    // 当前规则走主榜；其他规则走分规则榜（None 表示主榜）
+    let rule = match q.rule.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        None => None,
+        Some(code) => {
+            let version = RksRuleVersion::from_code(code)
+                .ok_or_else(|| AppError::Validation(format!("rule 无效：{code}")))?;
+            (!version.is_current()).then_some(version.code())
+        }
+    };
+    let region = super::parse_region_filter(q.region.as_deref())?.map(ServerRegion::as_str);
     let max_limit = if lite { 1000 } else { 200 };
     let limit = q.limit.unwrap_or(50).clamp(1, max_limit);
     let offset = q.offset.unwrap_or(0).max(0);
@@ -196,21 +267,51 @@ pub async fn get_top(
         .unwrap_or(offset + 1);
     let fetch_limit = limit.saturating_add(1);
 
-    let total_fut = storage.count_public_leaderboard_total();
+    let total_fut = async {
+        match rule {
+            Some(rule) => {
+                storage
+                    .count_public_rule_leaderboard_total(rule, region)
+                    .await
+            }
+            None => storage.count_public_leaderboard_total(region).await,
+        }
+    };
     let rows_fut = async {
-        if let Some(cursor) = seek.as_ref() {
-            storage
-                .query_leaderboard_top_seek(
-                    cursor.score,
-                    &cursor.updated_at,
-                    &cursor.user_hash,
-                    fetch_limit,
-                )
-                .await
-        } else {
-            storage
-                .query_leaderboard_top_offset(fetch_limit, offset)
-                .await
+        match (rule, seek.as_ref()) {
+            (Some(rule), Some(cursor)) => {
+                storage
+                    .query_rule_leaderboard_top_seek(
+                        rule,
+                        region,
+                        cursor.score,
+                        &cursor.updated_at,
+                        &cursor.user_hash,
+                        fetch_limit,
+                    )
+                    .await
+            }
+            (Some(rule), None) => {
+                storage
+                    .query_rule_leaderboard_top_offset(rule, region, fetch_limit, offset)
+                    .await
+            }
+            (None, Some(cursor)) => {
+                storage
+                    .query_leaderboard_top_seek(
+                        region,
+                        cursor.score,
+                        &cursor.updated_at,
+                        &cursor.user_hash,
+                        fetch_limit,
+                    )
+                    .await
+            }
+            (None, None) => {
+                storage
+                    .query_leaderboard_top_offset(region, fetch_limit, offset)
+                    .await
+            }
         }
     };
     let (total, mut rows) = tokio::try_join!(total_fut, rows_fut)?;
@@ -232,6 +333,8 @@ pub async fn get_top(
         phase = "total",
         status = "ok",
         lite,
+        rule = rule.unwrap_or("current"),
+        region = region.unwrap_or("all"),
         items = items.len(),
         total,
         total_dur_ms = t_total.elapsed().as_millis(),
@@ -280,13 +383,14 @@ pub async fn get_top(
         ("start" = Option<i64>, Query, description = "起始排名（1-based）"),
         ("end" = Option<i64>, Query, description = "结束排名（包含）"),
         ("count" = Option<i64>, Query, description = "返回数量（与 start 组合使用）"),
-        ("lite" = Option<bool>, Query, description = "精简模式：不返回 bestTop3/apTop3（默认 false）")
+        ("lite" = Option<bool>, Query, description = "精简模式：不返回 bestTop3/apTop3（默认 false）"),
+        ("region" = Option<String>, Query, description = "服务器区域：all（默认，合并榜）/ cn / global / unspecified")
     ),
     responses(
         (status = 200, description = "区间结果", body = LeaderboardTopResponse),
         (
             status = 422,
-            description = "参数校验失败（缺少 rank/start 等）",
+            description = "参数校验失败（缺少 rank/start、region 无效等）",
             body = crate::error::ProblemDetails,
             content_type = "application/problem+json"
         ),
@@ -309,29 +413,17 @@ pub async fn get_by_rank(
         .as_ref()
         .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
 
-    // 解析区间
-    let (start_rank, count) = if let Some(r) = q.rank {
-        (r.max(1), 1_i64)
-    } else if let (Some(s), Some(e)) = (q.start, q.end) {
-        let s = s.max(1);
-        let e = e.max(s);
-        (s, (e - s + 1).min(200))
-    } else if let (Some(s), Some(c)) = (q.start, q.count) {
-        let s = s.max(1);
-        let c = c.clamp(1, 200);
-        (s, c)
-    } else {
-        return Err(AppError::Validation(
-            "必须提供 rank 或 (start,end)/(start,count)".into(),
-        ));
-    };
+    let (start_rank, count) = parse_rank_range(&q)?;
+    let region = super::parse_region_filter(q.region.as_deref())?.map(ServerRegion::as_str);
 
     let offset = start_rank - 1;
     let limit = count;
     let lite = q.lite.unwrap_or(false);
 
-    let total = storage.count_public_leaderboard_total().await?;
-    let rows = storage.query_leaderboard_by_rank(limit, offset).await?;
+    let total = storage.count_public_leaderboard_total(region).await?;
+    let rows = storage
+        .query_leaderboard_by_rank(region, limit, offset)
+        .await?;
 
     let has_more = ((start_rank - 1) + usize_to_i64_saturating(rows.len())) < total;
     let (mut last_score, mut last_updated, mut last_user_hash) =
@@ -350,6 +442,7 @@ pub async fn get_by_rank(
         phase = "total",
         status = "ok",
         lite,
+        region = region.unwrap_or("all"),
         items = items.len(),
         total,
         total_dur_ms = t_total.elapsed().as_millis(),
@@ -373,10 +466,19 @@ pub async fn get_by_rank(
     post,
     path = "/leaderboard/rks/me",
     summary = "我的名次（按RKS）",
-    description = "通过认证信息推导用户身份，返回名次、分数、总量与百分位（竞争排名）",
+    description = "通过认证信息推导用户身份，返回名次、分数、总量与百分位（竞争排名）。指定 region 时在该区域榜内计算；用户记录的区域与之不符时名次为 0",
     request_body = crate::auth_contract::UnifiedSaveRequest,
+    params(
+        ("region" = Option<String>, Query, description = "服务器区域：all（默认，合并榜）/ cn / global / unspecified")
+    ),
     responses(
         (status = 200, description = "查询成功", body = MeResponse),
+        (
+            status = 422,
+            description = "参数校验失败（region 无效）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
         (
             status = 500,
             description = "统计存储未初始化/查询失败/无法识别用户",
@@ -388,8 +490,10 @@ pub async fn get_by_rank(
 )]
 pub async fn post_me(
     State(state): State<AppState>,
+    Query(q): Query<MeQuery>,
     request: axum::extract::Request,
 ) -> Result<Json<MeResponse>, AppError> {
+    let region_filter = super::parse_region_filter(q.region.as_deref())?;
     let (mut auth, bearer_state) = crate::session_auth::parse_json_with_bearer_state::<
         crate::auth_contract::UnifiedSaveRequest,
     >(request)
@@ -422,19 +526,25 @@ pub async fn post_me(
             (0.0, String::new())
         };
 
-    let total = storage.count_public_leaderboard_total().await?;
+    let my_region = storage
+        .get_leaderboard_region(&user_hash)
+        .await?
+        .unwrap_or_else(|| ServerRegion::Unspecified.as_str().to_string());
+    let region = region_filter.map(ServerRegion::as_str);
+    let total = storage.count_public_leaderboard_total(region).await?;
 
-    if total == 0 || my_score <= 0.0 {
+    if total == 0 || my_score <= 0.0 || region.is_some_and(|r| r != my_region) {
         return Ok(Json(MeResponse {
             rank: 0,
             score: 0.0,
             total,
             percentile: 0.0,
+            region: my_region,
         }));
     }
 
     let higher = storage
-        .count_public_leaderboard_higher(my_score, &my_updated, &user_hash)
+        .count_public_leaderboard_higher(region, my_score, &my_updated, &user_hash)
         .await?;
     let rank = higher + 1;
     let percentile = 100.0 * (1.0 - (i64_to_f64_lossy(rank - 1) / i64_to_f64_lossy(total)));
@@ -443,6 +553,7 @@ pub async fn post_me(
         score: my_score,
         total,
         percentile,
+        region: my_region,
     }))
 }
 
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, Sse},
+};
+use futures_util::Stream;
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::realtime::{self, StreamGuard};
+use crate::stats_contract::ServerRegion;
+use crate::{error::AppError, state::AppState};
+
+use super::super::models::RksRankChangeEvent;
+use super::super::stream::subscribe;
+use super::parse_region_filter;
+
+#[derive(Deserialize)]
+pub struct RksStreamQuery {
+    /// 服务器区域：all（默认，合并榜）/ cn / global / unspecified
+    pub region: Option<String>,
+}
+
+struct RksStreamState {
+    guard: StreamGuard,
+    rx: broadcast::Receiver<Arc<RksRankChangeEvent>>,
+    region: Option<ServerRegion>,
+    done: bool,
+}
+
+fn matches_region(ev: &RksRankChangeEvent, region: Option<ServerRegion>) -> bool {
+    region.is_none_or(|r| ev.region == r.as_str())
+}
+
+impl RksStreamState {
+    async fn next_event(&mut self) -> Option<Result<Event, axum::Error>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            tokio::select! {
+                () = self.guard.closed() => {
+                    self.done = true;
+                    return Some(Ok(realtime::shutdown_event()));
+                }
+                recv = self.rx.recv() => match recv {
+                    Ok(ev) if matches_region(&ev, self.region) => {
+                        return Some(Event::default().event("rank").json_data(&*ev));
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(missed)) => return Some(Ok(realtime::lagged_event(missed))),
+                    Err(RecvError::Closed) => return None,
+                },
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/leaderboard/rks/stream",
+    summary = "订阅排行榜变更（SSE）",
+    description = "以 Server-Sent Events 推送公开玩家的成绩刷新，替代定时刷新 /leaderboard/rks/top。\
+\n\n- `rank`：数据为 RksRankChangeEvent，含刷新后的分数、合并榜名次与所在区域榜名次\
+\n- `lagged`：客户端消费过慢，部分事件已丢弃（data 中 missed 为条数），应重新拉取榜单\
+\n- `shutdown`：服务正在关闭，客户端应稍后重连\
+\n\n仅推送成绩提升；服务端按配置间隔发送心跳注释帧；连接数超过 `realtime.max_connections` 或推送未启用时返回 403。",
+    params(
+        ("region" = Option<String>, Query, description = "服务器区域：all（默认，合并榜）/ cn / global / unspecified")
+    ),
+    responses(
+        (status = 200, description = "SSE 事件流（rank 事件数据见 RksRankChangeEvent）", content_type = "text/event-stream", body = RksRankChangeEvent),
+        (
+            status = 403,
+            description = "实时推送未启用或连接数已达上限",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "参数校验失败（region 无效）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Leaderboard"
+)]
+pub async fn get_rks_stream(
+    State(state): State<AppState>,
+    Query(q): Query<RksStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let region = parse_region_filter(q.region.as_deref())?;
+    let guard = realtime::acquire_stream()?;
+    let init = RksStreamState {
+        guard,
+        rx: subscribe(storage),
+        region,
+        done: false,
+    };
+    let stream = futures_util::stream::unfold(init, |mut s| async move {
+        s.next_event().await.map(|ev| (ev, s))
+    });
+    Ok(Sse::new(stream).keep_alive(realtime::keep_alive()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(region: &str) -> RksRankChangeEvent {
+        RksRankChangeEvent {
+            alias: None,
+            user: "ab12****".into(),
+            score: 13.0,
+            rank: 5,
+            region: region.into(),
+            region_rank: 2,
+            updated_at: "2025-09-20T04:10:44Z".into(),
+        }
+    }
+
+    #[test]
+    fn region_filter_passes_combined_and_matching_only() {
+        assert!(matches_region(&event("cn"), None));
+        assert!(matches_region(&event("cn"), Some(ServerRegion::Cn)));
+        assert!(!matches_region(&event("global"), Some(ServerRegion::Cn)));
+    }
+}
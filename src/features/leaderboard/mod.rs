@@ -1,2 +1,6 @@
+pub mod anti_cheat;
+pub mod distribution;
 pub mod handler;
 pub mod models;
+pub mod recompute;
+pub mod stream;
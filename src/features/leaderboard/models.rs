@@ -96,19 +96,217 @@ pub struct LeaderboardTopResponse {
     pub next_cursor: Option<String>,
 }
 
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "rank": 1,
+  "alias": "Alice",
+  "user": "ab12****",
+  "score": 1_000_000,
+  "acc": 100.0,
+  "fc": true,
+  "updatedAt": "2025-09-20T04:10:44Z"
+}))]
+pub struct ChartLeaderboardItem {
+    /// 名次（按 score、acc、更新时间稳定排序）
+    pub rank: i64,
+    /// 公开别名（如有）
+    pub alias: Option<String>,
+    /// 去敏化用户标识（hash 前缀）
+    pub user: String,
+    /// 分数
+    pub score: i64,
+    /// ACC 百分比
+    pub acc: f64,
+    /// 是否 Full Combo
+    pub fc: bool,
+    /// 该谱面成绩的最近更新时间（UTC RFC3339）
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "songId": "Glaciaxion.SunsetRay.0",
+  "songName": "Glaciaxion",
+  "difficulty": "IN",
+  "items": [
+    {
+      "rank": 1,
+      "alias": "Alice",
+      "user": "ab12****",
+      "score": 1_000_000,
+      "acc": 100.0,
+      "fc": true,
+      "updatedAt": "2025-09-20T04:10:44Z"
+    }
+  ],
+  "total": 321,
+  "nextCursor": "encrypted-cursor"
+}))]
+pub struct ChartLeaderboardResponse {
+    pub song_id: String,
+    pub song_name: String,
+    /// 难度（EZ/HD/IN/AT）
+    pub difficulty: String,
+    pub items: Vec<ChartLeaderboardItem>,
+    pub total: i64,
+    /// 下一页加密游标（仅可用于同一谱面）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "rank": 1,
+  "alias": "Alice",
+  "user": "ab12****",
+  "score": 15.12,
+  "gain": 0.35,
+  "fromRks": 14.77,
+  "toRks": 15.12
+}))]
+pub struct RksGainerItem {
+    /// 涨幅名次
+    pub rank: i64,
+    /// 公开别名（如有）
+    pub alias: Option<String>,
+    /// 去敏化用户标识（hash 前缀）
+    pub user: String,
+    /// 当前排行榜 RKS
+    pub score: f64,
+    /// 窗口内 RKS 涨幅（toRks - fromRks）
+    pub gain: f64,
+    /// 窗口开始前的最高 RKS
+    pub from_rks: f64,
+    /// 窗口内的最高 RKS
+    pub to_rks: f64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "window": "7d",
+  "since": "2025-09-13T04:10:44Z",
+  "items": [
+    {
+      "rank": 1,
+      "alias": "Alice",
+      "user": "ab12****",
+      "score": 15.12,
+      "gain": 0.35,
+      "fromRks": 14.77,
+      "toRks": 15.12
+    }
+  ],
+  "total": 87
+}))]
+pub struct RksGainersResponse {
+    /// 统计窗口（7d/30d）
+    pub window: String,
+    /// 窗口起点（UTC RFC3339）
+    pub since: String,
+    pub items: Vec<RksGainerItem>,
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({"date": "2025-09-20", "rank": 42, "score": 13.21}))]
+pub struct RankHistoryPoint {
+    /// 快照日期（UTC，YYYY-MM-DD）
+    pub date: String,
+    /// 当日名次
+    pub rank: i64,
+    /// 当日 RKS
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "alias": "Alice",
+  "days": 30,
+  "points": [
+    {"date": "2025-09-19", "rank": 45, "score": 13.18},
+    {"date": "2025-09-20", "rank": 42, "score": 13.21}
+  ]
+}))]
+pub struct RankHistoryResponse {
+    /// 公开别名（如有）
+    pub alias: Option<String>,
+    /// 查询的天数窗口
+    pub days: i64,
+    /// 每日名次（按日期升序；当日未上榜则缺省）
+    pub points: Vec<RankHistoryPoint>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({"from": 13.0, "to": 13.5, "count": 120}))]
+pub struct RksBucket {
+    /// 桶下界（含）
+    pub from: f64,
+    /// 桶上界（不含）
+    pub to: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({"score": 13.21, "rank": 42, "percentile": 99.59, "topPercent": 0.42}))]
+pub struct RksStanding {
+    pub score: f64,
+    /// 名次（快照内分数更高的人数 + 1）
+    pub rank: i64,
+    /// 百分位（与 /leaderboard/rks/me 同口径）
+    pub percentile: f64,
+    /// 位于前百分之多少（rank / total × 100）
+    pub top_percent: f64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "bucketWidth": 0.5,
+  "total": 10000,
+  "buckets": [
+    {"from": 12.5, "to": 13.0, "count": 380},
+    {"from": 13.0, "to": 13.5, "count": 120}
+  ],
+  "me": {"score": 13.21, "rank": 42, "percentile": 99.59, "topPercent": 0.42},
+  "computedAt": "2025-09-20T04:10:44Z"
+}))]
+pub struct RksDistributionResponse {
+    pub bucket_width: f64,
+    /// 快照内公开玩家总数
+    pub total: i64,
+    /// 从最低到最高非空桶之间的连续桶
+    pub buckets: Vec<RksBucket>,
+    /// 调用者（Bearer 会话或 score 参数）的位置；无法确定分数时缺省
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub me: Option<RksStanding>,
+    /// 分数快照计算时间（UTC RFC3339）
+    pub computed_at: String,
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 #[schema(example = json!({
   "rank": 42,
   "score": 13.21,
   "total": 10000,
-  "percentile": 99.58
+  "percentile": 99.58,
+  "region": "cn"
 }))]
 pub struct MeResponse {
     pub rank: i64,
     pub score: f64,
     pub total: i64,
     pub percentile: f64,
+    /// 用户记录的服务器区域：cn / global / unspecified
+    pub region: String,
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
@@ -145,6 +343,7 @@ pub struct ProfileUpdateRequest {
   "alias": "Alice",
   "score": 14.73,
   "updatedAt": "2025-09-20T04:10:44Z",
+  "region": "cn",
   "rksComposition": {"best27Sum": 390.12, "apTop3Sum": 49.20},
   "bestTop3": [{"song":"Tempestissimo","difficulty":"AT","acc":99.43,"rks":15.12}],
   "apTop3": [{"song":"AP Song","difficulty":"IN","acc":100.0,"rks":13.45}]
@@ -153,6 +352,8 @@ pub struct PublicProfileResponse {
     pub alias: String,
     pub score: f64,
     pub updated_at: String,
+    /// 服务器区域：cn / global / unspecified
+    pub region: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rks_composition: Option<RksCompositionText>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -160,3 +361,124 @@ pub struct PublicProfileResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ap_top3: Option<Vec<ChartTextItem>>,
 }
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+  "name": "周末音游部"
+}))]
+pub struct GroupCreateRequest {
+    pub auth: UnifiedSaveRequest,
+    /// 群组名称（1~32 字符）
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+  "inviteCode": "K7QX2MPA"
+}))]
+pub struct GroupJoinRequest {
+    pub auth: UnifiedSaveRequest,
+    /// 邀请码（大小写不敏感）
+    pub invite_code: String,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+  "memberId": 12
+}))]
+pub struct GroupKickRequest {
+    pub auth: UnifiedSaveRequest,
+    /// 成员记录 ID（来自成员列表的 memberId）
+    pub member_id: i64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "id": "5f0c6c1e3a2b4f0e9d7c8b6a5e4d3c2b",
+  "name": "周末音游部",
+  "isOwner": true,
+  "inviteCode": "K7QX2MPA",
+  "memberCount": 8,
+  "createdAt": "2025-09-20T04:10:44Z"
+}))]
+pub struct GroupInfo {
+    pub id: String,
+    pub name: String,
+    /// 当前用户是否为创建者
+    pub is_owner: bool,
+    /// 邀请码（成员可见，可转发给他人加入）
+    pub invite_code: String,
+    pub member_count: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupListResponse {
+    pub items: Vec<GroupInfo>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "memberId": 12,
+  "alias": "Alice",
+  "user": "ab12****",
+  "isOwner": false,
+  "isSelf": false,
+  "joinedAt": "2025-09-20T04:10:44Z"
+}))]
+pub struct GroupMemberItem {
+    /// 成员记录 ID（创建者移除成员时使用）
+    pub member_id: i64,
+    /// 别名（如有；群组内不要求公开）
+    pub alias: Option<String>,
+    /// 去敏化用户标识（hash 前缀）
+    pub user: String,
+    pub is_owner: bool,
+    /// 是否为当前用户
+    pub is_self: bool,
+    pub joined_at: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMembersResponse {
+    pub group: GroupInfo,
+    pub items: Vec<GroupMemberItem>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+  "alias": "Alice",
+  "user": "ab12****",
+  "score": 14.73,
+  "rank": 3,
+  "region": "cn",
+  "regionRank": 2,
+  "updatedAt": "2025-09-20T04:10:44Z"
+}))]
+pub struct RksRankChangeEvent {
+    /// 公开别名（如有）
+    pub alias: Option<String>,
+    /// 去敏化用户标识（hash 前缀）
+    pub user: String,
+    /// 刷新后的总 RKS
+    pub score: f64,
+    /// 合并榜名次
+    pub rank: i64,
+    /// 服务器区域：cn / global / unspecified
+    pub region: String,
+    /// 所在区域榜名次
+    pub region_rank: i64,
+    /// 成绩更新时间（UTC RFC3339）
+    pub updated_at: String,
+}
@@ -0,0 +1,426 @@
+//! 排行榜重算任务
+//!
+//! 定数更新后，按玩家最近一次提交的谱面快照（`save_submissions.details_json`）以当前定数
+//! 重写 `leaderboard_rks` / `leaderboard_rks_rule` / `leaderboard_details`。
+//!
+//! 任务按 user_hash 升序分批执行，每批提交后把进度写入 `stats_meta`；进程中途退出时状态
+//! 保持为 running，下次启动自动从上次的游标继续。同一进程内同时只会运行一个任务。
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::game_data::GameData;
+use crate::rks_contract::chart_snapshot::ChartAccSnapshot;
+use crate::rks_contract::engine::{calculate_player_rks, calculate_player_rks_with_rule};
+use crate::rks_contract::rules::RksRuleVersion;
+use crate::state::AppState;
+use crate::stats_contract::{LeaderboardChartDetails, RecomputedLeaderboardRow, StatsStorage};
+
+const JOB_META_KEY: &str = "leaderboard_recompute_job";
+/// 每批处理的用户数
+const BATCH_SIZE: i64 = 200;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RecomputeStatus {
+    /// 从未运行
+    #[default]
+    Idle,
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// 持久化的任务进度
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecomputeJobState {
+    pub status: RecomputeStatus,
+    /// 最近一批使用的游戏数据版本
+    pub data_version: u64,
+    /// 开始时间（RFC3339）
+    pub started_at: Option<String>,
+    /// 最近一次进度更新时间（RFC3339）
+    pub updated_at: Option<String>,
+    /// 结束时间（RFC3339）
+    pub finished_at: Option<String>,
+    /// 已处理的最后一个 user_hash（续跑游标）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    /// 开始时排行榜中的用户数
+    pub total_users: u64,
+    /// 已处理用户数
+    pub processed_users: u64,
+    /// 已重写的用户数
+    pub updated_users: u64,
+    /// 跳过的用户数：没有谱面快照（下次提交存档时才会更新），或重算期间已有新提交
+    pub skipped_users: u64,
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 任务进度响应
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecomputeProgressResponse {
+    #[serde(flatten)]
+    pub job: RecomputeJobState,
+    /// 本实例是否正在执行任务
+    pub running: bool,
+    /// 完成百分比（0-100）
+    pub percent: f64,
+}
+
+impl RecomputeProgressResponse {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(job: RecomputeJobState) -> Self {
+        let percent = match job.status {
+            RecomputeStatus::Completed => 100.0,
+            _ if job.total_users == 0 => 0.0,
+            _ => (job.processed_users as f64 / job.total_users as f64 * 100.0).min(100.0),
+        };
+        Self {
+            job,
+            running: RUNNING.load(Ordering::SeqCst),
+            percent,
+        }
+    }
+}
+
+/// 启动请求
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecomputeStartRequest {
+    /// 忽略上次未完成的进度，从头开始（默认 false：存在未完成任务时续跑）
+    #[serde(default)]
+    pub restart: bool,
+}
+
+async fn load_job(storage: &StatsStorage) -> Result<RecomputeJobState, AppError> {
+    Ok(storage
+        .get_stats_meta(JOB_META_KEY)
+        .await?
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default())
+}
+
+async fn save_job(storage: &StatsStorage, job: &RecomputeJobState) -> Result<(), AppError> {
+    let raw = serde_json::to_string(job)
+        .map_err(|e| AppError::Internal(format!("serialize recompute job: {e}")))?;
+    storage.set_stats_meta(JOB_META_KEY, &raw).await
+}
+
+/// 读取当前进度
+pub async fn progress(storage: &StatsStorage) -> Result<RecomputeProgressResponse, AppError> {
+    Ok(RecomputeProgressResponse::new(load_job(storage).await?))
+}
+
+/// 启动（或续跑）重算任务；本实例已有任务在运行时返回 409。
+pub async fn start(
+    state: AppState,
+    storage: Arc<StatsStorage>,
+    restart: bool,
+) -> Result<RecomputeProgressResponse, AppError> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(AppError::Conflict("排行榜重算任务正在运行".into()));
+    }
+    let prepared = prepare_job(&state, &storage, restart).await;
+    let job = match prepared {
+        Ok(job) => job,
+        Err(e) => {
+            RUNNING.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+    };
+    CANCEL_REQUESTED.store(false, Ordering::SeqCst);
+    tokio::spawn(run(state, storage, job.clone()));
+    Ok(RecomputeProgressResponse::new(job))
+}
+
+async fn prepare_job(
+    state: &AppState,
+    storage: &StatsStorage,
+    restart: bool,
+) -> Result<RecomputeJobState, AppError> {
+    let prev = load_job(storage).await?;
+    let resumable = matches!(
+        prev.status,
+        RecomputeStatus::Running | RecomputeStatus::Cancelled | RecomputeStatus::Failed
+    );
+    let now = chrono::Utc::now().to_rfc3339();
+    let job = if resumable && !restart {
+        tracing::info!(
+            target: "phi_backend::leaderboard",
+            processed = prev.processed_users,
+            total = prev.total_users,
+            "排行榜重算任务续跑"
+        );
+        RecomputeJobState {
+            status: RecomputeStatus::Running,
+            updated_at: Some(now),
+            finished_at: None,
+            error: None,
+            ..prev
+        }
+    } else {
+        let total = storage.count_leaderboard_users().await?;
+        RecomputeJobState {
+            status: RecomputeStatus::Running,
+            data_version: state.game_data().version,
+            started_at: Some(now.clone()),
+            updated_at: Some(now),
+            total_users: u64::try_from(total).unwrap_or(0),
+            ..RecomputeJobState::default()
+        }
+    };
+    save_job(storage, &job).await?;
+    Ok(job)
+}
+
+/// 请求停止当前任务（在当前批次结束后生效）；返回是否有任务在运行。
+pub fn request_cancel() -> bool {
+    let running = RUNNING.load(Ordering::SeqCst);
+    if running {
+        CANCEL_REQUESTED.store(true, Ordering::SeqCst);
+    }
+    running
+}
+
+/// 启动时检查：上次进程退出时任务仍在运行则自动续跑。
+pub fn spawn_resume_if_interrupted(state: AppState) {
+    let Some(storage) = state.stats_storage.clone() else {
+        return;
+    };
+    tokio::spawn(async move {
+        match load_job(&storage).await {
+            Ok(job) if job.status == RecomputeStatus::Running => {
+                if let Err(e) = start(state, storage, false).await {
+                    tracing::warn!(target: "phi_backend::leaderboard", "排行榜重算任务续跑失败: {e}");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(target: "phi_backend::leaderboard", "读取排行榜重算任务状态失败: {e}");
+            }
+        }
+    });
+}
+
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+async fn run(state: AppState, storage: Arc<StatsStorage>, mut job: RecomputeJobState) {
+    let _guard = RunningGuard;
+    loop {
+        if CANCEL_REQUESTED.swap(false, Ordering::SeqCst) {
+            job.status = RecomputeStatus::Cancelled;
+            break;
+        }
+        match run_batch(&state, &storage, &mut job).await {
+            Ok(true) => {}
+            Ok(false) => {
+                job.status = RecomputeStatus::Completed;
+                break;
+            }
+            Err(e) => {
+                tracing::warn!(target: "phi_backend::leaderboard", "排行榜重算任务失败: {e}");
+                job.status = RecomputeStatus::Failed;
+                job.error = Some(e.to_string());
+                break;
+            }
+        }
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    job.updated_at = Some(now.clone());
+    job.finished_at = Some(now);
+    if let Err(e) = save_job(&storage, &job).await {
+        tracing::warn!(target: "phi_backend::leaderboard", "保存排行榜重算任务状态失败: {e}");
+    }
+    tracing::info!(
+        target: "phi_backend::leaderboard",
+        status = ?job.status,
+        processed = job.processed_users,
+        updated = job.updated_users,
+        skipped = job.skipped_users,
+        "排行榜重算任务结束"
+    );
+}
+
+/// 处理一批用户；返回是否还有下一批。
+async fn run_batch(
+    state: &AppState,
+    storage: &StatsStorage,
+    job: &mut RecomputeJobState,
+) -> Result<bool, AppError> {
+    let rows = storage
+        .query_leaderboard_chart_details(job.cursor.as_deref(), BATCH_SIZE)
+        .await?;
+    let Some(last) = rows.last() else {
+        return Ok(false);
+    };
+    let next_cursor = last.user_hash.clone();
+    let row_count = rows.len();
+    let has_more = i64::try_from(row_count).unwrap_or(i64::MAX) >= BATCH_SIZE;
+
+    // 每批取一次快照：任务期间定数热更新时，后续批次使用新定数
+    let game = state.game_data();
+    let data_version = game.version;
+    let recomputed = tokio::task::spawn_blocking(move || recompute_rows(&rows, &game))
+        .await
+        .map_err(|e| AppError::Internal(format!("排行榜重算计算任务失败: {e}")))?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    // 读取之后又有新提交的用户不写回（上传时已按当前定数写入），计入跳过
+    let updated = storage
+        .apply_leaderboard_recompute(&recomputed, &now)
+        .await?;
+
+    job.data_version = data_version;
+    job.processed_users += row_count as u64;
+    job.updated_users += updated;
+    job.skipped_users += row_count as u64 - updated;
+    job.cursor = Some(next_cursor);
+    job.updated_at = Some(now);
+    save_job(storage, job).await?;
+    Ok(has_more)
+}
+
+/// 按当前定数重算一批用户；没有谱面快照的用户不产出结果。
+fn recompute_rows(
+    rows: &[LeaderboardChartDetails],
+    game: &GameData,
+) -> Vec<RecomputedLeaderboardRow> {
+    rows.iter()
+        .filter_map(|row| {
+            let snapshot = ChartAccSnapshot::from_json(row.details_json.as_deref()?)?;
+            Some(recompute_user(row, &snapshot, game))
+        })
+        .collect()
+}
+
+fn recompute_user(
+    row: &LeaderboardChartDetails,
+    snapshot: &ChartAccSnapshot,
+    game: &GameData,
+) -> RecomputedLeaderboardRow {
+    let record = snapshot.to_game_record();
+    let current = calculate_player_rks(&record, &game.chart_constants);
+    let (best_top3, ap_top3, rks_comp) =
+        crate::save_contract::build_textual_details_from_rks(&record, &current, &game.song_catalog);
+    let rule_totals = RksRuleVersion::ALL
+        .into_iter()
+        .filter(|v| !v.is_current())
+        .map(|v| {
+            let total =
+                calculate_player_rks_with_rule(&record, &game.chart_constants, v.rule()).total_rks;
+            (v.code(), total)
+        })
+        .collect();
+    RecomputedLeaderboardRow {
+        user_hash: row.user_hash.clone(),
+        based_on_submission_id: row.latest_submission_id,
+        total_rks: current.total_rks,
+        rule_totals,
+        rks_comp_json: serde_json::to_string(&rks_comp).ok(),
+        best_top3_json: serde_json::to_string(&best_top3).ok(),
+        ap_top3_json: serde_json::to_string(&ap_top3).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_contract::{Difficulty, DifficultyRecord};
+    use crate::startup::chart_loader::{ChartConstants, ChartConstantsMap};
+    use std::collections::HashMap;
+
+    fn game(level: f32) -> GameData {
+        let mut constants = ChartConstantsMap::new();
+        constants.insert(
+            "song".to_string(),
+            ChartConstants {
+                ez: None,
+                hd: None,
+                in_level: Some(level),
+                at: None,
+            },
+        );
+        GameData {
+            chart_constants: Arc::new(constants),
+            song_catalog: Arc::new(crate::song_contract::SongCatalog::default()),
+            version: 1,
+            source: crate::game_data::GameDataSource::Local,
+            loaded_at: String::new(),
+        }
+    }
+
+    fn details(user: &str, with_snapshot: bool) -> LeaderboardChartDetails {
+        let mut records = HashMap::new();
+        records.insert(
+            "song".to_string(),
+            vec![DifficultyRecord {
+                difficulty: Difficulty::IN,
+                score: 1_000_000,
+                accuracy: 100.0,
+                is_full_combo: true,
+                chart_constant: None,
+                push_acc: None,
+                push_acc_hint: None,
+            }],
+        );
+        LeaderboardChartDetails {
+            user_hash: user.to_string(),
+            total_rks: 0.0,
+            is_hidden: false,
+            details_json: with_snapshot
+                .then(|| ChartAccSnapshot::from_game_record(&records).to_json())
+                .flatten(),
+            latest_submission_id: Some(7),
+        }
+    }
+
+    #[test]
+    fn recompute_uses_current_constants_and_skips_users_without_snapshot() {
+        let rows = vec![details("a", true), details("b", false)];
+        let old = recompute_rows(&rows, &game(12.0));
+        let new = recompute_rows(&rows, &game(13.0));
+        assert_eq!(old.len(), 1);
+        assert_eq!(new[0].user_hash, "a");
+        assert_eq!(new[0].based_on_submission_id, Some(7));
+        // 一个 AP 谱面同时计入 Best27 与 AP3：(c + c) / 30
+        assert!((old[0].total_rks - 24.0 / 30.0).abs() < 1e-9);
+        assert!((new[0].total_rks - 26.0 / 30.0).abs() < 1e-9);
+        assert_eq!(new[0].rule_totals.len(), RksRuleVersion::ALL.len() - 1);
+        assert!(new[0].best_top3_json.is_some());
+    }
+
+    #[test]
+    fn progress_percent_follows_processed_users() {
+        let job = RecomputeJobState {
+            status: RecomputeStatus::Running,
+            total_users: 200,
+            processed_users: 50,
+            ..RecomputeJobState::default()
+        };
+        assert!((RecomputeProgressResponse::new(job).percent - 25.0).abs() < 1e-9);
+        let done = RecomputeJobState {
+            status: RecomputeStatus::Completed,
+            ..RecomputeJobState::default()
+        };
+        assert!((RecomputeProgressResponse::new(done).percent - 100.0).abs() < 1e-9);
+    }
+}
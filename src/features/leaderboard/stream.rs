@@ -0,0 +1,110 @@
+//! 排行榜实时变更
+//!
+//! 存储层在 `upsert_leaderboard_rks` 刷新成绩时广播原始通知；这里为每个存储实例
+//! 懒启动一个补全任务，查询别名与名次后再扇出给所有 SSE 订阅者。
+//! 补全只在有订阅者时进行，单个慢连接也只会丢弃自己的缓冲，不影响写入路径。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::error::AppError;
+use crate::stats_contract::{LeaderboardRksChange, StatsStorage};
+
+use super::handler::mask_user_prefix;
+use super::models::RksRankChangeEvent;
+
+type EventSender = broadcast::Sender<Arc<RksRankChangeEvent>>;
+
+fn hubs() -> &'static Mutex<HashMap<usize, EventSender>> {
+    static HUBS: OnceLock<Mutex<HashMap<usize, EventSender>>> = OnceLock::new();
+    HUBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 订阅某个存储实例的排行榜变更（含名次）；首次订阅时启动补全任务。
+pub fn subscribe(storage: &Arc<StatsStorage>) -> broadcast::Receiver<Arc<RksRankChangeEvent>> {
+    let key = Arc::as_ptr(storage) as usize;
+    let mut hubs = hubs()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(tx) = hubs.get(&key) {
+        return tx.subscribe();
+    }
+    let (tx, rx) = broadcast::channel(crate::realtime::channel_capacity());
+    let raw = storage.subscribe_leaderboard_changes();
+    tokio::spawn(run_enricher(key, Arc::downgrade(storage), raw, tx.clone()));
+    hubs.insert(key, tx);
+    rx
+}
+
+async fn run_enricher(
+    key: usize,
+    storage: Weak<StatsStorage>,
+    mut raw: broadcast::Receiver<LeaderboardRksChange>,
+    tx: EventSender,
+) {
+    loop {
+        let change = match raw.recv().await {
+            Ok(change) => change,
+            Err(RecvError::Lagged(missed)) => {
+                tracing::warn!(missed, "排行榜变更补全落后，已跳过部分通知");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if tx.receiver_count() == 0 {
+            continue;
+        }
+        let Some(storage) = storage.upgrade() else {
+            break;
+        };
+        match enrich(&storage, change).await {
+            Ok(Some(ev)) => {
+                let _ = tx.send(Arc::new(ev));
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(err = %e, "排行榜变更补全失败"),
+        }
+    }
+    hubs()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .remove(&key);
+}
+
+/// 补全别名与名次；未公开资料的用户不出现在公开榜，返回 None。
+async fn enrich(
+    storage: &StatsStorage,
+    change: LeaderboardRksChange,
+) -> Result<Option<RksRankChangeEvent>, AppError> {
+    let Some(alias) = storage.get_public_alias(&change.user_hash).await? else {
+        return Ok(None);
+    };
+    let higher = storage
+        .count_public_leaderboard_higher(
+            None,
+            change.total_rks,
+            &change.updated_at,
+            &change.user_hash,
+        )
+        .await?;
+    let region = change.region.as_str();
+    let region_higher = storage
+        .count_public_leaderboard_higher(
+            Some(region),
+            change.total_rks,
+            &change.updated_at,
+            &change.user_hash,
+        )
+        .await?;
+    Ok(Some(RksRankChangeEvent {
+        alias,
+        user: mask_user_prefix(&change.user_hash),
+        score: change.total_rks,
+        rank: higher + 1,
+        region: region.to_string(),
+        region_rank: region_higher + 1,
+        updated_at: change.updated_at,
+    }))
+}
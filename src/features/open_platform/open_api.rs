@@ -15,10 +15,13 @@ pub(crate) mod search;
 use super::token_auth::{OpenApiRoutePolicy, open_api_token_middleware};
 
 pub(crate) use self::auth::{open_auth_qrcode, open_auth_qrcode_status};
-pub use self::image::{open_image_bn, open_image_song};
-pub use self::leaderboard::{open_get_leaderboard_by_rank, open_get_leaderboard_top};
+pub use self::image::{open_image_bn, open_image_leaderboard, open_image_song};
+pub use self::leaderboard::{
+    open_get_chart_leaderboard_top, open_get_leaderboard_by_rank, open_get_leaderboard_top,
+    open_get_rank_history, open_get_rks_distribution, open_get_rks_gainers,
+};
 pub use self::rks::open_post_rks_history;
-pub use self::save::open_save_data;
+pub use self::save::{open_save_data, open_save_upload};
 pub use self::search::open_search_songs;
 
 pub fn create_open_platform_open_api_router() -> Router<AppState> {
@@ -47,6 +50,15 @@ pub fn create_open_platform_open_api_router() -> Router<AppState> {
                 open_api_token_middleware,
             )),
         )
+        .route(
+            "/open/save/upload",
+            post(open_save_upload)
+                .layer(axum::extract::DefaultBodyLimit::disable())
+                .route_layer(axum::middleware::from_fn_with_state(
+                    profile_read_policy.clone(),
+                    open_api_token_middleware,
+                )),
+        )
         .route(
             "/open/image/bn",
             post(open_image_bn).route_layer(axum::middleware::from_fn_with_state(
@@ -61,6 +73,13 @@ pub fn create_open_platform_open_api_router() -> Router<AppState> {
                 open_api_token_middleware,
             )),
         )
+        .route(
+            "/open/image/leaderboard",
+            get(open_image_leaderboard).route_layer(axum::middleware::from_fn_with_state(
+                public_read_policy.clone(),
+                open_api_token_middleware,
+            )),
+        )
         .route(
             "/open/songs/search",
             get(open_search_songs).route_layer(axum::middleware::from_fn_with_state(
@@ -78,6 +97,34 @@ pub fn create_open_platform_open_api_router() -> Router<AppState> {
         .route(
             "/open/leaderboard/rks/by-rank",
             get(open_get_leaderboard_by_rank).route_layer(axum::middleware::from_fn_with_state(
+                public_read_policy.clone(),
+                open_api_token_middleware,
+            )),
+        )
+        .route(
+            "/open/leaderboard/chart/top",
+            get(open_get_chart_leaderboard_top).route_layer(axum::middleware::from_fn_with_state(
+                public_read_policy.clone(),
+                open_api_token_middleware,
+            )),
+        )
+        .route(
+            "/open/leaderboard/rks/gainers",
+            get(open_get_rks_gainers).route_layer(axum::middleware::from_fn_with_state(
+                public_read_policy.clone(),
+                open_api_token_middleware,
+            )),
+        )
+        .route(
+            "/open/leaderboard/rks/rank-history",
+            get(open_get_rank_history).route_layer(axum::middleware::from_fn_with_state(
+                public_read_policy.clone(),
+                open_api_token_middleware,
+            )),
+        )
+        .route(
+            "/open/leaderboard/rks/distribution",
+            get(open_get_rks_distribution).route_layer(axum::middleware::from_fn_with_state(
                 public_read_policy,
                 open_api_token_middleware,
             )),
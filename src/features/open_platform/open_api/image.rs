@@ -102,3 +102,55 @@ pub async fn open_image_song(
     let resp = crate::image_api::render_song(State(state), Query(svg_only_query), req).await?;
     Ok(resp.into_response())
 }
+
+#[utoipa::path(
+    get,
+    path = "/open/image/leaderboard",
+    summary = "Open API: Render Leaderboard Image (SVG Only)",
+    description = "Open platform endpoint for public RKS leaderboard image rendering. Requires X-OpenApi-Token and scope public.read. Only format=svg is allowed.",
+    security(
+        ("OpenApiToken" = [])
+    ),
+    params(
+        ("limit" = Option<i64>, Query, description = "Number of rows, default 20, max 50."),
+        ("rank" = Option<i64>, Query, description = "Center the window on this rank (1-based). Omit for top N."),
+        ("format" = Option<String>, Query, description = "Only supports svg. Omit or pass svg.")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Request succeeded.",
+            content((String = "image/svg+xml"))
+        ),
+        (
+            status = 401,
+            description = "Token is missing, invalid, revoked or expired.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "Scope is insufficient or request is rate limited.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "Validation failed (only format=svg is allowed).",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "OpenPlatformOpenApi"
+)]
+pub async fn open_image_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<crate::image_api::ImageQueryOpts>,
+    Query(lb_query): Query<crate::image_api::LeaderboardImageQuery>,
+) -> Result<Response, AppError> {
+    let svg_only_query = query.into_open_svg_only()?;
+    let resp =
+        crate::image_api::render_leaderboard(State(state), Query(svg_only_query), Query(lb_query))
+            .await?;
+    Ok(resp.into_response())
+}
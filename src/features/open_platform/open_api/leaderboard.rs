@@ -1,15 +1,24 @@
 use axum::{
     Json,
-    extract::{Query, State},
+    extract::{Path, Query, State},
 };
+use serde::Deserialize;
 
 use crate::{error::AppError, state::AppState};
 
+#[derive(Deserialize)]
+pub struct OpenRankHistoryQuery {
+    /// 公开别名
+    pub alias: String,
+    /// 回溯天数，默认30，最大366
+    pub days: Option<i64>,
+}
+
 #[utoipa::path(
     get,
     path = "/open/leaderboard/rks/top",
     summary = "Open API: Leaderboard Top",
-    description = "Open platform endpoint for public RKS top list. Requires X-OpenApi-Token and scope public.read.",
+    description = "Open platform endpoint for public RKS top list. Accepts the same query as /leaderboard/rks/top, including region=all|cn|global|unspecified. Requires X-OpenApi-Token and scope public.read.",
     security(
         ("OpenApiToken" = [])
     ),
@@ -41,7 +50,7 @@ pub async fn open_get_leaderboard_top(
     get,
     path = "/open/leaderboard/rks/by-rank",
     summary = "Open API: Leaderboard Range",
-    description = "Open platform endpoint for public RKS rank range query. Requires X-OpenApi-Token and scope public.read.",
+    description = "Open platform endpoint for public RKS rank range query. Accepts region=all|cn|global|unspecified. Requires X-OpenApi-Token and scope public.read.",
     security(
         ("OpenApiToken" = [])
     ),
@@ -68,3 +77,163 @@ pub async fn open_get_leaderboard_by_rank(
 ) -> Result<Json<crate::leaderboard_api::LeaderboardTopResponse>, AppError> {
     crate::leaderboard_api::get_by_rank(State(state), Query(query)).await
 }
+
+#[utoipa::path(
+    get,
+    path = "/open/leaderboard/chart/top",
+    summary = "Open API: Chart Leaderboard Top",
+    description = "Open platform endpoint for the public per-chart score leaderboard (song + difficulty). Requires X-OpenApi-Token and scope public.read.",
+    security(
+        ("OpenApiToken" = [])
+    ),
+    params(
+        ("song" = String, Query, description = "Song id, name or alias (must match uniquely)"),
+        ("difficulty" = String, Query, description = "EZ/HD/IN/AT"),
+        ("limit" = Option<i64>, Query, description = "Page size, default 50, max 200"),
+        ("offset" = Option<i64>, Query, description = "Offset"),
+        ("cursor" = Option<String>, Query, description = "Encrypted cursor from nextCursor; only valid for the same chart")
+    ),
+    responses(
+        (status = 200, description = "Request succeeded.", body = crate::leaderboard_api::ChartLeaderboardResponse),
+        (
+            status = 401,
+            description = "Token is missing, invalid, revoked or expired.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "Scope is insufficient or request is rate limited.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "OpenPlatformOpenApi"
+)]
+pub async fn open_get_chart_leaderboard_top(
+    State(state): State<AppState>,
+    Query(query): Query<crate::leaderboard_api::ChartTopQuery>,
+) -> Result<Json<crate::leaderboard_api::ChartLeaderboardResponse>, AppError> {
+    crate::leaderboard_api::get_chart_top(State(state), Query(query)).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/open/leaderboard/rks/gainers",
+    summary = "Open API: RKS Gainers",
+    description = "Open platform endpoint for the public RKS gainers board over the last 7 or 30 days. Requires X-OpenApi-Token and scope public.read.",
+    security(
+        ("OpenApiToken" = [])
+    ),
+    params(
+        ("window" = Option<String>, Query, description = "7d (default) or 30d"),
+        ("limit" = Option<i64>, Query, description = "Page size, default 50, max 200"),
+        ("offset" = Option<i64>, Query, description = "Offset")
+    ),
+    responses(
+        (status = 200, description = "Request succeeded.", body = crate::leaderboard_api::RksGainersResponse),
+        (
+            status = 401,
+            description = "Token is missing, invalid, revoked or expired.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "Scope is insufficient or request is rate limited.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "OpenPlatformOpenApi"
+)]
+pub async fn open_get_rks_gainers(
+    State(state): State<AppState>,
+    Query(query): Query<crate::leaderboard_api::GainersQuery>,
+) -> Result<Json<crate::leaderboard_api::RksGainersResponse>, AppError> {
+    crate::leaderboard_api::get_rks_gainers(State(state), Query(query)).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/open/leaderboard/rks/rank-history",
+    summary = "Open API: Rank History",
+    description = "Open platform endpoint for the daily rank trajectory of a public player, looked up by alias. Requires X-OpenApi-Token and scope public.read.",
+    security(
+        ("OpenApiToken" = [])
+    ),
+    params(
+        ("alias" = String, Query, description = "Public alias"),
+        ("days" = Option<i64>, Query, description = "Days to look back, default 30, max 366")
+    ),
+    responses(
+        (status = 200, description = "Request succeeded.", body = crate::leaderboard_api::RankHistoryResponse),
+        (
+            status = 401,
+            description = "Token is missing, invalid, revoked or expired.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "Scope is insufficient or request is rate limited.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "Alias not found or profile is not public.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "OpenPlatformOpenApi"
+)]
+pub async fn open_get_rank_history(
+    State(state): State<AppState>,
+    Query(query): Query<OpenRankHistoryQuery>,
+) -> Result<Json<crate::leaderboard_api::RankHistoryResponse>, AppError> {
+    crate::leaderboard_api::get_public_rank_history(
+        State(state),
+        Path(query.alias),
+        Query(crate::leaderboard_api::RankHistoryQuery { days: query.days }),
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/open/leaderboard/rks/distribution",
+    summary = "Open API: RKS Distribution",
+    description = "Open platform endpoint for the public RKS histogram (cached snapshot). Pass score to get its rank and percentile. Requires X-OpenApi-Token and scope public.read.",
+    security(
+        ("OpenApiToken" = [])
+    ),
+    params(
+        ("bucket_width" = Option<f64>, Query, description = "Bucket width (0.01 to 5.0), default 0.5"),
+        ("score" = Option<f64>, Query, description = "Score to locate in the distribution"),
+        ("region" = Option<String>, Query, description = "Server region: all (default, combined) / cn / global / unspecified")
+    ),
+    responses(
+        (status = 200, description = "Request succeeded.", body = crate::leaderboard_api::RksDistributionResponse),
+        (
+            status = 401,
+            description = "Token is missing, invalid, revoked or expired.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "Scope is insufficient or request is rate limited.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "OpenPlatformOpenApi"
+)]
+pub async fn open_get_rks_distribution(
+    State(state): State<AppState>,
+    Query(query): Query<crate::leaderboard_api::DistributionQuery>,
+) -> Result<Json<crate::leaderboard_api::RksDistributionResponse>, AppError> {
+    crate::leaderboard_api::get_rks_distribution(State(state), Query(query), None).await
+}
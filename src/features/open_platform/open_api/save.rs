@@ -41,3 +41,45 @@ pub async fn open_save_data(
 ) -> Result<Response, AppError> {
     crate::save_api::get_save_data(State(state), Query(params), req).await
 }
+
+#[utoipa::path(
+    post,
+    path = "/open/save/upload",
+    summary = "Open API: Parse Uploaded Save Zip",
+    description = "Open platform endpoint for offline save ingestion: upload the encrypted cloud-save zip (multipart field `file` or application/octet-stream body). Requires X-OpenApi-Token and scope profile.read.",
+    security(
+        ("OpenApiToken" = [])
+    ),
+    params(
+        ("calculate_rks" = Option<bool>, Query, description = "Set true to include RKS calculation result.")
+    ),
+    request_body(
+        content(
+            (crate::save_contract::SaveUploadForm = "multipart/form-data"),
+            (crate::save_contract::BinarySaveBlob = "application/octet-stream")
+        )
+    ),
+    responses(
+        (status = 200, description = "Request succeeded."),
+        (
+            status = 401,
+            description = "Token is missing, invalid, revoked or expired.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 403,
+            description = "Scope is insufficient or request is rate limited.",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "OpenPlatformOpenApi"
+)]
+pub async fn open_save_upload(
+    State(state): State<AppState>,
+    Query(params): Query<std::collections::BTreeMap<String, String>>,
+    req: Request,
+) -> Result<Response, AppError> {
+    crate::save_api::post_save_upload(State(state), Query(params), req).await
+}
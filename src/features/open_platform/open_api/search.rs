@@ -13,6 +13,14 @@ use crate::{error::AppError, state::AppState};
     security(
         ("OpenApiToken" = [])
     ),
+    params(
+        ("q" = String, Query, description = "Search keyword."),
+        ("unique" = Option<bool>, Query, description = "Expect a unique match (404 when none, 409 when ambiguous)."),
+        ("mode" = Option<String>, Query, description = "Multi-keyword mode: and/or."),
+        ("limit" = Option<u32>, Query, description = "Page size (default 20, max 100)."),
+        ("offset" = Option<u32>, Query, description = "Page offset (default 0)."),
+        ("pinyin" = Option<bool>, Query, description = "Set true to also match full pinyin / initials (e.g. `ywzj`) and kana romaji.")
+    ),
     responses(
         (status = 200, description = "Request succeeded."),
         (
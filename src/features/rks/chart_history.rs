@@ -0,0 +1,138 @@
+//! 逐谱面成绩历史与最近个人最佳
+//!
+//! 数据由存档提交时的排行榜写入链路追加（只在分数/ACC/FC 变化时记录），这里只负责请求/响应
+//! 结构与存储记录的转换。
+
+use serde::{Deserialize, Serialize};
+
+use crate::song_contract::SongCatalog;
+use crate::stats_contract::ChartScoreHistoryEntry;
+
+/// 单谱面成绩历史查询请求
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+    "song": "Glaciaxion",
+    "difficulty": "IN",
+    "limit": 50
+}))]
+pub struct ChartHistoryRequest {
+    /// 认证信息
+    pub auth: crate::auth_contract::UnifiedSaveRequest,
+    /// 歌曲 ID 或名称/别名（需唯一命中）
+    pub song: String,
+    /// 难度：EZ/HD/IN/AT（大小写不敏感）
+    pub difficulty: String,
+    /// 返回数量（默认 50，最大 200）
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// 游标分页位置（上一页响应的 nextCursor）
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// 最近个人最佳查询请求
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+    "limit": 20
+}))]
+pub struct RecentPbRequest {
+    /// 认证信息
+    pub auth: crate::auth_contract::UnifiedSaveRequest,
+    /// 返回数量（默认 50，最大 200）
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// 游标分页位置（上一页响应的 nextCursor）
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// 单条谱面成绩变化
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartScoreItem {
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    /// 难度（EZ/HD/IN/AT）
+    pub difficulty: String,
+    pub score: i64,
+    /// ACC 百分比
+    pub acc: f64,
+    pub is_full_combo: bool,
+    /// 变化前的分数；首次记录时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_score: Option<i64>,
+    /// 变化前的 ACC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_acc: Option<f64>,
+    /// 变化前是否 FC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_full_combo: Option<bool>,
+    /// 是否刷新个人最佳（分数/ACC 提升或新达成 FC；首次提交存档时建立的基线不计）
+    pub is_pb: bool,
+    /// 是否在本次达成 AP（ACC 100%）
+    pub is_new_ap: bool,
+    /// 记录时间（UTC RFC3339）
+    pub created_at: String,
+}
+
+/// 谱面成绩历史响应
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartHistoryResponse {
+    pub song_id: String,
+    pub song_name: String,
+    pub difficulty: String,
+    /// 成绩变化记录（按时间倒序）
+    pub items: Vec<ChartScoreItem>,
+    /// 是否还有下一页
+    pub has_more: bool,
+    /// 下一页游标；为空表示已到末尾
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// 最近个人最佳响应
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentPbResponse {
+    /// 个人最佳记录（按时间倒序）
+    pub items: Vec<ChartScoreItem>,
+    /// 是否还有下一页
+    pub has_more: bool,
+    /// 下一页游标；为空表示已到末尾
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+const AP_ACC: f64 = 100.0 - 1e-6;
+
+pub(crate) fn chart_score_item(
+    entry: ChartScoreHistoryEntry,
+    catalog: &SongCatalog,
+) -> ChartScoreItem {
+    let is_new_ap = entry.acc >= AP_ACC && entry.prev_acc.is_none_or(|p| p < AP_ACC);
+    ChartScoreItem {
+        song_name: catalog.by_id.get(&entry.song_id).map(|s| s.name.clone()),
+        song_id: entry.song_id,
+        difficulty: entry.difficulty,
+        score: entry.score,
+        acc: entry.acc,
+        is_full_combo: entry.is_fc,
+        prev_score: entry.prev_score,
+        prev_acc: entry.prev_acc,
+        prev_full_combo: entry.prev_fc,
+        is_pb: entry.is_pb,
+        is_new_ap: is_new_ap && entry.is_pb,
+        created_at: entry.created_at,
+    }
+}
+
+/// `createdAt|id` 游标
+pub(crate) fn encode_cursor(entry: Option<&ChartScoreHistoryEntry>) -> Option<String> {
+    entry.map(|e| format!("{}|{}", e.created_at, e.id))
+}
@@ -0,0 +1,138 @@
+//! 谱面 ACC 快照
+//!
+//! 提交存档时把全部谱面的 ACC 以紧凑 JSON 写入 `save_submissions.details_json`，
+//! 使服务端可以在不重新拉取存档的情况下按任意定数表/规则重算玩家 RKS。
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::save_contract::{Difficulty, DifficultyRecord};
+
+/// 单个谱面的 ACC（字段名压缩以减小存储体积）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredChartAcc {
+    #[serde(rename = "s")]
+    pub song_id: String,
+    #[serde(rename = "d")]
+    pub difficulty: Difficulty,
+    /// ACC 百分比
+    #[serde(rename = "a")]
+    pub acc: f32,
+}
+
+/// 一次提交的谱面 ACC 快照
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartAccSnapshot {
+    #[serde(rename = "v")]
+    pub version: u8,
+    pub charts: Vec<StoredChartAcc>,
+}
+
+impl ChartAccSnapshot {
+    pub const VERSION: u8 = 1;
+
+    /// 从存档成绩构建；按曲目 ID 与难度排序，ACC 为 0 的谱面不记录。
+    #[must_use]
+    pub fn from_game_record<S>(records: &HashMap<String, Vec<DifficultyRecord>, S>) -> Self
+    where
+        S: std::hash::BuildHasher,
+    {
+        let mut charts: Vec<StoredChartAcc> = records
+            .iter()
+            .flat_map(|(song_id, diffs)| {
+                diffs
+                    .iter()
+                    .filter(|r| r.accuracy > 0.0)
+                    .map(|r| StoredChartAcc {
+                        song_id: song_id.clone(),
+                        difficulty: r.difficulty,
+                        acc: r.accuracy,
+                    })
+            })
+            .collect();
+        charts.sort_by(|a, b| {
+            a.song_id
+                .cmp(&b.song_id)
+                .then_with(|| (a.difficulty as u8).cmp(&(b.difficulty as u8)))
+        });
+        Self {
+            version: Self::VERSION,
+            charts,
+        }
+    }
+
+    /// 还原为 RKS 计算所需的存档成绩（仅含 ACC，定数由计算时的定数表决定）。
+    #[must_use]
+    pub fn to_game_record(&self) -> HashMap<String, Vec<DifficultyRecord>> {
+        let mut out: HashMap<String, Vec<DifficultyRecord>> = HashMap::new();
+        for c in &self.charts {
+            out.entry(c.song_id.clone())
+                .or_default()
+                .push(DifficultyRecord {
+                    difficulty: c.difficulty,
+                    score: 0,
+                    accuracy: c.acc,
+                    is_full_combo: false,
+                    chart_constant: None,
+                    push_acc: None,
+                    push_acc_hint: None,
+                });
+        }
+        out
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string(self).ok()
+    }
+
+    /// 解析 `details_json`；格式不符或版本不支持时返回 None。
+    #[must_use]
+    pub fn from_json(raw: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(raw)
+            .ok()
+            .filter(|s| s.version == Self::VERSION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(difficulty: Difficulty, accuracy: f32) -> DifficultyRecord {
+        DifficultyRecord {
+            difficulty,
+            score: 900_000,
+            accuracy,
+            is_full_combo: false,
+            chart_constant: Some(10.0),
+            push_acc: None,
+            push_acc_hint: None,
+        }
+    }
+
+    #[test]
+    fn snapshot_roundtrips_through_json_and_skips_unplayed() {
+        let mut records = HashMap::new();
+        records.insert(
+            "b".to_string(),
+            vec![rec(Difficulty::IN, 98.5), rec(Difficulty::EZ, 0.0)],
+        );
+        records.insert("a".to_string(), vec![rec(Difficulty::AT, 100.0)]);
+
+        let snap = ChartAccSnapshot::from_game_record(&records);
+        assert_eq!(snap.charts.len(), 2);
+        assert_eq!(snap.charts[0].song_id, "a");
+
+        let json = snap.to_json().unwrap();
+        assert!(json.contains("\"s\":\"a\""));
+        let back = ChartAccSnapshot::from_json(&json).unwrap();
+        assert_eq!(back, snap);
+
+        let record = back.to_game_record();
+        assert_eq!(record["b"].len(), 1);
+        assert!((record["b"][0].accuracy - 98.5).abs() < f32::EPSILON);
+        assert!(ChartAccSnapshot::from_json("{\"v\":9,\"charts\":[]}").is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! 定数表差异报告与排行榜影响评估
+//!
+//! 差异来自 [`crate::constant_tables::diff_constants`]；影响评估基于玩家最近一次提交的
+//! 谱面 ACC 快照（[`super::chart_snapshot::ChartAccSnapshot`]），按两张定数表各算一次
+//! 当前规则下的总 RKS 并统计变化。
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constant_tables::{ConstantChange, ConstantTableInfo};
+use crate::save_contract::Difficulty;
+use crate::song_contract::SongCatalog;
+use crate::startup::chart_loader::ChartConstantsMap;
+
+use super::chart_snapshot::ChartAccSnapshot;
+use super::engine::calculate_player_rks;
+
+/// 影响评估视为“未变化”的精确 RKS 阈值
+const RKS_MOVE_EPS: f64 = 1e-9;
+
+/// 线上定数表概要
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentConstantsInfo {
+    /// 线上数据版本（进程内，每次热更新递增）
+    pub data_version: u64,
+    /// 含定数的曲目数
+    pub songs: usize,
+    /// 加载时间（RFC3339）
+    pub loaded_at: String,
+}
+
+/// 可用定数表列表
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksConstantsResponse {
+    /// 线上定数表（constantsVersion=current）
+    pub current: CurrentConstantsInfo,
+    /// 归档定数表（按版本标识排序）
+    pub tables: Vec<ConstantTableInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConstantsDiffQuery {
+    /// 基准定数表版本（默认 current）
+    pub from: Option<String>,
+    /// 对比定数表版本（默认 current）
+    pub to: Option<String>,
+    /// 是否评估排行榜影响（默认 true）
+    pub impact: Option<bool>,
+}
+
+/// 单个谱面的定数变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstantChangeItem {
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    /// 难度（EZ/HD/IN/AT）
+    pub difficulty: String,
+    /// 基准表中的定数；为空表示新增谱面
+    pub from: Option<f32>,
+    /// 对比表中的定数；为空表示移除谱面
+    pub to: Option<f32>,
+    /// 定数变化量（新增/移除时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<f32>,
+}
+
+/// 排行榜影响统计（按当前规则）
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardImpact {
+    /// 排行榜中的用户数
+    pub scanned_users: u64,
+    /// 没有谱面快照（提交早于快照功能）的用户数，未参与评估
+    pub users_without_snapshot: u64,
+    /// 精确 RKS 会变化的用户数
+    pub affected_users: u64,
+    /// 显示 RKS（两位小数）会变化的用户数
+    pub display_changed_users: u64,
+    /// RKS 上升的用户数
+    pub increased_users: u64,
+    /// RKS 下降的用户数
+    pub decreased_users: u64,
+    /// 受影响用户的平均变化量（精确值）
+    pub mean_delta: f64,
+    /// 最大上升量
+    pub max_increase: f64,
+    /// 最大下降量（负数）
+    pub max_decrease: f64,
+}
+
+/// 定数表差异报告
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstantsDiffResponse {
+    pub from: String,
+    pub to: String,
+    /// 定数有变化的歌曲数
+    pub changed_songs: usize,
+    /// 按曲目 ID 与难度排序的变化列表
+    pub changes: Vec<ConstantChangeItem>,
+    /// 排行榜影响（impact=false 或统计存储未启用时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact: Option<LeaderboardImpact>,
+}
+
+fn parse_difficulty(code: &str) -> Option<Difficulty> {
+    match code {
+        "EZ" => Some(Difficulty::EZ),
+        "HD" => Some(Difficulty::HD),
+        "IN" => Some(Difficulty::IN),
+        "AT" => Some(Difficulty::AT),
+        _ => None,
+    }
+}
+
+pub(crate) fn build_change_items(
+    changes: &[ConstantChange],
+    catalog: &SongCatalog,
+) -> (Vec<ConstantChangeItem>, usize) {
+    let items: Vec<ConstantChangeItem> = changes
+        .iter()
+        .map(|c| ConstantChangeItem {
+            song_id: c.song_id.clone(),
+            song_name: catalog.by_id.get(&c.song_id).map(|s| s.name.clone()),
+            difficulty: c.difficulty.clone(),
+            from: c.from,
+            to: c.to,
+            delta: c.from.zip(c.to).map(|(a, b)| b - a),
+        })
+        .collect();
+    let songs = changes
+        .iter()
+        .map(|c| c.song_id.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+    (items, songs)
+}
+
+/// 逐个用户累加影响统计
+pub(crate) struct ImpactAccumulator {
+    from: Arc<ChartConstantsMap>,
+    to: Arc<ChartConstantsMap>,
+    changed: HashSet<(String, Difficulty)>,
+    delta_sum: f64,
+    impact: LeaderboardImpact,
+}
+
+impl ImpactAccumulator {
+    pub(crate) fn new(
+        from: Arc<ChartConstantsMap>,
+        to: Arc<ChartConstantsMap>,
+        changes: &[ConstantChange],
+    ) -> Self {
+        let changed = changes
+            .iter()
+            .filter_map(|c| Some((c.song_id.clone(), parse_difficulty(&c.difficulty)?)))
+            .collect();
+        Self {
+            from,
+            to,
+            changed,
+            delta_sum: 0.0,
+            impact: LeaderboardImpact::default(),
+        }
+    }
+
+    /// 记录一个用户；`snapshot` 为空表示该用户没有谱面快照。
+    pub(crate) fn add(&mut self, snapshot: Option<&ChartAccSnapshot>) {
+        self.impact.scanned_users += 1;
+        let Some(snapshot) = snapshot else {
+            self.impact.users_without_snapshot += 1;
+            return;
+        };
+        // 没有玩过任何变化谱面的用户 RKS 不可能变化，跳过重算
+        if !snapshot
+            .charts
+            .iter()
+            .any(|c| self.changed.contains(&(c.song_id.clone(), c.difficulty)))
+        {
+            return;
+        }
+        let record = snapshot.to_game_record();
+        let before = calculate_player_rks(&record, &self.from).total_rks;
+        let after = calculate_player_rks(&record, &self.to).total_rks;
+        let delta = after - before;
+        if delta.abs() < RKS_MOVE_EPS {
+            return;
+        }
+        let display = |v: f64| (v * 100.0).round();
+        let impact = &mut self.impact;
+        impact.affected_users += 1;
+        if display(before).total_cmp(&display(after)).is_ne() {
+            impact.display_changed_users += 1;
+        }
+        if delta > 0.0 {
+            impact.increased_users += 1;
+            impact.max_increase = impact.max_increase.max(delta);
+        } else {
+            impact.decreased_users += 1;
+            impact.max_decrease = impact.max_decrease.min(delta);
+        }
+        self.delta_sum += delta;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn finish(mut self) -> LeaderboardImpact {
+        if self.impact.affected_users > 0 {
+            self.impact.mean_delta = self.delta_sum / self.impact.affected_users as f64;
+        }
+        self.impact
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant_tables::diff_constants;
+    use crate::save_contract::DifficultyRecord;
+    use crate::startup::chart_loader::ChartConstants;
+    use std::collections::HashMap;
+
+    fn consts(levels: &[(&str, f32)]) -> ChartConstantsMap {
+        levels
+            .iter()
+            .map(|(id, lv)| {
+                (
+                    (*id).to_string(),
+                    ChartConstants {
+                        ez: None,
+                        hd: None,
+                        in_level: Some(*lv),
+                        at: None,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn snapshot(songs: &[&str]) -> ChartAccSnapshot {
+        let records: HashMap<String, Vec<DifficultyRecord>> = songs
+            .iter()
+            .map(|id| {
+                (
+                    (*id).to_string(),
+                    vec![DifficultyRecord {
+                        difficulty: Difficulty::IN,
+                        score: 990_000,
+                        accuracy: 99.0,
+                        is_full_combo: false,
+                        chart_constant: None,
+                        push_acc: None,
+                        push_acc_hint: None,
+                    }],
+                )
+            })
+            .collect();
+        ChartAccSnapshot::from_game_record(&records)
+    }
+
+    #[test]
+    fn impact_counts_only_players_of_changed_charts() {
+        let from = consts(&[("a", 12.0), ("b", 13.0)]);
+        let to = consts(&[("a", 12.5), ("b", 13.0)]);
+        let changes = diff_constants(&from, &to);
+        assert_eq!(changes.len(), 1);
+
+        let mut acc = ImpactAccumulator::new(Arc::new(from), Arc::new(to), &changes);
+        acc.add(Some(&snapshot(&["a", "b"])));
+        acc.add(Some(&snapshot(&["b"])));
+        acc.add(None);
+        let impact = acc.finish();
+
+        assert_eq!(impact.scanned_users, 3);
+        assert_eq!(impact.users_without_snapshot, 1);
+        assert_eq!(impact.affected_users, 1);
+        assert_eq!(impact.increased_users, 1);
+        assert_eq!(impact.decreased_users, 0);
+        assert!(impact.max_increase > 0.0);
+        assert!((impact.mean_delta - impact.max_increase).abs() < 1e-12);
+    }
+}
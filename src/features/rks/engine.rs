@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use crate::save_contract::{Difficulty, DifficultyRecord};
 use crate::startup::chart_loader::{ChartConstants, ChartConstantsMap};
+
+use super::rules::{RksRuleSet, RksRuleVersion};
 use serde::{Deserialize, Serialize};
 
 /// 单张谱面的 RKS 结果
@@ -40,7 +42,7 @@ pub fn calculate_single_chart_rks(accuracy: f64, chart_constant: f32) -> f64 {
 #[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerRksResult {
-    /// 玩家总 RKS （Best27 + AP3）/ 30
+    /// 玩家总 RKS（当前规则：(Best27 + AP3) / 30；其他规则见 `RksRuleVersion`）
     #[schema(example = 14.56)]
     pub total_rks: f64,
     pub b30_charts: Vec<ChartRankingScore>,
@@ -55,55 +57,100 @@ pub fn calculate_player_rks<S>(
 where
     S: std::hash::BuildHasher,
 {
-    const TOP_GENERAL: usize = 27;
-    const TOP_PHI: usize = 3;
-    let mut best27 = TopKChartScores::new(TOP_GENERAL);
-    let mut ap3 = TopKChartScores::new(TOP_PHI);
-
-    // 用于在 rks 相等时模拟稳定排序：先遍历到的优先。
-    let mut scan_index: u64 = 0;
+    calculate_player_rks_with_rule(records, chart_constants, RksRuleVersion::default().rule())
+}
 
+/// 按指定规则计算总 RKS；`b30_charts` 依次为 Best 部分与 Phi 部分（按规则的数量）。
+#[must_use]
+pub fn calculate_player_rks_with_rule<S>(
+    records: &HashMap<String, Vec<DifficultyRecord>, S>,
+    chart_constants: &ChartConstantsMap,
+    rule: &RksRuleSet,
+) -> PlayerRksResult
+where
+    S: std::hash::BuildHasher,
+{
     // records 使用 HashMap：遍历顺序不稳定，可能导致 tie-break 与浮点求和出现极小抖动。
     // 这里按 song_id 排序遍历，保证同一份存档重复计算结果稳定可复现。
     let mut song_ids: Vec<&String> = records.keys().collect();
     song_ids.sort();
 
-    for song_id in song_ids {
-        let Some(diffs) = records.get(song_id) else {
-            continue;
-        };
-        for rec in diffs {
-            scan_index = scan_index.saturating_add(1);
-
-            let Some(consts) = chart_constants.get(song_id) else {
-                continue;
-            };
-            let Some(level) = level_for_difficulty(consts, &rec.difficulty) else {
+    // 依次回调 (song_id, difficulty, rks, 是否满 ACC, scan_index)；
+    // scan_index 用于在 rks 相等时模拟稳定排序：先遍历到的优先。
+    let for_each_scored = |f: &mut dyn FnMut(&String, Difficulty, f64, bool, u64)| {
+        let mut scan_index: u64 = 0;
+        for song_id in &song_ids {
+            let Some(diffs) = records.get(*song_id) else {
                 continue;
             };
+            for rec in diffs {
+                scan_index = scan_index.saturating_add(1);
 
-            let (acc_percent, acc_decimal) = normalize_accuracy(rec.accuracy);
-            let rks_value = calculate_single_chart_rks(acc_decimal, level);
+                let Some(consts) = chart_constants.get(*song_id) else {
+                    continue;
+                };
+                let Some(level) = level_for_difficulty(consts, &rec.difficulty) else {
+                    continue;
+                };
 
-            best27.consider(rks_value, scan_index, || ChartRankingScore {
+                let (acc_percent, acc_decimal) = normalize_accuracy(rec.accuracy);
+                let rks_value = (rule.chart_rks)(acc_decimal, level);
+                f(
+                    song_id,
+                    rec.difficulty,
+                    rks_value,
+                    acc_percent >= 100.0,
+                    scan_index,
+                );
+            }
+        }
+    };
+
+    let mut best = TopKChartScores::new(rule.best_count);
+    let mut phi = TopKChartScores::new(rule.phi_count);
+    for_each_scored(&mut |song_id, difficulty, rks_value, is_phi, scan_index| {
+        let build = || ChartRankingScore {
+            song_id: song_id.clone(),
+            difficulty,
+            rks: rks_value,
+        };
+        if rule.overlap {
+            best.consider(rks_value, scan_index, build);
+        }
+        if is_phi {
+            phi.consider(rks_value, scan_index, build);
+        }
+    });
+
+    if !rule.overlap {
+        // 不允许重叠：先定 Phi 部分，Best 部分从其余谱面中选取。
+        let picked_phi: Vec<(String, u8)> = phi
+            .items
+            .iter()
+            .map(|c| {
+                (
+                    c.score.song_id.clone(),
+                    key_of_difficulty(&c.score.difficulty),
+                )
+            })
+            .collect();
+        for_each_scored(&mut |song_id, difficulty, rks_value, _is_phi, scan_index| {
+            let key = key_of_difficulty(&difficulty);
+            if picked_phi.iter().any(|(s, k)| s == song_id && *k == key) {
+                return;
+            }
+            best.consider(rks_value, scan_index, || ChartRankingScore {
                 song_id: song_id.clone(),
-                difficulty: rec.difficulty,
+                difficulty,
                 rks: rks_value,
             });
-            if acc_percent >= 100.0 {
-                ap3.consider(rks_value, scan_index, || ChartRankingScore {
-                    song_id: song_id.clone(),
-                    difficulty: rec.difficulty,
-                    rks: rks_value,
-                });
-            }
-        }
+        });
     }
 
-    // 简化口径：Best27 + AP Top3（允许重叠），不足 30 仍以 30 为分母（缺口视为 0）。
-    let total_rks = (best27.sum() + ap3.sum()) / 30.0;
-    let mut picked: Vec<ChartRankingScore> = best27.into_sorted_scores();
-    picked.extend(ap3.into_sorted_scores());
+    // 不足 N 个时仍以规则分母计算（缺口视为 0）。
+    let total_rks = (best.sum() + phi.sum()) / rule.divisor();
+    let mut picked: Vec<ChartRankingScore> = best.into_sorted_scores();
+    picked.extend(phi.into_sorted_scores());
 
     PlayerRksResult {
         total_rks,
@@ -112,7 +159,7 @@ where
 }
 
 #[allow(clippy::trivially_copy_pass_by_ref)]
-fn level_for_difficulty(consts: &ChartConstants, diff: &Difficulty) -> Option<f32> {
+pub(crate) fn level_for_difficulty(consts: &ChartConstants, diff: &Difficulty) -> Option<f32> {
     match diff {
         Difficulty::EZ => consts.ez,
         Difficulty::HD => consts.hd,
@@ -315,7 +362,7 @@ impl PushAccHint {
     }
 }
 
-fn target_rks_threshold_from_exact(current_exact_rks: f64) -> f64 {
+pub(crate) fn target_rks_threshold_from_exact(current_exact_rks: f64) -> f64 {
     // 目标阈值（取决于第三位小数是否 >= 5）
     // 约定：目标是让「四舍五入到两位的显示 RKS」提升 0.01。
     let third_decimal_ge_5 = (current_exact_rks * 1000.0) % 10.0 >= 5.0;
@@ -349,8 +396,12 @@ impl<'a> PushAccBatchSolver<'a> {
     #[must_use]
     pub fn new(records: &'a [RksRecord]) -> Self {
         let (current_exact_rks, _rounded) = calculate_player_rks_details(records);
-        let target_rks_threshold = target_rks_threshold_from_exact(current_exact_rks);
+        Self::with_threshold(records, target_rks_threshold_from_exact(current_exact_rks))
+    }
 
+    /// 以自定义的精确 RKS 阈值构建（用于“达到目标 RKS”而非“显示 RKS +0.01”的场景）。
+    #[must_use]
+    pub fn with_threshold(records: &'a [RksRecord], target_rks_threshold: f64) -> Self {
         let total_rks_sum: f64 = records.iter().map(|r| r.rks).sum();
         let sum_first_27: f64 = records.iter().take(27).map(|r| r.rks).sum();
         let sum_first_28: f64 = records.iter().take(28).map(|r| r.rks).sum();
@@ -1,15 +1,39 @@
-//! RKS 历史查询 API 处理模块
+//! RKS 历史查询 / 假设模拟 / 推分规划 API 处理模块
 
-use axum::{Router, extract::State, response::Json, routing::post};
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{get, post},
+};
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
 use crate::{
+    constant_tables::CURRENT_VERSION,
     error::AppError,
     features::stats::storage::{RksHistoryCursor, RksHistoryEntry},
+    save_contract,
     state::AppState,
 };
 
+use super::chart_history::{
+    ChartHistoryRequest, ChartHistoryResponse, RecentPbRequest, RecentPbResponse, chart_score_item,
+    encode_cursor as encode_chart_cursor,
+};
+use super::chart_snapshot::ChartAccSnapshot;
+use super::constants_diff::{
+    ConstantsDiffQuery, ConstantsDiffResponse, CurrentConstantsInfo, ImpactAccumulator,
+    RksConstantsResponse, build_change_items,
+};
+use super::planner::{RksPlanRequest, RksPlanResponse, fill_plan_song_names, plan_target_rks};
+use super::simulate::{
+    MAX_SIMULATE_RECORDS, ResolvedChart, RksSimulateRequest, RksSimulateResponse,
+    SimulateChartInput, charts_from_game_record, fill_song_names, parse_difficulty,
+    rebase_constants, resolve_chart_inputs, simulate_rks, validate_simulate_request,
+};
+
 fn parse_rks_history_cursor(raw: Option<&str>) -> Result<Option<RksHistoryCursor>, AppError> {
     let Some(raw) = raw.map(str::trim).filter(|s| !s.is_empty()) else {
         return Ok(None);
@@ -222,9 +246,610 @@ pub async fn post_rks_history(
     }))
 }
 
+/// RKS 假设模拟（What-if）
+#[utoipa::path(
+    post,
+    path = "/rks/simulate",
+    summary = "RKS 假设模拟（What-if）",
+    description = "在玩家现有成绩之上叠加一组假设成绩（歌曲、难度、ACC），返回模拟前后的总 RKS、Best27/AP3 构成以及每个假设谱面的 RKS 变化。提供 `records` 时完全离线计算（不读取存档、无需认证）；否则通过认证信息拉取云存档。同一谱面取较高 ACC，与游戏内规则一致。可通过 `rule` 选择 RKS 规则版本（默认当前 b27-ap3，亦支持旧版 b19-phi1 及不重叠变体）；通过 `constantsVersion` 选择定数表（current / auto / 归档版本，见 GET /rks/constants）。",
+    request_body = RksSimulateRequest,
+    responses(
+        (status = 200, body = RksSimulateResponse, description = "模拟结果"),
+        (
+            status = 401,
+            description = "认证失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "歌曲未找到",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 409,
+            description = "歌曲查询不唯一",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "参数校验失败（难度无效/无定数/ACC 越界/条数越界）或存档无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 502,
+            description = "上游网络错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "RKS"
+)]
+pub async fn post_rks_simulate(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<RksSimulateResponse>, AppError> {
+    let t_total = Instant::now();
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<RksSimulateRequest>(request).await?;
+    validate_simulate_request(&req)?;
+
+    // 整个请求固定使用同一份定数/目录快照
+    let game = state.game_data();
+    let changes = resolve_chart_inputs("changes", &req.changes, &game.song_catalog)?;
+    let base = load_base_charts(
+        &state,
+        &game,
+        &mut req.auth,
+        &bearer_state,
+        req.records.as_deref(),
+    )
+    .await?;
+
+    let selected = state.game_data.constant_tables().select(
+        &game,
+        req.constants_version.as_deref(),
+        base.game_version,
+    )?;
+    let (base_charts, changes) = if selected.version == CURRENT_VERSION {
+        (base.charts, changes)
+    } else {
+        let change_count = changes.len();
+        let changes = rebase_constants(changes, &selected.constants);
+        if changes.len() != change_count {
+            return Err(AppError::Validation(format!(
+                "changes 中有谱面在定数表 {} 中没有定数",
+                selected.version
+            )));
+        }
+        (rebase_constants(base.charts, &selected.constants), changes)
+    };
+
+    let base_count = base_charts.len();
+    let change_count = changes.len();
+    let mut resp = simulate_rks(base_charts, changes, req.rule);
+    resp.constants_version = selected.version;
+    fill_song_names(&mut resp, &game.song_catalog);
+
+    if let Some(stats) = state.stats.as_ref() {
+        let extra = serde_json::json!({
+            "mode": base.mode,
+            "changes": change_count,
+            "rule": req.rule.code(),
+            "constants": resp.constants_version,
+        });
+        stats.track_feature("rks", "simulate", base.user_hash, Some(extra));
+    }
+    tracing::info!(
+        target: "phi_backend::rks::performance",
+        route = "/rks/simulate",
+        phase = "total",
+        status = "ok",
+        mode = base.mode,
+        records = base_count,
+        changes = change_count,
+        total_dur_ms = t_total.elapsed().as_millis(),
+        "rks performance"
+    );
+
+    Ok(Json(resp))
+}
+
+/// 目标 RKS 推分规划
+#[utoipa::path(
+    post,
+    path = "/rks/plan",
+    summary = "目标 RKS 推分规划",
+    description = "给定目标显示 RKS（不传则为“显示 RKS +0.01”），在玩家已游玩与未游玩（定数表中存在）的谱面中搜索代价最低的 ACC 提升组合，按代价升序返回若干方案及每个谱面的目标 ACC。代价随所需 ACC 提升、当前 ACC（越接近 100% 越贵）与定数（相对玩家 Best27 平均定数）增长。提供 `records` 时完全离线计算。可通过 `constantsVersion` 选择定数表（current / auto / 归档版本）。",
+    request_body = RksPlanRequest,
+    responses(
+        (status = 200, body = RksPlanResponse, description = "规划结果"),
+        (
+            status = 401,
+            description = "认证失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "参数校验失败或存档无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 502,
+            description = "上游网络错误",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "RKS"
+)]
+pub async fn post_rks_plan(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<RksPlanResponse>, AppError> {
+    let t_total = Instant::now();
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<RksPlanRequest>(request).await?;
+    let opts = req.options()?;
+    if let Some(records) = req.records.as_ref()
+        && records.len() > MAX_SIMULATE_RECORDS
+    {
+        return Err(AppError::Validation(format!(
+            "records 最多 {MAX_SIMULATE_RECORDS} 条"
+        )));
+    }
+
+    let game = state.game_data();
+    let base = load_base_charts(
+        &state,
+        &game,
+        &mut req.auth,
+        &bearer_state,
+        req.records.as_deref(),
+    )
+    .await?;
+    let selected = state.game_data.constant_tables().select(
+        &game,
+        req.constants_version.as_deref(),
+        base.game_version,
+    )?;
+    let charts = if selected.version == CURRENT_VERSION {
+        base.charts
+    } else {
+        rebase_constants(base.charts, &selected.constants)
+    };
+    let base_count = charts.len();
+
+    // 搜索为纯 CPU 计算，放到阻塞线程池避免占用 async worker
+    let chart_constants = selected.constants.clone();
+    let mut resp =
+        tokio::task::spawn_blocking(move || plan_target_rks(charts, &chart_constants, opts))
+            .await
+            .map_err(|e| AppError::Internal(format!("推分规划任务失败: {e}")))?;
+    resp.constants_version = selected.version;
+    fill_plan_song_names(&mut resp, &game.song_catalog);
+
+    if let Some(stats) = state.stats.as_ref() {
+        let extra = serde_json::json!({
+            "mode": base.mode,
+            "has_target": opts.target_rks.is_some(),
+            "plans": resp.plans.len(),
+            "constants": resp.constants_version,
+        });
+        stats.track_feature("rks", "plan", base.user_hash, Some(extra));
+    }
+    tracing::info!(
+        target: "phi_backend::rks::performance",
+        route = "/rks/plan",
+        phase = "total",
+        status = "ok",
+        mode = base.mode,
+        records = base_count,
+        plans = resp.plans.len(),
+        total_dur_ms = t_total.elapsed().as_millis(),
+        "rks performance"
+    );
+
+    Ok(Json(resp))
+}
+
+/// 可用定数表列表
+#[utoipa::path(
+    get,
+    path = "/rks/constants",
+    summary = "可用定数表列表",
+    description = "列出线上定数表与 resources.constant_tables 中配置的归档定数表。RKS 模拟/规划接口的 `constantsVersion` 可取 current、auto（按存档 summary 的 game_version 匹配，未匹配时使用线上定数）或此处列出的版本标识。",
+    responses(
+        (status = 200, body = RksConstantsResponse, description = "定数表列表")
+    ),
+    tag = "RKS"
+)]
+pub async fn get_rks_constants(State(state): State<AppState>) -> Json<RksConstantsResponse> {
+    let game = state.game_data();
+    Json(RksConstantsResponse {
+        current: CurrentConstantsInfo {
+            data_version: game.version,
+            songs: game.chart_constants.len(),
+            loaded_at: game.loaded_at.clone(),
+        },
+        tables: state.game_data.constant_tables().infos(),
+    })
+}
+
+/// 影响评估每批读取的用户数
+const IMPACT_SCAN_BATCH: i64 = 500;
+
+/// 定数表差异报告
+#[utoipa::path(
+    get,
+    path = "/admin/rks/constants/diff",
+    summary = "定数表差异报告",
+    description = "比较两张定数表，列出定数变化（含新增/移除）的谱面；impact=true（默认）时基于排行榜用户最近一次提交的谱面快照，按当前规则评估有多少用户的 RKS 会变化及变化幅度。需要在 Header 中提供 X-Admin-Token。",
+    params(
+        ("X-Admin-Token" = String, Header, description = "管理员令牌（config.leaderboard.admin_tokens）"),
+        ("from" = Option<String>, Query, description = "基准定数表版本（默认 current）"),
+        ("to" = Option<String>, Query, description = "对比定数表版本（默认 current）"),
+        ("impact" = Option<bool>, Query, description = "是否评估排行榜影响（默认 true；统计存储未启用时忽略）")
+    ),
+    security(("AdminToken" = [])),
+    responses(
+        (status = 200, body = ConstantsDiffResponse, description = "差异报告"),
+        (
+            status = 401,
+            description = "管理员令牌缺失/无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "定数表版本无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "RKS"
+)]
+pub async fn get_admin_constants_diff(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ConstantsDiffQuery>,
+) -> Result<Json<ConstantsDiffResponse>, AppError> {
+    crate::leaderboard_contract::require_admin(&headers)?;
+    let t_total = Instant::now();
+    let game = state.game_data();
+    let tables = state.game_data.constant_tables();
+    // 差异报告只比较显式选定的表，auto 没有意义，按 current 处理
+    let from = tables.select(&game, q.from.as_deref(), None)?;
+    let to = tables.select(&game, q.to.as_deref(), None)?;
+
+    let changes = crate::constant_tables::diff_constants(&from.constants, &to.constants);
+    let (items, changed_songs) = build_change_items(&changes, &game.song_catalog);
+
+    let impact = match state.stats_storage.as_ref() {
+        Some(storage) if q.impact.unwrap_or(true) && !changes.is_empty() => {
+            let mut acc =
+                ImpactAccumulator::new(from.constants.clone(), to.constants.clone(), &changes);
+            let mut after_user: Option<String> = None;
+            loop {
+                let rows = storage
+                    .query_leaderboard_chart_details(after_user.as_deref(), IMPACT_SCAN_BATCH)
+                    .await?;
+                let Some(last) = rows.last() else {
+                    break;
+                };
+                after_user = Some(last.user_hash.clone());
+                let full_page = i64::try_from(rows.len()).is_ok_and(|n| n == IMPACT_SCAN_BATCH);
+                // 重算为纯 CPU 计算，按批放到阻塞线程池
+                acc = tokio::task::spawn_blocking(move || {
+                    for row in &rows {
+                        let snapshot = row
+                            .details_json
+                            .as_deref()
+                            .and_then(ChartAccSnapshot::from_json);
+                        acc.add(snapshot.as_ref());
+                    }
+                    acc
+                })
+                .await
+                .map_err(|e| AppError::Internal(format!("影响评估任务失败: {e}")))?;
+                if !full_page {
+                    break;
+                }
+            }
+            Some(acc.finish())
+        }
+        _ => None,
+    };
+
+    tracing::info!(
+        target: "phi_backend::rks::performance",
+        route = "/admin/rks/constants/diff",
+        phase = "total",
+        status = "ok",
+        from = %from.version,
+        to = %to.version,
+        changes = changes.len(),
+        impact = impact.is_some(),
+        total_dur_ms = t_total.elapsed().as_millis(),
+        "rks performance"
+    );
+
+    Ok(Json(ConstantsDiffResponse {
+        from: from.version,
+        to: to.version,
+        changed_songs,
+        changes: items,
+        impact,
+    }))
+}
+
+/// 模拟/规划的基础成绩来源
+struct BaseCharts {
+    charts: Vec<ResolvedChart>,
+    mode: &'static str,
+    user_hash: Option<String>,
+    /// 存档 summary 中的 game_version（离线模式为空）
+    game_version: Option<u8>,
+}
+
+/// 提供 `records` 时离线解析；否则合并 Bearer 认证后拉取云存档。
+async fn load_base_charts(
+    state: &AppState,
+    game: &crate::game_data::GameData,
+    auth: &mut crate::auth_contract::UnifiedSaveRequest,
+    bearer_state: &crate::session_auth::BearerAuthState,
+    records: Option<&[SimulateChartInput]>,
+) -> Result<BaseCharts, AppError> {
+    if let Some(records) = records {
+        return Ok(BaseCharts {
+            charts: resolve_chart_inputs("records", records, &game.song_catalog)?,
+            mode: "offline",
+            user_hash: None,
+            game_version: None,
+        });
+    }
+
+    crate::session_auth::merge_auth_from_bearer_if_missing(
+        state.stats_storage.as_ref(),
+        bearer_state,
+        auth,
+    )
+    .await?;
+    let salt = crate::config::AppConfig::global()
+        .stats
+        .user_hash_salt
+        .as_deref();
+    let (user_hash, _kind) =
+        crate::session_auth::derive_user_identity_with_bearer(salt, auth, bearer_state)?;
+    if let (Some(storage), Some(hash)) = (state.stats_storage.as_ref(), user_hash.as_deref()) {
+        storage.ensure_user_not_banned(hash).await?;
+    }
+
+    let source = save_contract::validate_and_create_source(auth)?;
+    let meta = save_contract::fetch_save_meta(
+        source,
+        &crate::config::AppConfig::global().taptap,
+        auth.taptap_version.as_deref(),
+    )
+    .await?;
+    let parsed =
+        save_contract::get_decrypted_save_from_meta(meta, game.chart_constants.clone()).await?;
+    Ok(BaseCharts {
+        charts: charts_from_game_record(&parsed.game_record),
+        mode: "save",
+        user_hash,
+        game_version: parsed.summary_parsed.as_ref().map(|s| s.game_version),
+    })
+}
+
+/// 与 /rks/history 相同的认证流程：合并 Bearer 凭证后解析用户身份并检查封禁状态。
+async fn resolve_history_user(
+    storage: &crate::stats_contract::StatsStorage,
+    auth: &crate::auth_contract::UnifiedSaveRequest,
+    bearer_state: &crate::session_auth::BearerAuthState,
+) -> Result<String, AppError> {
+    let salt = crate::config::AppConfig::global()
+        .stats
+        .user_hash_salt
+        .as_deref();
+    let (user_hash_opt, _kind) =
+        crate::session_auth::derive_user_identity_with_bearer(salt, auth, bearer_state)?;
+    let user_hash =
+        user_hash_opt.ok_or_else(|| AppError::Auth("无法识别用户（缺少可用凭证）".into()))?;
+    storage.ensure_user_not_banned(&user_hash).await?;
+    Ok(user_hash)
+}
+
+/// 查询单个谱面的成绩历史
+#[utoipa::path(
+    post,
+    path = "/rks/history/chart",
+    summary = "查询单谱面成绩历史",
+    description = "返回指定谱面每次成绩（分数/ACC/FC）变化的记录，按时间倒序、游标分页。记录在提交存档（写排行榜）时追加，成绩未变化的提交不会产生记录；首次提交建立的基线记录 isPb=false。认证方式与 /rks/history 相同。",
+    request_body = ChartHistoryRequest,
+    responses(
+        (status = 200, body = ChartHistoryResponse, description = "成绩历史"),
+        (
+            status = 401,
+            description = "认证失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 404,
+            description = "歌曲未找到",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 409,
+            description = "歌曲匹配到多个结果",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "难度或 cursor 无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "RKS"
+)]
+pub async fn post_rks_chart_history(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<ChartHistoryResponse>, AppError> {
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<ChartHistoryRequest>(request).await?;
+    crate::session_auth::merge_auth_from_bearer_if_missing(
+        state.stats_storage.as_ref(),
+        &bearer_state,
+        &mut req.auth,
+    )
+    .await?;
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let user_hash = resolve_history_user(storage, &req.auth, &bearer_state).await?;
+
+    let Some(difficulty) = parse_difficulty(&req.difficulty) else {
+        return Err(AppError::Validation(format!(
+            "difficulty 无效：{}（可选 EZ/HD/IN/AT）",
+            req.difficulty
+        )));
+    };
+    let game = state.game_data();
+    let song = game
+        .song_catalog
+        .search_unique(req.song.trim())
+        .map_err(AppError::Search)?;
+    let limit = req.limit.unwrap_or(50).clamp(1, 200);
+    let cursor = parse_rks_history_cursor(req.cursor.as_deref())?;
+    let difficulty = difficulty.to_string();
+
+    let page = storage
+        .query_chart_score_history(&user_hash, &song.id, &difficulty, limit, cursor.as_ref())
+        .await?;
+    let next_cursor = if page.has_more {
+        encode_chart_cursor(page.entries.last())
+    } else {
+        None
+    };
+    let items = page
+        .entries
+        .into_iter()
+        .map(|e| chart_score_item(e, &game.song_catalog))
+        .collect();
+    Ok(Json(ChartHistoryResponse {
+        song_id: song.id.clone(),
+        song_name: song.name.clone(),
+        difficulty,
+        items,
+        has_more: page.has_more,
+        next_cursor,
+    }))
+}
+
+/// 查询最近刷新的个人最佳
+#[utoipa::path(
+    post,
+    path = "/rks/history/pbs",
+    summary = "查询最近个人最佳",
+    description = "返回最近刷新个人最佳（分数/ACC 提升或新达成 FC）的谱面记录，按时间倒序、游标分页；isNewAp 标记本次达成 AP。认证方式与 /rks/history 相同。",
+    request_body = RecentPbRequest,
+    responses(
+        (status = 200, body = RecentPbResponse, description = "最近个人最佳"),
+        (
+            status = 401,
+            description = "认证失败/无法识别用户",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "cursor 无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "统计存储未初始化/查询失败",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "RKS"
+)]
+pub async fn post_rks_recent_pbs(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<RecentPbResponse>, AppError> {
+    let (mut req, bearer_state) =
+        crate::session_auth::parse_json_with_bearer_state::<RecentPbRequest>(request).await?;
+    crate::session_auth::merge_auth_from_bearer_if_missing(
+        state.stats_storage.as_ref(),
+        &bearer_state,
+        &mut req.auth,
+    )
+    .await?;
+    let storage = state
+        .stats_storage
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let user_hash = resolve_history_user(storage, &req.auth, &bearer_state).await?;
+
+    let limit = req.limit.unwrap_or(50).clamp(1, 200);
+    let cursor = parse_rks_history_cursor(req.cursor.as_deref())?;
+    let page = storage
+        .query_recent_pbs(&user_hash, limit, cursor.as_ref())
+        .await?;
+    let next_cursor = if page.has_more {
+        encode_chart_cursor(page.entries.last())
+    } else {
+        None
+    };
+    let game = state.game_data();
+    let items = page
+        .entries
+        .into_iter()
+        .map(|e| chart_score_item(e, &game.song_catalog))
+        .collect();
+    Ok(Json(RecentPbResponse {
+        items,
+        has_more: page.has_more,
+        next_cursor,
+    }))
+}
+
 /// 创建 RKS 路由
 pub fn create_rks_router() -> Router<AppState> {
-    Router::new().route("/rks/history", post(post_rks_history))
+    Router::new()
+        .route("/rks/history", post(post_rks_history))
+        .route("/rks/history/chart", post(post_rks_chart_history))
+        .route("/rks/history/pbs", post(post_rks_recent_pbs))
+        .route("/rks/simulate", post(post_rks_simulate))
+        .route("/rks/plan", post(post_rks_plan))
+        .route("/rks/constants", get(get_rks_constants))
+        .route("/admin/rks/constants/diff", get(get_admin_constants_diff))
 }
 
 #[cfg(test)]
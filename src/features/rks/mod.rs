@@ -1,2 +1,8 @@
+pub mod chart_history;
+pub mod chart_snapshot;
+pub mod constants_diff;
 pub mod engine;
 pub mod handler;
+pub mod planner;
+pub mod rules;
+pub mod simulate;
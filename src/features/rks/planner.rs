@@ -0,0 +1,683 @@
+//! 目标 RKS 推分规划
+//!
+//! 在单谱面推分（[`PushAccBatchSolver`]）之上扩展为多谱面规划：给定目标总 RKS
+//! （或“显示 RKS +0.01”），在玩家已游玩与未游玩（定数表中存在）的谱面里搜索
+//! 代价最低的若干 ACC 提升组合，并按代价排序返回。
+//!
+//! 代价模型（启发式，仅用于排序）：
+//!
+//! `cost = ln((100.5 - from) / (100.5 - to)) × (定数 / 参考定数)² [+ 未游玩惩罚]`
+//!
+//! - `from` 为当前 ACC（低于 70% 或未游玩按 70% 计），越接近 100% 每提升 0.1% 越贵；
+//! - 参考定数为当前 Best27 的平均定数，高于玩家常打水平的谱面代价按平方放大。
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::save_contract::Difficulty;
+use crate::startup::chart_loader::ChartConstantsMap;
+
+use super::engine::{
+    PushAccBatchSolver, PushAccHint, RksRecord, calculate_chart_rks, calculate_player_rks_details,
+    level_for_difficulty, target_rks_threshold_from_exact,
+};
+use super::simulate::{ResolvedChart, SimulateChartInput, merge_best};
+
+/// 返回方案数上限
+pub(crate) const MAX_PLAN_LIMIT: usize = 20;
+/// 单个方案涉及谱面数上限
+pub(crate) const MAX_PLAN_CHARTS: usize = 10;
+
+const DEFAULT_PLAN_LIMIT: usize = 5;
+const DEFAULT_PLAN_CHARTS: usize = 5;
+/// 参与搜索的候选谱面上限（按满 ACC 可得 RKS 取前 N）
+const MAX_CANDIDATES: usize = 150;
+/// 贪心搜索的最大步数
+const MAX_GREEDY_STEPS: usize = 60;
+/// 代价模型中 ACC 的渐近上限（>100 以免 100% 处代价发散）
+const ACC_CEILING: f64 = 100.5;
+/// 低于该 ACC 的谱面不计 RKS，代价从此处起算
+const ACC_FLOOR: f64 = 70.0;
+/// 未游玩谱面的额外代价
+const UNPLAYED_PENALTY: f64 = 0.3;
+/// 贪心搜索时尝试的目标 ACC 档位
+const ACC_LEVELS: [f64; 17] = [
+    70.0, 80.0, 85.0, 90.0, 92.0, 94.0, 95.0, 96.0, 97.0, 97.5, 98.0, 98.5, 99.0, 99.25, 99.5,
+    99.75, 100.0,
+];
+
+/// 目标 RKS 规划请求
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+    "targetRks": 15.0,
+    "limit": 5,
+    "maxCharts": 3
+}))]
+pub struct RksPlanRequest {
+    /// 认证信息（未提供 `records` 时用于拉取云存档；也可仅通过 Bearer 认证）
+    #[serde(default)]
+    pub auth: crate::auth_contract::UnifiedSaveRequest,
+    /// 离线基础成绩列表：提供时不读取存档、不需要认证（最多 2000 条）
+    #[serde(default)]
+    pub records: Option<Vec<SimulateChartInput>>,
+    /// 目标显示 RKS（两位小数口径）；不传则以“显示 RKS +0.01”为目标
+    #[serde(default)]
+    pub target_rks: Option<f64>,
+    /// 是否考虑未游玩的谱面（默认 true）
+    #[serde(default = "default_include_unplayed")]
+    pub include_unplayed: bool,
+    /// 返回方案数（默认 5，最大 20）
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// 单个方案最多涉及的谱面数（默认 5，最大 10）
+    #[serde(default)]
+    pub max_charts: Option<usize>,
+    /// 定数表版本：current（默认，线上定数）/ auto（按存档 game_version 匹配）/ 归档版本标识
+    #[serde(default)]
+    pub constants_version: Option<String>,
+}
+
+fn default_include_unplayed() -> bool {
+    true
+}
+
+/// 方案中的单个谱面
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksPlanChart {
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    pub difficulty: Difficulty,
+    pub constant: f64,
+    /// 当前 ACC；未游玩时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_acc: Option<f64>,
+    /// 需要达到的 ACC（千分位精度）
+    pub target_acc: f64,
+    /// 达到目标 ACC 后的谱面 RKS
+    pub new_rks: f64,
+    /// 该谱面的代价
+    pub cost: f64,
+}
+
+/// 单个推分方案
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksPlan {
+    /// 名次（1 为代价最低）
+    pub rank: usize,
+    /// 总代价（越低越容易）
+    pub cost: f64,
+    /// 完成方案后的精确 RKS
+    pub resulting_exact_rks: f64,
+    /// 完成方案后的显示 RKS
+    pub resulting_display_rks: f64,
+    pub charts: Vec<RksPlanChart>,
+}
+
+/// 目标 RKS 规划响应
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksPlanResponse {
+    /// 计算所用的定数表版本（线上定数为 current）
+    pub constants_version: String,
+    pub current_exact_rks: f64,
+    pub current_display_rks: f64,
+    /// 目标显示 RKS
+    pub target_display_rks: f64,
+    /// 达成目标所需的最低精确 RKS
+    pub target_exact_threshold: f64,
+    /// 在谱面数限制内是否可达
+    pub reachable: bool,
+    /// 按代价升序的方案；当前已达标时为空
+    pub plans: Vec<RksPlan>,
+}
+
+/// 规划参数（已归一化）
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PlanOptions {
+    pub target_rks: Option<f64>,
+    pub include_unplayed: bool,
+    pub limit: usize,
+    pub max_charts: usize,
+}
+
+impl RksPlanRequest {
+    pub(crate) fn options(&self) -> Result<PlanOptions, crate::error::AppError> {
+        if let Some(t) = self.target_rks
+            && (!t.is_finite() || t <= 0.0)
+        {
+            return Err(crate::error::AppError::Validation(
+                "targetRks 必须为正数".into(),
+            ));
+        }
+        Ok(PlanOptions {
+            target_rks: self.target_rks,
+            include_unplayed: self.include_unplayed,
+            limit: self
+                .limit
+                .unwrap_or(DEFAULT_PLAN_LIMIT)
+                .clamp(1, MAX_PLAN_LIMIT),
+            max_charts: self
+                .max_charts
+                .unwrap_or(DEFAULT_PLAN_CHARTS)
+                .clamp(1, MAX_PLAN_CHARTS),
+        })
+    }
+}
+
+/// 规划用的谱面集合：已游玩 + 未游玩（acc=0）统一按 rks 降序排列。
+struct PlanSpace {
+    records: Vec<RksRecord>,
+    played: Vec<bool>,
+    ap_indices: Vec<usize>,
+    ref_constant: f64,
+}
+
+impl PlanSpace {
+    fn build(
+        base: Vec<ResolvedChart>,
+        constants: &ChartConstantsMap,
+        include_unplayed: bool,
+    ) -> Self {
+        let merged = merge_best(base);
+        let mut entries: Vec<(RksRecord, bool)> = merged
+            .into_values()
+            .map(|c| {
+                (
+                    RksRecord {
+                        rks: calculate_chart_rks(c.acc, c.constant),
+                        song_id: c.song_id,
+                        difficulty: c.difficulty,
+                        score: 0,
+                        acc: c.acc,
+                        chart_constant: c.constant,
+                    },
+                    true,
+                )
+            })
+            .collect();
+        if include_unplayed {
+            let played: HashSet<(String, Difficulty)> = entries
+                .iter()
+                .map(|(r, _)| (r.song_id.clone(), r.difficulty))
+                .collect();
+            for (song_id, consts) in constants {
+                for difficulty in [
+                    Difficulty::EZ,
+                    Difficulty::HD,
+                    Difficulty::IN,
+                    Difficulty::AT,
+                ] {
+                    let Some(constant) = level_for_difficulty(consts, &difficulty)
+                        .map(f64::from)
+                        .filter(|c| *c > 0.0)
+                    else {
+                        continue;
+                    };
+                    if played.contains(&(song_id.clone(), difficulty)) {
+                        continue;
+                    }
+                    entries.push((
+                        RksRecord {
+                            song_id: song_id.clone(),
+                            difficulty,
+                            score: 0,
+                            acc: 0.0,
+                            rks: 0.0,
+                            chart_constant: constant,
+                        },
+                        false,
+                    ));
+                }
+            }
+        }
+        // 同分按谱面键排序，保证结果稳定
+        entries.sort_by(|(a, _), (b, _)| {
+            b.rks
+                .total_cmp(&a.rks)
+                .then_with(|| b.chart_constant.total_cmp(&a.chart_constant))
+                .then_with(|| a.song_id.cmp(&b.song_id))
+                .then_with(|| (a.difficulty as u8).cmp(&(b.difficulty as u8)))
+        });
+        let (records, played): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        let ap_indices = records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.acc >= 100.0)
+            .map(|(i, _)| i)
+            .collect();
+        let best: Vec<f64> = records
+            .iter()
+            .zip(&played)
+            .filter(|(_, p)| **p)
+            .take(27)
+            .map(|(r, _)| r.chart_constant)
+            .collect();
+        let ref_constant = if best.is_empty() {
+            1.0
+        } else {
+            #[allow(clippy::cast_precision_loss)] // 至多 27 条，不会损失精度
+            let n = best.len() as f64;
+            (best.iter().sum::<f64>() / n).max(1.0)
+        };
+        Self {
+            records,
+            played,
+            ap_indices,
+            ref_constant,
+        }
+    }
+
+    fn acc_of(&self, mods: &[(usize, f64)], idx: usize) -> f64 {
+        mods.iter()
+            .find(|(i, _)| *i == idx)
+            .map_or(self.records[idx].acc, |(_, a)| *a)
+    }
+
+    /// 在部分谱面改为指定 ACC 后的精确 RKS（只需扫描各榜前若干条）
+    fn eval(&self, mods: &[(usize, f64)]) -> f64 {
+        let is_mod = |i: usize| mods.iter().any(|(j, _)| *j == i);
+        let mut best: Vec<f64> = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !is_mod(*i))
+            .take(27)
+            .map(|(_, r)| r.rks)
+            .collect();
+        let mut ap: Vec<f64> = self
+            .ap_indices
+            .iter()
+            .filter(|i| !is_mod(**i))
+            .take(3)
+            .map(|i| self.records[*i].rks)
+            .collect();
+        for (i, acc) in mods {
+            let rks = calculate_chart_rks(*acc, self.records[*i].chart_constant);
+            best.push(rks);
+            if *acc >= 100.0 {
+                ap.push(rks);
+            }
+        }
+        best.sort_by(|a, b| b.total_cmp(a));
+        ap.sort_by(|a, b| b.total_cmp(a));
+        (best.iter().take(27).sum::<f64>() + ap.iter().take(3).sum::<f64>()) / 30.0
+    }
+
+    /// 其余改动固定时，`idx` 需要的最低 ACC（千分位）；100% 也达不到时返回 `None`。
+    fn min_acc_for(&self, mods: &[(usize, f64)], idx: usize, threshold: f64) -> Option<f64> {
+        let others: Vec<(usize, f64)> = mods.iter().copied().filter(|(i, _)| *i != idx).collect();
+        let meets = |acc_thousand: u32| {
+            let mut m = others.clone();
+            m.push((idx, f64::from(acc_thousand) / 1000.0));
+            self.eval(&m) >= threshold
+        };
+        if !meets(100_000) {
+            return None;
+        }
+        // 已 clamp 到 [0, 100000]，转换不会截断
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let mut lo = (self.records[idx].acc * 1000.0)
+            .ceil()
+            .clamp(0.0, 100_000.0) as u32;
+        let mut hi = 100_000;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if meets(mid) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        Some(f64::from(lo) / 1000.0)
+    }
+
+    fn chart_cost(&self, idx: usize, to_acc: f64) -> f64 {
+        let rec = &self.records[idx];
+        let played = self.played[idx];
+        let from = if played { rec.acc } else { 0.0 }.max(ACC_FLOOR);
+        if to_acc <= rec.acc {
+            return 0.0;
+        }
+        let to = to_acc.max(from);
+        let gain = ((ACC_CEILING - from) / (ACC_CEILING - to)).ln();
+        let weight = (rec.chart_constant / self.ref_constant).powi(2);
+        gain * weight + if played { 0.0 } else { UNPLAYED_PENALTY }
+    }
+
+    fn plan_cost(&self, mods: &[(usize, f64)]) -> f64 {
+        mods.iter().map(|(i, a)| self.chart_cost(*i, *a)).sum()
+    }
+
+    /// 能对 Best27 或 AP3 产生贡献的谱面（满 ACC 时的 RKS 高于当前门槛）
+    fn candidates(&self) -> Vec<usize> {
+        let b27_floor = self
+            .records
+            .iter()
+            .filter(|r| r.rks > 0.0)
+            .nth(26)
+            .map_or(0.0, |r| r.rks);
+        let ap3_floor = self.ap_indices.get(2).map_or(0.0, |i| self.records[*i].rks);
+        let mut out: Vec<usize> = (0..self.records.len())
+            .filter(|i| {
+                let r = &self.records[*i];
+                r.acc < 100.0 && (r.chart_constant > b27_floor || r.chart_constant > ap3_floor)
+            })
+            .collect();
+        out.sort_by(|a, b| {
+            self.records[*b]
+                .chart_constant
+                .total_cmp(&self.records[*a].chart_constant)
+        });
+        out.truncate(MAX_CANDIDATES);
+        out
+    }
+
+    /// 贪心：每步选择“RKS 增量 / 代价增量”最高的一次 ACC 提升，达标后逐项收紧。
+    fn greedy_plan(
+        &self,
+        candidates: &[usize],
+        banned: &HashSet<usize>,
+        threshold: f64,
+        max_charts: usize,
+    ) -> Option<Vec<(usize, f64)>> {
+        let mut mods: Vec<(usize, f64)> = Vec::new();
+        for _ in 0..MAX_GREEDY_STEPS {
+            let current = self.eval(&mods);
+            if current >= threshold {
+                break;
+            }
+            let mut best: Option<(f64, usize, f64)> = None;
+            for &idx in candidates {
+                if banned.contains(&idx) {
+                    continue;
+                }
+                let in_plan = mods.iter().any(|(i, _)| *i == idx);
+                if !in_plan && mods.len() >= max_charts {
+                    continue;
+                }
+                let cur_acc = self.acc_of(&mods, idx);
+                let cur_cost = if in_plan {
+                    self.chart_cost(idx, cur_acc)
+                } else {
+                    0.0
+                };
+                for &level in ACC_LEVELS.iter().filter(|l| **l > cur_acc) {
+                    let mut trial = mods.clone();
+                    match trial.iter_mut().find(|(i, _)| *i == idx) {
+                        Some(entry) => entry.1 = level,
+                        None => trial.push((idx, level)),
+                    }
+                    let gain = self.eval(&trial) - current;
+                    if gain <= 0.0 {
+                        continue;
+                    }
+                    let d_cost = (self.chart_cost(idx, level) - cur_cost).max(1e-9);
+                    let ratio = gain / d_cost;
+                    if best.is_none_or(|(r, _, _)| ratio > r) {
+                        best = Some((ratio, idx, level));
+                    }
+                }
+            }
+            let (_, idx, level) = best?;
+            match mods.iter_mut().find(|(i, _)| *i == idx) {
+                Some(entry) => entry.1 = level,
+                None => mods.push((idx, level)),
+            }
+        }
+        if self.eval(&mods) < threshold {
+            return None;
+        }
+        self.tighten(mods, threshold)
+    }
+
+    /// 从代价最高的谱面开始：能去掉则去掉，否则降到刚好达标的最低 ACC。
+    fn tighten(&self, mut mods: Vec<(usize, f64)>, threshold: f64) -> Option<Vec<(usize, f64)>> {
+        let mut order: Vec<usize> = mods.iter().map(|(i, _)| *i).collect();
+        order.sort_by(|a, b| {
+            let ca = self.chart_cost(*a, self.acc_of(&mods, *a));
+            let cb = self.chart_cost(*b, self.acc_of(&mods, *b));
+            cb.total_cmp(&ca)
+        });
+        for idx in order {
+            let without: Vec<(usize, f64)> =
+                mods.iter().copied().filter(|(i, _)| *i != idx).collect();
+            if self.eval(&without) >= threshold {
+                mods = without;
+                continue;
+            }
+            let acc = self.min_acc_for(&mods, idx, threshold)?;
+            if let Some(entry) = mods.iter_mut().find(|(i, _)| *i == idx) {
+                entry.1 = acc;
+            }
+        }
+        Some(mods)
+    }
+}
+
+/// 规划结果（不含歌曲名，由调用方回填）
+pub(crate) fn plan_target_rks(
+    base: Vec<ResolvedChart>,
+    constants: &ChartConstantsMap,
+    opts: PlanOptions,
+) -> RksPlanResponse {
+    let space = PlanSpace::build(base, constants, opts.include_unplayed);
+    let played_records: Vec<RksRecord> = space
+        .records
+        .iter()
+        .zip(&space.played)
+        .filter(|(_, p)| **p)
+        .map(|(r, _)| r.clone())
+        .collect();
+    let (current_exact_rks, current_display_rks) = calculate_player_rks_details(&played_records);
+    let (target_display_rks, threshold) = if let Some(t) = opts.target_rks {
+        (t, t - 0.005)
+    } else {
+        let threshold = target_rks_threshold_from_exact(current_exact_rks);
+        (((threshold + 0.005) * 100.0).round() / 100.0, threshold)
+    };
+
+    let mut resp = RksPlanResponse {
+        constants_version: crate::constant_tables::CURRENT_VERSION.to_string(),
+        current_exact_rks,
+        current_display_rks,
+        target_display_rks,
+        target_exact_threshold: threshold,
+        reachable: current_exact_rks >= threshold,
+        plans: Vec::new(),
+    };
+    if resp.reachable {
+        return resp;
+    }
+
+    let candidates = space.candidates();
+    let mut found: Vec<Vec<(usize, f64)>> = Vec::new();
+
+    // 单谱面方案：沿用推分求解器（未游玩谱面以 acc=0 参与排序）
+    let solver = PushAccBatchSolver::with_threshold(&space.records, threshold);
+    for &idx in &candidates {
+        let acc = match solver.solve_for_index(idx, space.records[idx].chart_constant) {
+            Some(PushAccHint::TargetAcc { acc }) => acc,
+            Some(PushAccHint::PhiOnly) => 100.0,
+            _ => continue,
+        };
+        found.push(vec![(idx, acc)]);
+    }
+
+    // 多谱面方案：贪心 + 依次排除首选方案中的谱面得到备选
+    if opts.max_charts > 1 {
+        let none = HashSet::new();
+        if let Some(first) = space.greedy_plan(&candidates, &none, threshold, opts.max_charts) {
+            for (idx, _) in &first {
+                let banned = HashSet::from([*idx]);
+                if let Some(alt) =
+                    space.greedy_plan(&candidates, &banned, threshold, opts.max_charts)
+                {
+                    found.push(alt);
+                }
+            }
+            found.push(first);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut plans: Vec<(f64, Vec<(usize, f64)>)> = found
+        .into_iter()
+        .filter_map(|mut mods| {
+            mods.sort_by_key(|(i, _)| *i);
+            // ACC 位于 [0, 100]，按千分位取整作去重键不会截断
+            #[allow(clippy::cast_possible_truncation)]
+            let key: Vec<(usize, i64)> = mods
+                .iter()
+                .map(|(i, a)| (*i, (a * 1000.0).round() as i64))
+                .collect();
+            seen.insert(key).then(|| (space.plan_cost(&mods), mods))
+        })
+        .collect();
+    plans.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.len().cmp(&b.1.len())));
+    plans.truncate(opts.limit);
+
+    resp.reachable = !plans.is_empty();
+    resp.plans = plans
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (cost, mods))| {
+            let exact = space.eval(&mods);
+            let mut charts: Vec<RksPlanChart> = mods
+                .iter()
+                .map(|(idx, acc)| {
+                    let rec = &space.records[*idx];
+                    RksPlanChart {
+                        song_id: rec.song_id.clone(),
+                        song_name: None,
+                        difficulty: rec.difficulty,
+                        constant: rec.chart_constant,
+                        current_acc: space.played[*idx].then_some(rec.acc),
+                        target_acc: *acc,
+                        new_rks: calculate_chart_rks(*acc, rec.chart_constant),
+                        cost: space.chart_cost(*idx, *acc),
+                    }
+                })
+                .collect();
+            charts.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+            RksPlan {
+                rank: rank + 1,
+                cost,
+                resulting_exact_rks: exact,
+                resulting_display_rks: (exact * 100.0).round() / 100.0,
+                charts,
+            }
+        })
+        .collect();
+    resp
+}
+
+/// 回填歌曲名（目录中找不到的保持为空）
+pub(crate) fn fill_plan_song_names(
+    resp: &mut RksPlanResponse,
+    catalog: &crate::song_contract::SongCatalog,
+) {
+    for chart in resp.plans.iter_mut().flat_map(|p| p.charts.iter_mut()) {
+        chart.song_name = catalog.by_id.get(&chart.song_id).map(|s| s.name.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::startup::chart_loader::ChartConstants;
+
+    fn chart(id: &str, constant: f64, acc: f64) -> ResolvedChart {
+        ResolvedChart {
+            song_id: id.to_string(),
+            difficulty: Difficulty::IN,
+            constant,
+            acc,
+        }
+    }
+
+    fn opts(target_rks: Option<f64>, include_unplayed: bool) -> PlanOptions {
+        PlanOptions {
+            target_rks,
+            include_unplayed,
+            limit: 10,
+            max_charts: 5,
+        }
+    }
+
+    fn base_charts() -> Vec<ResolvedChart> {
+        (0..30)
+            .map(|i| chart(&format!("s{i:02}"), 10.0 + f64::from(i) * 0.1, 95.0))
+            .collect()
+    }
+
+    #[test]
+    fn next_display_plans_meet_threshold_and_are_ranked_by_cost() {
+        let resp = plan_target_rks(base_charts(), &ChartConstantsMap::new(), opts(None, false));
+        assert!(resp.reachable);
+        assert!(!resp.plans.is_empty());
+        for plan in &resp.plans {
+            assert!(plan.resulting_exact_rks >= resp.target_exact_threshold);
+            assert!(plan.charts.iter().all(|c| c.target_acc <= 100.0));
+        }
+        for pair in resp.plans.windows(2) {
+            assert!(pair[0].cost <= pair[1].cost);
+        }
+        assert!((resp.target_display_rks - (resp.current_display_rks + 0.01)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn larger_target_needs_multiple_charts() {
+        let resp = plan_target_rks(
+            base_charts(),
+            &ChartConstantsMap::new(),
+            opts(Some(9.0), false),
+        );
+        assert!(resp.reachable);
+        assert!(resp.plans.iter().all(|p| p.charts.len() > 1));
+        assert!(
+            resp.plans
+                .iter()
+                .all(|p| p.resulting_exact_rks >= resp.target_exact_threshold)
+        );
+    }
+
+    #[test]
+    fn unplayed_charts_are_considered_when_enabled() {
+        let mut constants = ChartConstantsMap::new();
+        constants.insert(
+            "new".to_string(),
+            ChartConstants {
+                ez: None,
+                hd: None,
+                in_level: Some(16.0),
+                at: None,
+            },
+        );
+        let base = vec![chart("a", 10.0, 95.0)];
+        let target = Some(1.0);
+        let without = plan_target_rks(base.clone(), &constants, opts(target, false));
+        let with = plan_target_rks(base, &constants, opts(target, true));
+        assert!(!without.reachable);
+        assert!(with.reachable);
+        let c = &with.plans[0].charts[0];
+        assert_eq!(c.song_id, "new");
+        assert!(c.current_acc.is_none());
+    }
+
+    #[test]
+    fn already_reached_target_returns_no_plans() {
+        let resp = plan_target_rks(
+            base_charts(),
+            &ChartConstantsMap::new(),
+            opts(Some(1.0), false),
+        );
+        assert!(resp.reachable);
+        assert!(resp.plans.is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! RKS 规则版本
+//!
+//! 不同游戏版本的总 RKS 口径不同（Best 数量、Phi/AP 数量、两者是否可重叠、单谱面公式）。
+//! 这里把口径抽象为 [`RksRuleSet`]，由 [`RksRuleVersion`] 选择；新增规则只需新增一个
+//! 版本枚举值与对应的静态规则描述。
+
+use serde::{Deserialize, Serialize};
+
+use super::engine::{RksRecord, calculate_single_chart_rks};
+
+/// RKS 规则版本
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum RksRuleVersion {
+    /// 当前规则：Best27 + AP Top3，/30，AP 谱面可同时计入 Best27
+    #[default]
+    B27Ap3,
+    /// Best27 + AP Top3，/30，AP3 中的谱面不再计入 Best27
+    B27Ap3Exclusive,
+    /// 旧版规则：Best19 + Phi1，/20，Phi 谱面可同时计入 Best19
+    B19Phi1,
+    /// 旧版规则：Best19 + Phi1，/20，Phi 谱面不再计入 Best19
+    B19Phi1Exclusive,
+}
+
+/// 某一版本的 RKS 口径
+#[derive(Debug)]
+pub struct RksRuleSet {
+    pub version: RksRuleVersion,
+    /// Best 部分取前 N 个谱面
+    pub best_count: usize,
+    /// Phi（ACC 100%）部分取前 N 个谱面
+    pub phi_count: usize,
+    /// Phi 部分的谱面是否仍可计入 Best 部分
+    pub overlap: bool,
+    /// 单谱面公式：`(acc 小数, 定数) -> rks`，与 [`calculate_single_chart_rks`] 同签名
+    pub chart_rks: fn(f64, f32) -> f64,
+}
+
+static B27_AP3: RksRuleSet = RksRuleSet {
+    version: RksRuleVersion::B27Ap3,
+    best_count: 27,
+    phi_count: 3,
+    overlap: true,
+    chart_rks: calculate_single_chart_rks,
+};
+
+static B27_AP3_EXCLUSIVE: RksRuleSet = RksRuleSet {
+    version: RksRuleVersion::B27Ap3Exclusive,
+    best_count: 27,
+    phi_count: 3,
+    overlap: false,
+    chart_rks: calculate_single_chart_rks,
+};
+
+static B19_PHI1: RksRuleSet = RksRuleSet {
+    version: RksRuleVersion::B19Phi1,
+    best_count: 19,
+    phi_count: 1,
+    overlap: true,
+    chart_rks: calculate_single_chart_rks,
+};
+
+static B19_PHI1_EXCLUSIVE: RksRuleSet = RksRuleSet {
+    version: RksRuleVersion::B19Phi1Exclusive,
+    best_count: 19,
+    phi_count: 1,
+    overlap: false,
+    chart_rks: calculate_single_chart_rks,
+};
+
+impl RksRuleVersion {
+    pub const ALL: [Self; 4] = [
+        Self::B27Ap3,
+        Self::B27Ap3Exclusive,
+        Self::B19Phi1,
+        Self::B19Phi1Exclusive,
+    ];
+
+    #[must_use]
+    pub fn rule(self) -> &'static RksRuleSet {
+        match self {
+            Self::B27Ap3 => &B27_AP3,
+            Self::B27Ap3Exclusive => &B27_AP3_EXCLUSIVE,
+            Self::B19Phi1 => &B19_PHI1,
+            Self::B19Phi1Exclusive => &B19_PHI1_EXCLUSIVE,
+        }
+    }
+
+    /// 对外标识（与 serde 表示一致，用于查询参数与存储）
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::B27Ap3 => "b27-ap3",
+            Self::B27Ap3Exclusive => "b27-ap3-exclusive",
+            Self::B19Phi1 => "b19-phi1",
+            Self::B19Phi1Exclusive => "b19-phi1-exclusive",
+        }
+    }
+
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim();
+        Self::ALL
+            .into_iter()
+            .find(|v| v.code().eq_ignore_ascii_case(code))
+    }
+
+    /// 是否为当前游戏使用的规则（主排行榜即按此规则）
+    #[must_use]
+    pub fn is_current(self) -> bool {
+        self == Self::default()
+    }
+}
+
+/// 按规则从已按 rks 降序排列的记录中选出的 Best / Phi 部分
+#[derive(Debug, Clone, Default)]
+pub struct RulePick {
+    /// Best 部分在记录中的下标（按 rks 降序）
+    pub best: Vec<usize>,
+    /// Phi 部分在记录中的下标（按 rks 降序）
+    pub phi: Vec<usize>,
+    pub exact_rks: f64,
+    /// 显示 RKS（四舍五入到两位小数）
+    pub display_rks: f64,
+}
+
+impl RksRuleSet {
+    /// 总 RKS 的分母（不足时缺口视为 0）
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // 条数为几十量级，不会损失精度
+    pub fn divisor(&self) -> f64 {
+        (self.best_count + self.phi_count) as f64
+    }
+
+    /// 以百分比 ACC 计算单谱面 RKS
+    #[must_use]
+    pub fn chart_rks_percent(&self, acc_percent: f64, constant: f64) -> f64 {
+        #[allow(clippy::cast_possible_truncation)]
+        let constant = constant as f32;
+        (self.chart_rks)(acc_percent / 100.0, constant)
+    }
+
+    /// 在已按 rks 降序排列的记录上应用规则（`rks` 字段视为已按本规则计算）。
+    #[must_use]
+    pub fn pick_sorted(&self, records: &[RksRecord]) -> RulePick {
+        let phi: Vec<usize> = records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.acc >= 100.0)
+            .take(self.phi_count)
+            .map(|(i, _)| i)
+            .collect();
+        let best: Vec<usize> = (0..records.len())
+            .filter(|i| self.overlap || !phi.contains(i))
+            .take(self.best_count)
+            .collect();
+        let sum: f64 = best.iter().chain(phi.iter()).map(|i| records[*i].rks).sum();
+        let exact_rks = sum / self.divisor();
+        RulePick {
+            best,
+            phi,
+            exact_rks,
+            display_rks: (exact_rks * 100.0).round() / 100.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_contract::Difficulty;
+
+    fn rec(id: &str, acc: f64, rks: f64) -> RksRecord {
+        RksRecord {
+            song_id: id.to_string(),
+            difficulty: Difficulty::IN,
+            score: 0,
+            acc,
+            rks,
+            chart_constant: rks,
+        }
+    }
+
+    #[test]
+    fn rule_codes_roundtrip_through_serde() {
+        for v in RksRuleVersion::ALL {
+            let json = serde_json::to_string(&v).unwrap();
+            assert_eq!(json, format!("\"{}\"", v.code()));
+            assert_eq!(RksRuleVersion::from_code(v.code()), Some(v));
+        }
+        assert!(RksRuleVersion::default().is_current());
+        assert_eq!(RksRuleVersion::from_code("b30"), None);
+    }
+
+    #[test]
+    fn overlap_and_exclusive_pick_differ_when_phi_is_in_best() {
+        // 第一名是 AP，其余非 AP
+        let mut records = vec![rec("ap", 100.0, 15.0)];
+        records.extend((0..20).map(|i| rec(&format!("s{i:02}"), 98.0, 14.0 - f64::from(i) * 0.1)));
+
+        let overlap = RksRuleVersion::B19Phi1.rule().pick_sorted(&records);
+        assert_eq!(overlap.best.len(), 19);
+        assert!(overlap.best.contains(&0));
+        assert_eq!(overlap.phi, vec![0]);
+
+        let exclusive = RksRuleVersion::B19Phi1Exclusive
+            .rule()
+            .pick_sorted(&records);
+        assert!(!exclusive.best.contains(&0));
+        assert_eq!(exclusive.best.len(), 19);
+        assert!(exclusive.exact_rks < overlap.exact_rks);
+    }
+}
@@ -0,0 +1,508 @@
+//! RKS 假设模拟（What-if）
+//!
+//! 在玩家现有成绩（云存档或离线提交的成绩列表）之上叠加一组假设成绩，
+//! 计算新的总 RKS、Best27/AP3 构成以及每个假设谱面的 RKS 变化。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::save_contract::{Difficulty, DifficultyRecord};
+use crate::song_contract::SongCatalog;
+use crate::startup::chart_loader::ChartConstantsMap;
+
+use super::engine::RksRecord;
+use super::rules::{RksRuleSet, RksRuleVersion};
+
+/// 单次请求允许的假设成绩条数上限
+pub(crate) const MAX_SIMULATE_CHANGES: usize = 50;
+/// 离线模式下允许提交的基础成绩条数上限
+pub(crate) const MAX_SIMULATE_RECORDS: usize = 2000;
+
+/// 单条谱面成绩输入（假设成绩或离线基础成绩）
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateChartInput {
+    /// 歌曲 ID 或名称/别名（需唯一命中）
+    #[schema(example = "Glaciaxion")]
+    pub song: String,
+    /// 难度：EZ/HD/IN/AT（大小写不敏感）
+    #[schema(example = "IN")]
+    pub difficulty: String,
+    /// ACC 百分比（0-100，例：99.5 表示 99.5%）
+    #[schema(example = 99.5)]
+    pub acc: f64,
+}
+
+/// RKS 假设模拟请求
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "auth": {"sessionToken": "r:abcdefg.hijklmn"},
+    "changes": [
+        {"song": "Glaciaxion", "difficulty": "IN", "acc": 100.0}
+    ]
+}))]
+pub struct RksSimulateRequest {
+    /// 认证信息（未提供 `records` 时用于拉取云存档；也可仅通过 Bearer 认证）
+    #[serde(default)]
+    pub auth: crate::auth_contract::UnifiedSaveRequest,
+    /// 离线基础成绩列表：提供时不读取存档、不需要认证（最多 2000 条）
+    #[serde(default)]
+    pub records: Option<Vec<SimulateChartInput>>,
+    /// 假设成绩（1-50 条）；与现有成绩取较高 ACC，与游戏内规则一致
+    pub changes: Vec<SimulateChartInput>,
+    /// RKS 规则版本（默认当前规则 b27-ap3）
+    #[serde(default)]
+    pub rule: RksRuleVersion,
+    /// 定数表版本：current（默认，线上定数）/ auto（按存档 game_version 匹配）/ 归档版本标识
+    #[serde(default)]
+    pub constants_version: Option<String>,
+}
+
+/// 模拟结果中的单个谱面条目
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateChartItem {
+    #[schema(example = "Glaciaxion.SunsetRay")]
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    pub difficulty: Difficulty,
+    /// 定数
+    #[schema(example = 12.7)]
+    pub constant: f64,
+    /// ACC 百分比
+    #[schema(example = 99.5)]
+    pub acc: f64,
+    /// 谱面 RKS
+    #[schema(example = 12.42)]
+    pub rks: f64,
+}
+
+/// 某一时刻（模拟前/后）的 RKS 构成
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateRksSnapshot {
+    /// 精确 RKS
+    pub exact_rks: f64,
+    /// 游戏内显示 RKS（保留两位小数）
+    pub display_rks: f64,
+    /// Best 部分（当前规则为 Best27，按 RKS 降序）
+    pub best: Vec<SimulateChartItem>,
+    /// Phi 部分（当前规则为 AP Top3：ACC 100% 中 RKS 最高的 3 个）
+    pub phi: Vec<SimulateChartItem>,
+}
+
+/// 单个假设谱面的变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateChartDelta {
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    pub difficulty: Difficulty,
+    pub constant: f64,
+    /// 原有 ACC；此前未游玩时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_acc: Option<f64>,
+    /// 生效 ACC（原有与假设中的较高者）
+    pub new_acc: f64,
+    pub old_rks: f64,
+    pub new_rks: f64,
+    /// 谱面 RKS 变化量
+    pub delta_chart_rks: f64,
+    /// 模拟后是否位于 Best 部分
+    pub in_best: bool,
+    /// 模拟后是否位于 Phi 部分
+    pub in_phi: bool,
+}
+
+/// RKS 假设模拟响应
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksSimulateResponse {
+    /// 计算所用的规则版本
+    pub rule: RksRuleVersion,
+    /// 计算所用的定数表版本（线上定数为 current）
+    pub constants_version: String,
+    pub before: SimulateRksSnapshot,
+    pub after: SimulateRksSnapshot,
+    /// 总 RKS 变化量（精确值）
+    pub delta_rks: f64,
+    /// 每个假设谱面的变化（按请求顺序，同一谱面合并为一条）
+    pub charts: Vec<SimulateChartDelta>,
+}
+
+/// 已解析的谱面成绩（歌曲、难度、定数均已确定）
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedChart {
+    pub song_id: String,
+    pub difficulty: Difficulty,
+    pub constant: f64,
+    pub acc: f64,
+}
+
+pub(crate) fn parse_difficulty(input: &str) -> Option<Difficulty> {
+    match input.trim().to_ascii_uppercase().as_str() {
+        "EZ" => Some(Difficulty::EZ),
+        "HD" => Some(Difficulty::HD),
+        "IN" => Some(Difficulty::IN),
+        "AT" => Some(Difficulty::AT),
+        _ => None,
+    }
+}
+
+/// 校验请求规模（不涉及歌曲解析）
+pub(crate) fn validate_simulate_request(req: &RksSimulateRequest) -> Result<(), AppError> {
+    if req.changes.is_empty() || req.changes.len() > MAX_SIMULATE_CHANGES {
+        return Err(AppError::Validation(format!(
+            "changes 条数必须在 1-{MAX_SIMULATE_CHANGES} 之间"
+        )));
+    }
+    if let Some(records) = req.records.as_ref()
+        && records.len() > MAX_SIMULATE_RECORDS
+    {
+        return Err(AppError::Validation(format!(
+            "records 最多 {MAX_SIMULATE_RECORDS} 条"
+        )));
+    }
+    Ok(())
+}
+
+/// 将输入解析为谱面：歌曲走目录唯一搜索，定数取自同一份目录快照。
+pub(crate) fn resolve_chart_inputs(
+    field: &str,
+    inputs: &[SimulateChartInput],
+    catalog: &SongCatalog,
+) -> Result<Vec<ResolvedChart>, AppError> {
+    let mut lookup_cache = HashMap::<&str, Arc<_>>::new();
+    let mut out = Vec::with_capacity(inputs.len());
+    for (idx, input) in inputs.iter().enumerate() {
+        if !input.acc.is_finite() || !(0.0..=100.0).contains(&input.acc) {
+            return Err(AppError::Validation(format!(
+                "{field}[{idx}].acc 必须在 0-100 之间"
+            )));
+        }
+        let Some(difficulty) = parse_difficulty(&input.difficulty) else {
+            return Err(AppError::Validation(format!(
+                "{field}[{idx}].difficulty 无效：{}（可选 EZ/HD/IN/AT）",
+                input.difficulty
+            )));
+        };
+        let key = input.song.trim();
+        let info = if let Some(info) = lookup_cache.get(key) {
+            Arc::clone(info)
+        } else {
+            let info = catalog.search_unique(key).map_err(AppError::Search)?;
+            lookup_cache.insert(key, Arc::clone(&info));
+            info
+        };
+        let Some(constant) =
+            super::engine::level_for_difficulty(&info.chart_constants, &difficulty)
+                .map(f64::from)
+                .filter(|c| *c > 0.0)
+        else {
+            return Err(AppError::Validation(format!(
+                "{field}[{idx}]：歌曲「{}」没有 {difficulty} 难度定数",
+                info.name
+            )));
+        };
+        out.push(ResolvedChart {
+            song_id: info.id.clone(),
+            difficulty,
+            constant,
+            acc: input.acc,
+        });
+    }
+    Ok(out)
+}
+
+/// 将存档成绩转换为谱面列表（缺少定数的谱面不参与 RKS 计算）
+pub(crate) fn charts_from_game_record(
+    game_record: &HashMap<String, Vec<DifficultyRecord>>,
+) -> Vec<ResolvedChart> {
+    game_record
+        .iter()
+        .flat_map(|(song_id, diffs)| {
+            diffs.iter().filter_map(|rec| {
+                let constant = f64::from(rec.chart_constant?);
+                Some(ResolvedChart {
+                    song_id: song_id.clone(),
+                    difficulty: rec.difficulty,
+                    constant,
+                    acc: f64::from(rec.accuracy),
+                })
+            })
+        })
+        .collect()
+}
+
+/// 按指定定数表重新确定谱面定数；该表中没有定数的谱面被丢弃。
+pub(crate) fn rebase_constants(
+    charts: Vec<ResolvedChart>,
+    constants: &ChartConstantsMap,
+) -> Vec<ResolvedChart> {
+    charts
+        .into_iter()
+        .filter_map(|mut chart| {
+            let level = constants
+                .get(&chart.song_id)
+                .and_then(|c| super::engine::level_for_difficulty(c, &chart.difficulty))
+                .map(f64::from)
+                .filter(|c| *c > 0.0)?;
+            chart.constant = level;
+            Some(chart)
+        })
+        .collect()
+}
+
+pub(super) type ChartKey = (String, Difficulty);
+
+/// 按谱面合并，同一谱面保留最高 ACC
+pub(super) fn merge_best(
+    charts: impl IntoIterator<Item = ResolvedChart>,
+) -> HashMap<ChartKey, ResolvedChart> {
+    let mut map = HashMap::<ChartKey, ResolvedChart>::new();
+    for chart in charts {
+        let key = (chart.song_id.clone(), chart.difficulty);
+        match map.get_mut(&key) {
+            Some(existing) if existing.acc >= chart.acc => {}
+            Some(existing) => *existing = chart,
+            None => {
+                map.insert(key, chart);
+            }
+        }
+    }
+    map
+}
+
+fn sorted_records(map: &HashMap<ChartKey, ResolvedChart>, rule: &RksRuleSet) -> Vec<RksRecord> {
+    let mut records: Vec<RksRecord> = map
+        .values()
+        .map(|c| RksRecord {
+            song_id: c.song_id.clone(),
+            difficulty: c.difficulty,
+            score: 0,
+            acc: c.acc,
+            rks: rule.chart_rks_percent(c.acc, c.constant),
+            chart_constant: c.constant,
+        })
+        .collect();
+    // 同分时按谱面键排序，保证结果稳定
+    records.sort_by(|a, b| {
+        b.rks
+            .total_cmp(&a.rks)
+            .then_with(|| a.song_id.cmp(&b.song_id))
+            .then_with(|| (a.difficulty as u8).cmp(&(b.difficulty as u8)))
+    });
+    records
+}
+
+fn chart_item(record: &RksRecord) -> SimulateChartItem {
+    SimulateChartItem {
+        song_id: record.song_id.clone(),
+        song_name: None,
+        difficulty: record.difficulty,
+        constant: record.chart_constant,
+        acc: record.acc,
+        rks: record.rks,
+    }
+}
+
+fn snapshot(records: &[RksRecord], rule: &RksRuleSet) -> SimulateRksSnapshot {
+    let pick = rule.pick_sorted(records);
+    SimulateRksSnapshot {
+        exact_rks: pick.exact_rks,
+        display_rks: pick.display_rks,
+        best: pick.best.iter().map(|i| chart_item(&records[*i])).collect(),
+        phi: pick.phi.iter().map(|i| chart_item(&records[*i])).collect(),
+    }
+}
+
+/// 核心模拟：基础成绩 + 假设成绩 -> 前后构成与逐谱面变化（不含歌曲名）
+pub(crate) fn simulate_rks(
+    base: Vec<ResolvedChart>,
+    changes: Vec<ResolvedChart>,
+    version: RksRuleVersion,
+) -> RksSimulateResponse {
+    let rule = version.rule();
+    let before_map = merge_best(base);
+    let mut after_map = before_map.clone();
+    let mut change_order: Vec<ChartKey> = Vec::with_capacity(changes.len());
+    for chart in changes {
+        let key = (chart.song_id.clone(), chart.difficulty);
+        if !change_order.contains(&key) {
+            change_order.push(key.clone());
+        }
+        match after_map.get_mut(&key) {
+            Some(existing) if existing.acc >= chart.acc => {}
+            Some(existing) => *existing = chart,
+            None => {
+                after_map.insert(key, chart);
+            }
+        }
+    }
+
+    let before = snapshot(&sorted_records(&before_map, rule), rule);
+    let after = snapshot(&sorted_records(&after_map, rule), rule);
+
+    let charts = change_order
+        .into_iter()
+        .filter_map(|key| {
+            let new = after_map.get(&key)?;
+            let old = before_map.get(&key);
+            let old_rks = old.map_or(0.0, |c| rule.chart_rks_percent(c.acc, c.constant));
+            let new_rks = rule.chart_rks_percent(new.acc, new.constant);
+            let same = |item: &SimulateChartItem| item.song_id == key.0 && item.difficulty == key.1;
+            Some(SimulateChartDelta {
+                song_id: key.0.clone(),
+                song_name: None,
+                difficulty: key.1,
+                constant: new.constant,
+                old_acc: old.map(|c| c.acc),
+                new_acc: new.acc,
+                old_rks,
+                new_rks,
+                delta_chart_rks: new_rks - old_rks,
+                in_best: after.best.iter().any(same),
+                in_phi: after.phi.iter().any(same),
+            })
+        })
+        .collect();
+
+    RksSimulateResponse {
+        rule: version,
+        constants_version: crate::constant_tables::CURRENT_VERSION.to_string(),
+        delta_rks: after.exact_rks - before.exact_rks,
+        before,
+        after,
+        charts,
+    }
+}
+
+/// 回填歌曲名（目录中找不到的保持为空）
+pub(crate) fn fill_song_names(resp: &mut RksSimulateResponse, catalog: &SongCatalog) {
+    let name_of = |id: &str| catalog.by_id.get(id).map(|s| s.name.clone());
+    for item in resp
+        .before
+        .best
+        .iter_mut()
+        .chain(resp.before.phi.iter_mut())
+        .chain(resp.after.best.iter_mut())
+        .chain(resp.after.phi.iter_mut())
+    {
+        item.song_name = name_of(&item.song_id);
+    }
+    for delta in &mut resp.charts {
+        delta.song_name = name_of(&delta.song_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(id: &str, difficulty: Difficulty, constant: f64, acc: f64) -> ResolvedChart {
+        ResolvedChart {
+            song_id: id.to_string(),
+            difficulty,
+            constant,
+            acc,
+        }
+    }
+
+    #[test]
+    fn simulate_keeps_higher_existing_acc() {
+        let base = vec![chart("a", Difficulty::IN, 12.0, 99.0)];
+        let resp = simulate_rks(
+            base,
+            vec![chart("a", Difficulty::IN, 12.0, 95.0)],
+            RksRuleVersion::B27Ap3,
+        );
+        assert_eq!(resp.charts.len(), 1);
+        assert!((resp.charts[0].new_acc - 99.0).abs() < 1e-9);
+        assert!(resp.charts[0].delta_chart_rks.abs() < 1e-12);
+        assert!(resp.delta_rks.abs() < 1e-12);
+    }
+
+    #[test]
+    fn simulate_new_ap_enters_ap3_and_raises_total() {
+        let base = vec![
+            chart("a", Difficulty::IN, 12.0, 98.0),
+            chart("b", Difficulty::AT, 14.0, 97.0),
+        ];
+        let resp = simulate_rks(
+            base,
+            vec![chart("b", Difficulty::AT, 14.0, 100.0)],
+            RksRuleVersion::B27Ap3,
+        );
+        assert!(resp.before.phi.is_empty());
+        assert_eq!(resp.after.phi.len(), 1);
+        let delta = &resp.charts[0];
+        assert!(delta.in_best && delta.in_phi);
+        assert_eq!(delta.old_acc, Some(97.0));
+        assert!((delta.new_rks - 14.0).abs() < 1e-9);
+        // AP 谱面同时计入 Best27 与 AP3
+        let expected =
+            (14.0 + 14.0 + crate::rks_contract::engine::calculate_chart_rks(98.0, 12.0)) / 30.0;
+        assert!((resp.after.exact_rks - expected).abs() < 1e-9);
+        assert!(resp.delta_rks > 0.0);
+    }
+
+    #[test]
+    fn simulate_reports_composition_change_beyond_best27() {
+        let base: Vec<_> = (0..30)
+            .map(|i| chart(&format!("s{i:02}"), Difficulty::IN, 10.0, 95.0))
+            .collect();
+        let resp = simulate_rks(
+            base,
+            vec![chart("new", Difficulty::HD, 8.0, 90.0)],
+            RksRuleVersion::B27Ap3,
+        );
+        let delta = &resp.charts[0];
+        assert_eq!(delta.old_acc, None);
+        assert!(!delta.in_best);
+        assert!(resp.delta_rks.abs() < 1e-12);
+        assert_eq!(resp.after.best.len(), 27);
+    }
+
+    #[test]
+    fn simulate_honours_legacy_rule() {
+        let base: Vec<_> = (0..25)
+            .map(|i| chart(&format!("s{i:02}"), Difficulty::IN, 10.0, 95.0))
+            .collect();
+        let resp = simulate_rks(
+            base,
+            vec![chart("s00", Difficulty::IN, 10.0, 100.0)],
+            RksRuleVersion::B19Phi1,
+        );
+        assert_eq!(resp.rule, RksRuleVersion::B19Phi1);
+        assert_eq!(resp.after.best.len(), 19);
+        assert_eq!(resp.after.phi.len(), 1);
+        let rks95 = crate::rks_contract::engine::calculate_chart_rks(95.0, 10.0);
+        let expected = (10.0 + 18.0 * rks95 + 10.0) / 20.0;
+        assert!((resp.after.exact_rks - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn validate_simulate_request_limits_changes() {
+        let req = RksSimulateRequest {
+            auth: crate::auth_contract::UnifiedSaveRequest::default(),
+            records: None,
+            changes: Vec::new(),
+            rule: RksRuleVersion::default(),
+            constants_version: None,
+        };
+        assert!(validate_simulate_request(&req).is_err());
+    }
+
+    #[test]
+    fn parse_difficulty_is_case_insensitive() {
+        assert_eq!(parse_difficulty(" at "), Some(Difficulty::AT));
+        assert_eq!(parse_difficulty("hd"), Some(Difficulty::HD));
+        assert_eq!(parse_difficulty("SP"), None);
+    }
+}
@@ -4,8 +4,8 @@ use aes_gcm::{
     aead::{Aead, Payload},
 };
 use cbc::{
-    Decryptor as CbcDecryptor,
-    cipher::{BlockDecryptMut, KeyIvInit},
+    Decryptor as CbcDecryptor, Encryptor as CbcEncryptor,
+    cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit},
 };
 use hmac::{Hmac, Mac};
 use pbkdf2::pbkdf2_hmac;
@@ -138,6 +138,27 @@ pub fn decrypt_zip_entry_with_derived_key(
     }
 }
 
+/// 使用默认 AES-256-CBC 参数加密一个 zip entry（`decrypt_zip_entry` 在默认元信息下的逆操作）。
+///
+/// `plain` 的第 1 字节为 entry 前缀，原样保留在密文前；用于构造离线存档夹具与本地模拟。
+pub fn encrypt_zip_entry(plain: &[u8]) -> Result<Vec<u8>, SaveProviderError> {
+    let (&prefix, body) = plain
+        .split_first()
+        .ok_or(SaveProviderError::InvalidHeader)?;
+    use cipher::block_padding::Pkcs7;
+    type Aes256CbcEnc = CbcEncryptor<Aes256>;
+    let mut buf = vec![0u8; 1 + body.len() + 16];
+    buf[0] = prefix;
+    buf[1..=body.len()].copy_from_slice(body);
+    let enc = Aes256CbcEnc::new((&DEFAULT_KEY).into(), (&DEFAULT_IV).into());
+    let ct_len = enc
+        .encrypt_padded_mut::<Pkcs7>(&mut buf[1..], body.len())
+        .map_err(|e| SaveProviderError::Decrypt(format!("AES 加密失败: {e:?}")))?
+        .len();
+    buf.truncate(1 + ct_len);
+    Ok(buf)
+}
+
 fn decrypt_aes256_cbc_in_place(
     ciphertext: &mut [u8],
     key: &[u8; 32],
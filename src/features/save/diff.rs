@@ -0,0 +1,708 @@
+//! 存档差异（两份已解析存档之间的结构化变化）
+//!
+//! 覆盖谱面成绩（新游玩/提升/回退、FC/AP 达成）、`gameKey` 中的收藏品/曲绘/头像/单曲解锁、
+//! 课题模式等级、Data（money）、设置与个人资料变化，以及按当前规则计算的 RKS 变化和逐谱面贡献。
+//!
+//! 差异在 [`SaveBaseline`] 上计算：它只保留上述要素，也是服务端保存的“上次同步”基准的格式。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use phi_save_codec::game_key::{Key, NormalKey};
+use serde::{Deserialize, Serialize};
+
+use crate::game_data::GameData;
+use crate::rks_contract::engine::{
+    PlayerRksResult, calculate_chart_rks, calculate_player_rks, level_for_difficulty,
+};
+use crate::rks_contract::rules::RksRuleVersion;
+
+use super::models::{Difficulty, DifficultyRecord};
+use super::parser::{SettingsParsed, UserParsed};
+use super::provider::ParsedSave;
+
+/// 视为“无变化”的 ACC / RKS 阈值
+const EPS: f64 = 1e-6;
+
+/// 单谱面成绩
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartScore {
+    pub score: u32,
+    /// ACC 百分比
+    pub acc: f64,
+    pub is_full_combo: bool,
+}
+
+/// 谱面成绩变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartChangeKind {
+    /// 新游玩的谱面
+    NewlyPlayed,
+    /// 分数/ACC 提升或新达成 FC
+    Improved,
+    /// 成绩回退（例如存档回滚）
+    Regressed,
+}
+
+/// 单谱面成绩变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartDiffItem {
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    pub difficulty: Difficulty,
+    pub kind: ChartChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<ChartScore>,
+    pub after: ChartScore,
+    /// 分数变化量
+    pub score_delta: i64,
+    /// ACC 变化量（百分点）
+    pub acc_delta: f64,
+    /// 本次新达成 FC
+    pub fc_gained: bool,
+    /// 本次新达成 AP（ACC 100%）
+    pub ap_gained: bool,
+    /// 该谱面单曲 RKS 变化量（按当前定数；无定数时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chart_rks_delta: Option<f64>,
+}
+
+/// 收藏品进度变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionProgress {
+    pub key: String,
+    pub before: u8,
+    pub after: u8,
+}
+
+/// `gameKey` 解锁变化（仅列出本次新解锁的键）
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockDiff {
+    /// 新解锁的单曲
+    pub songs: Vec<String>,
+    /// 新解锁的曲绘
+    pub illustrations: Vec<String>,
+    /// 新解锁的头像
+    pub avatars: Vec<String>,
+    /// 收藏品碎片解锁数增加的键
+    pub collections: Vec<CollectionProgress>,
+}
+
+impl UnlockDiff {
+    fn count(&self) -> usize {
+        self.songs.len() + self.illustrations.len() + self.avatars.len() + self.collections.len()
+    }
+}
+
+/// 数值变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeRankChange {
+    /// 课题模式等级（百位为颜色：1 绿/2 蓝/3 红/4 金/5 彩；其余为等级和）
+    pub before: Option<u16>,
+    pub after: Option<u16>,
+}
+
+/// Data（money）变化；五档依次为 KB/MB/GB/TB/PB
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MoneyDiff {
+    pub before: [i32; 5],
+    pub after: [i32; 5],
+    /// 各档变化量
+    pub delta: [i64; 5],
+    /// 折算为 KB 的总变化量
+    pub delta_kb: i64,
+}
+
+/// 单个字段的变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    #[schema(value_type = Object)]
+    pub before: serde_json::Value,
+    #[schema(value_type = Object)]
+    pub after: serde_json::Value,
+}
+
+/// 单谱面对总 RKS 的贡献变化
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksContribution {
+    pub song_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_name: Option<String>,
+    pub difficulty: Difficulty,
+    /// 变化前对总 RKS 的贡献（未入选 Best/AP 时为 0）
+    pub before: f64,
+    pub after: f64,
+    pub delta: f64,
+}
+
+/// RKS 变化（按当前规则与线上定数）
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RksDiff {
+    pub before: f64,
+    pub after: f64,
+    pub delta: f64,
+    /// 贡献有变化的谱面，按变化量降序
+    pub contributions: Vec<RksContribution>,
+}
+
+/// 差异计数
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDiffSummary {
+    pub newly_played: usize,
+    pub improved: usize,
+    pub regressed: usize,
+    pub fc_gained: usize,
+    pub ap_gained: usize,
+    pub unlocks: usize,
+    pub settings_changed: usize,
+}
+
+/// 两份存档之间的差异
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDiff {
+    /// 基准存档的 updatedAt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_updated_at: Option<String>,
+    /// 对比存档的 updatedAt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_updated_at: Option<String>,
+    pub summary: SaveDiffSummary,
+    /// 谱面成绩变化（按歌曲 ID 与难度排序）
+    pub charts: Vec<ChartDiffItem>,
+    pub unlocks: UnlockDiff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_mode_rank: Option<ChallengeRankChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub money: Option<MoneyDiff>,
+    /// 设置变化（settings）
+    pub settings: Vec<FieldChange>,
+    /// 个人资料变化（user：头像/背景/简介等）
+    pub profile: Vec<FieldChange>,
+    pub rks: RksDiff,
+}
+
+/// `/save/diff` 响应
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncDiffResponse {
+    /// 是否存在基准快照；首次调用时为 false，此时只保存当前存档作为基准
+    pub has_baseline: bool,
+    /// 基准快照的写入时间（RFC3339）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline_stored_at: Option<String>,
+    /// 当前存档是否已保存为新的基准（commit=false 时不保存）
+    pub committed: bool,
+    /// 基准 → 当前存档的差异；无基准时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<SaveDiff>,
+}
+
+type ChartKey = (String, Difficulty);
+
+/// 基准中单个谱面的成绩（字段名压缩以减小存储体积）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineChart {
+    #[serde(rename = "s")]
+    pub song_id: String,
+    #[serde(rename = "d")]
+    pub difficulty: Difficulty,
+    #[serde(rename = "sc")]
+    pub score: u32,
+    /// ACC 百分比
+    #[serde(rename = "a")]
+    pub acc: f32,
+    #[serde(rename = "fc", default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_full_combo: bool,
+}
+
+/// 存档差异基准：只保留差异计算用到的要素（逐谱面成绩、解锁状态、课题等级、Data、设置与个人资料），
+/// 以紧凑 JSON 写入 `save_snapshot.snapshot_json`。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveBaseline {
+    #[serde(rename = "v")]
+    pub version: u8,
+    #[serde(rename = "u", default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// 已游玩谱面，按曲目 ID 与难度排序
+    #[serde(rename = "c")]
+    pub charts: Vec<BaselineChart>,
+    /// `gameKey` 中可解析的键（不含 Raw）
+    #[serde(rename = "k", default)]
+    pub unlocks: BTreeMap<String, NormalKey>,
+    #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+    pub challenge_mode_rank: Option<u16>,
+    #[serde(rename = "m", default, skip_serializing_if = "Option::is_none")]
+    pub money: Option<[i32; 5]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<SettingsParsed>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<UserParsed>,
+}
+
+impl SaveBaseline {
+    pub const VERSION: u8 = 1;
+
+    #[must_use]
+    pub fn from_parsed(save: &ParsedSave) -> Self {
+        let mut charts: Vec<BaselineChart> = save
+            .game_record
+            .iter()
+            .flat_map(|(song_id, diffs)| {
+                diffs
+                    .iter()
+                    .filter(|r| played(r))
+                    .map(move |r| BaselineChart {
+                        song_id: song_id.clone(),
+                        difficulty: r.difficulty,
+                        score: r.score,
+                        acc: r.accuracy,
+                        is_full_combo: r.is_full_combo,
+                    })
+            })
+            .collect();
+        charts.sort_by(|a, b| {
+            a.song_id
+                .cmp(&b.song_id)
+                .then_with(|| (a.difficulty as u8).cmp(&(b.difficulty as u8)))
+        });
+        let unlocks = save
+            .game_key
+            .as_ref()
+            .map(|gk| {
+                gk.keys
+                    .iter()
+                    .filter_map(|(k, v)| match v {
+                        Key::Normal(n) => Some((k.clone(), n.clone())),
+                        Key::Raw(_) => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            version: Self::VERSION,
+            updated_at: save.updated_at.clone(),
+            charts,
+            unlocks,
+            challenge_mode_rank: save
+                .game_progress
+                .as_ref()
+                .and_then(|p| p.challenge_mode_rank)
+                .or_else(|| save.summary_parsed.as_ref().map(|s| s.challenge_mode_rank)),
+            money: save.game_progress.as_ref().and_then(|p| p.money),
+            settings: save.settings.clone(),
+            user: save.user.clone(),
+        }
+    }
+
+    /// 还原为 RKS 计算所需的存档成绩
+    fn to_game_record(&self) -> HashMap<String, Vec<DifficultyRecord>> {
+        let mut out: HashMap<String, Vec<DifficultyRecord>> = HashMap::new();
+        for c in &self.charts {
+            out.entry(c.song_id.clone())
+                .or_default()
+                .push(DifficultyRecord {
+                    difficulty: c.difficulty,
+                    score: c.score,
+                    accuracy: c.acc,
+                    is_full_combo: c.is_full_combo,
+                    chart_constant: None,
+                    push_acc: None,
+                    push_acc_hint: None,
+                });
+        }
+        out
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string(self).ok()
+    }
+
+    /// 解析 `snapshot_json`；格式不符或版本不支持时返回 None。
+    #[must_use]
+    pub fn from_json(raw: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(raw)
+            .ok()
+            .filter(|s| s.version == Self::VERSION)
+    }
+}
+
+fn played(rec: &DifficultyRecord) -> bool {
+    rec.score > 0 || rec.accuracy > 0.0
+}
+
+fn chart_score(c: &BaselineChart) -> ChartScore {
+    ChartScore {
+        score: c.score,
+        acc: f64::from(c.acc),
+        is_full_combo: c.is_full_combo,
+    }
+}
+
+fn index_charts(save: &SaveBaseline) -> BTreeMap<ChartKey, &BaselineChart> {
+    save.charts
+        .iter()
+        .map(|c| ((c.song_id.clone(), c.difficulty), c))
+        .collect()
+}
+
+fn diff_charts(before: &SaveBaseline, after: &SaveBaseline, game: &GameData) -> Vec<ChartDiffItem> {
+    let old = index_charts(before);
+    let new = index_charts(after);
+    let mut out = Vec::new();
+    for ((song_id, difficulty), rec) in &new {
+        let prev = old.get(&(song_id.clone(), *difficulty)).copied();
+        let after_score = chart_score(rec);
+        let before_score = prev.map(chart_score);
+        let kind = match &before_score {
+            None => ChartChangeKind::NewlyPlayed,
+            Some(b) => {
+                let unchanged = b.score == after_score.score
+                    && (b.acc - after_score.acc).abs() < EPS
+                    && b.is_full_combo == after_score.is_full_combo;
+                if unchanged {
+                    continue;
+                }
+                let improved = after_score.score > b.score
+                    || after_score.acc > b.acc + EPS
+                    || (after_score.is_full_combo && !b.is_full_combo);
+                if improved {
+                    ChartChangeKind::Improved
+                } else {
+                    ChartChangeKind::Regressed
+                }
+            }
+        };
+        let (b_score, b_acc, b_fc) = before_score
+            .as_ref()
+            .map_or((0, 0.0, false), |b| (b.score, b.acc, b.is_full_combo));
+        let chart_rks_delta = game
+            .chart_constants
+            .get(song_id)
+            .and_then(|c| level_for_difficulty(c, difficulty))
+            .map(|level| {
+                calculate_chart_rks(after_score.acc, f64::from(level))
+                    - calculate_chart_rks(b_acc, f64::from(level))
+            });
+        out.push(ChartDiffItem {
+            song_id: song_id.clone(),
+            song_name: game.song_catalog.by_id.get(song_id).map(|s| s.name.clone()),
+            difficulty: *difficulty,
+            kind,
+            score_delta: i64::from(after_score.score) - i64::from(b_score),
+            acc_delta: after_score.acc - b_acc,
+            fc_gained: after_score.is_full_combo && !b_fc,
+            ap_gained: after_score.acc >= 100.0 - EPS && b_acc < 100.0 - EPS,
+            chart_rks_delta,
+            before: before_score,
+            after: after_score,
+        });
+    }
+    out
+}
+
+fn diff_unlocks(
+    old: &BTreeMap<String, NormalKey>,
+    new: &BTreeMap<String, NormalKey>,
+) -> UnlockDiff {
+    let mut out = UnlockDiff::default();
+    for (key, n) in new {
+        let o = old.get(key);
+        let gained = |now: Option<bool>, prev: Option<Option<bool>>| {
+            now == Some(true) && prev.flatten() != Some(true)
+        };
+        if gained(n.unlock_single, o.map(|o| o.unlock_single)) {
+            out.songs.push(key.clone());
+        }
+        if gained(n.unlock_illustration, o.map(|o| o.unlock_illustration)) {
+            out.illustrations.push(key.clone());
+        }
+        if gained(n.unlock_avatar, o.map(|o| o.unlock_avatar)) {
+            out.avatars.push(key.clone());
+        }
+        let before_pieces = o.and_then(|o| o.unlock_collection_piece_num).unwrap_or(0);
+        let after_pieces = n.unlock_collection_piece_num.unwrap_or(0);
+        if after_pieces > before_pieces {
+            out.collections.push(CollectionProgress {
+                key: key.clone(),
+                before: before_pieces,
+                after: after_pieces,
+            });
+        }
+    }
+    out
+}
+
+fn diff_money(before: &SaveBaseline, after: &SaveBaseline) -> Option<MoneyDiff> {
+    let old = before.money.unwrap_or_default();
+    let new = after.money?;
+    if old == new {
+        return None;
+    }
+    let delta: [i64; 5] = std::array::from_fn(|i| i64::from(new[i]) - i64::from(old[i]));
+    let delta_kb = delta
+        .iter()
+        .enumerate()
+        .map(|(i, d)| d.saturating_mul(1_i64 << (10 * i)))
+        .fold(0_i64, i64::saturating_add);
+    Some(MoneyDiff {
+        before: old,
+        after: new,
+        delta,
+        delta_kb,
+    })
+}
+
+/// 逐字段比较两个可序列化结构（按 JSON 字段名）。
+fn diff_fields<T: Serialize>(before: Option<&T>, after: Option<&T>) -> Vec<FieldChange> {
+    let to_map = |v: Option<&T>| match v.map(serde_json::to_value) {
+        Some(Ok(serde_json::Value::Object(m))) => m,
+        _ => serde_json::Map::new(),
+    };
+    let old = to_map(before);
+    let new = to_map(after);
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let b = old.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            let a = new.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            (b != a).then(|| FieldChange {
+                field: field.clone(),
+                before: b,
+                after: a,
+            })
+        })
+        .collect()
+}
+
+fn contributions(rks: &PlayerRksResult, divisor: f64) -> HashMap<ChartKey, f64> {
+    let mut out: HashMap<ChartKey, f64> = HashMap::new();
+    for c in &rks.b30_charts {
+        *out.entry((c.song_id.clone(), c.difficulty)).or_default() += c.rks / divisor;
+    }
+    out
+}
+
+fn diff_rks(before: &SaveBaseline, after: &SaveBaseline, game: &GameData) -> RksDiff {
+    let divisor = RksRuleVersion::default().rule().divisor();
+    let old = calculate_player_rks(&before.to_game_record(), &game.chart_constants);
+    let new = calculate_player_rks(&after.to_game_record(), &game.chart_constants);
+    let old_c = contributions(&old, divisor);
+    let new_c = contributions(&new, divisor);
+    let keys: BTreeSet<&ChartKey> = old_c.keys().chain(new_c.keys()).collect();
+    let mut items: Vec<RksContribution> = keys
+        .into_iter()
+        .filter_map(|key| {
+            let b = old_c.get(key).copied().unwrap_or(0.0);
+            let a = new_c.get(key).copied().unwrap_or(0.0);
+            ((a - b).abs() >= EPS).then(|| RksContribution {
+                song_id: key.0.clone(),
+                song_name: game.song_catalog.by_id.get(&key.0).map(|s| s.name.clone()),
+                difficulty: key.1,
+                before: b,
+                after: a,
+                delta: a - b,
+            })
+        })
+        .collect();
+    items.sort_by(|x, y| y.delta.total_cmp(&x.delta));
+    RksDiff {
+        before: old.total_rks,
+        after: new.total_rks,
+        delta: new.total_rks - old.total_rks,
+        contributions: items,
+    }
+}
+
+/// 计算 `before` → `after` 的存档差异；谱面 RKS 按 `game` 的线上定数与当前规则计算。
+#[must_use]
+pub fn diff_saves(before: &ParsedSave, after: &ParsedSave, game: &GameData) -> SaveDiff {
+    diff_baselines(
+        &SaveBaseline::from_parsed(before),
+        &SaveBaseline::from_parsed(after),
+        game,
+    )
+}
+
+/// 与 [`diff_saves`] 相同，输入为差异基准形式（服务端保存的基准与当前存档对比时使用）。
+#[must_use]
+pub fn diff_baselines(before: &SaveBaseline, after: &SaveBaseline, game: &GameData) -> SaveDiff {
+    let charts = diff_charts(before, after, game);
+    let unlocks = diff_unlocks(&before.unlocks, &after.unlocks);
+    let (rank_before, rank_after) = (before.challenge_mode_rank, after.challenge_mode_rank);
+    let challenge_mode_rank =
+        (rank_before != rank_after && rank_after.is_some()).then_some(ChallengeRankChange {
+            before: rank_before,
+            after: rank_after,
+        });
+    let settings = diff_fields(before.settings.as_ref(), after.settings.as_ref());
+    let profile = diff_fields(before.user.as_ref(), after.user.as_ref());
+
+    let count = |k: ChartChangeKind| charts.iter().filter(|c| c.kind == k).count();
+    let summary = SaveDiffSummary {
+        newly_played: count(ChartChangeKind::NewlyPlayed),
+        improved: count(ChartChangeKind::Improved),
+        regressed: count(ChartChangeKind::Regressed),
+        fc_gained: charts.iter().filter(|c| c.fc_gained).count(),
+        ap_gained: charts.iter().filter(|c| c.ap_gained).count(),
+        unlocks: unlocks.count(),
+        settings_changed: settings.len(),
+    };
+    SaveDiff {
+        before_updated_at: before.updated_at.clone(),
+        after_updated_at: after.updated_at.clone(),
+        summary,
+        rks: diff_rks(before, after, game),
+        charts,
+        unlocks,
+        challenge_mode_rank,
+        money: diff_money(before, after),
+        settings,
+        profile,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::song_contract::SongCatalog;
+    use crate::startup::chart_loader::ChartConstants;
+    use std::sync::Arc;
+
+    fn rec(difficulty: Difficulty, score: u32, accuracy: f32, fc: bool) -> DifficultyRecord {
+        DifficultyRecord {
+            difficulty,
+            score,
+            accuracy,
+            is_full_combo: fc,
+            chart_constant: None,
+            push_acc: None,
+            push_acc_hint: None,
+        }
+    }
+
+    fn save(records: Vec<(&str, DifficultyRecord)>, money: Option<[i32; 5]>) -> ParsedSave {
+        let mut game_record: HashMap<String, Vec<DifficultyRecord>> = HashMap::new();
+        for (id, r) in records {
+            game_record.entry(id.to_string()).or_default().push(r);
+        }
+        let game_progress = money.map(|m| {
+            let mut p: phi_save_codec::GameProgressParsed =
+                serde_json::from_value(serde_json::json!({"version": 4})).unwrap();
+            p.money = Some(m);
+            p
+        });
+        ParsedSave {
+            game_record,
+            game_progress,
+            user: None,
+            settings: None,
+            game_key: None,
+            summary_parsed: None,
+            updated_at: None,
+        }
+    }
+
+    fn game() -> GameData {
+        let consts = ["a", "b"]
+            .into_iter()
+            .map(|id| {
+                (
+                    id.to_string(),
+                    ChartConstants {
+                        ez: None,
+                        hd: None,
+                        in_level: Some(12.0),
+                        at: None,
+                    },
+                )
+            })
+            .collect();
+        GameData {
+            chart_constants: Arc::new(consts),
+            song_catalog: Arc::new(SongCatalog::default()),
+            version: 1,
+            source: crate::game_data::GameDataSource::Local,
+            loaded_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn diff_reports_chart_progress_money_and_rks_contributions() {
+        let before = save(
+            vec![("a", rec(Difficulty::IN, 950_000, 97.0, false))],
+            Some([100, 2, 0, 0, 0]),
+        );
+        let after = save(
+            vec![
+                ("a", rec(Difficulty::IN, 1_000_000, 100.0, true)),
+                ("b", rec(Difficulty::IN, 900_000, 95.0, false)),
+            ],
+            Some([50, 3, 0, 0, 0]),
+        );
+        let diff = diff_saves(&before, &after, &game());
+
+        assert_eq!(diff.summary.newly_played, 1);
+        assert_eq!(diff.summary.improved, 1);
+        assert_eq!(diff.summary.fc_gained, 1);
+        assert_eq!(diff.summary.ap_gained, 1);
+        let a = diff.charts.iter().find(|c| c.song_id == "a").unwrap();
+        assert_eq!(a.score_delta, 50_000);
+        assert!(a.chart_rks_delta.unwrap() > 0.0);
+
+        let money = diff.money.unwrap();
+        assert_eq!(money.delta, [-50, 1, 0, 0, 0]);
+        assert_eq!(money.delta_kb, 1024 - 50);
+
+        assert!(diff.rks.delta > 0.0);
+        let sum: f64 = diff.rks.contributions.iter().map(|c| c.delta).sum();
+        assert!((sum - diff.rks.delta).abs() < 1e-9);
+
+        let same = diff_saves(&after, &after, &game());
+        assert!(same.charts.is_empty());
+        assert!(same.money.is_none());
+        assert!(same.rks.contributions.is_empty());
+    }
+
+    #[test]
+    fn stored_baseline_round_trips_and_diffs_like_full_save() {
+        let before = save(
+            vec![
+                ("a", rec(Difficulty::IN, 950_000, 97.0, false)),
+                ("b", rec(Difficulty::IN, 0, 0.0, false)),
+            ],
+            Some([100, 2, 0, 0, 0]),
+        );
+        let after = save(
+            vec![("a", rec(Difficulty::IN, 990_000, 99.5, true))],
+            Some([120, 2, 0, 0, 0]),
+        );
+
+        let json = SaveBaseline::from_parsed(&before).to_json().unwrap();
+        // 未游玩谱面不记录；完整存档 JSON 不被当作基准
+        let stored = SaveBaseline::from_json(&json).unwrap();
+        assert_eq!(stored.charts.len(), 1);
+        assert!(SaveBaseline::from_json(&serde_json::to_string(&before).unwrap()).is_none());
+
+        let full = diff_saves(&before, &after, &game());
+        let via_baseline = diff_baselines(&stored, &SaveBaseline::from_parsed(&after), &game());
+        assert_eq!(via_baseline.summary.improved, full.summary.improved);
+        assert_eq!(via_baseline.summary.fc_gained, 1);
+        assert_eq!(via_baseline.money.unwrap().delta_kb, 20);
+        assert!((via_baseline.rks.delta - full.rks.delta).abs() < 1e-12);
+    }
+}
@@ -2,8 +2,9 @@
 use axum::{
     Router,
     body::Bytes,
-    extract::{Query, State},
-    response::Response,
+    extract::{DefaultBodyLimit, FromRequest, Multipart, Query, State},
+    http::header::CONTENT_TYPE,
+    response::{Json, Response},
     routing::post,
 };
 use moka::future::Cache;
@@ -13,9 +14,13 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crate::error::AppError;
-use crate::rks_contract::engine::{PlayerRksResult, calculate_player_rks};
+use crate::rks_contract::chart_snapshot::ChartAccSnapshot;
+use crate::rks_contract::engine::{
+    PlayerRksResult, calculate_player_rks, calculate_player_rks_with_rule,
+};
+use crate::rks_contract::rules::RksRuleVersion;
 use crate::state::AppState;
-use crate::stats_contract::SubmissionRecord;
+use crate::stats_contract::{ServerRegion, SubmissionRecord};
 
 use super::{
     models::UnifiedSaveRequest,
@@ -24,10 +29,9 @@ use super::{
 
 mod response;
 
+pub(crate) use self::response::build_textual_details_from_rks;
 pub use self::response::{SaveAndRksResponse, SaveApiResponse};
-use self::response::{
-    build_save_response, build_textual_details_from_rks, serialize_save_data_body,
-};
+use self::response::{build_save_response, serialize_save_data_body};
 
 // ── 内部阶段结果结构体 ──
 
@@ -52,11 +56,28 @@ pub(super) struct SaveWithCache {
 
 pub(super) struct RksComputeResult {
     pub(super) game_record: HashMap<String, Vec<super::models::DifficultyRecord>>,
+    /// 按请求规则计算的 RKS（用于响应）
     pub(super) rks: PlayerRksResult,
+    /// 按当前规则计算的 RKS（用于排行榜）
+    leaderboard_rks: PlayerRksResult,
+    leaderboard: LeaderboardPayload,
+    pub(super) calc_ms: i64,
+}
+
+/// 排行榜写入所需的附加数据（仅在需要写排行榜时计算）
+#[derive(Default)]
+struct LeaderboardPayload {
     best_top3_json: Option<String>,
     ap_top3_json: Option<String>,
     rks_comp_json: Option<String>,
-    pub(super) calc_ms: i64,
+    /// 全部谱面 ACC 快照（写入 save_submissions.details_json，供服务端按其他定数表重算）
+    chart_details_json: Option<String>,
+    /// 其他规则下的总 RKS（用于分规则排行榜）
+    rule_totals: Vec<(RksRuleVersion, f64)>,
+    /// 已游玩谱面的当前成绩（用于逐谱面成绩历史）
+    chart_scores: Vec<crate::stats_contract::ChartScoreSnapshot>,
+    /// 存档 summary 中游戏记录的 RKS（反作弊一致性校验）
+    summary_ranking_score: Option<f64>,
 }
 
 // ── 内部工具函数 ──
@@ -72,16 +93,17 @@ pub(super) fn duration_ms_i64(duration: Duration) -> i64 {
     i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
 }
 
-/// /save 缓存 key：同一用户 + 同一 updatedAt + 同一认证版本视为同一份存档结果。
+/// /save 缓存 key：同一用户 + 同一 updatedAt + 同一认证版本 + 同一数据版本视为同一份存档结果。
 fn build_save_cache_key(
     user_hash: Option<&str>,
     updated_at: Option<&str>,
     taptap_version: Option<&str>,
+    data_version: u64,
 ) -> Option<String> {
     let user_hash = user_hash?;
     let updated_at = updated_at?;
     let ver = taptap_version.unwrap_or("default");
-    Some(format!("{user_hash}:{updated_at}:{ver}"))
+    Some(format!("{user_hash}:{updated_at}:{ver}:d{data_version}"))
 }
 
 fn save_cache() -> &'static Cache<String, SaveCacheEntry> {
@@ -96,6 +118,26 @@ fn save_cache() -> &'static Cache<String, SaveCacheEntry> {
     })
 }
 
+/// 解析 `rks_rule` 查询参数（缺省为当前规则）。
+fn parse_rks_rule_param(
+    params: &std::collections::BTreeMap<String, String>,
+) -> Result<RksRuleVersion, AppError> {
+    match params
+        .get("rks_rule")
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+    {
+        None => Ok(RksRuleVersion::default()),
+        Some(code) => RksRuleVersion::from_code(code)
+            .ok_or_else(|| AppError::Validation(format!("rks_rule 无效：{code}"))),
+    }
+}
+
+/// 清空 /save 缓存（定数表热更新后调用）。
+pub(crate) fn invalidate_save_cache() {
+    save_cache().invalidate_all();
+}
+
 // ── Phase 1: 认证 + 身份推导 ──
 
 async fn authenticate_for_save(
@@ -185,7 +227,7 @@ async fn fetch_save_with_cache(
     source: SaveSource,
     taptap_version: Option<&str>,
     user_hash: Option<&str>,
-    chart_constants: Arc<crate::startup::chart_loader::ChartConstantsMap>,
+    game: &crate::game_data::GameData,
     stats: Option<&crate::stats_contract::StatsHandle>,
     auth_ms: i64,
     source_ms: i64,
@@ -233,96 +275,104 @@ async fn fetch_save_with_cache(
     }
 
     let cache_key = if save_cfg.cache_enabled {
-        build_save_cache_key(user_hash, meta.updated_at.as_deref(), taptap_version)
+        build_save_cache_key(
+            user_hash,
+            meta.updated_at.as_deref(),
+            taptap_version,
+            game.version,
+        )
     } else {
         None
     };
 
-    let (parsed, data_body, cache_lookup_ms, decode_ms, cache_status) =
-        if let Some(key) = cache_key.as_ref() {
-            let t_cache = Instant::now();
-            if let Some(entry) = save_cache().get(key).await {
-                let cache_lookup_ms = duration_ms_i64(t_cache.elapsed());
-                if let Some(stats) = stats {
-                    let extra = serde_json::json!({
-                        "status": "hit",
-                        "version": taptap_version.unwrap_or("default")
-                    });
-                    stats.track_feature(
-                        "save_cache",
-                        "hit",
-                        user_hash.map(str::to_string),
-                        Some(extra),
-                    );
-                }
-                let t_decode = Instant::now();
-                let parsed = entry.parsed.clone();
-                let data_body_bytes = entry.data_body_bytes.clone();
-                let save_decode_ms = duration_ms_i64(t_decode.elapsed());
-                (
-                    parsed,
-                    data_body_bytes,
-                    cache_lookup_ms,
-                    save_decode_ms,
+    let (parsed, data_body, cache_lookup_ms, decode_ms, cache_status) = if let Some(key) =
+        cache_key.as_ref()
+    {
+        let t_cache = Instant::now();
+        if let Some(entry) = save_cache().get(key).await {
+            let cache_lookup_ms = duration_ms_i64(t_cache.elapsed());
+            if let Some(stats) = stats {
+                let extra = serde_json::json!({
+                    "status": "hit",
+                    "version": taptap_version.unwrap_or("default")
+                });
+                stats.track_feature(
+                    "save_cache",
                     "hit",
-                )
-            } else {
-                let cache_lookup_ms = duration_ms_i64(t_cache.elapsed());
-                if let Some(stats) = stats {
-                    let extra = serde_json::json!({
-                        "status": "miss",
-                        "version": taptap_version.unwrap_or("default")
-                    });
-                    stats.track_feature(
-                        "save_cache",
-                        "miss",
-                        user_hash.map(str::to_string),
-                        Some(extra),
-                    );
-                }
-                let t_decode = Instant::now();
-                let parsed = provider::get_decrypted_save_from_meta(meta, chart_constants).await?;
-                let parsed = Arc::new(parsed);
-                let data_body_bytes = serialize_save_data_body(parsed.as_ref())?;
-                let save_decode_ms = duration_ms_i64(t_decode.elapsed());
-                save_cache()
-                    .insert(
-                        key.clone(),
-                        SaveCacheEntry {
-                            parsed: parsed.clone(),
-                            data_body_bytes: data_body_bytes.clone(),
-                        },
-                    )
-                    .await;
-                (
-                    parsed,
-                    data_body_bytes,
-                    cache_lookup_ms,
-                    save_decode_ms,
-                    "miss",
-                )
+                    user_hash.map(str::to_string),
+                    Some(extra),
+                );
             }
+            let t_decode = Instant::now();
+            let parsed = entry.parsed.clone();
+            let data_body_bytes = entry.data_body_bytes.clone();
+            let save_decode_ms = duration_ms_i64(t_decode.elapsed());
+            (
+                parsed,
+                data_body_bytes,
+                cache_lookup_ms,
+                save_decode_ms,
+                "hit",
+            )
         } else {
+            let cache_lookup_ms = duration_ms_i64(t_cache.elapsed());
             if let Some(stats) = stats {
                 let extra = serde_json::json!({
-                    "status": "skipped",
-                    "reason": cache_skip_reason.unwrap_or("unknown"),
+                    "status": "miss",
                     "version": taptap_version.unwrap_or("default")
                 });
                 stats.track_feature(
                     "save_cache",
-                    "skipped",
+                    "miss",
                     user_hash.map(str::to_string),
                     Some(extra),
                 );
             }
             let t_decode = Instant::now();
-            let parsed = provider::get_decrypted_save_from_meta(meta, chart_constants).await?;
+            let parsed =
+                provider::get_decrypted_save_from_meta(meta, game.chart_constants.clone()).await?;
             let parsed = Arc::new(parsed);
             let data_body_bytes = serialize_save_data_body(parsed.as_ref())?;
             let save_decode_ms = duration_ms_i64(t_decode.elapsed());
-            (parsed, data_body_bytes, 0_i64, save_decode_ms, "skipped")
-        };
+            save_cache()
+                .insert(
+                    key.clone(),
+                    SaveCacheEntry {
+                        parsed: parsed.clone(),
+                        data_body_bytes: data_body_bytes.clone(),
+                    },
+                )
+                .await;
+            (
+                parsed,
+                data_body_bytes,
+                cache_lookup_ms,
+                save_decode_ms,
+                "miss",
+            )
+        }
+    } else {
+        if let Some(stats) = stats {
+            let extra = serde_json::json!({
+                "status": "skipped",
+                "reason": cache_skip_reason.unwrap_or("unknown"),
+                "version": taptap_version.unwrap_or("default")
+            });
+            stats.track_feature(
+                "save_cache",
+                "skipped",
+                user_hash.map(str::to_string),
+                Some(extra),
+            );
+        }
+        let t_decode = Instant::now();
+        let parsed =
+            provider::get_decrypted_save_from_meta(meta, game.chart_constants.clone()).await?;
+        let parsed = Arc::new(parsed);
+        let data_body_bytes = serialize_save_data_body(parsed.as_ref())?;
+        let save_decode_ms = duration_ms_i64(t_decode.elapsed());
+        (parsed, data_body_bytes, 0_i64, save_decode_ms, "skipped")
+    };
 
     let cache_lookup_status = if cache_status == "skipped" {
         "skipped"
@@ -364,9 +414,10 @@ async fn fetch_save_with_cache(
 
 async fn compute_rks_and_details(
     parsed: Arc<provider::ParsedSave>,
-    state: AppState,
+    game: Arc<crate::game_data::GameData>,
     calc_rks: bool,
     need_leaderboard: bool,
+    rule: RksRuleVersion,
 ) -> Result<RksComputeResult, AppError> {
     let permit = super::save_rks_blocking_semaphore()
         .clone()
@@ -376,53 +427,52 @@ async fn compute_rks_and_details(
     let t_calc = Instant::now();
     let join = tokio::task::spawn_blocking(move || {
         let _permit = permit;
-        let game_record = parsed.game_record.clone();
+        let mut game_record = parsed.game_record.clone();
         if calc_rks {
-            let mut game_record = game_record;
             crate::rks_contract::engine::fill_push_acc_for_game_record(&mut game_record);
-            let rks_res = calculate_player_rks(&game_record, &state.chart_constants);
-            let (best_top3_json, ap_top3_json, rks_comp_json) = if need_leaderboard {
-                let (best_top3, ap_top3, rks_comp) =
-                    build_textual_details_from_rks(&game_record, &rks_res, &state);
-                (
-                    serde_json::to_string(&best_top3).ok(),
-                    serde_json::to_string(&ap_top3).ok(),
-                    serde_json::to_string(&rks_comp).ok(),
-                )
-            } else {
-                (None, None, None)
-            };
-            (
-                game_record,
-                rks_res,
-                best_top3_json,
-                ap_top3_json,
-                rks_comp_json,
-            )
-        } else {
-            let rks_res = calculate_player_rks(&game_record, &state.chart_constants);
-            let (best_top3_json, ap_top3_json, rks_comp_json) = if need_leaderboard {
-                let (best_top3, ap_top3, rks_comp) =
-                    build_textual_details_from_rks(&game_record, &rks_res, &state);
-                (
-                    serde_json::to_string(&best_top3).ok(),
-                    serde_json::to_string(&ap_top3).ok(),
-                    serde_json::to_string(&rks_comp).ok(),
-                )
-            } else {
-                (None, None, None)
-            };
-            (
-                game_record,
-                rks_res,
-                best_top3_json,
-                ap_top3_json,
-                rks_comp_json,
-            )
         }
+        // 排行榜始终按当前规则；响应中的 rks 按请求选择的规则计算。
+        let current = calculate_player_rks(&game_record, &game.chart_constants);
+        let rks_res = if rule.is_current() {
+            current.clone()
+        } else {
+            calculate_player_rks_with_rule(&game_record, &game.chart_constants, rule.rule())
+        };
+        let leaderboard = if need_leaderboard {
+            let (best_top3, ap_top3, rks_comp) =
+                build_textual_details_from_rks(&game_record, &current, &game.song_catalog);
+            let rule_totals = RksRuleVersion::ALL
+                .into_iter()
+                .filter(|v| !v.is_current())
+                .map(|v| {
+                    let total = calculate_player_rks_with_rule(
+                        &game_record,
+                        &game.chart_constants,
+                        v.rule(),
+                    )
+                    .total_rks;
+                    (v, total)
+                })
+                .collect();
+            LeaderboardPayload {
+                best_top3_json: serde_json::to_string(&best_top3).ok(),
+                ap_top3_json: serde_json::to_string(&ap_top3).ok(),
+                rks_comp_json: serde_json::to_string(&rks_comp).ok(),
+                chart_details_json: ChartAccSnapshot::from_game_record(&game_record).to_json(),
+                rule_totals,
+                chart_scores: chart_score_snapshots(&game_record),
+                summary_ranking_score: parsed
+                    .summary_parsed
+                    .as_ref()
+                    .map(|s| f64::from(s.ranking_score)),
+            }
+        } else {
+            LeaderboardPayload::default()
+        };
+        (game_record, rks_res, current, leaderboard)
     })
     .await;
-    let (game_record, rks, best_top3_json, ap_top3_json, rks_comp_json) = match join {
+    let (game_record, rks, leaderboard_rks, leaderboard) = match join {
         Ok(v) => v,
         Err(e) => {
             tracing::info!(
@@ -457,66 +507,175 @@ async fn compute_rks_and_details(
     Ok(RksComputeResult {
         game_record,
         rks,
-        best_top3_json,
-        ap_top3_json,
-        rks_comp_json,
+        leaderboard_rks,
+        leaderboard,
         calc_ms,
     })
 }
 
 // ── Phase 4b: 排行榜写入（后台 best-effort） ──
 
+fn chart_score_snapshots(
+    game_record: &HashMap<String, Vec<super::models::DifficultyRecord>>,
+) -> Vec<crate::stats_contract::ChartScoreSnapshot> {
+    game_record
+        .iter()
+        .flat_map(|(song_id, diffs)| {
+            diffs
+                .iter()
+                .filter(|r| r.score > 0 || r.accuracy > 0.0)
+                .map(|r| crate::stats_contract::ChartScoreSnapshot {
+                    song_id: song_id.clone(),
+                    difficulty: r.difficulty.to_string(),
+                    score: i64::from(r.score),
+                    acc: f64::from(r.accuracy),
+                    is_fc: r.is_full_combo,
+                })
+        })
+        .collect()
+}
+
+/// 判定本次提交的服务器区域：走 TapTap 会话的按实际使用的版本归区，
+/// 外部平台凭证（platform/apiUserId）无法判定来源，记为 unspecified。
+fn submission_region(payload: &UnifiedSaveRequest, taptap_version: Option<&str>) -> ServerRegion {
+    let via_taptap = payload.session_token.is_some()
+        || payload
+            .external_credentials
+            .as_ref()
+            .is_some_and(|c| c.sessiontoken.is_some());
+    if !via_taptap {
+        return ServerRegion::Unspecified;
+    }
+    match crate::config::AppConfig::global()
+        .taptap
+        .resolve_version(taptap_version)
+    {
+        crate::config::TapTapVersion::CN => ServerRegion::Cn,
+        crate::config::TapTapVersion::Global => ServerRegion::Global,
+    }
+}
+
+/// 把本次同步的存档记为 /save/diff 的基准（后台 best-effort）
+fn spawn_diff_baseline_write(
+    storage: Arc<crate::stats_contract::StatsStorage>,
+    user_hash: String,
+    parsed: Arc<provider::ParsedSave>,
+) {
+    tokio::spawn(async move {
+        let Some(snapshot_json) = super::diff::SaveBaseline::from_parsed(&parsed).to_json() else {
+            return;
+        };
+        let snapshot = crate::stats_contract::StoredSaveSnapshot {
+            snapshot_json,
+            save_updated_at: parsed.updated_at.clone(),
+            stored_at: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Err(e) = storage.put_save_snapshot(&user_hash, &snapshot).await {
+            tracing::warn!(target: "phi_backend::save", user_hash = %user_hash, "put_save_snapshot failed (ignored): {e}");
+        }
+    });
+}
+
 fn spawn_leaderboard_write(
     storage: Arc<crate::stats_contract::StatsStorage>,
     user_hash: String,
     user_kind: Option<String>,
+    client_ip_hash: Option<String>,
+    region: ServerRegion,
     rks_result: &PlayerRksResult,
-    best_top3_json: Option<String>,
-    ap_top3_json: Option<String>,
-    rks_comp_json: Option<String>,
+    payload: LeaderboardPayload,
 ) {
     let total_rks = rks_result.total_rks;
-    let now = chrono::Utc::now().to_rfc3339();
+    let LeaderboardPayload {
+        best_top3_json,
+        ap_top3_json,
+        rks_comp_json,
+        chart_details_json,
+        rule_totals,
+        chart_scores,
+        summary_ranking_score,
+    } = payload;
+    let now = chrono::Utc::now();
     tokio::spawn(async move {
-        let prev = match storage.get_prev_rks(&user_hash).await {
-            Ok(v) => v,
-            Err(e) => {
-                tracing::warn!(
-                    target: "phi_backend::leaderboard",
-                    user_hash = %user_hash,
-                    "get_prev_rks failed (ignored): {e}"
-                );
-                None
+        let anti_cheat = &crate::config::AppConfig::global().leaderboard.anti_cheat;
+        let since = now - chrono::Duration::hours(anti_cheat.shared_ip_window_hours.max(0));
+        let now = now.to_rfc3339();
+        // 反作弊输入均为 best-effort：读取失败时视为缺少该信号，不阻断写入
+        let (prev, prev_charts, other_accounts_on_ip) = tokio::join!(
+            storage.get_prev_rks(&user_hash),
+            storage.get_chart_scores(&user_hash),
+            async {
+                match client_ip_hash.as_deref() {
+                    Some(ip) => storage
+                        .count_other_accounts_on_ip(ip, &user_hash, &since.to_rfc3339())
+                        .await
+                        .map(Some),
+                    None => Ok(None),
+                }
             }
-        };
-        let prev_rks = prev.as_ref().map_or(0.0, |v| v.0);
-        const RKS_JUMP_EPS: f64 = 1e-9;
-        let rks_jump = if prev_rks > 0.0 {
-            total_rks - prev_rks
-        } else {
-            0.0
-        };
-        let rks_jump = if rks_jump.abs() < RKS_JUMP_EPS {
-            0.0
-        } else {
-            rks_jump
-        };
+        );
+        let prev = prev.unwrap_or_else(|e| {
+            tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "get_prev_rks failed (ignored): {e}");
+            None
+        });
+        let prev_charts = prev_charts.unwrap_or_else(|e| {
+            tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "get_chart_scores failed (ignored): {e}");
+            Vec::new()
+        });
+        let other_accounts_on_ip = other_accounts_on_ip.unwrap_or_else(|e| {
+            tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "count_other_accounts_on_ip failed (ignored): {e}");
+            None
+        });
 
-        let mut suspicion = 0.0_f64;
-        if total_rks > 20.0 {
-            suspicion += 0.5;
-        }
-        if rks_jump > 1.0 {
-            suspicion += 0.8;
-        } else if rks_jump > 0.5 {
-            suspicion += 0.3;
-        }
-        if let Some(kind) = user_kind.as_deref()
-            && kind == "session_token"
-        {
-            suspicion = (suspicion - 0.2).max(0.0);
+        let prev_rks = prev.as_ref().map(|v| v.0);
+        let rks_jump = crate::leaderboard_contract::rks_jump(total_rks, prev_rks);
+        let verdict = crate::leaderboard_contract::evaluate_submission(
+            anti_cheat,
+            &crate::leaderboard_contract::SubmissionSignals {
+                total_rks,
+                prev_rks,
+                user_kind: user_kind.as_deref(),
+                summary_ranking_score,
+                charts: &chart_scores,
+                prev_charts: (!prev_charts.is_empty()).then_some(prev_charts.as_slice()),
+                other_accounts_on_ip,
+            },
+        );
+        let suspicion = verdict.suspicion;
+        let hide = verdict.hide;
+        if !verdict.fired.is_empty() {
+            let reasons: Vec<String> = verdict
+                .fired
+                .iter()
+                .map(|r| format!("[{}] {}", r.rule, r.reason))
+                .collect();
+            let flags: Vec<crate::stats_contract::NewModerationFlag<'_>> = verdict
+                .fired
+                .iter()
+                .zip(&reasons)
+                .map(|(r, reason)| {
+                    // 规则权重为配置中的小数，放大为百分制严重度
+                    #[allow(clippy::cast_possible_truncation)]
+                    let severity = (r.weight * 100.0).round() as i64;
+                    crate::stats_contract::NewModerationFlag {
+                        status: if hide { "auto_hidden" } else { "auto_flagged" },
+                        reason,
+                        severity,
+                    }
+                })
+                .collect();
+            if let Err(e) = storage
+                .insert_moderation_flags(
+                    &user_hash,
+                    &flags,
+                    crate::leaderboard_contract::ANTI_CHEAT_ACTOR,
+                    &now,
+                )
+                .await
+            {
+                tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "insert_moderation_flags failed (ignored): {e}");
+            }
         }
-        let hide = suspicion >= 1.0;
 
         if let Err(e) = storage
             .insert_submission(SubmissionRecord {
@@ -524,15 +683,22 @@ fn spawn_leaderboard_write(
                 total_rks,
                 rks_jump,
                 route: "/save",
-                client_ip_hash: None,
-                details_json: None,
+                client_ip_hash: client_ip_hash.as_deref(),
+                details_json: chart_details_json.as_deref(),
                 suspicion_score: suspicion,
+                region,
                 now_rfc3339: &now,
             })
             .await
         {
             tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "insert_submission failed (ignored): {e}");
         }
+        if let Err(e) = storage
+            .record_chart_scores(&user_hash, &chart_scores, &now)
+            .await
+        {
+            tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "record_chart_scores failed (ignored): {e}");
+        }
         if let Err(e) = storage
             .upsert_leaderboard_rks(
                 &user_hash,
@@ -540,12 +706,21 @@ fn spawn_leaderboard_write(
                 user_kind.as_deref(),
                 suspicion,
                 hide,
+                region,
                 &now,
             )
             .await
         {
             tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, "upsert_leaderboard_rks failed (ignored): {e}");
         }
+        for (rule, rule_total) in rule_totals {
+            if let Err(e) = storage
+                .upsert_leaderboard_rule_rks(&user_hash, rule.code(), rule_total, &now)
+                .await
+            {
+                tracing::warn!(target: "phi_backend::leaderboard", user_hash = %user_hash, rule = rule.code(), "upsert_leaderboard_rule_rks failed (ignored): {e}");
+            }
+        }
         if let Err(e) = storage
             .upsert_details(
                 &user_hash,
@@ -587,6 +762,7 @@ fn spawn_leaderboard_write(
     request_body = UnifiedSaveRequest,
     params(
         ("calculate_rks" = Option<bool>, Query, description = "是否计算玩家RKS（true=计算，默认不计算）"),
+        ("rks_rule" = Option<String>, Query, description = "RKS 规则版本：b27-ap3（默认，当前规则）/ b27-ap3-exclusive / b19-phi1 / b19-phi1-exclusive；仅影响响应中的 rks，排行榜始终按当前规则"),
     ),
     responses(
         (status = 200, description = "成功解析存档；当 calculate_rks=true 时同时包含 rks 字段，并为每个谱面回填 push_acc 与 push_acc_hint（推分提示）", body = SaveApiResponse),
@@ -605,6 +781,7 @@ pub async fn get_save_data(
     req: axum::extract::Request,
 ) -> Result<Response, AppError> {
     let t_total = Instant::now();
+    let client_ip_hash = crate::stats_contract::client_ip_hash(req.headers());
 
     // Phase 1: 认证 + 身份推导
     let auth = authenticate_for_save(&state, req).await?;
@@ -619,12 +796,13 @@ pub async fn get_save_data(
         dur_ms = source_ms, "save performance"
     );
 
-    // Phase 3: 元数据获取 + 缓存
+    // Phase 3: 元数据获取 + 缓存（整个请求固定使用同一份定数快照）
+    let game = state.game_data();
     let data = fetch_save_with_cache(
         source,
         auth.taptap_version.as_deref(),
         auth.user_hash.as_deref(),
-        state.chart_constants.clone(),
+        &game,
         state.stats.as_ref(),
         auth.auth_ms,
         source_ms,
@@ -638,31 +816,34 @@ pub async fn get_save_data(
     }
 
     let calc_rks = params.get("calculate_rks").is_some_and(|v| v == "true");
+    let rks_rule = parse_rks_rule_param(&params)?;
     let need_leaderboard = state.stats_storage.is_some() && auth.user_hash.is_some();
     let need_calc = calc_rks || need_leaderboard;
 
     // Phase 4: RKS 计算 + 排行榜写入
     let (rks_opt, calc_ms) = if need_calc {
-        let result = compute_rks_and_details(
+        let mut result = compute_rks_and_details(
             data.parsed.clone(),
-            state.clone(),
+            game.clone(),
             calc_rks,
             need_leaderboard,
+            rks_rule,
         )
         .await?;
 
-        // 排行榜后台写入
+        // 排行榜与存档差异基准后台写入
         if let Some(storage) = state.stats_storage.as_ref()
             && let Some(ref user_hash_ref) = auth.user_hash
         {
+            spawn_diff_baseline_write(storage.clone(), user_hash_ref.clone(), data.parsed.clone());
             spawn_leaderboard_write(
                 storage.clone(),
                 user_hash_ref.clone(),
                 auth.user_kind.clone(),
-                &result.rks,
-                result.best_top3_json.clone(),
-                result.ap_top3_json.clone(),
-                result.rks_comp_json.clone(),
+                client_ip_hash,
+                submission_region(&auth.payload, auth.taptap_version.as_deref()),
+                &result.leaderboard_rks,
+                std::mem::take(&mut result.leaderboard),
             );
         }
         let calc_ms = result.calc_ms;
@@ -718,7 +899,373 @@ pub async fn get_save_data(
     Ok(response)
 }
 
-fn validate_and_create_source(payload: &UnifiedSaveRequest) -> Result<SaveSource, AppError> {
+// ── 离线上传 ──
+
+/// 分块读取 multipart 文件字段，超过 `limit` 字节时报错。
+async fn read_multipart_blob(
+    field: &mut axum::extract::multipart::Field<'_>,
+    limit: usize,
+) -> Result<Bytes, AppError> {
+    let mut buf = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| AppError::SaveHandlerError(format!("读取上传存档失败: {e}")))?
+    {
+        if buf.len().saturating_add(chunk.len()) > limit {
+            return Err(AppError::SaveHandlerError(format!(
+                "上传存档超过上限 {limit} 字节"
+            )));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(buf))
+}
+
+/// multipart 文本字段（summary 等）的上限；存档 summary 的 base64 仅数百字节
+const MULTIPART_TEXT_MAX_BYTES: usize = 64 * 1024;
+
+/// 分块读取 multipart 文本字段（如 summary），超过上限时报错；空白内容视为未提供。
+async fn read_multipart_text(
+    mut field: axum::extract::multipart::Field<'_>,
+) -> Result<Option<String>, AppError> {
+    let name = field.name().unwrap_or("summary").to_string();
+    let mut buf = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| AppError::SaveHandlerError(format!("读取 {name} 失败: {e}")))?
+    {
+        if buf.len().saturating_add(chunk.len()) > MULTIPART_TEXT_MAX_BYTES {
+            return Err(AppError::Validation(format!(
+                "{name} 超过上限 {MULTIPART_TEXT_MAX_BYTES} 字节"
+            )));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    let text = String::from_utf8(buf)
+        .map_err(|_| AppError::Validation(format!("{name} 不是有效的 UTF-8 文本")))?;
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| text.to_string()))
+}
+
+/// 读取上传的存档 blob：支持 multipart/form-data（字段 `file`，可选 `summary`）或直接二进制 body。
+async fn read_upload_body(
+    state: &AppState,
+    req: axum::extract::Request,
+    limit: usize,
+) -> Result<(Bytes, Option<String>), AppError> {
+    let is_multipart = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("multipart/form-data"));
+    if !is_multipart {
+        let body = axum::body::to_bytes(req.into_body(), limit)
+            .await
+            .map_err(|e| {
+                AppError::SaveHandlerError(format!("读取上传存档失败（上限 {limit} 字节）: {e}"))
+            })?;
+        return Ok((body, None));
+    }
+
+    let mut multipart = Multipart::from_request(req, state)
+        .await
+        .map_err(|e| AppError::SaveHandlerError(format!("multipart 解析失败: {e}")))?;
+    let mut blob: Option<Bytes> = None;
+    let mut summary: Option<String> = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::SaveHandlerError(format!("multipart 解析失败: {e}")))?
+    {
+        match field.name() {
+            Some("file") => blob = Some(read_multipart_blob(&mut field, limit).await?),
+            Some("summary") => summary = read_multipart_text(field).await?,
+            _ => {}
+        }
+    }
+    let blob =
+        blob.ok_or_else(|| AppError::SaveHandlerError("multipart 缺少 file 字段".to_string()))?;
+    Ok((blob, summary))
+}
+
+#[utoipa::path(
+    post,
+    path = "/save/upload",
+    summary = "上传并解析加密存档 zip（离线导入）",
+    description = "直接上传云存档 zip（与官方云端下载得到的 blob 格式一致），不访问 TapTap/LeanCloud。支持 multipart/form-data（字段 file，可选 summary）或 application/octet-stream。解压/解密/解析与 /save 共用同一套上限与解析器；上传存档无法证明归属，因此不会写入排行榜。",
+    request_body(
+        description = "加密存档 zip",
+        content(
+            (crate::features::save::models::SaveUploadForm = "multipart/form-data"),
+            (crate::features::save::models::BinarySaveBlob = "application/octet-stream")
+        )
+    ),
+    params(
+        ("calculate_rks" = Option<bool>, Query, description = "是否计算玩家RKS（true=计算，默认不计算）"),
+        ("rks_rule" = Option<String>, Query, description = "RKS 规则版本：b27-ap3（默认，当前规则）/ b27-ap3-exclusive / b19-phi1 / b19-phi1-exclusive；仅影响响应中的 rks，排行榜始终按当前规则"),
+    ),
+    responses(
+        (status = 200, description = "成功解析存档；当 calculate_rks=true 时同时包含 rks 字段", body = SaveApiResponse),
+        (status = 400, description = "请求体缺失/过大/multipart 格式错误", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "存档数据无效（解压、解密或解析失败等），或 summary 字段超过 64 KiB", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "服务器内部错误", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Save"
+)]
+pub async fn post_save_upload(
+    State(state): State<AppState>,
+    Query(params): Query<std::collections::BTreeMap<String, String>>,
+    req: axum::extract::Request,
+) -> Result<Response, AppError> {
+    let t_total = Instant::now();
+    let limit = usize::try_from(crate::config::AppConfig::global().save.max_download_bytes)
+        .unwrap_or(usize::MAX);
+    let (blob, summary_b64) = read_upload_body(&state, req, limit).await?;
+    let upload_bytes = blob.len();
+
+    let t_decode = Instant::now();
+    let game = state.game_data();
+    let parsed =
+        provider::get_decrypted_save_from_upload(blob, summary_b64, game.chart_constants.clone())
+            .await?;
+    let parsed = Arc::new(parsed);
+    let data_body = serialize_save_data_body(parsed.as_ref())?;
+    let decode_ms = duration_ms_i64(t_decode.elapsed());
+    tracing::info!(
+        target: "phi_backend::save::performance",
+        route = "/save/upload", phase = "decode_parse", status = "ok",
+        upload_bytes, dur_ms = decode_ms, "save performance"
+    );
+    let data = SaveWithCache {
+        parsed,
+        data_body,
+        cache_status: "skipped",
+        auth_ms: 0,
+        source_ms: 0,
+        meta_ms: 0,
+        cache_lookup_ms: 0,
+        decode_ms,
+    };
+
+    if let Some(stats) = state.stats.as_ref() {
+        let extra = serde_json::json!({ "upload_bytes": upload_bytes });
+        stats.track_feature("save", "upload", None, Some(extra));
+    }
+
+    // 上传的存档无法证明归属，只计算 RKS，不写排行榜。
+    let calc_rks = params.get("calculate_rks").is_some_and(|v| v == "true");
+    let rks_rule = parse_rks_rule_param(&params)?;
+    let response = if calc_rks {
+        let result =
+            compute_rks_and_details(data.parsed.clone(), game, true, false, rks_rule).await?;
+        build_save_response(&data, Some((&result, data.parsed.as_ref())))?
+    } else {
+        build_save_response(&data, None)?
+    };
+
+    tracing::info!(
+        target: "phi_backend::save::performance",
+        route = "/save/upload", phase = "total", status = "ok",
+        calculate_rks = calc_rks,
+        total_dur_ms = t_total.elapsed().as_millis(),
+        "save performance"
+    );
+    Ok(response)
+}
+
+// ── 存档差异 ──
+
+#[utoipa::path(
+    post,
+    path = "/save/diff",
+    summary = "与上次同步的存档对比",
+    description = "拉取当前存档（认证方式与 /save 相同），与服务端保存的上次同步快照对比，返回新游玩谱面、成绩提升、FC/AP 达成、gameKey 解锁、课题模式等级、Data、设置变化以及 RKS 变化与逐谱面贡献。首次调用没有基准，只保存当前存档（hasBaseline=false）。基准为最近一次同步的存档：每次 /save 拉取存档后都会更新；本接口默认对比后也把当前存档保存为新基准，commit=false 时只对比不保存。",
+    request_body = UnifiedSaveRequest,
+    params(
+        ("commit" = Option<bool>, Query, description = "是否把当前存档保存为新的基准（默认 true）"),
+    ),
+    responses(
+        (status = 200, description = "差异结果", body = super::diff::SyncDiffResponse),
+        (status = 400, description = "请求参数错误", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "认证失败/无法识别用户", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "存档数据无效", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 502, description = "上游网络错误（非超时）", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 504, description = "上游超时", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "统计存储未初始化/服务器内部错误", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Save"
+)]
+pub async fn post_save_diff(
+    State(state): State<AppState>,
+    Query(params): Query<std::collections::BTreeMap<String, String>>,
+    req: axum::extract::Request,
+) -> Result<Json<super::diff::SyncDiffResponse>, AppError> {
+    let commit = params.get("commit").is_none_or(|v| v != "false");
+    let auth = authenticate_for_save(&state, req).await?;
+    let storage = state
+        .stats_storage
+        .clone()
+        .ok_or_else(|| AppError::Internal("统计存储未初始化".into()))?;
+    let user_hash = auth
+        .user_hash
+        .clone()
+        .ok_or_else(|| AppError::Auth("无法识别用户（缺少可用凭证）".into()))?;
+
+    let source = validate_and_create_source(&auth.payload)?;
+    let game = state.game_data();
+    let data = fetch_save_with_cache(
+        source,
+        auth.taptap_version.as_deref(),
+        Some(&user_hash),
+        &game,
+        state.stats.as_ref(),
+        auth.auth_ms,
+        0,
+    )
+    .await?;
+
+    let baseline = storage.get_save_snapshot(&user_hash).await?;
+    let baseline_stored_at = baseline.as_ref().map(|b| b.stored_at.clone());
+    let current = data.parsed.clone();
+    let (diff, snapshot_json) = tokio::task::spawn_blocking(move || {
+        let prev = baseline.and_then(|b| {
+            let parsed = super::diff::SaveBaseline::from_json(&b.snapshot_json);
+            if parsed.is_none() {
+                tracing::warn!(target: "phi_backend::save", "存档差异基准解析失败，视为无基准");
+            }
+            parsed
+        });
+        let current = super::diff::SaveBaseline::from_parsed(&current);
+        let diff = prev
+            .as_ref()
+            .map(|p| super::diff::diff_baselines(p, &current, &game));
+        let snapshot_json = if commit { current.to_json() } else { None };
+        (diff, snapshot_json)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("spawn_blocking cancelled: {e}")))?;
+
+    let committed = if let Some(snapshot_json) = snapshot_json {
+        storage
+            .put_save_snapshot(
+                &user_hash,
+                &crate::stats_contract::StoredSaveSnapshot {
+                    snapshot_json,
+                    save_updated_at: data.parsed.updated_at.clone(),
+                    stored_at: chrono::Utc::now().to_rfc3339(),
+                },
+            )
+            .await?;
+        true
+    } else {
+        false
+    };
+
+    if let Some(stats) = state.stats.as_ref() {
+        let extra = serde_json::json!({
+            "user_kind": auth.user_kind,
+            "has_baseline": diff.is_some(),
+            "commit": commit
+        });
+        stats.track_feature("save", "diff", Some(user_hash), Some(extra));
+    }
+
+    Ok(Json(super::diff::SyncDiffResponse {
+        has_baseline: diff.is_some(),
+        baseline_stored_at: diff.as_ref().and(baseline_stored_at),
+        committed,
+        diff,
+    }))
+}
+
+/// 上传两份存档对比（仅用于 OpenAPI 文档展示）
+#[allow(dead_code)]
+#[derive(Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDiffUploadForm {
+    /// 基准存档 zip
+    #[schema(value_type = String, format = Binary)]
+    pub before: Vec<u8>,
+    /// 对比存档 zip
+    #[schema(value_type = String, format = Binary)]
+    pub after: Vec<u8>,
+    /// 可选：基准存档 summary（base64）
+    pub before_summary: Option<String>,
+    /// 可选：对比存档 summary（base64）
+    pub after_summary: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/save/diff/upload",
+    summary = "上传两份存档对比",
+    description = "以 multipart/form-data 上传两份云存档 zip（字段 before / after，可选 beforeSummary / afterSummary），返回 before → after 的结构化差异。不访问 TapTap/LeanCloud，也不读写服务端快照；每份存档的大小上限与 /save/upload 相同。",
+    request_body(content = SaveDiffUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "差异结果", body = super::diff::SaveDiff),
+        (status = 400, description = "请求体缺失/过大/multipart 格式错误", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "存档数据无效（解压、解密或解析失败等），或 summary 字段超过 64 KiB", body = crate::error::ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "服务器内部错误", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Save"
+)]
+pub async fn post_save_diff_upload(
+    State(state): State<AppState>,
+    req: axum::extract::Request,
+) -> Result<Json<super::diff::SaveDiff>, AppError> {
+    let limit = usize::try_from(crate::config::AppConfig::global().save.max_download_bytes)
+        .unwrap_or(usize::MAX);
+    let mut multipart = Multipart::from_request(req, &state)
+        .await
+        .map_err(|e| AppError::SaveHandlerError(format!("multipart 解析失败: {e}")))?;
+    let (mut before, mut after) = (None, None);
+    let (mut before_summary, mut after_summary) = (None, None);
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::SaveHandlerError(format!("multipart 解析失败: {e}")))?
+    {
+        match field.name() {
+            Some("before") => before = Some(read_multipart_blob(&mut field, limit).await?),
+            Some("after") => after = Some(read_multipart_blob(&mut field, limit).await?),
+            Some("beforeSummary") => before_summary = read_multipart_text(field).await?,
+            Some("afterSummary") => after_summary = read_multipart_text(field).await?,
+            _ => {}
+        }
+    }
+    let before = before
+        .ok_or_else(|| AppError::SaveHandlerError("multipart 缺少 before 字段".to_string()))?;
+    let after =
+        after.ok_or_else(|| AppError::SaveHandlerError("multipart 缺少 after 字段".to_string()))?;
+
+    let game = state.game_data();
+    let (before, after) = tokio::try_join!(
+        provider::get_decrypted_save_from_upload(
+            before,
+            before_summary,
+            game.chart_constants.clone()
+        ),
+        provider::get_decrypted_save_from_upload(
+            after,
+            after_summary,
+            game.chart_constants.clone()
+        ),
+    )?;
+    let diff = tokio::task::spawn_blocking(move || super::diff::diff_saves(&before, &after, &game))
+        .await
+        .map_err(|e| AppError::Internal(format!("spawn_blocking cancelled: {e}")))?;
+
+    if let Some(stats) = state.stats.as_ref() {
+        stats.track_feature("save", "diff_upload", None, None);
+    }
+    Ok(Json(diff))
+}
+
+pub(crate) fn validate_and_create_source(
+    payload: &UnifiedSaveRequest,
+) -> Result<SaveSource, AppError> {
     match (&payload.session_token, &payload.external_credentials) {
         (Some(token), None) => {
             if token.is_empty() {
@@ -748,20 +1295,62 @@ fn validate_and_create_source(payload: &UnifiedSaveRequest) -> Result<SaveSource
 }
 
 pub fn create_save_router() -> Router<AppState> {
-    Router::<AppState>::new().route("/save", post(get_save_data))
+    Router::<AppState>::new()
+        .route("/save", post(get_save_data))
+        // 上传体积由 handler 按 SaveLimitsConfig.max_download_bytes 自行限制
+        .route(
+            "/save/upload",
+            post(post_save_upload).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/save/diff", post(post_save_diff))
+        .route(
+            "/save/diff/upload",
+            post(post_save_diff_upload).layer(DefaultBodyLimit::disable()),
+        )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::build_save_cache_key;
+    use axum::extract::{FromRequest, Multipart};
+
+    use super::{MULTIPART_TEXT_MAX_BYTES, build_save_cache_key, read_multipart_text};
+    use crate::error::AppError;
+
+    async fn multipart_with_summary(summary: &str) -> Multipart {
+        let body = format!(
+            "--b\r\nContent-Disposition: form-data; name=\"summary\"\r\n\r\n{summary}\r\n--b--\r\n"
+        );
+        let req = axum::http::Request::builder()
+            .header("content-type", "multipart/form-data; boundary=b")
+            .body(axum::body::Body::from(body))
+            .expect("request");
+        Multipart::from_request(req, &()).await.expect("multipart")
+    }
+
+    #[tokio::test]
+    async fn read_multipart_text_caps_field_size() {
+        let mut mp = multipart_with_summary("  abc  ").await;
+        let field = mp.next_field().await.expect("field").expect("some");
+        assert_eq!(
+            read_multipart_text(field).await.expect("text").as_deref(),
+            Some("abc")
+        );
+
+        let mut mp = multipart_with_summary(&"a".repeat(MULTIPART_TEXT_MAX_BYTES + 1)).await;
+        let field = mp.next_field().await.expect("field").expect("some");
+        assert!(matches!(
+            read_multipart_text(field).await,
+            Err(AppError::Validation(_))
+        ));
+    }
 
     #[test]
     fn build_save_cache_key_requires_user_and_updated_at() {
-        assert!(build_save_cache_key(None, Some("2026-02-10T00:00:00Z"), None).is_none());
-        assert!(build_save_cache_key(Some("u1"), None, None).is_none());
+        assert!(build_save_cache_key(None, Some("2026-02-10T00:00:00Z"), None, 1).is_none());
+        assert!(build_save_cache_key(Some("u1"), None, None, 1).is_none());
 
-        let key = build_save_cache_key(Some("u1"), Some("2026-02-10T00:00:00Z"), Some("global"))
+        let key = build_save_cache_key(Some("u1"), Some("2026-02-10T00:00:00Z"), Some("global"), 3)
             .expect("cache key");
-        assert_eq!(key, "u1:2026-02-10T00:00:00Z:global");
+        assert_eq!(key, "u1:2026-02-10T00:00:00Z:global:d3");
     }
 }
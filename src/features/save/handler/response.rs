@@ -8,7 +8,6 @@ use std::time::Instant;
 
 use crate::error::AppError;
 use crate::rks_contract::engine::{ChartRankingScore, PlayerRksResult};
-use crate::state::AppState;
 
 use super::super::{
     models::{SaveAndRksResponseDoc, SaveResponseDoc},
@@ -175,10 +174,10 @@ fn build_chart_acc_index(
     (acc_by_chart, valid_count, ap_count)
 }
 
-pub(super) fn build_textual_details_from_rks(
+pub(crate) fn build_textual_details_from_rks(
     records: &HashMap<String, Vec<super::super::models::DifficultyRecord>>,
     rks_result: &PlayerRksResult,
-    state: &AppState,
+    song_catalog: &crate::song_contract::SongCatalog,
 ) -> (
     Vec<crate::leaderboard_contract::ChartTextItem>,
     Vec<crate::leaderboard_contract::ChartTextItem>,
@@ -194,8 +193,7 @@ pub(super) fn build_textual_details_from_rks(
     let ap_slice = &rks_result.b30_charts[best27_len..best27_len + ap3_len];
 
     let name_of = |sid: &str| -> String {
-        state
-            .song_catalog
+        song_catalog
             .by_id
             .get(sid)
             .map_or_else(|| sid.to_string(), |s| s.name.clone())
@@ -2,12 +2,14 @@
 //!
 //! 设计目标：
 //! - 默认“安全输出”：不打印 stoken、不完整泄露存档内容；
-//! - 输出结构化报告（JSON 可序列化），便于扩展字段（例如原始 summary、更多元信息）。
+//! - 输出结构化报告（JSON 可序列化），便于扩展字段（例如原始 summary、更多元信息）；
+//! - 支持离线输入（本地 zip / 已解密的单个 entry），无需 stoken 即可复现解析问题。
 
 use std::collections::HashMap;
 use std::io::Read;
 
 use flate2::read::{GzDecoder, ZlibDecoder};
+use phi_save_codec::{EntryAnnotation, annotate_entry};
 use serde::Serialize;
 use sha2::{Digest as _, Sha256};
 use zip::ZipArchive;
@@ -40,6 +42,8 @@ pub struct InspectOptions {
     pub preview_plain_bytes: usize,
     /// 允许下载的最大字节数（防止意外的内存峰值）。
     pub max_download_bytes: usize,
+    /// 是否输出逐字段标注（包含明文字段值，默认关闭）。
+    pub annotate: bool,
 }
 
 impl Default for InspectOptions {
@@ -49,6 +53,7 @@ impl Default for InspectOptions {
             show_summary_raw: false,
             preview_plain_bytes: 0,
             max_download_bytes: 64 * 1024 * 1024,
+            annotate: false,
         }
     }
 }
@@ -159,6 +164,10 @@ pub struct EntryReport {
     pub parser_handling: String,
 
     pub decrypted: EntryDecryptedReport,
+
+    /// 逐字段标注（仅在 `InspectOptions::annotate` 开启且解密成功时存在）。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<EntryAnnotation>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -218,17 +227,7 @@ pub async fn inspect_official_save(
         }
     };
 
-    let mut notes = Vec::new();
-    notes.push(
-        "提示：decrypt_zip_entry 会保留 entry 第 1 字节 prefix，但 parser.rs 对不同 entry 的 prefix 处理不一致（部分会跳过）。".to_string(),
-    );
-    notes.push(
-        "提示：当前链路未启用 HMAC 等完整性校验；CBC 模式主要依赖 padding 报错来发现密文异常。"
-            .to_string(),
-    );
-    if matches!(decrypt_meta.cipher, CipherSuite::Aes128Gcm { .. }) {
-        notes.push("提示：AES-128-GCM 实现基于 Aes128Gcm（固定 16 字节 tag），若 meta.tag_len != 16 可能出现兼容性问题。".to_string());
-    }
+    let notes = build_decrypt_notes(&decrypt_meta);
 
     Ok(InspectReport {
         generated_at: now,
@@ -252,6 +251,114 @@ pub async fn inspect_official_save(
     })
 }
 
+/// 离线诊断输入（不需要 stoken）。
+#[derive(Debug, Clone)]
+pub enum LocalSaveInput {
+    /// 官方存档 zip 原始字节（与下载内容一致，可为 gzip/zlib 包装），按默认密钥解密。
+    Zip {
+        bytes: Vec<u8>,
+        /// 可选：同时诊断 summary（base64）。
+        summary_b64: Option<String>,
+    },
+    /// 已解密的单个 entry 明文（含第 1 字节 prefix/version），跳过解密直接诊断。
+    DecryptedEntry { name: String, plain: Vec<u8> },
+}
+
+/// 仅用于诊断：从本地输入生成与 `inspect_official_save` 结构相同的报告。
+///
+/// `source_label` 写入 `meta.download_url`（通常为本地文件路径），便于区分报告来源。
+#[must_use]
+pub fn inspect_local_save(
+    input: LocalSaveInput,
+    source_label: &str,
+    options: &InspectOptions,
+) -> InspectReport {
+    let now = chrono::Utc::now().to_rfc3339();
+    let decrypt_meta = DecryptionMeta::default();
+    let mut errors = Vec::new();
+
+    let (summary_b64, input_len, decompress_report, zip_report, entry_reports) = match input {
+        LocalSaveInput::Zip { bytes, summary_b64 } => {
+            let input_len = bytes.len();
+            let (zip_bytes, decompress_report) = try_decompress_bytes(bytes);
+            let (zip_report, entry_reports) = match try_open_zip(&zip_bytes, &decrypt_meta, options)
+            {
+                Ok(ok) => ok,
+                Err(e) => {
+                    errors.push(format!("zip parse failed: {e}"));
+                    (None, build_missing_entry_reports())
+                }
+            };
+            (
+                summary_b64,
+                input_len,
+                decompress_report,
+                zip_report,
+                entry_reports,
+            )
+        }
+        LocalSaveInput::DecryptedEntry { name, plain } => {
+            let input_len = plain.len();
+            let entry = EntryReport {
+                parser_handling: parser_handling_for_entry(&name),
+                present: true,
+                encrypted_len: None,
+                encrypted_prefix_u8: None,
+                decrypted: build_decrypted_report(&plain, options),
+                annotation: options.annotate.then(|| annotate_entry(&name, &plain)),
+                name,
+            };
+            let decompress_report = DecompressReport {
+                detected: "decrypted-entry".to_string(),
+                input_bytes: input_len,
+                output_bytes: input_len,
+            };
+            (None, input_len, decompress_report, None, vec![entry])
+        }
+    };
+
+    let summary_report = build_summary_report(summary_b64.as_deref(), options.show_summary_raw);
+    let mut notes = build_decrypt_notes(&decrypt_meta);
+    notes
+        .push("提示：离线模式使用默认 AES 密钥/IV（与官方 crypto 元信息缺省值一致）。".to_string());
+
+    InspectReport {
+        generated_at: now,
+        taptap_version: None,
+        meta: SaveMetaReport {
+            download_url: source_label.to_string(),
+            updated_at: None,
+            summary_b64: summary_report.summary_b64,
+            summary_parsed: summary_report.summary_parsed,
+            summary_parse_error: summary_report.summary_parse_error,
+        },
+        decrypt_meta: build_decrypt_meta_report(&decrypt_meta),
+        transport: TransportReport {
+            download_bytes: input_len,
+            decompress: decompress_report,
+            errors,
+        },
+        zip: zip_report,
+        entries: entry_reports,
+        notes,
+    }
+}
+
+fn build_decrypt_notes(decrypt_meta: &DecryptionMeta) -> Vec<String> {
+    let mut notes = Vec::new();
+    notes.push(
+        "提示：decrypt_zip_entry 会保留 entry 第 1 字节 prefix，但 parser.rs 对不同 entry 的 prefix 处理不一致（部分会跳过）。".to_string(),
+    );
+    notes.push(
+        "提示：当前链路未启用 HMAC 等完整性校验；CBC 模式主要依赖 padding 报错来发现密文异常。"
+            .to_string(),
+    );
+    if matches!(decrypt_meta.cipher, CipherSuite::Aes128Gcm { .. }) {
+        notes.push("提示：AES-128-GCM 实现基于 Aes128Gcm（固定 16 字节 tag），若 meta.tag_len != 16 可能出现兼容性问题。".to_string());
+    }
+    notes
+}
+
 #[allow(clippy::struct_field_names)]
 struct SummaryReportParts {
     summary_b64: Option<String>,
@@ -451,36 +558,23 @@ fn try_open_zip(
                 let encrypted_len = enc.len();
                 let encrypted_prefix = enc.first().copied();
 
-                let decrypted = match decrypt_zip_entry(enc, decrypt_meta) {
-                    Ok(out) => {
-                        let decrypted_prefix = out.first().copied();
-                        let plain = out.get(1..).unwrap_or(&[]);
-                        let plain_sha256_hex = hex::encode(Sha256::digest(plain));
-                        let plain_preview_hex = if options.preview_plain_bytes == 0 {
-                            None
-                        } else {
-                            let n = options.preview_plain_bytes.min(plain.len());
-                            Some(hex::encode(&plain[..n]))
-                        };
+                let (decrypted, annotation) = match decrypt_zip_entry(enc, decrypt_meta) {
+                    Ok(out) => (
+                        build_decrypted_report(&out, options),
+                        options.annotate.then(|| annotate_entry(name, &out)),
+                    ),
+                    Err(e) => (
                         EntryDecryptedReport {
-                            ok: true,
-                            error: None,
-                            decrypted_len: Some(out.len()),
-                            decrypted_prefix_u8: decrypted_prefix,
-                            plain_len: Some(plain.len()),
-                            plain_sha256_hex: Some(plain_sha256_hex),
-                            plain_preview_hex,
-                        }
-                    }
-                    Err(e) => EntryDecryptedReport {
-                        ok: false,
-                        error: Some(e.to_string()),
-                        decrypted_len: None,
-                        decrypted_prefix_u8: None,
-                        plain_len: None,
-                        plain_sha256_hex: None,
-                        plain_preview_hex: None,
-                    },
+                            ok: false,
+                            error: Some(e.to_string()),
+                            decrypted_len: None,
+                            decrypted_prefix_u8: None,
+                            plain_len: None,
+                            plain_sha256_hex: None,
+                            plain_preview_hex: None,
+                        },
+                        None,
+                    ),
                 };
 
                 entries.push(EntryReport {
@@ -490,6 +584,7 @@ fn try_open_zip(
                     encrypted_prefix_u8: encrypted_prefix,
                     parser_handling,
                     decrypted,
+                    annotation,
                 });
             }
             Err(_) => {
@@ -508,6 +603,7 @@ fn try_open_zip(
                         plain_sha256_hex: None,
                         plain_preview_hex: None,
                     },
+                    annotation: None,
                 });
             }
         }
@@ -516,6 +612,28 @@ fn try_open_zip(
     Ok((zip_report, entries))
 }
 
+/// 解密后的 entry（含第 1 字节 prefix）→ 长度/哈希/预览摘要。
+fn build_decrypted_report(out: &[u8], options: &InspectOptions) -> EntryDecryptedReport {
+    let decrypted_prefix = out.first().copied();
+    let plain = out.get(1..).unwrap_or(&[]);
+    let plain_sha256_hex = hex::encode(Sha256::digest(plain));
+    let plain_preview_hex = if options.preview_plain_bytes == 0 {
+        None
+    } else {
+        let n = options.preview_plain_bytes.min(plain.len());
+        Some(hex::encode(&plain[..n]))
+    };
+    EntryDecryptedReport {
+        ok: true,
+        error: None,
+        decrypted_len: Some(out.len()),
+        decrypted_prefix_u8: decrypted_prefix,
+        plain_len: Some(plain.len()),
+        plain_sha256_hex: Some(plain_sha256_hex),
+        plain_preview_hex,
+    }
+}
+
 fn build_missing_entry_reports() -> Vec<EntryReport> {
     let expected = ["gameRecord", "gameKey", "gameProgress", "user", "settings"];
     expected
@@ -535,6 +653,7 @@ fn build_missing_entry_reports() -> Vec<EntryReport> {
                 plain_sha256_hex: None,
                 plain_preview_hex: None,
             },
+            annotation: None,
         })
         .collect()
 }
@@ -607,6 +726,52 @@ mod tests {
         assert!(out.contains("abcd"));
     }
 
+    #[test]
+    fn inspect_local_decrypted_entry_annotates_without_token() {
+        // user entry: prefix + flags + 三个字符串
+        let plain = vec![0u8, 1, 2, b'h', b'i', 1, b'a', 0];
+        let options = InspectOptions {
+            annotate: true,
+            ..InspectOptions::default()
+        };
+        let report = inspect_local_save(
+            LocalSaveInput::DecryptedEntry {
+                name: "user".to_string(),
+                plain,
+            },
+            "user.bin",
+            &options,
+        );
+        assert_eq!(report.meta.download_url, "user.bin");
+        assert!(report.zip.is_none());
+        assert_eq!(report.entries.len(), 1);
+        let ent = &report.entries[0];
+        assert!(ent.decrypted.ok);
+        assert_eq!(ent.decrypted.plain_len, Some(7));
+        let ann = ent.annotation.as_ref().expect("annotation");
+        assert!(ann.error.is_none());
+        assert!(
+            ann.spans
+                .iter()
+                .any(|s| s.field == "self_intro" && s.value == "\"hi\"")
+        );
+    }
+
+    #[test]
+    fn inspect_local_zip_reports_parse_failure_for_garbage() {
+        let report = inspect_local_save(
+            LocalSaveInput::Zip {
+                bytes: b"not a zip".to_vec(),
+                summary_b64: None,
+            },
+            "save.zip",
+            &InspectOptions::default(),
+        );
+        assert!(report.zip.is_none());
+        assert!(!report.transport.errors.is_empty());
+        assert!(report.entries.iter().all(|e| !e.present));
+    }
+
     #[test]
     fn redact_b64_long_is_truncated() {
         let raw = vec![0u8; 64];
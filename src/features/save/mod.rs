@@ -1,5 +1,6 @@
 pub mod client;
 pub mod decryptor;
+pub mod diff;
 pub mod handler;
 pub mod inspector;
 pub mod models;
@@ -14,7 +15,9 @@ use tokio::sync::Semaphore;
 
 // Re-exports for external use (main.rs, OpenAPI, etc.)
 pub use client::ExternalApiCredentials;
-pub use handler::{create_save_router, get_save_data};
+pub use handler::{
+    create_save_router, get_save_data, post_save_diff, post_save_diff_upload, post_save_upload,
+};
 pub use models::{SaveResponse, UnifiedSaveRequest};
 pub use provider::SaveSource;
 
@@ -39,7 +39,7 @@ mod float_serialize {
 }
 
 /// 统一的存档请求结构
-#[derive(Debug, Deserialize, Serialize, Clone, utoipa::ToSchema)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UnifiedSaveRequest {
     /// 官方 LeanCloud 会话令牌
@@ -57,6 +57,23 @@ pub struct UnifiedSaveRequest {
     pub taptap_version: Option<String>,
 }
 
+/// 存档上传表单（multipart/form-data；仅用于 OpenAPI 文档展示）
+#[allow(dead_code)]
+#[derive(Debug, utoipa::ToSchema)]
+pub struct SaveUploadForm {
+    /// 加密云存档 zip（与官方云端下载得到的 blob 格式相同）
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    /// 可选：存档 summary（base64，与 LeanCloud gameSave.summary 相同）
+    pub summary: Option<String>,
+}
+
+/// 原始存档二进制（application/octet-stream；仅用于 OpenAPI 文档展示）
+#[allow(dead_code)]
+#[derive(Debug, utoipa::ToSchema)]
+#[schema(value_type = String, format = Binary)]
+pub struct BinarySaveBlob(pub Vec<u8>);
+
 /// 存档响应结构
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct SaveResponse {
@@ -82,7 +99,19 @@ pub fn save_response_example() -> serde_json::Value {
 }
 
 /// 难度枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
 pub enum Difficulty {
     EZ,
     HD,
@@ -33,7 +33,10 @@ struct SaveLimits {
 
 impl SaveLimits {
     fn from_global() -> Result<Self, SaveProviderError> {
-        let cfg = &crate::config::AppConfig::global().save;
+        Self::from_config(&crate::config::AppConfig::global().save)
+    }
+
+    fn from_config(cfg: &crate::config::SaveLimitsConfig) -> Result<Self, SaveProviderError> {
         Ok(Self {
             max_download_bytes: usize::try_from(cfg.max_download_bytes)
                 .map_err(|_| SaveProviderError::Io("max_download_bytes 超出平台 usize".into()))?,
@@ -71,8 +74,18 @@ pub struct ParsedSave {
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum SaveSource {
-    Official { session_token: String },
-    ExternalApi { credentials: ExternalApiCredentials },
+    Official {
+        session_token: String,
+    },
+    ExternalApi {
+        credentials: ExternalApiCredentials,
+    },
+    /// 客户端直接上传的加密云存档 zip（离线导入，不访问 TapTap/LeanCloud）
+    #[serde(skip)]
+    Upload {
+        blob: Bytes,
+        summary_b64: Option<String>,
+    },
 }
 
 impl SaveSource {
@@ -85,6 +98,12 @@ impl SaveSource {
     pub fn external(credentials: ExternalApiCredentials) -> Self {
         Self::ExternalApi { credentials }
     }
+    pub fn upload(blob: impl Into<Bytes>, summary_b64: Option<String>) -> Self {
+        Self::Upload {
+            blob: blob.into(),
+            summary_b64,
+        }
+    }
 }
 
 /// 仅获取存档元信息（download_url / 解密参数 / updatedAt / summary），不下载存档本体。
@@ -110,6 +129,11 @@ pub async fn fetch_save_meta(
                 (url, DecryptionMeta::default(), None, ext_updated_at)
             }
         }
+        SaveSource::Upload { .. } => {
+            return Err(SaveProviderError::Unsupported(
+                "上传存档没有远端元信息，请直接调用 get_decrypted_save_from_upload".into(),
+            ));
+        }
     };
 
     Ok(SaveMeta {
@@ -126,10 +150,42 @@ pub async fn get_decrypted_save(
     taptap_config: &crate::config::TapTapMultiConfig,
     version: Option<&str>,
 ) -> Result<ParsedSave, SaveProviderError> {
+    if let SaveSource::Upload { blob, summary_b64 } = source {
+        return get_decrypted_save_from_upload(
+            blob,
+            summary_b64,
+            Arc::new(chart_constants.clone()),
+        )
+        .await;
+    }
     let meta = fetch_save_meta(source, taptap_config, version).await?;
     get_decrypted_save_from_meta(meta, Arc::new(chart_constants.clone())).await
 }
 
+/// 解析客户端上传的加密存档 zip（与官方下载的 blob 格式一致，使用默认 AES-256-CBC 参数解密）。
+///
+/// 上传体积受 `SaveLimitsConfig.max_download_bytes` 约束，解压/entry 上限与下载路径一致。
+pub async fn get_decrypted_save_from_upload(
+    blob: Bytes,
+    summary_b64: Option<String>,
+    chart_constants: Arc<ChartConstantsMap>,
+) -> Result<ParsedSave, SaveProviderError> {
+    let limits = SaveLimits::from_global()?;
+    if blob.len() > limits.max_download_bytes {
+        return Err(SaveProviderError::Io(format!(
+            "upload too large: bytes={} exceeds limit={}",
+            blob.len(),
+            limits.max_download_bytes
+        )));
+    }
+    if blob.is_empty() {
+        return Err(SaveProviderError::MissingField("save blob".to_string()));
+    }
+    let entries =
+        decode_save_blob_blocking(blob, DecryptionMeta::default(), limits, chart_constants).await?;
+    Ok(entries.into_parsed_save(summary_b64.as_deref(), None))
+}
+
 /// 使用已获取的元信息下载/解密/解析存档（用于缓存前移后的 miss 路径，避免重复请求元信息接口）。
 pub async fn get_decrypted_save_from_meta(
     meta: SaveMeta,
@@ -144,144 +200,174 @@ pub async fn get_decrypted_save_from_meta(
     } = meta;
 
     let encrypted_bytes = download_encrypted_save(&download_url, limits.max_download_bytes).await?;
+    let entries =
+        decode_save_blob_blocking(encrypted_bytes, decrypt_meta, limits, chart_constants).await?;
+    Ok(entries.into_parsed_save(summary_b64.as_deref(), updated_at))
+}
+
+/// 从 zip 中解出的各 entry（gameRecord 已按定数表解析）
+struct DecodedEntries {
+    game_record: HashMap<String, Vec<DifficultyRecord>>,
+    game_progress: Option<super::parser::GameProgressParsed>,
+    user: Option<super::parser::UserParsed>,
+    settings: Option<super::parser::SettingsParsed>,
+    game_key: Option<super::parser::GameKeyParsed>,
+}
+
+impl DecodedEntries {
+    fn into_parsed_save(self, summary_b64: Option<&str>, updated_at: Option<String>) -> ParsedSave {
+        let summary_parsed = summary_b64.and_then(|b64| parse_summary_base64(b64).ok());
+        ParsedSave {
+            game_record: self.game_record,
+            game_progress: self.game_progress,
+            user: self.user,
+            settings: self.settings,
+            game_key: self.game_key,
+            summary_parsed,
+            updated_at,
+        }
+    }
+}
 
+/// 在 blocking 线程池中执行解压/解密/解析（受 save decode 信号量约束）。
+async fn decode_save_blob_blocking(
+    encrypted_bytes: Bytes,
+    decrypt_meta: DecryptionMeta,
+    limits: SaveLimits,
+    chart_constants: Arc<ChartConstantsMap>,
+) -> Result<DecodedEntries, SaveProviderError> {
     let permit = super::save_decode_blocking_semaphore()
         .clone()
         .acquire_owned()
         .await
         .map_err(|e| SaveProviderError::Io(format!("save blocking semaphore closed: {e}")))?;
-    let chart_constants_for_parse = Arc::clone(&chart_constants);
 
     // 注意：解压/解密/解析属于 CPU/内存密集型同步任务，为避免阻塞 Tokio worker，这里 offload 到 blocking 线程池。
     let join = tokio::task::spawn_blocking(move || {
         let _permit = permit;
-        let zip_bytes = try_decompress(encrypted_bytes, limits.max_decompress_bytes);
-        let mut archive = zip::ZipArchive::new(Cursor::new(zip_bytes))?;
-
-        // P1：PBKDF2 key 在单份 save 内只派生一次并复用，避免按 entry 重复派生。
-        let derived_key_arr = if matches!(
-            &decrypt_meta.kdf,
-            super::decryptor::KdfSpec::Pbkdf2Sha1 { .. }
-        ) {
-            let key_bytes = derive_key(&decrypt_meta.kdf, 32)?;
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(&key_bytes);
-            Some(arr)
-        } else {
-            None
-        };
-
-        let mut game_record_entry: Option<Vec<u8>> = None;
-        let mut game_key: Option<super::parser::GameKeyParsed> = None;
-        let mut game_progress: Option<super::parser::GameProgressParsed> = None;
-        let mut user: Option<super::parser::UserParsed> = None;
-        let mut settings: Option<super::parser::SettingsParsed> = None;
-        let archive_len = archive.len();
-        if archive_len > limits.max_zip_entries {
-            return Err(SaveProviderError::Io(format!(
-                "zip entry 数量超限: count={} limit={}",
-                archive_len, limits.max_zip_entries
-            )));
-        }
-        let mut matched_count = 0usize;
-        for idx in 0..archive_len {
-            let mut f = archive.by_index(idx)?;
-            let name = {
-                let raw_name = f.name();
-                raw_name.rsplit('/').next().unwrap_or(raw_name).to_owned()
-            };
-            let supported = matches!(
-                name.as_str(),
-                "gameRecord" | "gameKey" | "gameProgress" | "user" | "settings"
-            );
-            if !supported {
-                continue;
-            }
-            if (name == "gameRecord" && game_record_entry.is_some())
-                || (name == "gameKey" && game_key.is_some())
-                || (name == "gameProgress" && game_progress.is_some())
-                || (name == "user" && user.is_some())
-                || (name == "settings" && settings.is_some())
-            {
-                continue;
-            }
-
-            matched_count = matched_count.saturating_add(1);
-            // zip entry 通常携带 size 信息，预分配可以显著减少扩容次数。
-            let size = usize::try_from(f.size()).unwrap_or(usize::MAX);
-            let mut enc = Vec::with_capacity(size.min(ZIP_ENTRY_PREALLOC_CAP));
-            read_to_end_limited(
-                &mut f,
-                &mut enc,
-                limits.max_zip_entry_bytes,
-                "zip entry 读取超限",
-            )?;
-            let plain =
-                decrypt_zip_entry_with_derived_key(enc, &decrypt_meta, derived_key_arr.as_ref())?;
-            if name == "gameRecord" {
-                game_record_entry = Some(plain);
-            } else {
-                match name.as_str() {
-                    "gameKey" => game_key = Some(super::parser::parse_game_key_entry(&plain)?),
-                    "gameProgress" => {
-                        game_progress = Some(super::parser::parse_game_progress_entry(&plain)?);
-                    }
-                    "user" => user = Some(super::parser::parse_user_entry(&plain)?),
-                    "settings" => settings = Some(super::parser::parse_settings_entry(&plain)?),
-                    _ => {}
-                }
-            }
-
-            if matched_count >= 5 {
-                break;
-            }
-        }
-
-        let game_record_entry = game_record_entry
-            .ok_or_else(|| SaveProviderError::MissingField("gameRecord".to_string()))?;
-        let game_record = record_parser::parse_game_record_bytes(
-            &game_record_entry,
-            chart_constants_for_parse.as_ref(),
+        decode_save_blob(
+            encrypted_bytes,
+            &decrypt_meta,
+            limits,
+            chart_constants.as_ref(),
         )
-        .map_err(|e| SaveProviderError::Json(format!("parse gameRecord failed: {e}")))?;
-        Ok::<
-            (
-                HashMap<String, Vec<DifficultyRecord>>,
-                Option<super::parser::GameProgressParsed>,
-                Option<super::parser::UserParsed>,
-                Option<super::parser::SettingsParsed>,
-                Option<super::parser::GameKeyParsed>,
-            ),
-            SaveProviderError,
-        >((game_record, game_progress, user, settings, game_key))
     })
     .await;
-    let (game_record, game_progress, user, settings, game_key) = match join {
-        Ok(res) => res?,
+    match join {
+        Ok(res) => res,
         Err(e) => {
             // 为保持“异常情况下”的原有行为：如果 blocking 任务发生 panic，则继续向上传播 panic。
             let e_str = e.to_string();
             if let Ok(panic) = e.try_into_panic() {
                 std::panic::resume_unwind(panic);
             }
-            return Err(SaveProviderError::Io(format!(
+            Err(SaveProviderError::Io(format!(
                 "spawn_blocking cancelled: {e_str}"
-            )));
+            )))
         }
+    }
+}
+
+/// 同步解码一份存档 blob：解压 → 逐 entry 解密 → 解析。
+fn decode_save_blob(
+    encrypted_bytes: Bytes,
+    decrypt_meta: &DecryptionMeta,
+    limits: SaveLimits,
+    chart_constants: &ChartConstantsMap,
+) -> Result<DecodedEntries, SaveProviderError> {
+    let zip_bytes = try_decompress(encrypted_bytes, limits.max_decompress_bytes);
+    let mut archive = zip::ZipArchive::new(Cursor::new(zip_bytes))?;
+
+    // P1：PBKDF2 key 在单份 save 内只派生一次并复用，避免按 entry 重复派生。
+    let derived_key_arr = if matches!(
+        &decrypt_meta.kdf,
+        super::decryptor::KdfSpec::Pbkdf2Sha1 { .. }
+    ) {
+        let key_bytes = derive_key(&decrypt_meta.kdf, 32)?;
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&key_bytes);
+        Some(arr)
+    } else {
+        None
     };
 
-    let summary_parsed = summary_b64
-        .as_deref()
-        .and_then(|b64| parse_summary_base64(b64).ok());
+    let mut game_record_entry: Option<Vec<u8>> = None;
+    let mut game_key: Option<super::parser::GameKeyParsed> = None;
+    let mut game_progress: Option<super::parser::GameProgressParsed> = None;
+    let mut user: Option<super::parser::UserParsed> = None;
+    let mut settings: Option<super::parser::SettingsParsed> = None;
+    let archive_len = archive.len();
+    if archive_len > limits.max_zip_entries {
+        return Err(SaveProviderError::Io(format!(
+            "zip entry 数量超限: count={} limit={}",
+            archive_len, limits.max_zip_entries
+        )));
+    }
+    let mut matched_count = 0usize;
+    for idx in 0..archive_len {
+        let mut f = archive.by_index(idx)?;
+        let name = {
+            let raw_name = f.name();
+            raw_name.rsplit('/').next().unwrap_or(raw_name).to_owned()
+        };
+        let supported = matches!(
+            name.as_str(),
+            "gameRecord" | "gameKey" | "gameProgress" | "user" | "settings"
+        );
+        if !supported {
+            continue;
+        }
+        if (name == "gameRecord" && game_record_entry.is_some())
+            || (name == "gameKey" && game_key.is_some())
+            || (name == "gameProgress" && game_progress.is_some())
+            || (name == "user" && user.is_some())
+            || (name == "settings" && settings.is_some())
+        {
+            continue;
+        }
 
-    Ok(ParsedSave {
+        matched_count = matched_count.saturating_add(1);
+        // zip entry 通常携带 size 信息，预分配可以显著减少扩容次数。
+        let size = usize::try_from(f.size()).unwrap_or(usize::MAX);
+        let mut enc = Vec::with_capacity(size.min(ZIP_ENTRY_PREALLOC_CAP));
+        read_to_end_limited(
+            &mut f,
+            &mut enc,
+            limits.max_zip_entry_bytes,
+            "zip entry 读取超限",
+        )?;
+        let plain =
+            decrypt_zip_entry_with_derived_key(enc, decrypt_meta, derived_key_arr.as_ref())?;
+        if name == "gameRecord" {
+            game_record_entry = Some(plain);
+        } else {
+            match name.as_str() {
+                "gameKey" => game_key = Some(super::parser::parse_game_key_entry(&plain)?),
+                "gameProgress" => {
+                    game_progress = Some(super::parser::parse_game_progress_entry(&plain)?);
+                }
+                "user" => user = Some(super::parser::parse_user_entry(&plain)?),
+                "settings" => settings = Some(super::parser::parse_settings_entry(&plain)?),
+                _ => {}
+            }
+        }
+
+        if matched_count >= 5 {
+            break;
+        }
+    }
+
+    let game_record_entry = game_record_entry
+        .ok_or_else(|| SaveProviderError::MissingField("gameRecord".to_string()))?;
+    let game_record =
+        record_parser::parse_game_record_bytes(&game_record_entry, chart_constants)
+            .map_err(|e| SaveProviderError::Json(format!("parse gameRecord failed: {e}")))?;
+    Ok(DecodedEntries {
         game_record,
         game_progress,
         user,
         settings,
         game_key,
-        summary_parsed,
-        updated_at,
     })
 }
 
@@ -442,6 +528,100 @@ mod tests {
         assert_eq!(out, gz);
     }
 
+    /// 用 codec 编码器 + 默认密钥构造一份离线存档 zip（gameRecord/user/settings）
+    fn build_fixture_save_zip() -> Vec<u8> {
+        use std::collections::BTreeMap;
+        use zip::write::SimpleFileOptions;
+
+        let mut levels = BTreeMap::new();
+        levels.insert(
+            "Test.Song".to_string(),
+            phi_save_codec::SongLevelRecord {
+                ez: None,
+                hd: None,
+                r#in: Some(phi_save_codec::LevelRecord {
+                    score: 1_000_000,
+                    acc: 100.0,
+                    fc: true,
+                }),
+                at: Some(phi_save_codec::LevelRecord {
+                    score: 950_000,
+                    acc: 98.5,
+                    fc: false,
+                }),
+            },
+        );
+        let game_record = phi_save_codec::encode_game_record_levels(1, &levels).unwrap();
+        let user = phi_save_codec::encode_user_entry(
+            1,
+            &phi_save_codec::UserParsed {
+                show_player_id: true,
+                self_intro: "hi".into(),
+                avatar: "Introduction".into(),
+                background: "Glaciaxion.SunsetRay".into(),
+                unknown_flags: None,
+                overflow: None,
+            },
+        )
+        .unwrap();
+
+        let mut w = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, plain) in [("gameRecord", game_record), ("user", user)] {
+            w.start_file(name, SimpleFileOptions::default()).unwrap();
+            let enc = super::super::decryptor::encrypt_zip_entry(&plain).unwrap();
+            w.write_all(&enc).unwrap();
+        }
+        w.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn decode_save_blob_runs_offline_pipeline_through_rks() {
+        use crate::startup::chart_loader::ChartConstants;
+
+        let mut chart_constants = ChartConstantsMap::new();
+        chart_constants.insert(
+            "Test.Song".to_string(),
+            ChartConstants {
+                ez: None,
+                hd: None,
+                in_level: Some(12.0),
+                at: Some(15.0),
+            },
+        );
+        let limits = SaveLimits::from_config(&crate::config::SaveLimitsConfig::default()).unwrap();
+        let blob = Bytes::from(build_fixture_save_zip());
+
+        let entries =
+            decode_save_blob(blob, &DecryptionMeta::default(), limits, &chart_constants).unwrap();
+        let parsed = entries.into_parsed_save(None, None);
+        let user = parsed.user.as_ref().expect("user entry");
+        assert_eq!(user.avatar, "Introduction");
+        let recs = parsed.game_record.get("Test.Song").expect("song parsed");
+        assert_eq!(recs.len(), 2);
+        assert_eq!(recs[0].chart_constant, Some(12.0));
+
+        let rks = crate::rks_contract::engine::calculate_player_rks(
+            &parsed.game_record,
+            &chart_constants,
+        );
+        assert!(rks.total_rks > 0.0);
+        assert!(rks.b30_charts.iter().any(|c| c.song_id == "Test.Song"));
+    }
+
+    #[test]
+    fn decode_save_blob_rejects_zip_without_game_record() {
+        let mut w = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        w.start_file("user", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        w.write_all(&super::super::decryptor::encrypt_zip_entry(&[1, 0, 0, 0, 0]).unwrap())
+            .unwrap();
+        let blob = Bytes::from(w.finish().unwrap().into_inner());
+        let limits = SaveLimits::from_config(&crate::config::SaveLimitsConfig::default()).unwrap();
+
+        let res = decode_save_blob(blob, &DecryptionMeta::default(), limits, &HashMap::new());
+        assert!(matches!(res, Err(SaveProviderError::MissingField(_))));
+    }
+
     #[test]
     fn read_to_end_limited_returns_error_when_exceeds_limit() {
         let data = vec![1u8; 32];
@@ -1,12 +1,13 @@
 use axum::{
     Router,
     extract::{Query, State},
+    http::HeaderMap,
     response::{IntoResponse, Json},
-    routing::get,
+    routing::{get, post},
 };
 
 use crate::error::AppError;
-use crate::features::song::models::{SearchMode, SongCandidatePreview, SongInfo};
+use crate::features::song::models::{SearchMode, SearchOptions, SongCandidatePreview, SongInfo};
 use crate::state::AppState;
 
 const DEFAULT_LIMIT: u32 = 20;
@@ -56,6 +57,8 @@ pub struct SongSearchQuery {
     limit: Option<u32>,
     /// 结果偏移（可选，默认 0）
     offset: Option<u32>,
+    /// 是否启用拼音/罗马音匹配（可选，支持 1/true/yes/on）
+    pinyin: Option<String>,
 }
 
 fn parse_bool(s: &str) -> bool {
@@ -99,13 +102,14 @@ fn build_song_page(items: Vec<SongInfo>, total: usize, limit: u32, offset: u32)
     get,
     path = "/songs/search",
     summary = "歌曲检索（支持别名与模糊匹配）",
-    description = "默认按 ID/官方名/别名进行搜索。显式传 `mode=and|or` 时启用多关键词搜索，支持空格分词、双引号短语与前缀 `-` 排除。`unique=true` 时期望唯一命中，未命中返回 404，多命中返回 409。",
+    description = "默认按 ID/官方名/别名进行搜索。显式传 `mode=and|or` 时启用多关键词搜索，支持空格分词、双引号短语与前缀 `-` 排除。`unique=true` 时期望唯一命中，未命中返回 404，多命中返回 409。`pinyin=true` 时额外按全拼/首字母（如 `yiwang`、`ywzj`）与假名罗马音匹配官方名和别名，命中排在子串匹配之后、模糊匹配之前。",
     params(
         ("q" = String, Query, description = "查询字符串"),
         ("unique" = Option<bool>, Query, description = "是否强制唯一匹配（可选）"),
         ("mode" = Option<String>, Query, description = "多关键词模式（可选：and/or）。仅显式传入时启用多关键词搜索"),
         ("limit" = Option<u32>, Query, description = "最大返回条数（可选，默认 20，上限 100，最小 1）"),
-        ("offset" = Option<u32>, Query, description = "结果偏移（可选，默认 0）")
+        ("offset" = Option<u32>, Query, description = "结果偏移（可选，默认 0）"),
+        ("pinyin" = Option<bool>, Query, description = "是否启用拼音/罗马音匹配（可选，默认 false）")
     ),
     responses(
         (status = 200, description = "查询成功（unique=true 时返回单个对象，否则为分页对象）", body = SongSearchResult),
@@ -158,6 +162,10 @@ pub async fn search_songs(
     }
 
     let unique = params.unique.as_deref().is_some_and(parse_bool);
+    let options = SearchOptions {
+        pinyin: params.pinyin.as_deref().is_some_and(parse_bool),
+        ..SearchOptions::default()
+    };
     let multi_mode = match params.mode.as_deref() {
         None => None,
         Some(raw) => Some(
@@ -178,6 +186,7 @@ pub async fn search_songs(
         let extra = serde_json::json!({
             "unique": unique,
             "multi_mode": params.mode.as_deref(),
+            "pinyin": options.pinyin,
             "q_len": q_len,
             "limit": limit,
             "offset": offset
@@ -185,12 +194,10 @@ pub async fn search_songs(
         stats_handle.track_feature("song_search", "search", None, Some(extra));
     }
 
+    let game = state.game_data();
+    let catalog = game.song_catalog.as_ref();
     if let Some(mode) = multi_mode {
-        let results = state.song_catalog.search_multi(
-            q,
-            mode,
-            crate::features::song::models::SearchOptions::default(),
-        );
+        let results = catalog.search_multi(q, mode, options);
 
         if unique {
             match results.as_slice() {
@@ -220,15 +227,110 @@ pub async fn search_songs(
             Ok(Json(build_song_page(page_items, total, limit, offset)).into_response())
         }
     } else if unique {
-        let item = state.song_catalog.search_unique(q)?;
+        let item = catalog.search_unique_with_options(q, options)?;
         Ok(Json::<SongInfo>(item.as_ref().clone()).into_response())
     } else {
-        let (items, total) = state.song_catalog.search_page(q, offset, limit);
+        let (items, total) = catalog.search_page_with_options(q, offset, limit, options);
         let page_items: Vec<SongInfo> = items.iter().map(|a| a.as_ref().clone()).collect();
         Ok(Json(build_song_page(page_items, total, limit, offset)).into_response())
     }
 }
 
+#[derive(serde::Deserialize)]
+pub struct InfoReloadQuery {
+    /// 数据来源：local / remote（默认：配置了 info_base_url 时为 remote，否则 local）
+    source: Option<String>,
+    /// remote 时忽略 ETag 缓存强制下载；local 时允许覆盖当前的远端数据
+    force: Option<bool>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/info/reload",
+    summary = "热更新定数表与歌曲目录",
+    description = "重新加载 difficulty.csv / info.csv / nicklist.yaml，校验通过后原子替换线上数据并清空图片/存档缓存；在途请求继续使用旧版本。同时重新加载 resources.constant_tables 中的归档定数表。远端数据不会写入本地 info 目录，因此当前数据来自远端时 source=local 会被拒绝（409），需携带 force=true 才会回退到本地文件。需要在 Header 中提供 X-Admin-Token。",
+    params(
+        ("X-Admin-Token" = String, Header, description = "管理员令牌（config.leaderboard.admin_tokens）"),
+        ("source" = Option<String>, Query, description = "local | remote；默认在配置了 resources.info_base_url 时为 remote"),
+        ("force" = Option<bool>, Query, description = "remote 时忽略 ETag 缓存强制下载；local 时允许用本地文件覆盖当前的远端数据")
+    ),
+    security(("AdminToken" = [])),
+    responses(
+        (status = 200, description = "重载结果（远端无更新时 reloaded=false）", body = crate::game_data::ReloadOutcome),
+        (
+            status = 401,
+            description = "管理员令牌缺失/无效",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 409,
+            description = "当前数据来自远端且未携带 force=true，拒绝以本地文件回退",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 422,
+            description = "来源参数无效或新数据校验失败（线上数据保持不变）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        ),
+        (
+            status = 500,
+            description = "读取/解析 info 文件失败（线上数据保持不变）",
+            body = crate::error::ProblemDetails,
+            content_type = "application/problem+json"
+        )
+    ),
+    tag = "Song"
+)]
+pub async fn post_admin_info_reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<InfoReloadQuery>,
+) -> Result<Json<crate::game_data::ReloadOutcome>, AppError> {
+    crate::leaderboard_contract::require_admin(&headers)?;
+    let cfg = crate::config::AppConfig::global();
+    let info_dir = cfg.info_path();
+    let base_url = cfg.resources.info_base_url.as_deref();
+
+    let outcome = match (q.source.as_deref().map(str::trim), base_url) {
+        (Some("local"), _) | (None, None) => {
+            crate::game_data::reload_from_local(&state, &info_dir, q.force.unwrap_or(false)).await?
+        }
+        (Some("remote") | None, Some(base_url)) => {
+            crate::game_data::reload_from_remote(
+                &state,
+                base_url,
+                &info_dir,
+                q.force.unwrap_or(false),
+            )
+            .await?
+        }
+        (Some("remote"), None) => {
+            return Err(AppError::Validation(
+                "未配置 resources.info_base_url，无法从远端重载".into(),
+            ));
+        }
+        (Some(other), _) => {
+            return Err(AppError::Validation(format!(
+                "source 无效: {other}（可选 local/remote）"
+            )));
+        }
+    };
+    // 归档定数表始终来自本地文件，随每次重载一并刷新
+    let configs = cfg.resources.constant_tables.clone();
+    let tables = tokio::task::spawn_blocking(move || {
+        crate::constant_tables::load_constant_tables(&info_dir, &configs)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("归档定数表加载任务失败: {e}")))?;
+    state.game_data.replace_constant_tables(tables);
+    Ok(Json(outcome))
+}
+
 pub fn create_song_router() -> Router<AppState> {
-    Router::new().route("/songs/search", get(search_songs))
+    Router::new()
+        .route("/songs/search", get(search_songs))
+        .route("/admin/info/reload", post(post_admin_info_reload))
 }
@@ -1,4 +1,5 @@
 pub mod handler;
 pub mod models;
+mod phonetic;
 
 pub use handler::create_song_router;
@@ -5,6 +5,8 @@ use std::{
 
 use crate::startup::chart_loader::ChartConstants;
 
+use super::phonetic::{PhoneticKey, romanize_query};
+
 pub(crate) fn normalize_song_search_text(input: &str) -> String {
     input
         .chars()
@@ -54,6 +56,12 @@ pub struct SongCatalog {
     pub by_nickname: HashMap<String, Vec<Arc<SongInfo>>>,
     search_cache_name_lower: Vec<(Arc<SongInfo>, String, String)>,
     search_cache_nick_lower: Vec<(String, String, String, Vec<Arc<SongInfo>>)>,
+    /// 含汉字/假名的官方名的拼音/罗马音检索键
+    search_cache_name_phonetic: Vec<(Arc<SongInfo>, PhoneticKey)>,
+    /// 含汉字/假名的别名的拼音/罗马音检索键
+    search_cache_nick_phonetic: Vec<(PhoneticKey, Vec<Arc<SongInfo>>)>,
+    /// 多关键词检索用：字段原文（官方名/作曲者/别名）到拼音/罗马音检索键的映射
+    search_cache_field_phonetic: HashMap<String, PhoneticKey>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -64,6 +72,7 @@ enum SearchMatchKind {
     NickEquals,
     NickPrefix,
     NickContains,
+    Phonetic,
     NameFuzzy,
     NickFuzzy,
 }
@@ -98,10 +107,34 @@ impl SongCatalog {
             SearchMatchKind::NickPrefix => 3000 + q_len * 5 - extra_len_penalty,
             SearchMatchKind::NameContains => 2000 + pos_bonus - extra_len_penalty,
             SearchMatchKind::NickContains => 1000 + pos_bonus - extra_len_penalty,
-            SearchMatchKind::NameFuzzy | SearchMatchKind::NickFuzzy => 0,
+            SearchMatchKind::Phonetic | SearchMatchKind::NameFuzzy | SearchMatchKind::NickFuzzy => {
+                0
+            }
         }
     }
 
+    /// 拼音/罗马音命中：全拼优于首字母，官方名优于别名，检索键越短越优。
+    fn score_phonetic_match(q_key: &str, hay_key: &str, by_initials: bool, is_name: bool) -> i32 {
+        let q_len = Self::usize_to_i32_saturating(q_key.len());
+        let hay_len = Self::usize_to_i32_saturating(hay_key.len());
+        let len_penalty = (hay_len - q_len).clamp(0, 40);
+        let base = if by_initials { 940 } else { 980 };
+        base + if is_name { 10 } else { 0 } - len_penalty
+    }
+
+    /// 判断查询键是否命中某个拼音/罗马音检索键，返回 (命中的键, 是否按首字母命中)。
+    ///
+    /// 全拼：前缀命中，或长度 >= 3 时子串命中；首字母：长度 >= 2 时前缀命中。
+    fn phonetic_hit<'k>(q_key: &str, key: &'k PhoneticKey) -> Option<(&'k str, bool)> {
+        if key.full.starts_with(q_key) || (q_key.len() >= 3 && key.full.contains(q_key)) {
+            return Some((key.full.as_str(), false));
+        }
+        if q_key.len() >= 2 && key.initials.starts_with(q_key) {
+            return Some((key.initials.as_str(), true));
+        }
+        None
+    }
+
     fn score_fuzzy_match(
         kind: SearchMatchKind,
         q_normalized: &str,
@@ -132,8 +165,9 @@ impl SongCatalog {
             SearchMatchKind::NickPrefix => 3,
             SearchMatchKind::NameContains => 4,
             SearchMatchKind::NickContains => 5,
-            SearchMatchKind::NameFuzzy => 6,
-            SearchMatchKind::NickFuzzy => 7,
+            SearchMatchKind::Phonetic => 6,
+            SearchMatchKind::NameFuzzy => 7,
+            SearchMatchKind::NickFuzzy => 8,
         }
     }
 
@@ -183,7 +217,7 @@ impl SongCatalog {
         (distance <= max_distance).then_some(distance)
     }
 
-    fn visit_search_matches<F>(&self, query: &str, mut on_match: F)
+    fn visit_search_matches<F>(&self, query: &str, pinyin: bool, mut on_match: F)
     where
         F: FnMut(&Arc<SongInfo>, SearchMatchKind, i32),
     {
@@ -313,6 +347,34 @@ impl SongCatalog {
             }
         }
 
+        // 拼音/罗马音（需显式开启）：全拼 / 首字母 / 假名罗马音
+        if pinyin {
+            let q_key = romanize_query(q);
+            if !q_key.is_empty() {
+                for (item, key) in &self.search_cache_name_phonetic {
+                    if let Some((hay_key, by_initials)) = Self::phonetic_hit(&q_key, key)
+                        && seen.insert(item.id.as_str())
+                    {
+                        on_match(
+                            item,
+                            SearchMatchKind::Phonetic,
+                            Self::score_phonetic_match(&q_key, hay_key, by_initials, true),
+                        );
+                    }
+                }
+                for (key, list) in &self.search_cache_nick_phonetic {
+                    if let Some((hay_key, by_initials)) = Self::phonetic_hit(&q_key, key) {
+                        let score = Self::score_phonetic_match(&q_key, hay_key, by_initials, false);
+                        for item in list {
+                            if seen.insert(item.id.as_str()) {
+                                on_match(item, SearchMatchKind::Phonetic, score);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
         if seen.is_empty() && !q_normalized.is_empty() {
             let max_distance = Self::fuzzy_distance_limit(q_normalized.chars().count());
 
@@ -366,6 +428,18 @@ impl SongCatalog {
     /// 设计目标：避免 HTTP 层“先全量构建 Vec 再切片”的不必要分配；同时保持与 `search()` 一致的排序语义。
     #[must_use]
     pub fn search_page(&self, query: &str, offset: u32, limit: u32) -> (Vec<Arc<SongInfo>>, usize) {
+        self.search_page_with_options(query, offset, limit, SearchOptions::default())
+    }
+
+    /// 同 `search_page`，但读取 `options.pinyin` 决定是否启用拼音/罗马音层级（其余选项仅用于多关键词搜索）。
+    #[must_use]
+    pub fn search_page_with_options(
+        &self,
+        query: &str,
+        offset: u32,
+        limit: u32,
+        options: SearchOptions,
+    ) -> (Vec<Arc<SongInfo>>, usize) {
         let q = query.trim();
         if q.is_empty() {
             return (Vec::new(), 0);
@@ -400,13 +474,13 @@ impl SongCatalog {
 
         if max_take == 0 {
             // 语义保持：limit=0 视为不返回 items，但 total 仍应正确（调用方通常已校验 limit>=1）。
-            self.visit_search_matches(q, |_item, _kind, _score| {
+            self.visit_search_matches(q, options.pinyin, |_item, _kind, _score| {
                 total = total.saturating_add(1);
             });
             return (items, total);
         }
 
-        self.visit_search_matches(q, |item, _kind, _score| {
+        self.visit_search_matches(q, options.pinyin, |item, _kind, _score| {
             if total >= start && total < end {
                 items.push(Arc::clone(item));
             }
@@ -469,6 +543,37 @@ impl SongCatalog {
             a_lower.cmp(b_lower).then_with(|| a_nick.cmp(b_nick))
         });
         self.search_cache_nick_lower = nick_entries;
+
+        // 3) 拼音/罗马音缓存：沿用上面的稳定顺序，仅收录含汉字/假名的条目
+        self.search_cache_name_phonetic = self
+            .search_cache_name_lower
+            .iter()
+            .filter_map(|(item, _, _)| {
+                PhoneticKey::from_text(&item.name).map(|key| (Arc::clone(item), key))
+            })
+            .collect();
+        self.search_cache_nick_phonetic = self
+            .search_cache_nick_lower
+            .iter()
+            .filter_map(|(nick, _, _, list)| {
+                PhoneticKey::from_text(nick).map(|key| (key, list.clone()))
+            })
+            .collect();
+        let name_keys = self
+            .search_cache_name_phonetic
+            .iter()
+            .map(|(item, key)| (item.name.clone(), key.clone()));
+        let composer_keys = self.by_id.values().filter_map(|item| {
+            PhoneticKey::from_text(&item.composer).map(|key| (item.composer.clone(), key))
+        });
+        let nick_keys = self
+            .search_cache_nick_lower
+            .iter()
+            .filter_map(|(nick, _, _, _)| {
+                PhoneticKey::from_text(nick).map(|key| (nick.clone(), key))
+            });
+        self.search_cache_field_phonetic =
+            name_keys.chain(composer_keys).chain(nick_keys).collect();
     }
 
     /// 通用查询：按 ID -> 官方名/别名 的顺序查找。
@@ -478,6 +583,8 @@ impl SongCatalog {
     /// 3) 官方名：前缀包含 -> 别名：前缀包含
     /// 4) 官方名：子串包含 -> 别名：子串包含
     /// 5) 若前四层完全无命中，则回退到归一化文本的轻量模糊匹配
+    ///
+    /// 拼音/罗马音层级（位于子串之后、模糊之前）需通过 `search_page_with_options` 显式开启。
     #[must_use]
     pub fn search(&self, query: &str) -> Vec<Arc<SongInfo>> {
         let (items, _total) = self.search_page(query, 0, u32::MAX);
@@ -486,6 +593,15 @@ impl SongCatalog {
 
     /// 强制唯一查询：当结果为 0/多于 1 时返回错误。
     pub fn search_unique(&self, query: &str) -> Result<Arc<SongInfo>, crate::error::SearchError> {
+        self.search_unique_with_options(query, SearchOptions::default())
+    }
+
+    /// 同 `search_unique`，但读取 `options.pinyin` 决定是否启用拼音/罗马音层级。
+    pub fn search_unique_with_options(
+        &self,
+        query: &str,
+        options: SearchOptions,
+    ) -> Result<Arc<SongInfo>, crate::error::SearchError> {
         use std::cmp::Ordering;
 
         use crate::error::SearchError;
@@ -542,7 +658,7 @@ impl SongCatalog {
         // 说明：
         // - unique 判定只看“首个非空匹配层级”，避免较弱 contains 把明显唯一的 exact/prefix 命中误判为歧义。
         // - 同一层级内仍会统计 total，并仅保留受控数量的候选预览。
-        self.visit_search_matches(q, |item, kind, score| {
+        self.visit_search_matches(q, options.pinyin, |item, kind, score| {
             let priority = Self::match_priority(kind);
             match best_priority {
                 None => best_priority = Some(priority),
@@ -624,7 +740,7 @@ impl SongCatalog {
         let mut negatives: Vec<HashSet<&str>> = Vec::new();
 
         for t in &tokens {
-            let matched = self.match_token(&t.text, options);
+            let matched = self.match_token(t, options);
             if t.is_exclude {
                 negatives.push(matched);
             } else {
@@ -674,14 +790,14 @@ impl SongCatalog {
     }
 
     /// 针对单个 token 生成命中的歌曲 ID 集合（按任意字段）
-    fn match_token<'a>(&'a self, token: &str, options: SearchOptions) -> HashSet<&'a str> {
+    fn match_token<'a>(&'a self, token: &Token, options: SearchOptions) -> HashSet<&'a str> {
         let mut set: HashSet<&str> = HashSet::new();
 
         // 遍历所有歌曲：ID / 官方名 / 作曲者
         for s in self.by_id.values() {
-            if field_match(&s.id, token, options)
-                || field_match(&s.name, token, options)
-                || field_match(&s.composer, token, options)
+            if field_match(&s.id, token, options, self)
+                || field_match(&s.name, token, options, self)
+                || field_match(&s.composer, token, options, self)
             {
                 set.insert(s.id.as_str());
             }
@@ -689,7 +805,7 @@ impl SongCatalog {
 
         // 别名匹配：匹配到键则将该键下的歌曲全部加入
         for (nick, list) in &self.by_nickname {
-            if field_match(nick, token, options) {
+            if field_match(nick, token, options, self) {
                 for s in list {
                     set.insert(s.id.as_str());
                 }
@@ -713,7 +829,8 @@ pub struct SearchOptions {
     pub case_insensitive: bool,
     pub prefix: bool,
     pub substring: bool,
-    pub pinyin: bool, // 预留（当前未实现）
+    /// 拼音/罗马音匹配（全拼、首字母、假名罗马音；默认关闭）
+    pub pinyin: bool,
     pub enable_not: bool,
     pub enable_phrase: bool,
 }
@@ -736,6 +853,8 @@ impl Default for SearchOptions {
 struct Token {
     text: String,
     is_exclude: bool,
+    /// 拼音/罗马音查询键（仅开启 pinyin 时计算，每个 token 只算一次）
+    phonetic: String,
 }
 
 /// 将原始查询串解析为 tokens：支持双引号短语与负号排除
@@ -758,7 +877,11 @@ fn parse_tokens(input: &str, options: SearchOptions) -> Vec<Token> {
                     // 结束短语
                     let text = buf.trim().to_string();
                     if !text.is_empty() {
-                        tokens.push(Token { text, is_exclude });
+                        tokens.push(Token {
+                            text,
+                            is_exclude,
+                            phonetic: String::new(),
+                        });
                     }
                     buf.clear();
                     in_quote = false;
@@ -781,7 +904,11 @@ fn parse_tokens(input: &str, options: SearchOptions) -> Vec<Token> {
             c if c.is_whitespace() && !in_quote => {
                 let text = buf.trim().to_string();
                 if !text.is_empty() {
-                    tokens.push(Token { text, is_exclude });
+                    tokens.push(Token {
+                        text,
+                        is_exclude,
+                        phonetic: String::new(),
+                    });
                 }
                 buf.clear();
                 is_exclude = false;
@@ -791,18 +918,46 @@ fn parse_tokens(input: &str, options: SearchOptions) -> Vec<Token> {
     }
     let text = buf.trim().to_string();
     if !text.is_empty() {
-        tokens.push(Token { text, is_exclude });
+        tokens.push(Token {
+            text,
+            is_exclude,
+            phonetic: String::new(),
+        });
+    }
+    if options.pinyin {
+        for t in &mut tokens {
+            t.phonetic = romanize_query(&t.text);
+        }
     }
 
     tokens
 }
 
-/// 字段匹配：等于（忽略大小写）/ 前缀 / 子串
-fn field_match_rank(field: &str, token: &str, options: SearchOptions) -> Option<u8> {
-    if field.is_empty() || token.is_empty() {
+/// 字段匹配：等于（忽略大小写）/ 前缀 / 子串；开启 pinyin 时拼音/罗马音命中按子串档计
+///
+/// 字段的拼音/罗马音检索键取自目录缓存，查询键由 `parse_tokens` 预先算好。
+fn field_match_rank(
+    field: &str,
+    token: &Token,
+    options: SearchOptions,
+    catalog: &SongCatalog,
+) -> Option<u8> {
+    if field.is_empty() || token.text.is_empty() {
         return None;
     }
 
+    let rank = field_match_rank_literal(field, &token.text, options);
+    if rank.is_some() || !options.pinyin || token.phonetic.is_empty() {
+        return rank;
+    }
+    catalog
+        .search_cache_field_phonetic
+        .get(field)
+        .filter(|key| SongCatalog::phonetic_hit(&token.phonetic, key).is_some())
+        .map(|_| 1)
+}
+
+fn field_match_rank_literal(field: &str, token: &str, options: SearchOptions) -> Option<u8> {
     if options.case_insensitive {
         let f = field.to_lowercase();
         let t = token.to_lowercase();
@@ -846,8 +1001,8 @@ fn field_match_rank(field: &str, token: &str, options: SearchOptions) -> Option<
     None
 }
 
-fn field_match(field: &str, token: &str, options: SearchOptions) -> bool {
-    field_match_rank(field, token, options).is_some()
+fn field_match(field: &str, token: &Token, options: SearchOptions, catalog: &SongCatalog) -> bool {
+    field_match_rank(field, token, options, catalog).is_some()
 }
 
 /// 简易打分：精确等于 > 前缀 > 子串；官方名 > 别名 > 作曲 > ID；多 token 累加
@@ -862,33 +1017,32 @@ fn score_song(
         if t.is_exclude {
             continue;
         }
-        let tok = t.text.as_str();
-
         // 官方名
-        score += score_field(&song.name, tok, options, 100, 80, 60);
+        score += score_field(&song.name, t, options, catalog, 100, 80, 60);
         // 别名（任一匹配即可按权重计分）
         let mut nick_scored = false;
         for (nick, songs) in &catalog.by_nickname {
             if !songs.iter().any(|candidate| candidate.id == song.id) {
                 continue;
             }
-            if !nick_scored && field_match(nick, tok, options) {
+            if !nick_scored && field_match(nick, t, options, catalog) {
                 score += 70; // 近似于官方名下一档
                 nick_scored = true;
             }
         }
         // 作曲者
-        score += score_field(&song.composer, tok, options, 50, 35, 20);
+        score += score_field(&song.composer, t, options, catalog, 50, 35, 20);
         // ID
-        score += score_field(&song.id, tok, options, 40, 25, 10);
+        score += score_field(&song.id, t, options, catalog, 40, 25, 10);
     }
     score
 }
 
 fn score_field(
     field: &str,
-    token: &str,
+    token: &Token,
     options: SearchOptions,
+    catalog: &SongCatalog,
     eq_w: i32,
     pre_w: i32,
     sub_w: i32,
@@ -896,7 +1050,7 @@ fn score_field(
     if field.is_empty() {
         return 0;
     }
-    match field_match_rank(field, token, options) {
+    match field_match_rank(field, token, options, catalog) {
         Some(3) => eq_w,
         Some(2) => pre_w,
         Some(1) => sub_w,
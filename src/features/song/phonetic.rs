@@ -0,0 +1,275 @@
+//! 拼音 / 罗马音检索键
+//!
+//! - 汉字：逐字转为无声调拼音（基于 deunicode 的音译表），支持全拼与首字母；
+//! - 假名：平假名/片假名统一按平文式（Hepburn）转罗马音，处理拗音、促音与小写元音；
+//! - 其余字母数字按单词保留（小写），符号与空白丢弃。
+//!
+//! 日文汉字同样按拼音处理（无读音词典），属于已知限制。
+
+/// 检索键：全拼与首字母（均为小写 ASCII）。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PhoneticKey {
+    pub full: String,
+    pub initials: String,
+}
+
+impl PhoneticKey {
+    /// 文本中包含汉字或假名时生成检索键；纯拉丁文本返回 None（交给常规匹配即可）。
+    pub(crate) fn from_text(text: &str) -> Option<Self> {
+        let (key, has_phonetic) = romanize(text);
+        (has_phonetic && !key.full.is_empty()).then_some(key)
+    }
+}
+
+/// 将查询串转为与 `PhoneticKey::full` 可比较的形式（假名/汉字同样转写）。
+pub(crate) fn romanize_query(query: &str) -> String {
+    romanize(query).0.full
+}
+
+fn is_han(ch: char) -> bool {
+    matches!(ch, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+/// 片假名转平假名（长音符等非对应字符原样返回）。
+fn to_hiragana(ch: char) -> char {
+    if ('\u{30A1}'..='\u{30F6}').contains(&ch) {
+        char::from_u32(ch as u32 - 0x60).unwrap_or(ch)
+    } else {
+        ch
+    }
+}
+
+fn is_kana(ch: char) -> bool {
+    matches!(to_hiragana(ch), '\u{3041}'..='\u{3096}') || ch == 'ー' || ch == 'ヷ' || ch == 'ヺ'
+}
+
+fn kana_base(ch: char) -> Option<&'static str> {
+    let s = match ch {
+        'あ' => "a",
+        'い' | 'ゐ' => "i",
+        'う' => "u",
+        'え' | 'ゑ' => "e",
+        'お' | 'を' | 'ヺ' => "o",
+        'か' | 'ゕ' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' | 'ゖ' => "ke",
+        'こ' => "ko",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' => "ya",
+        'ゆ' => "yu",
+        'よ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' | 'ヷ' => "wa",
+        'ん' => "n",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'ざ' => "za",
+        'じ' | 'ぢ' => "ji",
+        'ず' | 'づ' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ゔ' => "vu",
+        _ => return None,
+    };
+    Some(s)
+}
+
+/// 小写元音 / 小写 ya-yu-yo：与前一音节合并（拗音、外来音）。
+fn small_kana(ch: char) -> Option<(&'static str, bool)> {
+    // (元音, 是否为 ya/yu/yo 系)
+    let v = match ch {
+        'ぁ' => ("a", false),
+        'ぃ' => ("i", false),
+        'ぅ' => ("u", false),
+        'ぇ' => ("e", false),
+        'ぉ' => ("o", false),
+        'ゃ' => ("a", true),
+        'ゅ' => ("u", true),
+        'ょ' => ("o", true),
+        _ => return None,
+    };
+    Some(v)
+}
+
+fn strip_vowel(syl: &str) -> &str {
+    syl.strip_suffix(['a', 'i', 'u', 'e', 'o']).unwrap_or(syl)
+}
+
+/// 把一段假名转为音节列表（每个音节为一个拍，拗音合并为一拍）。
+fn kana_syllables(run: &[char]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut sokuon = false;
+    for &raw in run {
+        let ch = to_hiragana(raw);
+        if ch == 'っ' {
+            sokuon = true;
+            continue;
+        }
+        if ch == 'ー' {
+            // 长音：用户输入通常省略，直接丢弃
+            continue;
+        }
+        if let Some((vowel, is_y)) = small_kana(ch) {
+            if let Some(prev) = out.last_mut() {
+                let stem = strip_vowel(prev).to_string();
+                let merged = if is_y
+                    && !(stem.ends_with("sh") || stem.ends_with("ch") || stem.ends_with('j'))
+                {
+                    format!("{stem}y{vowel}")
+                } else if stem.is_empty() && prev == "u" {
+                    format!("w{vowel}")
+                } else {
+                    format!("{stem}{vowel}")
+                };
+                *prev = merged;
+            } else {
+                out.push(if is_y {
+                    format!("y{vowel}")
+                } else {
+                    vowel.to_string()
+                });
+            }
+            continue;
+        }
+        let Some(base) = kana_base(ch) else {
+            continue;
+        };
+        let mut syl = base.to_string();
+        if std::mem::take(&mut sokuon) {
+            if syl.starts_with("ch") {
+                syl.insert(0, 't');
+            } else if let Some(first) = syl.chars().next().filter(|c| !"aiueon".contains(*c)) {
+                syl.insert(0, first);
+            }
+        }
+        out.push(syl);
+    }
+    out
+}
+
+/// 逐字符转写；返回检索键与“是否包含汉字/假名”。
+fn romanize(text: &str) -> (PhoneticKey, bool) {
+    let mut syllables: Vec<String> = Vec::new();
+    let mut has_phonetic = false;
+    let mut word = String::new();
+    let mut kana_run: Vec<char> = Vec::new();
+
+    let flush_word = |word: &mut String, syllables: &mut Vec<String>| {
+        if !word.is_empty() {
+            syllables.push(std::mem::take(word));
+        }
+    };
+
+    for ch in text.chars() {
+        if is_kana(ch) {
+            flush_word(&mut word, &mut syllables);
+            kana_run.push(ch);
+            has_phonetic = true;
+            continue;
+        }
+        if !kana_run.is_empty() {
+            syllables.extend(kana_syllables(&kana_run));
+            kana_run.clear();
+        }
+        if is_han(ch) {
+            flush_word(&mut word, &mut syllables);
+            if let Some(py) = deunicode::deunicode_char(ch) {
+                let py: String = py
+                    .chars()
+                    .filter(char::is_ascii_alphanumeric)
+                    .map(|c| c.to_ascii_lowercase())
+                    .collect();
+                if !py.is_empty() {
+                    syllables.push(py);
+                    has_phonetic = true;
+                }
+            }
+        } else if ch.is_alphanumeric() {
+            word.extend(ch.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut syllables);
+        }
+    }
+    if !kana_run.is_empty() {
+        syllables.extend(kana_syllables(&kana_run));
+    }
+    flush_word(&mut word, &mut syllables);
+
+    let full = syllables.concat();
+    let initials = syllables.iter().filter_map(|s| s.chars().next()).collect();
+    (PhoneticKey { full, initials }, has_phonetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn han_yields_full_pinyin_and_initials() {
+        let key = PhoneticKey::from_text("遗忘之境").expect("han text");
+        assert_eq!(key.full, "yiwangzhijing");
+        assert_eq!(key.initials, "ywzj");
+    }
+
+    #[test]
+    fn kana_uses_hepburn_with_youon_and_sokuon() {
+        assert_eq!(romanize_query("ツ"), "tsu");
+        assert_eq!(romanize_query("しゃ"), "sha");
+        assert_eq!(romanize_query("キャット"), "kyatto");
+        assert_eq!(romanize_query("マッチ"), "matchi");
+        assert_eq!(romanize_query("ティーパーティー"), "tipati");
+        assert_eq!(romanize_query("ネオン"), romanize_query("ねおん"));
+    }
+
+    #[test]
+    fn latin_only_text_has_no_phonetic_key() {
+        assert!(PhoneticKey::from_text("Rrhar'il").is_none());
+        let mixed = PhoneticKey::from_text("雪降り Snow").expect("mixed text");
+        assert!(mixed.full.ends_with("snow"));
+    }
+}
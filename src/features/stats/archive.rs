@@ -25,6 +25,8 @@ const STARTUP_BACKFILL_MAX_DAYS: usize = 7;
 const DAILY_BACKFILL_MAX_DAYS: usize = 30;
 // 历史清理按批次删除，避免长事务锁住写入。
 const CLEANUP_DELETE_BATCH_SIZE: i64 = 5000;
+// 每日排名快照保留天数（覆盖一年的名次轨迹）。
+const RANK_SNAPSHOT_RETENTION_DAYS: i64 = 400;
 
 #[derive(Default, Debug, Clone, Copy)]
 struct ReconcileStats {
@@ -41,6 +43,10 @@ struct CleanupStats {
 }
 
 pub async fn run_daily_archiver(storage: Arc<StatsStorage>, cfg: StatsConfig) {
+    // 启动时仅在当日快照缺失时补写，避免重启覆盖当日已有的名次。
+    if let Err(e) = snapshot_ranks_once(&storage, false).await {
+        tracing::warn!("排名快照（启动补偿）失败: {}", e);
+    }
     // 启动后先执行一次轻量维护（限额补档 + 清理），避免长期缺口一直积累。
     if let Err(e) = run_maintenance_once(&storage, &cfg, Some(STARTUP_BACKFILL_MAX_DAYS)).await {
         tracing::warn!("统计维护（启动补偿）失败: {}", e);
@@ -55,12 +61,36 @@ pub async fn run_daily_archiver(storage: Arc<StatsStorage>, cfg: StatsConfig) {
         tracing::info!("统计维护：将在 {} 触发", next);
         tokio::time::sleep(sleep_dur).await;
 
+        if let Err(e) = snapshot_ranks_once(&storage, true).await {
+            tracing::warn!("排名快照失败: {}", e);
+        }
         if let Err(e) = run_maintenance_once(&storage, &cfg, Some(DAILY_BACKFILL_MAX_DAYS)).await {
             tracing::warn!("统计维护失败: {}", e);
         }
     }
 }
 
+/// 写入当日（UTC）排名快照并清理过期快照；`overwrite=false` 时已有当日快照则跳过。
+async fn snapshot_ranks_once(storage: &StatsStorage, overwrite: bool) -> Result<(), AppError> {
+    let today = Utc::now().date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+    if !overwrite && storage.has_rank_snapshot(&date).await? {
+        return Ok(());
+    }
+    let users = storage.snapshot_leaderboard_ranks(&date).await?;
+    let cutoff = (today - chrono::Duration::days(RANK_SNAPSHOT_RETENTION_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    let pruned = storage.prune_rank_snapshots(&cutoff).await?;
+    tracing::info!(
+        "排名快照完成: date={}, users={}, pruned_rows={}",
+        date,
+        users,
+        pruned
+    );
+    Ok(())
+}
+
 async fn run_maintenance_once(
     storage: &StatsStorage,
     cfg: &StatsConfig,
@@ -49,8 +49,10 @@ async fn build_test_state(sqlite_path: &str) -> AppState {
     let song_image_cache: Cache<String, Bytes> = Cache::builder().max_capacity(10).build();
 
     AppState {
-        chart_constants: Arc::new(chart_constants),
-        song_catalog: Arc::new(song_catalog),
+        game_data: crate::game_data::GameDataStore::new(
+            Arc::new(chart_constants),
+            Arc::new(song_catalog),
+        ),
         taptap_client,
         qrcode_service,
         stats: None,
@@ -32,14 +32,7 @@ pub async fn stats_middleware(
         .map(|m| m.as_str().to_string());
 
     // 去敏 IP 哈希（优先 X-Forwarded-For / X-Real-IP）
-    let ip_raw = client_ip_from_headers(req.headers());
-    let client_ip_hash = ip_raw.and_then(|ip| {
-        AppConfig::global()
-            .stats
-            .user_hash_salt
-            .as_deref()
-            .map(|salt| hmac_hex16(salt, ip))
-    });
+    let client_ip_hash = client_ip_hash(req.headers());
     // 透传
     let res = next.run(req).await;
     let status = res.status().as_u16();
@@ -69,6 +62,17 @@ pub struct StateWithStats {
     pub stats: StatsHandle,
 }
 
+/// 请求来源 IP 的去敏哈希（HMAC，盐为 `stats.user_hash_salt`）；未配置盐或无 IP 头时为 None。
+#[must_use]
+pub fn client_ip_hash(headers: &axum::http::HeaderMap) -> Option<String> {
+    let ip = client_ip_from_headers(headers)?;
+    AppConfig::global()
+        .stats
+        .user_hash_salt
+        .as_deref()
+        .map(|salt| hmac_hex16(salt, ip))
+}
+
 fn hmac_hex16(salt: &str, value: &str) -> String {
     let mut mac = Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC key");
     mac.update(value.as_bytes());
@@ -307,8 +307,8 @@ pub async fn init_stats(config: &AppConfig) -> Result<(StatsHandle, Arc<StatsSto
         });
     }
 
-    // 每日归档任务
-    if config.stats.archive.parquet {
+    // 每日维护任务（排名快照 + 归档）；归档部分仍受 archive.parquet 控制
+    {
         let archiver_storage = storage.clone();
         let cfg = config.stats.clone();
         tokio::spawn(async move {
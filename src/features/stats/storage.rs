@@ -1,17 +1,25 @@
 use sqlx::SqlitePool;
+use tokio::sync::broadcast;
 
+mod chart_history;
 mod connection;
 mod daily;
 mod events;
+mod group;
 mod http;
 mod latency;
 mod leaderboard;
 mod moderation;
 mod profile;
 mod public_leaderboard;
+mod qrcode_login;
+mod rank_history;
+mod save_snapshot;
 mod session;
+mod session_refresh;
 mod submission;
 mod summary;
+mod taptap_profile;
 
 /// 保存提交入库参数，减少函数参数数量
 pub struct SubmissionRecord<'a> {
@@ -22,9 +30,128 @@ pub struct SubmissionRecord<'a> {
     pub client_ip_hash: Option<&'a str>,
     pub details_json: Option<&'a str>,
     pub suspicion_score: f64,
+    pub region: ServerRegion,
     pub now_rfc3339: &'a str,
 }
 
+/// 存档所属服务器区域（按提交时使用的 TapTap 版本区分）
+///
+/// 历史数据与无法判定来源的提交（如外部平台凭证）记为 `Unspecified`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerRegion {
+    Cn,
+    Global,
+    Unspecified,
+}
+
+impl ServerRegion {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Cn => "cn",
+            Self::Global => "global",
+            Self::Unspecified => "unspecified",
+        }
+    }
+
+    /// 解析区域代码（大小写不敏感）；未知代码返回 None。
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim();
+        [Self::Cn, Self::Global, Self::Unspecified]
+            .into_iter()
+            .find(|r| code.eq_ignore_ascii_case(r.as_str()))
+    }
+}
+
+/// 排行榜成绩提升通知（由 `upsert_leaderboard_rks` 在分数刷新时发出）
+///
+/// 仅包含未隐藏的用户；名次、别名等展示信息由订阅方按需补齐。
+#[derive(Debug, Clone)]
+pub struct LeaderboardRksChange {
+    pub user_hash: String,
+    pub total_rks: f64,
+    pub updated_at: String,
+    pub region: ServerRegion,
+}
+
+/// 二维码登录状态（`qrcode_login` 一行，供多实例共享）
+///
+/// 时间均为 Unix 毫秒；`status` 取值 pending / scanned / confirmed。
+#[derive(Debug, Clone)]
+pub struct QrCodeLoginRow {
+    pub status: String,
+    pub device_code: String,
+    pub device_id: String,
+    pub interval_secs: i64,
+    pub next_poll_at_ms: i64,
+    pub expires_at_ms: i64,
+    pub version: Option<String>,
+    pub session_token: Option<String>,
+}
+
+/// 刷新令牌会话族（`session_family` 一行）
+///
+/// 一次 exchange 建立一个族（对应一台设备/一次登录），刷新令牌轮换时族 ID 不变；
+/// 时间均为 RFC3339，与 `session_token_blacklist` / `session_logout_gate` 一致。
+#[derive(Debug, Clone)]
+pub struct SessionFamilyRow {
+    pub family_id: String,
+    pub user_hash: String,
+    pub device_label: Option<String>,
+    /// 加密后的登录凭证，刷新时据此签发新的 access token
+    pub auth_sealed: String,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub revoke_reason: Option<String>,
+}
+
+/// 按哈希查到的刷新令牌及其所属会话族
+#[derive(Debug, Clone)]
+pub struct RefreshTokenLookup {
+    pub family: SessionFamilyRow,
+    pub expires_at: String,
+    /// 已被轮换使用的时间；再次出现即视为重放
+    pub used_at: Option<String>,
+}
+
+/// 新建会话族参数（同时写入首个刷新令牌）
+pub struct NewSessionFamily<'a> {
+    pub family_id: &'a str,
+    pub user_hash: &'a str,
+    pub device_label: Option<&'a str>,
+    pub auth_sealed: &'a str,
+    pub token_hash: &'a str,
+    pub access_jti: &'a str,
+    pub access_expires_at: &'a str,
+    pub expires_at: &'a str,
+    pub now: &'a str,
+}
+
+/// 刷新令牌轮换参数
+pub struct RefreshRotation<'a> {
+    pub family_id: &'a str,
+    pub old_hash: &'a str,
+    pub new_hash: &'a str,
+    pub access_jti: &'a str,
+    pub access_expires_at: &'a str,
+    pub expires_at: &'a str,
+    pub now: &'a str,
+}
+
+/// 缓存的 TapTap 基础资料（`taptap_profile` 一行）
+///
+/// `fetched_at_ms` 为最近一次从上游成功获取的 Unix 毫秒，用于判断是否需要刷新。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapTapProfileRow {
+    pub nickname: Option<String>,
+    pub avatar_url: Option<String>,
+    pub taptap_version: Option<String>,
+    pub fetched_at_ms: i64,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct UserAliasDefaults<'a> {
     pub is_public: bool,
@@ -255,7 +382,171 @@ pub struct RksHistoryPage {
     pub has_more: bool,
 }
 
+/// 排行榜用户及其最近一次提交的谱面快照
+#[derive(Debug, Clone)]
+pub struct LeaderboardChartDetails {
+    pub user_hash: String,
+    pub total_rks: f64,
+    pub is_hidden: bool,
+    /// 最近一次带快照提交的 `details_json`；旧提交没有快照时为 None
+    pub details_json: Option<String>,
+    /// 读取时该用户最新一次提交的 ID；重算写回时据此判断期间是否有新提交
+    pub latest_submission_id: Option<i64>,
+}
+
+/// 排行榜重算结果；写入时整体覆盖，不走提交时“只升不降”的语义
+#[derive(Debug, Clone)]
+pub struct RecomputedLeaderboardRow {
+    pub user_hash: String,
+    /// 重算所依据的最新提交 ID；写回时该用户已有更新的提交则跳过
+    pub based_on_submission_id: Option<i64>,
+    pub total_rks: f64,
+    /// 非当前规则下的总 RKS：`(规则标识, 总 RKS)`
+    pub rule_totals: Vec<(&'static str, f64)>,
+    pub rks_comp_json: Option<String>,
+    pub best_top3_json: Option<String>,
+    pub ap_top3_json: Option<String>,
+}
+
+/// 存档中单个谱面的当前成绩（用于逐谱面成绩历史的去重写入）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartScoreSnapshot {
+    pub song_id: String,
+    /// 难度（EZ/HD/IN/AT）
+    pub difficulty: String,
+    pub score: i64,
+    pub acc: f64,
+    pub is_fc: bool,
+}
+
+/// 单条谱面成绩变化记录
+#[derive(Debug, Clone)]
+pub struct ChartScoreHistoryEntry {
+    pub id: i64,
+    pub song_id: String,
+    pub difficulty: String,
+    pub score: i64,
+    pub acc: f64,
+    pub is_fc: bool,
+    /// 变化前的成绩；首次记录时为 None
+    pub prev_score: Option<i64>,
+    pub prev_acc: Option<f64>,
+    pub prev_fc: Option<bool>,
+    /// 是否刷新个人最佳（分数/ACC 提升或新达成 FC）
+    pub is_pb: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChartScoreHistoryPage {
+    pub entries: Vec<ChartScoreHistoryEntry>,
+    pub has_more: bool,
+}
+
+/// 单谱面公开排行榜的一行
+#[derive(Debug, Clone)]
+pub struct ChartLeaderboardRow {
+    pub user_hash: String,
+    pub alias: Option<String>,
+    pub score: i64,
+    pub acc: f64,
+    pub is_fc: bool,
+    pub updated_at: String,
+}
+
+/// 每日排名快照中的一点
+#[derive(Debug, Clone)]
+pub struct RankSnapshotPoint {
+    /// UTC 日期（YYYY-MM-DD）
+    pub date: String,
+    pub rank: i64,
+    pub total_rks: f64,
+}
+
+/// RKS 涨幅榜的一行
+#[derive(Debug, Clone)]
+pub struct RksGainerRow {
+    pub user_hash: String,
+    pub alias: Option<String>,
+    /// 当前排行榜 RKS
+    pub total_rks: f64,
+    /// 窗口开始前的最高 RKS
+    pub from_rks: f64,
+    /// 窗口内的最高 RKS
+    pub to_rks: f64,
+}
+
+/// 好友群组
+#[derive(Debug, Clone)]
+pub struct LeaderboardGroup {
+    pub id: String,
+    pub name: String,
+    pub owner_hash: String,
+    pub invite_code: String,
+    pub member_count: i64,
+    pub created_at: String,
+}
+
+/// 好友群组成员
+#[derive(Debug, Clone)]
+pub struct LeaderboardGroupMember {
+    /// 成员记录 ID（移除成员时使用）
+    pub id: i64,
+    pub user_hash: String,
+    pub alias: Option<String>,
+    pub joined_at: String,
+}
+
+/// 待写入的审核标记
+#[derive(Debug, Clone)]
+pub struct NewModerationFlag<'a> {
+    pub status: &'a str,
+    pub reason: &'a str,
+    pub severity: i64,
+}
+
+/// 审核标记（`moderation_flags` 一行）
+#[derive(Debug, Clone)]
+pub struct ModerationFlag {
+    pub id: i64,
+    pub status: String,
+    pub reason: Option<String>,
+    pub severity: i64,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// 存档差异基准快照（/save 同步与 /save/diff 写入）
+#[derive(Debug, Clone)]
+pub struct StoredSaveSnapshot {
+    /// 差异基准的紧凑 JSON（由存档功能编码，本层不解析）
+    pub snapshot_json: String,
+    /// 该存档的 updatedAt
+    pub save_updated_at: Option<String>,
+    /// 写入时间（RFC3339）
+    pub stored_at: String,
+}
+
+/// 排行榜变更广播的缓冲条数；订阅方消费过慢时最旧的通知会被丢弃
+const LEADERBOARD_CHANGE_CAPACITY: usize = 1024;
+
 #[derive(Clone)]
 pub struct StatsStorage {
     pub pool: SqlitePool,
+    lb_changes: broadcast::Sender<LeaderboardRksChange>,
+}
+
+impl StatsStorage {
+    /// 基于已建立的连接池构造（如只读连接）；常规入口为 `connect_sqlite`
+    #[must_use]
+    pub fn from_pool(pool: SqlitePool) -> Self {
+        let (lb_changes, _) = broadcast::channel(LEADERBOARD_CHANGE_CAPACITY);
+        Self { pool, lb_changes }
+    }
+
+    /// 订阅排行榜成绩提升通知
+    #[must_use]
+    pub fn subscribe_leaderboard_changes(&self) -> broadcast::Receiver<LeaderboardRksChange> {
+        self.lb_changes.subscribe()
+    }
 }
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use sqlx::Row;
+
+use crate::error::AppError;
+
+use super::{
+    ChartScoreHistoryEntry, ChartScoreHistoryPage, ChartScoreSnapshot, RksHistoryCursor,
+    StatsStorage,
+};
+
+// f32 ACC 转存为 REAL 后的比较容差
+const ACC_EPS: f64 = 1e-6;
+
+/// 与上次写入的成绩比较：返回 None 表示无变化；否则返回是否为个人最佳。
+///
+/// `baseline` 为用户第一次写入（此前没有任何谱面记录），此时所有谱面只作为历史起点，不计入 PB。
+fn classify_change(
+    prev: Option<&ChartScoreSnapshot>,
+    cur: &ChartScoreSnapshot,
+    baseline: bool,
+) -> Option<bool> {
+    let Some(prev) = prev else {
+        return Some(!baseline);
+    };
+    let changed =
+        cur.score != prev.score || (cur.acc - prev.acc).abs() > ACC_EPS || cur.is_fc != prev.is_fc;
+    if !changed {
+        return None;
+    }
+    Some(cur.score > prev.score || cur.acc > prev.acc + ACC_EPS || (cur.is_fc && !prev.is_fc))
+}
+
+const CHART_STATE_SQL: &str =
+    "SELECT song_id, difficulty, score, acc, is_fc FROM chart_score_state WHERE user_hash = ?";
+
+fn row_to_snapshot(row: &sqlx::sqlite::SqliteRow) -> ChartScoreSnapshot {
+    ChartScoreSnapshot {
+        song_id: row.try_get("song_id").unwrap_or_default(),
+        difficulty: row.try_get("difficulty").unwrap_or_default(),
+        score: row.try_get("score").unwrap_or(0),
+        acc: row.try_get("acc").unwrap_or(0.0),
+        is_fc: row.try_get::<i64, _>("is_fc").unwrap_or(0) != 0,
+    }
+}
+
+fn row_to_history_entry(row: &sqlx::sqlite::SqliteRow) -> ChartScoreHistoryEntry {
+    ChartScoreHistoryEntry {
+        id: row.try_get("id").unwrap_or(0),
+        song_id: row.try_get("song_id").unwrap_or_default(),
+        difficulty: row.try_get("difficulty").unwrap_or_default(),
+        score: row.try_get("score").unwrap_or(0),
+        acc: row.try_get("acc").unwrap_or(0.0),
+        is_fc: row.try_get::<i64, _>("is_fc").unwrap_or(0) != 0,
+        prev_score: row.try_get("prev_score").ok().flatten(),
+        prev_acc: row.try_get("prev_acc").ok().flatten(),
+        prev_fc: row
+            .try_get::<Option<i64>, _>("prev_fc")
+            .ok()
+            .flatten()
+            .map(|v| v != 0),
+        is_pb: row.try_get::<i64, _>("is_pb").unwrap_or(0) != 0,
+        created_at: row.try_get("created_at").unwrap_or_default(),
+    }
+}
+
+fn into_page(rows: &[sqlx::sqlite::SqliteRow], limit: i64) -> ChartScoreHistoryPage {
+    let limit = usize::try_from(limit).unwrap_or(0);
+    let mut entries: Vec<ChartScoreHistoryEntry> = rows.iter().map(row_to_history_entry).collect();
+    let has_more = entries.len() > limit;
+    if has_more {
+        entries.truncate(limit);
+    }
+    ChartScoreHistoryPage { entries, has_more }
+}
+
+impl StatsStorage {
+    /// 读取用户各谱面最近一次写入的成绩（即上一次提交后的状态）。
+    pub async fn get_chart_scores(
+        &self,
+        user_hash: &str,
+    ) -> Result<Vec<ChartScoreSnapshot>, AppError> {
+        let rows = sqlx::query(CHART_STATE_SQL)
+            .bind(user_hash)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("get chart scores: {e}")))?;
+        Ok(rows.iter().map(row_to_snapshot).collect())
+    }
+
+    /// 写入一次提交的逐谱面成绩，只为成绩（分数/ACC/FC）有变化的谱面追加历史。
+    ///
+    /// 返回追加的历史条数。
+    pub async fn record_chart_scores(
+        &self,
+        user_hash: &str,
+        charts: &[ChartScoreSnapshot],
+        now_rfc3339: &str,
+    ) -> Result<usize, AppError> {
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("record chart scores: {e}"));
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        let rows = sqlx::query(CHART_STATE_SQL)
+            .bind(user_hash)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(map_err)?;
+        let prev: HashMap<(String, String), ChartScoreSnapshot> = rows
+            .iter()
+            .map(|row| {
+                let snap = row_to_snapshot(row);
+                ((snap.song_id.clone(), snap.difficulty.clone()), snap)
+            })
+            .collect();
+        let baseline = prev.is_empty();
+
+        let mut written = 0usize;
+        for cur in charts {
+            let old = prev.get(&(cur.song_id.clone(), cur.difficulty.clone()));
+            let Some(is_pb) = classify_change(old, cur, baseline) else {
+                continue;
+            };
+            sqlx::query(
+                "INSERT INTO chart_score_history(user_hash,song_id,difficulty,score,acc,is_fc,prev_score,prev_acc,prev_fc,is_pb,created_at)
+                 VALUES(?,?,?,?,?,?,?,?,?,?,?)",
+            )
+            .bind(user_hash)
+            .bind(&cur.song_id)
+            .bind(&cur.difficulty)
+            .bind(cur.score)
+            .bind(cur.acc)
+            .bind(i64::from(cur.is_fc))
+            .bind(old.map(|o| o.score))
+            .bind(old.map(|o| o.acc))
+            .bind(old.map(|o| i64::from(o.is_fc)))
+            .bind(i64::from(is_pb))
+            .bind(now_rfc3339)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+            sqlx::query(
+                "INSERT INTO chart_score_state(user_hash,song_id,difficulty,score,acc,is_fc,updated_at) VALUES(?,?,?,?,?,?,?)
+                 ON CONFLICT(user_hash, song_id, difficulty) DO UPDATE SET
+                   score = excluded.score,
+                   acc = excluded.acc,
+                   is_fc = excluded.is_fc,
+                   updated_at = excluded.updated_at",
+            )
+            .bind(user_hash)
+            .bind(&cur.song_id)
+            .bind(&cur.difficulty)
+            .bind(cur.score)
+            .bind(cur.acc)
+            .bind(i64::from(cur.is_fc))
+            .bind(now_rfc3339)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+            written += 1;
+        }
+        tx.commit().await.map_err(map_err)?;
+        Ok(written)
+    }
+
+    /// 查询单个谱面的成绩变化历史（按时间倒序，`(created_at, id)` seek 分页）。
+    pub async fn query_chart_score_history(
+        &self,
+        user_hash: &str,
+        song_id: &str,
+        difficulty: &str,
+        limit: i64,
+        cursor: Option<&RksHistoryCursor>,
+    ) -> Result<ChartScoreHistoryPage, AppError> {
+        let limit = limit.clamp(1, 500);
+        let (cursor_ts, cursor_id) =
+            cursor.map_or(("\u{10FFFF}", i64::MAX), |c| (c.created_at.as_str(), c.id));
+        let rows = sqlx::query(
+            "SELECT id, song_id, difficulty, score, acc, is_fc, prev_score, prev_acc, prev_fc, is_pb, created_at
+             FROM chart_score_history
+             WHERE user_hash = ? AND song_id = ? AND difficulty = ?
+               AND (created_at < ? OR (created_at = ? AND id < ?))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?",
+        )
+        .bind(user_hash)
+        .bind(song_id)
+        .bind(difficulty)
+        .bind(cursor_ts)
+        .bind(cursor_ts)
+        .bind(cursor_id)
+        .bind(limit.saturating_add(1))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("query chart score history: {e}")))?;
+        Ok(into_page(&rows, limit))
+    }
+
+    /// 查询最近刷新的个人最佳（按时间倒序，`(created_at, id)` seek 分页）。
+    pub async fn query_recent_pbs(
+        &self,
+        user_hash: &str,
+        limit: i64,
+        cursor: Option<&RksHistoryCursor>,
+    ) -> Result<ChartScoreHistoryPage, AppError> {
+        let limit = limit.clamp(1, 500);
+        let (cursor_ts, cursor_id) =
+            cursor.map_or(("\u{10FFFF}", i64::MAX), |c| (c.created_at.as_str(), c.id));
+        let rows = sqlx::query(
+            "SELECT id, song_id, difficulty, score, acc, is_fc, prev_score, prev_acc, prev_fc, is_pb, created_at
+             FROM chart_score_history
+             WHERE user_hash = ? AND is_pb = 1
+               AND (created_at < ? OR (created_at = ? AND id < ?))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?",
+        )
+        .bind(user_hash)
+        .bind(cursor_ts)
+        .bind(cursor_ts)
+        .bind(cursor_id)
+        .bind(limit.saturating_add(1))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("query recent pbs: {e}")))?;
+        Ok(into_page(&rows, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(score: i64, acc: f64, is_fc: bool) -> ChartScoreSnapshot {
+        ChartScoreSnapshot {
+            song_id: "s".into(),
+            difficulty: "IN".into(),
+            score,
+            acc,
+            is_fc,
+        }
+    }
+
+    #[test]
+    fn classify_change_dedups_and_flags_personal_bests() {
+        let prev = snap(990_000, 99.0, false);
+        assert_eq!(classify_change(Some(&prev), &prev.clone(), false), None);
+        assert_eq!(
+            classify_change(Some(&prev), &snap(995_000, 99.2, false), false),
+            Some(true)
+        );
+        assert_eq!(
+            classify_change(Some(&prev), &snap(990_000, 99.0, true), false),
+            Some(true)
+        );
+        // 成绩回退（例如存档回滚）也记录，但不是 PB
+        assert_eq!(
+            classify_change(Some(&prev), &snap(980_000, 98.5, false), false),
+            Some(false)
+        );
+        assert_eq!(classify_change(None, &prev, true), Some(false));
+        assert_eq!(classify_change(None, &prev, false), Some(true));
+    }
+}
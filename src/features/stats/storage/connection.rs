@@ -24,7 +24,7 @@ impl StatsStorage {
         let pool = SqlitePool::connect_with(opt)
             .await
             .map_err(|e| AppError::Internal(format!("sqlite connect: {e}")))?;
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
     }
 
     pub async fn init_schema(&self) -> Result<(), AppError> {
@@ -137,6 +137,7 @@ impl StatsStorage {
             user_kind TEXT,
             suspicion_score REAL NOT NULL DEFAULT 0.0,
             is_hidden INTEGER NOT NULL DEFAULT 0,
+            region TEXT NOT NULL DEFAULT 'unspecified',
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL
         );
@@ -144,6 +145,16 @@ impl StatsStorage {
         CREATE INDEX IF NOT EXISTS idx_lb_visible_order ON leaderboard_rks(is_hidden, total_rks DESC, updated_at ASC, user_hash ASC);
         CREATE INDEX IF NOT EXISTS idx_lb_suspicion_order ON leaderboard_rks(suspicion_score DESC, total_rks DESC, user_hash ASC);
 
+        -- 非当前规则（旧版 B19+Phi1 等）下的总 RKS；可见性沿用 leaderboard_rks.is_hidden
+        CREATE TABLE IF NOT EXISTS leaderboard_rks_rule (
+            user_hash TEXT NOT NULL,
+            rule TEXT NOT NULL,
+            total_rks REAL NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY(user_hash, rule)
+        );
+        CREATE INDEX IF NOT EXISTS idx_lb_rule_order ON leaderboard_rks_rule(rule, total_rks DESC, updated_at ASC, user_hash ASC);
+
         CREATE TABLE IF NOT EXISTS user_profile (
             user_hash TEXT PRIMARY KEY,
             alias TEXT UNIQUE COLLATE NOCASE,
@@ -167,11 +178,24 @@ impl StatsStorage {
             client_ip_hash TEXT,
             details_json TEXT,
             suspicion_score REAL NOT NULL DEFAULT 0.0,
+            region TEXT NOT NULL DEFAULT 'unspecified',
             created_at TEXT NOT NULL
         );
         CREATE INDEX IF NOT EXISTS idx_submissions_user ON save_submissions(user_hash, created_at DESC);
         CREATE INDEX IF NOT EXISTS idx_submissions_user_created_id ON save_submissions(user_hash, created_at DESC, id DESC);
         CREATE INDEX IF NOT EXISTS idx_submissions_user_total_rks ON save_submissions(user_hash, total_rks DESC);
+        CREATE INDEX IF NOT EXISTS idx_submissions_ip_created ON save_submissions(client_ip_hash, created_at) WHERE client_ip_hash IS NOT NULL;
+        CREATE INDEX IF NOT EXISTS idx_submissions_created_user ON save_submissions(created_at, user_hash, total_rks);
+
+        -- 每日排名快照：由每日维护任务写入，日期为 UTC 自然日（YYYY-MM-DD）
+        CREATE TABLE IF NOT EXISTS leaderboard_rank_daily (
+            date TEXT NOT NULL,
+            user_hash TEXT NOT NULL,
+            rank INTEGER NOT NULL,
+            total_rks REAL NOT NULL,
+            PRIMARY KEY(date, user_hash)
+        );
+        CREATE INDEX IF NOT EXISTS idx_rank_daily_user ON leaderboard_rank_daily(user_hash, date);
 
         CREATE TABLE IF NOT EXISTS leaderboard_details (
             user_hash TEXT PRIMARY KEY,
@@ -181,6 +205,86 @@ impl StatsStorage {
             updated_at TEXT NOT NULL
         );
 
+        -- 逐谱面成绩：state 保存每个谱面最近一次写入的成绩（去重依据），history 只在成绩变化时追加
+        CREATE TABLE IF NOT EXISTS chart_score_state (
+            user_hash TEXT NOT NULL,
+            song_id TEXT NOT NULL,
+            difficulty TEXT NOT NULL,
+            score INTEGER NOT NULL,
+            acc REAL NOT NULL,
+            is_fc INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY(user_hash, song_id, difficulty)
+        );
+        CREATE TABLE IF NOT EXISTS chart_score_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_hash TEXT NOT NULL,
+            song_id TEXT NOT NULL,
+            difficulty TEXT NOT NULL,
+            score INTEGER NOT NULL,
+            acc REAL NOT NULL,
+            is_fc INTEGER NOT NULL DEFAULT 0,
+            prev_score INTEGER,
+            prev_acc REAL,
+            prev_fc INTEGER,
+            is_pb INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_chart_state_board ON chart_score_state(song_id, difficulty, score DESC, acc DESC, updated_at ASC, user_hash ASC);
+        CREATE INDEX IF NOT EXISTS idx_chart_history_chart ON chart_score_history(user_hash, song_id, difficulty, created_at DESC, id DESC);
+        CREATE INDEX IF NOT EXISTS idx_chart_history_pb ON chart_score_history(user_hash, created_at DESC, id DESC) WHERE is_pb = 1;
+
+        -- 好友群组（私有排行榜）：成员通过邀请码加入
+        CREATE TABLE IF NOT EXISTS leaderboard_group (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            owner_hash TEXT NOT NULL,
+            invite_code TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_group_owner ON leaderboard_group(owner_hash);
+        CREATE TABLE IF NOT EXISTS leaderboard_group_member (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            group_id TEXT NOT NULL,
+            user_hash TEXT NOT NULL,
+            joined_at TEXT NOT NULL,
+            UNIQUE(group_id, user_hash)
+        );
+        CREATE INDEX IF NOT EXISTS idx_group_member_user ON leaderboard_group_member(user_hash, joined_at);
+
+        -- 存档差异基准：每个用户只保留最近一次同步（/save 或 /save/diff）的紧凑存档要素
+        CREATE TABLE IF NOT EXISTS save_snapshot (
+            user_hash TEXT PRIMARY KEY,
+            snapshot_json TEXT NOT NULL,
+            save_updated_at TEXT,
+            stored_at TEXT NOT NULL
+        );
+
+        -- 二维码登录状态：多实例共享，时间为 Unix 毫秒
+        CREATE TABLE IF NOT EXISTS qrcode_login (
+            qr_id TEXT PRIMARY KEY,
+            status TEXT NOT NULL,
+            device_code TEXT NOT NULL,
+            device_id TEXT NOT NULL,
+            interval_secs INTEGER NOT NULL,
+            next_poll_at_ms INTEGER NOT NULL,
+            expires_at_ms INTEGER NOT NULL,
+            version TEXT,
+            session_token TEXT,
+            updated_at_ms INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_qrcode_login_expires_at ON qrcode_login(expires_at_ms);
+
+        -- TapTap 基础资料缓存：按 user_hash 存放昵称/头像，时间为 Unix 毫秒
+        CREATE TABLE IF NOT EXISTS taptap_profile (
+            user_hash TEXT PRIMARY KEY,
+            nickname TEXT,
+            avatar_url TEXT,
+            taptap_version TEXT,
+            fetched_at_ms INTEGER NOT NULL
+        );
+
         CREATE TABLE IF NOT EXISTS session_token_blacklist (
             jti TEXT PRIMARY KEY,
             expires_at TEXT NOT NULL,
@@ -196,6 +300,32 @@ impl StatsStorage {
         );
         CREATE INDEX IF NOT EXISTS idx_session_logout_gate_expires_at ON session_logout_gate(expires_at);
 
+        -- 刷新令牌：一次登录一个会话族，令牌只存 SHA-256 哈希，每次使用即轮换
+        CREATE TABLE IF NOT EXISTS session_family (
+            family_id TEXT PRIMARY KEY,
+            user_hash TEXT NOT NULL,
+            device_label TEXT,
+            auth_sealed TEXT NOT NULL,
+            current_jti TEXT,
+            current_jti_expires_at TEXT,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            revoked_at TEXT,
+            revoke_reason TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_family_user ON session_family(user_hash);
+        CREATE INDEX IF NOT EXISTS idx_session_family_expires_at ON session_family(expires_at);
+
+        CREATE TABLE IF NOT EXISTS session_refresh_token (
+            token_hash TEXT PRIMARY KEY,
+            family_id TEXT NOT NULL,
+            issued_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            used_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_refresh_token_family ON session_refresh_token(family_id);
+
         CREATE TABLE IF NOT EXISTS user_moderation_state (
             user_hash TEXT PRIMARY KEY,
             status TEXT NOT NULL DEFAULT 'active',
@@ -225,6 +355,7 @@ impl StatsStorage {
         // `daily_agg` 在 fast-path 中需要按 feature/route/max(ts_utc) 输出 last_ts，
         // 而初始建表不含该列，需幂等补一次。
         self.ensure_daily_agg_last_ts_column().await?;
+        self.ensure_region_columns().await?;
         Ok(())
     }
 
@@ -258,22 +389,20 @@ impl StatsStorage {
         Ok(())
     }
 
-    /// 为历史上未包含 `last_ts` 列的 `daily_agg` 表幂等补列；已存在时跳过。
-    async fn ensure_daily_agg_last_ts_column(&self) -> Result<(), AppError> {
-        let rows = sqlx::query("PRAGMA table_info(daily_agg)")
+    async fn has_column(&self, table: &str, column: &str) -> Result<bool, AppError> {
+        let rows = sqlx::query(&format!("PRAGMA table_info({table})"))
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| AppError::Internal(format!("table_info daily_agg: {e}")))?;
-        let mut has_last_ts = false;
-        for r in rows {
-            if let Ok(name) = r.try_get::<String, _>("name")
-                && name == "last_ts"
-            {
-                has_last_ts = true;
-                break;
-            }
-        }
-        if !has_last_ts {
+            .map_err(|e| AppError::Internal(format!("table_info {table}: {e}")))?;
+        Ok(rows.iter().any(|r| {
+            r.try_get::<String, _>("name")
+                .is_ok_and(|name| name == column)
+        }))
+    }
+
+    /// 为历史上未包含 `last_ts` 列的 `daily_agg` 表幂等补列；已存在时跳过。
+    async fn ensure_daily_agg_last_ts_column(&self) -> Result<(), AppError> {
+        if !self.has_column("daily_agg", "last_ts").await? {
             sqlx::query("ALTER TABLE daily_agg ADD COLUMN last_ts TEXT")
                 .execute(&self.pool)
                 .await
@@ -281,4 +410,28 @@ impl StatsStorage {
         }
         Ok(())
     }
+
+    /// 为旧库的 `leaderboard_rks` / `save_submissions` 幂等补 `region` 列。
+    ///
+    /// 补列前的数据无法判断来源区服，统一回填为 `unspecified`；
+    /// 分区索引依赖该列，因此放在补列之后创建。
+    async fn ensure_region_columns(&self) -> Result<(), AppError> {
+        for table in ["leaderboard_rks", "save_submissions"] {
+            if !self.has_column(table, "region").await? {
+                sqlx::query(&format!(
+                    "ALTER TABLE {table} ADD COLUMN region TEXT NOT NULL DEFAULT 'unspecified'"
+                ))
+                .execute(&self.pool)
+                .await
+                .map_err(|e| AppError::Internal(format!("alter {table} region: {e}")))?;
+            }
+        }
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_lb_region_order ON leaderboard_rks(region, is_hidden, total_rks DESC, updated_at ASC, user_hash ASC)",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("create idx_lb_region_order: {e}")))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,313 @@
+#![allow(clippy::items_after_test_module)]
+
+use sqlx::{Row, sqlite::SqliteRow};
+
+use crate::error::AppError;
+
+use super::{LeaderboardGroup, LeaderboardGroupMember, StatsStorage};
+
+const GROUP_COLUMNS_SQL: &str = "SELECT g.id, g.name, g.owner_hash, g.invite_code, g.created_at,
+        (SELECT COUNT(1) FROM leaderboard_group_member m WHERE m.group_id=g.id) AS member_count
+     FROM leaderboard_group g";
+
+// 群组榜：成员无需公开资料；被隐藏（审核/反作弊）的用户同样不上榜。
+// 别名与 BestTop3/APTop3 展示开关取自各成员的 user_profile，无资料行时不展示详情。
+const GROUP_LEADERBOARD_FROM_SQL: &str = "FROM leaderboard_group_member gm
+             JOIN leaderboard_rks lr ON lr.user_hash=gm.user_hash
+             LEFT JOIN user_profile up ON up.user_hash=gm.user_hash
+             WHERE gm.group_id=? AND lr.is_hidden=0";
+
+fn group_leaderboard_count_sql() -> String {
+    format!("SELECT COUNT(1) AS c {GROUP_LEADERBOARD_FROM_SQL}")
+}
+
+fn group_leaderboard_offset_sql() -> String {
+    format!(
+        "SELECT lr.user_hash, lr.total_rks, lr.updated_at, up.alias, COALESCE(up.show_best_top3,0) AS sbt, COALESCE(up.show_ap_top3,0) AS sat
+             {GROUP_LEADERBOARD_FROM_SQL}
+             ORDER BY lr.total_rks DESC, lr.updated_at ASC, lr.user_hash ASC
+             LIMIT ? OFFSET ?"
+    )
+}
+
+fn group_row(r: &SqliteRow) -> LeaderboardGroup {
+    LeaderboardGroup {
+        id: r.try_get("id").unwrap_or_default(),
+        name: r.try_get("name").unwrap_or_default(),
+        owner_hash: r.try_get("owner_hash").unwrap_or_default(),
+        invite_code: r.try_get("invite_code").unwrap_or_default(),
+        member_count: r.try_get("member_count").unwrap_or(0),
+        created_at: r.try_get("created_at").unwrap_or_default(),
+    }
+}
+
+fn map_invite_conflict(e: &sqlx::Error, ctx: &str) -> AppError {
+    if e.to_string().to_lowercase().contains("unique") {
+        AppError::Conflict("邀请码冲突，请重试".into())
+    } else {
+        AppError::Internal(format!("{ctx}: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_leaderboard_is_scoped_and_hides_moderated_users() {
+        for sql in [
+            group_leaderboard_count_sql(),
+            group_leaderboard_offset_sql(),
+        ] {
+            assert!(sql.contains("gm.group_id=?"));
+            assert!(sql.contains("lr.is_hidden=0"));
+            assert!(!sql.contains("up.is_public"));
+        }
+    }
+}
+
+impl StatsStorage {
+    /// 创建群组并把创建者加入成员；邀请码冲突时返回 Conflict。
+    pub async fn create_group(
+        &self,
+        id: &str,
+        name: &str,
+        owner_hash: &str,
+        invite_code: &str,
+        now_rfc3339: &str,
+    ) -> Result<(), AppError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Internal(format!("create group tx begin: {e}")))?;
+        sqlx::query(
+            "INSERT INTO leaderboard_group (id, name, owner_hash, invite_code, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(owner_hash)
+        .bind(invite_code)
+        .bind(now_rfc3339)
+        .bind(now_rfc3339)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| map_invite_conflict(&e, "create group"))?;
+        sqlx::query(
+            "INSERT INTO leaderboard_group_member (group_id, user_hash, joined_at) VALUES (?, ?, ?)",
+        )
+        .bind(id)
+        .bind(owner_hash)
+        .bind(now_rfc3339)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(format!("create group owner member: {e}")))?;
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Internal(format!("create group tx commit: {e}")))?;
+        Ok(())
+    }
+
+    pub async fn get_group(&self, id: &str) -> Result<Option<LeaderboardGroup>, AppError> {
+        let row = sqlx::query(&format!("{GROUP_COLUMNS_SQL} WHERE g.id = ?"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("get group: {e}")))?;
+        Ok(row.as_ref().map(group_row))
+    }
+
+    /// 按邀请码查找群组（大小写不敏感）
+    pub async fn get_group_by_invite_code(
+        &self,
+        invite_code: &str,
+    ) -> Result<Option<LeaderboardGroup>, AppError> {
+        let row = sqlx::query(&format!(
+            "{GROUP_COLUMNS_SQL} WHERE g.invite_code = ? COLLATE NOCASE"
+        ))
+        .bind(invite_code)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("get group by invite code: {e}")))?;
+        Ok(row.as_ref().map(group_row))
+    }
+
+    /// 用户加入的全部群组（含自己创建的），按加入时间升序
+    pub async fn list_user_groups(
+        &self,
+        user_hash: &str,
+    ) -> Result<Vec<LeaderboardGroup>, AppError> {
+        let rows = sqlx::query(&format!(
+            "{GROUP_COLUMNS_SQL} JOIN leaderboard_group_member me ON me.group_id=g.id
+             WHERE me.user_hash = ? ORDER BY me.joined_at ASC, me.id ASC"
+        ))
+        .bind(user_hash)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("list user groups: {e}")))?;
+        Ok(rows.iter().map(group_row).collect())
+    }
+
+    pub async fn count_owned_groups(&self, user_hash: &str) -> Result<i64, AppError> {
+        let row = sqlx::query("SELECT COUNT(1) AS c FROM leaderboard_group WHERE owner_hash = ?")
+            .bind(user_hash)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("count owned groups: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    pub async fn count_joined_groups(&self, user_hash: &str) -> Result<i64, AppError> {
+        let row =
+            sqlx::query("SELECT COUNT(1) AS c FROM leaderboard_group_member WHERE user_hash = ?")
+                .bind(user_hash)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| AppError::Internal(format!("count joined groups: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    pub async fn is_group_member(&self, group_id: &str, user_hash: &str) -> Result<bool, AppError> {
+        let row = sqlx::query(
+            "SELECT 1 AS x FROM leaderboard_group_member WHERE group_id = ? AND user_hash = ?",
+        )
+        .bind(group_id)
+        .bind(user_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("check group member: {e}")))?;
+        Ok(row.is_some())
+    }
+
+    /// 在成员数未达 `max_members` 时加入群组；已满或已是成员时返回 false。
+    pub async fn add_group_member(
+        &self,
+        group_id: &str,
+        user_hash: &str,
+        max_members: i64,
+        now_rfc3339: &str,
+    ) -> Result<bool, AppError> {
+        let res = sqlx::query(
+            "INSERT OR IGNORE INTO leaderboard_group_member (group_id, user_hash, joined_at)
+             SELECT ?, ?, ?
+             WHERE (SELECT COUNT(1) FROM leaderboard_group_member WHERE group_id = ?) < ?",
+        )
+        .bind(group_id)
+        .bind(user_hash)
+        .bind(now_rfc3339)
+        .bind(group_id)
+        .bind(max_members)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("add group member: {e}")))?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn remove_group_member(
+        &self,
+        group_id: &str,
+        user_hash: &str,
+    ) -> Result<bool, AppError> {
+        let res = sqlx::query(
+            "DELETE FROM leaderboard_group_member WHERE group_id = ? AND user_hash = ?",
+        )
+        .bind(group_id)
+        .bind(user_hash)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("remove group member: {e}")))?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// 群组成员列表，按加入时间升序
+    pub async fn query_group_members(
+        &self,
+        group_id: &str,
+    ) -> Result<Vec<LeaderboardGroupMember>, AppError> {
+        let rows = sqlx::query(
+            "SELECT gm.id, gm.user_hash, gm.joined_at, up.alias
+             FROM leaderboard_group_member gm
+             LEFT JOIN user_profile up ON up.user_hash=gm.user_hash
+             WHERE gm.group_id = ? ORDER BY gm.joined_at ASC, gm.id ASC",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("query group members: {e}")))?;
+        Ok(rows
+            .iter()
+            .map(|r| LeaderboardGroupMember {
+                id: r.try_get("id").unwrap_or(0),
+                user_hash: r.try_get("user_hash").unwrap_or_default(),
+                alias: r.try_get("alias").ok().flatten(),
+                joined_at: r.try_get("joined_at").unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// 重置邀请码；新邀请码冲突时返回 Conflict。
+    pub async fn set_group_invite_code(
+        &self,
+        group_id: &str,
+        invite_code: &str,
+        now_rfc3339: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE leaderboard_group SET invite_code = ?, updated_at = ? WHERE id = ?")
+            .bind(invite_code)
+            .bind(now_rfc3339)
+            .bind(group_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| map_invite_conflict(&e, "set group invite code"))?;
+        Ok(())
+    }
+
+    /// 解散群组（删除群组与全部成员关系）
+    pub async fn delete_group(&self, group_id: &str) -> Result<(), AppError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Internal(format!("delete group tx begin: {e}")))?;
+        sqlx::query("DELETE FROM leaderboard_group_member WHERE group_id = ?")
+            .bind(group_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Internal(format!("delete group members: {e}")))?;
+        sqlx::query("DELETE FROM leaderboard_group WHERE id = ?")
+            .bind(group_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Internal(format!("delete group: {e}")))?;
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Internal(format!("delete group tx commit: {e}")))?;
+        Ok(())
+    }
+
+    pub async fn count_group_leaderboard_total(&self, group_id: &str) -> Result<i64, AppError> {
+        let row = sqlx::query(&group_leaderboard_count_sql())
+            .bind(group_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("count group leaderboard total: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    /// 群组榜分页查询；列与公开榜 TOP 查询一致（user_hash/total_rks/updated_at/alias/sbt/sat）。
+    pub async fn query_group_leaderboard_offset(
+        &self,
+        group_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SqliteRow>, AppError> {
+        sqlx::query(&group_leaderboard_offset_sql())
+            .bind(group_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query group leaderboard: {e}")))
+    }
+}
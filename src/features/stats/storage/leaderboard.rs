@@ -2,7 +2,7 @@ use sqlx::Row;
 
 use crate::error::AppError;
 
-use super::StatsStorage;
+use super::{LeaderboardRksChange, RecomputedLeaderboardRow, ServerRegion, StatsStorage};
 
 impl StatsStorage {
     pub async fn get_prev_rks(&self, user_hash: &str) -> Result<Option<(f64, String)>, AppError> {
@@ -22,6 +22,20 @@ impl StatsStorage {
         }
     }
 
+    /// 读取用户在排行榜中记录的服务器区域（未上榜时为 None）
+    pub async fn get_leaderboard_region(
+        &self,
+        user_hash: &str,
+    ) -> Result<Option<String>, AppError> {
+        let row = sqlx::query("SELECT region FROM leaderboard_rks WHERE user_hash = ?")
+            .bind(user_hash)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("get leaderboard region: {e}")))?;
+        Ok(row.and_then(|r| r.try_get::<String, _>("region").ok()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn upsert_leaderboard_rks(
         &self,
         user_hash: &str,
@@ -29,28 +43,75 @@ impl StatsStorage {
         user_kind: Option<&str>,
         suspicion_score: f64,
         hide: bool,
+        region: ServerRegion,
         now_rfc3339: &str,
     ) -> Result<(), AppError> {
         let is_hidden_i = i64::from(hide);
-        sqlx::query(
-            "INSERT INTO leaderboard_rks(user_hash,total_rks,user_kind,suspicion_score,is_hidden,created_at,updated_at) VALUES(?,?,?,?,?,?,?)
+        // 区域只在本次提交能判定来源时覆盖，避免外部凭证提交把已知区域改回 unspecified
+        let row = sqlx::query(
+            "INSERT INTO leaderboard_rks(user_hash,total_rks,user_kind,suspicion_score,is_hidden,region,created_at,updated_at) VALUES(?,?,?,?,?,?,?,?)
              ON CONFLICT(user_hash) DO UPDATE SET
                total_rks = CASE WHEN excluded.total_rks > leaderboard_rks.total_rks THEN excluded.total_rks ELSE leaderboard_rks.total_rks END,
                updated_at = CASE WHEN excluded.total_rks > leaderboard_rks.total_rks THEN excluded.updated_at ELSE leaderboard_rks.updated_at END,
                user_kind = COALESCE(excluded.user_kind, leaderboard_rks.user_kind),
                suspicion_score = excluded.suspicion_score,
-               is_hidden = CASE WHEN leaderboard_rks.is_hidden=1 OR excluded.is_hidden=1 THEN 1 ELSE 0 END"
+               is_hidden = CASE WHEN leaderboard_rks.is_hidden=1 OR excluded.is_hidden=1 THEN 1 ELSE 0 END,
+               region = CASE WHEN excluded.region <> 'unspecified' THEN excluded.region ELSE leaderboard_rks.region END
+             RETURNING total_rks, updated_at, is_hidden, region"
         )
         .bind(user_hash)
         .bind(total_rks)
         .bind(user_kind)
         .bind(suspicion_score)
         .bind(is_hidden_i)
+        .bind(region.as_str())
         .bind(now_rfc3339)
         .bind(now_rfc3339)
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await
         .map_err(|e| AppError::Internal(format!("upsert leaderboard: {e}")))?;
+        if self.lb_changes.receiver_count() == 0 {
+            return Ok(());
+        }
+        let stored_rks: f64 = row.try_get("total_rks").unwrap_or_default();
+        let updated_at: String = row.try_get("updated_at").unwrap_or_default();
+        let hidden: i64 = row.try_get("is_hidden").unwrap_or(1);
+        // 本次写入确实刷新了成绩（而非保留旧的更高分）时才通知
+        #[allow(clippy::float_cmp)]
+        let improved = updated_at == now_rfc3339 && stored_rks == total_rks;
+        if improved && hidden == 0 {
+            let region: String = row.try_get("region").unwrap_or_default();
+            let _ = self.lb_changes.send(LeaderboardRksChange {
+                user_hash: user_hash.to_string(),
+                total_rks: stored_rks,
+                updated_at,
+                region: ServerRegion::from_code(&region).unwrap_or(ServerRegion::Unspecified),
+            });
+        }
+        Ok(())
+    }
+
+    /// 写入某一非当前规则下的总 RKS（与主榜一致：只在更高时覆盖）。
+    pub async fn upsert_leaderboard_rule_rks(
+        &self,
+        user_hash: &str,
+        rule: &str,
+        total_rks: f64,
+        now_rfc3339: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO leaderboard_rks_rule(user_hash,rule,total_rks,updated_at) VALUES(?,?,?,?)
+             ON CONFLICT(user_hash, rule) DO UPDATE SET
+               total_rks = CASE WHEN excluded.total_rks > leaderboard_rks_rule.total_rks THEN excluded.total_rks ELSE leaderboard_rks_rule.total_rks END,
+               updated_at = CASE WHEN excluded.total_rks > leaderboard_rks_rule.total_rks THEN excluded.updated_at ELSE leaderboard_rks_rule.updated_at END",
+        )
+        .bind(user_hash)
+        .bind(rule)
+        .bind(total_rks)
+        .bind(now_rfc3339)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("upsert leaderboard rule: {e}")))?;
         Ok(())
     }
 
@@ -95,4 +156,80 @@ impl StatsStorage {
         .map_err(|e| AppError::Internal(format!("upsert details: {e}")))?;
         Ok(())
     }
+
+    /// 排行榜用户总数（含隐藏用户）
+    pub async fn count_leaderboard_users(&self) -> Result<i64, AppError> {
+        let row = sqlx::query("SELECT COUNT(1) AS c FROM leaderboard_rks")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("count leaderboard users: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    /// 在一个事务内写入一批重算结果，返回实际写入的用户数。
+    ///
+    /// 读取快照之后用户又有新提交（最新提交 ID 与 `based_on_submission_id` 不符）时跳过该用户，
+    /// 避免用旧快照覆盖上传时已写入的新成绩。
+    /// `leaderboard_rks.updated_at` 保持不变（它表示最近一次成绩提升时间，也是同分排序依据）。
+    pub async fn apply_leaderboard_recompute(
+        &self,
+        rows: &[RecomputedLeaderboardRow],
+        now_rfc3339: &str,
+    ) -> Result<u64, AppError> {
+        let map_err =
+            |e: sqlx::Error| AppError::Internal(format!("apply leaderboard recompute: {e}"));
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        let mut applied = 0u64;
+        for row in rows {
+            let updated = sqlx::query(
+                "UPDATE leaderboard_rks SET total_rks=? WHERE user_hash=?
+                   AND (SELECT MAX(id) FROM save_submissions WHERE user_hash = ?) IS ?",
+            )
+            .bind(row.total_rks)
+            .bind(&row.user_hash)
+            .bind(&row.user_hash)
+            .bind(row.based_on_submission_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?
+            .rows_affected();
+            if updated == 0 {
+                continue;
+            }
+            applied += 1;
+            for (rule, total) in &row.rule_totals {
+                sqlx::query(
+                    "INSERT INTO leaderboard_rks_rule(user_hash,rule,total_rks,updated_at) VALUES(?,?,?,?)
+                     ON CONFLICT(user_hash, rule) DO UPDATE SET
+                       total_rks = excluded.total_rks,
+                       updated_at = excluded.updated_at",
+                )
+                .bind(&row.user_hash)
+                .bind(rule)
+                .bind(total)
+                .bind(now_rfc3339)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_err)?;
+            }
+            sqlx::query(
+                "INSERT INTO leaderboard_details(user_hash,rks_composition_json,best_top3_json,ap_top3_json,updated_at) VALUES(?,?,?,?,?)
+                 ON CONFLICT(user_hash) DO UPDATE SET
+                   rks_composition_json = excluded.rks_composition_json,
+                   best_top3_json = excluded.best_top3_json,
+                   ap_top3_json = excluded.ap_top3_json,
+                   updated_at = excluded.updated_at",
+            )
+            .bind(&row.user_hash)
+            .bind(row.rks_comp_json.as_deref())
+            .bind(row.best_top3_json.as_deref())
+            .bind(row.ap_top3_json.as_deref())
+            .bind(now_rfc3339)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+        }
+        tx.commit().await.map_err(map_err)?;
+        Ok(applied)
+    }
 }
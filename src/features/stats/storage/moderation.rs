@@ -4,7 +4,7 @@ use sqlx::{QueryBuilder, Row, Sqlite, sqlite::SqliteRow};
 
 use crate::error::AppError;
 
-use super::StatsStorage;
+use super::{ModerationFlag, NewModerationFlag, StatsStorage};
 
 fn push_admin_status_filter(qb: &mut QueryBuilder<'_, Sqlite>, status: &str) {
     if status.eq_ignore_ascii_case("active") {
@@ -287,4 +287,67 @@ impl StatsStorage {
             .map_err(|e| AppError::Internal(format!("moderation tx commit: {e}")))?;
         Ok(())
     }
+
+    /// 批量追加审核标记（自动规则命中记录等），不改变用户状态。
+    pub async fn insert_moderation_flags(
+        &self,
+        user_hash: &str,
+        flags: &[NewModerationFlag<'_>],
+        created_by: &str,
+        created_at: &str,
+    ) -> Result<(), AppError> {
+        if flags.is_empty() {
+            return Ok(());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO moderation_flags(user_hash,status,reason,severity,created_by,created_at) ",
+        );
+        qb.push_values(flags, |mut b, f| {
+            b.push_bind(user_hash)
+                .push_bind(f.status)
+                .push_bind(f.reason)
+                .push_bind(f.severity)
+                .push_bind(created_by)
+                .push_bind(created_at);
+        });
+        qb.build()
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("insert moderation flags: {e}")))?;
+        Ok(())
+    }
+
+    /// 按时间倒序读取用户的审核标记。
+    pub async fn query_moderation_flags(
+        &self,
+        user_hash: &str,
+        limit: i64,
+    ) -> Result<Vec<ModerationFlag>, AppError> {
+        let rows = sqlx::query(
+            "SELECT id, status, reason, severity, created_by, created_at
+             FROM moderation_flags
+             WHERE user_hash = ?
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?",
+        )
+        .bind(user_hash)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("query moderation flags: {e}")))?;
+        Ok(rows
+            .iter()
+            .map(|r| ModerationFlag {
+                id: r.try_get("id").unwrap_or(0),
+                status: r.try_get("status").unwrap_or_default(),
+                reason: r
+                    .try_get::<Option<String>, _>("reason")
+                    .unwrap_or(None)
+                    .filter(|v| !v.is_empty()),
+                severity: r.try_get("severity").unwrap_or(0),
+                created_by: r.try_get("created_by").unwrap_or_default(),
+                created_at: r.try_get("created_at").unwrap_or_default(),
+            })
+            .collect())
+    }
 }
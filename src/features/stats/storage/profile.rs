@@ -1,4 +1,4 @@
-use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, sqlite::SqliteRow};
 
 use crate::error::AppError;
 
@@ -57,7 +57,7 @@ impl StatsStorage {
         alias: &str,
     ) -> Result<Option<SqliteRow>, AppError> {
         sqlx::query(
-            "SELECT up.user_hash, up.is_public, up.show_rks_composition, up.show_best_top3, up.show_ap_top3, lr.total_rks, lr.updated_at
+            "SELECT up.user_hash, up.is_public, up.show_rks_composition, up.show_best_top3, up.show_ap_top3, lr.total_rks, lr.updated_at, lr.region
              FROM user_profile up LEFT JOIN leaderboard_rks lr ON lr.user_hash=up.user_hash WHERE up.alias = ?",
         )
         .bind(alias)
@@ -66,6 +66,30 @@ impl StatsStorage {
         .map_err(|e| AppError::Internal(format!("query public profile by alias: {e}")))
     }
 
+    /// 读取用户当前别名（未设置时为 None）
+    /// 用户公开展示的别名：未公开资料时返回 None，公开但未设置别名时返回 `Some(None)`。
+    pub async fn get_public_alias(
+        &self,
+        user_hash: &str,
+    ) -> Result<Option<Option<String>>, AppError> {
+        let row =
+            sqlx::query("SELECT alias FROM user_profile WHERE user_hash = ? AND is_public = 1")
+                .bind(user_hash)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| AppError::Internal(format!("get public alias: {e}")))?;
+        Ok(row.map(|r| r.try_get::<Option<String>, _>("alias").ok().flatten()))
+    }
+
+    pub async fn get_user_alias(&self, user_hash: &str) -> Result<Option<String>, AppError> {
+        let row = sqlx::query("SELECT alias FROM user_profile WHERE user_hash = ?")
+            .bind(user_hash)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("get user alias: {e}")))?;
+        Ok(row.and_then(|r| r.try_get::<Option<String>, _>("alias").ok().flatten()))
+    }
+
     pub async fn query_leaderboard_details_row(
         &self,
         user_hash: &str,
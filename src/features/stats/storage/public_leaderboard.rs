@@ -4,16 +4,17 @@ use sqlx::{QueryBuilder, Row, Sqlite, sqlite::SqliteRow};
 
 use crate::error::AppError;
 
-use super::StatsStorage;
+use super::{ChartLeaderboardRow, StatsStorage};
 
+// 公开榜查询均带可选区域过滤：region 绑定为 NULL 时即 cn/global/unspecified 合并榜。
 const COUNT_PUBLIC_LEADERBOARD_TOTAL_SQL: &str = "SELECT COUNT(1) AS c
              FROM leaderboard_rks lr JOIN user_profile up ON up.user_hash=lr.user_hash AND up.is_public=1
-             WHERE lr.is_hidden=0";
+             WHERE lr.is_hidden=0 AND (? IS NULL OR lr.region=?)";
 
 const QUERY_LEADERBOARD_TOP_SEEK_SQL: &str =
     "SELECT lr.user_hash, lr.total_rks, lr.updated_at, up.alias, COALESCE(up.show_best_top3,0) AS sbt, COALESCE(up.show_ap_top3,0) AS sat
              FROM leaderboard_rks lr JOIN user_profile up ON up.user_hash=lr.user_hash AND up.is_public=1
-             WHERE lr.is_hidden=0 AND (
+             WHERE lr.is_hidden=0 AND (? IS NULL OR lr.region=?) AND (
                lr.total_rks < ? OR (lr.total_rks = ? AND (lr.updated_at > ? OR (lr.updated_at = ? AND lr.user_hash > ?)))
              )
              ORDER BY lr.total_rks DESC, lr.updated_at ASC, lr.user_hash ASC
@@ -22,16 +23,102 @@ const QUERY_LEADERBOARD_TOP_SEEK_SQL: &str =
 const QUERY_LEADERBOARD_TOP_OFFSET_SQL: &str =
     "SELECT lr.user_hash, lr.total_rks, lr.updated_at, up.alias, COALESCE(up.show_best_top3,0) AS sbt, COALESCE(up.show_ap_top3,0) AS sat
              FROM leaderboard_rks lr JOIN user_profile up ON up.user_hash=lr.user_hash AND up.is_public=1
-             WHERE lr.is_hidden=0
+             WHERE lr.is_hidden=0 AND (? IS NULL OR lr.region=?)
              ORDER BY lr.total_rks DESC, lr.updated_at ASC, lr.user_hash ASC
              LIMIT ? OFFSET ?";
 
 const COUNT_PUBLIC_LEADERBOARD_HIGHER_SQL: &str =
     "SELECT COUNT(1) as higher FROM leaderboard_rks lr JOIN user_profile up ON up.user_hash=lr.user_hash AND up.is_public=1
-             WHERE lr.is_hidden=0 AND (
+             WHERE lr.is_hidden=0 AND (? IS NULL OR lr.region=?) AND (
                lr.total_rks > ? OR (lr.total_rks = ? AND (lr.updated_at < ? OR (lr.updated_at = ? AND lr.user_hash < ?)))
              )";
 
+// 分布快照：公开榜全部分数（升序），供缓存后计算直方图与百分位。
+const QUERY_PUBLIC_RKS_SCORES_SQL: &str = "SELECT lr.total_rks
+             FROM leaderboard_rks lr JOIN user_profile up ON up.user_hash=lr.user_hash AND up.is_public=1
+             WHERE lr.is_hidden=0 AND (? IS NULL OR lr.region=?)
+             ORDER BY lr.total_rks ASC";
+
+// 分规则排行榜：分数与排序字段取自 leaderboard_rks_rule，可见性取自主榜。
+const COUNT_PUBLIC_RULE_LEADERBOARD_TOTAL_SQL: &str = "SELECT COUNT(1) AS c
+             FROM leaderboard_rks_rule lrr
+             JOIN leaderboard_rks lr ON lr.user_hash=lrr.user_hash
+             JOIN user_profile up ON up.user_hash=lrr.user_hash AND up.is_public=1
+             WHERE lrr.rule=? AND lr.is_hidden=0 AND (? IS NULL OR lr.region=?)";
+
+const QUERY_RULE_LEADERBOARD_TOP_SEEK_SQL: &str =
+    "SELECT lrr.user_hash, lrr.total_rks, lrr.updated_at, up.alias, COALESCE(up.show_best_top3,0) AS sbt, COALESCE(up.show_ap_top3,0) AS sat
+             FROM leaderboard_rks_rule lrr
+             JOIN leaderboard_rks lr ON lr.user_hash=lrr.user_hash
+             JOIN user_profile up ON up.user_hash=lrr.user_hash AND up.is_public=1
+             WHERE lrr.rule=? AND lr.is_hidden=0 AND (? IS NULL OR lr.region=?) AND (
+               lrr.total_rks < ? OR (lrr.total_rks = ? AND (lrr.updated_at > ? OR (lrr.updated_at = ? AND lrr.user_hash > ?)))
+             )
+             ORDER BY lrr.total_rks DESC, lrr.updated_at ASC, lrr.user_hash ASC
+             LIMIT ?";
+
+const QUERY_RULE_LEADERBOARD_TOP_OFFSET_SQL: &str =
+    "SELECT lrr.user_hash, lrr.total_rks, lrr.updated_at, up.alias, COALESCE(up.show_best_top3,0) AS sbt, COALESCE(up.show_ap_top3,0) AS sat
+             FROM leaderboard_rks_rule lrr
+             JOIN leaderboard_rks lr ON lr.user_hash=lrr.user_hash
+             JOIN user_profile up ON up.user_hash=lrr.user_hash AND up.is_public=1
+             WHERE lrr.rule=? AND lr.is_hidden=0 AND (? IS NULL OR lr.region=?)
+             ORDER BY lrr.total_rks DESC, lrr.updated_at ASC, lrr.user_hash ASC
+             LIMIT ? OFFSET ?";
+
+// 单谱面排行榜：成绩取自 chart_score_state，可见性取自主榜与 user_profile，
+// 另外直接排除处于 shadow/banned/rejected 状态的用户（防止主榜隐藏标记滞后）。
+const CHART_LEADERBOARD_VISIBLE_SQL: &str = "FROM chart_score_state cs
+             JOIN leaderboard_rks lr ON lr.user_hash=cs.user_hash
+             JOIN user_profile up ON up.user_hash=cs.user_hash AND up.is_public=1
+             WHERE cs.song_id=? AND cs.difficulty=? AND lr.is_hidden=0
+               AND NOT EXISTS (
+                 SELECT 1 FROM user_moderation_state ums
+                 WHERE ums.user_hash=cs.user_hash
+                   AND ums.status COLLATE NOCASE IN ('shadow','banned','rejected')
+               )";
+
+const CHART_LEADERBOARD_COLUMNS_SQL: &str =
+    "SELECT cs.user_hash, cs.score, cs.acc, cs.is_fc, cs.updated_at, up.alias ";
+
+const CHART_LEADERBOARD_ORDER_SQL: &str =
+    " ORDER BY cs.score DESC, cs.acc DESC, cs.updated_at ASC, cs.user_hash ASC";
+
+const CHART_LEADERBOARD_SEEK_SQL: &str = " AND (
+               cs.score < ? OR (cs.score = ? AND (
+                 cs.acc < ? OR (cs.acc = ? AND (
+                   cs.updated_at > ? OR (cs.updated_at = ? AND cs.user_hash > ?)
+                 ))
+               ))
+             )";
+
+fn chart_leaderboard_count_sql() -> String {
+    format!("SELECT COUNT(1) AS c {CHART_LEADERBOARD_VISIBLE_SQL}")
+}
+
+fn chart_leaderboard_seek_sql() -> String {
+    format!(
+        "{CHART_LEADERBOARD_COLUMNS_SQL}{CHART_LEADERBOARD_VISIBLE_SQL}{CHART_LEADERBOARD_SEEK_SQL}{CHART_LEADERBOARD_ORDER_SQL} LIMIT ?"
+    )
+}
+
+fn chart_leaderboard_offset_sql() -> String {
+    format!(
+        "{CHART_LEADERBOARD_COLUMNS_SQL}{CHART_LEADERBOARD_VISIBLE_SQL}{CHART_LEADERBOARD_ORDER_SQL} LIMIT ? OFFSET ?"
+    )
+}
+
+fn chart_leaderboard_row(r: &SqliteRow) -> ChartLeaderboardRow {
+    ChartLeaderboardRow {
+        user_hash: r.try_get("user_hash").unwrap_or_default(),
+        alias: r.try_get("alias").ok(),
+        score: r.try_get("score").unwrap_or(0),
+        acc: r.try_get("acc").unwrap_or(0.0),
+        is_fc: r.try_get::<i64, _>("is_fc").unwrap_or(0) != 0,
+        updated_at: r.try_get("updated_at").unwrap_or_default(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,22 +130,56 @@ mod tests {
             QUERY_LEADERBOARD_TOP_SEEK_SQL,
             QUERY_LEADERBOARD_TOP_OFFSET_SQL,
             COUNT_PUBLIC_LEADERBOARD_HIGHER_SQL,
-        ];
+            QUERY_PUBLIC_RKS_SCORES_SQL,
+            COUNT_PUBLIC_RULE_LEADERBOARD_TOTAL_SQL,
+            QUERY_RULE_LEADERBOARD_TOP_SEEK_SQL,
+            QUERY_RULE_LEADERBOARD_TOP_OFFSET_SQL,
+        ]
+        .map(str::to_string)
+        .into_iter()
+        .chain([
+            chart_leaderboard_count_sql(),
+            chart_leaderboard_seek_sql(),
+            chart_leaderboard_offset_sql(),
+        ])
+        .collect::<Vec<_>>();
+
+        for sql in &queries[..8] {
+            assert!(sql.contains("AND (? IS NULL OR lr.region=?)"));
+        }
 
         let coalesce_public = ["COALESCE(", "up.is_public"].concat();
         let left_profile_join = ["LEFT JOIN ", "user_profile"].concat();
-        for sql in queries {
+        for sql in &queries {
             assert!(sql.contains("JOIN user_profile up"));
             assert!(sql.contains("up.is_public=1"));
             assert!(!sql.contains(&coalesce_public));
             assert!(!sql.contains(&left_profile_join));
         }
     }
+
+    #[test]
+    fn chart_leaderboard_filters_hidden_and_moderated_users() {
+        for sql in [
+            chart_leaderboard_count_sql(),
+            chart_leaderboard_seek_sql(),
+            chart_leaderboard_offset_sql(),
+        ] {
+            assert!(sql.contains("lr.is_hidden=0"));
+            assert!(sql.contains("user_moderation_state"));
+            assert!(sql.contains("cs.song_id=? AND cs.difficulty=?"));
+        }
+    }
 }
 
 impl StatsStorage {
-    pub async fn count_public_leaderboard_total(&self) -> Result<i64, AppError> {
+    pub async fn count_public_leaderboard_total(
+        &self,
+        region: Option<&str>,
+    ) -> Result<i64, AppError> {
         let row = sqlx::query(COUNT_PUBLIC_LEADERBOARD_TOTAL_SQL)
+            .bind(region)
+            .bind(region)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| AppError::Internal(format!("count public leaderboard total: {e}")))?;
@@ -67,12 +188,15 @@ impl StatsStorage {
 
     pub async fn query_leaderboard_top_seek(
         &self,
+        region: Option<&str>,
         after_score: f64,
         after_updated: &str,
         after_user: &str,
         limit: i64,
     ) -> Result<Vec<SqliteRow>, AppError> {
         sqlx::query(QUERY_LEADERBOARD_TOP_SEEK_SQL)
+            .bind(region)
+            .bind(region)
             .bind(after_score)
             .bind(after_score)
             .bind(after_updated)
@@ -86,10 +210,13 @@ impl StatsStorage {
 
     pub async fn query_leaderboard_top_offset(
         &self,
+        region: Option<&str>,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<SqliteRow>, AppError> {
         sqlx::query(QUERY_LEADERBOARD_TOP_OFFSET_SQL)
+            .bind(region)
+            .bind(region)
             .bind(limit)
             .bind(offset)
             .fetch_all(&self.pool)
@@ -97,12 +224,71 @@ impl StatsStorage {
             .map_err(|e| AppError::Internal(format!("query top offset: {e}")))
     }
 
+    pub async fn count_public_rule_leaderboard_total(
+        &self,
+        rule: &str,
+        region: Option<&str>,
+    ) -> Result<i64, AppError> {
+        let row = sqlx::query(COUNT_PUBLIC_RULE_LEADERBOARD_TOTAL_SQL)
+            .bind(rule)
+            .bind(region)
+            .bind(region)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("count public rule leaderboard total: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    pub async fn query_rule_leaderboard_top_seek(
+        &self,
+        rule: &str,
+        region: Option<&str>,
+        after_score: f64,
+        after_updated: &str,
+        after_user: &str,
+        limit: i64,
+    ) -> Result<Vec<SqliteRow>, AppError> {
+        sqlx::query(QUERY_RULE_LEADERBOARD_TOP_SEEK_SQL)
+            .bind(rule)
+            .bind(region)
+            .bind(region)
+            .bind(after_score)
+            .bind(after_score)
+            .bind(after_updated)
+            .bind(after_updated)
+            .bind(after_user)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query rule top seek: {e}")))
+    }
+
+    pub async fn query_rule_leaderboard_top_offset(
+        &self,
+        rule: &str,
+        region: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SqliteRow>, AppError> {
+        sqlx::query(QUERY_RULE_LEADERBOARD_TOP_OFFSET_SQL)
+            .bind(rule)
+            .bind(region)
+            .bind(region)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query rule top offset: {e}")))
+    }
+
     pub async fn query_leaderboard_by_rank(
         &self,
+        region: Option<&str>,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<SqliteRow>, AppError> {
-        self.query_leaderboard_top_offset(limit, offset).await
+        self.query_leaderboard_top_offset(region, limit, offset)
+            .await
     }
 
     pub async fn fetch_top3_details_for_users(
@@ -137,11 +323,14 @@ impl StatsStorage {
 
     pub async fn count_public_leaderboard_higher(
         &self,
+        region: Option<&str>,
         score: f64,
         updated_at: &str,
         user_hash: &str,
     ) -> Result<i64, AppError> {
         let row = sqlx::query(COUNT_PUBLIC_LEADERBOARD_HIGHER_SQL)
+            .bind(region)
+            .bind(region)
             .bind(score)
             .bind(score)
             .bind(updated_at)
@@ -152,4 +341,80 @@ impl StatsStorage {
             .map_err(|e| AppError::Internal(format!("count public leaderboard higher: {e}")))?;
         Ok(row.try_get("higher").unwrap_or(0))
     }
+
+    /// 公开榜全部 RKS（升序）；`region` 为 None 时为合并榜
+    pub async fn query_public_rks_scores(
+        &self,
+        region: Option<&str>,
+    ) -> Result<Vec<f64>, AppError> {
+        let rows = sqlx::query(QUERY_PUBLIC_RKS_SCORES_SQL)
+            .bind(region)
+            .bind(region)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query public rks scores: {e}")))?;
+        Ok(rows
+            .iter()
+            .map(|r| r.try_get("total_rks").unwrap_or(0.0))
+            .collect())
+    }
+
+    pub async fn count_public_chart_leaderboard_total(
+        &self,
+        song_id: &str,
+        difficulty: &str,
+    ) -> Result<i64, AppError> {
+        let row = sqlx::query(&chart_leaderboard_count_sql())
+            .bind(song_id)
+            .bind(difficulty)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("count chart leaderboard total: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    /// 单谱面榜 seek 查询：返回排序键严格位于 `after` 之后的行
+    /// （score DESC, acc DESC, updated_at ASC, user_hash ASC）。
+    pub async fn query_chart_leaderboard_seek(
+        &self,
+        song_id: &str,
+        difficulty: &str,
+        after: (i64, f64, &str, &str),
+        limit: i64,
+    ) -> Result<Vec<ChartLeaderboardRow>, AppError> {
+        let (score, acc, updated_at, user_hash) = after;
+        let rows = sqlx::query(&chart_leaderboard_seek_sql())
+            .bind(song_id)
+            .bind(difficulty)
+            .bind(score)
+            .bind(score)
+            .bind(acc)
+            .bind(acc)
+            .bind(updated_at)
+            .bind(updated_at)
+            .bind(user_hash)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query chart leaderboard seek: {e}")))?;
+        Ok(rows.iter().map(chart_leaderboard_row).collect())
+    }
+
+    pub async fn query_chart_leaderboard_offset(
+        &self,
+        song_id: &str,
+        difficulty: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ChartLeaderboardRow>, AppError> {
+        let rows = sqlx::query(&chart_leaderboard_offset_sql())
+            .bind(song_id)
+            .bind(difficulty)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query chart leaderboard offset: {e}")))?;
+        Ok(rows.iter().map(chart_leaderboard_row).collect())
+    }
 }
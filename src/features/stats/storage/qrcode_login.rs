@@ -0,0 +1,185 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use sqlx::Row;
+
+use crate::error::AppError;
+
+use super::{QrCodeLoginRow, StatsStorage};
+
+const QRCODE_CLEANUP_INTERVAL_MS: i64 = 60_000;
+static LAST_QRCODE_CLEANUP_MS: AtomicI64 = AtomicI64::new(0);
+
+fn map_row(r: &sqlx::sqlite::SqliteRow) -> QrCodeLoginRow {
+    QrCodeLoginRow {
+        status: r.try_get("status").unwrap_or_default(),
+        device_code: r.try_get("device_code").unwrap_or_default(),
+        device_id: r.try_get("device_id").unwrap_or_default(),
+        interval_secs: r.try_get("interval_secs").unwrap_or(5),
+        next_poll_at_ms: r.try_get("next_poll_at_ms").unwrap_or(0),
+        expires_at_ms: r.try_get("expires_at_ms").unwrap_or(0),
+        version: r.try_get("version").unwrap_or(None),
+        session_token: r.try_get("session_token").unwrap_or(None),
+    }
+}
+
+impl StatsStorage {
+    /// 写入/覆盖一条二维码登录状态
+    pub async fn upsert_qrcode_login(
+        &self,
+        qr_id: &str,
+        row: &QrCodeLoginRow,
+        now_ms: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO qrcode_login(qr_id,status,device_code,device_id,interval_secs,next_poll_at_ms,expires_at_ms,version,session_token,updated_at_ms)
+             VALUES(?,?,?,?,?,?,?,?,?,?)
+             ON CONFLICT(qr_id) DO UPDATE SET
+               status=excluded.status, device_code=excluded.device_code, device_id=excluded.device_id,
+               interval_secs=excluded.interval_secs, next_poll_at_ms=excluded.next_poll_at_ms,
+               expires_at_ms=excluded.expires_at_ms, version=excluded.version,
+               session_token=excluded.session_token, updated_at_ms=excluded.updated_at_ms",
+        )
+        .bind(qr_id)
+        .bind(&row.status)
+        .bind(&row.device_code)
+        .bind(&row.device_id)
+        .bind(row.interval_secs)
+        .bind(row.next_poll_at_ms)
+        .bind(row.expires_at_ms)
+        .bind(row.version.as_deref())
+        .bind(row.session_token.as_deref())
+        .bind(now_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("upsert qrcode login: {e}")))?;
+        Ok(())
+    }
+
+    pub async fn get_qrcode_login(&self, qr_id: &str) -> Result<Option<QrCodeLoginRow>, AppError> {
+        let row = sqlx::query(
+            "SELECT status,device_code,device_id,interval_secs,next_poll_at_ms,expires_at_ms,version,session_token
+             FROM qrcode_login WHERE qr_id = ?",
+        )
+        .bind(qr_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("get qrcode login: {e}")))?;
+        Ok(row.as_ref().map(map_row))
+    }
+
+    pub async fn delete_qrcode_login(&self, qr_id: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM qrcode_login WHERE qr_id = ?")
+            .bind(qr_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("delete qrcode login: {e}")))?;
+        Ok(())
+    }
+
+    /// 抢占一轮 TapTap 轮询：仅当仍为 pending 且已到轮询时间时把下次轮询推后到 `next_poll_at_ms`。
+    ///
+    /// 多实例并发调用时只有一个能成功，避免同一 device code 被重复消费。
+    pub async fn claim_qrcode_poll(
+        &self,
+        qr_id: &str,
+        now_ms: i64,
+        next_poll_at_ms: i64,
+    ) -> Result<bool, AppError> {
+        let affected = sqlx::query(
+            "UPDATE qrcode_login SET next_poll_at_ms = ?, updated_at_ms = ?
+             WHERE qr_id = ? AND status = 'pending' AND next_poll_at_ms <= ? AND expires_at_ms > ?",
+        )
+        .bind(next_poll_at_ms)
+        .bind(now_ms)
+        .bind(qr_id)
+        .bind(now_ms)
+        .bind(now_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("claim qrcode poll: {e}")))?
+        .rows_affected();
+        Ok(affected == 1)
+    }
+
+    /// 把仍为 pending 的记录的下次轮询设为 `next_poll_at_ms`；已确认或已删除时返回 false。
+    pub async fn reschedule_qrcode_poll(
+        &self,
+        qr_id: &str,
+        next_poll_at_ms: i64,
+        now_ms: i64,
+    ) -> Result<bool, AppError> {
+        let affected = sqlx::query(
+            "UPDATE qrcode_login SET next_poll_at_ms = ?, updated_at_ms = ?
+             WHERE qr_id = ? AND status = 'pending'",
+        )
+        .bind(next_poll_at_ms)
+        .bind(now_ms)
+        .bind(qr_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("reschedule qrcode poll: {e}")))?
+        .rows_affected();
+        Ok(affected == 1)
+    }
+
+    /// pending → confirmed 原子转换；记录已不是 pending 时返回 false。
+    ///
+    /// `sealed_session_token` 为调用方加密后的会话令牌，本层不接触明文。
+    pub async fn confirm_qrcode_login(
+        &self,
+        qr_id: &str,
+        sealed_session_token: &str,
+        now_ms: i64,
+    ) -> Result<bool, AppError> {
+        let affected = sqlx::query(
+            "UPDATE qrcode_login SET status = 'confirmed', session_token = ?, updated_at_ms = ?
+             WHERE qr_id = ? AND status = 'pending'",
+        )
+        .bind(sealed_session_token)
+        .bind(now_ms)
+        .bind(qr_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("confirm qrcode login: {e}")))?
+        .rows_affected();
+        Ok(affected == 1)
+    }
+
+    /// 删除已过期且超过 `grace_ms` 的记录（已确认但未被取走的令牌同样清理）
+    pub async fn cleanup_expired_qrcode_logins(
+        &self,
+        now_ms: i64,
+        grace_ms: i64,
+    ) -> Result<u64, AppError> {
+        let deleted = sqlx::query("DELETE FROM qrcode_login WHERE expires_at_ms <= ?")
+            .bind(now_ms.saturating_sub(grace_ms))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("cleanup qrcode login: {e}")))?
+            .rows_affected();
+        Ok(deleted)
+    }
+
+    /// 节流版清理：同一进程内至多每分钟执行一次
+    pub async fn maybe_cleanup_expired_qrcode_logins(
+        &self,
+        now_ms: i64,
+        grace_ms: i64,
+    ) -> Result<bool, AppError> {
+        let last = LAST_QRCODE_CLEANUP_MS.load(Ordering::Relaxed);
+        if now_ms - last < QRCODE_CLEANUP_INTERVAL_MS {
+            return Ok(false);
+        }
+        if LAST_QRCODE_CLEANUP_MS
+            .compare_exchange(last, now_ms, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            return Ok(false);
+        }
+        if let Err(e) = self.cleanup_expired_qrcode_logins(now_ms, grace_ms).await {
+            LAST_QRCODE_CLEANUP_MS.store(last, Ordering::Relaxed);
+            return Err(e);
+        }
+        Ok(true)
+    }
+}
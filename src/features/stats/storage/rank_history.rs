@@ -0,0 +1,172 @@
+#![allow(clippy::items_after_test_module)]
+
+use sqlx::{Row, sqlite::SqliteRow};
+
+use crate::error::AppError;
+
+use super::{RankSnapshotPoint, RksGainerRow, StatsStorage};
+
+// 与公开 RKS 榜同一口径（公开且未隐藏）与同一排序，写入某日的名次快照。
+const INSERT_RANK_SNAPSHOT_SQL: &str =
+    "INSERT INTO leaderboard_rank_daily (date, user_hash, rank, total_rks)
+             SELECT ?, lr.user_hash,
+                    ROW_NUMBER() OVER (ORDER BY lr.total_rks DESC, lr.updated_at ASC, lr.user_hash ASC),
+                    lr.total_rks
+             FROM leaderboard_rks lr JOIN user_profile up ON up.user_hash=lr.user_hash AND up.is_public=1
+             WHERE lr.is_hidden=0";
+
+// 窗口涨幅：窗口内最高 RKS 减去窗口开始前的最高 RKS；窗口前无提交的新用户不参与排名。
+const RKS_GAINERS_CTE_SQL: &str = "WITH win AS (
+               SELECT user_hash, MAX(total_rks) AS to_rks FROM save_submissions
+               WHERE created_at >= ? GROUP BY user_hash
+             ), base AS (
+               SELECT s.user_hash, MAX(s.total_rks) AS from_rks
+               FROM save_submissions s JOIN win ON win.user_hash=s.user_hash
+               WHERE s.created_at < ? GROUP BY s.user_hash
+             )";
+
+const RKS_GAINERS_VISIBLE_SQL: &str = "FROM win JOIN base ON base.user_hash=win.user_hash
+             JOIN leaderboard_rks lr ON lr.user_hash=win.user_hash
+             JOIN user_profile up ON up.user_hash=win.user_hash AND up.is_public=1
+             WHERE lr.is_hidden=0 AND win.to_rks - base.from_rks > 1e-9";
+
+fn rks_gainers_count_sql() -> String {
+    format!("{RKS_GAINERS_CTE_SQL} SELECT COUNT(1) AS c {RKS_GAINERS_VISIBLE_SQL}")
+}
+
+fn rks_gainers_page_sql() -> String {
+    format!(
+        "{RKS_GAINERS_CTE_SQL} SELECT win.user_hash, lr.total_rks, base.from_rks, win.to_rks, up.alias
+             {RKS_GAINERS_VISIBLE_SQL}
+             ORDER BY win.to_rks - base.from_rks DESC, win.user_hash ASC
+             LIMIT ? OFFSET ?"
+    )
+}
+
+fn rks_gainer_row(r: &SqliteRow) -> RksGainerRow {
+    RksGainerRow {
+        user_hash: r.try_get("user_hash").unwrap_or_default(),
+        alias: r.try_get("alias").ok(),
+        total_rks: r.try_get("total_rks").unwrap_or(0.0),
+        from_rks: r.try_get("from_rks").unwrap_or(0.0),
+        to_rks: r.try_get("to_rks").unwrap_or(0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_queries_only_include_public_visible_users() {
+        for sql in [
+            INSERT_RANK_SNAPSHOT_SQL.to_string(),
+            rks_gainers_count_sql(),
+            rks_gainers_page_sql(),
+        ] {
+            assert!(sql.contains("JOIN user_profile up"));
+            assert!(sql.contains("up.is_public=1"));
+            assert!(sql.contains("lr.is_hidden=0"));
+            assert!(!sql.contains("LEFT JOIN"));
+        }
+    }
+}
+
+impl StatsStorage {
+    pub async fn has_rank_snapshot(&self, date: &str) -> Result<bool, AppError> {
+        let row = sqlx::query("SELECT 1 AS x FROM leaderboard_rank_daily WHERE date = ? LIMIT 1")
+            .bind(date)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("check rank snapshot: {e}")))?;
+        Ok(row.is_some())
+    }
+
+    /// 重写某日（UTC，YYYY-MM-DD）的排名快照，返回写入的用户数。
+    pub async fn snapshot_leaderboard_ranks(&self, date: &str) -> Result<u64, AppError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Internal(format!("rank snapshot tx begin: {e}")))?;
+        sqlx::query("DELETE FROM leaderboard_rank_daily WHERE date = ?")
+            .bind(date)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Internal(format!("rank snapshot delete: {e}")))?;
+        let inserted = sqlx::query(INSERT_RANK_SNAPSHOT_SQL)
+            .bind(date)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Internal(format!("rank snapshot insert: {e}")))?
+            .rows_affected();
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Internal(format!("rank snapshot tx commit: {e}")))?;
+        Ok(inserted)
+    }
+
+    /// 删除早于 `before_date` 的排名快照，返回删除行数。
+    pub async fn prune_rank_snapshots(&self, before_date: &str) -> Result<u64, AppError> {
+        let res = sqlx::query("DELETE FROM leaderboard_rank_daily WHERE date < ?")
+            .bind(before_date)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("prune rank snapshots: {e}")))?;
+        Ok(res.rows_affected())
+    }
+
+    /// 用户自 `since_date`（含）起的每日名次，按日期升序。
+    pub async fn query_rank_history(
+        &self,
+        user_hash: &str,
+        since_date: &str,
+    ) -> Result<Vec<RankSnapshotPoint>, AppError> {
+        let rows = sqlx::query(
+            "SELECT date, rank, total_rks FROM leaderboard_rank_daily
+             WHERE user_hash = ? AND date >= ? ORDER BY date ASC",
+        )
+        .bind(user_hash)
+        .bind(since_date)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("query rank history: {e}")))?;
+        Ok(rows
+            .iter()
+            .map(|r| RankSnapshotPoint {
+                date: r.try_get("date").unwrap_or_default(),
+                rank: r.try_get("rank").unwrap_or(0),
+                total_rks: r.try_get("total_rks").unwrap_or(0.0),
+            })
+            .collect())
+    }
+
+    /// 自 `since`（RFC3339）起 RKS 涨幅为正的公开用户数。
+    pub async fn count_rks_gainers(&self, since: &str) -> Result<i64, AppError> {
+        let row = sqlx::query(&rks_gainers_count_sql())
+            .bind(since)
+            .bind(since)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("count rks gainers: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    /// 自 `since`（RFC3339）起按 RKS 涨幅降序的公开用户。
+    pub async fn query_rks_gainers(
+        &self,
+        since: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<RksGainerRow>, AppError> {
+        let rows = sqlx::query(&rks_gainers_page_sql())
+            .bind(since)
+            .bind(since)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query rks gainers: {e}")))?;
+        Ok(rows.iter().map(rks_gainer_row).collect())
+    }
+}
@@ -0,0 +1,49 @@
+use sqlx::Row;
+
+use crate::error::AppError;
+
+use super::{StatsStorage, StoredSaveSnapshot};
+
+impl StatsStorage {
+    /// 读取用户的存档差异基准快照
+    pub async fn get_save_snapshot(
+        &self,
+        user_hash: &str,
+    ) -> Result<Option<StoredSaveSnapshot>, AppError> {
+        let row = sqlx::query(
+            "SELECT snapshot_json, save_updated_at, stored_at FROM save_snapshot WHERE user_hash = ?",
+        )
+        .bind(user_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("get save snapshot: {e}")))?;
+        Ok(row.map(|r| StoredSaveSnapshot {
+            snapshot_json: r.try_get("snapshot_json").unwrap_or_default(),
+            save_updated_at: r.try_get("save_updated_at").ok().flatten(),
+            stored_at: r.try_get("stored_at").unwrap_or_default(),
+        }))
+    }
+
+    /// 覆盖写入用户的存档差异基准快照
+    pub async fn put_save_snapshot(
+        &self,
+        user_hash: &str,
+        snapshot: &StoredSaveSnapshot,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO save_snapshot(user_hash,snapshot_json,save_updated_at,stored_at) VALUES(?,?,?,?)
+             ON CONFLICT(user_hash) DO UPDATE SET
+               snapshot_json = excluded.snapshot_json,
+               save_updated_at = excluded.save_updated_at,
+               stored_at = excluded.stored_at",
+        )
+        .bind(user_hash)
+        .bind(&snapshot.snapshot_json)
+        .bind(snapshot.save_updated_at.as_deref())
+        .bind(&snapshot.stored_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("put save snapshot: {e}")))?;
+        Ok(())
+    }
+}
@@ -85,19 +85,23 @@ impl StatsStorage {
         Ok(row.and_then(|r| r.try_get::<String, _>("logout_before").ok()))
     }
 
+    /// 查询令牌吊销状态；`family_id` 对应的刷新会话族已吊销时同样视为已拉黑
     pub async fn get_session_revoke_state(
         &self,
         jti: &str,
+        family_id: Option<&str>,
         user_hash: &str,
         now_rfc3339: &str,
     ) -> Result<(bool, Option<String>), AppError> {
         let row = sqlx::query(
             "SELECT
-               EXISTS(SELECT 1 FROM session_token_blacklist WHERE jti = ? AND expires_at > ?) AS blacklisted,
+               EXISTS(SELECT 1 FROM session_token_blacklist WHERE jti = ? AND expires_at > ?)
+                 OR EXISTS(SELECT 1 FROM session_family WHERE family_id = ? AND revoked_at IS NOT NULL) AS blacklisted,
                (SELECT logout_before FROM session_logout_gate WHERE user_hash = ? AND expires_at > ? LIMIT 1) AS logout_before",
         )
         .bind(jti)
         .bind(now_rfc3339)
+        .bind(family_id)
         .bind(user_hash)
         .bind(now_rfc3339)
         .fetch_one(&self.pool)
@@ -147,10 +151,12 @@ impl StatsStorage {
             return Ok(false);
         }
 
-        if let Err(e) = self
-            .cleanup_expired_session_records(&now_utc.to_rfc3339())
-            .await
-        {
+        let now = now_utc.to_rfc3339();
+        let cleaned = match self.cleanup_expired_session_records(&now).await {
+            Ok(_) => self.cleanup_expired_refresh_tokens(&now).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = cleaned {
             LAST_SESSION_CLEANUP_TS.store(last_ts, Ordering::Relaxed);
             return Err(e);
         }
@@ -0,0 +1,284 @@
+use sqlx::Row;
+
+use crate::error::AppError;
+
+use super::{
+    NewSessionFamily, RefreshRotation, RefreshTokenLookup, SessionFamilyRow, StatsStorage,
+};
+
+const FAMILY_COLUMNS: &str = "f.family_id, f.user_hash, f.device_label, f.auth_sealed, f.created_at, \
+     f.last_used_at, f.expires_at, f.revoked_at, f.revoke_reason";
+
+fn family_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<SessionFamilyRow, sqlx::Error> {
+    Ok(SessionFamilyRow {
+        family_id: row.try_get("family_id")?,
+        user_hash: row.try_get("user_hash")?,
+        device_label: row.try_get("device_label")?,
+        auth_sealed: row.try_get("auth_sealed")?,
+        created_at: row.try_get("created_at")?,
+        last_used_at: row.try_get("last_used_at")?,
+        expires_at: row.try_get("expires_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+        revoke_reason: row.try_get("revoke_reason")?,
+    })
+}
+
+impl StatsStorage {
+    /// 新建刷新会话族并写入首个刷新令牌（同一事务）
+    pub async fn insert_session_family(&self, new: &NewSessionFamily<'_>) -> Result<(), AppError> {
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("insert session family: {e}"));
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        sqlx::query(
+            "INSERT INTO session_family(family_id,user_hash,device_label,auth_sealed,current_jti,
+               current_jti_expires_at,created_at,last_used_at,expires_at)
+             VALUES(?,?,?,?,?,?,?,?,?)",
+        )
+        .bind(new.family_id)
+        .bind(new.user_hash)
+        .bind(new.device_label)
+        .bind(new.auth_sealed)
+        .bind(new.access_jti)
+        .bind(new.access_expires_at)
+        .bind(new.now)
+        .bind(new.now)
+        .bind(new.expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+        sqlx::query(
+            "INSERT INTO session_refresh_token(token_hash,family_id,issued_at,expires_at)
+             VALUES(?,?,?,?)",
+        )
+        .bind(new.token_hash)
+        .bind(new.family_id)
+        .bind(new.now)
+        .bind(new.expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+        tx.commit().await.map_err(map_err)?;
+        Ok(())
+    }
+
+    /// 按令牌哈希查找刷新令牌（含已使用/已过期的记录，由调用方判定）
+    pub async fn get_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenLookup>, AppError> {
+        let sql = format!(
+            "SELECT {FAMILY_COLUMNS}, t.expires_at AS token_expires_at, t.used_at
+             FROM session_refresh_token t JOIN session_family f ON f.family_id = t.family_id
+             WHERE t.token_hash = ?"
+        );
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("query refresh token: {e}"));
+        let Some(row) = sqlx::query(&sql)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_err)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(RefreshTokenLookup {
+            family: family_from_row(&row).map_err(map_err)?,
+            expires_at: row.try_get("token_expires_at").map_err(map_err)?,
+            used_at: row.try_get("used_at").map_err(map_err)?,
+        }))
+    }
+
+    /// 轮换刷新令牌：标记旧令牌已使用并写入新令牌。
+    ///
+    /// 旧令牌已被并发使用或会话族已吊销时返回 `false`（不写入任何内容）。
+    pub async fn rotate_refresh_token(&self, rot: &RefreshRotation<'_>) -> Result<bool, AppError> {
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("rotate refresh token: {e}"));
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        let marked = sqlx::query(
+            "UPDATE session_refresh_token SET used_at = ?
+             WHERE token_hash = ? AND family_id = ? AND used_at IS NULL",
+        )
+        .bind(rot.now)
+        .bind(rot.old_hash)
+        .bind(rot.family_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?
+        .rows_affected();
+        if marked == 0 {
+            return Ok(false);
+        }
+        let updated = sqlx::query(
+            "UPDATE session_family SET last_used_at = ?, expires_at = ?, current_jti = ?,
+               current_jti_expires_at = ?
+             WHERE family_id = ? AND revoked_at IS NULL",
+        )
+        .bind(rot.now)
+        .bind(rot.expires_at)
+        .bind(rot.access_jti)
+        .bind(rot.access_expires_at)
+        .bind(rot.family_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            "INSERT INTO session_refresh_token(token_hash,family_id,issued_at,expires_at)
+             VALUES(?,?,?,?)",
+        )
+        .bind(rot.new_hash)
+        .bind(rot.family_id)
+        .bind(rot.now)
+        .bind(rot.expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+        tx.commit().await.map_err(map_err)?;
+        Ok(true)
+    }
+
+    /// 吊销会话族：此后其刷新令牌全部失效，当前 access token 同时写入黑名单。
+    ///
+    /// 已吊销的会话族保持首次吊销的时间与原因；返回本次是否发生了吊销。
+    pub async fn revoke_session_family(
+        &self,
+        family_id: &str,
+        reason: &str,
+        now_rfc3339: &str,
+    ) -> Result<bool, AppError> {
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("revoke session family: {e}"));
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        let revoked = sqlx::query(
+            "UPDATE session_family SET revoked_at = ?, revoke_reason = ?
+             WHERE family_id = ? AND revoked_at IS NULL",
+        )
+        .bind(now_rfc3339)
+        .bind(reason)
+        .bind(family_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?
+        .rows_affected();
+        sqlx::query(
+            "INSERT INTO session_token_blacklist(jti,expires_at,created_at)
+             SELECT current_jti, current_jti_expires_at, ? FROM session_family
+             WHERE family_id = ? AND current_jti IS NOT NULL AND current_jti_expires_at > ?
+             ON CONFLICT(jti) DO NOTHING",
+        )
+        .bind(now_rfc3339)
+        .bind(family_id)
+        .bind(now_rfc3339)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+        tx.commit().await.map_err(map_err)?;
+        Ok(revoked > 0)
+    }
+
+    /// 吊销某用户全部未吊销的会话族（logout scope=all 使用），返回吊销数量
+    pub async fn revoke_user_session_families(
+        &self,
+        user_hash: &str,
+        reason: &str,
+        now_rfc3339: &str,
+    ) -> Result<u64, AppError> {
+        let map_err =
+            |e: sqlx::Error| AppError::Internal(format!("revoke user session families: {e}"));
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        sqlx::query(
+            "INSERT INTO session_token_blacklist(jti,expires_at,created_at)
+             SELECT current_jti, current_jti_expires_at, ? FROM session_family
+             WHERE user_hash = ? AND revoked_at IS NULL
+               AND current_jti IS NOT NULL AND current_jti_expires_at > ?
+             ON CONFLICT(jti) DO NOTHING",
+        )
+        .bind(now_rfc3339)
+        .bind(user_hash)
+        .bind(now_rfc3339)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+        let revoked = sqlx::query(
+            "UPDATE session_family SET revoked_at = ?, revoke_reason = ?
+             WHERE user_hash = ? AND revoked_at IS NULL",
+        )
+        .bind(now_rfc3339)
+        .bind(reason)
+        .bind(user_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?
+        .rows_affected();
+        tx.commit().await.map_err(map_err)?;
+        Ok(revoked)
+    }
+
+    pub async fn get_session_family(
+        &self,
+        family_id: &str,
+    ) -> Result<Option<SessionFamilyRow>, AppError> {
+        let sql = format!("SELECT {FAMILY_COLUMNS} FROM session_family f WHERE f.family_id = ?");
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("query session family: {e}"));
+        let row = sqlx::query(&sql)
+            .bind(family_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_err)?;
+        row.as_ref()
+            .map(family_from_row)
+            .transpose()
+            .map_err(map_err)
+    }
+
+    /// 列出某用户未吊销且未过期的会话族（最近使用在前）
+    pub async fn list_active_session_families(
+        &self,
+        user_hash: &str,
+        now_rfc3339: &str,
+    ) -> Result<Vec<SessionFamilyRow>, AppError> {
+        let sql = format!(
+            "SELECT {FAMILY_COLUMNS} FROM session_family f
+             WHERE f.user_hash = ? AND f.revoked_at IS NULL AND f.expires_at > ?
+             ORDER BY f.last_used_at DESC, f.family_id"
+        );
+        let map_err = |e: sqlx::Error| AppError::Internal(format!("list session families: {e}"));
+        let rows = sqlx::query(&sql)
+            .bind(user_hash)
+            .bind(now_rfc3339)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(map_err)?;
+        rows.iter()
+            .map(family_from_row)
+            .collect::<Result<_, _>>()
+            .map_err(map_err)
+    }
+
+    /// 清理过期的刷新令牌与会话族。
+    ///
+    /// 已使用的令牌在过期前保留，用于重放检测；会话族过期后其令牌必然也已过期。
+    pub async fn cleanup_expired_refresh_tokens(
+        &self,
+        now_rfc3339: &str,
+    ) -> Result<(u64, u64), AppError> {
+        let tokens_deleted = sqlx::query("DELETE FROM session_refresh_token WHERE expires_at <= ?")
+            .bind(now_rfc3339)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("cleanup refresh tokens: {e}")))?
+            .rows_affected();
+
+        let families_deleted = sqlx::query(
+            "DELETE FROM session_family WHERE expires_at <= ?
+               AND NOT EXISTS(SELECT 1 FROM session_refresh_token t WHERE t.family_id = session_family.family_id)",
+        )
+        .bind(now_rfc3339)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("cleanup session families: {e}")))?
+        .rows_affected();
+
+        Ok((tokens_deleted, families_deleted))
+    }
+}
@@ -4,11 +4,25 @@ use sqlx::Row;
 
 use crate::error::AppError;
 
-use super::{RksHistoryCursor, RksHistoryEntry, RksHistoryPage, StatsStorage, SubmissionRecord};
+use super::{
+    LeaderboardChartDetails, RksHistoryCursor, RksHistoryEntry, RksHistoryPage, StatsStorage,
+    SubmissionRecord,
+};
 
 // 归一化浮点噪声：避免把 1e-15 量级差值当成“RKS 变化”暴露给客户端。
 const RKS_JUMP_EPS: f64 = 1e-9;
 const PEAK_RKS_SQL: &str = "SELECT total_rks as peak FROM save_submissions WHERE user_hash = ? ORDER BY total_rks DESC LIMIT 1";
+// 按 user_hash 顺序遍历排行榜用户，并取其最近一次带谱面快照的提交（走 idx_submissions_user_created_id）
+const LEADERBOARD_CHART_DETAILS_SQL: &str = "SELECT lb.user_hash, lb.total_rks, lb.is_hidden,
+        (SELECT s.details_json FROM save_submissions s
+          WHERE s.user_hash = lb.user_hash AND s.details_json IS NOT NULL
+          ORDER BY s.created_at DESC, s.id DESC LIMIT 1) AS details_json,
+        (SELECT MAX(s.id) FROM save_submissions s WHERE s.user_hash = lb.user_hash)
+          AS latest_submission_id
+     FROM leaderboard_rks lb
+     WHERE lb.user_hash > ?
+     ORDER BY lb.user_hash ASC
+     LIMIT ?";
 
 #[cfg(test)]
 mod tests {
@@ -49,9 +63,10 @@ impl StatsStorage {
             client_ip_hash,
             details_json,
             suspicion_score,
+            region,
             now_rfc3339,
         } = record;
-        sqlx::query("INSERT INTO save_submissions(user_hash,total_rks,acc_stats,rks_jump,route,client_ip_hash,details_json,suspicion_score,created_at) VALUES(?,?,?,?,?,?,?,?,?)")
+        sqlx::query("INSERT INTO save_submissions(user_hash,total_rks,acc_stats,rks_jump,route,client_ip_hash,details_json,suspicion_score,region,created_at) VALUES(?,?,?,?,?,?,?,?,?,?)")
             .bind(user_hash)
             .bind(total_rks)
             .bind(Option::<String>::None)
@@ -60,6 +75,7 @@ impl StatsStorage {
             .bind(client_ip_hash)
             .bind(details_json)
             .bind(suspicion_score)
+            .bind(region.as_str())
             .bind(now_rfc3339)
             .execute(&self.pool)
             .await
@@ -67,6 +83,50 @@ impl StatsStorage {
         Ok(())
     }
 
+    /// 统计 `since` 之后在同一 IP 上提交过存档的其他账号数。
+    pub async fn count_other_accounts_on_ip(
+        &self,
+        client_ip_hash: &str,
+        user_hash: &str,
+        since_rfc3339: &str,
+    ) -> Result<i64, AppError> {
+        let row = sqlx::query(
+            "SELECT COUNT(DISTINCT user_hash) AS c FROM save_submissions
+             WHERE client_ip_hash = ? AND created_at >= ? AND user_hash <> ?",
+        )
+        .bind(client_ip_hash)
+        .bind(since_rfc3339)
+        .bind(user_hash)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("count accounts on ip: {e}")))?;
+        Ok(row.try_get("c").unwrap_or(0))
+    }
+
+    /// 按 user_hash 升序分页读取排行榜用户的最近谱面快照；`after_user` 为上一页最后一个用户。
+    pub async fn query_leaderboard_chart_details(
+        &self,
+        after_user: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardChartDetails>, AppError> {
+        let rows = sqlx::query(LEADERBOARD_CHART_DETAILS_SQL)
+            .bind(after_user.unwrap_or(""))
+            .bind(limit.clamp(1, 1000))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("query leaderboard chart details: {e}")))?;
+        Ok(rows
+            .into_iter()
+            .map(|row| LeaderboardChartDetails {
+                user_hash: row.try_get("user_hash").unwrap_or_default(),
+                total_rks: row.try_get("total_rks").unwrap_or(0.0),
+                is_hidden: row.try_get::<i64, _>("is_hidden").unwrap_or(0) != 0,
+                details_json: row.try_get("details_json").ok().flatten(),
+                latest_submission_id: row.try_get("latest_submission_id").ok().flatten(),
+            })
+            .collect())
+    }
+
     /// 查询用户 RKS 历史记录
     ///
     /// 返回 (历史记录列表, 总记录数)
@@ -0,0 +1,50 @@
+use sqlx::Row;
+
+use crate::error::AppError;
+
+use super::{StatsStorage, TapTapProfileRow};
+
+impl StatsStorage {
+    /// 写入/覆盖某用户的 TapTap 基础资料
+    pub async fn upsert_taptap_profile(
+        &self,
+        user_hash: &str,
+        row: &TapTapProfileRow,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO taptap_profile(user_hash,nickname,avatar_url,taptap_version,fetched_at_ms)
+             VALUES(?,?,?,?,?)
+             ON CONFLICT(user_hash) DO UPDATE SET
+               nickname=excluded.nickname, avatar_url=excluded.avatar_url,
+               taptap_version=excluded.taptap_version, fetched_at_ms=excluded.fetched_at_ms",
+        )
+        .bind(user_hash)
+        .bind(row.nickname.as_deref())
+        .bind(row.avatar_url.as_deref())
+        .bind(row.taptap_version.as_deref())
+        .bind(row.fetched_at_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("upsert taptap profile: {e}")))?;
+        Ok(())
+    }
+
+    pub async fn get_taptap_profile(
+        &self,
+        user_hash: &str,
+    ) -> Result<Option<TapTapProfileRow>, AppError> {
+        let row = sqlx::query(
+            "SELECT nickname,avatar_url,taptap_version,fetched_at_ms FROM taptap_profile WHERE user_hash = ?",
+        )
+        .bind(user_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("get taptap profile: {e}")))?;
+        Ok(row.map(|r| TapTapProfileRow {
+            nickname: r.try_get("nickname").unwrap_or(None),
+            avatar_url: r.try_get("avatar_url").unwrap_or(None),
+            taptap_version: r.try_get("taptap_version").unwrap_or(None),
+            fetched_at_ms: r.try_get("fetched_at_ms").unwrap_or(0),
+        }))
+    }
+}
@@ -0,0 +1,379 @@
+//! 游戏数据（定数表 + 歌曲目录）快照与热更新
+//!
+//! 定数表与歌曲目录以不可变快照 [`GameData`] 的形式持有，由 [`GameDataStore`] 原子替换：
+//! 请求在入口处取一次快照（`Arc` 克隆）并在整个处理过程中使用，重载期间已在途的请求
+//! 继续使用旧版本。每次替换递增 `version`，缓存 key 带上版本号，旧定数算出的结果不会被命中。
+
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constant_tables::ConstantTableSet;
+use crate::error::AppError;
+use crate::features::song::models::SongCatalog;
+use crate::shutdown::ShutdownHandle;
+use crate::startup::chart_loader::{ChartConstantsMap, load_chart_constants};
+use crate::startup::{remote_info, song_loader};
+use crate::state::AppState;
+
+/// 数据来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GameDataSource {
+    /// 本地 info 目录
+    Local,
+    /// 远端 `resources.info_base_url`
+    Remote,
+}
+
+/// 某一版本的定数表与歌曲目录（不可变）
+pub struct GameData {
+    pub chart_constants: Arc<ChartConstantsMap>,
+    pub song_catalog: Arc<SongCatalog>,
+    /// 单调递增的数据版本（进程内，从 1 开始）
+    pub version: u64,
+    pub source: GameDataSource,
+    /// 加载时间（RFC3339）
+    pub loaded_at: String,
+}
+
+/// 当前游戏数据的持有者（可克隆，内部共享）
+#[derive(Clone)]
+pub struct GameDataStore {
+    current: Arc<RwLock<Arc<GameData>>>,
+    /// 归档定数表（独立于线上数据替换，见 [`crate::constant_tables`]）
+    constant_tables: Arc<RwLock<Arc<ConstantTableSet>>>,
+}
+
+impl GameDataStore {
+    /// 以初始数据构建（版本号为 1，来源视为本地）
+    #[must_use]
+    pub fn new(chart_constants: Arc<ChartConstantsMap>, song_catalog: Arc<SongCatalog>) -> Self {
+        Self::with_source(chart_constants, song_catalog, GameDataSource::Local)
+    }
+
+    #[must_use]
+    pub fn with_source(
+        chart_constants: Arc<ChartConstantsMap>,
+        song_catalog: Arc<SongCatalog>,
+        source: GameDataSource,
+    ) -> Self {
+        let data = GameData {
+            chart_constants,
+            song_catalog,
+            version: 1,
+            source,
+            loaded_at: chrono::Utc::now().to_rfc3339(),
+        };
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(data))),
+            constant_tables: Arc::new(RwLock::new(Arc::new(ConstantTableSet::default()))),
+        }
+    }
+
+    /// 取当前快照；调用方应在一次请求内复用同一快照。
+    pub fn snapshot(&self) -> Arc<GameData> {
+        self.current
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// 取归档定数表快照。
+    pub fn constant_tables(&self) -> Arc<ConstantTableSet> {
+        self.constant_tables
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// 替换归档定数表（不影响线上数据版本号）。
+    pub fn replace_constant_tables(&self, tables: ConstantTableSet) {
+        *self
+            .constant_tables
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Arc::new(tables);
+    }
+
+    /// 原子替换为新数据，返回 `(旧快照, 新快照)`。
+    pub fn replace(
+        &self,
+        chart_constants: ChartConstantsMap,
+        song_catalog: SongCatalog,
+        source: GameDataSource,
+    ) -> (Arc<GameData>, Arc<GameData>) {
+        let mut guard = self
+            .current
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let next = Arc::new(GameData {
+            chart_constants: Arc::new(chart_constants),
+            song_catalog: Arc::new(song_catalog),
+            version: guard.version + 1,
+            source,
+            loaded_at: chrono::Utc::now().to_rfc3339(),
+        });
+        let prev = std::mem::replace(&mut *guard, next.clone());
+        (prev, next)
+    }
+}
+
+/// 校验结果摘要
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InfoCheck {
+    pub songs: usize,
+    pub charts: usize,
+    /// difficulty.csv 中存在但 info.csv 中缺失的曲目数
+    pub charts_without_song: usize,
+}
+
+/// 校验新解析出的定数表与歌曲目录可以替换线上数据。
+///
+/// - 两者均不能为空；
+/// - 两者的曲目 ID 必须有交集（防止误把不同来源/截断的文件组合在一起）。
+///
+/// 仅部分曲目缺失时只记录警告，不阻断替换。
+pub fn validate_info(
+    chart_constants: &ChartConstantsMap,
+    song_catalog: &SongCatalog,
+) -> Result<InfoCheck, AppError> {
+    if chart_constants.is_empty() {
+        return Err(AppError::Validation("difficulty.csv 未包含任何定数".into()));
+    }
+    if song_catalog.by_id.is_empty() {
+        return Err(AppError::Validation("info.csv 未包含任何曲目".into()));
+    }
+    let charts_without_song = chart_constants
+        .keys()
+        .filter(|id| !song_catalog.by_id.contains_key(*id))
+        .count();
+    if charts_without_song == chart_constants.len() {
+        return Err(AppError::Validation(
+            "difficulty.csv 与 info.csv 的曲目 ID 无交集".into(),
+        ));
+    }
+    if charts_without_song > 0 {
+        tracing::warn!("difficulty.csv 中有 {charts_without_song} 首曲目在 info.csv 中缺失");
+    }
+    Ok(InfoCheck {
+        songs: song_catalog.by_id.len(),
+        charts: chart_constants.len(),
+        charts_without_song,
+    })
+}
+
+/// 从本地 info 目录读取并解析 difficulty.csv / info.csv / nicklist.yaml
+pub fn load_local_info(info_dir: &Path) -> Result<(ChartConstantsMap, SongCatalog), AppError> {
+    let chart_constants = load_chart_constants(&info_dir.join("difficulty.csv"))?;
+    let song_catalog = song_loader::load_song_catalog(info_dir)?;
+    Ok((chart_constants, song_catalog))
+}
+
+/// 一次重载的结果
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadOutcome {
+    /// 是否发生了替换（远端无更新时为 false）
+    pub reloaded: bool,
+    /// 当前生效的数据版本
+    pub version: u64,
+    /// 替换前的数据版本
+    pub previous_version: u64,
+    pub source: GameDataSource,
+    /// 当前生效数据的曲目数
+    pub songs: usize,
+    /// 当前生效数据的定数条目数
+    pub charts: usize,
+    /// 当前生效数据的加载时间（RFC3339）
+    pub loaded_at: String,
+}
+
+impl ReloadOutcome {
+    fn unchanged(current: &GameData) -> Self {
+        Self {
+            reloaded: false,
+            version: current.version,
+            previous_version: current.version,
+            source: current.source,
+            songs: current.song_catalog.by_id.len(),
+            charts: current.chart_constants.len(),
+            loaded_at: current.loaded_at.clone(),
+        }
+    }
+}
+
+/// 校验并替换线上数据，同时清空按旧定数计算的图片/存档缓存。
+pub fn apply_game_data(
+    state: &AppState,
+    chart_constants: ChartConstantsMap,
+    song_catalog: SongCatalog,
+    source: GameDataSource,
+) -> Result<ReloadOutcome, AppError> {
+    let check = validate_info(&chart_constants, &song_catalog)?;
+    let (prev, next) = state
+        .game_data
+        .replace(chart_constants, song_catalog, source);
+
+    // 缓存 key 已带数据版本，这里只是尽快释放旧版本占用的内存
+    state.bn_image_cache.invalidate_all();
+    state.song_image_cache.invalidate_all();
+    crate::save_contract::invalidate_save_cache();
+
+    tracing::info!(
+        "游戏数据已替换: v{} -> v{} ({:?}, songs={}, charts={})",
+        prev.version,
+        next.version,
+        source,
+        check.songs,
+        check.charts
+    );
+    Ok(ReloadOutcome {
+        reloaded: true,
+        version: next.version,
+        previous_version: prev.version,
+        source,
+        songs: check.songs,
+        charts: check.charts,
+        loaded_at: next.loaded_at.clone(),
+    })
+}
+
+/// 从本地 info 目录重载
+///
+/// 远端数据不会落盘到 info 目录，当前数据来自远端时本地文件通常更旧：
+/// 除非 `force=true`，否则拒绝以免静默回退线上数据。
+pub async fn reload_from_local(
+    state: &AppState,
+    info_dir: &Path,
+    force: bool,
+) -> Result<ReloadOutcome, AppError> {
+    let current = state.game_data();
+    if !force && current.source == GameDataSource::Remote {
+        return Err(AppError::Conflict(format!(
+            "当前数据 v{} 来自远端，本地 info 目录可能更旧；确需回退请携带 force=true",
+            current.version
+        )));
+    }
+    let dir = info_dir.to_path_buf();
+    let (chart_constants, song_catalog) =
+        tokio::task::spawn_blocking(move || load_local_info(&dir))
+            .await
+            .map_err(|e| AppError::Internal(format!("info 加载任务失败: {e}")))??;
+    apply_game_data(state, chart_constants, song_catalog, GameDataSource::Local)
+}
+
+/// 从远端重载；`force=false` 时远端 ETag 未变化则不替换。
+pub async fn reload_from_remote(
+    state: &AppState,
+    base_url: &str,
+    info_dir: &Path,
+    force: bool,
+) -> Result<ReloadOutcome, AppError> {
+    match remote_info::fetch_remote_info(base_url, info_dir, force).await? {
+        Some(remote) => apply_game_data(
+            state,
+            remote.chart_constants,
+            remote.song_catalog,
+            GameDataSource::Remote,
+        ),
+        None => Ok(ReloadOutcome::unchanged(&state.game_data())),
+    }
+}
+
+/// 周期性轮询远端 ETag，有更新时自动重载（首次 tick 跳过，启动时已加载过）。
+///
+/// 进程开始优雅退出时轮询随之结束（包括进行中的下载）。
+#[must_use]
+pub fn spawn_remote_poll(
+    state: AppState,
+    base_url: String,
+    info_dir: std::path::PathBuf,
+    interval: Duration,
+    mut shutdown: ShutdownHandle,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        if shutdown.is_shutting_down() {
+            return;
+        }
+        let poll = async {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match reload_from_remote(&state, &base_url, &info_dir, false).await {
+                    Ok(outcome) if outcome.reloaded => {
+                        tracing::info!("远端 info 轮询：已切换至 v{}", outcome.version);
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("远端 info 轮询重载失败，继续使用当前数据: {e}"),
+                }
+            }
+        };
+        tokio::select! {
+            () = poll => {}
+            _ = shutdown.wait() => tracing::info!("进程退出，远端 info 轮询已停止"),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::startup::chart_loader::ChartConstants;
+    use crate::startup::song_loader::parse_song_catalog;
+
+    fn catalog(ids: &[&str]) -> SongCatalog {
+        use std::fmt::Write;
+
+        let mut csv = String::from("id,song,composer,illustrator,EZ,HD,IN,AT\n");
+        for id in ids {
+            writeln!(csv, "{id},Song {id},c,i,1,2,3,").unwrap();
+        }
+        parse_song_catalog(csv.as_bytes(), "{}".as_bytes()).expect("catalog")
+    }
+
+    fn constants(ids: &[&str]) -> ChartConstantsMap {
+        ids.iter()
+            .map(|id| {
+                (
+                    (*id).to_string(),
+                    ChartConstants {
+                        ez: Some(1.0),
+                        hd: Some(2.0),
+                        in_level: Some(3.0),
+                        at: None,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replace_bumps_version_and_keeps_old_snapshot_alive() {
+        let store = GameDataStore::new(Arc::new(constants(&["a"])), Arc::new(catalog(&["a"])));
+        let in_flight = store.snapshot();
+        let (prev, next) = store.replace(
+            constants(&["a", "b"]),
+            catalog(&["a", "b"]),
+            GameDataSource::Remote,
+        );
+        assert_eq!(prev.version, 1);
+        assert_eq!(next.version, 2);
+        assert_eq!(in_flight.chart_constants.len(), 1);
+        assert_eq!(store.snapshot().chart_constants.len(), 2);
+        assert_eq!(store.snapshot().source, GameDataSource::Remote);
+    }
+
+    #[test]
+    fn validate_rejects_empty_or_disjoint_tables() {
+        assert!(validate_info(&ChartConstantsMap::new(), &catalog(&["a"])).is_err());
+        assert!(validate_info(&constants(&["a"]), &catalog(&[])).is_err());
+        assert!(validate_info(&constants(&["x"]), &catalog(&["a"])).is_err());
+
+        let check = validate_info(&constants(&["a", "x"]), &catalog(&["a"])).expect("valid");
+        assert_eq!(check.charts_without_song, 1);
+    }
+}
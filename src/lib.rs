@@ -30,6 +30,12 @@ pub mod features;
 /// 应用状态聚合模块
 pub mod state;
 
+/// 定数表与歌曲目录快照（热更新）
+pub mod game_data;
+
+/// 按游戏版本归档的定数表
+pub mod constant_tables;
+
 /// 优雅退出管理模块
 pub mod shutdown;
 
@@ -42,6 +48,9 @@ pub mod http;
 /// 请求 request_id 中间件与上下文工具
 pub mod request_id;
 
+/// 实时推送（SSE）连接管理
+pub mod realtime;
+
 #[path = "contracts/auth_contract.rs"]
 pub mod auth_contract;
 #[path = "api/auth_qrcode_api.rs"]
@@ -13,6 +13,7 @@ use axum::body::Bytes;
 use moka::future::Cache;
 use phi_backend::features::auth::client::TapTapClient;
 use phi_backend::features::stats;
+use phi_backend::game_data::{GameDataSource, GameDataStore};
 use phi_backend::router::build_app;
 use phi_backend::startup::chart_loader::{ChartConstantsMap, load_chart_constants};
 use phi_backend::startup::{run_startup_checks, song_loader};
@@ -94,6 +95,7 @@ async fn main() {
     // 加载 difficulty.csv
     let info_dir = config.info_path();
     let csv_path = info_dir.join("difficulty.csv");
+    let mut info_source = GameDataSource::Local;
     let mut chart_map: ChartConstantsMap = load_chart_constants(&csv_path).unwrap_or_else(|e| {
         tracing::error!("Failed to load difficulty.csv: {}", e);
         panic!("missing or invalid difficulty.csv");
@@ -112,6 +114,7 @@ async fn main() {
                 tracing::info!("远端 info 版本更新，已切换至远端数据");
                 chart_map = remote.chart_constants;
                 song_catalog = remote.song_catalog;
+                info_source = GameDataSource::Remote;
             }
             Ok(None) => {
                 tracing::info!("远端 info 无更新或不可达，继续使用本地数据");
@@ -130,9 +133,6 @@ async fn main() {
             std::process::exit(1);
         }
     };
-    let qrcode_service =
-        Arc::new(phi_backend::features::auth::qrcode_service::QrCodeService::new());
-
     let (stats_handle_opt, stats_storage_opt) = if config.stats.enabled {
         match stats::init_stats(config).await {
             Ok((h, storage)) => (Some(h), Some(storage)),
@@ -145,6 +145,39 @@ async fn main() {
         (None, None)
     };
 
+    // 二维码登录状态：sqlite 模式复用统计库，供多实例/重启后共享
+    let qrcode_service = {
+        use phi_backend::features::auth::{
+            bearer::session_auth_sealing_configured, qrcode_service::QrCodeService,
+            qrcode_store::SqliteQrCodeStore,
+        };
+        match (
+            config.qrcode_login.store.as_str(),
+            stats_storage_opt.as_ref(),
+        ) {
+            ("sqlite", Some(_)) if !session_auth_sealing_configured() => {
+                tracing::warn!(
+                    "qrcode_login.store=sqlite 需要会话凭证加密密钥（session.jwt_secret），当前未配置，回退到进程内存储"
+                );
+                Arc::new(QrCodeService::new())
+            }
+            ("sqlite", Some(storage)) => Arc::new(QrCodeService::with_store(Arc::new(
+                SqliteQrCodeStore::new(storage.clone()),
+            ))),
+            ("sqlite", None) => {
+                tracing::warn!(
+                    "qrcode_login.store=sqlite 需要统计库，当前不可用，回退到进程内存储"
+                );
+                Arc::new(QrCodeService::new())
+            }
+            ("memory", _) => Arc::new(QrCodeService::new()),
+            (other, _) => {
+                tracing::warn!("未知的 qrcode_login.store：{other}，使用进程内存储");
+                Arc::new(QrCodeService::new())
+            }
+        }
+    };
+
     if config.open_platform.enabled {
         let op_storage = match phi_backend::features::open_platform::storage::OpenPlatformStorage::connect_sqlite(
             &config.open_platform.sqlite_path,
@@ -196,8 +229,11 @@ async fn main() {
     };
 
     let app_state = AppState {
-        chart_constants: Arc::new(chart_map),
-        song_catalog: Arc::new(song_catalog),
+        game_data: GameDataStore::with_source(
+            Arc::new(chart_map),
+            Arc::new(song_catalog),
+            info_source,
+        ),
         taptap_client,
         qrcode_service,
         stats: stats_handle_opt.clone(),
@@ -209,6 +245,35 @@ async fn main() {
         bn_image_cache,
         song_image_cache,
     };
+    app_state.game_data.replace_constant_tables(
+        phi_backend::constant_tables::load_constant_tables(
+            &info_dir,
+            &config.resources.constant_tables,
+        ),
+    );
+
+    // 上次进程退出时未完成的排行榜重算任务自动续跑
+    phi_backend::features::leaderboard::recompute::spawn_resume_if_interrupted(app_state.clone());
+
+    // 远端 info 轮询（热更新定数表与歌曲目录），收到退出信号后自行结束
+    let remote_poll = if let Some(base_url) = config.resources.info_base_url.clone()
+        && config.resources.info_poll_interval_secs > 0
+    {
+        let interval = Duration::from_secs(config.resources.info_poll_interval_secs);
+        tracing::info!("远端 info 轮询已启用，间隔 {}s", interval.as_secs());
+        Some(phi_backend::game_data::spawn_remote_poll(
+            app_state.clone(),
+            base_url,
+            info_dir.clone(),
+            interval,
+            phi_backend::ShutdownHandle::new(&shutdown_manager),
+        ))
+    } else {
+        None
+    };
+
+    // SSE 推送流随优雅退出一并结束
+    phi_backend::realtime::init_global(&shutdown_manager);
 
     // 构建路由（含中间件）
     let app = build_app(app_state, config, stats_handle_opt.as_ref());
@@ -252,6 +317,12 @@ async fn main() {
         if let Ok(()) = tokio::time::timeout(shutdown_timeout, async move {
             tracing::info!("优雅退出超时时间: {}秒", shutdown_config.timeout_secs);
 
+            if let Some(poll) = remote_poll
+                && let Err(e) = poll.await
+            {
+                tracing::warn!("远端 info 轮询任务异常结束: {}", e);
+            }
+
             if let Some(stats_handle) = stats_handle_for_cleanup.clone() {
                 tracing::info!("开始关闭统计服务...");
                 if let Err(e) = stats_handle
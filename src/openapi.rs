@@ -50,12 +50,19 @@ impl Modify for ApiServers {
     paths(
         crate::features::health::handler::health_check,
         crate::features::save::handler::get_save_data,
+        crate::features::save::handler::post_save_upload,
+        crate::features::save::handler::post_save_diff,
+        crate::features::save::handler::post_save_diff_upload,
         crate::features::auth::handler::qrcode::post_qrcode,
         crate::features::auth::handler::qrcode::get_qrcode_status,
+        crate::features::auth::handler::qrcode_stream::get_qrcode_events,
         crate::features::auth::handler::user_id::post_user_id,
+        crate::features::auth::handler::me::get_auth_me,
         crate::features::auth::handler::session::post_session_exchange,
         crate::features::auth::handler::session::post_session_refresh,
         crate::features::auth::handler::session::post_session_logout,
+        crate::features::auth::handler::sessions::get_sessions,
+        crate::features::auth::handler::sessions::post_revoke_session,
         crate::features::open_platform::auth::handlers::get_github_login,
         crate::features::open_platform::auth::handlers::get_github_callback,
         crate::features::open_platform::auth::handlers::get_me,
@@ -70,16 +77,26 @@ impl Modify for ApiServers {
         crate::features::open_platform::open_api::auth::open_auth_qrcode,
         crate::features::open_platform::open_api::auth::open_auth_qrcode_status,
         crate::features::open_platform::open_api::save::open_save_data,
+        crate::features::open_platform::open_api::save::open_save_upload,
         crate::features::open_platform::open_api::image::open_image_bn,
         crate::features::open_platform::open_api::image::open_image_song,
+        crate::features::open_platform::open_api::image::open_image_leaderboard,
         crate::features::open_platform::open_api::search::open_search_songs,
         crate::features::open_platform::open_api::leaderboard::open_get_leaderboard_top,
         crate::features::open_platform::open_api::leaderboard::open_get_leaderboard_by_rank,
+        crate::features::open_platform::open_api::leaderboard::open_get_chart_leaderboard_top,
+        crate::features::open_platform::open_api::leaderboard::open_get_rks_gainers,
+        crate::features::open_platform::open_api::leaderboard::open_get_rank_history,
+        crate::features::open_platform::open_api::leaderboard::open_get_rks_distribution,
         crate::features::open_platform::open_api::rks::open_post_rks_history,
         crate::features::song::handler::search_songs,
+        crate::features::song::handler::post_admin_info_reload,
         crate::features::image::handler::bn::render_bn,
         crate::features::image::handler::song::render_song,
         crate::features::image::handler::user_bn::render_bn_user,
+        crate::features::image::handler::leaderboard::render_leaderboard,
+        crate::features::image::handler::login_qrcode::render_login_qrcode,
+        crate::features::image::handler::rks_history::render_rks_history,
         crate::features::stats::handler::get_daily_stats,
         crate::features::stats::handler::get_daily_features,
         crate::features::stats::handler::get_daily_dau,
@@ -90,16 +107,42 @@ impl Modify for ApiServers {
         crate::features::leaderboard::handler::ranking::get_top,
         crate::features::leaderboard::handler::ranking::get_by_rank,
         crate::features::leaderboard::handler::ranking::post_me,
+        crate::features::leaderboard::handler::history::post_me_rank_history,
+        crate::features::leaderboard::handler::history::get_rks_gainers,
+        crate::features::leaderboard::handler::distribution::get_rks_distribution,
+        crate::features::leaderboard::handler::stream::get_rks_stream,
+        crate::features::leaderboard::handler::chart::get_chart_top,
+        crate::features::leaderboard::handler::group::post_create_group,
+        crate::features::leaderboard::handler::group::post_my_groups,
+        crate::features::leaderboard::handler::group::post_join_group,
+        crate::features::leaderboard::handler::group::post_group_members,
+        crate::features::leaderboard::handler::group::post_leave_group,
+        crate::features::leaderboard::handler::group::post_kick_group_member,
+        crate::features::leaderboard::handler::group::post_reset_group_invite,
+        crate::features::leaderboard::handler::group::post_disband_group,
+        crate::features::leaderboard::handler::group::post_group_top,
+        crate::features::leaderboard::handler::group::post_group_by_rank,
         crate::features::leaderboard::handler::profile::put_alias,
         crate::features::leaderboard::handler::profile::put_profile,
         crate::features::leaderboard::handler::profile::get_public_profile,
+        crate::features::leaderboard::handler::history::get_public_rank_history,
         crate::features::leaderboard::handler::admin::get_suspicious,
         crate::features::leaderboard::handler::admin::get_admin_leaderboard_users,
         crate::features::leaderboard::handler::admin::post_resolve,
         crate::features::leaderboard::handler::admin::get_admin_user_status,
         crate::features::leaderboard::handler::admin::post_admin_user_status,
+        crate::features::leaderboard::handler::admin::get_admin_user_flags,
         crate::features::leaderboard::handler::admin::post_alias_force,
+        crate::features::leaderboard::handler::admin::get_recompute_progress,
+        crate::features::leaderboard::handler::admin::post_recompute_start,
+        crate::features::leaderboard::handler::admin::post_recompute_cancel,
         crate::features::rks::handler::post_rks_history,
+        crate::features::rks::handler::post_rks_chart_history,
+        crate::features::rks::handler::post_rks_recent_pbs,
+        crate::features::rks::handler::post_rks_simulate,
+        crate::features::rks::handler::post_rks_plan,
+        crate::features::rks::handler::get_rks_constants,
+        crate::features::rks::handler::get_admin_constants_diff,
     ),
     modifiers(&AdminTokenSecurity, &ApiServers),
     tags(
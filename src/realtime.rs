@@ -0,0 +1,116 @@
+//! 实时推送（SSE）连接管理
+//!
+//! 所有 SSE 端点共用：全局并发连接上限、心跳间隔，以及与 [`crate::shutdown`] 的联动——
+//! 进程开始优雅退出时，各推送流发送 `shutdown` 事件后立即结束，
+//! 避免长连接拖住 HTTP 服务器的 graceful shutdown。
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use axum::response::sse::{Event, KeepAlive};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::shutdown::{ShutdownHandle, ShutdownManager};
+
+struct RealtimeHub {
+    permits: Arc<Semaphore>,
+    shutdown: Option<ShutdownHandle>,
+}
+
+static HUB: OnceLock<RealtimeHub> = OnceLock::new();
+
+fn build_hub(shutdown: Option<ShutdownHandle>) -> RealtimeHub {
+    let max = AppConfig::global().realtime.max_connections.max(1);
+    RealtimeHub {
+        permits: Arc::new(Semaphore::new(max)),
+        shutdown,
+    }
+}
+
+/// 绑定进程的退出管理器；应在构建路由前调用一次。
+///
+/// 未初始化时（如测试）推送流不会因退出而提前结束，其余行为相同。
+pub fn init_global(shutdown: &ShutdownManager) {
+    if HUB
+        .set(build_hub(Some(ShutdownHandle::new(shutdown))))
+        .is_err()
+    {
+        tracing::warn!("实时推送模块重复初始化，已忽略");
+    }
+}
+
+fn hub() -> &'static RealtimeHub {
+    HUB.get_or_init(|| build_hub(None))
+}
+
+/// 单条 SSE 连接的占位：持有连接许可，并可等待进程退出信号。
+pub struct StreamGuard {
+    _permit: OwnedSemaphorePermit,
+    shutdown: Option<ShutdownHandle>,
+}
+
+impl StreamGuard {
+    /// 进程开始优雅退出时返回；未绑定退出管理器时永不返回。
+    pub async fn closed(&mut self) {
+        let Some(handle) = self.shutdown.as_mut() else {
+            return std::future::pending().await;
+        };
+        if handle.is_shutting_down() {
+            return;
+        }
+        let _ = handle.wait().await;
+    }
+}
+
+/// 申请一条 SSE 连接；功能关闭或连接数已满时返回 403。
+pub fn acquire_stream() -> Result<StreamGuard, AppError> {
+    if !AppConfig::global().realtime.enabled {
+        return Err(AppError::Forbidden("实时推送未启用".into()));
+    }
+    let hub = hub();
+    if hub
+        .shutdown
+        .as_ref()
+        .is_some_and(ShutdownHandle::is_shutting_down)
+    {
+        return Err(AppError::Forbidden("服务正在关闭".into()));
+    }
+    let permit = hub
+        .permits
+        .clone()
+        .try_acquire_owned()
+        .map_err(|_| AppError::Forbidden("实时推送连接数已达上限，请稍后重试".into()))?;
+    Ok(StreamGuard {
+        _permit: permit,
+        // clone 会重新订阅退出广播，保证每条连接都能收到信号
+        shutdown: hub.shutdown.clone(),
+    })
+}
+
+/// 心跳配置（SSE 注释帧）
+pub fn keep_alive() -> KeepAlive {
+    let secs = AppConfig::global().realtime.heartbeat_secs.max(1);
+    KeepAlive::new()
+        .interval(Duration::from_secs(secs))
+        .text("heartbeat")
+}
+
+/// 每条连接的事件缓冲上限
+#[must_use]
+pub fn channel_capacity() -> usize {
+    AppConfig::global().realtime.channel_capacity.max(1)
+}
+
+/// 退出前发送的最后一个事件，客户端收到后应稍后重连。
+pub fn shutdown_event() -> Event {
+    Event::default().event("shutdown").data("{}")
+}
+
+/// 消费过慢被丢弃事件时发送，`missed` 为丢弃条数；客户端应重新拉取全量数据。
+pub fn lagged_event(missed: u64) -> Event {
+    Event::default()
+        .event("lagged")
+        .data(format!("{{\"missed\":{missed}}}"))
+}
@@ -33,6 +33,18 @@ pub struct RemoteInfo {
 pub async fn try_load_remote_info(
     base_url: &str,
     info_dir: &Path,
+) -> Result<Option<RemoteInfo>, AppError> {
+    fetch_remote_info(base_url, info_dir, false).await
+}
+
+/// 同 [`try_load_remote_info`]；`force=true` 时忽略本地 ETag 缓存，始终下载。
+///
+/// 解析结果通过 [`crate::game_data::validate_info`] 校验后才会写入新的 ETag 缓存，
+/// 校验失败的远端版本会在下次检查时重试。
+pub async fn fetch_remote_info(
+    base_url: &str,
+    info_dir: &Path,
+    force: bool,
 ) -> Result<Option<RemoteInfo>, AppError> {
     let etag_path = info_dir.join(".remote-etags.json");
     let cached_etags = load_etag_cache(&etag_path);
@@ -82,7 +94,7 @@ pub async fn try_load_remote_info(
         }
     }
 
-    if !any_changed {
+    if !any_changed && !force {
         tracing::info!("远端 info 文件与本地缓存一致，跳过下载");
         return Ok(None);
     }
@@ -128,6 +140,7 @@ pub async fn try_load_remote_info(
     // Phase 3: 解析
     let chart_constants = parse_chart_constants(Cursor::new(&difficulty_bytes))?;
     let song_catalog = parse_song_catalog(Cursor::new(&info_bytes), Cursor::new(&nicklist_bytes))?;
+    crate::game_data::validate_info(&chart_constants, &song_catalog)?;
 
     // 保存新 ETag 缓存
     save_etag_cache(&etag_path, &remote_etags);
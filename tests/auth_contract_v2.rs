@@ -1,24 +1,30 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Once};
+use std::time::Duration;
 
-use axum::Json;
 use axum::body::{Bytes, to_bytes};
 use axum::extract::{Path, State};
 use axum::http::{StatusCode, header};
 use axum::response::IntoResponse;
+use axum::{Extension, Json};
 use moka::future::Cache;
 use tokio::sync::Semaphore;
 use uuid::Uuid;
 
 use phi_backend::config::{AppConfig, TapTapConfig, TapTapMultiConfig, TapTapVersion};
+use phi_backend::features::auth::bearer::{BearerAuthContext, BearerAuthState, SessionClaims};
 use phi_backend::features::auth::client::TapTapClient;
 use phi_backend::features::auth::handler::{
-    SessionExchangeRequest, SessionLogoutRequest, SessionLogoutScope, post_session_exchange,
-    post_session_logout, post_session_refresh,
+    SessionExchangeRequest, SessionLogoutRequest, SessionLogoutScope, get_auth_me, get_sessions,
+    post_revoke_session, post_session_exchange, post_session_logout, post_session_refresh,
 };
+use phi_backend::features::auth::models::SessionData;
 use phi_backend::features::auth::qrcode_service::QrCodeService;
+use phi_backend::features::auth::qrcode_store::SqliteQrCodeStore;
+use phi_backend::features::save::client::ExternalApiCredentials;
 use phi_backend::features::save::models::UnifiedSaveRequest;
 use phi_backend::features::song::models::SongCatalog;
-use phi_backend::features::stats::storage::StatsStorage;
+use phi_backend::features::stats::storage::{StatsStorage, TapTapProfileRow};
 use phi_backend::state::AppState;
 
 fn init_test_config() {
@@ -45,9 +51,16 @@ fn dummy_taptap_config(app_id: &str, app_key: &str) -> TapTapConfig {
 }
 
 fn make_state() -> AppState {
+    make_state_with_taptap(
+        dummy_taptap_config("cn-app-id", "cn-app-key"),
+        dummy_taptap_config("global-app-id", "global-app-key"),
+    )
+}
+
+fn make_state_with_taptap(cn: TapTapConfig, global: TapTapConfig) -> AppState {
     let taptap_cfg = TapTapMultiConfig {
-        cn: dummy_taptap_config("cn-app-id", "cn-app-key"),
-        global: dummy_taptap_config("global-app-id", "global-app-key"),
+        cn,
+        global,
         default_version: TapTapVersion::CN,
     };
     let taptap_client = Arc::new(TapTapClient::new(&taptap_cfg).expect("TapTapClient::new"));
@@ -57,8 +70,10 @@ fn make_state() -> AppState {
     let song_image_cache: Cache<String, Bytes> = Cache::builder().max_capacity(16).build();
 
     AppState {
-        chart_constants: Arc::new(std::collections::HashMap::default()),
-        song_catalog: Arc::new(SongCatalog::default()),
+        game_data: phi_backend::game_data::GameDataStore::new(
+            Arc::new(std::collections::HashMap::default()),
+            Arc::new(SongCatalog::default()),
+        ),
         taptap_client,
         qrcode_service,
         stats: None,
@@ -87,6 +102,7 @@ fn make_exchange_request() -> SessionExchangeRequest {
             external_credentials: None,
             taptap_version: None,
         },
+        device_label: None,
     }
 }
 
@@ -112,8 +128,9 @@ fn make_refresh_headers(secret: &str, token: &str) -> axum::http::HeaderMap {
     headers
 }
 
+/// 无统计存储时签发的旧式令牌（不关联刷新会话，不含 sid）
 async fn exchange_token(secret: &str) -> String {
-    let state = make_state_with_storage().await;
+    let state = make_state();
     let (_, Json(resp)) = post_session_exchange(
         State(state),
         make_exchange_headers(secret),
@@ -233,7 +250,8 @@ async fn qrcode_status_expires_in_zero_becomes_expired() {
             Some(0),
             None,
         )
-        .await;
+        .await
+        .expect("set pending");
 
     let resp = phi_backend::features::auth::handler::get_qrcode_status(State(state), Path(qr_id))
         .await
@@ -246,6 +264,120 @@ async fn qrcode_status_expires_in_zero_becomes_expired() {
     assert_eq!(v["status"], "Expired");
 }
 
+/// 模拟 `TapTap` token 端点：每次请求计数，延迟 `delay` 后返回“等待授权”
+async fn start_pending_token_server(delay: Duration, hits: Arc<AtomicUsize>) -> String {
+    let app = axum::Router::new().route(
+        "/token",
+        axum::routing::post(move || {
+            let hits = hits.clone();
+            async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                Json(serde_json::json!({
+                    "success": false,
+                    "data": { "error": "authorization_pending", "error_description": "pending" }
+                }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind tcp listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    format!("http://{addr}")
+}
+
+async fn qrcode_status_json(state: AppState, qr_id: &str) -> serde_json::Value {
+    let resp = phi_backend::features::auth::handler::get_qrcode_status(
+        State(state),
+        Path(qr_id.to_string()),
+    )
+    .await
+    .expect("handler ok");
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("parse json")
+}
+
+#[tokio::test]
+async fn qrcode_status_concurrent_resolves_share_sqlite_store() {
+    init_test_config();
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base = start_pending_token_server(Duration::from_millis(300), hits.clone()).await;
+    let mut taptap = dummy_taptap_config("cn-app-id", "cn-app-key");
+    taptap.token_endpoint = format!("{base}/token");
+
+    let db_path = format!("./resources/test_qrcode_shared_{}.db", Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&db_path, true)
+        .await
+        .expect("connect sqlite");
+    storage.init_schema().await.expect("init schema");
+    let storage = Arc::new(storage);
+    // 两个实例各自的服务共用同一个 SQLite 存储
+    let instance = || {
+        let mut state = make_state_with_taptap(
+            taptap.clone(),
+            dummy_taptap_config("global-app-id", "global-app-key"),
+        );
+        state.qrcode_service = Arc::new(QrCodeService::with_store(Arc::new(
+            SqliteQrCodeStore::new(storage.clone()),
+        )));
+        state
+    };
+    let (a, b) = (instance(), instance());
+
+    // interval=0：租约必须覆盖上游请求耗时，否则两个实例会各自消费一次 device code
+    a.qrcode_service
+        .set_pending(
+            "qr".to_string(),
+            "device_code".to_string(),
+            "device_id".to_string(),
+            0,
+            Some(300),
+            None,
+        )
+        .await
+        .expect("set pending");
+    let (va, vb) = tokio::join!(
+        qrcode_status_json(a.clone(), "qr"),
+        qrcode_status_json(b.clone(), "qr")
+    );
+    assert_eq!(va["status"], "Pending");
+    assert_eq!(vb["status"], "Pending");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // 慢速的“仍未授权”轮询返回时，另一实例已确认：不得覆盖为 Pending，也不得下发新令牌
+    let slow = tokio::spawn(qrcode_status_json(a.clone(), "qr"));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(
+        b.qrcode_service
+            .set_confirmed(
+                "qr",
+                SessionData {
+                    session_token: "r:confirmed-elsewhere".to_string(),
+                },
+            )
+            .await
+            .expect("confirm")
+    );
+    let va = slow.await.expect("join");
+    assert_eq!(va["status"], "Confirmed");
+    assert_eq!(va["sessionToken"], "r:confirmed-elsewhere");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // 已确认的记录保留到 TTL，另一实例读取到的仍是同一令牌
+    let vb = qrcode_status_json(b.clone(), "qr").await;
+    assert_eq!(vb["status"], "Confirmed");
+    assert_eq!(vb["sessionToken"], "r:confirmed-elsewhere");
+
+    drop((a, b, storage));
+    let _ = std::fs::remove_file(&db_path);
+}
+
 #[tokio::test]
 async fn session_exchange_requires_valid_shared_secret() {
     init_test_config();
@@ -454,6 +586,7 @@ async fn session_refresh_success_with_old_token_and_secret() {
     let (status, Json(resp)) = post_session_refresh(
         State(state),
         make_refresh_headers("test-exchange-secret", &old_token),
+        Bytes::new(),
     )
     .await
     .expect("refresh success");
@@ -494,6 +627,7 @@ async fn session_refresh_rejects_invalid_exchange_secret() {
     let result = post_session_refresh(
         State(state),
         make_refresh_headers("wrong-secret", &old_token),
+        Bytes::new(),
     )
     .await;
 
@@ -529,6 +663,7 @@ async fn session_refresh_rejects_blacklisted_token() {
     let result = post_session_refresh(
         State(state),
         make_refresh_headers("test-exchange-secret", &token),
+        Bytes::new(),
     )
     .await;
 
@@ -557,6 +692,7 @@ async fn session_refresh_accepts_expired_token_within_refresh_window() {
     let (status, Json(resp)) = post_session_refresh(
         State(state),
         make_refresh_headers("test-exchange-secret", &expired_token),
+        Bytes::new(),
     )
     .await
     .expect("refresh should succeed for recently expired token");
@@ -593,6 +729,7 @@ async fn session_refresh_rejects_token_expired_too_long() {
     let result = post_session_refresh(
         State(state),
         make_refresh_headers("test-exchange-secret", &expired_too_long_token),
+        Bytes::new(),
     )
     .await;
 
@@ -601,3 +738,250 @@ async fn session_refresh_rejects_token_expired_too_long() {
     let response = err.into_response();
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
+
+fn valid_bearer_for(auth: &UnifiedSaveRequest, sub: &str) -> BearerAuthState {
+    let now_ts = chrono::Utc::now().timestamp();
+    let token = mint_session_token_from_auth(auth, sub, now_ts, now_ts + 600);
+    let claims: SessionClaims =
+        serde_json::from_value(decode_claims(&token)).expect("session claims");
+    BearerAuthState::Valid(BearerAuthContext { token, claims })
+}
+
+#[tokio::test]
+async fn auth_me_returns_cached_profile_or_404() {
+    init_test_config();
+    let state = make_state_with_storage().await;
+    let storage = state.stats_storage.clone().expect("stats storage missing");
+    let auth = UnifiedSaveRequest {
+        session_token: None,
+        external_credentials: Some(ExternalApiCredentials {
+            platform: Some("TapTap".into()),
+            platform_id: Some("u1".into()),
+            sessiontoken: None,
+            api_user_id: None,
+            api_token: None,
+        }),
+        taptap_version: None,
+    };
+
+    // 无资料且无 sessionToken 可刷新 → 404
+    let err = get_auth_me(
+        State(state.clone()),
+        Some(Extension(valid_bearer_for(&auth, "me-user-missing"))),
+    )
+    .await
+    .expect_err("missing profile");
+    assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+
+    storage
+        .upsert_taptap_profile(
+            "me-user",
+            &TapTapProfileRow {
+                nickname: Some("Alice".into()),
+                avatar_url: Some("https://img.example/a.png".into()),
+                taptap_version: Some("cn".into()),
+                fetched_at_ms: chrono::Utc::now().timestamp_millis(),
+            },
+        )
+        .await
+        .expect("upsert profile");
+    let Json(me) = get_auth_me(
+        State(state.clone()),
+        Some(Extension(valid_bearer_for(&auth, "me-user"))),
+    )
+    .await
+    .expect("me success");
+    assert_eq!(me.user_id, "me-user");
+    assert_eq!(me.nickname.as_deref(), Some("Alice"));
+    assert_eq!(me.avatar_url.as_deref(), Some("https://img.example/a.png"));
+    assert!(!me.stale);
+
+    // 缺少 Bearer → 401
+    let err = get_auth_me(State(state), None)
+        .await
+        .expect_err("missing bearer");
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+}
+
+fn bearer_from_token(token: &str) -> BearerAuthState {
+    let claims: SessionClaims =
+        serde_json::from_value(decode_claims(token)).expect("session claims");
+    BearerAuthState::Valid(BearerAuthContext {
+        token: token.to_string(),
+        claims,
+    })
+}
+
+fn refresh_body(refresh_token: &str) -> Bytes {
+    Bytes::from(serde_json::json!({ "refreshToken": refresh_token }).to_string())
+}
+
+async fn exchange_on(state: &AppState, device_label: &str) -> (String, String, String) {
+    let mut req = make_exchange_request();
+    req.device_label = Some(device_label.to_string());
+    let (_, Json(resp)) = post_session_exchange(
+        State(state.clone()),
+        make_exchange_headers("test-exchange-secret"),
+        Json(req),
+    )
+    .await
+    .expect("exchange success");
+    (
+        resp.access_token,
+        resp.refresh_token.expect("refresh token"),
+        resp.session_id.expect("session id"),
+    )
+}
+
+#[tokio::test]
+async fn session_refresh_token_rotates_and_detects_reuse() {
+    init_test_config();
+    let state = make_state_with_storage().await;
+    let storage = state.stats_storage.clone().expect("stats storage missing");
+    let (access_token, refresh_token, session_id) = exchange_on(&state, "laptop").await;
+    let claims = decode_claims(&access_token);
+    assert_eq!(
+        claims.get("sid").and_then(|v| v.as_str()),
+        Some(session_id.as_str())
+    );
+
+    let (status, Json(rotated)) = post_session_refresh(
+        State(state.clone()),
+        make_exchange_headers("test-exchange-secret"),
+        refresh_body(&refresh_token),
+    )
+    .await
+    .expect("rotate success");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rotated.session_id.as_deref(), Some(session_id.as_str()));
+    assert_eq!(
+        rotated.refresh_expires_in,
+        Some(AppConfig::global().session.refresh_ttl_secs)
+    );
+    let new_refresh = rotated.refresh_token.expect("rotated refresh token");
+    assert_ne!(new_refresh, refresh_token);
+    let new_claims = decode_claims(&rotated.access_token);
+    assert_eq!(new_claims.get("sub"), claims.get("sub"));
+
+    // 关联刷新会话的 access token 不能走无请求体的旧刷新流程
+    let err = post_session_refresh(
+        State(state.clone()),
+        make_refresh_headers("test-exchange-secret", &rotated.access_token),
+        Bytes::new(),
+    )
+    .await
+    .expect_err("legacy refresh with sid");
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+
+    // 重放已轮换的旧令牌：吊销整个会话族
+    let err = post_session_refresh(
+        State(state.clone()),
+        make_exchange_headers("test-exchange-secret"),
+        refresh_body(&refresh_token),
+    )
+    .await
+    .expect_err("reused refresh token");
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+
+    let err = post_session_refresh(
+        State(state.clone()),
+        make_exchange_headers("test-exchange-secret"),
+        refresh_body(&new_refresh),
+    )
+    .await
+    .expect_err("family revoked after reuse");
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+
+    let family = storage
+        .get_session_family(&session_id)
+        .await
+        .expect("query family")
+        .expect("family exists");
+    assert_eq!(family.revoke_reason.as_deref(), Some("reuse"));
+    let now = chrono::Utc::now().to_rfc3339();
+    let (blacklisted, _) = storage
+        .get_session_revoke_state(
+            new_claims.get("jti").and_then(|v| v.as_str()).expect("jti"),
+            Some(&session_id),
+            new_claims.get("sub").and_then(|v| v.as_str()).expect("sub"),
+            &now,
+        )
+        .await
+        .expect("query revoke state");
+    assert!(blacklisted);
+
+    let err = post_session_refresh(
+        State(state),
+        make_exchange_headers("test-exchange-secret"),
+        refresh_body("not-a-refresh-token"),
+    )
+    .await
+    .expect_err("unknown refresh token");
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn session_list_and_revoke_one() {
+    init_test_config();
+    let state = make_state_with_storage().await;
+    let (phone_access, _, phone_sid) = exchange_on(&state, "phone").await;
+    let (_, tablet_refresh, tablet_sid) = exchange_on(&state, "tablet").await;
+
+    let Json(list) = get_sessions(
+        State(state.clone()),
+        Some(Extension(bearer_from_token(&phone_access))),
+    )
+    .await
+    .expect("list sessions");
+    assert_eq!(list.sessions.len(), 2);
+    let phone = list
+        .sessions
+        .iter()
+        .find(|s| s.session_id == phone_sid)
+        .expect("phone session");
+    assert!(phone.current);
+    assert_eq!(phone.device_label.as_deref(), Some("phone"));
+    assert!(
+        list.sessions
+            .iter()
+            .any(|s| s.session_id == tablet_sid && !s.current)
+    );
+
+    // 他人的会话 → 404
+    let auth = make_exchange_request().auth;
+    let err = post_revoke_session(
+        State(state.clone()),
+        Some(Extension(valid_bearer_for(&auth, "someone-else"))),
+        Path(tablet_sid.clone()),
+    )
+    .await
+    .expect_err("foreign session");
+    assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+
+    let Json(revoked) = post_revoke_session(
+        State(state.clone()),
+        Some(Extension(bearer_from_token(&phone_access))),
+        Path(tablet_sid.clone()),
+    )
+    .await
+    .expect("revoke session");
+    assert!(revoked.revoked);
+
+    let Json(list) = get_sessions(
+        State(state.clone()),
+        Some(Extension(bearer_from_token(&phone_access))),
+    )
+    .await
+    .expect("list sessions after revoke");
+    assert_eq!(list.sessions.len(), 1);
+    assert_eq!(list.sessions[0].session_id, phone_sid);
+
+    let err = post_session_refresh(
+        State(state),
+        make_exchange_headers("test-exchange-secret"),
+        refresh_body(&tablet_refresh),
+    )
+    .await
+    .expect_err("revoked session cannot refresh");
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+}
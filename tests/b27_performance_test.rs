@@ -274,6 +274,7 @@ async fn test_b27_generation_with_flamegraph() {
         best_27_avg,
         real_rks: Some(exact_rks),
         player_name: Some("性能测试用户".to_string()),
+        player_avatar: None,
         update_time: Utc::now(),
         n: 27,
         ap_top_3_scores: all_records
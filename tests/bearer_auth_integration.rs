@@ -61,8 +61,10 @@ fn make_state(storage: Arc<StatsStorage>) -> AppState {
     let song_image_cache: Cache<String, Bytes> = Cache::builder().max_capacity(16).build();
 
     AppState {
-        chart_constants: Arc::new(std::collections::HashMap::default()),
-        song_catalog: Arc::new(SongCatalog::default()),
+        game_data: phi_backend::game_data::GameDataStore::new(
+            Arc::new(std::collections::HashMap::default()),
+            Arc::new(SongCatalog::default()),
+        ),
         taptap_client,
         qrcode_service: Arc::new(
             phi_backend::features::auth::qrcode_service::QrCodeService::default(),
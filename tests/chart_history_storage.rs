@@ -0,0 +1,165 @@
+use phi_backend::features::stats::storage::{ChartScoreSnapshot, ServerRegion, StatsStorage};
+use uuid::Uuid;
+
+fn chart(song_id: &str, score: i64, acc: f64, is_fc: bool) -> ChartScoreSnapshot {
+    ChartScoreSnapshot {
+        song_id: song_id.to_string(),
+        difficulty: "IN".to_string(),
+        score,
+        acc,
+        is_fc,
+    }
+}
+
+#[tokio::test]
+async fn chart_history_dedups_unchanged_submissions_and_tracks_pbs() {
+    let path = format!("./resources/test_chart_history_{}.db", Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+
+    let base = vec![
+        chart("a", 950_000, 97.5, false),
+        chart("b", 900_000, 95.0, true),
+    ];
+    let written = storage
+        .record_chart_scores("u1", &base, "2026-01-01T00:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(written, 2);
+    // 相同成绩再次提交不追加历史
+    let written = storage
+        .record_chart_scores("u1", &base, "2026-01-02T00:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(written, 0);
+
+    let improved = vec![
+        chart("a", 1_000_000, 100.0, true),
+        chart("b", 900_000, 95.0, true),
+    ];
+    let written = storage
+        .record_chart_scores("u1", &improved, "2026-01-03T00:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(written, 1);
+
+    let page = storage
+        .query_chart_score_history("u1", "a", "IN", 1, None)
+        .await
+        .unwrap();
+    assert!(page.has_more);
+    assert_eq!(page.entries.len(), 1);
+    let latest = &page.entries[0];
+    assert_eq!(latest.score, 1_000_000);
+    assert_eq!(latest.prev_score, Some(950_000));
+    assert!(latest.is_pb);
+
+    let cursor = phi_backend::stats_contract::RksHistoryCursor {
+        created_at: latest.created_at.clone(),
+        id: latest.id,
+    };
+    let page = storage
+        .query_chart_score_history("u1", "a", "IN", 10, Some(&cursor))
+        .await
+        .unwrap();
+    assert!(!page.has_more);
+    assert_eq!(page.entries.len(), 1);
+    assert!(!page.entries[0].is_pb, "基线记录不计入 PB");
+
+    let pbs = storage.query_recent_pbs("u1", 10, None).await.unwrap();
+    assert_eq!(pbs.entries.len(), 1);
+    assert_eq!(pbs.entries[0].song_id, "a");
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn chart_leaderboard_orders_by_score_acc_time_and_filters_visibility() {
+    let path = format!("./resources/test_chart_board_{}.db", Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+
+    let now = "2026-01-01T00:00:00Z";
+    let players = [
+        ("u1", 1_000_000, 100.0, "2026-01-02T00:00:00Z"),
+        ("u2", 990_000, 99.5, "2026-01-02T00:00:00Z"),
+        ("u3", 990_000, 99.5, "2026-01-01T00:00:00Z"),
+        ("u4", 990_000, 99.8, "2026-01-03T00:00:00Z"),
+        ("hidden", 1_000_000, 100.0, now),
+        ("shadowed", 1_000_000, 100.0, now),
+        ("private", 1_000_000, 100.0, now),
+    ];
+    for (user, score, acc, at) in players {
+        storage
+            .record_chart_scores(user, &[chart("a", score, acc, false)], at)
+            .await
+            .unwrap();
+        storage
+            .upsert_leaderboard_rks(
+                user,
+                15.0,
+                None,
+                0.0,
+                user == "hidden",
+                ServerRegion::Unspecified,
+                now,
+            )
+            .await
+            .unwrap();
+        if user == "private" {
+            storage.ensure_user_profile_exists(user, now).await.unwrap();
+            storage
+                .update_user_profile_visibility(user, now, Some(0), None, None, None)
+                .await
+                .unwrap();
+        } else {
+            storage
+                .ensure_default_public_profile(user, None, false, false, false, now)
+                .await
+                .unwrap();
+        }
+    }
+    storage
+        .set_user_moderation_status("shadowed", "shadow", None, "admin", now)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        storage
+            .count_public_chart_leaderboard_total("a", "IN")
+            .await
+            .unwrap(),
+        4
+    );
+    let first = storage
+        .query_chart_leaderboard_offset("a", "IN", 2, 0)
+        .await
+        .unwrap();
+    let users: Vec<&str> = first.iter().map(|r| r.user_hash.as_str()).collect();
+    assert_eq!(users, ["u1", "u4"]);
+
+    let last = first.last().unwrap();
+    let rest = storage
+        .query_chart_leaderboard_seek(
+            "a",
+            "IN",
+            (last.score, last.acc, &last.updated_at, &last.user_hash),
+            10,
+        )
+        .await
+        .unwrap();
+    let users: Vec<&str> = rest.iter().map(|r| r.user_hash.as_str()).collect();
+    // 同分同 ACC 时更早达成者在前
+    assert_eq!(users, ["u3", "u2"]);
+    assert!(
+        storage
+            .query_chart_leaderboard_offset("a", "AT", 10, 0)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
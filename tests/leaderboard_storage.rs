@@ -1,4 +1,4 @@
-use phi_backend::features::stats::storage::StatsStorage;
+use phi_backend::features::stats::storage::{ServerRegion, StatsStorage};
 use sqlx::Row;
 
 #[tokio::test]
@@ -12,12 +12,28 @@ async fn upsert_improves_only() {
 
     let now = chrono::Utc::now().to_rfc3339();
     storage
-        .upsert_leaderboard_rks("u1", 10.0, Some("k"), 0.0, false, &now)
+        .upsert_leaderboard_rks(
+            "u1",
+            10.0,
+            Some("k"),
+            0.0,
+            false,
+            ServerRegion::Unspecified,
+            &now,
+        )
         .await
         .unwrap();
     // worse score should not overwrite
     storage
-        .upsert_leaderboard_rks("u1", 9.0, Some("k"), 0.0, false, &now)
+        .upsert_leaderboard_rks(
+            "u1",
+            9.0,
+            Some("k"),
+            0.0,
+            false,
+            ServerRegion::Unspecified,
+            &now,
+        )
         .await
         .unwrap();
     // read back
@@ -29,7 +45,15 @@ async fn upsert_improves_only() {
     assert!((v - 10.0).abs() < 1e-6);
     // better score overwrites
     storage
-        .upsert_leaderboard_rks("u1", 11.0, Some("k"), 0.0, false, &now)
+        .upsert_leaderboard_rks(
+            "u1",
+            11.0,
+            Some("k"),
+            0.0,
+            false,
+            ServerRegion::Unspecified,
+            &now,
+        )
         .await
         .unwrap();
     let row = sqlx::query("SELECT total_rks FROM leaderboard_rks WHERE user_hash='u1'")
@@ -39,3 +63,490 @@ async fn upsert_improves_only() {
     let v: f64 = row.get::<f64, _>(0);
     assert!((v - 11.0).abs() < 1e-6);
 }
+
+#[tokio::test]
+async fn anti_cheat_signals_and_flags_roundtrip() {
+    use phi_backend::features::stats::storage::{NewModerationFlag, SubmissionRecord};
+
+    let path = format!("./resources/test_anti_cheat_{}.db", uuid::Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+
+    for (user, ip, at) in [
+        ("u1", "ip1", "2026-01-02T00:00:00Z"),
+        ("u2", "ip1", "2026-01-02T01:00:00Z"),
+        ("u2", "ip1", "2026-01-02T02:00:00Z"),
+        ("u3", "ip1", "2025-12-01T00:00:00Z"),
+        ("u4", "ip2", "2026-01-02T00:00:00Z"),
+    ] {
+        storage
+            .insert_submission(SubmissionRecord {
+                user_hash: user,
+                total_rks: 12.0,
+                rks_jump: 0.0,
+                route: "/save",
+                client_ip_hash: Some(ip),
+                details_json: None,
+                suspicion_score: 0.0,
+                region: ServerRegion::Unspecified,
+                now_rfc3339: at,
+            })
+            .await
+            .unwrap();
+    }
+    // 窗口外的 u3 与其他 IP 的 u4 不计入，本账号也不计入
+    let others = storage
+        .count_other_accounts_on_ip("ip1", "u1", "2026-01-01T00:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(others, 1);
+
+    storage
+        .insert_moderation_flags(
+            "u1",
+            &[
+                NewModerationFlag {
+                    status: "auto_hidden",
+                    reason: "[impossible_score] 1 个谱面",
+                    severity: 100,
+                },
+                NewModerationFlag {
+                    status: "auto_hidden",
+                    reason: "[shared_ip] 2 个账号",
+                    severity: 30,
+                },
+            ],
+            "anti_cheat",
+            "2026-01-02T00:00:00Z",
+        )
+        .await
+        .unwrap();
+    let flags = storage.query_moderation_flags("u1", 10).await.unwrap();
+    assert_eq!(flags.len(), 2);
+    assert_eq!(flags[0].reason.as_deref(), Some("[shared_ip] 2 个账号"));
+    assert_eq!(flags[1].severity, 100);
+    assert!(flags.iter().all(|f| f.created_by == "anti_cheat"));
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn rank_snapshots_and_gainers_follow_public_board() {
+    use phi_backend::features::stats::storage::SubmissionRecord;
+
+    let path = format!("./resources/test_rank_history_{}.db", uuid::Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+
+    let now = "2026-01-10T00:00:00Z";
+    // u3 被隐藏，u4 未公开
+    for (user, rks, hide, public) in [
+        ("u1", 14.0, false, true),
+        ("u2", 13.0, false, true),
+        ("u3", 15.0, true, true),
+        ("u4", 12.5, false, false),
+    ] {
+        storage
+            .upsert_leaderboard_rks(user, rks, None, 0.0, hide, ServerRegion::Unspecified, now)
+            .await
+            .unwrap();
+        if public {
+            storage
+                .ensure_default_public_profile(user, None, true, true, true, now)
+                .await
+                .unwrap();
+        }
+    }
+    for (user, rks, at) in [
+        ("u1", 13.5, "2026-01-01T00:00:00Z"),
+        ("u1", 14.0, "2026-01-08T00:00:00Z"),
+        ("u2", 12.0, "2026-01-01T00:00:00Z"),
+        ("u2", 13.0, "2026-01-09T00:00:00Z"),
+        ("u3", 10.0, "2026-01-01T00:00:00Z"),
+        ("u3", 15.0, "2026-01-09T00:00:00Z"),
+        ("u4", 11.0, "2026-01-01T00:00:00Z"),
+        ("u4", 12.5, "2026-01-09T00:00:00Z"),
+    ] {
+        storage
+            .insert_submission(SubmissionRecord {
+                user_hash: user,
+                total_rks: rks,
+                rks_jump: 0.0,
+                route: "/save",
+                client_ip_hash: None,
+                details_json: None,
+                suspicion_score: 0.0,
+                region: ServerRegion::Unspecified,
+                now_rfc3339: at,
+            })
+            .await
+            .unwrap();
+    }
+
+    let since = "2026-01-05T00:00:00Z";
+    assert_eq!(storage.count_rks_gainers(since).await.unwrap(), 2);
+    let gainers = storage.query_rks_gainers(since, 10, 0).await.unwrap();
+    let users: Vec<&str> = gainers.iter().map(|g| g.user_hash.as_str()).collect();
+    assert_eq!(users, ["u2", "u1"]);
+    assert!((gainers[0].to_rks - gainers[0].from_rks - 1.0).abs() < 1e-9);
+
+    assert!(!storage.has_rank_snapshot("2026-01-09").await.unwrap());
+    assert_eq!(
+        storage
+            .snapshot_leaderboard_ranks("2026-01-09")
+            .await
+            .unwrap(),
+        2
+    );
+    storage
+        .upsert_leaderboard_rks("u2", 14.5, None, 0.0, false, ServerRegion::Unspecified, now)
+        .await
+        .unwrap();
+    storage
+        .snapshot_leaderboard_ranks("2026-01-10")
+        .await
+        .unwrap();
+    // 重写同一日期不会产生重复行
+    storage
+        .snapshot_leaderboard_ranks("2026-01-10")
+        .await
+        .unwrap();
+
+    let history = storage
+        .query_rank_history("u2", "2026-01-01")
+        .await
+        .unwrap();
+    let ranks: Vec<(&str, i64)> = history.iter().map(|p| (p.date.as_str(), p.rank)).collect();
+    assert_eq!(ranks, [("2026-01-09", 2), ("2026-01-10", 1)]);
+    assert!(
+        storage
+            .query_rank_history("u3", "2026-01-01")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    assert_eq!(storage.prune_rank_snapshots("2026-01-10").await.unwrap(), 2);
+    assert_eq!(
+        storage
+            .query_rank_history("u2", "2026-01-01")
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn group_membership_and_scoped_board() {
+    use sqlx::Row;
+
+    let path = format!("./resources/test_groups_{}.db", uuid::Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+
+    let now = "2026-01-10T00:00:00Z";
+    storage
+        .create_group("g1", "club", "u1", "ABCD2345", now)
+        .await
+        .unwrap();
+    // 邀请码唯一
+    assert!(matches!(
+        storage
+            .create_group("g2", "other", "u9", "ABCD2345", now)
+            .await,
+        Err(phi_backend::error::AppError::Conflict(_))
+    ));
+    let g = storage
+        .get_group_by_invite_code("abcd2345")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((g.id.as_str(), g.member_count), ("g1", 1));
+
+    assert!(storage.add_group_member("g1", "u2", 3, now).await.unwrap());
+    assert!(storage.add_group_member("g1", "u3", 3, now).await.unwrap());
+    // 已满 / 重复加入
+    assert!(!storage.add_group_member("g1", "u4", 3, now).await.unwrap());
+    assert!(!storage.add_group_member("g1", "u2", 10, now).await.unwrap());
+    assert_eq!(storage.count_joined_groups("u2").await.unwrap(), 1);
+    assert_eq!(storage.count_owned_groups("u1").await.unwrap(), 1);
+
+    // u2 未公开资料也能上群组榜；u3 被隐藏不上榜；详情开关按各自资料
+    for (user, rks, hide) in [("u1", 13.0, false), ("u2", 14.0, false), ("u3", 15.0, true)] {
+        storage
+            .upsert_leaderboard_rks(user, rks, None, 0.0, hide, ServerRegion::Unspecified, now)
+            .await
+            .unwrap();
+    }
+    storage
+        .ensure_default_public_profile("u1", None, true, true, false, now)
+        .await
+        .unwrap();
+    assert_eq!(
+        storage.count_group_leaderboard_total("g1").await.unwrap(),
+        2
+    );
+    let rows = storage
+        .query_group_leaderboard_offset("g1", 10, 0)
+        .await
+        .unwrap();
+    let board: Vec<(String, i64, i64)> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.get::<String, _>("user_hash"),
+                r.get::<i64, _>("sbt"),
+                r.get::<i64, _>("sat"),
+            )
+        })
+        .collect();
+    assert_eq!(board, [("u2".to_string(), 0, 0), ("u1".to_string(), 1, 0)]);
+
+    assert!(storage.remove_group_member("g1", "u2").await.unwrap());
+    assert!(!storage.is_group_member("g1", "u2").await.unwrap());
+    assert_eq!(storage.list_user_groups("u3").await.unwrap().len(), 1);
+
+    storage
+        .set_group_invite_code("g1", "ZZZZ9999", now)
+        .await
+        .unwrap();
+    assert!(
+        storage
+            .get_group_by_invite_code("ABCD2345")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    storage.delete_group("g1").await.unwrap();
+    assert!(storage.get_group("g1").await.unwrap().is_none());
+    assert!(storage.list_user_groups("u1").await.unwrap().is_empty());
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn region_columns_backfill_and_split_boards() {
+    let path = format!("./resources/test_regions_{}.db", uuid::Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    // 模拟区域记录上线前的旧表结构
+    sqlx::query(
+        "CREATE TABLE leaderboard_rks (
+            user_hash TEXT PRIMARY KEY,
+            total_rks REAL NOT NULL,
+            user_kind TEXT,
+            suspicion_score REAL NOT NULL DEFAULT 0.0,
+            is_hidden INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+    )
+    .execute(&storage.pool)
+    .await
+    .unwrap();
+    let now = "2026-01-10T00:00:00Z";
+    sqlx::query(
+        "INSERT INTO leaderboard_rks(user_hash,total_rks,created_at,updated_at) VALUES('old',16.0,?,?)",
+    )
+    .bind(now)
+    .bind(now)
+    .execute(&storage.pool)
+    .await
+    .unwrap();
+    storage.init_schema().await.unwrap();
+    // 重复初始化保持幂等
+    storage.init_schema().await.unwrap();
+    assert_eq!(
+        storage
+            .get_leaderboard_region("old")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("unspecified")
+    );
+
+    for (user, rks, region) in [
+        ("c1", 14.0, ServerRegion::Cn),
+        ("c2", 12.0, ServerRegion::Cn),
+        ("g1", 13.0, ServerRegion::Global),
+    ] {
+        storage
+            .upsert_leaderboard_rks(user, rks, None, 0.0, false, region, now)
+            .await
+            .unwrap();
+    }
+    // 无法判定来源的提交不会覆盖已知区域
+    storage
+        .upsert_leaderboard_rks("c2", 12.5, None, 0.0, false, ServerRegion::Unspecified, now)
+        .await
+        .unwrap();
+    for user in ["old", "c1", "c2", "g1"] {
+        storage
+            .ensure_default_public_profile(user, None, true, true, true, now)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        storage.count_public_leaderboard_total(None).await.unwrap(),
+        4
+    );
+    assert_eq!(
+        storage
+            .count_public_leaderboard_total(Some("cn"))
+            .await
+            .unwrap(),
+        2
+    );
+    let rows = storage
+        .query_leaderboard_top_offset(Some("cn"), 10, 0)
+        .await
+        .unwrap();
+    let users: Vec<String> = rows.iter().map(|r| r.get("user_hash")).collect();
+    assert_eq!(users, ["c1", "c2"]);
+    // g1 在合并榜第 3，在 global 榜第 1
+    assert_eq!(
+        storage
+            .count_public_leaderboard_higher(None, 13.0, now, "g1")
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        storage
+            .count_public_leaderboard_higher(Some("global"), 13.0, now, "g1")
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        storage
+            .query_public_rks_scores(Some("unspecified"))
+            .await
+            .unwrap(),
+        [16.0]
+    );
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn upsert_broadcasts_only_visible_improvements() {
+    let path = format!("./resources/test_lb_changes_{}.db", uuid::Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+    let mut rx = storage.subscribe_leaderboard_changes();
+
+    let t1 = "2026-01-10T00:00:00Z";
+    let t2 = "2026-01-11T00:00:00Z";
+    storage
+        .upsert_leaderboard_rks("u1", 12.0, None, 0.0, false, ServerRegion::Cn, t1)
+        .await
+        .unwrap();
+    let ev = rx.try_recv().expect("first submission is broadcast");
+    assert_eq!(ev.user_hash, "u1");
+    assert!((ev.total_rks - 12.0).abs() < 1e-9);
+    assert_eq!(ev.updated_at, t1);
+    assert_eq!(ev.region, ServerRegion::Cn);
+
+    // 未提升：保留旧成绩，不通知
+    storage
+        .upsert_leaderboard_rks("u1", 11.0, None, 0.0, false, ServerRegion::Cn, t2)
+        .await
+        .unwrap();
+    assert!(rx.try_recv().is_err());
+
+    // 隐藏用户的提升不通知
+    storage
+        .upsert_leaderboard_rks("u2", 15.0, None, 0.0, true, ServerRegion::Global, t2)
+        .await
+        .unwrap();
+    assert!(rx.try_recv().is_err());
+
+    // 未指定区域的提交沿用已记录的区域
+    storage
+        .upsert_leaderboard_rks("u1", 13.0, None, 0.0, false, ServerRegion::Unspecified, t2)
+        .await
+        .unwrap();
+    let ev = rx.try_recv().expect("improvement is broadcast");
+    assert!((ev.total_rks - 13.0).abs() < 1e-9);
+    assert_eq!(ev.region, ServerRegion::Cn);
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn recompute_skips_users_with_newer_submission() {
+    use phi_backend::features::stats::storage::{RecomputedLeaderboardRow, SubmissionRecord};
+
+    let path = format!("./resources/test_lb_recompute_{}.db", uuid::Uuid::new_v4());
+    let storage = StatsStorage::connect_sqlite(&path, false).await.unwrap();
+    storage.init_schema().await.unwrap();
+
+    let now = "2026-01-10T00:00:00Z";
+    let submit = |user: &'static str, rks: f64| SubmissionRecord {
+        user_hash: user,
+        total_rks: rks,
+        rks_jump: 0.0,
+        route: "/save",
+        client_ip_hash: None,
+        details_json: Some("{}"),
+        suspicion_score: 0.0,
+        region: ServerRegion::Unspecified,
+        now_rfc3339: now,
+    };
+    for user in ["u1", "u2"] {
+        storage.insert_submission(submit(user, 12.0)).await.unwrap();
+        storage
+            .upsert_leaderboard_rks(user, 12.0, None, 0.0, false, ServerRegion::Unspecified, now)
+            .await
+            .unwrap();
+    }
+
+    let read = storage
+        .query_leaderboard_chart_details(None, 10)
+        .await
+        .unwrap();
+    let recomputed: Vec<RecomputedLeaderboardRow> = read
+        .iter()
+        .map(|d| RecomputedLeaderboardRow {
+            user_hash: d.user_hash.clone(),
+            based_on_submission_id: d.latest_submission_id,
+            total_rks: 11.0,
+            rule_totals: Vec::new(),
+            rks_comp_json: None,
+            best_top3_json: None,
+            ap_top3_json: None,
+        })
+        .collect();
+
+    // 读取快照之后 u2 上传了新存档
+    storage.insert_submission(submit("u2", 13.0)).await.unwrap();
+    storage
+        .upsert_leaderboard_rks("u2", 13.0, None, 0.0, false, ServerRegion::Unspecified, now)
+        .await
+        .unwrap();
+
+    let applied = storage
+        .apply_leaderboard_recompute(&recomputed, now)
+        .await
+        .unwrap();
+    assert_eq!(applied, 1);
+
+    let totals: Vec<(String, f64)> =
+        sqlx::query_as("SELECT user_hash, total_rks FROM leaderboard_rks ORDER BY user_hash")
+            .fetch_all(&storage.pool)
+            .await
+            .unwrap();
+    assert_eq!(totals, vec![("u1".into(), 11.0), ("u2".into(), 13.0)]);
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
@@ -13,8 +13,10 @@ use uuid::Uuid;
 use phi_backend::{
     config::{TapTapConfig, TapTapMultiConfig, TapTapVersion},
     features::{
-        auth::client::TapTapClient, leaderboard::handler::create_leaderboard_router,
-        song::models::SongCatalog, stats::storage::StatsStorage,
+        auth::client::TapTapClient,
+        leaderboard::handler::create_leaderboard_router,
+        song::models::SongCatalog,
+        stats::storage::{ServerRegion, StatsStorage},
     },
     state::AppState,
 };
@@ -51,8 +53,10 @@ fn new_test_state(storage: Arc<StatsStorage>) -> AppState {
     let song_image_cache: Cache<String, Bytes> = Cache::builder().max_capacity(1024).build();
 
     AppState {
-        chart_constants: Arc::new(std::collections::HashMap::default()),
-        song_catalog: Arc::new(SongCatalog::default()),
+        game_data: phi_backend::game_data::GameDataStore::new(
+            Arc::new(std::collections::HashMap::default()),
+            Arc::new(SongCatalog::default()),
+        ),
         taptap_client: Arc::new(taptap_client),
         qrcode_service: Arc::new(
             phi_backend::features::auth::qrcode_service::QrCodeService::default(),
@@ -109,7 +113,15 @@ async fn seed_leaderboard_db(storage: &StatsStorage) {
         .expect("insert user_profile");
 
         storage
-            .upsert_leaderboard_rks(user_hash, score, Some("k"), 0.0, false, &now)
+            .upsert_leaderboard_rks(
+                user_hash,
+                score,
+                Some("k"),
+                0.0,
+                false,
+                ServerRegion::Unspecified,
+                &now,
+            )
             .await
             .expect("upsert leaderboard_rks");
 
@@ -149,7 +161,15 @@ async fn seed_many_public_users(storage: &StatsStorage, n: usize) {
         .expect("insert user_profile");
 
         storage
-            .upsert_leaderboard_rks(&user_hash, score, Some("k"), 0.0, false, &now)
+            .upsert_leaderboard_rks(
+                &user_hash,
+                score,
+                Some("k"),
+                0.0,
+                false,
+                ServerRegion::Unspecified,
+                &now,
+            )
             .await
             .expect("upsert leaderboard_rks");
     }
@@ -13,7 +13,7 @@ use phi_backend::{
     config::{TapTapConfig, TapTapMultiConfig, TapTapVersion},
     features::{
         auth::client::TapTapClient,
-        song::models::{SongCatalog, SongInfo},
+        song::models::{SearchMode, SearchOptions, SongCatalog, SongInfo},
     },
     startup::chart_loader::ChartConstants,
     state::AppState,
@@ -49,8 +49,10 @@ fn new_test_state(song_catalog: SongCatalog) -> AppState {
     let song_image_cache: Cache<String, Bytes> = Cache::builder().max_capacity(1024).build();
 
     AppState {
-        chart_constants: Arc::new(std::collections::HashMap::default()),
-        song_catalog: Arc::new(song_catalog),
+        game_data: phi_backend::game_data::GameDataStore::new(
+            Arc::new(std::collections::HashMap::default()),
+            Arc::new(song_catalog),
+        ),
         taptap_client: Arc::new(taptap_client),
         qrcode_service: Arc::new(
             phi_backend::features::auth::qrcode_service::QrCodeService::default(),
@@ -189,6 +191,83 @@ fn song_catalog_search_fuzzy_matches_japanese_name_when_normalized_match_fails()
     assert_eq!(items[0].id, "amb");
 }
 
+fn pinyin_catalog() -> SongCatalog {
+    let mut catalog = SongCatalog::default();
+
+    let oblivion = Arc::new(SongInfo {
+        id: "oblivion".to_string(),
+        name: "遗忘之境".to_string(),
+        composer: "c".to_string(),
+        illustrator: "i".to_string(),
+        chart_constants: chart_constants_none(),
+    });
+    let neon = Arc::new(SongInfo {
+        id: "neon".to_string(),
+        name: "Light Show".to_string(),
+        composer: "c".to_string(),
+        illustrator: "i".to_string(),
+        chart_constants: chart_constants_none(),
+    });
+    for song in [&oblivion, &neon] {
+        catalog.by_id.insert(song.id.clone(), Arc::clone(song));
+    }
+    catalog
+        .by_nickname
+        .entry("ネオンライト".to_string())
+        .or_default()
+        .push(Arc::clone(&neon));
+    catalog.rebuild_search_cache();
+    catalog
+}
+
+#[test]
+fn song_catalog_pinyin_matches_full_pinyin_and_initials_only_when_enabled() {
+    let catalog = pinyin_catalog();
+    let options = SearchOptions {
+        pinyin: true,
+        ..SearchOptions::default()
+    };
+
+    assert_eq!(catalog.search_page("yiwang", 0, 20).1, 0);
+
+    for q in ["yiwang", "yiwangzhijing", "ywzj", "YWZJ"] {
+        let (items, total) = catalog.search_page_with_options(q, 0, 20, options);
+        assert_eq!(total, 1, "query {q}");
+        assert_eq!(items[0].id, "oblivion");
+    }
+
+    let unique = catalog
+        .search_unique_with_options("ywzj", options)
+        .expect("initials should be unique");
+    assert_eq!(unique.id, "oblivion");
+}
+
+#[test]
+fn song_catalog_pinyin_matches_kana_alias_by_romaji() {
+    let catalog = pinyin_catalog();
+    let options = SearchOptions {
+        pinyin: true,
+        ..SearchOptions::default()
+    };
+
+    let (items, total) = catalog.search_page_with_options("neonraito", 0, 20, options);
+    assert_eq!(total, 1);
+    assert_eq!(items[0].id, "neon");
+
+    // 平假名查询同样按罗马音归一化
+    let (items, _) = catalog.search_page_with_options("ねおん", 0, 20, options);
+    assert_eq!(items[0].id, "neon");
+
+    let multi = catalog.search_multi("ywzj", SearchMode::Or, options);
+    assert_eq!(multi.len(), 1);
+    assert_eq!(multi[0].id, "oblivion");
+
+    // 多关键词检索同样命中别名缓存的罗马音键
+    let multi = catalog.search_multi("neonraito", SearchMode::And, options);
+    assert_eq!(multi.len(), 1);
+    assert_eq!(multi[0].id, "neon");
+}
+
 #[tokio::test]
 async fn songs_search_default_limit_is_applied() {
     let mut catalog = SongCatalog::default();
@@ -983,3 +1062,64 @@ async fn songs_search_unique_candidates_are_limited_but_total_is_reported() {
     assert_eq!(candidates[0]["id"].as_str().expect("id"), "id-000");
     assert_eq!(candidates[9]["id"].as_str().expect("id"), "id-009");
 }
+
+#[tokio::test]
+async fn songs_search_pinyin_flag_enables_phonetic_tier() {
+    let app = build_app(new_test_state(pinyin_catalog()));
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v2/songs/search?q=ywzj")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("request");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let v: serde_json::Value = serde_json::from_slice(&bytes).expect("parse json");
+    assert_eq!(v["total"].as_u64(), Some(0));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v2/songs/search?q=ywzj&pinyin=true&unique=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("request");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let v: serde_json::Value = serde_json::from_slice(&bytes).expect("parse json");
+    assert_eq!(v["id"].as_str(), Some("oblivion"));
+}
+
+#[tokio::test]
+async fn local_reload_refuses_to_override_remote_data_without_force() {
+    let state = new_test_state(pinyin_catalog());
+    state.game_data.replace(
+        std::collections::HashMap::default(),
+        pinyin_catalog(),
+        phi_backend::game_data::GameDataSource::Remote,
+    );
+
+    let missing_dir = std::path::Path::new("resources/__no_such_info_dir__");
+    let err = phi_backend::game_data::reload_from_local(&state, missing_dir, false)
+        .await
+        .expect_err("remote data must not be replaced silently");
+    assert!(matches!(err, phi_backend::error::AppError::Conflict(_)));
+    assert_eq!(state.game_data().version, 2);
+
+    // force=true 时越过来源检查，进入实际加载（此处目录不存在而失败）
+    let err = phi_backend::game_data::reload_from_local(&state, missing_dir, true)
+        .await
+        .expect_err("missing info dir");
+    assert!(!matches!(err, phi_backend::error::AppError::Conflict(_)));
+}